@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rcon_cli::{RconError, RconPacket};
+
+// Arbitrary bytes must either parse or be rejected as invalid, never panic
+fuzz_target!(|data: &[u8]| {
+    if let Err(e) = RconPacket::from_bytes(data) {
+        assert!(matches!(e, RconError::InvalidPacket(_)), "unexpected error: {e}");
+    }
+});
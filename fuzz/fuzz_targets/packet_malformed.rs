@@ -0,0 +1,30 @@
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use rcon_cli::RconPacket;
+
+/// A well-formed packet whose length prefix and tail are then tampered with
+#[derive(Debug, Arbitrary)]
+struct MalformedFrame {
+    packet: RconPacket,
+    declared_length: Option<i32>,
+    truncate_by: u8,
+    trailing: Vec<u8>,
+}
+
+fuzz_target!(|frame: MalformedFrame| {
+    let Ok(mut bytes) = frame.packet.to_bytes() else {
+        return;
+    };
+
+    if let Some(length) = frame.declared_length {
+        bytes[0..4].copy_from_slice(&length.to_le_bytes());
+    }
+
+    let keep = bytes.len().saturating_sub(frame.truncate_by as usize);
+    bytes.truncate(keep);
+    bytes.extend_from_slice(&frame.trailing);
+
+    let _ = RconPacket::from_bytes(&bytes);
+});
@@ -0,0 +1,19 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rcon_cli::RconPacket;
+
+// Anything we can serialize must deserialize back to the same packet
+fuzz_target!(|packet: RconPacket| {
+    // Trailing NULs are indistinguishable from the packet terminator
+    if packet.payload.contains('\0') {
+        return;
+    }
+
+    if let Ok(bytes) = packet.to_bytes() {
+        let decoded = RconPacket::from_bytes(&bytes).expect("serialized packet must parse");
+        assert_eq!(decoded.request_id, packet.request_id);
+        assert_eq!(decoded.packet_type, packet.packet_type);
+        assert_eq!(decoded.payload, packet.payload);
+    }
+});
@@ -0,0 +1,338 @@
+# Deutsche Meldungen der rcon-cli-Kommandozeile.
+
+## Fehler
+
+error = Fehler: { $message }
+error-retrying = Fehler: { $message }. Neuer Versuch...
+error-after-retries = Fehler: { $message } (nach { $retries ->
+        [one] 1 Wiederholung
+       *[other] { $retries } Wiederholungen
+    })
+output-open-failed = Ausgabedatei '{ $path }' konnte nicht geöffnet werden: { $error }
+deadline-exceeded = Zeitlimit von { $seconds }s überschritten
+invalid-arguments = Ungültige Argumente: { $error }
+logging-init-failed = Protokollierung konnte nicht initialisiert werden
+input-error = Eingabefehler: { $error }
+
+## Verbindungen
+
+connected = Erfolgreich verbunden
+connect-attempt-failed = Verbindungsversuch { $attempt } fehlgeschlagen
+connection-lost = Verbindung verloren
+reconnected = Erfolgreich neu verbunden
+reconnected-retrying = Neu verbunden. Befehl wird wiederholt...
+reconnect-failed = Neuverbindung fehlgeschlagen
+status-connected = Verbunden
+status-disconnected = Getrennt
+connection-status = Verbindungsstatus: { $status } ({ $address })
+connect-resolving = { $address } wird aufgelöst...
+connect-connecting = Verbinde mit { $address }...
+connect-authenticating = Anmeldung bei { $address }...
+
+## Befehle
+
+executed-in = Ausgeführt in { $ms }ms
+info-failed = { $command } konnte nicht abgefragt werden
+players-online = { $online } von { $max } Spielern online
+ban-count = { $count ->
+        [one] 1 Bann
+       *[other] { $count } Banns
+    }
+player-list-unrecognized = Unbekanntes Format der Spielerliste: { $response }
+player-list-poll-failed = Spielerliste konnte nicht abgefragt werden
+addons-count = { $count ->
+        [one] 1 Erweiterung
+       *[other] { $count } Erweiterungen
+    } auf { $flavor }
+unexpected-response = Unerwartete Antwort auf '{ $command }': { $response }
+dry-run-stopped = Probelauf beendet, der nächste Schritt braucht die Antwort des Servers ({ $error })
+mc-version-unknown = Minecraft-Version nicht erkannt, nehme { $version } an
+mc-version-guessed = Der Server meldet seine Minecraft-Version nicht, nehme { $version } oder neuer an
+file-read-failed = '{ $path }' konnte nicht gelesen werden: { $error }
+tp-needs-coordinates = --dimension und --confirm-loaded brauchen x-y-z-Koordinaten als Ziel
+tp-confirm-needs-absolute = --confirm-loaded braucht absolute x- und z-Koordinaten
+tp-chunk-not-loaded = Chunk [{ $x }, { $z }] in { $dimension } ist nicht dauerhaft geladen (siehe 'forceload add')
+forceload-changed = { $changed } von { $total ->
+        [one] 1 Chunk
+       *[other] { $total } Chunks
+    } geändert ({ $commands ->
+        [one] 1 Befehl
+       *[other] { $commands } Befehle
+    })
+forceload-queried = { $forced } von { $total ->
+        [one] 1 Chunk
+       *[other] { $total } Chunks
+    } dauerhaft geladen
+command-check-failed = { $problem }; wird trotzdem gesendet
+heredoc-empty = Kein Befehl auf stdin
+tps-unsupported = { $flavor }-Server haben keinen Befehl, der die Ticks pro Sekunde meldet
+whitelist-in-sync = Whitelist entspricht bereits der Liste ({ $count ->
+        [one] 1 Spieler
+       *[other] { $count } Spieler
+    })
+whitelist-synced = Whitelist abgeglichen: { $added } hinzugefügt, { $removed } entfernt
+
+## Interaktiver Modus
+
+interactive-welcome = Interaktiver Modus. Mit 'quit', 'exit' oder Strg+C beenden.
+interactive-prefix = Befehle werden mit dem Präfix '{ $prefix }' gesendet (mit ! am Zeilenanfang ohne)
+interactive-goodbye = Auf Wiedersehen!
+history-save-failed = Verlauf konnte nicht in '{ $path }' gespeichert werden: { $error }
+interactive-help =
+    Befehle im interaktiven Modus:
+      help         Diese Hilfe anzeigen
+      status       Verbindungsstatus anzeigen
+      reconnect    Neu mit dem Server verbinden
+      quit/exit    Interaktiven Modus beenden
+
+    Jede andere Eingabe wird als Befehl an den Server gesendet.
+    Zeilen, die mit ! beginnen, werden ohne Befehlspräfix gesendet.
+    Endet eine Zeile mit \, geht der Befehl in der nächsten weiter.
+
+    Häufige Minecraft-Befehle:
+      list         Spieler online anzeigen
+      time set day Tageszeit auf Tag setzen
+      weather clear Wetter aufklaren lassen
+      gamemode creative <spieler>  Spieler in den Kreativmodus setzen
+      tp <spieler1> <spieler2>     Spieler1 zu Spieler2 teleportieren
+
+## Ping
+
+ping-start = Pinge { $address } { $count ->
+        [one] einmal
+       *[other] { $count }-mal
+    }
+ping-success = Ping { $attempt }: Verbunden in { $ms }ms
+ping-failed = Ping { $attempt } fehlgeschlagen
+ping-summary = Zusammenfassung: { $successful }/{ $count } erfolgreich ({ $rate }%), Durchschnitt: { $ms }ms
+ping-profiles-start = Pinge { $profiles } Profil(e) { $count ->
+        [one] einmal
+       *[other] { $count }-mal
+    }
+no-profiles = Keine Profile konfiguriert
+history-no-default = Kein Datenverzeichnis gefunden; gib einen Pfad für die Verlaufsdatei an
+history-open-failed = Verlaufsdatei '{ $path }' konnte nicht geöffnet werden
+report-no-samples = Keine Messwerte in '{ $path }' für diesen Zeitraum
+report-ongoing = andauernd
+
+## Warten und Skripte
+
+wait-start = Warte bis zu { $seconds }s, bis { $address } RCON-Verbindungen annimmt
+wait-attempt = Versuch { $attempt } ({ $seconds }s vergangen): { $error }
+wait-attempt-dropped = Versuch { $attempt } ({ $seconds }s vergangen): Verbindung angenommen, dann verloren: { $error }
+wait-crashed = Der Server ist nach dem Annehmen einer Verbindung ausgefallen ({ $crashes } { $crashes ->
+        [one] Absturz
+       *[other] Abstürze
+    } in den letzten { $window }s)
+wait-crash-loop = Warten vorzeitig beendet
+wait-ready = Server { $address } ist bereit
+wait-timeout = Server war nach { $seconds }s nicht bereit
+script-line-failed = Zeile { $line } ('{ $command }') fehlgeschlagen
+script-critical-failed = Kritische Zeile { $line } ('{ $command }') fehlgeschlagen, Skript wird abgebrochen
+script-line-warning = Zeile { $line } ('{ $command }') fehlgeschlagen, weiter: { $error }
+script-line-ignored = Zeile { $line } ('{ $command }') fehlgeschlagen, ignoriert: { $error }
+script-complete = Skript beendet: { $succeeded }/{ $total } Befehl(e) erfolgreich, { $warnings } Warnung(en), { $ignored } ignoriert
+script-stopped = Skript in Zeile { $line } abgebrochen: { $succeeded }/{ $total } Befehl(e) erfolgreich, { $warnings } Warnung(en), { $ignored } ignoriert
+playbook-plan = Plan: { $steps } Schritt(e) in { $stages } Stufe(n), bis zu { $concurrency } gleichzeitig
+playbook-step-start = Starte Schritt '{ $step }'
+playbook-complete = Playbook beendet: { $succeeded }/{ $total } Schritt(e) erfolgreich, { $warnings } Warnung(en), { $ignored } ignoriert, { $skipped } übersprungen
+playbook-failed = { $failed } von { $total } Schritt(en) fehlgeschlagen
+playbook-critical-failed = Kritischer Schritt '{ $step }' fehlgeschlagen, keine weiteren Schritte gestartet
+eval-interrupted = Skript angehalten
+
+## Massenbefehle
+
+bulk-start = Sende { $count ->
+        [one] 1 Befehl
+       *[other] { $count } Befehle
+    } über { $connections ->
+        [one] 1 Verbindung
+       *[other] { $connections } Verbindungen
+    }
+bulk-short-row = Zeile hat { $fields } Feld(er), die Vorlage benötigt { $required }
+bulk-extra-connection-failed = Weitere Verbindung konnte nicht geöffnet werden, fahre mit weniger fort
+bulk-complete = { $succeeded }/{ $total } Befehl(e) erfolgreich
+progress-failed = ({ $count } fehlgeschlagen)
+
+## Sitzungen
+
+sessions-watching = Beobachte Spieler auf { $address } alle { $interval } (Strg+C zum Beenden)
+sessions-ended = { $player } (Sitzung { $duration })
+sessions-summary = { $duration } beobachtet, { $players ->
+        [one] 1 Spieler
+       *[other] { $players } Spieler
+    } gesehen
+
+## Alarme
+
+alert-watching = Alarm, wenn { $condition } für { $hold }, Abfrage alle { $interval } (Strg+C zum Beenden)
+alert-triggered = { $condition } ({ $online }/{ $max } Spieler)
+alert-resolved = Nicht mehr { $condition } ({ $online }/{ $max } Spieler)
+alert-exec-status = Alarmbefehl endete mit { $status }
+alert-exec-failed = Alarmbefehl konnte nicht ausgeführt werden: { $error }
+
+## Speichern
+
+save-pending = Speichern läuft, erneute Prüfung...
+save-confirmed = Speichern nach { $ms }ms bestätigt
+save-failed = Der Server konnte nicht speichern: { $reason }
+save-unrecognized = Unerwartete Antwort auf save-all: { $response }
+save-timeout = Speichern wurde nicht innerhalb von { $seconds }s bestätigt
+
+## Automatisches Stoppen
+
+autostop-watching = Server wird nach { $idle } ohne Spieler gestoppt, mit { $warn } Vorwarnung (Strg+C zum Abbrechen)
+autostop-cancelled = Herunterfahren abgebrochen: { $players ->
+        [one] 1 Spieler
+       *[other] { $players } Spieler
+    } online
+autostop-stopping = Server untätig, sende '{ $command }'
+
+## Neustart
+
+restart-waiting = Warte, bis { $players ->
+        [0] niemand
+        [one] höchstens 1 Spieler
+       *[other] höchstens { $players } Spieler
+    } online ist (Strg+C zum Abbrechen)
+restart-waiting-until = Warte bis zu { $duration }, bis { $players ->
+        [0] niemand
+        [one] höchstens 1 Spieler
+       *[other] höchstens { $players } Spieler
+    } online ist (Strg+C zum Abbrechen)
+restart-players-left = { $players ->
+        [0] Niemand online
+        [one] 1 Spieler online
+       *[other] { $players } Spieler online
+    }, starte neu
+restart-deadline = Nach { $duration } noch belegt, starte trotzdem neu
+restart-stopping = Sende '{ $command }' für den Neustart
+
+## Diagnose
+
+doctor-start = Untersuche { $address }...
+doctor-summary = { $ok } in Ordnung, { $warnings } Warnung(en), { $failed } fehlgeschlagen, { $skipped } übersprungen
+
+## Netzwerke
+
+net-no-proxy = Für das Netzwerk ist kein Proxy konfiguriert
+net-unknown-server = '{ $server }' ist kein Server des Netzwerks (Server: { $servers })
+net-exec-failed = { $failed } von { $total ->
+        [one] 1 Server
+       *[other] { $total } Servern
+    } fehlgeschlagen
+net-players = { $players ->
+        [one] 1 Spieler
+       *[other] { $players } Spieler
+    } online auf { $servers ->
+        [one] 1 Server
+       *[other] { $servers } Servern
+    }
+
+## Daemon
+
+daemon-skip-profile = Profil '{ $name }' wird übersprungen: { $error }
+daemon-listening = Daemon lauscht auf { $path } ({ $count ->
+        [one] 1 Server
+       *[other] { $count } Server
+    } konfiguriert)
+daemon-reloaded = Konfiguration neu geladen: { $added } hinzugefügt, { $removed } entfernt, { $count ->
+        [one] 1 Server
+       *[other] { $count } Server
+    } konfiguriert
+daemon-reload-failed = Konfiguration konnte nicht neu geladen werden, die aktuellen Server bleiben bestehen
+daemon-unsupported = Der Daemon benötigt Unix-Domain-Sockets
+daemon-not-running = Kein Daemon lauscht auf { $path }: { $error }
+
+## Annotationen
+
+annotation-saved = Welt auf { $server } gespeichert
+annotation-ready = { $server } nimmt RCON-Verbindungen an
+annotation-script = { $script } auf { $server } ausgeführt: { $succeeded }/{ $total } Befehle erfolgreich
+annotation-playbook = { $playbook } auf { $server } ausgeführt: { $succeeded }/{ $total } Schritte erfolgreich
+annotation-stopped = { $server } nach { $idle } ohne Spieler gestoppt
+annotation-restarting = { $server } nach { $waited } Wartezeit für einen Neustart gestoppt ({ $players } online)
+annotation-maintenance-started = Wartung auf { $server } begonnen: { $message }
+annotation-maintenance-ended = Wartung auf { $server } beendet: { $message }
+annotation-alert = Alarm auf { $server }: { $condition } ({ $online }/{ $max } Spieler)
+annotation-posted = Annotation { $id } gesendet
+annotation-dry-run = Würde Annotation senden: { $text }
+grafana-not-configured = Kein [grafana]-Abschnitt in der Konfigurationsdatei
+
+## Warteschlange
+
+queue-no-default = Kein Datenverzeichnis gefunden; gib mit --file eine Warteschlangendatei an
+queue-open-failed = Die Warteschlangendatei '{ $path }' konnte nicht geöffnet werden
+queue-empty-command = Nichts einzureihen: der Befehl ist leer
+queue-pushed = Für { $server } eingereiht ({ $count ->
+        [one] 1 Befehl
+       *[other] { $count } Befehle
+    } ausstehend)
+queue-empty = Nichts für { $server } eingereiht
+queue-drained = { $count ->
+        [one] 1 eingereihter Befehl
+       *[other] { $count } eingereihte Befehle
+    } an { $server } gesendet
+queue-drain-stopped = Abarbeitung für { $server } nach { $sent } gesendeten abgebrochen; { $remaining } bleiben eingereiht
+
+## Lieferungen
+
+deliver-no-default = Kein Datenverzeichnis gefunden; gib eine Ledger-Datei mit --ledger an
+deliver-open-failed = Ledger-Datei '{ $path }' konnte nicht geöffnet werden
+deliver-missing-value = Kein --set-Wert für einen Platzhalter von '{ $template }'
+deliver-already = { $key } wurde bereits geliefert; nichts gesendet
+deliver-unconfirmed = Prüfe auf dem Server, ob er ausgeführt wurde, und schließe { $key } sonst mit --resend-unconfirmed ab
+deliver-conflict = Verwende für eine andere Lieferung einen neuen Schlüssel
+deliver-stopped = Die Lieferung von { $key } wurde abgebrochen; starte sie mit demselben Schlüssel erneut, um fortzufahren
+
+## Wartung
+
+maintenance-no-default = Kein Datenverzeichnis gefunden; gib ein Audit-Log mit --log an
+maintenance-open-failed = Wartungs-Audit-Log '{ $path }' konnte nicht geöffnet werden
+maintenance-default-start = Der Server wird gewartet, bitte komm später wieder
+maintenance-default-end = Die Wartung ist vorbei, willkommen zurück!
+maintenance-already-open = Die Wartung auf { $server } läuft seit { $since }; beende sie zuerst
+maintenance-not-open = Auf { $server } läuft keine Wartung
+maintenance-whitelist-on = eingeschaltet
+maintenance-whitelist-already-on = bereits an, bleibt am Ende an
+maintenance-whitelist-off = ausgeschaltet
+maintenance-whitelist-left-on = bleibt an, wie vor der Wartung
+
+## Pins
+
+warning = Warnung: { $message }
+pin-no-default = Kein Datenverzeichnis für Server-Pins gefunden
+pin-new-addresses = { $server } auf { $addresses } gepinnt
+pin-new-identity = Identität von { $server } gepinnt ({ $fingerprint })
+pin-addresses-changed = { $server } löst jetzt zu { $seen } auf, ist aber auf { $pinned } gepinnt. Die Verbindung wird womöglich umgeleitet; prüfe die DNS-Einträge und starte mit --accept-pin-change, wenn der Umzug erwartet ist
+pin-identity-changed = { $server } beantwortet '{ $command }' mit { $seen }, ist aber auf { $pinned } gepinnt. Es könnte ein anderer Server sein; starte mit --accept-pin-change, wenn die Änderung erwartet ist
+pin-accepted = { $message } (der neue Wert wird gepinnt)
+
+## Lint
+
+lint-clean = Keine Probleme in { $commands ->
+        [one] 1 Befehl
+       *[other] { $commands } Befehlen
+    } aus { $scripts ->
+        [one] 1 Skript
+       *[other] { $scripts } Skripten
+    }
+lint-problems = { $count ->
+    [one] 1 Problem gefunden
+   *[other] { $count } Probleme gefunden
+}
+
+## Plugins
+
+plugins-unsupported = WebAssembly-Plugins erfordern einen Build mit dem Feature `wasm`
+plugins-none = Kein Plugin definiert einen Unterbefehl
+plugin-unknown = Kein Plugin definiert den Unterbefehl '{ $name }' (verfügbar: { $available })
+
+## Übersetzungen
+
+messages-missing = { $count ->
+        [one] 1 Meldung fehlt
+       *[other] { $count } Meldungen fehlen
+    } in der Sprache '{ $locale }'
+messages-complete = Die Sprache '{ $locale }' ist vollständig
+unknown-locale = Unbekannte Sprache '{ $locale }' (verfügbar: { $available })
@@ -0,0 +1,342 @@
+# English messages of the rcon-cli command-line interface.
+#
+# This is the reference catalog: every other locale is checked against it
+# with `rcon-cli messages --check <LANG>`. Numbers passed as `$ms` or
+# `$seconds` are already formatted.
+
+## Errors
+
+error = Error: { $message }
+error-retrying = Error: { $message }. Retrying...
+error-after-retries = Error: { $message } (after { $retries ->
+        [one] 1 retry
+       *[other] { $retries } retries
+    })
+output-open-failed = Failed to open output file '{ $path }': { $error }
+deadline-exceeded = Deadline of { $seconds }s exceeded
+invalid-arguments = Invalid arguments: { $error }
+logging-init-failed = Failed to initialize logging
+input-error = Input error: { $error }
+
+## Connections
+
+connected = Connected successfully
+connect-attempt-failed = Connection attempt { $attempt } failed
+connection-lost = Connection lost
+reconnected = Reconnected successfully
+reconnected-retrying = Reconnected. Retrying command...
+reconnect-failed = Failed to reconnect
+status-connected = Connected
+status-disconnected = Disconnected
+connection-status = Connection status: { $status } ({ $address })
+connect-resolving = Resolving { $address }...
+connect-connecting = Connecting to { $address }...
+connect-authenticating = Logging in to { $address }...
+
+## Commands
+
+executed-in = Executed in { $ms }ms
+info-failed = Failed to get { $command }
+players-online = { $online } of { $max } players online
+ban-count = { $count ->
+        [one] 1 ban
+       *[other] { $count } bans
+    }
+player-list-unrecognized = Unrecognized player list: { $response }
+player-list-poll-failed = Failed to poll the player list
+addons-count = { $count ->
+        [one] 1 add-on
+       *[other] { $count } add-ons
+    } on { $flavor }
+unexpected-response = Unexpected response to '{ $command }': { $response }
+dry-run-stopped = Dry run stopped, the next step needs the server's response ({ $error })
+mc-version-unknown = Could not detect the Minecraft version, assuming { $version }
+mc-version-guessed = The server does not report its Minecraft version, assuming { $version } or newer
+file-read-failed = Failed to read '{ $path }': { $error }
+tp-needs-coordinates = --dimension and --confirm-loaded need x y z coordinates as the destination
+tp-confirm-needs-absolute = --confirm-loaded needs absolute x and z coordinates
+tp-chunk-not-loaded = Chunk [{ $x }, { $z }] in { $dimension } is not force-loaded (see 'forceload add')
+forceload-changed = { $changed } of { $total ->
+        [one] 1 chunk
+       *[other] { $total } chunks
+    } changed ({ $commands ->
+        [one] 1 command
+       *[other] { $commands } commands
+    })
+forceload-queried = { $forced } of { $total ->
+        [one] 1 chunk
+       *[other] { $total } chunks
+    } force-loaded
+command-check-failed = { $problem }; sending it anyway
+heredoc-empty = No command on stdin
+tps-unsupported = { $flavor } servers have no command reporting ticks per second
+whitelist-in-sync = Whitelist already matches the roster ({ $count ->
+        [one] 1 player
+       *[other] { $count } players
+    })
+whitelist-synced = Whitelist synchronized: { $added } added, { $removed } removed
+
+## Interactive mode
+
+interactive-welcome = Entering interactive mode. Type 'quit', 'exit', or Ctrl+C to leave.
+interactive-prefix = Commands are sent with the prefix '{ $prefix }' (start a line with ! to leave it out)
+interactive-goodbye = Goodbye!
+history-save-failed = Failed to save the history to '{ $path }': { $error }
+interactive-help =
+    Interactive Mode Commands:
+      help         Show this help message
+      status       Show connection status
+      reconnect    Reconnect to the server
+      quit/exit    Leave interactive mode
+
+    Any other input will be sent as a command to the server.
+    Start a line with ! to send it without the command prefix.
+    End a line with \ to continue the command on the next one.
+
+    Common Minecraft commands:
+      list         Show online players
+      time set day Set time to day
+      weather clear Clear weather
+      gamemode creative <player>  Set player to creative mode
+      tp <player1> <player2>      Teleport player1 to player2
+
+## Ping
+
+ping-start = Pinging { $address } { $count ->
+        [one] once
+       *[other] { $count } times
+    }
+ping-success = Ping { $attempt }: Connected in { $ms }ms
+ping-failed = Ping { $attempt } failed
+ping-summary = Summary: { $successful }/{ $count } successful ({ $rate }%), average: { $ms }ms
+ping-profiles-start = Pinging { $profiles } profile(s) { $count ->
+        [one] once
+       *[other] { $count } times
+    }
+no-profiles = No profiles configured
+history-no-default = No data directory found; pass a history file path
+history-open-failed = Failed to open the history file '{ $path }'
+report-no-samples = No samples recorded in '{ $path }' for this period
+report-ongoing = ongoing
+
+## Waiting and scripts
+
+wait-start = Waiting up to { $seconds }s for { $address } to accept RCON connections
+wait-attempt = Attempt { $attempt } ({ $seconds }s elapsed): { $error }
+wait-attempt-dropped = Attempt { $attempt } ({ $seconds }s elapsed): connection accepted, then lost: { $error }
+wait-crashed = The server went down after accepting a connection ({ $crashes } { $crashes ->
+        [one] crash
+       *[other] crashes
+    } in the last { $window }s)
+wait-crash-loop = Stopped waiting early
+wait-ready = Server at { $address } is ready
+wait-timeout = Server did not become ready within { $seconds }s
+script-line-failed = Line { $line } ('{ $command }') failed
+script-critical-failed = Critical line { $line } ('{ $command }') failed, stopping the script
+script-line-warning = Line { $line } ('{ $command }') failed, continuing: { $error }
+script-line-ignored = Line { $line } ('{ $command }') failed, ignored: { $error }
+script-complete = Script complete: { $succeeded }/{ $total } command(s) succeeded, { $warnings } warning(s), { $ignored } ignored
+script-stopped = Script stopped at line { $line }: { $succeeded }/{ $total } command(s) succeeded, { $warnings } warning(s), { $ignored } ignored
+playbook-plan = Plan: { $steps } step(s) in { $stages } stage(s), up to { $concurrency } at a time
+playbook-step-start = Starting step '{ $step }'
+playbook-complete = Playbook complete: { $succeeded }/{ $total } step(s) succeeded, { $warnings } warning(s), { $ignored } ignored, { $skipped } skipped
+playbook-failed = { $failed } of { $total } step(s) failed
+playbook-critical-failed = Critical step '{ $step }' failed, no further steps were started
+eval-interrupted = Script stopped
+
+## Bulk
+
+bulk-start = Sending { $count ->
+        [one] 1 command
+       *[other] { $count } commands
+    } over { $connections ->
+        [one] 1 connection
+       *[other] { $connections } connections
+    }
+bulk-short-row = Row has { $fields } field(s), the template needs { $required }
+bulk-extra-connection-failed = Could not open another connection, continuing with fewer
+bulk-complete = { $succeeded }/{ $total } command(s) succeeded
+progress-failed = ({ $count } failed)
+
+## Sessions
+
+sessions-watching = Watching players on { $address } every { $interval } (Ctrl+C to stop)
+sessions-ended = { $player } (session { $duration })
+sessions-summary = Watched for { $duration }, { $players ->
+        [one] 1 player
+       *[other] { $players } players
+    } seen
+
+## Alerts
+
+alert-watching = Alerting when { $condition } for { $hold }, polling every { $interval } (Ctrl+C to stop)
+alert-triggered = { $condition } ({ $online }/{ $max } players)
+alert-resolved = No longer { $condition } ({ $online }/{ $max } players)
+alert-exec-status = Alert command exited with { $status }
+alert-exec-failed = Failed to run the alert command: { $error }
+
+## Saving
+
+save-pending = Save in progress, checking again...
+save-confirmed = Save confirmed after { $ms }ms
+save-failed = The server could not save: { $reason }
+save-unrecognized = Unexpected response to save-all: { $response }
+save-timeout = Save was not confirmed within { $seconds }s
+
+## Autostop
+
+autostop-watching = Stopping the server after { $idle } without players, with { $warn } of warnings (Ctrl+C to cancel)
+autostop-cancelled = Shutdown cancelled: { $players ->
+        [one] 1 player
+       *[other] { $players } players
+    } online
+autostop-stopping = Server idle, sending '{ $command }'
+
+## Restart
+
+restart-waiting = Waiting until { $players ->
+        [0] nobody is
+        [one] at most 1 player is
+       *[other] at most { $players } players are
+    } online (Ctrl+C to cancel)
+restart-waiting-until = Waiting up to { $duration } until { $players ->
+        [0] nobody is
+        [one] at most 1 player is
+       *[other] at most { $players } players are
+    } online (Ctrl+C to cancel)
+restart-players-left = { $players ->
+        [0] Nobody is online
+        [one] 1 player online
+       *[other] { $players } players online
+    }, restarting
+restart-deadline = Still busy after { $duration }, restarting anyway
+restart-stopping = Sending '{ $command }' for the restart
+
+## Doctor
+
+doctor-start = Probing { $address }...
+doctor-summary = { $ok } ok, { $warnings } warning(s), { $failed } failed, { $skipped } skipped
+
+## Networks
+
+net-no-proxy = The network has no proxy configured
+net-unknown-server = '{ $server }' is not a server of the network (servers: { $servers })
+net-exec-failed = { $failed } of { $total ->
+        [one] 1 server
+       *[other] { $total } servers
+    } failed
+net-players = { $players ->
+        [one] 1 player
+       *[other] { $players } players
+    } online across { $servers ->
+        [one] 1 server
+       *[other] { $servers } servers
+    }
+
+## Daemon
+
+daemon-skip-profile = Skipping profile '{ $name }': { $error }
+daemon-listening = Daemon listening on { $path } ({ $count ->
+        [one] 1 server
+       *[other] { $count } servers
+    } configured)
+daemon-reloaded = Configuration reloaded: { $added } added, { $removed } removed, { $count ->
+        [one] 1 server
+       *[other] { $count } servers
+    } configured
+daemon-reload-failed = Failed to reload the configuration, keeping the current servers
+daemon-unsupported = The daemon requires Unix domain sockets
+daemon-not-running = No daemon is listening on { $path }: { $error }
+
+## Annotations
+
+annotation-saved = World saved on { $server }
+annotation-ready = { $server } is accepting RCON connections
+annotation-script = Ran { $script } on { $server }: { $succeeded }/{ $total } commands succeeded
+annotation-playbook = Ran { $playbook } on { $server }: { $succeeded }/{ $total } steps succeeded
+annotation-stopped = Stopped { $server } after { $idle } without players
+annotation-restarting = Stopped { $server } for a restart after waiting { $waited } ({ $players } online)
+annotation-maintenance-started = Maintenance started on { $server }: { $message }
+annotation-maintenance-ended = Maintenance ended on { $server }: { $message }
+annotation-alert = Alert on { $server }: { $condition } ({ $online }/{ $max } players)
+annotation-posted = Posted annotation { $id }
+annotation-dry-run = Would post annotation: { $text }
+grafana-not-configured = No [grafana] section in the config file
+
+## Queue
+
+queue-no-default = No data directory found; pass a queue file with --file
+queue-open-failed = Failed to open the queue file '{ $path }'
+queue-empty-command = Nothing to queue: the command is empty
+queue-pushed = Queued for { $server } ({ $count ->
+        [one] 1 command
+       *[other] { $count } commands
+    } pending)
+queue-empty = Nothing queued for { $server }
+queue-drained = Sent { $count ->
+        [one] 1 queued command
+       *[other] { $count } queued commands
+    } to { $server }
+queue-drain-stopped = Stopped draining { $server } after { $sent } sent; { $remaining } stay queued
+
+## Deliveries
+
+deliver-no-default = No data directory found; pass a ledger file with --ledger
+deliver-open-failed = Failed to open the delivery ledger '{ $path }'
+deliver-missing-value = No --set value for a placeholder of '{ $template }'
+deliver-already = { $key } was delivered already; nothing was sent
+deliver-unconfirmed = Check on the server whether it ran, then finish { $key } with --resend-unconfirmed if it didn't
+deliver-conflict = Use a new key for a different delivery
+deliver-stopped = The delivery of { $key } stopped; run it again with the same key to continue
+
+## Maintenance
+
+maintenance-no-default = No data directory found; pass an audit log with --log
+maintenance-open-failed = Failed to open the maintenance audit log '{ $path }'
+maintenance-default-start = The server is down for maintenance, please come back later
+maintenance-default-end = Maintenance is over, welcome back!
+maintenance-already-open = Maintenance on { $server } has been going on since { $since }; end it first
+maintenance-not-open = No maintenance window is open on { $server }
+maintenance-whitelist-on = turned on
+maintenance-whitelist-already-on = already on, left on at the end
+maintenance-whitelist-off = turned off
+maintenance-whitelist-left-on = left on, as before the window
+
+## Pins
+
+warning = Warning: { $message }
+pin-no-default = No data directory found to keep server pins in
+pin-new-addresses = Pinned { $server } to { $addresses }
+pin-new-identity = Pinned the identity of { $server } ({ $fingerprint })
+pin-addresses-changed = { $server } now resolves to { $seen }, but is pinned to { $pinned }. Someone may be redirecting the connection; check the DNS records and run with --accept-pin-change if the move is expected
+pin-identity-changed = { $server } answers '{ $command }' with { $seen }, but is pinned to { $pinned }. It may be a different server; run with --accept-pin-change if the change is expected
+pin-accepted = { $message } (pinning the new value)
+
+## Lint
+
+lint-clean = No problems in { $commands ->
+        [one] 1 command
+       *[other] { $commands } commands
+    } of { $scripts ->
+        [one] 1 script
+       *[other] { $scripts } scripts
+    }
+lint-problems = { $count ->
+    [one] 1 problem found
+   *[other] { $count } problems found
+}
+
+## Plugins
+
+plugins-unsupported = WebAssembly plugins require a build with the `wasm` feature
+plugins-none = No plugin defines a subcommand
+plugin-unknown = No plugin defines the subcommand '{ $name }' (available: { $available })
+
+## Translations
+
+messages-missing = { $count ->
+        [one] 1 message is
+       *[other] { $count } messages are
+    } missing from locale '{ $locale }'
+messages-complete = Locale '{ $locale }' is complete
+unknown-locale = Unknown locale '{ $locale }' (available: { $available })
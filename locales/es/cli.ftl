@@ -0,0 +1,338 @@
+# Mensajes en español de la interfaz de línea de comandos rcon-cli.
+
+## Errores
+
+error = Error: { $message }
+error-retrying = Error: { $message }. Reintentando...
+error-after-retries = Error: { $message } (tras { $retries ->
+        [one] 1 reintento
+       *[other] { $retries } reintentos
+    })
+output-open-failed = No se pudo abrir el archivo de salida '{ $path }': { $error }
+deadline-exceeded = Se superó el plazo de { $seconds }s
+invalid-arguments = Argumentos no válidos: { $error }
+logging-init-failed = No se pudo inicializar el registro
+input-error = Error de entrada: { $error }
+
+## Conexiones
+
+connected = Conectado correctamente
+connect-attempt-failed = Falló el intento de conexión { $attempt }
+connection-lost = Conexión perdida
+reconnected = Reconectado correctamente
+reconnected-retrying = Reconectado. Reintentando el comando...
+reconnect-failed = No se pudo reconectar
+status-connected = Conectado
+status-disconnected = Desconectado
+connection-status = Estado de la conexión: { $status } ({ $address })
+connect-resolving = Resolviendo { $address }...
+connect-connecting = Conectando con { $address }...
+connect-authenticating = Iniciando sesión en { $address }...
+
+## Comandos
+
+executed-in = Ejecutado en { $ms }ms
+info-failed = No se pudo obtener { $command }
+players-online = { $online } de { $max } jugadores en línea
+ban-count = { $count ->
+        [one] 1 baneo
+       *[other] { $count } baneos
+    }
+player-list-unrecognized = Lista de jugadores no reconocida: { $response }
+player-list-poll-failed = No se pudo consultar la lista de jugadores
+addons-count = { $count ->
+        [one] 1 complemento
+       *[other] { $count } complementos
+    } en { $flavor }
+unexpected-response = Respuesta inesperada a '{ $command }': { $response }
+dry-run-stopped = Simulación detenida, el siguiente paso necesita la respuesta del servidor ({ $error })
+mc-version-unknown = No se detectó la versión de Minecraft, se asume { $version }
+mc-version-guessed = El servidor no indica su versión de Minecraft, se asume { $version } o posterior
+file-read-failed = No se pudo leer '{ $path }': { $error }
+tp-needs-coordinates = --dimension y --confirm-loaded necesitan coordenadas x y z como destino
+tp-confirm-needs-absolute = --confirm-loaded necesita coordenadas absolutas para x y para z
+tp-chunk-not-loaded = El chunk [{ $x }, { $z }] en { $dimension } no está cargado de forma forzada (véase 'forceload add')
+forceload-changed = { $changed } de { $total ->
+        [one] 1 chunk
+       *[other] { $total } chunks
+    } cambiados ({ $commands ->
+        [one] 1 comando
+       *[other] { $commands } comandos
+    })
+forceload-queried = { $forced } de { $total ->
+        [one] 1 chunk
+       *[other] { $total } chunks
+    } con carga forzada
+command-check-failed = { $problem }; se envía de todos modos
+heredoc-empty = No hay ningún comando en stdin
+tps-unsupported = Los servidores { $flavor } no tienen ningún comando que indique los ticks por segundo
+whitelist-in-sync = La lista blanca ya coincide con la lista ({ $count ->
+        [one] 1 jugador
+       *[other] { $count } jugadores
+    })
+whitelist-synced = Lista blanca sincronizada: { $added } añadidos, { $removed } eliminados
+
+## Modo interactivo
+
+interactive-welcome = Modo interactivo. Escribe 'quit', 'exit' o pulsa Ctrl+C para salir.
+interactive-prefix = Los comandos se envían con el prefijo '{ $prefix }' (empieza la línea con ! para omitirlo)
+interactive-goodbye = ¡Hasta luego!
+history-save-failed = No se pudo guardar el historial en '{ $path }': { $error }
+interactive-help =
+    Comandos del modo interactivo:
+      help         Muestra esta ayuda
+      status       Muestra el estado de la conexión
+      reconnect    Vuelve a conectar con el servidor
+      quit/exit    Sale del modo interactivo
+
+    Cualquier otra entrada se envía como comando al servidor.
+    Las líneas que empiezan por ! se envían sin el prefijo de comandos.
+    Termina una línea con \ para seguir el comando en la siguiente.
+
+    Comandos habituales de Minecraft:
+      list         Muestra los jugadores en línea
+      time set day Cambia la hora a día
+      weather clear Despeja el clima
+      gamemode creative <jugador>   Pone al jugador en modo creativo
+      tp <jugador1> <jugador2>      Teletransporta jugador1 a jugador2
+
+## Ping
+
+ping-start = Haciendo ping a { $address } { $count ->
+        [one] una vez
+       *[other] { $count } veces
+    }
+ping-success = Ping { $attempt }: conectado en { $ms }ms
+ping-failed = Falló el ping { $attempt }
+ping-summary = Resumen: { $successful }/{ $count } correctos ({ $rate }%), media: { $ms }ms
+ping-profiles-start = Haciendo ping a { $profiles } perfil(es) { $count ->
+        [one] una vez
+       *[other] { $count } veces
+    }
+no-profiles = No hay perfiles configurados
+history-no-default = No se encontró un directorio de datos; indica la ruta del archivo de historial
+history-open-failed = No se pudo abrir el archivo de historial '{ $path }'
+report-no-samples = No hay muestras registradas en '{ $path }' para este periodo
+report-ongoing = en curso
+
+## Espera y scripts
+
+wait-start = Esperando hasta { $seconds }s a que { $address } acepte conexiones RCON
+wait-attempt = Intento { $attempt } ({ $seconds }s transcurridos): { $error }
+wait-attempt-dropped = Intento { $attempt } ({ $seconds }s transcurridos): conexión aceptada y luego perdida: { $error }
+wait-crashed = El servidor cayó tras aceptar una conexión ({ $crashes } { $crashes ->
+        [one] caída
+       *[other] caídas
+    } en los últimos { $window }s)
+wait-crash-loop = Se dejó de esperar antes de tiempo
+wait-ready = El servidor { $address } está listo
+wait-timeout = El servidor no estuvo listo en { $seconds }s
+script-line-failed = Falló la línea { $line } ('{ $command }')
+script-critical-failed = Falló la línea crítica { $line } ('{ $command }'), se detiene el script
+script-line-warning = Falló la línea { $line } ('{ $command }'), se continúa: { $error }
+script-line-ignored = Falló la línea { $line } ('{ $command }'), ignorada: { $error }
+script-complete = Script terminado: { $succeeded }/{ $total } comando(s) correctos, { $warnings } aviso(s), { $ignored } ignorado(s)
+script-stopped = Script detenido en la línea { $line }: { $succeeded }/{ $total } comando(s) correctos, { $warnings } aviso(s), { $ignored } ignorado(s)
+playbook-plan = Plan: { $steps } paso(s) en { $stages } etapa(s), hasta { $concurrency } a la vez
+playbook-step-start = Iniciando el paso '{ $step }'
+playbook-complete = Playbook terminado: { $succeeded }/{ $total } paso(s) correctos, { $warnings } aviso(s), { $ignored } ignorado(s), { $skipped } omitido(s)
+playbook-failed = Fallaron { $failed } de { $total } paso(s)
+playbook-critical-failed = Falló el paso crítico '{ $step }', no se iniciaron más pasos
+eval-interrupted = Script detenido
+
+## Operaciones masivas
+
+bulk-start = Enviando { $count ->
+        [one] 1 comando
+       *[other] { $count } comandos
+    } por { $connections ->
+        [one] 1 conexión
+       *[other] { $connections } conexiones
+    }
+bulk-short-row = La fila tiene { $fields } campo(s), la plantilla necesita { $required }
+bulk-extra-connection-failed = No se pudo abrir otra conexión, se continúa con menos
+bulk-complete = { $succeeded }/{ $total } comando(s) correctos
+progress-failed = ({ $count } fallidos)
+
+## Sesiones
+
+sessions-watching = Observando jugadores en { $address } cada { $interval } (Ctrl+C para detener)
+sessions-ended = { $player } (sesión de { $duration })
+sessions-summary = Observado durante { $duration }, { $players ->
+        [one] 1 jugador visto
+       *[other] { $players } jugadores vistos
+    }
+
+## Alertas
+
+alert-watching = Alerta cuando { $condition } durante { $hold }, consultando cada { $interval } (Ctrl+C para detener)
+alert-triggered = { $condition } ({ $online }/{ $max } jugadores)
+alert-resolved = Ya no se cumple { $condition } ({ $online }/{ $max } jugadores)
+alert-exec-status = El comando de alerta terminó con { $status }
+alert-exec-failed = No se pudo ejecutar el comando de alerta: { $error }
+
+## Guardado
+
+save-pending = Guardado en curso, comprobando de nuevo...
+save-confirmed = Guardado confirmado tras { $ms }ms
+save-failed = El servidor no pudo guardar: { $reason }
+save-unrecognized = Respuesta inesperada a save-all: { $response }
+save-timeout = El guardado no se confirmó en { $seconds }s
+
+## Parada automática
+
+autostop-watching = Se detendrá el servidor tras { $idle } sin jugadores, con { $warn } de avisos (Ctrl+C para cancelar)
+autostop-cancelled = Apagado cancelado: { $players ->
+        [one] 1 jugador en línea
+       *[other] { $players } jugadores en línea
+    }
+autostop-stopping = Servidor inactivo, enviando '{ $command }'
+
+## Reinicio
+
+restart-waiting = Esperando hasta que { $players ->
+        [0] no haya nadie
+        [one] haya como mucho 1 jugador
+       *[other] haya como mucho { $players } jugadores
+    } en línea (Ctrl+C para cancelar)
+restart-waiting-until = Esperando hasta { $duration } a que { $players ->
+        [0] no haya nadie
+        [one] haya como mucho 1 jugador
+       *[other] haya como mucho { $players } jugadores
+    } en línea (Ctrl+C para cancelar)
+restart-players-left = { $players ->
+        [0] No hay nadie en línea
+        [one] 1 jugador en línea
+       *[other] { $players } jugadores en línea
+    }, reiniciando
+restart-deadline = Sigue ocupado tras { $duration }, reiniciando de todos modos
+restart-stopping = Enviando '{ $command }' para el reinicio
+
+## Diagnóstico
+
+doctor-start = Analizando { $address }...
+doctor-summary = { $ok } correctas, { $warnings } advertencia(s), { $failed } fallidas, { $skipped } omitidas
+
+## Redes
+
+net-no-proxy = La red no tiene ningún proxy configurado
+net-unknown-server = '{ $server }' no es un servidor de la red (servidores: { $servers })
+net-exec-failed = { $failed } de { $total ->
+        [one] 1 servidor
+       *[other] { $total } servidores
+    } fallaron
+net-players = { $players ->
+        [one] 1 jugador
+       *[other] { $players } jugadores
+    } en línea en { $servers ->
+        [one] 1 servidor
+       *[other] { $servers } servidores
+    }
+
+## Daemon
+
+daemon-skip-profile = Se omite el perfil '{ $name }': { $error }
+daemon-listening = Daemon escuchando en { $path } ({ $count ->
+        [one] 1 servidor configurado
+       *[other] { $count } servidores configurados
+    })
+daemon-reloaded = Configuración recargada: { $added } añadidos, { $removed } eliminados, { $count ->
+        [one] 1 servidor configurado
+       *[other] { $count } servidores configurados
+    }
+daemon-reload-failed = No se pudo recargar la configuración, se mantienen los servidores actuales
+daemon-unsupported = El daemon requiere sockets de dominio Unix
+daemon-not-running = Ningún daemon escucha en { $path }: { $error }
+
+## Anotaciones
+
+annotation-saved = Mundo guardado en { $server }
+annotation-ready = { $server } acepta conexiones RCON
+annotation-script = Se ejecutó { $script } en { $server }: { $succeeded }/{ $total } comandos correctos
+annotation-playbook = Se ejecutó { $playbook } en { $server }: { $succeeded }/{ $total } pasos correctos
+annotation-stopped = { $server } detenido tras { $idle } sin jugadores
+annotation-restarting = { $server } detenido para reiniciar tras esperar { $waited } ({ $players } en línea)
+annotation-maintenance-started = Mantenimiento iniciado en { $server }: { $message }
+annotation-maintenance-ended = Mantenimiento terminado en { $server }: { $message }
+annotation-alert = Alerta en { $server }: { $condition } ({ $online }/{ $max } jugadores)
+annotation-posted = Anotación { $id } publicada
+annotation-dry-run = Se publicaría la anotación: { $text }
+grafana-not-configured = No hay sección [grafana] en el archivo de configuración
+
+## Cola
+
+queue-no-default = No se encontró un directorio de datos; indica un archivo de cola con --file
+queue-open-failed = No se pudo abrir el archivo de cola '{ $path }'
+queue-empty-command = Nada que encolar: el comando está vacío
+queue-pushed = Encolado para { $server } ({ $count ->
+        [one] 1 comando pendiente
+       *[other] { $count } comandos pendientes
+    })
+queue-empty = No hay nada encolado para { $server }
+queue-drained = { $count ->
+        [one] 1 comando encolado enviado
+       *[other] { $count } comandos encolados enviados
+    } a { $server }
+queue-drain-stopped = Se detuvo el envío a { $server } tras { $sent } enviados; { $remaining } siguen en cola
+
+## Entregas
+
+deliver-no-default = No se encontró un directorio de datos; indica un archivo de registro con --ledger
+deliver-open-failed = No se pudo abrir el registro de entregas '{ $path }'
+deliver-missing-value = Falta un valor --set para un marcador de '{ $template }'
+deliver-already = { $key } ya se entregó; no se envió nada
+deliver-unconfirmed = Comprueba en el servidor si se ejecutó y, si no, termina { $key } con --resend-unconfirmed
+deliver-conflict = Usa una clave nueva para una entrega distinta
+deliver-stopped = La entrega de { $key } se detuvo; ejecútala de nuevo con la misma clave para continuar
+
+## Mantenimiento
+
+maintenance-no-default = No se encontró un directorio de datos; indica un registro de auditoría con --log
+maintenance-open-failed = No se pudo abrir el registro de auditoría de mantenimiento '{ $path }'
+maintenance-default-start = El servidor está en mantenimiento, vuelve más tarde
+maintenance-default-end = ¡El mantenimiento ha terminado, bienvenidos de nuevo!
+maintenance-already-open = El mantenimiento en { $server } está en curso desde { $since }; termínalo primero
+maintenance-not-open = No hay ningún mantenimiento en curso en { $server }
+maintenance-whitelist-on = activada
+maintenance-whitelist-already-on = ya estaba activada, se mantiene al final
+maintenance-whitelist-off = desactivada
+maintenance-whitelist-left-on = se mantiene activada, como antes del mantenimiento
+
+## Pines
+
+warning = Aviso: { $message }
+pin-no-default = No se encontró un directorio de datos para guardar los pines de servidores
+pin-new-addresses = { $server } fijado a { $addresses }
+pin-new-identity = Identidad de { $server } fijada ({ $fingerprint })
+pin-addresses-changed = { $server } ahora resuelve a { $seen }, pero está fijado a { $pinned }. Alguien podría estar desviando la conexión; revisa los registros DNS y usa --accept-pin-change si el cambio es esperado
+pin-identity-changed = { $server } responde a '{ $command }' con { $seen }, pero está fijado a { $pinned }. Podría ser otro servidor; usa --accept-pin-change si el cambio es esperado
+pin-accepted = { $message } (se fija el valor nuevo)
+
+## Lint
+
+lint-clean = Ningún problema en { $commands ->
+        [one] 1 comando
+       *[other] { $commands } comandos
+    } de { $scripts ->
+        [one] 1 script
+       *[other] { $scripts } scripts
+    }
+lint-problems = { $count ->
+    [one] 1 problema encontrado
+   *[other] { $count } problemas encontrados
+}
+
+## Plugins
+
+plugins-unsupported = Los plugins WebAssembly requieren una compilación con la característica `wasm`
+plugins-none = Ningún plugin define un subcomando
+plugin-unknown = Ningún plugin define el subcomando '{ $name }' (disponibles: { $available })
+
+## Traducciones
+
+messages-missing = { $count ->
+        [one] Falta 1 mensaje
+       *[other] Faltan { $count } mensajes
+    } en el idioma '{ $locale }'
+messages-complete = El idioma '{ $locale }' está completo
+unknown-locale = Idioma desconocido '{ $locale }' (disponibles: { $available })
@@ -0,0 +1,123 @@
+//! Vanilla advancement IDs and completion of partial ones
+//!
+//! `data/advancements.txt` lists the advancements of the `minecraft`
+//! namespace; IDs from data packs are passed through unchecked.
+
+use crate::arguments::resource_location;
+use crate::items::edit_distance;
+use std::fmt;
+
+const REGISTRY: &str = include_str!("../data/advancements.txt");
+
+fn known() -> impl Iterator<Item = &'static str> {
+    REGISTRY
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+}
+
+/// Why an advancement ID couldn't be completed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AdvancementError {
+    /// Not a valid resource location
+    InvalidId(String),
+    /// Matches no vanilla advancement, with the closest known ID
+    Unknown {
+        id: String,
+        suggestion: Option<String>,
+    },
+    /// Matches several vanilla advancements
+    Ambiguous { id: String, candidates: Vec<String> },
+}
+
+impl fmt::Display for AdvancementError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AdvancementError::InvalidId(id) => write!(f, "'{}' is not a valid advancement ID", id),
+            AdvancementError::Unknown {
+                id,
+                suggestion: Some(suggestion),
+            } => write!(
+                f,
+                "Unknown advancement '{}' (did you mean '{}'?)",
+                id, suggestion
+            ),
+            AdvancementError::Unknown { id, .. } => write!(f, "Unknown advancement '{}'", id),
+            AdvancementError::Ambiguous { id, candidates } => write!(
+                f,
+                "Advancement '{}' is ambiguous: {}",
+                id,
+                candidates.join(", ")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for AdvancementError {}
+
+/// Complete a partial vanilla advancement ID into its full, namespaced form
+///
+/// Accepts full IDs, the name without its tab (`mine_diamond`), or a unique
+/// prefix of either (`story/mine_d`). Names matching exactly win over
+/// prefixes, so `trade` isn't confused with `trade_at_world_height`.
+///
+/// ```
+/// use rcon_cli::advancements::complete_advancement;
+///
+/// assert_eq!(complete_advancement("mine_diamond").unwrap(), "minecraft:story/mine_diamond");
+/// assert_eq!(complete_advancement("adventure/trade").unwrap(), "minecraft:adventure/trade");
+/// assert_eq!(complete_advancement("mypack:quests/first").unwrap(), "mypack:quests/first");
+/// assert!(complete_advancement("root").is_err());
+/// ```
+pub fn complete_advancement(input: &str) -> Result<String, AdvancementError> {
+    let id = resource_location(input)
+        .map_err(|_| AdvancementError::InvalidId(input.trim().to_string()))?;
+    let Some(path) = id.strip_prefix("minecraft:") else {
+        return Ok(id);
+    };
+    if known().any(|advancement| advancement == path) {
+        return Ok(id);
+    }
+
+    let name = |advancement: &'static str| advancement.rsplit('/').next().unwrap_or(advancement);
+    let mut candidates: Vec<&str> = known()
+        .filter(|advancement| name(advancement) == path)
+        .collect();
+    if candidates.is_empty() {
+        candidates = known()
+            .filter(|advancement| {
+                advancement.starts_with(path) || name(advancement).starts_with(path)
+            })
+            .collect();
+    }
+
+    let id = input.trim().to_string();
+    match candidates.as_slice() {
+        [advancement] => Ok(format!("minecraft:{}", advancement)),
+        [] => Err(AdvancementError::Unknown {
+            suggestion: closest_advancement(path)
+                .map(|advancement| format!("minecraft:{}", advancement)),
+            id,
+        }),
+        _ => Err(AdvancementError::Ambiguous {
+            candidates: candidates
+                .iter()
+                .map(|advancement| format!("minecraft:{}", advancement))
+                .collect(),
+            id,
+        }),
+    }
+}
+
+/// The known advancement closest to `path` by edit distance, if any is close
+fn closest_advancement(path: &str) -> Option<&'static str> {
+    known()
+        .map(|advancement| {
+            let name = advancement.rsplit('/').next().unwrap_or(advancement);
+            let distance = edit_distance(path, advancement).min(edit_distance(path, name));
+            (distance, advancement)
+        })
+        .filter(|(distance, advancement)| *distance <= 3.min(advancement.len() / 3 + 1))
+        .min()
+        .map(|(_, advancement)| advancement)
+}
@@ -0,0 +1,248 @@
+//! Player count threshold alerts
+//!
+//! A [`Condition`] such as `players == 0` is checked against each polled
+//! player list; [`AlertState`] fires once the condition has held for the
+//! rule's hold time and resolves when it stops holding.
+
+use crate::error::RconError;
+use crate::parsers::PlayerList;
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+/// Quantity taken from the player list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    /// Players online
+    Players,
+    /// Player limit of the server
+    MaxPlayers,
+    /// Open slots (`max - players`)
+    FreeSlots,
+}
+
+impl Metric {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Metric::Players => "players",
+            Metric::MaxPlayers => "max",
+            Metric::FreeSlots => "free",
+        }
+    }
+
+    /// Read this metric from a parsed player list
+    pub fn value(self, list: &PlayerList) -> u32 {
+        match self {
+            Metric::Players => list.online,
+            Metric::MaxPlayers => list.max,
+            Metric::FreeSlots => list.max.saturating_sub(list.online),
+        }
+    }
+}
+
+/// Comparison operator of a [`Condition`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl Comparison {
+    /// Operators in matching order, longest first so `<=` wins over `<`
+    const OPERATORS: [(&'static str, Comparison); 6] = [
+        ("==", Comparison::Equal),
+        ("!=", Comparison::NotEqual),
+        ("<=", Comparison::LessOrEqual),
+        (">=", Comparison::GreaterOrEqual),
+        ("<", Comparison::Less),
+        (">", Comparison::Greater),
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Comparison::Equal => "==",
+            Comparison::NotEqual => "!=",
+            Comparison::Less => "<",
+            Comparison::LessOrEqual => "<=",
+            Comparison::Greater => ">",
+            Comparison::GreaterOrEqual => ">=",
+        }
+    }
+
+    pub fn compare(self, left: u32, right: u32) -> bool {
+        match self {
+            Comparison::Equal => left == right,
+            Comparison::NotEqual => left != right,
+            Comparison::Less => left < right,
+            Comparison::LessOrEqual => left <= right,
+            Comparison::Greater => left > right,
+            Comparison::GreaterOrEqual => left >= right,
+        }
+    }
+}
+
+/// A threshold on a player list metric, e.g. `players == 0`
+///
+/// ```
+/// use rcon_cli::alert::Condition;
+/// use rcon_cli::parsers::parse_player_list;
+///
+/// let condition: Condition = "players < 2".parse().unwrap();
+/// let list = parse_player_list("There are 1 of a max of 20 players online: Steve").unwrap();
+/// assert!(condition.matches(&list));
+/// assert_eq!(condition.to_string(), "players < 2");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Condition {
+    pub metric: Metric,
+    pub comparison: Comparison,
+    pub threshold: u32,
+}
+
+impl Condition {
+    pub fn matches(&self, list: &PlayerList) -> bool {
+        self.comparison
+            .compare(self.metric.value(list), self.threshold)
+    }
+}
+
+impl FromStr for Condition {
+    type Err = RconError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (index, operator, comparison) = Comparison::OPERATORS
+            .iter()
+            .filter_map(|(operator, comparison)| {
+                value
+                    .find(operator)
+                    .map(|index| (index, *operator, *comparison))
+            })
+            .min_by_key(|(index, operator, _)| (*index, std::cmp::Reverse(operator.len())))
+            .ok_or_else(|| {
+                RconError::InvalidConfig(format!(
+                    "condition '{}' has no operator (expected ==, !=, <, <=, >, or >=)",
+                    value
+                ))
+            })?;
+
+        let metric = match value[..index].trim() {
+            "players" => Metric::Players,
+            "max" => Metric::MaxPlayers,
+            "free" => Metric::FreeSlots,
+            other => {
+                return Err(RconError::InvalidConfig(format!(
+                    "unknown metric '{}' (expected players, max, or free)",
+                    other
+                )))
+            }
+        };
+        let threshold = value[index + operator.len()..].trim();
+        let threshold = threshold
+            .parse()
+            .map_err(|_| RconError::InvalidConfig(format!("invalid threshold '{}'", threshold)))?;
+
+        Ok(Self {
+            metric,
+            comparison,
+            threshold,
+        })
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {}",
+            self.metric.as_str(),
+            self.comparison.as_str(),
+            self.threshold
+        )
+    }
+}
+
+/// A condition that has to hold for some time before the alert fires
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AlertRule {
+    pub condition: Condition,
+    /// How long the condition must hold; zero fires on the first match
+    pub hold: Duration,
+}
+
+impl AlertRule {
+    pub fn new(condition: Condition, hold: Duration) -> Self {
+        Self { condition, hold }
+    }
+}
+
+/// Change in an alert's state reported by [`AlertState::observe`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertTransition {
+    /// The condition has held for the whole hold time
+    Triggered,
+    /// The condition stopped holding after the alert had fired
+    Resolved,
+}
+
+impl AlertTransition {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AlertTransition::Triggered => "trigger",
+            AlertTransition::Resolved => "resolve",
+        }
+    }
+}
+
+/// Tracks how long an [`AlertRule`]'s condition has held
+///
+/// An alert fires once per episode: it has to resolve before it can
+/// trigger again.
+#[derive(Debug, Clone)]
+pub struct AlertState {
+    rule: AlertRule,
+    /// When the condition started holding
+    since: Option<Instant>,
+    firing: bool,
+}
+
+impl AlertState {
+    pub fn new(rule: AlertRule) -> Self {
+        Self {
+            rule,
+            since: None,
+            firing: false,
+        }
+    }
+
+    pub fn rule(&self) -> &AlertRule {
+        &self.rule
+    }
+
+    /// Whether the alert has triggered and not yet resolved
+    pub fn is_firing(&self) -> bool {
+        self.firing
+    }
+
+    /// Feed the player list polled at `at`
+    pub fn observe(&mut self, list: &PlayerList, at: Instant) -> Option<AlertTransition> {
+        if !self.rule.condition.matches(list) {
+            self.since = None;
+            if self.firing {
+                self.firing = false;
+                return Some(AlertTransition::Resolved);
+            }
+            return None;
+        }
+
+        let since = *self.since.get_or_insert(at);
+        if !self.firing && at.saturating_duration_since(since) >= self.rule.hold {
+            self.firing = true;
+            return Some(AlertTransition::Triggered);
+        }
+        None
+    }
+}
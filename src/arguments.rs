@@ -0,0 +1,511 @@
+//! Client-side validation of Minecraft command arguments
+//!
+//! Catching a malformed selector or coordinate before sending means a
+//! clear error instead of the server's "Incorrect argument" echo.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Selector variables accepted after `@`
+const SELECTOR_VARIABLES: &[char] = &['p', 'a', 'r', 's', 'e', 'n'];
+
+/// Check a player name, UUID, or target selector such as `@a[distance=..5]`
+///
+/// Only the shape is checked: selector arguments must be `key=value` pairs
+/// in balanced brackets, but keys aren't matched against a version.
+///
+/// ```
+/// use rcon_cli::arguments::validate_entity;
+///
+/// assert!(validate_entity("Steve").is_ok());
+/// assert!(validate_entity("@e[type=cow,limit=1]").is_ok());
+/// assert!(validate_entity("@x").is_err());
+/// ```
+pub fn validate_entity(value: &str) -> Result<(), String> {
+    if let Some(selector) = value.strip_prefix('@') {
+        let mut chars = selector.chars();
+        let variable = chars.next();
+        if !variable.is_some_and(|variable| SELECTOR_VARIABLES.contains(&variable)) {
+            return Err(format!(
+                "Invalid selector '{}' (expected @p, @a, @r, @s, @e, or @n)",
+                value
+            ));
+        }
+        let arguments = chars.as_str();
+        if arguments.is_empty() {
+            return Ok(());
+        }
+        return validate_selector_arguments(arguments)
+            .map_err(|reason| format!("Invalid selector '{}': {}", value, reason));
+    }
+
+    let is_name = (1..=16).contains(&value.len())
+        && value.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    let is_uuid = value.len() == 36
+        && value.chars().enumerate().all(|(index, c)| match index {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        });
+    if is_name || is_uuid {
+        Ok(())
+    } else {
+        Err(format!(
+            "'{}' is not a player name, UUID, or selector",
+            value
+        ))
+    }
+}
+
+fn validate_selector_arguments(arguments: &str) -> Result<(), String> {
+    let inner = arguments
+        .strip_prefix('[')
+        .and_then(|rest| rest.strip_suffix(']'))
+        .ok_or("arguments must be enclosed in [ ]")?;
+
+    // Split on top-level commas; values may nest brackets, braces, or quotes
+    let mut depth = 0i32;
+    let mut quoted = false;
+    let mut pairs = Vec::new();
+    let mut start = 0;
+    for (index, c) in inner.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '[' | '{' if !quoted => depth += 1,
+            ']' | '}' if !quoted => depth -= 1,
+            ',' if !quoted && depth == 0 => {
+                pairs.push(&inner[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+        if depth < 0 {
+            return Err("unbalanced brackets".to_string());
+        }
+    }
+    if depth != 0 || quoted {
+        return Err("unbalanced brackets or quotes".to_string());
+    }
+    pairs.push(&inner[start..]);
+
+    for pair in pairs.into_iter().filter(|pair| !pair.trim().is_empty()) {
+        let (key, _) = pair
+            .split_once('=')
+            .ok_or_else(|| format!("'{}' is not a key=value pair", pair.trim()))?;
+        let key = key.trim();
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(format!("invalid key '{}'", key));
+        }
+    }
+    Ok(())
+}
+
+/// One axis of a position: absolute, relative (`~`), or local (`^`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Coordinate {
+    Absolute(f64),
+    Relative(f64),
+    Local(f64),
+}
+
+impl Coordinate {
+    /// The absolute value, if the coordinate isn't relative
+    pub fn absolute(self) -> Option<f64> {
+        match self {
+            Coordinate::Absolute(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for Coordinate {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (constructor, number): (fn(f64) -> Coordinate, &str) =
+            if let Some(offset) = value.strip_prefix('~') {
+                (Coordinate::Relative, offset)
+            } else if let Some(offset) = value.strip_prefix('^') {
+                (Coordinate::Local, offset)
+            } else {
+                (Coordinate::Absolute, value)
+            };
+
+        let relative = number.len() < value.len();
+        let number = match number {
+            "" if relative => 0.0,
+            number => number
+                .parse::<f64>()
+                .ok()
+                .filter(|number| number.is_finite())
+                .ok_or_else(|| format!("'{}' is not a coordinate", value))?,
+        };
+        Ok(constructor(number))
+    }
+}
+
+impl fmt::Display for Coordinate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (prefix, value) = match self {
+            Coordinate::Absolute(value) => ("", *value),
+            Coordinate::Relative(value) => ("~", *value),
+            Coordinate::Local(value) => ("^", *value),
+        };
+        if !prefix.is_empty() && value == 0.0 {
+            write!(f, "{}", prefix)
+        } else {
+            write!(f, "{}{}", prefix, value)
+        }
+    }
+}
+
+/// A block position `x y z`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Position {
+    pub x: Coordinate,
+    pub y: Coordinate,
+    pub z: Coordinate,
+}
+
+impl Position {
+    /// Parse three coordinates; local (`^`) coordinates can't be mixed with others
+    ///
+    /// ```
+    /// use rcon_cli::arguments::Position;
+    ///
+    /// let position = Position::parse("100", "~5", "-20.5").unwrap();
+    /// assert_eq!(position.to_string(), "100 ~5 -20.5");
+    /// assert!(Position::parse("^", "~", "^").is_err());
+    /// ```
+    pub fn parse(x: &str, y: &str, z: &str) -> Result<Self, String> {
+        let position = Self {
+            x: x.parse()?,
+            y: y.parse()?,
+            z: z.parse()?,
+        };
+        let local = [position.x, position.y, position.z]
+            .iter()
+            .filter(|axis| matches!(axis, Coordinate::Local(_)))
+            .count();
+        if local != 0 && local != 3 {
+            return Err("Local coordinates (^) can't be mixed with other kinds".to_string());
+        }
+        Ok(position)
+    }
+
+    /// Block coordinates of the column, when x and z are absolute
+    pub fn block_column(&self) -> Option<(i64, i64)> {
+        Some((
+            self.x.absolute()?.floor() as i64,
+            self.z.absolute()?.floor() as i64,
+        ))
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.x, self.y, self.z)
+    }
+}
+
+/// Namespace a resource location such as `speed`, checking its characters
+///
+/// ```
+/// use rcon_cli::arguments::resource_location;
+///
+/// assert_eq!(resource_location("Speed").unwrap(), "minecraft:speed");
+/// assert!(resource_location("bad id").is_err());
+/// ```
+pub fn resource_location(value: &str) -> Result<String, String> {
+    let id = value.trim().to_lowercase();
+    let (namespace, path) = id.split_once(':').unwrap_or(("minecraft", id.as_str()));
+    let valid = |part: &str, extra: &str| {
+        !part.is_empty()
+            && part.chars().all(|c| {
+                c.is_ascii_lowercase()
+                    || c.is_ascii_digit()
+                    || "_-.".contains(c)
+                    || extra.contains(c)
+            })
+    };
+    if valid(namespace, "") && valid(path, "/") {
+        Ok(format!("{}:{}", namespace, path))
+    } else {
+        Err(format!("'{}' is not a valid resource location", value))
+    }
+}
+
+/// Expand a dimension shorthand such as `nether` into a namespaced ID
+pub fn normalize_dimension(value: &str) -> Result<String, String> {
+    match value.trim().to_lowercase().as_str() {
+        "overworld" => Ok("minecraft:overworld".to_string()),
+        "nether" | "the_nether" => Ok("minecraft:the_nether".to_string()),
+        "end" | "the_end" => Ok("minecraft:the_end".to_string()),
+        other => resource_location(other).map_err(|_| format!("'{}' is not a dimension ID", value)),
+    }
+}
+
+/// Check a time such as `1000`, `0.5d`, `30s`, or `20t` (days, seconds, ticks)
+///
+/// ```
+/// use rcon_cli::arguments::validate_time;
+///
+/// assert!(validate_time("6000").is_ok());
+/// assert!(validate_time("1.5d").is_ok());
+/// assert!(validate_time("-5").is_err());
+/// ```
+pub fn validate_time(value: &str) -> Result<(), String> {
+    let number = value.strip_suffix(['d', 's', 't']).unwrap_or(value);
+    let digits = number.starts_with(|c: char| c.is_ascii_digit());
+    match number.parse::<f64>() {
+        Ok(number) if digits && number.is_finite() => Ok(()),
+        _ => Err(format!(
+            "Invalid time '{}' (expected ticks, or a number with d, s, or t)",
+            value
+        )),
+    }
+}
+
+/// Check a LuckPerms permission node such as `essentials.fly` or `worldedit.*`
+///
+/// ```
+/// use rcon_cli::arguments::validate_permission;
+///
+/// assert!(validate_permission("essentials.fly").is_ok());
+/// assert!(validate_permission("essentials fly").is_err());
+/// ```
+pub fn validate_permission(value: &str) -> Result<(), String> {
+    if value.is_empty() || value.contains(char::is_whitespace) {
+        return Err(format!("Invalid permission '{}'", value));
+    }
+    Ok(())
+}
+
+/// Check a LuckPerms group name, which is lowercase letters, digits, `_`, and `-`
+pub fn validate_group(value: &str) -> Result<(), String> {
+    let valid = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid group '{}' (expected lowercase letters, digits, '_', or '-')",
+            value
+        ))
+    }
+}
+
+/// Check a LuckPerms context such as `server=survival`
+pub fn validate_context(value: &str) -> Result<(), String> {
+    match value.split_once('=') {
+        Some((key, value))
+            if !key.is_empty()
+                && !value.is_empty()
+                && !key.contains(char::is_whitespace)
+                && !value.contains(char::is_whitespace) =>
+        {
+            Ok(())
+        }
+        _ => Err(format!(
+            "Invalid context '{}' (expected key=value, e.g. server=survival)",
+            value
+        )),
+    }
+}
+
+/// Length of a status effect
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EffectDuration {
+    Seconds(u32),
+    /// `infinite`, accepted since Minecraft 1.19.4
+    Infinite,
+}
+
+impl EffectDuration {
+    /// Longest duration the server accepts, in seconds
+    pub const MAX_SECONDS: u32 = 1_000_000;
+
+    /// What `effect give` uses when no duration is given
+    pub const DEFAULT: EffectDuration = EffectDuration::Seconds(30);
+}
+
+impl FromStr for EffectDuration {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if value.eq_ignore_ascii_case("infinite") {
+            return Ok(EffectDuration::Infinite);
+        }
+        match value.parse::<u32>() {
+            Ok(seconds) if (1..=Self::MAX_SECONDS).contains(&seconds) => {
+                Ok(EffectDuration::Seconds(seconds))
+            }
+            _ => Err(format!(
+                "Invalid duration '{}' (expected 1-{} seconds or 'infinite')",
+                value,
+                Self::MAX_SECONDS
+            )),
+        }
+    }
+}
+
+impl fmt::Display for EffectDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EffectDuration::Seconds(seconds) => write!(f, "{}", seconds),
+            EffectDuration::Infinite => write!(f, "infinite"),
+        }
+    }
+}
+
+/// Quote an argument as a brigadier string if it contains whitespace
+///
+/// JSON text, SNBT, and already quoted arguments are left alone.
+pub fn quote_argument(argument: &str) -> String {
+    let structured = argument.starts_with(['{', '[', '"', '\'']);
+    if structured || !argument.contains(char::is_whitespace) {
+        argument.to_string()
+    } else {
+        format!(
+            "\"{}\"",
+            argument.replace('\\', "\\\\").replace('"', "\\\"")
+        )
+    }
+}
+
+/// Join separate shell words into one command
+///
+/// A single word is taken verbatim, so a whole command can be passed as
+/// one argument; otherwise words containing whitespace are quoted with
+/// [`quote_argument`]. A leading `/` is dropped.
+///
+/// ```
+/// use rcon_cli::arguments::join_command;
+///
+/// let words = ["tag", "@s", "add", "vip"].map(String::from);
+/// assert_eq!(join_command(&words), "tag @s add vip");
+/// let words = ["/give", "@s", "minecraft:name_tag{display:{Name:'\"Bob\"'}}"].map(String::from);
+/// assert_eq!(join_command(&words), "give @s minecraft:name_tag{display:{Name:'\"Bob\"'}}");
+/// ```
+pub fn join_command(words: &[String]) -> String {
+    let command = match words {
+        [command] => command.trim().to_string(),
+        words => words
+            .iter()
+            .map(|word| quote_argument(word))
+            .collect::<Vec<_>>()
+            .join(" "),
+    };
+    command.strip_prefix('/').unwrap_or(&command).to_string()
+}
+
+/// Join a command written across several lines into one
+///
+/// Lines are trimmed and joined with single spaces, skipping blank ones,
+/// so indented JSON stays valid; commands are single-line on the server.
+///
+/// ```
+/// use rcon_cli::arguments::join_lines;
+///
+/// let text = "tellraw @a {\n  \"text\": \"Restart in 5 minutes\",\n\n  \"color\": \"gold\"\n}\n";
+/// assert_eq!(
+///     join_lines(text.lines()),
+///     r#"tellraw @a { "text": "Restart in 5 minutes", "color": "gold" }"#
+/// );
+/// ```
+pub fn join_lines<'a>(lines: impl IntoIterator<Item = &'a str>) -> String {
+    lines
+        .into_iter()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Wrap a command in `execute as <selector>`
+///
+/// `execute` commands are extended rather than nested, so further
+/// subcommands such as `at @s` keep working.
+///
+/// ```
+/// use rcon_cli::arguments::execute_as;
+///
+/// assert_eq!(execute_as("@a", "say hi"), "execute as @a run say hi");
+/// assert_eq!(
+///     execute_as("@a", "execute at @s run particle flame ~ ~1 ~"),
+///     "execute as @a at @s run particle flame ~ ~1 ~"
+/// );
+/// ```
+pub fn execute_as(selector: &str, command: &str) -> String {
+    match command.strip_prefix("execute ") {
+        Some(subcommands) => format!("execute as {} {}", selector, subcommands.trim_start()),
+        None => format!("execute as {} run {}", selector, command),
+    }
+}
+
+/// Prepend a command prefix such as a plugin namespace (`mv:`) or a proxy
+/// wrapper (`send survival `)
+///
+/// The prefix is used verbatim, so wrappers need their trailing space.
+/// Commands that already start with it are left alone; a leading `/` is
+/// dropped first.
+///
+/// ```
+/// use rcon_cli::arguments::with_prefix;
+///
+/// assert_eq!(with_prefix("mv:", "list"), "mv:list");
+/// assert_eq!(with_prefix("mv:", "/mv:list"), "mv:list");
+/// assert_eq!(with_prefix("send survival ", "say hi"), "send survival say hi");
+/// ```
+pub fn with_prefix(prefix: &str, command: &str) -> String {
+    let command = command.trim();
+    let command = command.strip_prefix('/').unwrap_or(command);
+    if prefix.is_empty() || command.starts_with(prefix) {
+        command.to_string()
+    } else {
+        format!("{}{}", prefix, command)
+    }
+}
+
+/// Where to teleport to: another entity or a position
+#[derive(Debug, Clone, PartialEq)]
+pub enum Destination {
+    Entity(String),
+    Position(Position),
+}
+
+impl Destination {
+    /// Parse one target or three coordinates
+    ///
+    /// ```
+    /// use rcon_cli::arguments::Destination;
+    ///
+    /// let args = |values: &[&str]| values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+    /// assert!(matches!(Destination::parse(&args(&["Alex"])), Ok(Destination::Entity(_))));
+    /// assert!(matches!(Destination::parse(&args(&["0", "64", "0"])), Ok(Destination::Position(_))));
+    /// assert!(Destination::parse(&args(&["0", "64"])).is_err());
+    /// ```
+    pub fn parse(values: &[String]) -> Result<Self, String> {
+        match values {
+            [target] => {
+                validate_entity(target)?;
+                Ok(Destination::Entity(target.clone()))
+            }
+            [x, y, z] => Position::parse(x, y, z).map(Destination::Position),
+            _ => Err(format!(
+                "Expected a target or x y z coordinates, got {} values",
+                values.len()
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Destination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Destination::Entity(target) => write!(f, "{}", target),
+            Destination::Position(position) => write!(f, "{}", position),
+        }
+    }
+}
@@ -0,0 +1,310 @@
+//! Assertions on command responses for integration tests
+//!
+//! Plugin developers can check what their RCON-facing features answer with
+//! [`assert_response!`](crate::assert_response) and the matchers below, and
+//! run a test against a [`MockServer`] with [`rcon_test`]:
+//!
+//! ```
+//! use rcon_cli::assert_response;
+//! use rcon_cli::assertions::{contains, json_path, rcon_test};
+//! use rcon_cli::mock_server::MockServer;
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let server = MockServer::new("secret")
+//!     .respond("eco balance Steve", "Balance of Steve: $120")
+//!     .respond("stats json", r#"{"players": [{"name": "Steve", "kills": 3}]}"#);
+//!
+//! let server = rcon_test(server, |mut client| async move {
+//!     let balance = client.execute_command("eco balance Steve").await.unwrap();
+//!     assert_response!(balance, contains("$120"));
+//!
+//!     let stats = client.execute_command("stats json").await.unwrap();
+//!     assert_response!(stats, json_path("$.players[0].kills", 3));
+//! })
+//! .await;
+//! assert_eq!(server.received().len(), 2);
+//! # }
+//! ```
+
+use crate::client::RconClient;
+use crate::mock_server::{MockServer, MockServerHandle};
+use regex::Regex;
+use serde_json::Value;
+use std::fmt;
+use std::future::Future;
+
+/// A condition a response has to meet
+#[derive(Debug, Clone)]
+pub enum Matcher {
+    /// The response is exactly this text
+    Equals(String),
+    /// The response contains this text
+    Contains(String),
+    /// The regex matches somewhere in the response
+    Regex(Regex),
+    /// The response is JSON with a value at the path, equal to the expected
+    /// value if one is given
+    JsonPath {
+        path: String,
+        expected: Option<Value>,
+    },
+    /// The inner matcher fails
+    Not(Box<Matcher>),
+}
+
+/// Match a response that is exactly `text`
+pub fn equals(text: impl Into<String>) -> Matcher {
+    Matcher::Equals(text.into())
+}
+
+/// Match a response containing `text`
+pub fn contains(text: impl Into<String>) -> Matcher {
+    Matcher::Contains(text.into())
+}
+
+/// Match a response the regex `pattern` finds a match in
+///
+/// Panics if the pattern is invalid.
+pub fn regex(pattern: &str) -> Matcher {
+    match Regex::new(pattern) {
+        Ok(regex) => Matcher::Regex(regex),
+        Err(e) => panic!("Invalid regex '{}': {}", pattern, e),
+    }
+}
+
+/// Match a JSON response whose value at `path` equals `expected`
+///
+/// Paths use the common JSONPath subset of `$`, `.key`, `['key']`, and
+/// `[index]`, e.g. `$.players[0].name`.
+pub fn json_path(path: impl Into<String>, expected: impl Into<Value>) -> Matcher {
+    Matcher::JsonPath {
+        path: path.into(),
+        expected: Some(expected.into()),
+    }
+}
+
+/// Match a JSON response that has any value at `path`
+pub fn json_path_exists(path: impl Into<String>) -> Matcher {
+    Matcher::JsonPath {
+        path: path.into(),
+        expected: None,
+    }
+}
+
+/// Match a response the matcher rejects
+pub fn not(matcher: Matcher) -> Matcher {
+    Matcher::Not(Box::new(matcher))
+}
+
+impl fmt::Display for Matcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Equals(text) => write!(f, "equal {:?}", text),
+            Self::Contains(text) => write!(f, "contain {:?}", text),
+            Self::Regex(regex) => write!(f, "match /{}/", regex),
+            Self::JsonPath {
+                path,
+                expected: Some(expected),
+            } => write!(f, "have {} at {}", expected, path),
+            Self::JsonPath {
+                path,
+                expected: None,
+            } => write!(f, "have a value at {}", path),
+            Self::Not(matcher) => write!(f, "not {}", matcher),
+        }
+    }
+}
+
+/// Why a response did not meet a matcher
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssertionFailure {
+    /// What was expected, e.g. `contain "$120"`
+    pub expected: String,
+    /// The response that was checked
+    pub response: String,
+    /// Further detail, such as the value found at a JSON path
+    pub detail: Option<String>,
+}
+
+impl fmt::Display for AssertionFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "expected response to {}\n  response: {:?}",
+            self.expected, self.response
+        )?;
+        if let Some(detail) = &self.detail {
+            write!(f, "\n  {}", detail)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for AssertionFailure {}
+
+impl Matcher {
+    /// Check a response, explaining a mismatch
+    ///
+    /// ```
+    /// use rcon_cli::assertions::{regex, not, contains};
+    ///
+    /// assert!(regex(r"^There are \d+").check("There are 3 of a max of 20").is_ok());
+    /// assert!(not(contains("Unknown")).check("Unknown command").is_err());
+    /// ```
+    pub fn check(&self, response: &str) -> Result<(), AssertionFailure> {
+        let failure = |detail: Option<String>| AssertionFailure {
+            expected: self.to_string(),
+            response: response.to_string(),
+            detail,
+        };
+
+        match self {
+            Self::Equals(text) if response == text => Ok(()),
+            Self::Contains(text) if response.contains(text.as_str()) => Ok(()),
+            Self::Regex(regex) if regex.is_match(response) => Ok(()),
+            Self::Equals(_) | Self::Contains(_) | Self::Regex(_) => Err(failure(None)),
+            Self::JsonPath { path, expected } => {
+                let document: Value = serde_json::from_str(response)
+                    .map_err(|e| failure(Some(format!("not JSON: {}", e))))?;
+                let found = select(&document, path).map_err(|e| failure(Some(e)))?;
+                match (found, expected) {
+                    (None, _) => Err(failure(Some(format!("nothing at {}", path)))),
+                    (Some(found), Some(expected)) if found != expected => {
+                        Err(failure(Some(format!("found: {}", found))))
+                    }
+                    (Some(_), _) => Ok(()),
+                }
+            }
+            Self::Not(matcher) => match matcher.check(response) {
+                Ok(()) => Err(failure(None)),
+                Err(_) => Ok(()),
+            },
+        }
+    }
+}
+
+/// One step of a JSON path
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// Parse the supported JSONPath subset
+fn parse_path(path: &str) -> Result<Vec<Segment>, String> {
+    let invalid = || format!("invalid JSON path '{}'", path);
+    // A bare leading key, as in `players[0]`, is relative to the root
+    let dotted;
+    let mut rest = match path.strip_prefix('$') {
+        Some(rest) => rest,
+        None if path.starts_with(['.', '[']) => path,
+        None => {
+            dotted = format!(".{}", path);
+            &dotted
+        }
+    };
+    let mut segments = Vec::new();
+
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('.') {
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            if end == 0 {
+                return Err(invalid());
+            }
+            segments.push(Segment::Key(after[..end].to_string()));
+            rest = &after[end..];
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let end = after.find(']').ok_or_else(invalid)?;
+            let inner = &after[..end];
+            let quoted = inner
+                .strip_prefix('\'')
+                .and_then(|key| key.strip_suffix('\''))
+                .or_else(|| {
+                    inner
+                        .strip_prefix('"')
+                        .and_then(|key| key.strip_suffix('"'))
+                });
+            segments.push(match quoted {
+                Some(key) => Segment::Key(key.to_string()),
+                None => Segment::Index(inner.parse().map_err(|_| invalid())?),
+            });
+            rest = &after[end + 1..];
+        } else {
+            return Err(invalid());
+        }
+    }
+    Ok(segments)
+}
+
+/// Look up the value at a JSON path
+///
+/// ```
+/// use rcon_cli::assertions::select;
+/// use serde_json::json;
+///
+/// let document = json!({"players": [{"name": "Steve"}], "max players": 20});
+/// assert_eq!(select(&document, "$.players[0].name"), Ok(Some(&json!("Steve"))));
+/// assert_eq!(select(&document, "$['max players']"), Ok(Some(&json!(20))));
+/// assert_eq!(select(&document, "$.players[1]"), Ok(None));
+/// ```
+pub fn select<'a>(document: &'a Value, path: &str) -> Result<Option<&'a Value>, String> {
+    let mut value = document;
+    for segment in parse_path(path)? {
+        let next = match segment {
+            Segment::Key(key) => value.get(key.as_str()),
+            Segment::Index(index) => value.get(index),
+        };
+        match next {
+            Some(next) => value = next,
+            None => return Ok(None),
+        }
+    }
+    Ok(Some(value))
+}
+
+/// Assert that a response meets a matcher, panicking with the mismatch
+///
+/// ```
+/// use rcon_cli::assert_response;
+/// use rcon_cli::assertions::{contains, not};
+///
+/// assert_response!("Gave 64 [Stone] to Steve", contains("64"));
+/// assert_response!("Gave 64 [Stone] to Steve", not(contains("Unknown")), "give failed");
+/// ```
+#[macro_export]
+macro_rules! assert_response {
+    ($response:expr, $matcher:expr $(,)?) => {{
+        let response: &str = ::std::convert::AsRef::as_ref(&$response);
+        if let Err(failure) = $crate::assertions::Matcher::check(&$matcher, response) {
+            panic!("assertion failed: {}", failure);
+        }
+    }};
+    ($response:expr, $matcher:expr, $($message:tt)+) => {{
+        let response: &str = ::std::convert::AsRef::as_ref(&$response);
+        if let Err(failure) = $crate::assertions::Matcher::check(&$matcher, response) {
+            panic!("assertion failed: {}: {}", format_args!($($message)+), failure);
+        }
+    }};
+}
+
+/// Run a test against a freshly started mock server
+///
+/// The test gets a client already logged in to `server`. The returned
+/// handle keeps the server running, so the commands it received can be
+/// checked afterwards. Panics if the server cannot be started or connected
+/// to.
+pub async fn rcon_test<F, Fut>(server: MockServer, test: F) -> MockServerHandle
+where
+    F: FnOnce(RconClient) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    let handle = server
+        .start()
+        .await
+        .unwrap_or_else(|e| panic!("Failed to start the mock server: {}", e));
+    let client = RconClient::connect(handle.config())
+        .await
+        .unwrap_or_else(|e| panic!("Failed to connect to the mock server: {}", e));
+    test(client).await;
+    handle
+}
@@ -0,0 +1,209 @@
+//! Templated commands over rows of CSV input
+//!
+//! A template such as `whitelist add {name}` is rendered once per row; the
+//! placeholders name columns of the header row, or their 0-based positions
+//! (`{0}`) when the input has no header.
+
+use crate::error::{RconError, Result};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// CSV input: column names and the data rows
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Table {
+    /// Header names; empty when the input has no header row
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    /// Parse CSV with double-quoted fields and `""` escapes
+    ///
+    /// Blank lines are skipped. With `header`, the first row names the
+    /// columns.
+    pub fn parse(source: &str, header: bool) -> Result<Self> {
+        let mut records = parse_csv(source)?;
+        let columns = if header && !records.is_empty() {
+            records.remove(0)
+        } else {
+            Vec::new()
+        };
+        Ok(Self {
+            columns: columns.into_iter().map(|c| c.trim().to_string()).collect(),
+            rows: records,
+        })
+    }
+
+    /// Read and parse a CSV file
+    pub fn load(path: impl AsRef<Path>, header: bool) -> Result<Self> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path).map_err(|e| {
+            RconError::InvalidConfig(format!("Failed to read '{}': {}", path.display(), e))
+        })?;
+        Self::parse(&source, header)
+    }
+
+    /// Index of the column `name`, or `position` when there is no header
+    ///
+    /// ```
+    /// use rcon_cli::bulk::Table;
+    ///
+    /// let table = Table::parse("amount,player\n5,Alice\n", true).unwrap();
+    /// assert_eq!(table.column("player", 0).unwrap(), 1);
+    /// assert!(table.column("reason", 2).is_err());
+    /// ```
+    pub fn column(&self, name: &str, position: usize) -> Result<usize> {
+        if self.columns.is_empty() {
+            return Ok(position);
+        }
+        self.columns
+            .iter()
+            .position(|column| column.eq_ignore_ascii_case(name))
+            .ok_or_else(|| {
+                RconError::InvalidConfig(format!(
+                    "No '{}' column in CSV input (columns: {})",
+                    name,
+                    self.columns.join(", ")
+                ))
+            })
+    }
+}
+
+fn parse_csv(source: &str) -> Result<Vec<Vec<String>>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = source.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' if in_quotes => in_quotes = false,
+            '"' if field.trim().is_empty() => {
+                field.clear();
+                in_quotes = true;
+            }
+            ',' if !in_quotes => record.push(std::mem::take(&mut field)),
+            '\n' if !in_quotes => {
+                record.push(std::mem::take(&mut field));
+                push_record(&mut records, std::mem::take(&mut record));
+            }
+            '\r' if !in_quotes && chars.peek() == Some(&'\n') => {}
+            c => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err(RconError::InvalidConfig(
+            "Unterminated quoted field in CSV input".to_string(),
+        ));
+    }
+    record.push(field);
+    push_record(&mut records, record);
+
+    Ok(records)
+}
+
+fn push_record(records: &mut Vec<Vec<String>>, record: Vec<String>) {
+    if !(record.len() == 1 && record[0].trim().is_empty()) {
+        records.push(record);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Text(String),
+    Column(usize),
+}
+
+/// A command with `{column}` placeholders, bound to a table's columns
+///
+/// ```
+/// use rcon_cli::bulk::{Table, Template};
+///
+/// let table = Table::parse("name,group\nAlice,admin\n", true).unwrap();
+/// let template = Template::parse("lp user {name} parent add {group}", &table.columns).unwrap();
+/// assert_eq!(template.render(&table.rows[0]), "lp user Alice parent add admin");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    segments: Vec<Segment>,
+}
+
+impl Template {
+    /// Parse a template; `{{` and `}}` produce literal braces
+    ///
+    /// Placeholders must name one of `columns` or be a column index.
+    pub fn parse(template: &str, columns: &[String]) -> Result<Self> {
+        let indices: HashMap<&str, usize> = columns
+            .iter()
+            .enumerate()
+            .map(|(index, name)| (name.as_str(), index))
+            .collect();
+
+        let mut segments = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let name: String = chars.by_ref().take_while(|c| *c != '}').collect();
+                    let name = name.trim();
+                    let index = match indices.get(name) {
+                        Some(index) => *index,
+                        None => name.parse().map_err(|_| {
+                            RconError::InvalidConfig(format!(
+                                "Unknown column '{{{}}}' in template (columns: {})",
+                                name,
+                                columns.join(", ")
+                            ))
+                        })?,
+                    };
+                    if !text.is_empty() {
+                        segments.push(Segment::Text(std::mem::take(&mut text)));
+                    }
+                    segments.push(Segment::Column(index));
+                }
+                c => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            segments.push(Segment::Text(text));
+        }
+
+        Ok(Self { segments })
+    }
+
+    /// Highest column index referenced, to check rows before sending anything
+    pub fn max_column(&self) -> Option<usize> {
+        self.segments
+            .iter()
+            .filter_map(|segment| match segment {
+                Segment::Column(index) => Some(*index),
+                Segment::Text(_) => None,
+            })
+            .max()
+    }
+
+    /// Render the command for a row; missing fields render as empty
+    pub fn render(&self, row: &[String]) -> String {
+        self.segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Text(text) => text.as_str(),
+                Segment::Column(index) => row.get(*index).map_or("", |value| value.trim()),
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,147 @@
+//! Chunk areas for `forceload`
+//!
+//! The server refuses `forceload add` for more than 256 chunks at once and
+//! `forceload query` takes a single chunk, so larger areas are split into
+//! tiles the server accepts.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Most chunks a single `forceload add` or `forceload remove` accepts
+pub const FORCELOAD_LIMIT: u64 = 256;
+
+/// Block coordinates of a column, written `x,z`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockColumn {
+    pub x: i64,
+    pub z: i64,
+}
+
+impl BlockColumn {
+    /// The chunk containing this column
+    pub fn chunk(self) -> ChunkPos {
+        ChunkPos {
+            x: self.x.div_euclid(16),
+            z: self.z.div_euclid(16),
+        }
+    }
+}
+
+impl FromStr for BlockColumn {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid block position '{}' (expected x,z)", value);
+        let (x, z) = value.split_once(',').ok_or_else(invalid)?;
+        Ok(Self {
+            x: x.trim().parse().map_err(|_| invalid())?,
+            z: z.trim().parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+/// Chunk coordinates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ChunkPos {
+    pub x: i64,
+    pub z: i64,
+}
+
+impl ChunkPos {
+    /// Block coordinates of the chunk's north-west corner, as commands expect
+    pub fn block_args(self) -> String {
+        format!("{} {}", self.x * 16, self.z * 16)
+    }
+}
+
+impl fmt::Display for ChunkPos {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}, {}]", self.x, self.z)
+    }
+}
+
+/// A rectangle of chunks, corners included
+///
+/// ```
+/// use rcon_cli::chunks::{BlockColumn, ChunkArea, FORCELOAD_LIMIT};
+///
+/// let from: BlockColumn = "-100,-100".parse().unwrap();
+/// let to: BlockColumn = "400,200".parse().unwrap();
+/// let area = ChunkArea::between(from.chunk(), to.chunk());
+/// assert_eq!(area.chunk_count(), 33 * 20);
+///
+/// let tiles = area.split(FORCELOAD_LIMIT);
+/// assert!(tiles.iter().all(|tile| tile.chunk_count() <= FORCELOAD_LIMIT));
+/// assert_eq!(tiles.iter().map(|tile| tile.chunk_count()).sum::<u64>(), area.chunk_count());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkArea {
+    pub min: ChunkPos,
+    pub max: ChunkPos,
+}
+
+impl ChunkArea {
+    /// The area spanned by two corners in any order
+    pub fn between(a: ChunkPos, b: ChunkPos) -> Self {
+        Self {
+            min: ChunkPos {
+                x: a.x.min(b.x),
+                z: a.z.min(b.z),
+            },
+            max: ChunkPos {
+                x: a.x.max(b.x),
+                z: a.z.max(b.z),
+            },
+        }
+    }
+
+    /// Number of chunks in the area
+    pub fn chunk_count(&self) -> u64 {
+        (self.max.x - self.min.x + 1) as u64 * (self.max.z - self.min.z + 1) as u64
+    }
+
+    /// Whether the area is a single chunk; an area is never empty
+    pub fn is_single(&self) -> bool {
+        self.min == self.max
+    }
+
+    /// Every chunk of the area, row by row
+    pub fn chunks(&self) -> impl Iterator<Item = ChunkPos> + '_ {
+        (self.min.z..=self.max.z)
+            .flat_map(move |z| (self.min.x..=self.max.x).map(move |x| ChunkPos { x, z }))
+    }
+
+    /// Split into tiles of at most `limit` chunks each
+    ///
+    /// Tiles span as many whole rows as fit, so wide areas need few commands.
+    pub fn split(&self, limit: u64) -> Vec<ChunkArea> {
+        let limit = limit.max(1) as i64;
+        let width = (self.max.x - self.min.x + 1).min(limit);
+        let rows = limit / width;
+        let mut tiles = Vec::new();
+        let mut z = self.min.z;
+        while z <= self.max.z {
+            let z_end = (z + rows - 1).min(self.max.z);
+            let mut x = self.min.x;
+            while x <= self.max.x {
+                let x_end = (x + width - 1).min(self.max.x);
+                tiles.push(ChunkArea {
+                    min: ChunkPos { x, z },
+                    max: ChunkPos { x: x_end, z: z_end },
+                });
+                x = x_end + 1;
+            }
+            z = z_end + 1;
+        }
+        tiles
+    }
+
+    /// Block coordinates of both corners, as `forceload add` expects
+    pub fn block_args(&self) -> String {
+        if self.is_single() {
+            self.min.block_args()
+        } else {
+            format!("{} {}", self.min.block_args(), self.max.block_args())
+        }
+    }
+}
@@ -1,6 +1,35 @@
-use clap::{Parser, Subcommand, ValueEnum};
-use std::net::SocketAddr;
-use std::time::Duration;
+use crate::alert::Condition;
+use crate::arguments::{
+    normalize_dimension, resource_location, validate_context, validate_entity, validate_group,
+    validate_permission, validate_time, EffectDuration,
+};
+use crate::chunks::BlockColumn;
+use crate::client::{CommandResponse, ReconnectPolicy};
+use crate::config::{ConfigFile, Profile};
+use crate::dialect::DialectKind;
+use crate::error::RconError;
+use crate::filter::{JsonPath, LineRange, OutputFilter};
+use crate::highlight::Highlighter;
+use crate::items::McVersion;
+use crate::k8s::K8sConfig;
+use crate::metrics::MetricsTarget;
+use crate::mux::{Priority, RateLimit};
+use crate::net::split_host_port;
+use crate::parser_plugins::ExternalParsers;
+use crate::pins::PinPolicy;
+use crate::progress::{ConnectPhase, ConnectProgress};
+use crate::t;
+use clap::builder::FalseyValueParser;
+use clap::{ArgGroup, Parser, Subcommand, ValueEnum};
+use regex::Regex;
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::{self, IsTerminal, Write};
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::warn;
 
 /// CLI interface for the RCON client
 #[derive(Parser)]
@@ -23,30 +52,115 @@ pub struct Cli {
     #[arg(
         short = 'a',
         long = "address",
-        default_value = "localhost:25575",
-        help = "RCON server address (host:port)",
+        env = "RCON_ADDRESS",
+        help = "RCON server address (host:port) [default: localhost:25575]",
         value_name = "HOST:PORT"
     )]
-    pub address: String,
+    pub address: Option<String>,
 
     /// RCON password
-    #[arg(short = 'p', long = "password", help = "RCON server password")]
-    pub password: String,
+    #[arg(
+        short = 'p',
+        long = "password",
+        env = "RCON_PASSWORD",
+        hide_env_values = true,
+        help = "RCON server password"
+    )]
+    pub password: Option<String>,
+
+    /// Configuration file with server profiles
+    #[arg(
+        long = "config",
+        env = "RCON_CONFIG",
+        help = "Config file with server profiles [default: <config dir>/rcon-cli/config.toml]",
+        value_name = "FILE"
+    )]
+    pub config: Option<PathBuf>,
+
+    /// Named server profile from the configuration file
+    #[arg(
+        long = "profile",
+        env = "RCON_PROFILE",
+        help = "Server profile from the config file",
+        value_name = "NAME"
+    )]
+    pub profile: Option<String>,
+
+    /// Control socket of the persistent daemon
+    #[arg(
+        long = "socket",
+        env = "RCON_SOCKET",
+        help = "Daemon control socket [default: <runtime dir>/rcon-cli.sock]",
+        value_name = "PATH"
+    )]
+    pub socket: Option<PathBuf>,
+
+    /// Additional passwords tried in order if the primary one is rejected
+    // Only the selected server gets them; other profiles, e.g. with
+    // `ping --all-profiles`, use their own `fallback_passwords`
+    #[arg(
+        long = "fallback-password",
+        env = "RCON_FALLBACK_PASSWORD",
+        hide_env_values = true,
+        help = "Password to try on the selected server if the previous ones are rejected (repeatable)",
+        value_name = "PASSWORD"
+    )]
+    pub fallback_passwords: Vec<String>,
 
     /// Connection timeout in seconds
     #[arg(
         short = 't',
         long = "timeout",
-        default_value = "5",
-        help = "Connection timeout in seconds",
+        env = "RCON_TIMEOUT",
+        help = "Connection timeout in seconds [default: 5]",
         value_name = "SECONDS"
     )]
-    pub timeout: u64,
+    pub timeout: Option<u64>,
+
+    /// Hard upper bound on the whole invocation
+    #[arg(
+        long = "deadline",
+        env = "RCON_DEADLINE",
+        help = "Abort if the whole operation (DNS, connect, auth, commands) takes longer, e.g. 10s, 500ms, 2m",
+        value_name = "DURATION",
+        value_parser = parse_duration
+    )]
+    pub deadline: Option<Duration>,
+
+    /// Connection attempts after the first failed one
+    #[arg(
+        long = "retries",
+        env = "RCON_RETRIES",
+        help = "Retry a failed connection this many times [default: 2]",
+        value_name = "N"
+    )]
+    pub retries: Option<u32>,
+
+    /// Wait before the first retry
+    #[arg(
+        long = "retry-delay",
+        env = "RCON_RETRY_DELAY",
+        help = "Wait before the first connection retry, e.g. 500ms or 2s [default: 1s]",
+        value_name = "DURATION",
+        value_parser = parse_duration
+    )]
+    pub retry_delay: Option<Duration>,
+
+    /// Growth of the wait between retries
+    #[arg(
+        long = "retry-backoff",
+        env = "RCON_RETRY_BACKOFF",
+        help = "Multiply the wait by this factor after each retry, e.g. 2 for exponential backoff [default: 1]",
+        value_name = "FACTOR",
+        value_parser = parse_backoff
+    )]
+    pub retry_backoff: Option<f64>,
 
     /// Logging level
     #[arg(
         short = 'v',
         long = "verbose",
+        env = "RCON_VERBOSE",
         help = "Increase logging verbosity",
         action = clap::ArgAction::Count
     )]
@@ -56,15 +170,224 @@ pub struct Cli {
     #[arg(
         short = 'f',
         long = "format",
+        env = "RCON_FORMAT",
         default_value = "text",
         help = "Output format"
     )]
     pub format: OutputFormat,
 
-    /// Disable colored output
+    /// Columns to include in tabular output
+    #[arg(
+        long = "columns",
+        env = "RCON_COLUMNS",
+        help = "Only show these columns of tabular output, in order (e.g. name,uuid)",
+        value_name = "NAMES",
+        value_delimiter = ','
+    )]
+    pub columns: Vec<String>,
+
+    /// Select values from JSON output
+    #[arg(
+        long = "jsonpath",
+        env = "RCON_JSONPATH",
+        help = "Print the values this path selects from the JSON output, one per line (e.g. '$.records[*].name'); implies --format json",
+        value_name = "PATH"
+    )]
+    pub jsonpath: Option<JsonPath>,
+
+    /// Keep output lines matching a pattern
+    // Lines are matched one at a time, so no pattern can usefully contain a
+    // newline, which leaves it free to separate patterns in `RCON_GREP`
+    #[arg(
+        long = "grep",
+        env = "RCON_GREP",
+        help = "Only print output lines matching this regex (repeatable; lines must match all)",
+        value_name = "REGEX",
+        value_delimiter = '\n'
+    )]
+    pub grep: Vec<Regex>,
+
+    /// Keep a slice of the output lines
+    #[arg(
+        long = "lines",
+        env = "RCON_LINES",
+        help = "Only print output lines START to END, counted from 0; negative ends count from the last line (e.g. 0:20, -5:)",
+        value_name = "START:END",
+        allow_hyphen_values = true
+    )]
+    pub lines: Option<LineRange>,
+
+    /// Protocol dialect spoken by the server
+    #[arg(
+        long = "dialect",
+        env = "RCON_DIALECT",
+        help = "RCON protocol dialect of the server [default: minecraft]"
+    )]
+    pub dialect: Option<DialectKind>,
+
+    /// Local address to originate connections from
+    #[arg(
+        long = "bind",
+        env = "RCON_BIND",
+        help = "Local IP address (or IP:port) to connect from",
+        value_name = "ADDRESS"
+    )]
+    pub bind: Option<String>,
+
+    /// Prefix for commands sent with exec and in interactive mode
+    #[arg(
+        long = "command-prefix",
+        env = "RCON_COMMAND_PREFIX",
+        help = "Prepend PREFIX to commands sent with exec and in interactive mode, e.g. a plugin namespace like 'mv:' (overrides the profile's prefix)",
+        value_name = "PREFIX"
+    )]
+    pub command_prefix: Option<String>,
+
+    /// Print packets instead of sending them
+    #[arg(
+        long = "dry-run",
+        env = "RCON_DRY_RUN",
+        help = "Print the commands that would be sent instead of connecting to the server",
+        value_parser = FalseyValueParser::new(),
+        action = clap::ArgAction::SetTrue
+    )]
+    pub dry_run: bool,
+
+    /// Echo commands as sent, after substitution
+    #[arg(
+        long = "show-expansion",
+        env = "RCON_SHOW_EXPANSION",
+        help = "Show each command on stderr as it will be sent, after the command prefix or --template is applied",
+        value_parser = FalseyValueParser::new(),
+        action = clap::ArgAction::SetTrue
+    )]
+    pub show_expansion: bool,
+
+    /// Reject everything but queries
+    #[arg(
+        long = "read-only",
+        env = "RCON_READ_ONLY",
+        help = "Only send queries (list, version, seed, data get, and TPS commands), rejecting other commands without sending them",
+        value_parser = FalseyValueParser::new(),
+        action = clap::ArgAction::SetTrue
+    )]
+    pub read_only: bool,
+
+    /// Replace pins that no longer match
+    // Deliberately not read from the environment, so a pin change is only
+    // ever accepted for the invocation that asks for it
+    #[arg(
+        long = "accept-pin-change",
+        help = "Accept a pinned profile's new addresses or identity and pin them instead of warning or refusing",
+        action = clap::ArgAction::SetTrue
+    )]
+    pub accept_pin_change: bool,
+
+    /// Kubernetes Service in front of the server
+    #[arg(
+        long = "k8s",
+        env = "RCON_K8S",
+        help = "Discover the server from a Kubernetes Service (overrides the profile's)",
+        value_name = "NAMESPACE/SERVICE"
+    )]
+    pub k8s: Option<String>,
+
+    /// Kubernetes Secret holding the RCON password
+    #[arg(
+        long = "k8s-secret",
+        env = "RCON_K8S_SECRET",
+        help = "Read the password from a Secret in the Service's namespace [default key: rcon-password]",
+        value_name = "NAME[:KEY]"
+    )]
+    pub k8s_secret: Option<String>,
+
+    /// Kubernetes API server
+    #[arg(
+        long = "k8s-api",
+        env = "RCON_K8S_API",
+        help = "Kubernetes API server URL, e.g. of 'kubectl proxy' [default: in-cluster service account]",
+        value_name = "URL"
+    )]
+    pub k8s_api: Option<String>,
+
+    /// File receiving command responses
+    #[arg(
+        short = 'o',
+        long = "output",
+        env = "RCON_OUTPUT",
+        help = "Write responses to FILE instead of stdout (status messages stay on stderr)",
+        value_name = "FILE"
+    )]
+    pub output: Option<PathBuf>,
+
+    /// Append to the output file instead of truncating it
+    #[arg(
+        long = "append",
+        env = "RCON_APPEND",
+        help = "Append to the --output file instead of overwriting it",
+        requires = "output",
+        value_parser = FalseyValueParser::new(),
+        action = clap::ArgAction::SetTrue
+    )]
+    pub append: bool,
+
+    /// Timestamp printed lines and report command durations
+    #[arg(
+        long = "timestamps",
+        env = "RCON_TIMESTAMPS",
+        help = "Prefix output lines with a timestamp and append command durations",
+        value_name = "STYLE",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "rfc3339"
+    )]
+    pub timestamps: Option<TimestampStyle>,
+
+    /// Only print responses and errors
+    #[arg(
+        short = 'q',
+        long = "quiet",
+        env = "RCON_QUIET",
+        help = "Suppress informational and progress messages",
+        value_parser = FalseyValueParser::new(),
+        action = clap::ArgAction::SetTrue
+    )]
+    pub quiet: bool,
+
+    /// Stable output for scripts
+    #[arg(
+        long = "porcelain",
+        env = "RCON_PORCELAIN",
+        help = "Stable, parse-friendly output (implies --quiet and --color never)",
+        value_parser = FalseyValueParser::new(),
+        action = clap::ArgAction::SetTrue
+    )]
+    pub porcelain: bool,
+
+    /// Language of the CLI's own messages
+    #[arg(
+        long = "lang",
+        env = "RCON_LANG",
+        help = "Language for messages, e.g. de or es_ES [default: from LC_ALL/LC_MESSAGES/LANG]",
+        value_name = "LANG"
+    )]
+    pub lang: Option<String>,
+
+    /// When to use colored output
+    #[arg(
+        long = "color",
+        env = "RCON_COLOR",
+        default_value = "auto",
+        help = "When to color output; auto honors NO_COLOR and CLICOLOR_FORCE",
+        value_name = "WHEN"
+    )]
+    pub color: ColorChoice,
+
+    /// Deprecated alias for `--color never`
     #[arg(
         long = "no-color",
-        help = "Disable colored output",
+        hide = true,
+        conflicts_with = "color",
         action = clap::ArgAction::SetTrue
     )]
     pub no_color: bool,
@@ -78,8 +401,137 @@ pub struct Cli {
 pub enum OutputFormat {
     /// Plain text output (default)
     Text,
-    /// JSON formatted output
+    /// JSON formatted output, one compact object per line
     Json,
+    /// Indented JSON for reading by humans
+    JsonPretty,
+    /// Aligned unicode tables for structured subcommands
+    Table,
+}
+
+/// Color modes for `--color`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    /// Color when writing to a terminal, following NO_COLOR and CLICOLOR_FORCE
+    #[default]
+    Auto,
+    /// Always emit color escape sequences
+    Always,
+    /// Never color output
+    Never,
+}
+
+impl ColorChoice {
+    /// Decide whether to color output going to a terminal (or not)
+    ///
+    /// In `auto` mode a non-empty `NO_COLOR` disables colors, then a
+    /// `CLICOLOR_FORCE` other than `0` enables them, then `CLICOLOR=0`
+    /// disables them; otherwise terminals are colored unless `TERM=dumb`
+    /// or the console can't interpret escape sequences (see
+    /// [`ansi_supported`]).
+    pub fn resolve(self, is_terminal: bool) -> bool {
+        let env = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+        match self {
+            ColorChoice::Always => {
+                // Still turn on escape sequences in a Windows console
+                ansi_supported();
+                true
+            }
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                if env("NO_COLOR").is_some() {
+                    false
+                } else if env("CLICOLOR_FORCE").is_some_and(|value| value != "0") {
+                    true
+                } else if env("CLICOLOR").is_some_and(|value| value == "0") {
+                    false
+                } else {
+                    is_terminal && anstyle_query::term_supports_color() && ansi_supported()
+                }
+            }
+        }
+    }
+}
+
+/// Whether the console interprets ANSI escape sequences
+///
+/// Windows consoles only do once virtual terminal processing is enabled,
+/// which legacy `cmd.exe` consoles refuse; other platforms always do. The
+/// first call enables it.
+pub fn ansi_supported() -> bool {
+    static SUPPORTED: OnceLock<bool> = OnceLock::new();
+    *SUPPORTED.get_or_init(|| anstyle_query::windows::enable_ansi_colors().unwrap_or(true))
+}
+
+/// Sequence erasing a spinner or progress bar before a line is printed
+///
+/// Erasing needs escape sequences, so without them nothing is erased.
+fn clear_line() -> &'static str {
+    if io::stderr().is_terminal() && ansi_supported() {
+        "\r\x1b[2K"
+    } else {
+        ""
+    }
+}
+
+/// State of the connection spinner drawn by [`OutputFormatter::connect_progress`]
+struct Spinner {
+    /// Phase being waited on; `None` once ready or failed
+    phase: Option<ConnectPhase>,
+    /// Start of the current attempt
+    since: Instant,
+    drawn: bool,
+    ticking: bool,
+}
+
+impl Default for Spinner {
+    fn default() -> Self {
+        Self {
+            phase: None,
+            since: Instant::now(),
+            drawn: false,
+            ticking: false,
+        }
+    }
+}
+
+impl Spinner {
+    const FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+    /// How long a connection may take before the spinner appears
+    const DELAY: Duration = Duration::from_millis(300);
+    const INTERVAL: Duration = Duration::from_millis(100);
+
+    /// Redraw the spinner until the connection is ready or fails
+    async fn tick(spinner: Arc<Mutex<Spinner>>, address: String) {
+        for frame in Self::FRAMES.iter().cycle() {
+            tokio::time::sleep(Self::INTERVAL).await;
+            let mut state = spinner.lock().unwrap_or_else(|e| e.into_inner());
+            let Some(phase) = state.phase else {
+                state.ticking = false;
+                return;
+            };
+            let elapsed = state.since.elapsed();
+            if elapsed < Self::DELAY {
+                continue;
+            }
+            let message = match phase {
+                ConnectPhase::Resolving => t!("connect-resolving", address = address.as_str()),
+                ConnectPhase::Connecting => t!("connect-connecting", address = address.as_str()),
+                _ => t!("connect-authenticating", address = address.as_str()),
+            };
+            eprint!("\r\x1b[2K{} {} ({}s)", frame, message, elapsed.as_secs());
+            state.drawn = true;
+        }
+    }
+}
+
+/// Timestamp styles for `--timestamps`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum TimestampStyle {
+    /// Wall-clock time in RFC 3339 format, UTC (default)
+    Rfc3339,
+    /// Seconds since the invocation started
+    Relative,
 }
 
 /// Available commands
@@ -91,9 +543,19 @@ pub enum Commands {
         /// The command to execute on the server
         #[arg(
             help = "Command to execute (e.g., 'list', 'time set day')",
-            value_name = "COMMAND"
+            value_name = "COMMAND",
+            required_unless_present = "stdin_heredoc"
         )]
-        command: String,
+        command: Option<String>,
+
+        /// Read the command from stdin, joining its lines
+        #[arg(
+            long = "stdin-heredoc",
+            help = "Read one command from stdin, joining its lines with spaces (e.g. a heredoc with indented JSON)",
+            action = clap::ArgAction::SetTrue,
+            conflicts_with = "command"
+        )]
+        stdin_heredoc: bool,
 
         /// Show command execution time
         #[arg(
@@ -102,6 +564,56 @@ pub enum Commands {
             action = clap::ArgAction::SetTrue
         )]
         show_time: bool,
+
+        /// Connect directly even if a daemon is running
+        #[arg(
+            long = "no-daemon",
+            help = "Connect directly instead of routing through a running daemon",
+            action = clap::ArgAction::SetTrue
+        )]
+        no_daemon: bool,
+
+        /// Queue priority in a busy daemon
+        #[arg(
+            long = "priority",
+            help = "Queue priority when routed through a busy daemon [default: high for stop, else normal]",
+            value_name = "PRIORITY",
+            conflicts_with = "no_daemon"
+        )]
+        priority: Option<Priority>,
+
+        /// Check the command against a command tree before sending it
+        #[arg(
+            long = "validate",
+            help = "Warn about unknown commands and wrong arguments before sending",
+            action = clap::ArgAction::SetTrue
+        )]
+        validate: bool,
+
+        /// Send the command without the profile's prefix
+        #[arg(
+            long = "no-prefix",
+            help = "Send the command as given, without the --command-prefix or profile prefix",
+            action = clap::ArgAction::SetTrue
+        )]
+        no_prefix: bool,
+
+        /// Fail if the response reports an error
+        #[arg(
+            long = "fail-on-error",
+            help = "Exit with an error if the response reports one, e.g. \"Unknown or incomplete command\"",
+            action = clap::ArgAction::SetTrue
+        )]
+        fail_on_error: bool,
+
+        /// Command tree to validate against
+        #[arg(
+            long = "schema",
+            help = "Command tree exported by the server's data generator (reports/commands.json) [default: built-in]",
+            value_name = "FILE",
+            requires = "validate"
+        )]
+        schema: Option<PathBuf>,
     },
 
     /// Start an interactive RCON session
@@ -130,6 +642,22 @@ pub enum Commands {
             help = "Maximum number of history entries"
         )]
         history_size: usize,
+
+        /// Send commands without the profile's prefix
+        #[arg(
+            long = "no-prefix",
+            help = "Send commands as typed, without the --command-prefix or profile prefix",
+            action = clap::ArgAction::SetTrue
+        )]
+        no_prefix: bool,
+
+        /// Command tree to complete from
+        #[arg(
+            long = "schema",
+            help = "Command tree exported by the server's data generator (reports/commands.json) [default: built-in]",
+            value_name = "FILE"
+        )]
+        schema: Option<PathBuf>,
     },
 
     /// Test connection to the RCON server
@@ -151,19 +679,53 @@ pub enum Commands {
             help = "Interval between pings in seconds"
         )]
         interval: u64,
-    },
 
-    /// Show server information
-    Info {
-        /// Include detailed server stats
+        /// Ping every configured profile concurrently
         #[arg(
-            long = "detailed",
-            help = "Include detailed server statistics",
+            long = "all-profiles",
+            help = "Ping every configured profile concurrently and compare latencies",
             action = clap::ArgAction::SetTrue
         )]
+        all_profiles: bool,
+
+        /// Append every attempt to a latency history file
+        #[arg(
+            long = "record",
+            help = "Append every attempt to a CSV history file for `report` [default: <data dir>/rcon-cli/latency.csv]",
+            value_name = "FILE",
+            num_args = 0..=1,
+            require_equals = true
+        )]
+        record: Option<Option<PathBuf>>,
+
+        /// Write measurements as InfluxDB line protocol
+        #[arg(
+            long = "metrics-out",
+            help = "Write metrics as InfluxDB line protocol to influx://[user:pass@]host[:8086]/db, or '-' for stdout",
+            value_name = "TARGET",
+            value_parser = parse_metrics_target
+        )]
+        metrics_out: Option<MetricsTarget>,
+    },
+
+    /// Show server information
+    Info {
+        /// Deprecated: also print the `world` report
+        #[arg(long = "detailed", hide = true, action = clap::ArgAction::SetTrue)]
         detailed: bool,
     },
 
+    /// Summarize the world: seed, difficulty, border, day, time, and weather
+    ///
+    /// The queries are pipelined over one connection. Weather is read with
+    /// inline predicates, which need Minecraft 1.20.5 or later; values a
+    /// server doesn't report are shown as "-".
+    World {
+        /// Print the server's responses as they are, without parsing them
+        #[arg(long = "raw", action = clap::ArgAction::SetTrue)]
+        raw: bool,
+    },
+
     /// List online players
     Players {
         /// Show player UUIDs
@@ -173,163 +735,2809 @@ pub enum Commands {
             action = clap::ArgAction::SetTrue
         )]
         show_uuids: bool,
+
+        /// Print the server's response as is, without parsing it
+        #[arg(long = "raw", action = clap::ArgAction::SetTrue)]
+        raw: bool,
     },
-}
 
-impl Cli {
-    /// Parse the address string and convert localhost to 127.0.0.1
-    pub fn parse_address(&self) -> Result<SocketAddr, String> {
-        let address_str = if self.address.starts_with("localhost:") {
-            self.address.replace("localhost:", "127.0.0.1:")
-        } else if self.address == "localhost" {
-            "127.0.0.1".to_string()
-        } else {
-            self.address.clone()
-        };
+    /// List banned players or IP addresses
+    Banlist {
+        /// List banned IP addresses instead of players
+        #[arg(
+            long = "ips",
+            help = "List banned IP addresses instead of players",
+            action = clap::ArgAction::SetTrue
+        )]
+        ips: bool,
 
-        address_str
-            .parse::<SocketAddr>()
-            .map_err(|e| format!("Invalid address format '{}': {}", self.address, e))
-    }
+        /// Print the server's response as is, without parsing it
+        #[arg(long = "raw", action = clap::ArgAction::SetTrue)]
+        raw: bool,
+    },
 
-    /// Get the connection timeout as a Duration
-    pub fn timeout_duration(&self) -> Duration {
-        Duration::from_secs(self.timeout)
-    }
+    /// List the items in a player's inventory
+    ///
+    /// Reads the player's Inventory data and prints each stack's slot, item,
+    /// count, and enchantments. Armor and offhand are included before
+    /// Minecraft 1.21.5, which moved them out of the inventory.
+    Inventory {
+        /// Player name, UUID, or a selector matching one player
+        #[arg(value_name = "PLAYER", value_parser = parse_entity)]
+        player: String,
 
-    /// Get the appropriate logging level based on verbosity
-    pub fn log_level(&self) -> &'static str {
-        match self.verbose {
-            0 => "warn",
-            1 => "info",
-            2 => "debug",
-            _ => "trace",
-        }
-    }
+        /// Print the server's response as is, without parsing it
+        #[arg(long = "raw", action = clap::ArgAction::SetTrue)]
+        raw: bool,
+    },
 
-    /// Check if colors should be used for output
-    pub fn use_colors(&self) -> bool {
-        !self.no_color && atty::is(atty::Stream::Stdout)
-    }
+    /// Give a player items, checking the item ID first
+    ///
+    /// The item is validated against the built-in registry for the server's
+    /// Minecraft version (detected with `version`, or --mc-version) and the
+    /// command is assembled in that version's syntax.
+    Give {
+        /// Player name or selector
+        #[arg(value_name = "PLAYER")]
+        player: String,
 
-    /// Validate the CLI arguments
-    pub fn validate(&self) -> Result<(), String> {
-        // Validate timeout
-        if self.timeout == 0 {
-            return Err("Timeout must be greater than 0".to_string());
-        }
+        /// Item ID, e.g. diamond_sword or minecraft:oak_log
+        #[arg(value_name = "ITEM")]
+        item: String,
 
-        // Validate password is not empty
-        if self.password.is_empty() {
-            return Err("Password cannot be empty".to_string());
-        }
+        /// Number of items
+        #[arg(
+            value_name = "COUNT",
+            default_value = "1",
+            value_parser = clap::value_parser!(u32).range(1..=6400)
+        )]
+        count: u32,
 
-        // Command-specific validation
-        match &self.command {
-            Commands::Exec { command, .. } => {
-                if command.trim().is_empty() {
-                    return Err("Command cannot be empty".to_string());
-                }
-            }
-            Commands::Interactive { history_size, .. } => {
-                if *history_size == 0 {
-                    return Err("History size must be greater than 0".to_string());
-                }
-            }
-            Commands::Ping {
-                count, interval, ..
-            } => {
-                if *count == 0 {
-                    return Err("Ping count must be greater than 0".to_string());
-                }
-                if *interval == 0 {
-                    return Err("Ping interval must be greater than 0".to_string());
-                }
-            }
-            _ => {}
-        }
+        /// Item data: a JSON object (NBT, or components since 1.20.5) or raw SNBT
+        #[arg(long = "nbt", value_name = "FILE")]
+        nbt: Option<PathBuf>,
 
-        Ok(())
-    }
-}
+        /// Minecraft version of the server when it can't be detected
+        #[arg(long = "mc-version", value_name = "VERSION")]
+        mc_version: Option<McVersion>,
 
-/// Helper struct for formatting command output
-pub struct OutputFormatter {
-    format: OutputFormat,
-    use_colors: bool,
-}
+        /// Send the command even if the item ID isn't known
+        #[arg(long = "force", action = clap::ArgAction::SetTrue)]
+        force: bool,
+    },
 
-impl OutputFormatter {
-    pub fn new(format: OutputFormat, use_colors: bool) -> Self {
-        Self { format, use_colors }
-    }
+    /// Run a command as other entities, via `execute as ... run ...`
+    ///
+    /// Separate words after -- are joined, quoting those containing
+    /// spaces; a single word is sent as it is.
+    #[command(name = "as")]
+    As {
+        /// Player name, UUID, or selector to run the command as
+        #[arg(value_name = "SELECTOR", value_parser = parse_entity)]
+        selector: String,
 
-    pub fn format_response(&self, response: &str) -> String {
-        match self.format {
-            OutputFormat::Text => {
-                if self.use_colors {
-                    self.colorize_response(response)
-                } else {
-                    response.to_string()
-                }
-            }
-            OutputFormat::Json => serde_json::json!({
-                "response": response,
-                "timestamp": chrono::Utc::now().to_rfc3339()
-            })
-            .to_string(),
-        }
-    }
+        /// Command to run, e.g. -- tag @s add vip
+        #[arg(
+            value_name = "COMMAND",
+            required = true,
+            trailing_var_arg = true,
+            allow_hyphen_values = true
+        )]
+        command: Vec<String>,
 
-    pub fn format_error(&self, error: &str) -> String {
-        match self.format {
-            OutputFormat::Text => {
+        /// Show command execution time
+        #[arg(long = "time", action = clap::ArgAction::SetTrue)]
+        show_time: bool,
+    },
+
+    /// Teleport entities to another entity or to coordinates
+    ///
+    /// Selectors and coordinates are checked before sending. Coordinates
+    /// may be relative (~) or local (^); negative values need no escaping.
+    #[command(allow_negative_numbers = true)]
+    Tp {
+        /// Player name, UUID, or selector to teleport
+        #[arg(value_name = "WHO", value_parser = parse_entity)]
+        who: String,
+
+        /// Target entity, or x y z coordinates
+        #[arg(value_name = "TARGET|X Y Z", num_args = 1..=3, required = true)]
+        destination: Vec<String>,
+
+        /// Dimension of the coordinates, e.g. nether or minecraft:the_end
+        #[arg(long = "dimension", value_name = "DIMENSION", value_parser = parse_dimension)]
+        dimension: Option<String>,
+
+        /// Refuse unless the destination chunk is force-loaded
+        #[arg(long = "confirm-loaded", action = clap::ArgAction::SetTrue)]
+        confirm_loaded: bool,
+    },
+
+    /// Give or clear status effects
+    Effect {
+        /// Print the server's responses as they are, without parsing them
+        #[arg(long = "raw", global = true, action = clap::ArgAction::SetTrue)]
+        raw: bool,
+
+        #[command(subcommand)]
+        action: EffectCommand,
+    },
+
+    /// Add, set, or query player experience
+    Xp {
+        /// Print the server's responses as they are, without parsing them
+        #[arg(long = "raw", global = true, action = clap::ArgAction::SetTrue)]
+        raw: bool,
+
+        #[command(subcommand)]
+        action: XpCommand,
+    },
+
+    /// Grant or revoke advancements
+    ///
+    /// Partial vanilla IDs are completed, e.g. mine_diamond becomes
+    /// minecraft:story/mine_diamond; data pack IDs need their namespace.
+    Advancement {
+        /// Whether to grant or revoke
+        #[arg(value_name = "ACTION")]
+        action: AdvancementAction,
+
+        /// Player name, UUID, or selector
+        #[arg(value_name = "PLAYER", value_parser = parse_entity)]
+        player: String,
+
+        /// Advancement ID, or "everything"
+        #[arg(value_name = "ADVANCEMENT")]
+        advancement: String,
+
+        /// Print the command instead of sending it
+        #[arg(long = "dry-run", action = clap::ArgAction::SetTrue)]
+        dry_run: bool,
+    },
+
+    /// Print the world seed as a bare number
+    Seed {
+        /// Print the server's response as is, without parsing it
+        #[arg(long = "raw", action = clap::ArgAction::SetTrue)]
+        raw: bool,
+    },
+
+    /// Show ticks per second, using whichever command the server has
+    ///
+    /// Bukkit's `tps` on Paper and Spigot, `forge tps` or `neoforge tps` on
+    /// Forge, and `tick query` on vanilla and Fabric servers since 1.20.3.
+    Tps {
+        /// Print the server's response as is, without parsing it
+        #[arg(long = "raw", action = clap::ArgAction::SetTrue)]
+        raw: bool,
+    },
+
+    /// Set or query the time of day
+    Time {
+        /// Print the server's responses as they are, without parsing them
+        #[arg(long = "raw", global = true, action = clap::ArgAction::SetTrue)]
+        raw: bool,
+
+        #[command(subcommand)]
+        action: TimeCommand,
+    },
+
+    /// Change the weather
+    Weather {
+        /// Weather to switch to
+        #[arg(value_name = "WEATHER")]
+        kind: WeatherKind,
+
+        /// How long the weather lasts, e.g. 10m [default: random]
+        #[arg(
+            long = "duration",
+            value_name = "DURATION",
+            value_parser = parse_duration
+        )]
+        duration: Option<Duration>,
+
+        /// Minecraft version of the server when it can't be detected
+        #[arg(long = "mc-version", value_name = "VERSION", requires = "duration")]
+        mc_version: Option<McVersion>,
+    },
+
+    /// Force-load chunks, or list and query force-loaded chunks
+    ///
+    /// Areas given with --from and --to are split into as many commands as
+    /// the server needs (at most 256 chunks each, one per chunk for query)
+    /// and the results are added up.
+    Forceload {
+        /// Print the server's responses as they are, without parsing them
+        #[arg(long = "raw", global = true, action = clap::ArgAction::SetTrue)]
+        raw: bool,
+
+        #[command(subcommand)]
+        action: ForceloadCommand,
+    },
+
+    /// Manage the whitelist
+    Whitelist {
+        #[command(subcommand)]
+        action: WhitelistCommand,
+    },
+
+    /// Manage LuckPerms users and groups
+    ///
+    /// Wraps `lp user` and `lp group`. `info` and `check` parse the
+    /// responses into records; other actions print them without LuckPerms'
+    /// `[LP]` prefix.
+    Perms {
+        /// Print the server's responses as they are, without parsing them
+        #[arg(long = "raw", global = true, action = clap::ArgAction::SetTrue)]
+        raw: bool,
+
+        #[command(subcommand)]
+        holder: PermsHolder,
+    },
+
+    /// Give, take, and check money in an Essentials-style economy
+    ///
+    /// Wraps `eco give`, `eco take`, and `balance`, printing the balances
+    /// as numbers. With --input, every row of a CSV file is processed over
+    /// one connection; failed rows are listed at the end and the exit status
+    /// is 1 if any failed.
+    Eco {
+        /// Print the server's responses as they are, without parsing them
+        #[arg(long = "raw", global = true, action = clap::ArgAction::SetTrue)]
+        raw: bool,
+
+        #[command(subcommand)]
+        action: EcoCommand,
+    },
+
+    /// Edit the NBT data of entities, block entities, and command storage
+    Data {
+        #[command(subcommand)]
+        action: DataCommand,
+    },
+
+    /// Run a templated command for every row of a CSV file
+    ///
+    /// Placeholders such as {name} refer to header columns ({0}, {1}, ...
+    /// with --no-header). Rows are sent over --concurrency connections and
+    /// failures are listed at the end; the exit status is 1 if any failed.
+    Bulk {
+        /// Command template, e.g. "whitelist add {name}"
+        #[arg(long = "template", value_name = "TEMPLATE")]
+        template: String,
+
+        /// CSV file with one row per command ('-' reads stdin)
+        #[arg(long = "input", value_name = "FILE")]
+        input: PathBuf,
+
+        /// The first row is data, not column names
+        #[arg(long = "no-header", action = clap::ArgAction::SetTrue)]
+        no_header: bool,
+
+        /// Number of connections sending commands in parallel
+        #[arg(
+            short = 'j',
+            long = "concurrency",
+            default_value = "1",
+            value_name = "N",
+            value_parser = clap::value_parser!(u32).range(1..=64)
+        )]
+        concurrency: u32,
+
+        /// Send at most this many commands per second in total
+        #[arg(long = "rate", value_name = "PER_SECOND", value_parser = parse_rate)]
+        rate: Option<f64>,
+    },
+
+    /// Inspect the server's data packs, plugins, and mods
+    Addons {
+        /// Print the server's responses as they are, without parsing them
+        #[arg(long = "raw", global = true, action = clap::ArgAction::SetTrue)]
+        raw: bool,
+
+        #[command(subcommand)]
+        action: AddonsCommand,
+    },
+
+    /// Run commands across a proxy network's servers
+    ///
+    /// Networks are configured as `[networks.<name>]` with the profiles of
+    /// their backend servers and, optionally, of the proxy.
+    Net {
+        /// Network from the config file [default: the only one]
+        #[arg(long = "network", value_name = "NAME")]
+        network: Option<String>,
+
+        #[command(subcommand)]
+        action: NetCommand,
+    },
+
+    /// List the server profiles from the config file
+    Profiles,
+
+    /// Summarize availability and latency from `ping --record` history
+    ///
+    /// Reports, per server, the share of successful pings, latency
+    /// percentiles, and the longest outage.
+    Report {
+        /// Only include samples from this far back, e.g. 24h or 7d
+        #[arg(long = "since", value_name = "DURATION", value_parser = parse_duration)]
+        since: Option<Duration>,
+
+        /// History file written by `ping --record`
+        #[arg(long = "history", value_name = "FILE")]
+        history: Option<PathBuf>,
+
+        /// Only report this profile or address
+        #[arg(long = "target", value_name = "NAME")]
+        target: Option<String>,
+    },
+
+    /// Post a Grafana annotation, e.g. when a backup script finishes
+    ///
+    /// Uses the `[grafana]` section of the config file. The annotation is
+    /// tagged with the profile given by --profile, if any.
+    Annotate {
+        /// Annotation text
+        #[arg(value_name = "TEXT")]
+        text: String,
+
+        /// Additional tag; can be repeated
+        #[arg(long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+    },
+
+    /// Probe the server and print a compatibility report
+    ///
+    /// Detects the server software, measures the longest accepted command,
+    /// and checks that long responses arrive complete. Useful when commands
+    /// or responses get truncated.
+    Doctor {
+        /// Longest command to try when probing the payload limit
+        #[arg(
+            long = "payload-limit",
+            default_value_t = crate::doctor::DEFAULT_PAYLOAD_LIMIT,
+            help = "Longest command in bytes to try when probing the payload limit",
+            value_name = "BYTES"
+        )]
+        payload_limit: usize,
+
+        /// Command with long output used to test fragmentation
+        #[arg(
+            long = "fragmentation-command",
+            default_value = crate::doctor::DEFAULT_FRAGMENTATION_COMMAND,
+            help = "Command with long output used to test response fragmentation",
+            value_name = "COMMAND"
+        )]
+        fragmentation_command: String,
+    },
+
+    /// Track player joins and leaves by polling the player list
+    ///
+    /// Prints join and leave events as they are detected and a summary of
+    /// session durations when watching ends.
+    Sessions {
+        /// Keep polling until interrupted (or --for elapses)
+        #[arg(
+            long = "watch",
+            help = "Keep polling and stream events until interrupted",
+            action = clap::ArgAction::SetTrue
+        )]
+        watch: bool,
+
+        /// Time between polls
+        #[arg(
+            short = 'i',
+            long = "interval",
+            default_value = "10s",
+            help = "Time between player list polls, e.g. 10s or 1m",
+            value_name = "DURATION",
+            value_parser = parse_duration
+        )]
+        interval: Duration,
+
+        /// Stop watching after this long
+        #[arg(
+            long = "for",
+            help = "Stop watching after DURATION and print the summary",
+            value_name = "DURATION",
+            value_parser = parse_duration,
+            requires = "watch"
+        )]
+        watch_for: Option<Duration>,
+
+        /// Write measurements as InfluxDB line protocol
+        #[arg(
+            long = "metrics-out",
+            help = "Write metrics as InfluxDB line protocol to influx://[user:pass@]host[:8086]/db, or '-' for stdout",
+            value_name = "TARGET",
+            value_parser = parse_metrics_target
+        )]
+        metrics_out: Option<MetricsTarget>,
+    },
+
+    /// Watch the player count and act when a threshold holds
+    ///
+    /// Polls the player list and triggers once the condition has held for
+    /// the --for duration, e.g. to hibernate an empty server or page someone
+    /// when players drop off. The alert resolves when the condition stops
+    /// holding and can then trigger again.
+    Alert {
+        /// Condition on players, max, or free (e.g. "players == 0")
+        #[arg(
+            long = "when",
+            help = "Condition such as \"players == 0\" or \"free < 2\" (metrics: players, max, free)",
+            value_name = "CONDITION",
+            value_parser = parse_condition
+        )]
+        when: Condition,
+
+        /// How long the condition must hold before triggering
+        #[arg(
+            long = "for",
+            help = "Trigger only after the condition held for DURATION, e.g. 30m",
+            value_name = "DURATION",
+            value_parser = parse_duration
+        )]
+        hold: Option<Duration>,
+
+        /// Shell command to run when the alert triggers
+        #[arg(long = "exec", value_name = "COMMAND")]
+        exec: Option<String>,
+
+        /// Time between polls
+        #[arg(
+            short = 'i',
+            long = "interval",
+            default_value = "30s",
+            help = "Time between player list polls, e.g. 30s or 1m",
+            value_name = "DURATION",
+            value_parser = parse_duration
+        )]
+        interval: Duration,
+
+        /// Exit after the first trigger
+        #[arg(long = "once", action = clap::ArgAction::SetTrue)]
+        once: bool,
+
+        /// Write measurements as InfluxDB line protocol
+        #[arg(
+            long = "metrics-out",
+            help = "Write metrics as InfluxDB line protocol to influx://[user:pass@]host[:8086]/db, or '-' for stdout",
+            value_name = "TARGET",
+            value_parser = parse_metrics_target
+        )]
+        metrics_out: Option<MetricsTarget>,
+    },
+
+    /// Save the world with `save-all flush`
+    ///
+    /// With --verify the save is repeated until the server confirms it
+    /// finished. The exit status is 2 if the server reports a failure or an
+    /// unexpected response and 124 if no confirmation arrives in time, so
+    /// the command can guard backup scripts.
+    Save {
+        /// Wait until the server confirms the save
+        #[arg(long = "verify", action = clap::ArgAction::SetTrue)]
+        verify: bool,
+
+        /// How long to wait for confirmation
+        #[arg(
+            long = "timeout",
+            default_value = "60s",
+            help = "Give up waiting for confirmation after DURATION",
+            value_name = "DURATION",
+            value_parser = parse_duration,
+            requires = "verify"
+        )]
+        timeout: Duration,
+
+        /// Delay before asking again when the save is still in progress
+        #[arg(
+            short = 'i',
+            long = "interval",
+            default_value = "2s",
+            help = "Delay between save attempts while unconfirmed",
+            value_name = "DURATION",
+            value_parser = parse_duration,
+            requires = "verify"
+        )]
+        interval: Duration,
+    },
+
+    /// Stop the server after it has been empty for a while
+    ///
+    /// Once nobody has been online for --idle minus --warn, a countdown is
+    /// broadcast in game; if a player joins before it ends the shutdown is
+    /// cancelled, otherwise the stop command is sent and autostop exits.
+    Autostop {
+        /// How long the server must be empty before it is stopped
+        #[arg(
+            long = "idle",
+            default_value = "20m",
+            help = "Stop after the server was empty for DURATION, e.g. 20m",
+            value_name = "DURATION",
+            value_parser = parse_duration
+        )]
+        idle: Duration,
+
+        /// Length of the broadcast countdown at the end of the idle time
+        #[arg(
+            long = "warn",
+            default_value = "5m",
+            help = "Broadcast shutdown warnings during the last DURATION of --idle",
+            value_name = "DURATION",
+            value_parser = parse_duration
+        )]
+        warn: Duration,
+
+        /// Time between polls
+        #[arg(
+            short = 'i',
+            long = "interval",
+            default_value = "30s",
+            help = "Time between player list polls, e.g. 30s or 1m",
+            value_name = "DURATION",
+            value_parser = parse_duration
+        )]
+        interval: Duration,
+
+        /// Warning broadcast in game; {remaining} is replaced by the time left
+        #[arg(
+            long = "message",
+            default_value = "Server stopping in {remaining} because nobody is online",
+            value_name = "TEXT"
+        )]
+        message: String,
+
+        /// Command that stops the server
+        #[arg(long = "stop-command", default_value = "stop", value_name = "COMMAND")]
+        stop_command: String,
+    },
+
+    /// Stop the server for a restart once few players are online
+    ///
+    /// With --when-empty or --max-players, waits until at most that many
+    /// players are online, or until --max-wait has passed. Players still
+    /// online then see a countdown, after which the world is saved and the
+    /// stop command is sent. The exit status tells a wrapper script whether
+    /// to relaunch the server: 0 once it was stopped, 2 if saving failed,
+    /// 3 if cancelled, and 1 on other errors.
+    #[command(group(ArgGroup::new("threshold").args(["when_empty", "max_players"])))]
+    Restart {
+        /// Wait until nobody is online
+        #[arg(long = "when-empty", help = "Wait until nobody is online")]
+        when_empty: bool,
+
+        /// Wait until at most this many players are online
+        #[arg(
+            long = "max-players",
+            help = "Wait until at most N players are online",
+            value_name = "N"
+        )]
+        max_players: Option<u32>,
+
+        /// Restart anyway once this much time has passed
+        #[arg(
+            long = "max-wait",
+            help = "Stop waiting for players after DURATION, e.g. 2h",
+            value_name = "DURATION",
+            value_parser = parse_duration,
+            requires = "threshold"
+        )]
+        max_wait: Option<Duration>,
+
+        /// Points of the countdown at which warnings are broadcast
+        #[arg(
+            long = "warn",
+            default_value = "15m,5m,1m",
+            help = "Broadcast warnings when these durations remain, e.g. 15m,5m,1m",
+            value_name = "DURATIONS",
+            value_parser = parse_duration,
+            value_delimiter = ','
+        )]
+        warn: Vec<Duration>,
+
+        /// Time between polls
+        #[arg(
+            short = 'i',
+            long = "interval",
+            default_value = "30s",
+            help = "Time between player list polls, e.g. 30s or 1m",
+            value_name = "DURATION",
+            value_parser = parse_duration
+        )]
+        interval: Duration,
+
+        /// Warning broadcast in game; {remaining} is replaced by the time left
+        #[arg(
+            long = "message",
+            default_value = "Server restarting in {remaining}",
+            value_name = "TEXT"
+        )]
+        message: String,
+
+        /// Command that stops the server
+        #[arg(long = "stop-command", default_value = "stop", value_name = "COMMAND")]
+        stop_command: String,
+    },
+
+    /// Export or check the translation catalogs
+    ///
+    /// Without options, prints the English reference catalog as a template
+    /// for new translations.
+    Messages {
+        /// Print the catalog of this locale instead of the English one
+        #[arg(long = "locale", value_name = "LANG", conflicts_with = "check")]
+        locale: Option<String>,
+
+        /// List messages of the reference catalog missing from a locale
+        #[arg(long = "check", value_name = "LANG")]
+        check: Option<String>,
+
+        /// List the available locales
+        #[arg(
+            long = "list",
+            conflicts_with_all = ["locale", "check"],
+            action = clap::ArgAction::SetTrue
+        )]
+        list: bool,
+    },
+
+    /// Wait until the server accepts RCON connections
+    ///
+    /// A server that keeps accepting a connection and crashing right after
+    /// ends the wait early with exit status 4, so deployments can abort
+    /// instead of waiting out --timeout.
+    Wait {
+        /// Maximum time to wait in seconds
+        #[arg(
+            long = "timeout",
+            default_value = "300",
+            help = "Maximum time to wait in seconds",
+            value_name = "SECONDS"
+        )]
+        timeout: u64,
+
+        /// Interval between connection attempts in seconds
+        #[arg(
+            short = 'i',
+            long = "interval",
+            default_value = "5",
+            help = "Interval between connection attempts in seconds",
+            value_name = "SECONDS"
+        )]
+        interval: u64,
+
+        /// Crashes within --crash-window after which waiting stops early
+        #[arg(
+            long = "crash-limit",
+            default_value = "3",
+            help = "Give up after the server crashed N times within --crash-window (0 never does)",
+            value_name = "N"
+        )]
+        crash_limit: u32,
+
+        /// Window for --crash-limit in seconds
+        #[arg(
+            long = "crash-window",
+            default_value = "300",
+            help = "Window in seconds for --crash-limit",
+            value_name = "SECONDS"
+        )]
+        crash_window: u64,
+    },
+
+    /// Check scripts and playbooks without connecting to a server
+    ///
+    /// Reports unknown commands and arguments, malformed target selectors,
+    /// commands too long for the dialect, and commands outside the policy
+    /// (`--read-only`, the profile's `read_only`, or `--allow`); exits 1 if
+    /// any are found. Files ending in `.toml` are read as playbooks.
+    Lint {
+        /// Scripts with one command per line ('#' starts a comment), or
+        /// playbooks
+        #[arg(
+            value_name = "SCRIPT",
+            required = true,
+            help = "Script files with one command per line, or playbooks (.toml)"
+        )]
+        scripts: Vec<PathBuf>,
+
+        /// Command tree to check against
+        #[arg(
+            long = "schema",
+            help = "Command tree exported by the server's data generator (reports/commands.json) [default: built-in]",
+            value_name = "FILE"
+        )]
+        schema: Option<PathBuf>,
+
+        /// Skip the command tree check
+        #[arg(
+            long = "no-schema",
+            help = "Don't check commands against a command tree, e.g. for scripts of plugin commands",
+            conflicts_with = "schema",
+            action = clap::ArgAction::SetTrue
+        )]
+        no_schema: bool,
+
+        /// Commands the scripts may use
+        #[arg(
+            long = "allow",
+            help = "Only allow commands starting with these words (repeatable); with --read-only, queries are allowed too",
+            value_name = "COMMAND"
+        )]
+        allow: Vec<String>,
+    },
+
+    /// Run a subcommand defined by a WebAssembly plugin
+    ///
+    /// Without a name, lists the subcommands of the plugins in the config
+    /// file. The plugin turns the arguments into commands, which are sent
+    /// in order. Needs a build with the `wasm` feature.
+    Plugin {
+        /// Subcommand defined by a plugin
+        #[arg(value_name = "NAME", help = "Subcommand defined by a plugin")]
+        name: Option<String>,
+
+        /// Arguments passed to the plugin
+        #[arg(
+            value_name = "ARGS",
+            trailing_var_arg = true,
+            allow_hyphen_values = true,
+            help = "Arguments passed to the plugin"
+        )]
+        args: Vec<String>,
+    },
+
+    /// Run a Rhai script against the server
+    ///
+    /// Scripts send commands with `rcon.execute(command)` and get the
+    /// response parsers, `sleep`, and JSON helpers; see the `scripting`
+    /// module. A value the script ends with is printed.
+    Eval {
+        /// Rhai script
+        #[arg(value_name = "SCRIPT", help = "Rhai script to run")]
+        script: PathBuf,
+
+        /// Arguments passed to the script
+        #[arg(
+            value_name = "ARGS",
+            trailing_var_arg = true,
+            allow_hyphen_values = true,
+            help = "Arguments available to the script as ARGS"
+        )]
+        args: Vec<String>,
+    },
+
+    /// Wait until the server is ready, then run a script of commands
+    ///
+    /// Stops early with exit status 4 if the server is crash looping, like
+    /// `wait`.
+    OnReady {
+        /// Script with one command per line ('#' starts a comment)
+        #[arg(
+            short = 's',
+            long = "script",
+            help = "Script file with one command per line",
+            value_name = "FILE"
+        )]
+        script: PathBuf,
+
+        /// Maximum time to wait in seconds
+        #[arg(
+            long = "timeout",
+            default_value = "300",
+            help = "Maximum time to wait for the server in seconds",
+            value_name = "SECONDS"
+        )]
+        timeout: u64,
+
+        /// Interval between connection attempts in seconds
+        #[arg(
+            short = 'i',
+            long = "interval",
+            default_value = "5",
+            help = "Interval between connection attempts in seconds",
+            value_name = "SECONDS"
+        )]
+        interval: u64,
+
+        /// Crashes within --crash-window after which waiting stops early
+        #[arg(
+            long = "crash-limit",
+            default_value = "3",
+            help = "Give up after the server crashed N times within --crash-window (0 never does)",
+            value_name = "N"
+        )]
+        crash_limit: u32,
+
+        /// Window for --crash-limit in seconds
+        #[arg(
+            long = "crash-window",
+            default_value = "300",
+            help = "Window in seconds for --crash-limit",
+            value_name = "SECONDS"
+        )]
+        crash_window: u64,
+
+        /// Keep running the script after a command fails
+        #[arg(
+            long = "continue-on-error",
+            help = "Keep running the script after a command fails",
+            action = clap::ArgAction::SetTrue
+        )]
+        continue_on_error: bool,
+
+        /// Count responses reporting errors as failed lines
+        #[arg(
+            long = "fail-on-error",
+            help = "Count a line as failed if its response reports an error, e.g. \"Unknown or incomplete command\"",
+            action = clap::ArgAction::SetTrue
+        )]
+        fail_on_error: bool,
+    },
+
+    /// Run the steps of a playbook, independent steps in parallel
+    ///
+    /// Steps run on their `profile` or the selected server once the steps
+    /// they `depends_on` are done. With --dry-run, prints the execution
+    /// plan without connecting. Exits with status 5 if a critical step
+    /// failed, like `on-ready`.
+    Playbook {
+        /// Playbook file
+        #[arg(value_name = "FILE", help = "Playbook file with [[steps]]")]
+        file: PathBuf,
+
+        /// Number of steps running at the same time
+        #[arg(
+            short = 'j',
+            long = "concurrency",
+            default_value = "4",
+            value_name = "N",
+            value_parser = clap::value_parser!(u32).range(1..=64)
+        )]
+        concurrency: u32,
+
+        /// Keep starting steps that don't depend on a failed one
+        #[arg(
+            long = "continue-on-error",
+            help = "Keep running steps that don't depend on a failed step",
+            action = clap::ArgAction::SetTrue
+        )]
+        continue_on_error: bool,
+
+        /// Count responses reporting errors as failed steps
+        #[arg(
+            long = "fail-on-error",
+            help = "Count a step as failed if a response reports an error, e.g. \"Unknown or incomplete command\"",
+            action = clap::ArgAction::SetTrue
+        )]
+        fail_on_error: bool,
+    },
+
+    /// Queue commands on disk and send them once the server is reachable
+    ///
+    /// Commands are kept per server (profile name or address) until a drain
+    /// gets a response to them, so they are sent at least once, possibly
+    /// more than once if a response is lost.
+    Queue {
+        /// Queue file [default: <data dir>/rcon-cli/queue.jsonl]
+        #[arg(long = "file", value_name = "FILE", global = true)]
+        file: Option<PathBuf>,
+
+        #[command(subcommand)]
+        action: QueueCommand,
+    },
+
+    /// Open and close maintenance windows
+    ///
+    /// `start` turns the whitelist on, broadcasts the message, and kicks
+    /// everyone but operators and kept players; `end` turns the whitelist
+    /// off again unless it was on before, and broadcasts the end. Windows
+    /// are recorded in an audit log. Messages may use {server}, {eta}
+    /// (start), and {duration} (end).
+    Maintenance {
+        /// Audit log [default: <data dir>/rcon-cli/maintenance.jsonl]
+        #[arg(long = "log", value_name = "FILE", global = true)]
+        log: Option<PathBuf>,
+
+        #[command(subcommand)]
+        action: MaintenanceCommand,
+    },
+
+    /// Deliver a web shop purchase's reward commands exactly once
+    ///
+    /// The commands are recorded under --key in a local ledger. Running the
+    /// delivery again, e.g. after a timeout or a crash, sends only what the
+    /// server hasn't answered yet; a delivered key sends nothing and exits
+    /// with 0. A command sent without a recorded response is never resent
+    /// on its own: the exit status is 1 until it has been checked on the
+    /// server and resent with --resend-unconfirmed.
+    Deliver {
+        /// Idempotency key, e.g. the order ID
+        #[arg(long = "key", value_name = "KEY")]
+        key: String,
+
+        /// Reward command, e.g. "give {player} diamond 5"
+        #[arg(value_name = "TEMPLATE", required = true)]
+        templates: Vec<String>,
+
+        /// Value of a {placeholder} in the templates (repeatable)
+        #[arg(long = "set", value_name = "NAME=VALUE", value_parser = parse_placeholder)]
+        values: Vec<(String, String)>,
+
+        /// Ledger file [default: <data dir>/rcon-cli/deliveries.jsonl]
+        #[arg(long = "ledger", value_name = "FILE")]
+        ledger: Option<PathBuf>,
+
+        /// Send commands again that were sent without a recorded response
+        #[arg(long = "resend-unconfirmed", action = clap::ArgAction::SetTrue)]
+        resend_unconfirmed: bool,
+    },
+
+    /// Keep authenticated connections open and serve local commands
+    ///
+    /// Connects to the selected target and every configured profile, then
+    /// listens on a Unix socket. `exec` routes through a running daemon
+    /// automatically, skipping the connect and login round trips. Each
+    /// server gets a single connection, which local users take turns on.
+    Daemon {
+        /// Commands each local user may send per server
+        #[arg(
+            long = "rate-limit",
+            help = "Commands each local user may send to a server, e.g. 20/s or 100/5m",
+            value_name = "N/PERIOD"
+        )]
+        rate_limit: Option<RateLimit>,
+
+        /// Reject commands for a server while this many others wait for it
+        #[arg(long = "max-queue", value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+        max_queue: Option<u32>,
+
+        /// Show the queues of a running daemon instead of starting one
+        #[arg(long = "status", action = clap::ArgAction::SetTrue, conflicts_with_all = ["rate_limit", "max_queue"])]
+        status: bool,
+    },
+}
+
+/// Subcommands of `whitelist`
+#[derive(Subcommand)]
+pub enum WhitelistCommand {
+    /// Make the whitelist match a roster file or URL
+    ///
+    /// The roster holds one name per line, or a JSON array of names or of
+    /// objects with a "name" field (like whitelist.json). Only the required
+    /// add and remove commands are sent, and the changes are printed.
+    Sync {
+        /// Roster file
+        #[arg(
+            value_name = "FILE",
+            required_unless_present = "url",
+            conflicts_with = "url"
+        )]
+        file: Option<PathBuf>,
+
+        /// Download the roster over HTTP(S) instead
+        #[arg(long = "url", value_name = "URL")]
+        url: Option<String>,
+
+        /// Only add missing players; keep players not on the roster
+        #[arg(long = "no-remove", action = clap::ArgAction::SetTrue)]
+        no_remove: bool,
+    },
+}
+
+/// Whose permissions `perms` manages
+#[derive(Subcommand)]
+pub enum PermsHolder {
+    /// A player
+    User {
+        /// Player name or UUID
+        #[arg(value_name = "PLAYER", value_parser = parse_player)]
+        name: String,
+
+        #[command(subcommand)]
+        action: PermsAction,
+    },
+
+    /// A group
+    Group {
+        /// Group name
+        #[arg(value_name = "GROUP", value_parser = parse_group)]
+        name: String,
+
+        #[command(subcommand)]
+        action: PermsAction,
+    },
+}
+
+impl PermsHolder {
+    /// The LuckPerms command for the holder, e.g. `lp user Steve`
+    pub fn command(&self) -> String {
+        match self {
+            PermsHolder::User { name, .. } => format!("lp user {}", name),
+            PermsHolder::Group { name, .. } => format!("lp group {}", name),
+        }
+    }
+
+    pub fn action(&self) -> &PermsAction {
+        match self {
+            PermsHolder::User { action, .. } | PermsHolder::Group { action, .. } => action,
+        }
+    }
+}
+
+/// Subcommands of `perms user` and `perms group`
+#[derive(Subcommand)]
+pub enum PermsAction {
+    /// Show parent groups, the primary group, and a user's status
+    Info,
+
+    /// Show what a permission resolves to, with inheritance
+    Check {
+        /// Permission node, e.g. essentials.fly
+        #[arg(value_name = "PERMISSION", value_parser = parse_permission)]
+        permission: String,
+    },
+
+    /// Set a permission
+    Set {
+        /// Permission node, e.g. essentials.fly
+        #[arg(value_name = "PERMISSION", value_parser = parse_permission)]
+        permission: String,
+
+        /// Value to set
+        #[arg(value_name = "VALUE", default_value = "true", action = clap::ArgAction::Set)]
+        value: bool,
+
+        /// Only apply in a context, e.g. server=survival (repeatable)
+        #[arg(long = "context", value_name = "KEY=VALUE", value_parser = parse_context)]
+        contexts: Vec<String>,
+    },
+
+    /// Remove a permission
+    Unset {
+        /// Permission node, e.g. essentials.fly
+        #[arg(value_name = "PERMISSION", value_parser = parse_permission)]
+        permission: String,
+
+        /// Only remove it in a context, e.g. server=survival (repeatable)
+        #[arg(long = "context", value_name = "KEY=VALUE", value_parser = parse_context)]
+        contexts: Vec<String>,
+    },
+
+    /// Add, remove, or replace parent groups
+    Parent {
+        /// What to do with the group; `set` replaces all other parents
+        #[arg(value_name = "ACTION")]
+        action: ParentAction,
+
+        /// Group name
+        #[arg(value_name = "GROUP", value_parser = parse_group)]
+        group: String,
+
+        /// Only apply in a context, e.g. server=survival (repeatable)
+        #[arg(long = "context", value_name = "KEY=VALUE", value_parser = parse_context)]
+        contexts: Vec<String>,
+    },
+}
+
+impl PermsAction {
+    /// The arguments after `lp user|group <name>`
+    pub fn arguments(&self) -> String {
+        let with_contexts = |command: String, contexts: &[String]| {
+            std::iter::once(command)
+                .chain(contexts.iter().cloned())
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        match self {
+            PermsAction::Info => "info".to_string(),
+            PermsAction::Check { permission } => format!("permission check {}", permission),
+            PermsAction::Set {
+                permission,
+                value,
+                contexts,
+            } => with_contexts(format!("permission set {} {}", permission, value), contexts),
+            PermsAction::Unset {
+                permission,
+                contexts,
+            } => with_contexts(format!("permission unset {}", permission), contexts),
+            PermsAction::Parent {
+                action,
+                group,
+                contexts,
+            } => with_contexts(format!("parent {} {}", action.as_str(), group), contexts),
+        }
+    }
+}
+
+/// Subcommands of `maintenance`
+#[derive(Subcommand)]
+pub enum MaintenanceCommand {
+    /// Close the server to players
+    Start {
+        /// Broadcast and kick message, e.g. "Back in {eta}"
+        #[arg(long = "message", value_name = "TEMPLATE")]
+        message: Option<String>,
+
+        /// Expected length of the window, for {eta}
+        #[arg(long = "eta", value_name = "DURATION", value_parser = parse_duration)]
+        eta: Option<Duration>,
+
+        /// The server's ops.json, or any roster, of players not to kick
+        #[arg(long = "ops", value_name = "FILE")]
+        ops: Option<PathBuf>,
+
+        /// Player not to kick (repeatable)
+        #[arg(long = "keep", value_name = "PLAYER")]
+        keep: Vec<String>,
+    },
+
+    /// Open the server again
+    End {
+        /// Broadcast message, e.g. "Back after {duration}"
+        #[arg(long = "message", value_name = "TEMPLATE")]
+        message: Option<String>,
+    },
+}
+
+/// Subcommands of `eco`
+#[derive(Subcommand)]
+pub enum EcoCommand {
+    /// Add money to a player's balance
+    Give {
+        /// Player name
+        #[arg(
+            value_name = "PLAYER",
+            value_parser = parse_player,
+            required_unless_present = "input"
+        )]
+        player: Option<String>,
+
+        /// Amount of money
+        #[arg(
+            value_name = "AMOUNT",
+            value_parser = parse_amount,
+            required_unless_present = "input"
+        )]
+        amount: Option<f64>,
+
+        /// CSV file with `player` and `amount` columns ('-' reads stdin)
+        #[arg(long = "input", value_name = "FILE", conflicts_with_all = ["player", "amount"])]
+        input: Option<PathBuf>,
+
+        /// The first row is data: player, then amount
+        #[arg(long = "no-header", requires = "input", action = clap::ArgAction::SetTrue)]
+        no_header: bool,
+    },
+
+    /// Take money from a player's balance
+    Take {
+        /// Player name
+        #[arg(
+            value_name = "PLAYER",
+            value_parser = parse_player,
+            required_unless_present = "input"
+        )]
+        player: Option<String>,
+
+        /// Amount of money
+        #[arg(
+            value_name = "AMOUNT",
+            value_parser = parse_amount,
+            required_unless_present = "input"
+        )]
+        amount: Option<f64>,
+
+        /// CSV file with `player` and `amount` columns ('-' reads stdin)
+        #[arg(long = "input", value_name = "FILE", conflicts_with_all = ["player", "amount"])]
+        input: Option<PathBuf>,
+
+        /// The first row is data: player, then amount
+        #[arg(long = "no-header", requires = "input", action = clap::ArgAction::SetTrue)]
+        no_header: bool,
+    },
+
+    /// Show a player's balance
+    Balance {
+        /// Player name
+        #[arg(
+            value_name = "PLAYER",
+            value_parser = parse_player,
+            required_unless_present = "input"
+        )]
+        player: Option<String>,
+
+        /// CSV file with a `player` column ('-' reads stdin)
+        #[arg(long = "input", value_name = "FILE", conflicts_with = "player")]
+        input: Option<PathBuf>,
+
+        /// The first row is data: the player
+        #[arg(long = "no-header", requires = "input", action = clap::ArgAction::SetTrue)]
+        no_header: bool,
+    },
+}
+
+impl EcoCommand {
+    /// The CSV file to read, and whether it has a header row
+    pub fn input(&self) -> Option<(&Path, bool)> {
+        match self {
+            EcoCommand::Give {
+                input, no_header, ..
+            }
+            | EcoCommand::Take {
+                input, no_header, ..
+            }
+            | EcoCommand::Balance {
+                input, no_header, ..
+            } => input.as_deref().map(|input| (input, !*no_header)),
+        }
+    }
+
+    /// Whether the command changes balances and so takes an amount
+    pub fn takes_amount(&self) -> bool {
+        !matches!(self, EcoCommand::Balance { .. })
+    }
+
+    /// The command for one player, e.g. `eco give Steve 100`
+    ///
+    /// `amount` is ignored by `balance`.
+    pub fn command(&self, player: &str, amount: f64) -> String {
+        match self {
+            EcoCommand::Give { .. } => format!("eco give {} {}", player, amount),
+            EcoCommand::Take { .. } => format!("eco take {} {}", player, amount),
+            EcoCommand::Balance { .. } => format!("balance {}", player),
+        }
+    }
+}
+
+/// What `perms ... parent` does with a group
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ParentAction {
+    Add,
+    Remove,
+    Set,
+}
+
+impl ParentAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ParentAction::Add => "add",
+            ParentAction::Remove => "remove",
+            ParentAction::Set => "set",
+        }
+    }
+}
+
+/// Subcommands of `data`
+#[derive(Subcommand)]
+pub enum DataCommand {
+    /// Merge a JSON object into a target's NBT
+    ///
+    /// The JSON is converted to SNBT and checked before sending: booleans
+    /// become bytes, whole numbers ints or longs, and other numbers doubles.
+    /// Data too long for one command is split across several `data merge`
+    /// commands by its fields.
+    #[command(allow_negative_numbers = true)]
+    Merge {
+        /// entity <TARGET>, block <X> <Y> <Z>, or storage <ID>
+        #[arg(value_names = ["KIND", "TARGET"], num_args = 2..=4, required = true)]
+        target: Vec<String>,
+
+        /// JSON file with the object to merge ('-' reads stdin)
+        #[arg(long = "json", value_name = "FILE")]
+        json: PathBuf,
+    },
+}
+
+/// Subcommands of `queue`
+#[derive(Subcommand)]
+pub enum QueueCommand {
+    /// Add a command for the server without connecting
+    Push {
+        /// Command to send, e.g. "give Steve diamond 1"
+        #[arg(value_name = "COMMAND")]
+        command: String,
+    },
+
+    /// Send the server's queued commands in order, removing each once answered
+    ///
+    /// Stops at the first command that fails, leaving it and the rest
+    /// queued; the exit status is 1 then.
+    Drain {
+        /// Wait up to this many seconds for the server to accept logins
+        #[arg(long = "wait", value_name = "SECONDS")]
+        wait: Option<u64>,
+    },
+
+    /// List the server's queued commands
+    List {
+        /// List the commands of every server
+        #[arg(long = "all", action = clap::ArgAction::SetTrue)]
+        all: bool,
+    },
+}
+
+/// Subcommands of `addons`
+#[derive(Subcommand)]
+pub enum AddonsCommand {
+    /// List data packs and, depending on the server, plugins or mods
+    ///
+    /// Plugins are listed on Bukkit-based servers and mods on Forge and
+    /// NeoForge; the server software is detected with `version`.
+    List {
+        /// Leave out disabled data packs and plugins
+        #[arg(long = "enabled-only", action = clap::ArgAction::SetTrue)]
+        enabled_only: bool,
+    },
+}
+
+/// Subcommands of `net`
+#[derive(Subcommand)]
+pub enum NetCommand {
+    /// Run a command on backend servers, all of them by default
+    Exec {
+        /// Backend server to run the command on (repeatable)
+        #[arg(
+            short = 's',
+            long = "server",
+            value_name = "NAME",
+            conflicts_with = "proxy"
+        )]
+        servers: Vec<String>,
+
+        /// Run the command on the proxy instead
+        #[arg(long = "proxy", action = clap::ArgAction::SetTrue)]
+        proxy: bool,
+
+        /// Command to execute
+        #[arg(value_name = "COMMAND")]
+        command: String,
+    },
+
+    /// List the players on every backend server
+    Players,
+}
+
+/// Direction of `advancement`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum AdvancementAction {
+    Grant,
+    Revoke,
+}
+
+impl AdvancementAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AdvancementAction::Grant => "grant",
+            AdvancementAction::Revoke => "revoke",
+        }
+    }
+}
+
+/// Subcommands of `forceload`
+#[derive(Subcommand)]
+pub enum ForceloadCommand {
+    /// Keep chunks loaded
+    Add {
+        /// Block position x,z of the chunk, or of one corner of the area
+        #[arg(long = "from", value_name = "X,Z", allow_hyphen_values = true)]
+        from: BlockColumn,
+
+        /// Block position x,z of the opposite corner
+        #[arg(long = "to", value_name = "X,Z", allow_hyphen_values = true)]
+        to: Option<BlockColumn>,
+
+        /// Dimension of the chunks, e.g. nether or minecraft:the_end
+        #[arg(long = "dimension", value_name = "DIMENSION", value_parser = parse_dimension)]
+        dimension: Option<String>,
+    },
+
+    /// Stop keeping chunks loaded
+    Remove {
+        /// Block position x,z of the chunk, or of one corner of the area
+        #[arg(
+            long = "from",
+            value_name = "X,Z",
+            allow_hyphen_values = true,
+            required_unless_present = "all"
+        )]
+        from: Option<BlockColumn>,
+
+        /// Block position x,z of the opposite corner
+        #[arg(
+            long = "to",
+            value_name = "X,Z",
+            allow_hyphen_values = true,
+            requires = "from"
+        )]
+        to: Option<BlockColumn>,
+
+        /// Remove every force-loaded chunk of the dimension
+        #[arg(long = "all", action = clap::ArgAction::SetTrue, conflicts_with = "from")]
+        all: bool,
+
+        /// Dimension of the chunks, e.g. nether or minecraft:the_end
+        #[arg(long = "dimension", value_name = "DIMENSION", value_parser = parse_dimension)]
+        dimension: Option<String>,
+    },
+
+    /// Show whether chunks are force-loaded; lists all of them without --from
+    Query {
+        /// Block position x,z of the chunk, or of one corner of the area
+        #[arg(long = "from", value_name = "X,Z", allow_hyphen_values = true)]
+        from: Option<BlockColumn>,
+
+        /// Block position x,z of the opposite corner
+        #[arg(
+            long = "to",
+            value_name = "X,Z",
+            allow_hyphen_values = true,
+            requires = "from"
+        )]
+        to: Option<BlockColumn>,
+
+        /// Dimension of the chunks, e.g. nether or minecraft:the_end
+        #[arg(long = "dimension", value_name = "DIMENSION", value_parser = parse_dimension)]
+        dimension: Option<String>,
+    },
+}
+
+/// Subcommands of `time`
+#[derive(Subcommand)]
+pub enum TimeCommand {
+    /// Set the time to morning (1000)
+    Day,
+    /// Set the time to noon (6000)
+    Noon,
+    /// Set the time to night (13000)
+    Night,
+    /// Set the time to midnight (18000)
+    Midnight,
+    /// Set the time of day
+    Set {
+        /// Ticks, or a number with d, s, or t (days, seconds, ticks)
+        #[arg(value_name = "TIME", value_parser = parse_time)]
+        time: String,
+    },
+    /// Print the current time as a number
+    Query {
+        /// Which time to print
+        #[arg(value_name = "WHAT", default_value = "daytime")]
+        what: TimeQuery,
+    },
+}
+
+/// What `time query` reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum TimeQuery {
+    /// Ticks since the start of the current day
+    Daytime,
+    /// Ticks since the world was created
+    Gametime,
+    /// Days since the world was created
+    Day,
+}
+
+impl TimeQuery {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TimeQuery::Daytime => "daytime",
+            TimeQuery::Gametime => "gametime",
+            TimeQuery::Day => "day",
+        }
+    }
+}
+
+/// Weather for `weather`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum WeatherKind {
+    Clear,
+    Rain,
+    Thunder,
+}
+
+impl WeatherKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            WeatherKind::Clear => "clear",
+            WeatherKind::Rain => "rain",
+            WeatherKind::Thunder => "thunder",
+        }
+    }
+}
+
+/// Subcommands of `effect`
+#[derive(Subcommand)]
+pub enum EffectCommand {
+    /// Apply a status effect
+    Give {
+        /// Player name, UUID, or selector
+        #[arg(value_name = "TARGET", value_parser = parse_entity)]
+        target: String,
+
+        /// Effect ID, e.g. speed or minecraft:night_vision
+        #[arg(value_name = "EFFECT", value_parser = resource_location)]
+        effect: String,
+
+        /// Duration in seconds, or "infinite" [default: 30]
+        #[arg(value_name = "SECONDS")]
+        duration: Option<EffectDuration>,
+
+        /// Effect level minus one
+        #[arg(
+            long = "amplifier",
+            value_name = "LEVEL",
+            default_value = "0",
+            value_parser = clap::value_parser!(u8)
+        )]
+        amplifier: u8,
+
+        /// Hide the effect's particles
+        #[arg(long = "hide-particles", action = clap::ArgAction::SetTrue)]
+        hide_particles: bool,
+    },
+
+    /// Remove one status effect, or all of them
+    Clear {
+        /// Player name, UUID, or selector
+        #[arg(value_name = "TARGET", value_parser = parse_entity)]
+        target: String,
+
+        /// Effect ID to remove; every effect when omitted
+        #[arg(value_name = "EFFECT", value_parser = resource_location)]
+        effect: Option<String>,
+    },
+}
+
+/// Subcommands of `xp`
+#[derive(Subcommand)]
+pub enum XpCommand {
+    /// Give experience; negative amounts take it away
+    #[command(allow_negative_numbers = true)]
+    Add {
+        /// Player name, UUID, or selector
+        #[arg(value_name = "TARGET", value_parser = parse_entity)]
+        target: String,
+
+        /// Amount of experience
+        #[arg(value_name = "AMOUNT")]
+        amount: i32,
+
+        /// Count levels instead of points
+        #[arg(long = "levels", action = clap::ArgAction::SetTrue)]
+        levels: bool,
+    },
+
+    /// Set experience to an amount
+    #[command(allow_negative_numbers = true)]
+    Set {
+        /// Player name, UUID, or selector
+        #[arg(value_name = "TARGET", value_parser = parse_entity)]
+        target: String,
+
+        /// Amount of experience
+        #[arg(
+            value_name = "AMOUNT",
+            value_parser = clap::value_parser!(u32).range(0..=i32::MAX as i64)
+        )]
+        amount: u32,
+
+        /// Count levels instead of points
+        #[arg(long = "levels", action = clap::ArgAction::SetTrue)]
+        levels: bool,
+    },
+
+    /// Show a player's experience
+    Query {
+        /// Player name, UUID, or selector matching one player
+        #[arg(value_name = "PLAYER", value_parser = parse_entity)]
+        target: String,
+
+        /// Show levels instead of points
+        #[arg(long = "levels", action = clap::ArgAction::SetTrue)]
+        levels: bool,
+    },
+}
+
+/// Default server address when neither a flag nor a profile provides one
+pub const DEFAULT_ADDRESS: &str = "localhost:25575";
+
+/// Default connection timeout in seconds
+pub const DEFAULT_TIMEOUT_SECS: u64 = 5;
+
+/// Connection settings after merging command-line flags with a profile
+#[derive(Debug, Clone)]
+pub struct Target {
+    /// Name of the profile the settings came from, if any
+    pub profile: Option<String>,
+    /// Unresolved server address (`host:port` or `[ipv6]:port`)
+    pub address: String,
+    pub password: String,
+    /// Passwords tried in order if `password` is rejected
+    pub fallback_passwords: Vec<String>,
+    pub timeout: Duration,
+    pub dialect: DialectKind,
+    /// Local address the connection originates from
+    pub bind: Option<SocketAddr>,
+    /// Prepended to commands sent with `exec` and in interactive mode
+    pub prefix: Option<String>,
+    /// Only queries may be sent, see
+    /// [`CommandPolicy::read_only`](crate::policy::CommandPolicy::read_only)
+    pub read_only: bool,
+    /// Trust-on-first-use pinning of the profile's server
+    pub pin: Option<PinPolicy>,
+}
+
+/// Validate an address string, converting localhost to 127.0.0.1
+///
+/// Host names are kept as-is and resolved when connecting; IPv6 literals
+/// must be bracketed when a port is given (`[2001:db8::1]:25575`).
+pub fn parse_address(address: &str) -> Result<String, String> {
+    let address = if address.starts_with("localhost:") {
+        address.replace("localhost:", "127.0.0.1:")
+    } else if address == "localhost" {
+        "127.0.0.1".to_string()
+    } else {
+        address.to_string()
+    };
+
+    split_host_port(&address, crate::DEFAULT_PORT).map_err(|e| e.to_string())?;
+    Ok(address)
+}
+
+/// Parse a duration such as `10s`, `500ms`, `2m`, `1h`, or `7d`; bare numbers are seconds
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split);
+
+    let amount: u64 = amount
+        .parse()
+        .map_err(|_| format!("Invalid duration '{}'", value))?;
+    let duration = match unit.trim() {
+        "ms" => Duration::from_millis(amount),
+        "" | "s" => Duration::from_secs(amount),
+        "m" => Duration::from_secs(amount * 60),
+        "h" => Duration::from_secs(amount * 3600),
+        "d" => Duration::from_secs(amount * 86400),
+        unit => {
+            return Err(format!(
+                "Invalid duration unit '{}' (expected ms, s, m, h, or d)",
+                unit
+            ))
+        }
+    };
+
+    if duration.is_zero() {
+        return Err("Duration must be greater than 0".to_string());
+    }
+    Ok(duration)
+}
+
+/// Parse a `--retry-backoff` factor, at least 1
+pub fn parse_backoff(value: &str) -> Result<f64, String> {
+    match value.trim().parse::<f64>() {
+        Ok(factor) if factor.is_finite() && factor >= 1.0 => Ok(factor),
+        _ => Err(format!(
+            "Invalid backoff factor '{}' (expected a number of at least 1)",
+            value
+        )),
+    }
+}
+
+/// Parse an alert condition such as `players == 0`
+pub fn parse_condition(value: &str) -> Result<Condition, String> {
+    value.parse().map_err(|e: RconError| match e {
+        RconError::InvalidConfig(message) => message,
+        e => e.to_string(),
+    })
+}
+
+/// Parse `-` or an `influx://` URL for `--metrics-out`
+pub fn parse_metrics_target(value: &str) -> Result<MetricsTarget, String> {
+    value.parse().map_err(|e: RconError| match e {
+        RconError::InvalidConfig(message) => message,
+        e => e.to_string(),
+    })
+}
+
+/// Parse a player name, UUID, or target selector
+pub fn parse_entity(value: &str) -> Result<String, String> {
+    validate_entity(value).map(|_| value.to_string())
+}
+
+/// Parse a player name or UUID for LuckPerms, which takes no selectors
+pub fn parse_player(value: &str) -> Result<String, String> {
+    if value.starts_with('@') {
+        return Err(format!(
+            "'{}' is a selector, expected a player name or UUID",
+            value
+        ));
+    }
+    parse_entity(value)
+}
+
+/// Parse a `--set name=value` placeholder value for `deliver`
+pub fn parse_placeholder(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((name, value)) if !name.trim().is_empty() && !name.contains(['{', '}']) => {
+            Ok((name.trim().to_string(), value.to_string()))
+        }
+        _ => Err(format!(
+            "Invalid value '{}' (expected NAME=VALUE, e.g. player=Steve)",
+            value
+        )),
+    }
+}
+
+/// Parse an amount of money for `eco`
+pub fn parse_amount(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(amount) if amount > 0.0 && amount.is_finite() => Ok(amount),
+        _ => Err(format!(
+            "Invalid amount '{}' (expected a positive number)",
+            value
+        )),
+    }
+}
+
+/// Parse a LuckPerms permission node
+pub fn parse_permission(value: &str) -> Result<String, String> {
+    validate_permission(value).map(|_| value.to_string())
+}
+
+/// Parse a LuckPerms group name
+pub fn parse_group(value: &str) -> Result<String, String> {
+    validate_group(value).map(|_| value.to_string())
+}
+
+/// Parse a LuckPerms context such as `server=survival`
+pub fn parse_context(value: &str) -> Result<String, String> {
+    validate_context(value).map(|_| value.to_string())
+}
+
+/// Parse a time of day for `time set`
+pub fn parse_time(value: &str) -> Result<String, String> {
+    validate_time(value).map(|_| value.to_string())
+}
+
+/// Parse a dimension ID, expanding shorthands such as `nether`
+pub fn parse_dimension(value: &str) -> Result<String, String> {
+    normalize_dimension(value)
+}
+
+/// Parse a positive rate such as `20` or `0.5`
+pub fn parse_rate(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(rate) if rate > 0.0 && rate.is_finite() => Ok(rate),
+        _ => Err(format!(
+            "Invalid rate '{}' (expected a positive number)",
+            value
+        )),
+    }
+}
+
+/// Format a duration for humans, e.g. `45s`, `12m 3s`, or `1h 2m 3s`
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    let (hours, minutes, seconds) = (seconds / 3600, seconds % 3600 / 60, seconds % 60);
+    match (hours, minutes) {
+        (0, 0) => format!("{}s", seconds),
+        (0, _) => format!("{}m {}s", minutes, seconds),
+        _ => format!("{}h {}m {}s", hours, minutes, seconds),
+    }
+}
+
+/// Parse a local bind address; a bare IP lets the OS pick the port
+pub fn parse_bind_address(address: &str) -> Result<SocketAddr, String> {
+    if let Ok(ip) = address.parse::<IpAddr>() {
+        return Ok(SocketAddr::new(ip, 0));
+    }
+
+    address
+        .parse::<SocketAddr>()
+        .map_err(|e| format!("Invalid bind address '{}': {}", address, e))
+}
+
+impl Cli {
+    /// The profile selected with `--profile`, with the `--k8s` flags applied
+    ///
+    /// Without `--profile` this is an empty profile.
+    pub fn selected_profile(&self, config: &ConfigFile) -> crate::Result<Profile> {
+        let mut profile = match &self.profile {
+            Some(name) => config.profile(name)?.clone(),
+            None => Profile::default(),
+        };
+
+        if let Some(service) = &self.k8s {
+            profile.k8s = Some(K8sConfig {
+                service: service.clone(),
+                secret: None,
+                api: None,
+            });
+        }
+        if let Some(k8s) = &mut profile.k8s {
+            if let Some(secret) = &self.k8s_secret {
+                k8s.secret = Some(secret.clone());
+            }
+            if let Some(api) = &self.k8s_api {
+                k8s.api = Some(api.clone());
+            }
+        }
+        Ok(profile)
+    }
+
+    /// Resolve the connection target for the current invocation
+    ///
+    /// `profile` comes from [`Cli::selected_profile`]. Explicit flags take
+    /// precedence over it, which in turn takes precedence over the built-in
+    /// defaults.
+    pub fn resolve_target(&self, profile: &Profile) -> Result<Target, String> {
+        let target = build_target(
+            self.profile.clone(),
+            self.address.as_deref().or(profile.address.as_deref()),
+            self.password.as_deref().or(profile.password.as_deref()),
+            self.timeout.or(profile.timeout),
+            self.dialect.or(profile.dialect),
+            self.bind.as_deref().or(profile.bind.as_deref()),
+            self.command_prefix.as_deref().or(profile.prefix.as_deref()),
+        )?;
+        let fallback_passwords = if self.fallback_passwords.is_empty() {
+            profile.fallback_passwords.clone()
+        } else {
+            self.fallback_passwords.clone()
+        };
+        // A read-only profile can't be lifted from the command line
+        Ok(Target {
+            fallback_passwords,
+            read_only: self.read_only || profile.read_only,
+            pin: profile.pin_policy(),
+            ..target
+        })
+    }
+
+    /// Resolve the target for a specific profile
+    ///
+    /// The profile's settings take precedence; flags only fill in what the
+    /// profile leaves out (e.g. a shared password). `--fallback-password`
+    /// is meant for the selected server only and never applies here.
+    pub fn resolve_profile(&self, name: &str, profile: &Profile) -> Result<Target, String> {
+        let target = build_target(
+            Some(name.to_string()),
+            profile.address.as_deref().or(self.address.as_deref()),
+            profile.password.as_deref().or(self.password.as_deref()),
+            profile.timeout.or(self.timeout),
+            profile.dialect.or(self.dialect),
+            profile.bind.as_deref().or(self.bind.as_deref()),
+            profile.prefix.as_deref().or(self.command_prefix.as_deref()),
+        )?;
+        Ok(Target {
+            fallback_passwords: profile.fallback_passwords.clone(),
+            read_only: self.read_only || profile.read_only,
+            pin: profile.pin_policy(),
+            ..target
+        })
+    }
+
+    /// How failed connection attempts are retried
+    pub fn reconnect_policy(&self) -> ReconnectPolicy {
+        let default = ReconnectPolicy::default();
+        ReconnectPolicy {
+            retries: self.retries.unwrap_or(default.retries),
+            delay: self.retry_delay.unwrap_or(default.delay),
+            backoff: self.retry_backoff.unwrap_or(default.backoff),
+            max_delay: default.max_delay,
+        }
+    }
+
+    /// Get the appropriate logging level based on verbosity
+    pub fn log_level(&self) -> &'static str {
+        match self.verbose {
+            0 => "warn",
+            1 => "info",
+            2 => "debug",
+            _ => "trace",
+        }
+    }
+
+    /// Check if colors should be used for output
+    pub fn use_colors(&self) -> bool {
+        if self.no_color || self.porcelain {
+            return false;
+        }
+
+        // An output file is never a terminal
+        let is_terminal = self.output.is_none() && io::stdout().is_terminal();
+        self.color.resolve(is_terminal)
+    }
+
+    /// Build the output formatter selected by the global flags
+    ///
+    /// Fails if the `--output` file cannot be opened.
+    pub fn formatter(&self) -> io::Result<OutputFormatter> {
+        let format = match self.format {
+            OutputFormat::Text | OutputFormat::Table if self.jsonpath.is_some() => {
+                OutputFormat::Json
+            }
+            ref format => format.clone(),
+        };
+        let filter = OutputFilter {
+            jsonpath: self.jsonpath.clone(),
+            grep: self.grep.clone(),
+            lines: self.lines,
+        };
+        let formatter = OutputFormatter::new(format, self.use_colors())
+            .with_quiet(self.quiet)
+            .with_porcelain(self.porcelain)
+            .with_timestamps(self.timestamps)
+            .with_columns(self.columns.clone())
+            .with_dry_run(self.dry_run)
+            .with_show_expansion(self.show_expansion)
+            .with_filter(filter);
+
+        let formatter = match &self.output {
+            Some(path) => {
+                let file = OpenOptions::new()
+                    .create(true)
+                    .write(true)
+                    .append(self.append)
+                    .truncate(!self.append)
+                    .open(path)?;
+                formatter.with_output(Box::new(file))
+            }
+            None => formatter,
+        };
+        Ok(formatter.with_colors(self.use_colors()))
+    }
+
+    /// Validate the CLI arguments
+    pub fn validate(&self) -> Result<(), String> {
+        // Validate timeout
+        if self.timeout == Some(0) {
+            return Err("Timeout must be greater than 0".to_string());
+        }
+
+        // Command-specific validation
+        match &self.command {
+            Commands::Exec {
+                command: Some(command),
+                ..
+            } if command.trim().is_empty() => {
+                return Err("Command cannot be empty".to_string());
+            }
+            Commands::Interactive { history_size, .. } if *history_size == 0 => {
+                return Err("History size must be greater than 0".to_string());
+            }
+            Commands::Ping {
+                count, interval, ..
+            } => {
+                if *count == 0 {
+                    return Err("Ping count must be greater than 0".to_string());
+                }
+                if *interval == 0 {
+                    return Err("Ping interval must be greater than 0".to_string());
+                }
+            }
+            Commands::Wait {
+                timeout, interval, ..
+            }
+            | Commands::OnReady {
+                timeout, interval, ..
+            } => {
+                if *timeout == 0 {
+                    return Err("Wait timeout must be greater than 0".to_string());
+                }
+                if *interval == 0 {
+                    return Err("Wait interval must be greater than 0".to_string());
+                }
+            }
+            _ => {}
+        }
+
+        if self.dry_run && matches!(self.command, Commands::Daemon { .. }) {
+            return Err("The daemon cannot run with --dry-run".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+/// Assemble a target from already-merged settings, applying defaults
+fn build_target(
+    profile: Option<String>,
+    address: Option<&str>,
+    password: Option<&str>,
+    timeout: Option<u64>,
+    dialect: Option<DialectKind>,
+    bind: Option<&str>,
+    prefix: Option<&str>,
+) -> Result<Target, String> {
+    let address = parse_address(address.unwrap_or(DEFAULT_ADDRESS))?;
+
+    let password = password
+        .filter(|password| !password.is_empty())
+        .ok_or_else(|| "Password cannot be empty (use --password or a profile)".to_string())?;
+
+    let timeout = timeout.unwrap_or(DEFAULT_TIMEOUT_SECS);
+    if timeout == 0 {
+        return Err("Timeout must be greater than 0".to_string());
+    }
+
+    Ok(Target {
+        profile,
+        address,
+        password: password.to_string(),
+        fallback_passwords: Vec::new(),
+        timeout: Duration::from_secs(timeout),
+        dialect: dialect.unwrap_or_default(),
+        bind: bind.map(parse_bind_address).transpose()?,
+        prefix: prefix
+            .filter(|prefix| !prefix.is_empty())
+            .map(str::to_string),
+        read_only: false,
+        pin: None,
+    })
+}
+
+/// A failure as reported to the user
+///
+/// Carries a machine-readable code alongside the message so JSON consumers
+/// can branch on the kind of error instead of parsing text.
+#[derive(Debug, Clone)]
+pub struct ErrorReport {
+    /// Error kind, e.g. `network` or `authentication_failed`
+    pub code: &'static str,
+    pub message: String,
+    /// How many times the operation was retried before this failure
+    pub retries: u32,
+    /// Whether another attempt follows
+    pub retrying: bool,
+}
+
+impl ErrorReport {
+    pub fn new(code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            retries: 0,
+            retrying: false,
+        }
+    }
+
+    /// Prefix the message with what was being attempted
+    pub fn context(mut self, context: impl fmt::Display) -> Self {
+        self.message = format!("{}: {}", context, self.message);
+        self
+    }
+
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Mark the failure as transient, with another attempt to follow
+    pub fn retrying(mut self) -> Self {
+        self.retrying = true;
+        self
+    }
+}
+
+impl From<&RconError> for ErrorReport {
+    fn from(error: &RconError) -> Self {
+        Self::new(error.code(), error.to_string())
+    }
+}
+
+impl fmt::Display for ErrorReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ErrorReport {}
+
+/// Helper struct for formatting command output
+///
+/// Clones write to the same destination.
+#[derive(Clone)]
+pub struct OutputFormatter {
+    format: OutputFormat,
+    use_colors: bool,
+    quiet: bool,
+    porcelain: bool,
+    /// Destination for responses; stdout when unset
+    output: Option<Arc<Mutex<Box<dyn Write + Send>>>>,
+    timestamps: Option<TimestampStyle>,
+    /// Reference point for relative timestamps
+    started: Instant,
+    /// Record columns to show; all of them when empty
+    columns: Vec<String>,
+    highlighter: Highlighter,
+    /// Responses are placeholders that are not printed
+    dry_run: bool,
+    /// Commands are echoed before they are sent
+    show_expansion: bool,
+    /// Applied to everything printed to the destination
+    filter: OutputFilter,
+    /// External programs adding a `parsed` field to JSON responses
+    parsers: ExternalParsers,
+}
+
+impl OutputFormatter {
+    pub fn new(format: OutputFormat, use_colors: bool) -> Self {
+        Self {
+            format,
+            use_colors,
+            quiet: false,
+            porcelain: false,
+            output: None,
+            timestamps: None,
+            started: Instant::now(),
+            columns: Vec::new(),
+            highlighter: Highlighter::default(),
+            dry_run: false,
+            show_expansion: false,
+            filter: OutputFilter::default(),
+            parsers: ExternalParsers::default(),
+        }
+    }
+
+    /// Colorize responses with the given rules instead of the defaults
+    pub fn with_highlighter(mut self, highlighter: Highlighter) -> Self {
+        self.highlighter = highlighter;
+        self
+    }
+
+    /// Restrict records to the named columns, in the given order
+    ///
+    /// Names match headers case-insensitively; unknown names are ignored.
+    pub fn with_columns(mut self, columns: Vec<String>) -> Self {
+        self.columns = columns;
+        self
+    }
+
+    /// Prefix text lines with timestamps and report command durations
+    pub fn with_timestamps(mut self, timestamps: Option<TimestampStyle>) -> Self {
+        self.timestamps = timestamps;
+        self
+    }
+
+    /// Send responses to `output` instead of stdout
+    ///
+    /// Informational messages move to stderr so the destination only
+    /// receives responses. Colors are disabled.
+    pub fn with_output(mut self, output: Box<dyn Write + Send>) -> Self {
+        self.output = Some(Arc::new(Mutex::new(output)));
+        self.use_colors = false;
+        self
+    }
+
+    /// Enable or disable ANSI colors
+    pub fn with_colors(mut self, use_colors: bool) -> Self {
+        self.use_colors = use_colors;
+        self
+    }
+
+    /// Suppress informational and progress messages
+    pub fn with_quiet(mut self, quiet: bool) -> Self {
+        self.quiet = quiet;
+        self
+    }
+
+    /// Produce stable, parse-friendly output
+    ///
+    /// Text responses are printed verbatim, records as tab-separated rows
+    /// without a header, and errors as `error<TAB>code<TAB>message`. The
+    /// layout of porcelain output is kept stable across releases.
+    pub fn with_porcelain(mut self, porcelain: bool) -> Self {
+        self.porcelain = porcelain;
+        if porcelain {
+            self.use_colors = false;
+        }
+        self
+    }
+
+    /// Leave out responses, which are empty in a dry run
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Echo commands with [`print_expansion`](Self::print_expansion)
+    pub fn with_show_expansion(mut self, show_expansion: bool) -> Self {
+        self.show_expansion = show_expansion;
+        self
+    }
+
+    /// Filter printed output with `--jsonpath`, `--grep`, and `--lines`
+    ///
+    /// Informational messages move to stderr so they don't mix with the
+    /// filtered output.
+    pub fn with_filter(mut self, filter: OutputFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Parse JSON responses of matching commands with external programs
+    pub fn with_parsers(mut self, parsers: ExternalParsers) -> Self {
+        self.parsers = parsers;
+        self
+    }
+
+    /// The parsers adding a `parsed` field to JSON responses
+    pub fn parsers(&self) -> &ExternalParsers {
+        &self.parsers
+    }
+
+    /// Whether informational and progress messages are suppressed
+    pub fn is_quiet(&self) -> bool {
+        self.quiet || self.porcelain
+    }
+
+    /// Print a warning to stderr, even when quiet
+    pub fn warn(&self, message: &str) {
+        eprintln!("{}{}", clear_line(), self.format_warning(message));
+    }
+
+    /// Print an informational message to stdout unless quiet
+    pub fn info(&self, message: &str) {
+        if self.output.is_some() || !self.filter.is_empty() {
+            self.progress(message);
+        } else if !self.is_quiet() {
+            println!("{}", self.format_info(message));
+        }
+    }
+
+    /// Redraw a progress bar on stderr
+    ///
+    /// Only drawn for text output to a terminal, so it never ends up in
+    /// logs or machine-readable output.
+    pub fn progress_bar(&self, done: usize, total: usize, failed: usize) {
+        const WIDTH: usize = 30;
+
+        if self.is_quiet() || self.is_json() || !io::stderr().is_terminal() {
+            return;
+        }
+        let filled = (done * WIDTH).checked_div(total).unwrap_or(WIDTH);
+        let mut line = format!(
+            "\r[{}{}] {}/{}",
+            "#".repeat(filled),
+            "-".repeat(WIDTH - filled),
+            done,
+            total
+        );
+        if failed > 0 {
+            line.push(' ');
+            line.push_str(&t!("progress-failed", count = failed));
+        }
+        if done >= total {
+            line.push('\n');
+        }
+        eprint!("{}", line);
+    }
+
+    /// A spinner on stderr naming the phase a slow connection is in
+    ///
+    /// Only drawn for text output to a terminal, and only once a phase has
+    /// lasted a moment, so quick connections print nothing. The line is
+    /// erased as soon as the connection is ready or fails.
+    pub fn connect_progress(&self, address: &str) -> Option<ConnectProgress> {
+        if self.is_quiet() || self.is_json() || !io::stderr().is_terminal() || !ansi_supported() {
+            return None;
+        }
+        let spinner = Arc::new(Mutex::new(Spinner::default()));
+        let address = address.to_string();
+        Some(ConnectProgress::new(move |phase| {
+            let mut state = spinner.lock().unwrap_or_else(|e| e.into_inner());
+            if !phase.is_pending() {
+                if state.drawn {
+                    eprint!("\r\x1b[2K");
+                }
+                state.phase = None;
+                state.drawn = false;
+                return;
+            }
+            if state.phase.is_none() {
+                state.since = Instant::now();
+            }
+            state.phase = Some(phase);
+            if !state.ticking {
+                if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+                    state.ticking = true;
+                    runtime.spawn(Spinner::tick(spinner.clone(), address.clone()));
+                }
+            }
+        }))
+    }
+
+    /// Show a command on stderr exactly as it will be sent, if enabled
+    ///
+    /// Printed even when quiet, since it was asked for; a progress bar
+    /// being drawn is cleared first and redrawn by its next update.
+    pub fn print_expansion(&self, command: &str) {
+        if !self.show_expansion {
+            return;
+        }
+        if self.is_json() {
+            eprintln!(
+                "{}",
+                self.render_json(serde_json::json!({
+                    "expansion": command,
+                    "timestamp": chrono::Utc::now().to_rfc3339(),
+                }))
+            );
+            return;
+        }
+        let line = if self.use_colors {
+            format!("\x1b[2m→ {}\x1b[0m", command)
+        } else {
+            format!("→ {}", command)
+        };
+        eprintln!("{}{}", clear_line(), self.stamp_lines(line));
+    }
+
+    /// Print a progress message to stderr unless quiet
+    pub fn progress(&self, message: &str) {
+        if !self.is_quiet() {
+            eprintln!("{}", self.format_info(message));
+        }
+    }
+
+    /// Write a formatted response to the output destination
+    pub fn print_response(&self, response: &str) -> io::Result<()> {
+        if self.dry_run {
+            return Ok(());
+        }
+        self.print_output(&self.format_response(response))
+    }
+
+    /// Write a command response along with its execution details
+    pub fn print_command_response(&self, response: &CommandResponse) -> io::Result<()> {
+        if self.dry_run {
+            return Ok(());
+        }
+        self.print_output(&self.format_command_response(response))
+    }
+
+    /// Write the response to `command` with the output of its parser plugin
+    ///
+    /// Parsers only run for JSON output, where their result is added as a
+    /// `parsed` field. A failing parser is reported as a warning and the
+    /// response printed without it.
+    pub async fn print_parsed_response(
+        &self,
+        command: &str,
+        response: &CommandResponse,
+    ) -> io::Result<()> {
+        if self.dry_run {
+            return Ok(());
+        }
+        if !self.is_json() {
+            return self.print_command_response(response);
+        }
+        let parsed = match self.parsers.parse(command, &response.body).await {
+            Some(Ok(parsed)) => Some(parsed),
+            Some(Err(e)) => {
+                self.warn(&e.to_string());
+                None
+            }
+            None => None,
+        };
+        self.print_output(&self.render_response(&response.body, Some(response), parsed))
+    }
+
+    /// Write already formatted output (responses, records) to the destination
+    ///
+    /// Output filters are applied first; nothing is written if they leave
+    /// no lines.
+    pub fn print_output(&self, text: &str) -> io::Result<()> {
+        let filtered;
+        let text = if self.filter.is_empty() {
+            text
+        } else {
+            let lines = self
+                .filter
+                .apply(text)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            if lines.is_empty() {
+                return Ok(());
+            }
+            filtered = lines.join("\n");
+            &filtered
+        };
+        match &self.output {
+            Some(output) => {
+                let mut output = output.lock().unwrap_or_else(|e| e.into_inner());
+                writeln!(output, "{}", text)?;
+                output.flush()
+            }
+            None => {
+                println!("{}", text);
+                Ok(())
+            }
+        }
+    }
+
+    pub fn format_response(&self, response: &str) -> String {
+        self.render_response(response, None, None)
+    }
+
+    /// Format a command response
+    ///
+    /// JSON output always carries the duration, request ID, and fragment
+    /// count; text output appends the duration with `--timestamps`.
+    pub fn format_command_response(&self, response: &CommandResponse) -> String {
+        self.render_response(&response.body, Some(response), None)
+    }
+
+    fn render_response(
+        &self,
+        response: &str,
+        details: Option<&CommandResponse>,
+        parsed: Option<serde_json::Value>,
+    ) -> String {
+        match self.format {
+            OutputFormat::Text | OutputFormat::Table => {
+                let mut text = if self.use_colors {
+                    self.highlighter.apply(response)
+                } else {
+                    response.to_string()
+                };
+                if let Some(duration) = details
+                    .filter(|_| self.timestamps.is_some())
+                    .map(|details| details.duration)
+                {
+                    if !text.is_empty() {
+                        text.push(' ');
+                    }
+                    text.push_str(&format!("({:.2}ms)", duration.as_secs_f64() * 1000.0));
+                }
+                self.stamp_lines(text)
+            }
+            OutputFormat::Json | OutputFormat::JsonPretty => {
+                let mut value = serde_json::json!({
+                    "response": response,
+                    "timestamp": chrono::Utc::now().to_rfc3339()
+                });
+                if let Some(details) = details {
+                    value["duration_ms"] = (details.duration.as_micros() as f64 / 1000.0).into();
+                    value["request_id"] = details.request_id.into();
+                    value["fragments"] = details.fragments.into();
+                    value["fragment_bytes"] = details.fragment_sizes.clone().into();
+                    value["reassembly_ms"] =
+                        (details.reassembly.as_micros() as f64 / 1000.0).into();
+                }
+                if let Some(parsed) = parsed {
+                    value["parsed"] = parsed;
+                }
+                self.render_json(value)
+            }
+        }
+    }
+
+    /// Prefix every line with the configured timestamp
+    fn stamp_lines(&self, text: String) -> String {
+        let stamp = match self.timestamps {
+            None => return text,
+            Some(TimestampStyle::Rfc3339) => {
+                chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+            }
+            Some(TimestampStyle::Relative) => {
+                format!("+{:.3}s", self.started.elapsed().as_secs_f64())
+            }
+        };
+
+        text.lines()
+            .map(|line| format!("{} {}", stamp, line))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    pub fn format_warning(&self, warning: &str) -> String {
+        match self.format {
+            OutputFormat::Text | OutputFormat::Table if self.porcelain => {
+                format!("warning\t{}", warning)
+            }
+            OutputFormat::Text | OutputFormat::Table => {
+                let message = t!("warning", message = warning);
                 if self.use_colors {
-                    format!("\x1b[31mError: {}\x1b[0m", error)
+                    format!("\x1b[33m{}\x1b[0m", message)
                 } else {
-                    format!("Error: {}", error)
+                    message
                 }
             }
-            OutputFormat::Json => serde_json::json!({
-                "error": error,
+            OutputFormat::Json | OutputFormat::JsonPretty => self.render_json(serde_json::json!({
+                "warning": warning,
                 "timestamp": chrono::Utc::now().to_rfc3339()
-            })
-            .to_string(),
+            })),
         }
     }
 
-    pub fn format_info(&self, info: &str) -> String {
+    pub fn format_error(&self, error: &str) -> String {
+        self.format_failure(&ErrorReport::new("error", error))
+    }
+
+    /// Format a client error, tagged with its error code
+    pub fn format_rcon_error(&self, error: &RconError) -> String {
+        self.format_failure(&ErrorReport::from(error))
+    }
+
+    /// Format a failure; in JSON mode every field of the report is included
+    pub fn format_failure(&self, report: &ErrorReport) -> String {
         match self.format {
-            OutputFormat::Text => {
+            OutputFormat::Text | OutputFormat::Table if self.porcelain => {
+                format!("error\t{}\t{}", report.code, report.message)
+            }
+            OutputFormat::Text | OutputFormat::Table => {
+                let message = if report.retrying {
+                    t!("error-retrying", message = report.message.as_str())
+                } else if report.retries > 0 {
+                    t!(
+                        "error-after-retries",
+                        message = report.message.as_str(),
+                        retries = report.retries
+                    )
+                } else {
+                    t!("error", message = report.message.as_str())
+                };
+
                 if self.use_colors {
+                    format!("\x1b[31m{}\x1b[0m", message)
+                } else {
+                    message
+                }
+            }
+            OutputFormat::Json | OutputFormat::JsonPretty => {
+                let mut value = serde_json::json!({
+                    "error": report.message,
+                    "code": report.code,
+                    "retries": report.retries,
+                    "timestamp": chrono::Utc::now().to_rfc3339()
+                });
+                if report.retrying {
+                    value["retrying"] = true.into();
+                }
+                self.render_json(value)
+            }
+        }
+    }
+
+    pub fn format_info(&self, info: &str) -> String {
+        match self.format {
+            OutputFormat::Text | OutputFormat::Table => {
+                let text = if self.use_colors {
                     format!("\x1b[36m{}\x1b[0m", info)
                 } else {
                     info.to_string()
-                }
+                };
+                self.stamp_lines(text)
             }
-            OutputFormat::Json => serde_json::json!({
+            OutputFormat::Json | OutputFormat::JsonPretty => self.render_json(serde_json::json!({
                 "info": info,
                 "timestamp": chrono::Utc::now().to_rfc3339()
-            })
-            .to_string(),
+            })),
+        }
+    }
+
+    /// Format a single parsed value, printed bare in text for use in scripts
+    ///
+    /// JSON output is an object with the value under `key`.
+    pub fn format_value(&self, key: &str, value: serde_json::Value) -> String {
+        match self.format {
+            _ if self.is_json() => self.render_json(serde_json::json!({
+                key: value,
+                "timestamp": chrono::Utc::now().to_rfc3339(),
+            })),
+            _ => match value {
+                serde_json::Value::String(text) => text,
+                value => value.to_string(),
+            },
+        }
+    }
+
+    /// Format a command that would be sent, for dry runs
+    pub fn format_command(&self, command: &str) -> String {
+        match self.format {
+            _ if self.is_json() => self.render_json(serde_json::json!({
+                "command": command,
+                "dry_run": true,
+            })),
+            _ => command.to_string(),
         }
     }
 
-    fn colorize_response(&self, response: &str) -> String {
-        // Simple colorization for common Minecraft server responses
-        let mut colored = response.to_string();
+    /// Format an event reported by a watch loop
+    ///
+    /// Text output is `<timestamp> <event> <message>` (tab-separated with
+    /// `--porcelain`); JSON output is one object per event with `event`,
+    /// `timestamp`, and the entries of `fields`.
+    pub fn format_event(
+        &self,
+        event: &str,
+        message: &str,
+        timestamp: chrono::DateTime<chrono::Utc>,
+        fields: serde_json::Value,
+    ) -> String {
+        let stamp = timestamp.to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+        match self.format {
+            _ if self.is_json() => {
+                let mut value = serde_json::json!({
+                    "event": event,
+                    "timestamp": timestamp.to_rfc3339(),
+                });
+                if let serde_json::Value::Object(fields) = fields {
+                    value
+                        .as_object_mut()
+                        .expect("event is an object")
+                        .extend(fields);
+                }
+                self.render_json(value)
+            }
+            _ if self.porcelain => format!("{}\t{}\t{}", stamp, event, message),
+            _ if self.use_colors => {
+                format!("{} \x1b[1m{:<7}\x1b[0m {}", stamp, event, message)
+            }
+            _ => format!("{} {:<7} {}", stamp, event, message),
+        }
+    }
+
+    /// Format a list of records, as aligned columns, a table, or a JSON array
+    pub fn format_records(&self, headers: &[&str], rows: &[Vec<String>]) -> String {
+        let (headers, rows) = self.select_columns(headers, rows);
+
+        match self.format {
+            _ if self.porcelain && !self.is_json() => rows
+                .iter()
+                .map(|row| row.join("\t"))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            OutputFormat::Text => {
+                let widths = column_widths(&headers, &rows);
+                let render = |cells: Vec<&str>| {
+                    cells
+                        .iter()
+                        .zip(&widths)
+                        .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                        .collect::<Vec<_>>()
+                        .join("  ")
+                        .trim_end()
+                        .to_string()
+                };
+
+                let header = render(headers.to_vec());
+                let mut lines = vec![if self.use_colors {
+                    format!("\x1b[1m{}\x1b[0m", header)
+                } else {
+                    header
+                }];
+                lines.extend(
+                    rows.iter()
+                        .map(|row| render(row.iter().map(String::as_str).collect())),
+                );
+                lines.join("\n")
+            }
+            OutputFormat::Table => {
+                let widths = column_widths(&headers, &rows);
+                let border = |left: &str, middle: &str, right: &str| {
+                    let segments: Vec<String> =
+                        widths.iter().map(|width| "─".repeat(width + 2)).collect();
+                    format!("{}{}{}", left, segments.join(middle), right)
+                };
+                let render = |cells: Vec<&str>, bold: bool| {
+                    let cells: Vec<String> = cells
+                        .iter()
+                        .zip(&widths)
+                        .map(|(cell, width)| {
+                            let padded = format!(" {:<width$} ", cell, width = width);
+                            if bold && self.use_colors {
+                                format!("\x1b[1m{}\x1b[0m", padded)
+                            } else {
+                                padded
+                            }
+                        })
+                        .collect();
+                    format!("│{}│", cells.join("│"))
+                };
+
+                let mut lines = vec![
+                    border("┌", "┬", "┐"),
+                    render(headers.to_vec(), true),
+                    border("├", "┼", "┤"),
+                ];
+                lines.extend(
+                    rows.iter()
+                        .map(|row| render(row.iter().map(String::as_str).collect(), false)),
+                );
+                lines.push(border("└", "┴", "┘"));
+                lines.join("\n")
+            }
+            OutputFormat::Json | OutputFormat::JsonPretty => {
+                let records: Vec<serde_json::Value> = rows
+                    .iter()
+                    .map(|row| {
+                        let fields = headers
+                            .iter()
+                            .zip(row)
+                            .map(|(header, cell)| (header.to_lowercase(), cell.clone().into()));
+                        serde_json::Value::Object(fields.collect())
+                    })
+                    .collect();
+                self.render_json(serde_json::json!({
+                    "records": records,
+                    "timestamp": chrono::Utc::now().to_rfc3339()
+                }))
+            }
+        }
+    }
 
-        // Color player names (simple heuristic)
-        if response.contains("players online:") {
-            colored = colored.replace("players online:", "\x1b[32mplayers online:\x1b[0m");
+    /// Apply `--columns` to a set of records
+    fn select_columns<'a>(
+        &self,
+        headers: &[&'a str],
+        rows: &[Vec<String>],
+    ) -> (Vec<&'a str>, Vec<Vec<String>>) {
+        if self.columns.is_empty() {
+            return (headers.to_vec(), rows.to_vec());
         }
 
-        // Color numbers
-        colored = regex::Regex::new(r"\b(\d+)\b")
-            .unwrap()
-            .replace_all(&colored, "\x1b[33m$1\x1b[0m")
-            .to_string();
+        let indices: Vec<usize> = self
+            .columns
+            .iter()
+            .filter_map(|column| {
+                let index = headers
+                    .iter()
+                    .position(|header| header.eq_ignore_ascii_case(column.trim()));
+                if index.is_none() {
+                    warn!(
+                        "Unknown column '{}' (available: {})",
+                        column,
+                        headers.join(", ")
+                    );
+                }
+                index
+            })
+            .collect();
+
+        let headers = indices.iter().map(|&index| headers[index]).collect();
+        let rows = rows
+            .iter()
+            .map(|row| {
+                indices
+                    .iter()
+                    .map(|&index| row.get(index).cloned().unwrap_or_default())
+                    .collect()
+            })
+            .collect();
+        (headers, rows)
+    }
+
+    fn is_json(&self) -> bool {
+        matches!(self.format, OutputFormat::Json | OutputFormat::JsonPretty)
+    }
+
+    /// Serialize a JSON value, indented for `json-pretty`
+    fn render_json(&self, value: serde_json::Value) -> String {
+        match self.format {
+            OutputFormat::JsonPretty => {
+                serde_json::to_string_pretty(&value).unwrap_or_else(|_| value.to_string())
+            }
+            _ => value.to_string(),
+        }
+    }
+}
 
-        colored
+/// Display width of each column: the widest of its header and cells
+fn column_widths(headers: &[&str], rows: &[Vec<String>]) -> Vec<usize> {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
     }
+    widths
 }
@@ -1,17 +1,237 @@
+use crate::detect::{self, ServerInfo};
+use crate::dialect::{AuthFlow, Dialect, FragmentTermination, MinecraftJava};
+use crate::dry_run::{DryRun, Loopback};
 use crate::error::{RconError, Result};
-use crate::protocol::{RconPacket, MAX_RESPONSE_PAYLOAD_SIZE};
+use crate::hooks::{ClientHooks, HookFuture};
+use crate::lazy::LazyRconClient;
+use crate::net;
+use crate::outcome::ErrorClassifier;
+use crate::policy::{CommandPolicy, CommandTurn, ExclusiveCommands};
+use crate::progress::{ConnectPhase, ConnectProgress};
+use crate::protocol::{packet_type, validate_packet_length, RconPacket};
+use crate::redact::{self, MASK};
+use futures_util::stream::{self, Stream};
+use futures_util::FutureExt;
+use std::fmt;
+use std::io;
 use std::net::SocketAddr;
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::TcpStream;
-use tracing::{debug, info, warn};
+use tokio::time::Instant;
+use tracing::{debug, info, trace, warn};
 
-/// Configuration for RCON client connection
+/// Default maximum number of fragments accepted for a single response
+pub const DEFAULT_MAX_FRAGMENTS: usize = 1024;
+
+/// Default maximum total size of a single response in bytes
+pub const DEFAULT_MAX_RESPONSE_BYTES: usize = 4 * 1024 * 1024;
+
+/// Progress of a streamed command response
+enum StreamState {
+    Pending(String),
+    Reading {
+        request: PendingRequest,
+        fragments: usize,
+        /// Held until the last fragment
+        turn: Option<CommandTurn>,
+    },
+    Done,
+}
+
+/// Identifiers of an in-flight command
+#[derive(Debug, Clone, Copy)]
+struct PendingRequest {
+    request_id: i32,
+    /// ID of the empty packet whose echo terminates the response, if any
+    sentinel_id: Option<i32>,
+}
+
+/// Connection packets travel over
+enum Transport {
+    Tcp(TcpStream),
+    DryRun(Loopback),
+}
+
+impl Transport {
+    async fn write_packet(&mut self, packet: &RconPacket, bytes: &[u8]) -> io::Result<()> {
+        match self {
+            Self::Tcp(stream) => stream.write_all(bytes).await,
+            Self::DryRun(loopback) => loopback.send(packet, bytes),
+        }
+    }
+
+    async fn read_exact(&mut self, buffer: &mut [u8]) -> io::Result<()> {
+        match self {
+            Self::Tcp(stream) => stream.read_exact(buffer).await.map(|_| ()),
+            Self::DryRun(loopback) => loopback.read_exact(buffer),
+        }
+    }
+
+    async fn shutdown(&mut self) -> io::Result<()> {
+        match self {
+            Self::Tcp(stream) => stream.shutdown().await,
+            Self::DryRun(_) => Ok(()),
+        }
+    }
+
+    /// Peek without waiting, returning `None` if nothing has arrived yet
+    fn peek_ready(&mut self, buffer: &mut [u8]) -> Option<io::Result<usize>> {
+        match self {
+            Self::Tcp(stream) => stream.peek(buffer).now_or_never(),
+            // Replies are always read in full before the next command
+            Self::DryRun(_) => None,
+        }
+    }
+}
+
+/// Strategy for recovering when packets for other requests arrive
+///
+/// Once a packet with an unexpected request ID shows up, the client keeps
+/// discarding packets until the expected one arrives or `deadline` expires.
 #[derive(Debug, Clone)]
+pub struct ResyncPolicy {
+    /// How long to drain mismatched packets before giving up
+    pub deadline: Duration,
+    /// Discard packets already waiting on the socket before sending a command
+    pub flush_stale: bool,
+    /// Reconnect when the deadline expires instead of keeping the desynced stream
+    pub reconnect: bool,
+}
+
+impl Default for ResyncPolicy {
+    fn default() -> Self {
+        Self {
+            deadline: Duration::from_secs(5),
+            flush_stale: true,
+            reconnect: true,
+        }
+    }
+}
+
+/// How failed connection attempts are retried by
+/// [`RconClient::connect_with_retry`]
+///
+/// The wait before retry `n` (counted from 1) is `delay * backoff^(n-1)`,
+/// capped at `max_delay`.
+///
+/// ```
+/// use rcon_cli::ReconnectPolicy;
+/// use std::time::Duration;
+///
+/// let policy = ReconnectPolicy {
+///     retries: 5,
+///     delay: Duration::from_millis(500),
+///     backoff: 2.0,
+///     max_delay: Duration::from_secs(3),
+/// };
+/// assert_eq!(policy.delay_before(1), Duration::from_millis(500));
+/// assert_eq!(policy.delay_before(3), Duration::from_secs(2));
+/// assert_eq!(policy.delay_before(5), Duration::from_secs(3));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReconnectPolicy {
+    /// Attempts after the first one
+    pub retries: u32,
+    /// Wait before the first retry
+    pub delay: Duration,
+    /// Factor the wait grows by with each retry; 1 keeps it constant
+    pub backoff: f64,
+    /// Longest wait between two attempts
+    pub max_delay: Duration,
+}
+
+impl ReconnectPolicy {
+    /// Give up after the first failed attempt
+    pub fn none() -> Self {
+        Self {
+            retries: 0,
+            ..Self::default()
+        }
+    }
+
+    /// Wait before retry `retry`, counted from 1
+    pub fn delay_before(&self, retry: u32) -> Duration {
+        let factor = self.backoff.max(1.0).powi(retry.saturating_sub(1) as i32);
+        self.delay
+            .mul_f64(factor.min(u32::MAX as f64))
+            .min(self.max_delay)
+    }
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            retries: 2,
+            delay: Duration::from_secs(1),
+            backoff: 1.0,
+            max_delay: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Configuration for RCON client connection
+///
+/// Its `Debug` output masks the passwords.
+#[derive(Clone)]
 pub struct RconConfig {
     pub address: SocketAddr,
     pub password: String,
     pub timeout: Duration,
+    pub hooks: ClientHooks,
+    /// Maximum total size of a reassembled response in bytes
+    pub max_response_bytes: usize,
+    /// Maximum number of packets a single response may be split into
+    pub max_fragments: usize,
+    /// Recovery strategy for desynchronized packet streams
+    pub resync: ResyncPolicy,
+    /// Protocol variant spoken by the server
+    pub dialect: Arc<dyn Dialect>,
+    /// Passwords tried in order when `password` is rejected
+    pub fallback_passwords: Vec<String>,
+    /// Local address the connection originates from (port 0 picks any port)
+    pub local_addr: Option<SocketAddr>,
+    /// Further addresses of the same server (e.g. other DNS records),
+    /// raced against `address` when connecting
+    pub alternate_addresses: Vec<SocketAddr>,
+    /// Hand packets to a callback instead of connecting to the server
+    pub dry_run: Option<DryRun>,
+    /// Commands outside the policy are rejected without being sent
+    pub policy: Option<CommandPolicy>,
+    /// Commands that don't interleave with others, shared by every client
+    /// with a clone of this config
+    pub exclusive: Option<ExclusiveCommands>,
+    /// Receives the phases of connecting and reconnecting
+    pub progress: Option<ConnectProgress>,
+    /// Retries of [`RconClient::connect_with_retry`]
+    pub reconnect: ReconnectPolicy,
+    /// Turns responses reporting errors into [`RconError::CommandFailed`]
+    pub error_classifier: Option<ErrorClassifier>,
+}
+
+impl fmt::Debug for RconConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RconConfig")
+            .field("address", &self.address)
+            .field("password", &MASK)
+            .field("timeout", &self.timeout)
+            .field("hooks", &self.hooks)
+            .field("max_response_bytes", &self.max_response_bytes)
+            .field("max_fragments", &self.max_fragments)
+            .field("resync", &self.resync)
+            .field("dialect", &self.dialect)
+            .field("fallback_passwords", &self.fallback_passwords.len())
+            .field("local_addr", &self.local_addr)
+            .field("alternate_addresses", &self.alternate_addresses)
+            .field("dry_run", &self.dry_run)
+            .field("policy", &self.policy)
+            .field("exclusive", &self.exclusive)
+            .field("progress", &self.progress)
+            .field("reconnect", &self.reconnect)
+            .field("error_classifier", &self.error_classifier)
+            .finish()
+    }
 }
 
 impl RconConfig {
@@ -20,54 +240,408 @@ impl RconConfig {
             address,
             password: password.into(),
             timeout: Duration::from_secs(5),
+            hooks: ClientHooks::default(),
+            max_response_bytes: DEFAULT_MAX_RESPONSE_BYTES,
+            max_fragments: DEFAULT_MAX_FRAGMENTS,
+            resync: ResyncPolicy::default(),
+            dialect: Arc::new(MinecraftJava),
+            fallback_passwords: Vec::new(),
+            local_addr: None,
+            alternate_addresses: Vec::new(),
+            dry_run: None,
+            policy: None,
+            exclusive: None,
+            progress: None,
+            reconnect: ReconnectPolicy::default(),
+            error_classifier: None,
         }
     }
 
+    /// Resolve `host:port` (or `[ipv6]:port`) and use every address found
+    ///
+    /// The port defaults to 25575 when omitted.
+    pub async fn resolve(address: &str, password: impl Into<String>) -> Result<Self> {
+        let mut addresses = net::resolve(address, crate::DEFAULT_PORT)
+            .await?
+            .into_iter();
+        let primary = addresses
+            .next()
+            .ok_or_else(|| RconError::AddressResolution(address.to_string()))?;
+        Ok(Self::new(primary, password).with_alternate_addresses(addresses))
+    }
+
     pub fn with_timeout(mut self, timeout: Duration) -> Self {
         self.timeout = timeout;
         self
     }
+
+    pub fn with_hooks(mut self, hooks: ClientHooks) -> Self {
+        self.hooks = hooks;
+        self
+    }
+
+    pub fn with_max_response_bytes(mut self, max_response_bytes: usize) -> Self {
+        self.max_response_bytes = max_response_bytes;
+        self
+    }
+
+    pub fn with_max_fragments(mut self, max_fragments: usize) -> Self {
+        self.max_fragments = max_fragments;
+        self
+    }
+
+    pub fn with_resync(mut self, resync: ResyncPolicy) -> Self {
+        self.resync = resync;
+        self
+    }
+
+    pub fn with_dialect(mut self, dialect: Arc<dyn Dialect>) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    /// Add passwords to try after the primary one, e.g. during a rotation
+    pub fn with_fallback_passwords<I, S>(mut self, passwords: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.fallback_passwords
+            .extend(passwords.into_iter().map(Into::into));
+        self
+    }
+
+    /// Originate connections from a specific local address
+    ///
+    /// Lets multi-homed hosts pick the interface a server's firewall
+    /// allowlist expects.
+    pub fn with_local_addr(mut self, local_addr: SocketAddr) -> Self {
+        self.local_addr = Some(local_addr);
+        self
+    }
+
+    /// Add addresses to try alongside the primary one
+    pub fn with_alternate_addresses(
+        mut self,
+        addresses: impl IntoIterator<Item = SocketAddr>,
+    ) -> Self {
+        self.alternate_addresses.extend(addresses);
+        self
+    }
+
+    /// Serialize packets without sending them, see [`DryRun`]
+    pub fn with_dry_run(mut self, dry_run: DryRun) -> Self {
+        self.dry_run = Some(dry_run);
+        self
+    }
+
+    /// Only send commands the policy permits, see [`CommandPolicy`]
+    pub fn with_policy(mut self, policy: CommandPolicy) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    /// Report connection phases, e.g. to show a progress indicator
+    pub fn with_progress(mut self, progress: ConnectProgress) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    pub fn with_reconnect_policy(mut self, reconnect: ReconnectPolicy) -> Self {
+        self.reconnect = reconnect;
+        self
+    }
+
+    /// Run some commands alone across the clients sharing this config, see
+    /// [`ExclusiveCommands`]
+    pub fn with_exclusive_commands(mut self, exclusive: ExclusiveCommands) -> Self {
+        self.exclusive = Some(exclusive);
+        self
+    }
+
+    /// Fail commands whose responses report errors, see [`ErrorClassifier`]
+    pub fn with_error_classifier(mut self, classifier: ErrorClassifier) -> Self {
+        self.error_classifier = Some(classifier);
+        self
+    }
+
+    /// Fail a response that reports an error, if errors are classified
+    pub fn check_response(&self, response: CommandResponse) -> Result<CommandResponse> {
+        match &self.error_classifier {
+            Some(classifier) => classifier.check(response),
+            None => Ok(response),
+        }
+    }
+
+    /// All server addresses in the order they are tried
+    pub fn addresses(&self) -> impl Iterator<Item = SocketAddr> + '_ {
+        std::iter::once(self.address).chain(self.alternate_addresses.iter().copied())
+    }
+
+    /// All credentials in the order they are tried, primary password first
+    pub fn credentials(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.password.as_str())
+            .chain(self.fallback_passwords.iter().map(String::as_str))
+    }
+}
+
+/// A command response together with details about how it was received
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandResponse {
+    /// Reassembled response text
+    pub body: String,
+    /// Time from sending the command until the last fragment arrived
+    pub duration: Duration,
+    /// Request ID the command was sent with
+    pub request_id: i32,
+    /// Number of packets the response was split into
+    pub fragments: usize,
+    /// Payload bytes of each packet in arrival order; the empty packet
+    /// ending a response in some dialects counts as 0
+    pub fragment_sizes: Vec<usize>,
+    /// Time from the first fragment until the last
+    pub reassembly: Duration,
+}
+
+/// A response put back together from its fragments
+struct Reassembled {
+    body: String,
+    fragment_sizes: Vec<usize>,
+    reassembly: Duration,
+}
+
+/// Fragment sizes for logs, eliding all but the first few
+fn size_list(sizes: &[usize]) -> String {
+    const SHOWN: usize = 16;
+    let mut list: Vec<String> = sizes.iter().take(SHOWN).map(usize::to_string).collect();
+    if sizes.len() > SHOWN {
+        list.push(format!("... {} more", sizes.len() - SHOWN));
+    }
+    list.join(", ")
 }
 
 /// RCON client for communicating with Minecraft servers
 pub struct RconClient {
-    stream: TcpStream,
+    stream: Transport,
     next_request_id: i32,
     config: RconConfig,
+    connected: bool,
+    credential_index: usize,
+    server_info: Option<ServerInfo>,
+    /// Terminator of the last mirrored response; Source servers follow its
+    /// echo with a second packet of the same ID
+    answered_sentinel: Option<i32>,
+}
+
+fn report(progress: &Option<ConnectProgress>, phase: ConnectPhase) {
+    if let Some(progress) = progress {
+        progress.report(phase);
+    }
 }
 
 impl RconClient {
     /// Connect to an RCON server and authenticate
     pub async fn connect(config: RconConfig) -> Result<Self> {
+        let progress = config.progress.clone();
+        let result = Self::establish(config).await;
+        if result.is_err() {
+            report(&progress, ConnectPhase::Failed);
+        }
+        result
+    }
+
+    /// Connect, retrying failed attempts as the config's [`ReconnectPolicy`]
+    /// says
+    ///
+    /// Configuration errors and pin mismatches are returned at once, since
+    /// retrying can't fix them. `on_retry` sees each failure with the number
+    /// of the retry about to start and the wait before it; once retries run
+    /// out, the last attempt's error is returned.
+    pub async fn connect_with_retry<F>(config: RconConfig, mut on_retry: F) -> Result<Self>
+    where
+        F: FnMut(&RconError, u32, Duration),
+    {
+        let mut retry = 0;
+        loop {
+            match Self::connect(config.clone()).await {
+                Err(e @ (RconError::InvalidConfig(_) | RconError::PinMismatch(_))) => {
+                    return Err(e)
+                }
+                Err(e) if retry < config.reconnect.retries => {
+                    retry += 1;
+                    let delay = config.reconnect.delay_before(retry);
+                    on_retry(&e, retry, delay);
+                    tokio::time::sleep(delay).await;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    async fn establish(config: RconConfig) -> Result<Self> {
         info!("Connecting to RCON server at {}", config.address);
+        for credential in config.credentials() {
+            redact::add_secret(credential);
+        }
 
-        let stream = tokio::time::timeout(config.timeout, TcpStream::connect(config.address))
-            .await
-            .map_err(|_| RconError::Timeout)?
-            .map_err(RconError::Network)?;
+        report(&config.progress, ConnectPhase::Connecting);
+        let stream = Self::dial(&config).await?;
 
         let mut client = Self {
             stream,
             next_request_id: 1,
             config,
+            connected: false,
+            credential_index: 0,
+            server_info: None,
+            answered_sentinel: None,
         };
 
         // Authenticate immediately after connection
-        client.authenticate().await?;
+        report(&client.config.progress, ConnectPhase::Authenticating);
+        client.authenticate_with_fallback().await?;
+        client.connected = true;
         info!("Successfully connected and authenticated");
 
+        if let Some(hook) = client.config.hooks.on_connect.clone() {
+            hook(&mut client).await?;
+        }
+
+        report(&client.config.progress, ConnectPhase::Ready);
         Ok(client)
     }
 
+    /// Close the connection, letting the server see a clean end of stream
+    ///
+    /// Fires the `on_disconnect` hook if the connection was still alive.
+    pub async fn close(&mut self) -> Result<()> {
+        self.mark_disconnected();
+        self.stream.shutdown().await?;
+        Ok(())
+    }
+
+    /// Drop the current connection, then dial and authenticate again
+    ///
+    /// Fires the `on_disconnect` hook (if the connection was still considered
+    /// alive) followed by `on_reconnect` once the new session is authenticated.
+    pub async fn reconnect(&mut self) -> Result<()> {
+        let result = self.reestablish().await;
+        if result.is_err() {
+            report(&self.config.progress, ConnectPhase::Failed);
+        }
+        result
+    }
+
+    async fn reestablish(&mut self) -> Result<()> {
+        self.mark_disconnected();
+        // The server may have been updated while it was away
+        self.server_info = None;
+        info!("Reconnecting to RCON server at {}", self.config.address);
+
+        report(&self.config.progress, ConnectPhase::Connecting);
+        self.stream = Self::dial(&self.config).await?;
+        self.next_request_id = 1;
+        self.answered_sentinel = None;
+        report(&self.config.progress, ConnectPhase::Authenticating);
+        self.authenticate_with_fallback().await?;
+        self.connected = true;
+        info!("Successfully reconnected and authenticated");
+
+        if let Some(hook) = self.config.hooks.on_reconnect.clone() {
+            hook(self).await?;
+        }
+
+        report(&self.config.progress, ConnectPhase::Ready);
+        Ok(())
+    }
+
+    /// Open the TCP connection described by the configuration
+    async fn dial(config: &RconConfig) -> Result<Transport> {
+        if let Some(dry_run) = &config.dry_run {
+            let loopback = Loopback::new(dry_run.clone(), config.dialect.clone());
+            return Ok(Transport::DryRun(loopback));
+        }
+        let addresses: Vec<SocketAddr> = config.addresses().collect();
+        tokio::time::timeout(config.timeout, net::connect(&addresses, config.local_addr))
+            .await
+            .map_err(|_| RconError::Timeout)?
+            .map(Transport::Tcp)
+    }
+
+    /// Record a lost connection and fire the `on_disconnect` hook once
+    fn mark_disconnected(&mut self) {
+        if self.connected {
+            self.connected = false;
+            debug!("Connection to {} lost", self.config.address);
+            if let Some(hook) = &self.config.hooks.on_disconnect {
+                hook(self.config.address);
+            }
+        }
+    }
+
+    /// Give up on a stream whose packets can no longer be told apart
+    ///
+    /// The stream is shut down rather than kept, so the unread rest of a
+    /// packet is never taken for the start of the next one.
+    async fn drop_connection(&mut self) {
+        self.mark_disconnected();
+        if let Err(e) = self.stream.shutdown().await {
+            debug!("Failed to shut down connection: {}", e);
+        }
+    }
+
+    /// Authenticate, trying each configured credential in turn
+    ///
+    /// The credential that succeeded last is tried first, so reconnecting
+    /// after a password rotation doesn't retry the stale password each time.
+    async fn authenticate_with_fallback(&mut self) -> Result<()> {
+        let credentials: Vec<String> = self.config.credentials().map(String::from).collect();
+        let count = credentials.len();
+
+        for attempt in 0..count {
+            let index = (self.credential_index + attempt) % count;
+
+            if attempt > 0 {
+                // Servers may close the connection after a rejected login
+                self.stream = Self::dial(&self.config).await?;
+                self.next_request_id = 1;
+            }
+
+            match self.authenticate(&credentials[index]).await {
+                Ok(()) => {
+                    if index > 0 {
+                        info!("Authenticated with fallback credential #{}", index);
+                    }
+                    self.credential_index = index;
+                    return Ok(());
+                }
+                Err(RconError::AuthenticationFailed) if attempt + 1 < count => {
+                    debug!("Credential #{} rejected, trying the next one", index);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        Err(RconError::AuthenticationFailed)
+    }
+
     /// Authenticate with the server
-    async fn authenticate(&mut self) -> Result<()> {
+    async fn authenticate(&mut self, password: &str) -> Result<()> {
         debug!("Authenticating with server");
 
         let request_id = self.next_request_id();
-        let auth_packet = RconPacket::auth(request_id, &self.config.password);
+        let auth_packet = RconPacket::auth(request_id, password);
 
         self.send_packet(&auth_packet).await?;
-        let response = self.read_packet().await?;
+        let mut response = self.read_packet().await?;
+
+        // Source-style servers send an empty response value ahead of the auth result
+        if self.config.dialect.auth_flow() == AuthFlow::EmptyResponseFirst
+            && response.is_command_response()
+        {
+            debug!("Skipping empty response packet preceding auth response");
+            response = self.read_packet().await?;
+        }
 
         if response.auth_successful(request_id) {
             debug!("Authentication successful");
@@ -80,22 +654,103 @@ impl RconClient {
 
     /// Execute a command on the server
     pub async fn execute_command(&mut self, command: impl AsRef<str>) -> Result<String> {
-        let command = command.as_ref();
-        debug!("Executing command: {}", command);
+        self.execute_command_detailed(command)
+            .await
+            .map(|response| response.body)
+    }
 
-        let request_id = self.next_request_id();
-        let command_packet = RconPacket::command(request_id, command);
+    /// Execute a command and report how the response was received
+    ///
+    /// The duration covers sending the command until the last fragment
+    /// arrived, i.e. the server-side latency as seen by the client.
+    pub async fn execute_command_detailed(
+        &mut self,
+        command: impl AsRef<str>,
+    ) -> Result<CommandResponse> {
+        let command = command.as_ref();
+        if let Some(policy) = &self.config.policy {
+            policy.check(command)?;
+        }
+        let _turn = self.turn(command).await;
+        debug!("Executing command: {}", redact::command(command));
 
-        self.send_packet(&command_packet).await?;
+        let started = Instant::now();
+        let request = self.send_command(command).await?;
 
         // Handle potentially fragmented responses
-        let response = self.read_command_response(request_id).await?;
+        let response = self.read_command_response(request).await?;
         debug!(
             "Command executed successfully, response length: {} bytes",
-            response.len()
+            response.body.len()
         );
 
-        Ok(response)
+        self.config.check_response(CommandResponse {
+            body: response.body,
+            duration: started.elapsed(),
+            request_id: request.request_id,
+            fragments: response.fragment_sizes.len(),
+            fragment_sizes: response.fragment_sizes,
+            reassembly: response.reassembly,
+        })
+    }
+
+    /// Execute several commands, sending them all before reading any response
+    ///
+    /// Servers answer a connection's commands in the order they arrive, so
+    /// pipelining them costs one round trip instead of one per command.
+    /// Responses are returned in the order of `commands`. If any command is
+    /// exclusive, the whole batch waits for an exclusive turn.
+    ///
+    /// ```no_run
+    /// use rcon_cli::{RconClient, RconConfig};
+    ///
+    /// # async fn example() -> rcon_cli::Result<()> {
+    /// let config = RconConfig::new("127.0.0.1:25575".parse().unwrap(), "secret");
+    /// let mut client = RconClient::connect(config).await?;
+    /// let responses = client.execute_pipelined(&["seed", "difficulty"]).await?;
+    /// assert_eq!(responses.len(), 2);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn execute_pipelined<S: AsRef<str>>(
+        &mut self,
+        commands: &[S],
+    ) -> Result<Vec<String>> {
+        let commands: Vec<&str> = commands.iter().map(AsRef::as_ref).collect();
+        if let Some(policy) = &self.config.policy {
+            for command in &commands {
+                policy.check(command)?;
+            }
+        }
+        let turn_command = match &self.config.exclusive {
+            Some(exclusive) => commands
+                .iter()
+                .find(|command| exclusive.is_exclusive(command))
+                .or(commands.first()),
+            None => None,
+        };
+        let _turn = match turn_command {
+            Some(command) => self.turn(command).await,
+            None => None,
+        };
+        debug!("Pipelining {} command(s)", commands.len());
+
+        self.prepare_request().await?;
+        let mut requests = Vec::with_capacity(commands.len());
+        for command in &commands {
+            requests.push(self.send_request(command).await?);
+        }
+
+        let mut responses = Vec::with_capacity(requests.len());
+        for request in requests {
+            responses.push(self.read_command_response(request).await?.body);
+        }
+        if let Some(classifier) = &self.config.error_classifier {
+            for response in &responses {
+                classifier.check_body(response)?;
+            }
+        }
+        Ok(responses)
     }
 
     /// Test connectivity by sending a harmless command
@@ -106,20 +761,96 @@ impl RconClient {
         Ok(())
     }
 
+    /// Wait until `command` may be sent, if some commands are exclusive
+    async fn turn(&self, command: &str) -> Option<CommandTurn> {
+        match &self.config.exclusive {
+            Some(exclusive) => Some(exclusive.turn(command).await),
+            None => None,
+        }
+    }
+
+    /// Send a command, discarding stale packets first if the policy says so
+    async fn send_command(&mut self, command: &str) -> Result<PendingRequest> {
+        self.prepare_request().await?;
+        self.send_request(command).await
+    }
+
+    /// Check the connection can take a command, discarding stale packets
+    /// first if the policy says so
+    ///
+    /// A connection once lost is never written to again, since what is left
+    /// on it can't be trusted; it has to be reconnected first.
+    async fn prepare_request(&mut self) -> Result<()> {
+        if !self.connected {
+            return Err(RconError::Disconnected);
+        }
+        if self.config.resync.flush_stale {
+            self.flush_stale_packets().await?;
+        }
+        Ok(())
+    }
+
+    /// Send a command packet, followed by a terminator packet if the dialect needs one
+    async fn send_request(&mut self, command: &str) -> Result<PendingRequest> {
+        let request_id = self.next_request_id();
+        self.send_packet(&RconPacket::command(request_id, command))
+            .await?;
+
+        let sentinel_id = match self.config.dialect.termination() {
+            FragmentTermination::MirrorPacket => {
+                let sentinel_id = self.next_request_id();
+                let sentinel = RconPacket::new(sentinel_id, packet_type::RESPONSE_VALUE, "");
+                self.send_packet(&sentinel).await?;
+                Some(sentinel_id)
+            }
+            _ => None,
+        };
+
+        Ok(PendingRequest {
+            request_id,
+            sentinel_id,
+        })
+    }
+
+    /// Send a packet with an arbitrary type and payload
+    ///
+    /// Returns the request ID assigned to the packet. This bypasses the
+    /// command/response bookkeeping, so any replies must be consumed with
+    /// [`recv_raw`](Self::recv_raw) before issuing regular commands again.
+    pub async fn send_raw(&mut self, packet_type: i32, payload: impl Into<String>) -> Result<i32> {
+        let request_id = self.next_request_id();
+        let packet = RconPacket::new(request_id, packet_type, payload);
+        self.send_packet(&packet).await?;
+        Ok(request_id)
+    }
+
+    /// Receive the next packet from the server without any interpretation
+    pub async fn recv_raw(&mut self) -> Result<RconPacket> {
+        self.read_packet().await
+    }
+
     /// Send a packet to the server
     async fn send_packet(&mut self, packet: &RconPacket) -> Result<()> {
-        let bytes = packet.to_bytes()?;
-        debug!(
-            "Sending packet: type={}, id={}, size={} bytes",
-            packet.packet_type,
-            packet.request_id,
-            bytes.len()
-        );
+        let bytes = packet.encode(self.config.dialect.as_ref())?;
+        if packet.packet_type == packet_type::AUTH || redact::is_sensitive(&packet.payload) {
+            // The size would give away the password's length
+            debug!(
+                "Sending packet: type={}, id={}",
+                packet.packet_type, packet.request_id
+            );
+        } else {
+            debug!(
+                "Sending packet: type={}, id={}, size={} bytes",
+                packet.packet_type,
+                packet.request_id,
+                bytes.len()
+            );
+        }
 
-        self.stream
-            .write_all(&bytes)
-            .await
-            .map_err(RconError::Network)?;
+        if let Err(e) = self.stream.write_packet(packet, &bytes).await {
+            self.mark_disconnected();
+            return Err(RconError::Network(e));
+        }
         Ok(())
     }
 
@@ -127,26 +858,36 @@ impl RconClient {
     async fn read_packet(&mut self) -> Result<RconPacket> {
         // Read packet length (4 bytes)
         let mut length_buffer = [0u8; 4];
-        self.stream
-            .read_exact(&mut length_buffer)
-            .await
-            .map_err(RconError::Network)?;
+        if let Err(e) = self.stream.read_exact(&mut length_buffer).await {
+            self.mark_disconnected();
+            return Err(RconError::Network(e));
+        }
 
-        let packet_length = i32::from_le_bytes(length_buffer) as usize;
+        // A bad length leaves the stream out of sync, so nothing after it
+        // can be trusted
+        let packet_length = match validate_packet_length(i32::from_le_bytes(length_buffer)) {
+            Ok(length) => length,
+            Err(e) => {
+                self.drop_connection().await;
+                return Err(e);
+            }
+        };
         debug!("Reading packet of length: {} bytes", packet_length);
 
-        // Validate packet length
-        if packet_length < 8 {
-            return Err(RconError::InvalidPacket(format!(
-                "Packet too short: {} bytes",
-                packet_length
-            )));
-        }
-
-        if packet_length > MAX_RESPONSE_PAYLOAD_SIZE + 10 {
-            return Err(RconError::InvalidPacket(format!(
-                "Packet too large: {} bytes",
-                packet_length
+        // A single packet can be no larger than the dialect sends, nor than
+        // the whole response budget; 10 bytes of id, type, and terminators
+        let max_payload = self
+            .config
+            .dialect
+            .max_response_payload()
+            .min(self.config.max_response_bytes);
+        if packet_length > max_payload.saturating_add(10) {
+            // The rest of the packet is never read, so the stream is out of sync
+            self.drop_connection().await;
+            return Err(RconError::ResponseTooLarge(format!(
+                "packet of {} bytes exceeds the {} byte packet limit",
+                packet_length,
+                max_payload.saturating_add(10)
             )));
         }
 
@@ -154,12 +895,12 @@ impl RconClient {
         let mut packet_data = vec![0u8; packet_length + 4]; // +4 for length field
         packet_data[0..4].copy_from_slice(&length_buffer);
 
-        self.stream
-            .read_exact(&mut packet_data[4..])
-            .await
-            .map_err(RconError::Network)?;
+        if let Err(e) = self.stream.read_exact(&mut packet_data[4..]).await {
+            self.mark_disconnected();
+            return Err(RconError::Network(e));
+        }
 
-        let packet = RconPacket::from_bytes(&packet_data)?;
+        let packet = RconPacket::decode(&packet_data, self.config.dialect.as_ref())?;
         debug!(
             "Received packet: type={}, id={}, payload_len={}",
             packet.packet_type,
@@ -170,21 +911,98 @@ impl RconClient {
         Ok(packet)
     }
 
-    /// Read command response, handling fragmentation
-    async fn read_command_response(&mut self, expected_request_id: i32) -> Result<String> {
+    /// Read the fragments of a response and reassemble them
+    async fn read_command_response(&mut self, request: PendingRequest) -> Result<Reassembled> {
         let mut full_response = String::new();
-        let mut packets_received = 0;
+        let mut fragment_sizes = Vec::new();
+        let mut first_arrival = None;
+
+        loop {
+            let (payload, is_last) = self.read_fragment(request).await?;
+            let arrival = *first_arrival.get_or_insert_with(Instant::now);
+            fragment_sizes.push(payload.len());
+            trace!(
+                "Fragment {} of request {}: {} bytes",
+                fragment_sizes.len(),
+                request.request_id,
+                payload.len()
+            );
+
+            if full_response.len() + payload.len() > self.config.max_response_bytes {
+                return Err(RconError::ResponseTooLarge(format!(
+                    "response exceeds the {} byte limit after {} packet(s)",
+                    self.config.max_response_bytes,
+                    fragment_sizes.len()
+                )));
+            }
+            full_response.push_str(&payload);
+
+            if is_last {
+                let reassembly = arrival.elapsed();
+                debug!(
+                    "Response complete after {} packet(s) of {} bytes, total length: {} bytes, reassembled in {:.2}ms",
+                    fragment_sizes.len(),
+                    size_list(&fragment_sizes),
+                    full_response.len(),
+                    reassembly.as_secs_f64() * 1000.0
+                );
+                return Ok(Reassembled {
+                    body: full_response,
+                    fragment_sizes,
+                    reassembly,
+                });
+            }
+
+            // Safety check to prevent infinite loops
+            if fragment_sizes.len() >= self.config.max_fragments {
+                return Err(self.too_many_fragments());
+            }
+        }
+    }
+
+    /// Read the next response fragment for a request
+    ///
+    /// Returns the fragment payload and whether it terminates the response.
+    async fn read_fragment(&mut self, request: PendingRequest) -> Result<(String, bool)> {
+        let expected_request_id = request.request_id;
+        let mut resync_deadline: Option<Instant> = None;
+        let mut discarded = 0;
 
         loop {
-            let packet = self.read_packet().await?;
-            packets_received += 1;
+            let packet = match resync_deadline {
+                None => self.read_packet().await?,
+                Some(deadline) => match tokio::time::timeout_at(deadline, self.read_packet()).await
+                {
+                    Ok(packet) => packet?,
+                    Err(_) => return Err(self.resync_failed(expected_request_id, discarded).await),
+                },
+            };
+
+            // The echoed terminator packet closes the response
+            if request.sentinel_id == Some(packet.request_id) {
+                self.answered_sentinel = request.sentinel_id;
+                return Ok((String::new(), true));
+            }
+            if self.is_sentinel_trailer(&packet) {
+                continue;
+            }
 
             // Check if this packet belongs to our request
             if packet.request_id != expected_request_id {
+                discarded += 1;
                 warn!(
-                    "Received packet with unexpected request ID: {} (expected: {})",
-                    packet.request_id, expected_request_id
+                    "Discarding packet with unexpected request ID: {} (expected: {}, type={}, payload_len={})",
+                    packet.request_id,
+                    expected_request_id,
+                    packet.packet_type,
+                    packet.payload.len()
                 );
+
+                let deadline = *resync_deadline
+                    .get_or_insert_with(|| Instant::now() + self.config.resync.deadline);
+                if Instant::now() >= deadline {
+                    return Err(self.resync_failed(expected_request_id, discarded).await);
+                }
                 continue;
             }
 
@@ -196,28 +1014,160 @@ impl RconClient {
                 )));
             }
 
-            full_response.push_str(&packet.payload);
-
             // Check if this is the last fragment
-            // According to the spec, the last packet has payload < 4096 bytes
-            if packet.payload.len() < MAX_RESPONSE_PAYLOAD_SIZE {
-                debug!(
-                    "Response complete after {} packet(s), total length: {} bytes",
-                    packets_received,
-                    full_response.len()
-                );
-                break;
-            }
+            let is_last = match self.config.dialect.termination() {
+                // According to the spec, the last packet has payload < 4096 bytes
+                FragmentTermination::ShortFragment => {
+                    packet.payload.len() < self.config.dialect.max_response_payload()
+                }
+                FragmentTermination::MirrorPacket => false,
+                FragmentTermination::SinglePacket => true,
+            };
+            return Ok((packet.payload, is_last));
+        }
+    }
 
-            // Safety check to prevent infinite loops
-            if packets_received > 100 {
-                return Err(RconError::Protocol(
-                    "Too many response packets received".to_string(),
-                ));
+    /// Whether `packet` trails the echo of an earlier response's terminator,
+    /// as Source servers send, and can be dropped without a warning
+    fn is_sentinel_trailer(&self, packet: &RconPacket) -> bool {
+        let trailer = self.answered_sentinel == Some(packet.request_id);
+        if trailer {
+            trace!("Dropping packet trailing terminator {}", packet.request_id);
+        }
+        trailer
+    }
+
+    /// Give up on a desynchronized stream, reconnecting if the policy allows it
+    async fn resync_failed(&mut self, expected_request_id: i32, discarded: usize) -> RconError {
+        let message = format!(
+            "no response for request ID {} after discarding {} packet(s)",
+            expected_request_id, discarded
+        );
+        warn!("Stream desynchronized: {}", message);
+
+        if !self.config.resync.reconnect {
+            return RconError::Desynchronized(message);
+        }
+
+        match self.reconnect().await {
+            Ok(()) => RconError::Desynchronized(format!("{}, connection re-established", message)),
+            Err(e) => e,
+        }
+    }
+
+    /// Discard any packets already waiting on the socket
+    ///
+    /// Late fragments of an earlier response would otherwise be read as the
+    /// start of the next one.
+    async fn flush_stale_packets(&mut self) -> Result<()> {
+        let mut probe = [0u8; 1];
+
+        while let Some(peeked) = self.stream.peek_ready(&mut probe) {
+            match peeked {
+                Ok(0) => {
+                    self.mark_disconnected();
+                    return Err(RconError::Disconnected);
+                }
+                Ok(_) => {
+                    let packet = self.read_packet().await?;
+                    if self.is_sentinel_trailer(&packet) {
+                        continue;
+                    }
+                    warn!(
+                        "Flushed stale packet: type={}, id={}, payload_len={}",
+                        packet.packet_type,
+                        packet.request_id,
+                        packet.payload.len()
+                    );
+                }
+                Err(e) => {
+                    self.mark_disconnected();
+                    return Err(RconError::Network(e));
+                }
             }
         }
 
-        Ok(full_response)
+        Ok(())
+    }
+
+    /// Error returned when a response is split into more packets than allowed
+    fn too_many_fragments(&self) -> RconError {
+        RconError::ResponseTooLarge(format!(
+            "response did not complete within {} packets",
+            self.config.max_fragments
+        ))
+    }
+
+    /// Execute a command and yield each response fragment as it arrives
+    ///
+    /// Unlike [`execute_command`](Self::execute_command), the response is not
+    /// buffered, which keeps memory flat for very large outputs. Only the
+    /// fragment limit applies; the total byte limit is left to the consumer.
+    /// The stream ends after the final fragment or the first error.
+    ///
+    /// ```rust,no_run
+    /// use futures_util::StreamExt;
+    /// # async fn example(client: &mut rcon_cli::RconClient) -> rcon_cli::Result<()> {
+    /// let mut fragments = std::pin::pin!(client.execute_command_stream("banlist"));
+    /// while let Some(fragment) = fragments.next().await {
+    ///     print!("{}", fragment?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn execute_command_stream(
+        &mut self,
+        command: impl Into<String>,
+    ) -> impl Stream<Item = Result<String>> + '_ {
+        let state = StreamState::Pending(command.into());
+
+        stream::unfold((self, state), |(client, state)| async move {
+            let (request, fragments, turn) = match state {
+                StreamState::Done => return None,
+                StreamState::Pending(command) => {
+                    if let Some(policy) = &client.config.policy {
+                        if let Err(e) = policy.check(&command) {
+                            return Some((Err(e), (client, StreamState::Done)));
+                        }
+                    }
+                    let turn = client.turn(&command).await;
+                    debug!("Executing streamed command: {}", redact::command(&command));
+                    match client.send_command(&command).await {
+                        Ok(request) => (request, 0, turn),
+                        Err(e) => return Some((Err(e), (client, StreamState::Done))),
+                    }
+                }
+                StreamState::Reading {
+                    request,
+                    fragments,
+                    turn,
+                } => (request, fragments, turn),
+            };
+
+            let next = match client.read_fragment(request).await {
+                Ok((payload, true)) => {
+                    debug!(
+                        "Streamed response complete after {} packet(s)",
+                        fragments + 1
+                    );
+                    (Ok(payload), StreamState::Done)
+                }
+                Ok(_) if fragments + 1 >= client.config.max_fragments => {
+                    (Err(client.too_many_fragments()), StreamState::Done)
+                }
+                Ok((payload, false)) => (
+                    Ok(payload),
+                    StreamState::Reading {
+                        request,
+                        fragments: fragments + 1,
+                        turn,
+                    },
+                ),
+                Err(e) => (Err(e), StreamState::Done),
+            };
+
+            Some((next.0, (client, next.1)))
+        })
     }
 
     /// Generate the next request ID
@@ -235,11 +1185,38 @@ impl RconClient {
         self.config.address
     }
 
+    /// Index of the credential that authenticated this client
+    ///
+    /// `0` is the primary password, `n` the n-th fallback password.
+    pub fn credential_index(&self) -> usize {
+        self.credential_index
+    }
+
+    /// The server's flavor and version, detected on first use
+    ///
+    /// Cached until the client reconnects. See [`detect::detect`].
+    pub async fn server_info(&mut self) -> Result<ServerInfo> {
+        if let Some(info) = self.server_info {
+            return Ok(info);
+        }
+        let info = detect::detect(self).await?;
+        self.server_info = Some(info);
+        Ok(info)
+    }
+
     /// Check if the connection is still alive
     pub async fn is_connected(&mut self) -> bool {
         // Try to send a minimal ping command
         self.ping().await.is_ok()
     }
+
+    /// Whether the connection is known to be lost or closed
+    ///
+    /// Unlike [`is_connected`](Self::is_connected) this costs no round trip,
+    /// so a connection the server dropped while idle still counts as open.
+    pub fn is_closed(&self) -> bool {
+        !self.connected
+    }
 }
 
 /// Builder pattern for creating RCON client configurations
@@ -247,6 +1224,13 @@ pub struct RconClientBuilder {
     address: Option<SocketAddr>,
     password: Option<String>,
     timeout: Duration,
+    hooks: ClientHooks,
+    max_response_bytes: usize,
+    max_fragments: usize,
+    fallback_passwords: Vec<String>,
+    local_addr: Option<SocketAddr>,
+    progress: Option<ConnectProgress>,
+    reconnect: ReconnectPolicy,
 }
 
 impl RconClientBuilder {
@@ -255,6 +1239,13 @@ impl RconClientBuilder {
             address: None,
             password: None,
             timeout: Duration::from_secs(5),
+            hooks: ClientHooks::default(),
+            max_response_bytes: DEFAULT_MAX_RESPONSE_BYTES,
+            max_fragments: DEFAULT_MAX_FRAGMENTS,
+            fallback_passwords: Vec::new(),
+            local_addr: None,
+            progress: None,
+            reconnect: ReconnectPolicy::default(),
         }
     }
 
@@ -268,12 +1259,87 @@ impl RconClientBuilder {
         self
     }
 
+    /// Add a password to try if the previous ones are rejected
+    pub fn fallback_password(mut self, password: impl Into<String>) -> Self {
+        self.fallback_passwords.push(password.into());
+        self
+    }
+
+    /// Originate the connection from a specific local address
+    pub fn local_addr(mut self, local_addr: SocketAddr) -> Self {
+        self.local_addr = Some(local_addr);
+        self
+    }
+
     pub fn timeout(mut self, timeout: Duration) -> Self {
         self.timeout = timeout;
         self
     }
 
+    pub fn max_response_bytes(mut self, max_response_bytes: usize) -> Self {
+        self.max_response_bytes = max_response_bytes;
+        self
+    }
+
+    pub fn max_fragments(mut self, max_fragments: usize) -> Self {
+        self.max_fragments = max_fragments;
+        self
+    }
+
+    /// Register a hook fired after the initial connection is authenticated
+    pub fn on_connect<F>(mut self, hook: F) -> Self
+    where
+        F: for<'a> Fn(&'a mut RconClient) -> HookFuture<'a> + Send + Sync + 'static,
+    {
+        self.hooks = self.hooks.on_connect(hook);
+        self
+    }
+
+    /// Register a hook fired when the connection to the server is lost
+    pub fn on_disconnect<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(SocketAddr) + Send + Sync + 'static,
+    {
+        self.hooks = self.hooks.on_disconnect(hook);
+        self
+    }
+
+    /// Register a hook fired after every successful reconnect
+    pub fn on_reconnect<F>(mut self, hook: F) -> Self
+    where
+        F: for<'a> Fn(&'a mut RconClient) -> HookFuture<'a> + Send + Sync + 'static,
+    {
+        self.hooks = self.hooks.on_reconnect(hook);
+        self
+    }
+
+    /// Report connection phases, e.g. to show a progress indicator
+    pub fn progress(mut self, progress: ConnectProgress) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Retry failed connection attempts of
+    /// [`RconClient::connect_with_retry`] this way
+    pub fn reconnect_policy(mut self, reconnect: ReconnectPolicy) -> Self {
+        self.reconnect = reconnect;
+        self
+    }
+
     pub async fn connect(self) -> Result<RconClient> {
+        RconClient::connect(self.build()?).await
+    }
+
+    /// Create a handle that only dials the server when the first command is sent
+    ///
+    /// Useful for long-lived services that must start even while the game
+    /// server is still booting.
+    pub fn connect_lazy(self) -> Result<LazyRconClient> {
+        Ok(LazyRconClient::new(self.build()?))
+    }
+
+    /// Validate the builder and assemble the connection configuration
+    pub fn build(self) -> Result<RconConfig> {
         let address = self
             .address
             .ok_or_else(|| RconError::InvalidConfig("Server address is required".to_string()))?;
@@ -282,8 +1348,15 @@ impl RconClientBuilder {
             .password
             .ok_or_else(|| RconError::InvalidConfig("Password is required".to_string()))?;
 
-        let config = RconConfig::new(address, password).with_timeout(self.timeout);
-        RconClient::connect(config).await
+        let mut config = RconConfig::new(address, password)
+            .with_timeout(self.timeout)
+            .with_hooks(self.hooks)
+            .with_max_response_bytes(self.max_response_bytes)
+            .with_max_fragments(self.max_fragments)
+            .with_fallback_passwords(self.fallback_passwords);
+        config.local_addr = self.local_addr;
+        config.progress = self.progress;
+        Ok(config.with_reconnect_policy(self.reconnect))
     }
 }
 
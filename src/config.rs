@@ -0,0 +1,200 @@
+use crate::dialect::DialectKind;
+use crate::error::{RconError, Result};
+use crate::grafana::GrafanaConfig;
+use crate::highlight::HighlightConfig;
+use crate::k8s::K8sConfig;
+use crate::mqtt::MqttConfig;
+use crate::outcome::ErrorConfig;
+use crate::panel::PanelConfig;
+use crate::parser_plugins::ParserConfig;
+use crate::pins::{PinMode, PinPolicy, DEFAULT_PIN_COMMAND};
+use crate::redact::RedactConfig;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// A named server entry in the configuration file
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    /// Server address in format host:port
+    pub address: Option<String>,
+    /// RCON password
+    pub password: Option<String>,
+    /// Passwords tried in order if `password` is rejected
+    #[serde(default)]
+    pub fallback_passwords: Vec<String>,
+    /// Connection timeout in seconds
+    pub timeout: Option<u64>,
+    /// Protocol dialect spoken by the server
+    pub dialect: Option<DialectKind>,
+    /// Local address to originate connections from
+    pub bind: Option<String>,
+    /// Prepended to commands sent with `exec` and in interactive mode
+    pub prefix: Option<String>,
+    /// Only send the queries allowed by `--read-only`
+    #[serde(default)]
+    pub read_only: bool,
+    /// Pin the server's addresses and identity on first use
+    pub pin: Option<PinMode>,
+    /// Command whose response identifies the server (default: `seed`)
+    pub pin_command: Option<String>,
+    /// Hosting panel to look up a missing address and password from
+    pub panel: Option<PanelConfig>,
+    /// Kubernetes Service to discover the address and password from
+    pub k8s: Option<K8sConfig>,
+}
+
+impl Profile {
+    /// How connections to the profile's server are pinned, if at all
+    pub fn pin_policy(&self) -> Option<PinPolicy> {
+        self.pin.map(|mode| PinPolicy {
+            mode,
+            command: self
+                .pin_command
+                .clone()
+                .unwrap_or_else(|| DEFAULT_PIN_COMMAND.to_string()),
+        })
+    }
+}
+
+/// A BungeeCord or Velocity network: a proxy and the servers behind it
+///
+/// Both refer to profiles by name; backends are addressed by their profile
+/// name.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Network {
+    /// Profile of the proxy, if it accepts RCON through a plugin
+    pub proxy: Option<String>,
+    /// Profiles of the backend servers
+    #[serde(default)]
+    pub servers: Vec<String>,
+}
+
+/// A WebAssembly plugin, loaded when built with the `wasm` feature
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PluginConfig {
+    /// Component implementing `wit/plugin.wit`
+    pub path: PathBuf,
+    /// Instructions a single call may run, to stop runaway plugins
+    pub fuel: Option<u64>,
+}
+
+/// Contents of the `rcon-cli` configuration file
+///
+/// ```toml
+/// [profiles.survival]
+/// address = "mc.example.com:25575"
+/// password = "secret"
+///
+/// [networks.main]
+/// servers = ["survival", "creative"]
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+    /// Proxy networks made of profiles
+    #[serde(default)]
+    pub networks: BTreeMap<String, Network>,
+    /// Response highlighting rules
+    #[serde(default)]
+    pub highlight: HighlightConfig,
+    /// Commands whose arguments are masked in logs, history, and events
+    #[serde(default)]
+    pub redact: RedactConfig,
+    /// Responses reporting errors, per dialect
+    #[serde(default)]
+    pub errors: BTreeMap<DialectKind, ErrorConfig>,
+    /// External programs parsing the responses of matching commands
+    #[serde(default)]
+    pub parsers: Vec<ParserConfig>,
+    /// WebAssembly plugins
+    #[serde(default)]
+    pub plugins: Vec<PluginConfig>,
+    /// Broker to publish events and metrics to
+    pub mqtt: Option<MqttConfig>,
+    /// Grafana instance to post annotations to
+    pub grafana: Option<GrafanaConfig>,
+}
+
+impl ConfigFile {
+    /// Default location: `<config dir>/rcon-cli/config.toml`
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("rcon-cli").join("config.toml"))
+    }
+
+    /// Parse configuration from TOML text
+    pub fn parse(source: &str) -> Result<Self> {
+        toml::from_str(source)
+            .map_err(|e| RconError::InvalidConfig(format!("Invalid config file: {}", e)))
+    }
+
+    /// Read and parse a configuration file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path).map_err(|e| {
+            RconError::InvalidConfig(format!(
+                "Failed to read config file '{}': {}",
+                path.display(),
+                e
+            ))
+        })?;
+        Self::parse(&source)
+    }
+
+    /// Load an explicitly requested file, or the default file if it exists
+    ///
+    /// A missing default file yields an empty configuration; a missing
+    /// explicit file is an error.
+    pub fn load_or_default(explicit: Option<&Path>) -> Result<Self> {
+        match explicit {
+            Some(path) => Self::load(path),
+            None => match Self::default_path() {
+                Some(path) if path.exists() => Self::load(path),
+                _ => Ok(Self::default()),
+            },
+        }
+    }
+
+    /// Look up a profile by name
+    pub fn profile(&self, name: &str) -> Result<&Profile> {
+        self.profiles
+            .get(name)
+            .ok_or_else(|| RconError::InvalidConfig(format!("Unknown profile '{}'", name)))
+    }
+
+    /// Look up a network by name, or the only one if no name is given
+    pub fn network(&self, name: Option<&str>) -> Result<(&str, &Network)> {
+        let (name, network) = match name {
+            Some(name) => self
+                .networks
+                .get_key_value(name)
+                .ok_or_else(|| RconError::InvalidConfig(format!("Unknown network '{}'", name)))?,
+            None => match self.networks.len() {
+                0 => return Err(RconError::InvalidConfig("No networks configured".into())),
+                1 => self.networks.iter().next().unwrap(),
+                _ => {
+                    let names: Vec<&str> = self.networks.keys().map(String::as_str).collect();
+                    return Err(RconError::InvalidConfig(format!(
+                        "Several networks are configured, choose one with --network ({})",
+                        names.join(", ")
+                    )));
+                }
+            },
+        };
+
+        for profile in network.proxy.iter().chain(&network.servers) {
+            self.profile(profile).map_err(|_| {
+                RconError::InvalidConfig(format!(
+                    "Network '{}' refers to unknown profile '{}'",
+                    name, profile
+                ))
+            })?;
+        }
+        Ok((name, network))
+    }
+}
@@ -0,0 +1,566 @@
+//! Persistent daemon keeping authenticated connections behind a Unix socket
+//!
+//! Requests and responses are single lines of JSON. Connections are keyed by
+//! server address, local bind address, and password, so different
+//! credentials never share a session. Each connection serves one command at
+//! a time, [multiplexed](crate::mux) fairly between local users, whom an
+//! optional rate limit applies to separately.
+//!
+//! A request may carry the caller's timeout, after which its command fails
+//! instead of staying queued. A client disconnecting while its command
+//! waits or runs cancels the command too.
+//!
+//! A line of just `{"status": true}` is answered with the queue of every
+//! connection, see [`DaemonStatus`].
+
+use crate::client::{CommandResponse, RconConfig};
+use crate::dialect::DialectKind;
+use crate::error::{RconError, Result};
+use crate::lazy::LazyRconClient;
+use crate::mux::{Multiplexer, Priority, QueueStats, RateLimit};
+use crate::redact;
+use crate::shutdown::Shutdown;
+use futures_util::future::join_all;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::net::SocketAddr;
+use std::os::unix::fs::{DirBuilderExt, FileTypeExt, MetadataExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::unix::OwnedWriteHalf;
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::{watch, Mutex};
+use tokio::task::JoinSet;
+use tracing::{debug, info, warn};
+
+/// A command routed through the daemon
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonRequest {
+    pub address: SocketAddr,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alternate_addresses: Vec<SocketAddr>,
+    pub password: String,
+    #[serde(default)]
+    pub dialect: DialectKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bind: Option<SocketAddr>,
+    pub command: String,
+    /// Queue priority; by default [`Priority::of`] the command
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<Priority>,
+    /// Milliseconds the caller waits for the reply, queueing included
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+}
+
+/// The daemon's reply to a [`DaemonRequest`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonResponse {
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Server round trip in microseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_us: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fragments: Option<usize>,
+    /// Payload bytes of each fragment
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fragment_sizes: Option<Vec<usize>>,
+    /// Time from the first fragment until the last in microseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reassembly_us: Option<u64>,
+}
+
+/// Asks the daemon for a [`DaemonStatus`] instead of running a command
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StatusRequest {
+    status: bool,
+}
+
+/// The daemon's reply to a status request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonStatus {
+    /// Open connections, by address
+    pub servers: Vec<ServerStatus>,
+}
+
+/// The queue of one connection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerStatus {
+    pub address: SocketAddr,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bind: Option<SocketAddr>,
+    #[serde(flatten)]
+    pub queue: QueueStats,
+}
+
+/// Send a reply as a line of JSON
+async fn write_line(writer: &mut OwnedWriteHalf, reply: &impl Serialize) -> Result<()> {
+    let mut encoded = serde_json::to_string(reply)
+        .map_err(|e| RconError::Protocol(format!("Failed to encode reply: {}", e)))?;
+    encoded.push('\n');
+    writer.write_all(encoded.as_bytes()).await?;
+    Ok(())
+}
+
+/// Default control socket location
+///
+/// Uses the per-user runtime directory when available, falling back to the
+/// system temporary directory.
+pub fn default_socket_path() -> PathBuf {
+    match dirs::runtime_dir() {
+        Some(dir) => dir.join("rcon-cli.sock"),
+        None => {
+            let user = std::env::var("USER").unwrap_or_else(|_| "default".to_string());
+            std::env::temp_dir().join(format!("rcon-cli-{}.sock", user))
+        }
+    }
+}
+
+type ConnectionKey = (SocketAddr, Option<SocketAddr>, String);
+
+fn connection_key(config: &RconConfig) -> ConnectionKey {
+    (config.address, config.local_addr, config.password.clone())
+}
+
+/// Daemon holding one lazily (re)connecting client per server and credential
+pub struct Daemon {
+    connections: Mutex<HashMap<ConnectionKey, Multiplexer>>,
+    /// Servers registered up front, as opposed to ones first seen in a request
+    registered: Mutex<HashSet<ConnectionKey>>,
+    timeout: Duration,
+    rate_limit: Option<RateLimit>,
+    max_queue: Option<usize>,
+}
+
+impl Daemon {
+    /// Create a daemon dialing new servers with the given connection timeout
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            connections: Mutex::new(HashMap::new()),
+            registered: Mutex::new(HashSet::new()),
+            timeout,
+            rate_limit: None,
+            max_queue: None,
+        }
+    }
+
+    /// Limit the commands of each local user, per server
+    pub fn with_rate_limit(mut self, rate_limit: Option<RateLimit>) -> Self {
+        self.rate_limit = rate_limit;
+        self
+    }
+
+    /// Reject commands for a server while `max_queue` others wait for it
+    pub fn with_max_queue(mut self, max_queue: Option<usize>) -> Self {
+        self.max_queue = max_queue;
+        self
+    }
+
+    /// The queue of every connection
+    pub async fn status(&self) -> DaemonStatus {
+        let connections = self.connections.lock().await;
+        let mut servers: Vec<ServerStatus> = connections
+            .iter()
+            .map(|((address, bind, _), connection)| ServerStatus {
+                address: *address,
+                bind: *bind,
+                queue: connection.stats(),
+            })
+            .collect();
+        servers.sort_by_key(|server| (server.address, server.bind));
+        DaemonStatus { servers }
+    }
+
+    /// Register a server and connect to it right away
+    ///
+    /// A failed initial connection is logged and retried on first use.
+    pub async fn preconnect(&self, config: RconConfig) {
+        let address = config.address;
+        self.registered.lock().await.insert(connection_key(&config));
+        let connection = self.connection_for(config).await;
+        match connection.connect().await {
+            Ok(()) => info!("Daemon connected to {}", address),
+            Err(e) => warn!("Daemon could not connect to {} yet: {}", address, e),
+        }
+    }
+
+    /// Replace the registered servers, e.g. after the config file changed
+    ///
+    /// New servers are connected right away and ones no longer listed are
+    /// disconnected; connections to servers that remain, and those opened
+    /// for servers only seen in requests, are kept. Returns how many servers
+    /// were added and removed.
+    pub async fn set_servers(&self, configs: Vec<RconConfig>) -> (usize, usize) {
+        let keys: HashSet<ConnectionKey> = configs.iter().map(connection_key).collect();
+        let registered = std::mem::take(&mut *self.registered.lock().await);
+
+        let removed: Vec<Multiplexer> = {
+            let mut connections = self.connections.lock().await;
+            registered
+                .difference(&keys)
+                .filter_map(|key| connections.remove(key))
+                .collect()
+        };
+        for connection in &removed {
+            info!("Daemon disconnecting from {}", connection.address());
+            let _ = connection.close().await;
+        }
+
+        let added: Vec<RconConfig> = configs
+            .into_iter()
+            .filter(|config| !registered.contains(&connection_key(config)))
+            .collect();
+        let count = added.len();
+        self.registered.lock().await.extend(keys);
+        join_all(added.into_iter().map(|config| self.preconnect(config))).await;
+
+        (count, removed.len())
+    }
+
+    /// Accept requests on `path` until SIGINT or SIGTERM
+    pub async fn serve(self: Arc<Self>, path: &Path) -> Result<()> {
+        let listener = Self::bind(path).await?;
+        let mut shutdown = Shutdown::listen()?;
+        let result = self
+            .serve_listener(listener, async move { shutdown.requested().await })
+            .await;
+        let _ = std::fs::remove_file(path);
+        result
+    }
+
+    /// Create the control socket at `path`, usable by the owner only
+    ///
+    /// The socket is bound in a directory only the owner can enter and moved
+    /// to `path` once its permissions are set, so no one else can connect in
+    /// between. A stale socket left behind by a daemon that didn't shut down
+    /// cleanly is replaced; a live one, or anything at `path` that isn't a
+    /// socket of the current user, is an error.
+    pub async fn bind(path: &Path) -> Result<UnixListener> {
+        let name = path.file_name().ok_or_else(|| {
+            RconError::InvalidConfig(format!("Invalid socket path {}", path.display()))
+        })?;
+        let parent = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        let mut staging_name = std::ffi::OsString::from(".");
+        staging_name.push(name);
+        staging_name.push(format!(".{}", std::process::id()));
+        let staging = parent.join(staging_name);
+
+        std::fs::DirBuilder::new().mode(0o700).create(&staging)?;
+        let result = Self::bind_staged(path, &staging).await;
+        let _ = std::fs::remove_dir_all(&staging);
+        let listener = result?;
+        info!("Daemon listening on {}", path.display());
+        Ok(listener)
+    }
+
+    async fn bind_staged(path: &Path, staging: &Path) -> Result<UnixListener> {
+        let uid = std::fs::metadata(staging)?.uid();
+        match std::fs::symlink_metadata(path) {
+            Ok(existing) => {
+                if existing.uid() != uid || !existing.file_type().is_socket() {
+                    return Err(RconError::InvalidConfig(format!(
+                        "Refusing to replace {}, which is not a socket of the current user",
+                        path.display()
+                    )));
+                }
+                if UnixStream::connect(path).await.is_ok() {
+                    return Err(RconError::InvalidConfig(format!(
+                        "A daemon is already listening on {}",
+                        path.display()
+                    )));
+                }
+                std::fs::remove_file(path)?;
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        let staged = staging.join("rcon-cli.sock");
+        let listener = UnixListener::bind(&staged)?;
+        std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o600))?;
+        std::fs::rename(&staged, path)?;
+        Ok(listener)
+    }
+
+    /// Accept requests on an already listening socket, such as one passed
+    /// in by systemd, until `shutdown` completes
+    ///
+    /// Requests already being executed are answered before returning, and
+    /// the server connections are closed.
+    pub async fn serve_listener(
+        self: Arc<Self>,
+        listener: UnixListener,
+        shutdown: impl Future<Output = ()>,
+    ) -> Result<()> {
+        let (stop, stopped) = watch::channel(false);
+        let mut clients = JoinSet::new();
+        tokio::pin!(shutdown);
+
+        let result = loop {
+            tokio::select! {
+                accepted = listener.accept() => match accepted {
+                    Ok((stream, _)) => {
+                        let daemon = Arc::clone(&self);
+                        let stopped = stopped.clone();
+                        clients.spawn(async move {
+                            if let Err(e) = daemon.handle(stream, stopped).await {
+                                debug!("Daemon client error: {}", e);
+                            }
+                        });
+                    }
+                    Err(e) => break Err(RconError::Network(e)),
+                },
+                // Reap finished clients so the set doesn't grow
+                Some(_) = clients.join_next(), if !clients.is_empty() => {}
+                _ = &mut shutdown => {
+                    info!("Daemon shutting down");
+                    break Ok(());
+                }
+            }
+        };
+
+        let _ = stop.send(true);
+        while clients.join_next().await.is_some() {}
+        self.close_all().await;
+        result
+    }
+
+    /// Close every server connection
+    async fn close_all(&self) {
+        let connections: Vec<Multiplexer> = self
+            .connections
+            .lock()
+            .await
+            .drain()
+            .map(|(_, connection)| connection)
+            .collect();
+        for connection in connections {
+            if let Err(e) = connection.close().await {
+                debug!(
+                    "Failed to close the connection to {}: {}",
+                    connection.address(),
+                    e
+                );
+            }
+        }
+    }
+
+    /// Serve requests from one local client until it disconnects or the
+    /// daemon stops
+    async fn handle(&self, stream: UnixStream, mut stopped: watch::Receiver<bool>) -> Result<()> {
+        // Rate limits and turns are per user, however many processes they run
+        let user = stream.peer_cred().map_or_else(
+            |_| "local".to_string(),
+            |cred| format!("uid {}", cred.uid()),
+        );
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        // A request sent before the previous reply
+        let mut next = None;
+
+        loop {
+            // Waiting for the next request is cancel-safe; executing one is
+            // only interrupted by the client going away
+            let line = match next.take() {
+                Some(line) => Some(line),
+                None => tokio::select! {
+                    line = lines.next_line() => line?,
+                    _ = stopped.wait_for(|stopped| *stopped) => None,
+                },
+            };
+            let Some(line) = line else {
+                break;
+            };
+
+            if let Ok(StatusRequest { status: true }) = serde_json::from_str(&line) {
+                let status = self.status().await;
+                write_line(&mut writer, &status).await?;
+                continue;
+            }
+
+            let reply = match serde_json::from_str::<DaemonRequest>(&line) {
+                Ok(request) => {
+                    let execution = self.execute(&user, request);
+                    tokio::pin!(execution);
+                    loop {
+                        tokio::select! {
+                            reply = &mut execution => break reply,
+                            line = lines.next_line(), if next.is_none() => match line {
+                                Ok(Some(line)) => next = Some(line),
+                                // Nobody is left to read the reply
+                                _ => {
+                                    debug!("Client disconnected, cancelling its command");
+                                    return Ok(());
+                                }
+                            },
+                        }
+                    }
+                }
+                Err(e) => DaemonResponse::failure(format!("Invalid request: {}", e)),
+            };
+            write_line(&mut writer, &reply).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Run a request on the matching connection, creating it if needed
+    async fn execute(&self, user: &str, request: DaemonRequest) -> DaemonResponse {
+        let deadline = request
+            .timeout_ms
+            .map(|timeout| Instant::now() + Duration::from_millis(timeout));
+        debug!(
+            "Daemon executing command for {}: {}",
+            request.address,
+            redact::command(&request.command)
+        );
+        let mut config = RconConfig::new(request.address, request.password)
+            .with_timeout(self.timeout)
+            .with_dialect(request.dialect.dialect())
+            .with_alternate_addresses(request.alternate_addresses);
+        config.local_addr = request.bind;
+        let connection = self.connection_for(config).await;
+        let priority = request
+            .priority
+            .unwrap_or_else(|| Priority::of(&request.command));
+
+        match connection
+            .execute_with_deadline(user, &request.command, priority, deadline)
+            .await
+        {
+            Ok(response) => DaemonResponse::success(response),
+            Err(e) => DaemonResponse::failure(e.to_string()),
+        }
+    }
+
+    /// Get or create the shared connection for a configuration
+    async fn connection_for(&self, config: RconConfig) -> Multiplexer {
+        let key = connection_key(&config);
+        let mut connections = self.connections.lock().await;
+        connections
+            .entry(key)
+            .or_insert_with(|| {
+                Multiplexer::new(LazyRconClient::new(config))
+                    .with_rate_limit(self.rate_limit)
+                    .with_max_queue(self.max_queue)
+            })
+            .clone()
+    }
+}
+
+impl DaemonResponse {
+    fn success(response: CommandResponse) -> Self {
+        Self {
+            ok: true,
+            response: Some(response.body),
+            error: None,
+            duration_us: Some(response.duration.as_micros() as u64),
+            request_id: Some(response.request_id),
+            fragments: Some(response.fragments),
+            fragment_sizes: Some(response.fragment_sizes),
+            reassembly_us: Some(response.reassembly.as_micros() as u64),
+        }
+    }
+
+    fn failure(error: String) -> Self {
+        Self {
+            ok: false,
+            response: None,
+            error: Some(error),
+            duration_us: None,
+            request_id: None,
+            fragments: None,
+            fragment_sizes: None,
+            reassembly_us: None,
+        }
+    }
+}
+
+/// Connection to a running daemon
+pub struct DaemonClient {
+    lines: tokio::io::Lines<BufReader<tokio::net::unix::OwnedReadHalf>>,
+    writer: OwnedWriteHalf,
+}
+
+impl DaemonClient {
+    /// Connect to the daemon's control socket
+    pub async fn connect(path: &Path) -> Result<Self> {
+        let stream = UnixStream::connect(path).await?;
+        let (reader, writer) = stream.into_split();
+        Ok(Self {
+            lines: BufReader::new(reader).lines(),
+            writer,
+        })
+    }
+
+    /// Ask for the queue of every connection
+    pub async fn status(&mut self) -> Result<DaemonStatus> {
+        let request = serde_json::to_string(&StatusRequest { status: true })
+            .map_err(|e| RconError::Protocol(format!("Failed to encode request: {}", e)))?;
+        self.writer
+            .write_all(format!("{}\n", request).as_bytes())
+            .await?;
+        let line = self
+            .lines
+            .next_line()
+            .await?
+            .ok_or(RconError::Disconnected)?;
+        serde_json::from_str(&line)
+            .map_err(|e| RconError::Protocol(format!("Invalid daemon reply: {}", e)))
+    }
+
+    /// Send a request and wait for the daemon's reply
+    ///
+    /// The duration reported is the daemon's server round trip, excluding
+    /// the local socket hop.
+    pub async fn execute(&mut self, request: &DaemonRequest) -> Result<CommandResponse> {
+        let mut encoded = serde_json::to_string(request)
+            .map_err(|e| RconError::Protocol(format!("Failed to encode request: {}", e)))?;
+        encoded.push('\n');
+        self.writer.write_all(encoded.as_bytes()).await?;
+
+        let line = self
+            .lines
+            .next_line()
+            .await?
+            .ok_or(RconError::Disconnected)?;
+        let reply: DaemonResponse = serde_json::from_str(&line)
+            .map_err(|e| RconError::Protocol(format!("Invalid daemon reply: {}", e)))?;
+
+        match reply {
+            DaemonResponse {
+                ok: true,
+                response,
+                duration_us,
+                request_id,
+                fragments,
+                fragment_sizes,
+                reassembly_us,
+                ..
+            } => Ok(CommandResponse {
+                body: response.unwrap_or_default(),
+                duration: Duration::from_micros(duration_us.unwrap_or_default()),
+                request_id: request_id.unwrap_or_default(),
+                fragments: fragments.unwrap_or_default(),
+                fragment_sizes: fragment_sizes.unwrap_or_default(),
+                reassembly: Duration::from_micros(reassembly_us.unwrap_or_default()),
+            }),
+            DaemonResponse { error, .. } => Err(RconError::CommandFailed(
+                error.unwrap_or_else(|| "Unknown daemon error".to_string()),
+            )),
+        }
+    }
+}
@@ -0,0 +1,268 @@
+//! Exactly-once delivery of web shop rewards
+//!
+//! `rcon-cli deliver` sends a purchase's reward commands under an
+//! idempotency key, typically the order ID, and records every step in a
+//! JSON Lines ledger. Running it again with the same key, after a retry or
+//! a crash, sends only what hasn't been answered yet; a delivered key sends
+//! nothing. Unlike the [queue](crate::queue), which delivers at least once,
+//! a reward is never given twice.
+//!
+//! A `sending` entry is synced to disk before each command and a `sent`
+//! entry once the server answered it. RCON can't tell whether a command
+//! ran when its response is lost, so such a command is *unconfirmed*: later
+//! attempts refuse to send it again until someone checked the server and
+//! asked to resend it. Missing a reward is recoverable, giving it twice is
+//! not.
+//!
+//! The ledger is a [`StateFile`] and every delivery holds its lock
+//! throughout, so concurrent attempts with the same key can't both send.
+
+use crate::error::{RconError, Result};
+use crate::executor::RconExecutor;
+use crate::state_file::StateFile;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Default ledger file, `<data dir>/rcon-cli/deliveries.jsonl`
+pub fn default_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("rcon-cli").join("deliveries.jsonl"))
+}
+
+/// One line of the ledger
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    pub key: String,
+    pub at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub event: LedgerEvent,
+}
+
+/// A step of a delivery
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum LedgerEvent {
+    /// The key was first used, for these commands
+    Started {
+        /// Profile name or address the commands are for
+        server: String,
+        commands: Vec<String>,
+    },
+    /// The command at `index` is about to be sent
+    Sending { index: usize },
+    /// The server answered the command at `index`
+    Sent { index: usize, response: String },
+}
+
+/// How far a command of a delivery got
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandState {
+    /// Not sent yet
+    Pending,
+    /// Sent, but no response was recorded
+    Unconfirmed,
+    /// Answered by the server
+    Sent,
+}
+
+impl CommandState {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CommandState::Pending => "pending",
+            CommandState::Unconfirmed => "unconfirmed",
+            CommandState::Sent => "sent",
+        }
+    }
+}
+
+/// A delivery as recorded in the ledger
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Delivery {
+    pub key: String,
+    pub server: String,
+    pub commands: Vec<String>,
+    /// State of each command
+    pub states: Vec<CommandState>,
+    /// Recorded response of each answered command
+    pub responses: BTreeMap<usize, String>,
+    pub started_at: DateTime<Utc>,
+}
+
+impl Delivery {
+    /// Whether the server answered every command
+    pub fn is_delivered(&self) -> bool {
+        self.states.iter().all(|state| *state == CommandState::Sent)
+    }
+
+    /// Apply the entries of a key, in ledger order
+    fn replay<'a>(entries: impl IntoIterator<Item = &'a LedgerEntry>) -> Option<Self> {
+        let mut delivery: Option<Self> = None;
+        for entry in entries {
+            match (&entry.event, delivery.as_mut()) {
+                (LedgerEvent::Started { server, commands }, None) => {
+                    delivery = Some(Self {
+                        key: entry.key.clone(),
+                        server: server.clone(),
+                        commands: commands.clone(),
+                        states: vec![CommandState::Pending; commands.len()],
+                        responses: BTreeMap::new(),
+                        started_at: entry.at,
+                    });
+                }
+                (LedgerEvent::Sending { index }, Some(delivery)) => {
+                    if let Some(state) = delivery.states.get_mut(*index) {
+                        if *state == CommandState::Pending {
+                            *state = CommandState::Unconfirmed;
+                        }
+                    }
+                }
+                (LedgerEvent::Sent { index, response }, Some(delivery)) => {
+                    if let Some(state) = delivery.states.get_mut(*index) {
+                        *state = CommandState::Sent;
+                        delivery.responses.insert(*index, response.clone());
+                    }
+                }
+                _ => {}
+            }
+        }
+        delivery
+    }
+}
+
+/// What a call to [`DeliveryLedger::deliver`] did
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeliveryReport {
+    /// The delivery afterwards
+    pub delivery: Delivery,
+    /// Indices of the commands this call sent; empty if the key had been
+    /// delivered already
+    pub sent: Vec<usize>,
+}
+
+/// A ledger file
+#[derive(Debug, Clone)]
+pub struct DeliveryLedger {
+    file: StateFile,
+}
+
+impl DeliveryLedger {
+    /// Create the file and its directory if needed
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        Ok(Self {
+            file: StateFile::create("Ledger", path)?,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        self.file.path()
+    }
+
+    /// Append an entry, returning once it is on disk
+    fn append(&self, key: &str, event: LedgerEvent) -> Result<()> {
+        self.file.append_line(&LedgerEntry {
+            key: key.to_string(),
+            at: Utc::now(),
+            event,
+        })
+    }
+
+    fn find(&self, key: &str) -> Result<Option<Delivery>> {
+        let entries: Vec<LedgerEntry> = self.file.read_lines()?;
+        Ok(Delivery::replay(
+            entries.iter().filter(|entry| entry.key == key),
+        ))
+    }
+
+    /// The delivery recorded under `key`, if any
+    pub fn delivery(&self, key: &str) -> Result<Option<Delivery>> {
+        let _lock = self.file.lock()?;
+        self.find(key)
+    }
+
+    /// Send `commands` to `server` once for `key`
+    ///
+    /// Commands already answered in an earlier attempt are skipped. A key
+    /// first used with other commands or for another server is rejected,
+    /// and so is an unconfirmed command unless `resend_unconfirmed` is
+    /// set. An error while sending leaves that command unconfirmed.
+    ///
+    /// ```
+    /// use rcon_cli::delivery::DeliveryLedger;
+    /// use rcon_cli::mock::MockRconClient;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> rcon_cli::Result<()> {
+    /// let path = std::env::temp_dir().join(format!("rcon-ledger-{}.jsonl", std::process::id()));
+    /// let ledger = DeliveryLedger::open(&path)?;
+    /// let commands = vec!["give Steve diamond 5".to_string()];
+    ///
+    /// let mut client = MockRconClient::new().respond("give Steve diamond 5", "Gave 5 [Diamond] to Steve");
+    /// let report = ledger.deliver("order-1", "survival", &commands, &mut client, false).await?;
+    /// assert_eq!(report.sent, [0]);
+    ///
+    /// // A retry with the same key sends nothing
+    /// let report = ledger.deliver("order-1", "survival", &commands, &mut client, false).await?;
+    /// assert!(report.sent.is_empty() && report.delivery.is_delivered());
+    /// # std::fs::remove_file(&path).ok();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn deliver(
+        &self,
+        key: &str,
+        server: &str,
+        commands: &[String],
+        client: &mut dyn RconExecutor,
+        resend_unconfirmed: bool,
+    ) -> Result<DeliveryReport> {
+        let _lock = self.file.lock()?;
+        let mut delivery = match self.find(key)? {
+            Some(delivery) if delivery.server != server || delivery.commands != commands => {
+                return Err(RconError::KeyConflict(format!(
+                    "'{}' was used for other commands on {} at {}",
+                    key,
+                    delivery.server,
+                    delivery.started_at.to_rfc3339()
+                )));
+            }
+            Some(delivery) => delivery,
+            None => {
+                let started = LedgerEvent::Started {
+                    server: server.to_string(),
+                    commands: commands.to_vec(),
+                };
+                self.append(key, started)?;
+                self.find(key)?.expect("delivery was just started")
+            }
+        };
+
+        let mut sent = Vec::new();
+        for (index, command) in commands.iter().enumerate() {
+            match delivery.states[index] {
+                CommandState::Sent => continue,
+                CommandState::Unconfirmed if !resend_unconfirmed => {
+                    return Err(RconError::DeliveryUnconfirmed(format!(
+                        "'{}' of '{}' was sent without a recorded response and may have run",
+                        command, key
+                    )));
+                }
+                _ => {}
+            }
+            self.append(key, LedgerEvent::Sending { index })?;
+            delivery.states[index] = CommandState::Unconfirmed;
+            let response = client.execute_command(command).await?;
+            self.append(
+                key,
+                LedgerEvent::Sent {
+                    index,
+                    response: response.clone(),
+                },
+            )?;
+            delivery.states[index] = CommandState::Sent;
+            delivery.responses.insert(index, response);
+            sent.push(index);
+        }
+        Ok(DeliveryReport { delivery, sent })
+    }
+}
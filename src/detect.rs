@@ -0,0 +1,261 @@
+//! Server flavor detection
+//!
+//! Bukkit-derived servers and proxies name themselves in response to
+//! `version`. Vanilla, Fabric and Forge servers reject that command, so Forge
+//! and NeoForge are recognized by the commands they register, and the game
+//! version is narrowed down by which newer vanilla commands `help` knows.
+//! Fabric and Quilt register no commands of their own and pass for vanilla.
+
+use crate::client::RconClient;
+use crate::error::Result;
+use crate::items::McVersion;
+use crate::normalize::normalize;
+use crate::parsers::parse_server_version;
+use std::fmt;
+
+/// Server software
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Flavor {
+    Vanilla,
+    Paper,
+    Purpur,
+    Pufferfish,
+    Folia,
+    Spigot,
+    CraftBukkit,
+    Fabric,
+    Quilt,
+    Forge,
+    NeoForge,
+    Sponge,
+    Velocity,
+    Waterfall,
+    BungeeCord,
+}
+
+impl Flavor {
+    const ALL: [Flavor; 15] = [
+        Flavor::Vanilla,
+        Flavor::Paper,
+        Flavor::Purpur,
+        Flavor::Pufferfish,
+        Flavor::Folia,
+        Flavor::Spigot,
+        Flavor::CraftBukkit,
+        Flavor::Fabric,
+        Flavor::Quilt,
+        Flavor::Forge,
+        Flavor::NeoForge,
+        Flavor::Sponge,
+        Flavor::Velocity,
+        Flavor::Waterfall,
+        Flavor::BungeeCord,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Flavor::Vanilla => "Vanilla",
+            Flavor::Paper => "Paper",
+            Flavor::Purpur => "Purpur",
+            Flavor::Pufferfish => "Pufferfish",
+            Flavor::Folia => "Folia",
+            Flavor::Spigot => "Spigot",
+            Flavor::CraftBukkit => "CraftBukkit",
+            Flavor::Fabric => "Fabric",
+            Flavor::Quilt => "Quilt",
+            Flavor::Forge => "Forge",
+            Flavor::NeoForge => "NeoForge",
+            Flavor::Sponge => "Sponge",
+            Flavor::Velocity => "Velocity",
+            Flavor::Waterfall => "Waterfall",
+            Flavor::BungeeCord => "BungeeCord",
+        }
+    }
+
+    /// The flavor with this name, ignoring case
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|flavor| flavor.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Whether the server runs Bukkit plugins and has the `plugins` command
+    pub fn has_plugins(self) -> bool {
+        matches!(
+            self,
+            Flavor::Paper
+                | Flavor::Purpur
+                | Flavor::Pufferfish
+                | Flavor::Folia
+                | Flavor::Spigot
+                | Flavor::CraftBukkit
+        )
+    }
+
+    /// Whether the server is a proxy in front of game servers
+    pub fn is_proxy(self) -> bool {
+        matches!(
+            self,
+            Flavor::Velocity | Flavor::Waterfall | Flavor::BungeeCord
+        )
+    }
+
+    /// Command listing the installed mods, for mod loaders that have one
+    pub fn mod_list_command(self) -> Option<&'static str> {
+        match self {
+            Flavor::Forge => Some("forge mods"),
+            Flavor::NeoForge => Some("neoforge mods"),
+            _ => None,
+        }
+    }
+
+    /// Where the server reports ticks per second, if it does
+    ///
+    /// ```
+    /// use rcon_cli::detect::{Flavor, TpsSource};
+    /// use rcon_cli::items::McVersion;
+    ///
+    /// assert_eq!(Flavor::Paper.tps_source(None), Some(TpsSource::Bukkit));
+    /// assert_eq!(Flavor::Fabric.tps_source(Some(McVersion::new(1, 21, 0))), Some(TpsSource::TickQuery));
+    /// assert_eq!(Flavor::Vanilla.tps_source(Some(McVersion::new(1, 20, 1))), None);
+    /// ```
+    pub fn tps_source(self, version: Option<McVersion>) -> Option<TpsSource> {
+        match self {
+            flavor if flavor.has_plugins() => Some(TpsSource::Bukkit),
+            Flavor::Forge => Some(TpsSource::Forge),
+            Flavor::NeoForge => Some(TpsSource::NeoForge),
+            flavor if flavor.is_proxy() => None,
+            _ if version.is_some_and(|version| version < TpsSource::TICK_QUERY) => None,
+            _ => Some(TpsSource::TickQuery),
+        }
+    }
+}
+
+impl fmt::Display for Flavor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Command reporting ticks per second
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TpsSource {
+    /// Bukkit's `tps`, averaged over 1, 5 and 15 minutes
+    Bukkit,
+    /// `forge tps`, per dimension
+    Forge,
+    /// `neoforge tps`, per dimension
+    NeoForge,
+    /// Vanilla `tick query`
+    TickQuery,
+}
+
+impl TpsSource {
+    /// First version with `tick query`
+    pub const TICK_QUERY: McVersion = McVersion::new(1, 20, 3);
+
+    pub fn command(self) -> &'static str {
+        match self {
+            TpsSource::Bukkit => "tps",
+            TpsSource::Forge => "forge tps",
+            TpsSource::NeoForge => "neoforge tps",
+            TpsSource::TickQuery => "tick query",
+        }
+    }
+}
+
+/// What [`detect`] found out about a server
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ServerInfo {
+    pub flavor: Flavor,
+    /// Game version reported by the server
+    pub version: Option<McVersion>,
+    /// For servers that don't report their version, the oldest release
+    /// having every command the server knows
+    pub min_version: Option<McVersion>,
+}
+
+impl ServerInfo {
+    /// The reported version, or else the oldest one the server can be
+    ///
+    /// Good for picking command syntax, which only changes with releases
+    /// that also added commands; too old to rule out newer items.
+    pub fn assumed_version(&self) -> Option<McVersion> {
+        self.version.or(self.min_version)
+    }
+}
+
+/// Vanilla commands by the release that added them, newest first
+///
+/// Commands are never removed, so the server is at least as new as the
+/// newest one it knows.
+const VERSION_PROBES: [(&str, McVersion); 12] = [
+    ("rotate", McVersion::new(1, 21, 2)),
+    ("transfer", McVersion::new(1, 20, 5)),
+    ("tick", McVersion::new(1, 20, 3)),
+    ("random", McVersion::new(1, 20, 2)),
+    ("return", McVersion::new(1, 20, 0)),
+    ("damage", McVersion::new(1, 19, 4)),
+    ("fillbiome", McVersion::new(1, 19, 3)),
+    ("place", McVersion::new(1, 19, 0)),
+    ("jfr", McVersion::new(1, 18, 0)),
+    ("item", McVersion::new(1, 17, 0)),
+    ("attribute", McVersion::new(1, 16, 0)),
+    ("forceload", McVersion::new(1, 14, 0)),
+];
+
+/// Whether the server has a command, going by `help <command>`
+async fn knows_command(client: &mut RconClient, command: &str) -> Result<bool> {
+    let response = client.execute_command(format!("help {}", command)).await?;
+    let response = normalize(&response);
+    let response = response.trim();
+    Ok(!response.is_empty() && !response.starts_with("Unknown or incomplete command"))
+}
+
+/// Fingerprint the server software and version
+///
+/// Costs one `version` command for servers that report themselves, and a
+/// few `help` lookups for those that don't. [`RconClient::server_info`]
+/// caches the result per connection.
+pub async fn detect(client: &mut RconClient) -> Result<ServerInfo> {
+    let reported = parse_server_version(&client.execute_command("version").await?);
+    if let Some(flavor) = reported
+        .as_ref()
+        .and_then(|reported| Flavor::from_name(&reported.flavor))
+        .filter(|flavor| *flavor != Flavor::Vanilla)
+    {
+        return Ok(ServerInfo {
+            flavor,
+            version: reported
+                .and_then(|reported| reported.version)
+                .and_then(|version| version.parse().ok()),
+            min_version: None,
+        });
+    }
+
+    let flavor = if knows_command(client, "neoforge").await? {
+        Flavor::NeoForge
+    } else if knows_command(client, "forge").await? {
+        Flavor::Forge
+    } else {
+        Flavor::Vanilla
+    };
+
+    // Binary search for the newest known command; the probes are ordered
+    // newest first, so known ones form a suffix
+    let (mut low, mut high) = (0, VERSION_PROBES.len());
+    while low < high {
+        let middle = (low + high) / 2;
+        if knows_command(client, VERSION_PROBES[middle].0).await? {
+            high = middle;
+        } else {
+            low = middle + 1;
+        }
+    }
+
+    Ok(ServerInfo {
+        flavor,
+        version: None,
+        min_version: VERSION_PROBES.get(low).map(|(_, version)| *version),
+    })
+}
@@ -0,0 +1,178 @@
+use crate::outcome::{ErrorPattern, FACTORIO_ERRORS, MINECRAFT_ERRORS, SOURCE_ERRORS};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+
+/// How a server acknowledges an authentication request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthFlow {
+    /// The auth response is the first packet sent back
+    Direct,
+    /// An empty `RESPONSE_VALUE` packet precedes the auth response
+    EmptyResponseFirst,
+}
+
+/// How the end of a (possibly fragmented) command response is detected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FragmentTermination {
+    /// A fragment shorter than the maximum response payload is the last one
+    ShortFragment,
+    /// An empty packet is sent after each command; its echo marks the end
+    MirrorPacket,
+    /// Responses are never split across packets
+    SinglePacket,
+}
+
+/// Game-specific variations of the RCON protocol
+///
+/// The wire format is shared by every implementation, but servers disagree
+/// on payload limits, how authentication is acknowledged, how fragmented
+/// responses end, and which text encoding is used.
+pub trait Dialect: fmt::Debug + Send + Sync {
+    /// Human-readable name of the dialect
+    fn name(&self) -> &'static str;
+
+    /// Maximum payload size accepted in client-to-server packets
+    fn max_request_payload(&self) -> usize;
+
+    /// Maximum payload size of a single server-to-client packet
+    fn max_response_payload(&self) -> usize;
+
+    /// Authentication acknowledgement behaviour
+    fn auth_flow(&self) -> AuthFlow;
+
+    /// Strategy used to detect the last fragment of a response
+    fn termination(&self) -> FragmentTermination;
+
+    /// Encode a payload for the wire
+    fn encode(&self, payload: &str) -> Vec<u8> {
+        payload.as_bytes().to_vec()
+    }
+
+    /// Decode a payload received from the wire
+    fn decode(&self, bytes: &[u8]) -> String {
+        String::from_utf8_lossy(bytes).into_owned()
+    }
+
+    /// Phrasings of responses reporting that a command failed
+    fn error_patterns(&self) -> &'static [ErrorPattern] {
+        &[]
+    }
+}
+
+/// Minecraft: Java Edition (vanilla and its forks)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MinecraftJava;
+
+impl Dialect for MinecraftJava {
+    fn name(&self) -> &'static str {
+        "minecraft"
+    }
+
+    fn max_request_payload(&self) -> usize {
+        1446
+    }
+
+    fn max_response_payload(&self) -> usize {
+        4096
+    }
+
+    fn auth_flow(&self) -> AuthFlow {
+        AuthFlow::Direct
+    }
+
+    fn termination(&self) -> FragmentTermination {
+        FragmentTermination::ShortFragment
+    }
+
+    fn error_patterns(&self) -> &'static [ErrorPattern] {
+        MINECRAFT_ERRORS
+    }
+}
+
+/// Valve Source engine servers
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Source;
+
+impl Dialect for Source {
+    fn name(&self) -> &'static str {
+        "source"
+    }
+
+    fn max_request_payload(&self) -> usize {
+        4086
+    }
+
+    fn max_response_payload(&self) -> usize {
+        4086
+    }
+
+    fn auth_flow(&self) -> AuthFlow {
+        AuthFlow::EmptyResponseFirst
+    }
+
+    fn termination(&self) -> FragmentTermination {
+        FragmentTermination::MirrorPacket
+    }
+
+    fn error_patterns(&self) -> &'static [ErrorPattern] {
+        SOURCE_ERRORS
+    }
+}
+
+/// Factorio, which speaks Source RCON but never fragments responses
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Factorio;
+
+impl Dialect for Factorio {
+    fn name(&self) -> &'static str {
+        "factorio"
+    }
+
+    fn max_request_payload(&self) -> usize {
+        4086
+    }
+
+    fn max_response_payload(&self) -> usize {
+        usize::MAX
+    }
+
+    fn auth_flow(&self) -> AuthFlow {
+        AuthFlow::EmptyResponseFirst
+    }
+
+    fn termination(&self) -> FragmentTermination {
+        FragmentTermination::SinglePacket
+    }
+
+    fn error_patterns(&self) -> &'static [ErrorPattern] {
+        FACTORIO_ERRORS
+    }
+}
+
+/// Built-in dialects selectable from configuration
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum DialectKind {
+    /// Minecraft: Java Edition (default)
+    #[default]
+    Minecraft,
+    /// Valve Source engine
+    Source,
+    /// Factorio
+    Factorio,
+}
+
+impl DialectKind {
+    /// Instantiate the dialect implementation
+    pub fn dialect(self) -> Arc<dyn Dialect> {
+        match self {
+            DialectKind::Minecraft => Arc::new(MinecraftJava),
+            DialectKind::Source => Arc::new(Source),
+            DialectKind::Factorio => Arc::new(Factorio),
+        }
+    }
+}
@@ -0,0 +1,460 @@
+//! Compatibility probe for servers with unusual RCON implementations
+//!
+//! [`diagnose`] connects to a server and checks the behaviour that most
+//! often breaks RCON clients: which software is answering, how long a
+//! command may be, and whether long responses are split into fragments
+//! the way the selected dialect expects.
+
+use crate::client::{RconClient, RconConfig};
+use crate::dialect::{AuthFlow, Dialect, FragmentTermination};
+use crate::error::{RconError, Result};
+use crate::parsers::{parse_server_version, ServerVersion};
+use serde::Serialize;
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// Upper bound for the command length probe, in bytes
+pub const DEFAULT_PAYLOAD_LIMIT: usize = 8192;
+
+/// Command expected to produce a response longer than one packet
+pub const DEFAULT_FRAGMENTATION_COMMAND: &str = "help";
+
+/// Number of round trips averaged for the latency check
+const LATENCY_SAMPLES: u32 = 3;
+
+/// Prefix of the padded command sent by the payload probe
+///
+/// The leading slash keeps servers that treat plain text as chat (such as
+/// Factorio) from broadcasting the probe.
+const PROBE_COMMAND: &str = "/rcon-cli-doctor ";
+
+/// Outcome of a single check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Ok,
+    Warning,
+    Failed,
+    /// Not run, or not conclusive for this server
+    Skipped,
+}
+
+impl fmt::Display for CheckStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CheckStatus::Ok => "ok",
+            CheckStatus::Warning => "warning",
+            CheckStatus::Failed => "failed",
+            CheckStatus::Skipped => "skipped",
+        })
+    }
+}
+
+/// Result of one compatibility check
+#[derive(Debug, Clone, Serialize)]
+pub struct Check {
+    /// Stable identifier, e.g. `connect` or `max_payload`
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+impl Check {
+    fn new(name: &'static str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Settings for [`diagnose`]
+#[derive(Debug, Clone)]
+pub struct DoctorOptions {
+    /// Longest command tried by the payload probe
+    pub payload_limit: usize,
+    /// Command used to provoke a fragmented response
+    pub fragmentation_command: String,
+}
+
+impl Default for DoctorOptions {
+    fn default() -> Self {
+        Self {
+            payload_limit: DEFAULT_PAYLOAD_LIMIT,
+            fragmentation_command: DEFAULT_FRAGMENTATION_COMMAND.to_string(),
+        }
+    }
+}
+
+/// Compatibility report produced by [`diagnose`]
+#[derive(Debug, Clone, Default)]
+pub struct DoctorReport {
+    /// Checks in the order they ran
+    pub checks: Vec<Check>,
+    /// Detected server software, if recognized
+    pub server: Option<ServerVersion>,
+    /// Longest command the server accepted, in bytes
+    pub max_payload: Option<usize>,
+}
+
+impl DoctorReport {
+    /// Whether no check failed
+    pub fn is_healthy(&self) -> bool {
+        self.checks
+            .iter()
+            .all(|check| check.status != CheckStatus::Failed)
+    }
+
+    /// Number of checks with the given status
+    pub fn count(&self, status: CheckStatus) -> usize {
+        self.checks
+            .iter()
+            .filter(|check| check.status == status)
+            .count()
+    }
+}
+
+/// Run every compatibility check against a server
+///
+/// Connection failures are reported as a failed `connect` check rather
+/// than an error, with the remaining checks skipped.
+pub async fn diagnose(config: &RconConfig, options: &DoctorOptions) -> DoctorReport {
+    let mut report = DoctorReport::default();
+
+    let started = Instant::now();
+    let mut client = match RconClient::connect(config.clone()).await {
+        Ok(client) => client,
+        Err(e) => {
+            report
+                .checks
+                .push(Check::new("connect", CheckStatus::Failed, e.to_string()));
+            for name in ["latency", "server", "fragmentation", "max_payload"] {
+                report
+                    .checks
+                    .push(Check::new(name, CheckStatus::Skipped, "Not connected"));
+            }
+            return report;
+        }
+    };
+    let mut detail = format!(
+        "Authenticated with {} in {}",
+        client.server_address(),
+        format_duration(started.elapsed())
+    );
+    if client.credential_index() > 0 {
+        detail.push_str(&format!(
+            " using fallback password #{}",
+            client.credential_index()
+        ));
+    }
+    report
+        .checks
+        .push(Check::new("connect", CheckStatus::Ok, detail));
+
+    report.checks.push(check_latency(&mut client, config).await);
+
+    let (check, server) = check_server(&mut client, config).await;
+    report.checks.push(check);
+    report.server = server;
+
+    report
+        .checks
+        .push(check_fragmentation(&mut client, config, &options.fragmentation_command).await);
+
+    let (check, max_payload) = check_max_payload(config, options.payload_limit).await;
+    report.checks.push(check);
+    report.max_payload = max_payload;
+
+    report
+}
+
+/// Average a few command round trips
+async fn check_latency(client: &mut RconClient, config: &RconConfig) -> Check {
+    let mut total = Duration::ZERO;
+    for _ in 0..LATENCY_SAMPLES {
+        match with_timeout(config, client.execute_command_detailed("list")).await {
+            Ok(response) => total += response.duration,
+            Err(e) => return Check::new("latency", CheckStatus::Failed, e.to_string()),
+        }
+    }
+
+    Check::new(
+        "latency",
+        CheckStatus::Ok,
+        format!(
+            "{} average over {} round trips",
+            format_duration(total / LATENCY_SAMPLES),
+            LATENCY_SAMPLES
+        ),
+    )
+}
+
+/// Identify the server software via `version`
+async fn check_server(
+    client: &mut RconClient,
+    config: &RconConfig,
+) -> (Check, Option<ServerVersion>) {
+    let response = match with_timeout(config, client.execute_command("version")).await {
+        Ok(response) => response,
+        Err(e) => {
+            return (
+                Check::new("server", CheckStatus::Failed, e.to_string()),
+                None,
+            )
+        }
+    };
+
+    match parse_server_version(&response) {
+        Some(server) => {
+            let detail = match &server.version {
+                Some(version) => format!("{} {}", server.flavor, version),
+                None => server.flavor.clone(),
+            };
+            (
+                Check::new(
+                    "server",
+                    CheckStatus::Ok,
+                    format!("{} ({} dialect)", detail, config.dialect.name()),
+                ),
+                Some(server),
+            )
+        }
+        None => (
+            Check::new(
+                "server",
+                CheckStatus::Warning,
+                format!(
+                    "Unrecognized response to 'version': {}",
+                    response.chars().take(80).collect::<String>()
+                ),
+            ),
+            None,
+        ),
+    }
+}
+
+/// Check that a long response arrives complete
+async fn check_fragmentation(client: &mut RconClient, config: &RconConfig, command: &str) -> Check {
+    let max_fragment = config.dialect.max_response_payload();
+
+    match with_timeout(config, client.execute_command_detailed(command)).await {
+        Ok(response) if response.fragments > 1 => Check::new(
+            "fragmentation",
+            CheckStatus::Ok,
+            format!(
+                "'{}' returned {} bytes in {} fragments, reassembled",
+                command,
+                response.body.len(),
+                response.fragments
+            ),
+        ),
+        Ok(response) if response.body.len() < max_fragment => Check::new(
+            "fragmentation",
+            CheckStatus::Skipped,
+            format!(
+                "'{}' returned {} bytes in one packet; choose a command with longer output to exercise fragmentation",
+                command,
+                response.body.len()
+            ),
+        ),
+        Ok(response) => Check::new(
+            "fragmentation",
+            CheckStatus::Ok,
+            format!(
+                "'{}' returned {} bytes in a single packet",
+                command,
+                response.body.len()
+            ),
+        ),
+        Err(RconError::Timeout) => {
+            let detail = match config.dialect.termination() {
+                FragmentTermination::ShortFragment => format!(
+                    "No final fragment for '{}' within {}: the server likely cuts responses at {} bytes without continuing them; try --dialect source",
+                    command,
+                    format_duration(config.timeout),
+                    max_fragment
+                ),
+                _ => format!(
+                    "No complete response for '{}' within {}",
+                    command,
+                    format_duration(config.timeout)
+                ),
+            };
+            // The rest of the response may still arrive; start over
+            let _ = client.reconnect().await;
+            Check::new("fragmentation", CheckStatus::Warning, detail)
+        }
+        Err(e) => Check::new("fragmentation", CheckStatus::Failed, e.to_string()),
+    }
+}
+
+/// Find the longest command the server accepts
+///
+/// Uses a separate connection, as rejected commands often close it.
+async fn check_max_payload(config: &RconConfig, limit: usize) -> (Check, Option<usize>) {
+    let documented = config.dialect.max_request_payload();
+    let probe_config = config
+        .clone()
+        .with_dialect(Arc::new(Unrestricted(Arc::clone(&config.dialect))));
+
+    let mut prober = match PayloadProber::connect(probe_config).await {
+        Ok(prober) => prober,
+        Err(e) => {
+            return (
+                Check::new("max_payload", CheckStatus::Failed, e.to_string()),
+                None,
+            )
+        }
+    };
+
+    match prober.find_limit(documented.min(limit), limit).await {
+        Ok(max) if max >= limit => (
+            Check::new(
+                "max_payload",
+                CheckStatus::Ok,
+                format!(
+                    "Commands of at least {} bytes accepted (dialect assumes {})",
+                    max, documented
+                ),
+            ),
+            Some(max),
+        ),
+        Ok(max) if max >= documented => (
+            Check::new(
+                "max_payload",
+                CheckStatus::Ok,
+                format!(
+                    "Commands up to {} bytes accepted (dialect assumes {})",
+                    max, documented
+                ),
+            ),
+            Some(max),
+        ),
+        Ok(max) => (
+            Check::new(
+                "max_payload",
+                CheckStatus::Warning,
+                format!(
+                    "Commands longer than {} bytes fail, below the {} bytes the dialect assumes; long commands will be cut off or dropped",
+                    max, documented
+                ),
+            ),
+            Some(max),
+        ),
+        Err(e) => (Check::new("max_payload", CheckStatus::Failed, e.to_string()), None),
+    }
+}
+
+/// Sends padded commands of a given size, reconnecting after rejections
+struct PayloadProber {
+    client: RconClient,
+    timeout: Duration,
+}
+
+impl PayloadProber {
+    async fn connect(config: RconConfig) -> Result<Self> {
+        let timeout = config.timeout;
+        Ok(Self {
+            client: RconClient::connect(config).await?,
+            timeout,
+        })
+    }
+
+    /// Binary search for the largest accepted size, starting from a guess
+    async fn find_limit(&mut self, guess: usize, limit: usize) -> Result<usize> {
+        let smallest = PROBE_COMMAND.len();
+        if self.accepts(limit).await? {
+            return Ok(limit);
+        }
+
+        // `accepted` always works and `rejected` never does
+        let (mut accepted, mut rejected) = if guess < limit && self.accepts(guess).await? {
+            (guess, limit)
+        } else if self.accepts(smallest).await? {
+            (smallest, guess)
+        } else {
+            return Err(RconError::CommandFailed(
+                "the server rejected even the shortest probe command".to_string(),
+            ));
+        };
+
+        while rejected - accepted > 1 {
+            let size = accepted + (rejected - accepted) / 2;
+            if self.accepts(size).await? {
+                accepted = size;
+            } else {
+                rejected = size;
+            }
+        }
+        Ok(accepted)
+    }
+
+    /// Whether a command of `size` bytes gets a response
+    async fn accepts(&mut self, size: usize) -> Result<bool> {
+        let padding = size.saturating_sub(PROBE_COMMAND.len());
+        let command = format!("{}{}", PROBE_COMMAND, "x".repeat(padding));
+
+        let accepted = matches!(
+            tokio::time::timeout(self.timeout, self.client.execute_command(&command)).await,
+            Ok(Ok(_))
+        );
+        debug!("Probe of {} bytes accepted: {}", size, accepted);
+
+        if !accepted {
+            self.client.reconnect().await?;
+        }
+        Ok(accepted)
+    }
+}
+
+/// A dialect with the request size limit lifted, for probing
+#[derive(Debug)]
+struct Unrestricted(Arc<dyn Dialect>);
+
+impl Dialect for Unrestricted {
+    fn name(&self) -> &'static str {
+        self.0.name()
+    }
+
+    fn max_request_payload(&self) -> usize {
+        usize::MAX
+    }
+
+    fn max_response_payload(&self) -> usize {
+        self.0.max_response_payload()
+    }
+
+    fn auth_flow(&self) -> AuthFlow {
+        self.0.auth_flow()
+    }
+
+    fn termination(&self) -> FragmentTermination {
+        self.0.termination()
+    }
+
+    fn encode(&self, payload: &str) -> Vec<u8> {
+        self.0.encode(payload)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> String {
+        self.0.decode(bytes)
+    }
+}
+
+/// Bound a command by the connection timeout
+async fn with_timeout<T>(
+    config: &RconConfig,
+    operation: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    tokio::time::timeout(config.timeout, operation)
+        .await
+        .map_err(|_| RconError::Timeout)?
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.2}ms", duration.as_secs_f64() * 1000.0)
+}
@@ -0,0 +1,104 @@
+//! Dry runs that serialize packets without sending them
+//!
+//! A client configured with a [`DryRun`] never opens a connection. Every
+//! packet it would send is encoded as usual and handed to the dry run's
+//! callback, then answered in memory with an empty reply: logins succeed and
+//! commands produce no output. Callers therefore see exactly what a real run
+//! would send, up to the first response they need to interpret.
+
+use crate::dialect::Dialect;
+use crate::protocol::{packet_type, RconPacket};
+use std::collections::VecDeque;
+use std::fmt;
+use std::io;
+use std::sync::Arc;
+
+/// Callback receiving each packet and its encoded bytes
+pub type PacketHook = Arc<dyn Fn(&RconPacket, &[u8]) + Send + Sync>;
+
+/// Where the packets of a dry run go instead of the network
+///
+/// The callback also sees login packets, which carry the password, and the
+/// empty terminator packets some dialects send after each command.
+///
+/// ```
+/// use rcon_cli::dry_run::DryRun;
+/// use rcon_cli::{packet_type, RconConfig};
+///
+/// let dry_run = DryRun::new(|packet, _bytes| {
+///     if packet.packet_type == packet_type::EXECCOMMAND {
+///         println!("{}", packet.payload);
+///     }
+/// });
+/// let config = RconConfig::new("127.0.0.1:25575".parse().unwrap(), "secret")
+///     .with_dry_run(dry_run);
+/// ```
+#[derive(Clone)]
+pub struct DryRun {
+    on_packet: PacketHook,
+}
+
+impl DryRun {
+    pub fn new<F>(on_packet: F) -> Self
+    where
+        F: Fn(&RconPacket, &[u8]) + Send + Sync + 'static,
+    {
+        Self {
+            on_packet: Arc::new(on_packet),
+        }
+    }
+}
+
+impl fmt::Debug for DryRun {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DryRun").finish_non_exhaustive()
+    }
+}
+
+/// In-memory stand-in for a server connection
+pub(crate) struct Loopback {
+    dry_run: DryRun,
+    dialect: Arc<dyn Dialect>,
+    replies: VecDeque<u8>,
+}
+
+impl Loopback {
+    pub(crate) fn new(dry_run: DryRun, dialect: Arc<dyn Dialect>) -> Self {
+        Self {
+            dry_run,
+            dialect,
+            replies: VecDeque::new(),
+        }
+    }
+
+    /// Report a packet and queue the reply a cooperative server would send
+    pub(crate) fn send(&mut self, packet: &RconPacket, bytes: &[u8]) -> io::Result<()> {
+        (self.dry_run.on_packet)(packet, bytes);
+
+        // Auth responses reuse the command packet type
+        let reply_type = match packet.packet_type {
+            packet_type::AUTH => packet_type::EXECCOMMAND,
+            _ => packet_type::RESPONSE_VALUE,
+        };
+        let reply = RconPacket::new(packet.request_id, reply_type, "")
+            .encode(self.dialect.as_ref())
+            .map_err(io::Error::other)?;
+        self.replies.extend(reply);
+        Ok(())
+    }
+
+    /// Fill `buffer` from the queued replies
+    pub(crate) fn read_exact(&mut self, buffer: &mut [u8]) -> io::Result<()> {
+        if self.replies.len() < buffer.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "No reply pending in dry run",
+            ));
+        }
+        let length = buffer.len();
+        for (byte, reply) in buffer.iter_mut().zip(self.replies.drain(..length)) {
+            *byte = reply;
+        }
+        Ok(())
+    }
+}
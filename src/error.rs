@@ -7,6 +7,9 @@ pub enum RconError {
     #[error("Network error: {0}")]
     Network(#[from] io::Error),
 
+    #[error("Could not resolve address {0}")]
+    AddressResolution(String),
+
     #[error("Connection timeout")]
     Timeout,
 
@@ -25,8 +28,101 @@ pub enum RconError {
     #[error("Command execution failed: {0}")]
     CommandFailed(String),
 
+    #[error("Command denied: {0}")]
+    CommandDenied(String),
+
+    #[error("Stream desynchronized: {0}")]
+    Desynchronized(String),
+
+    #[error("Response too large: {0}")]
+    ResponseTooLarge(String),
+
+    #[error("Script error: {0}")]
+    Script(String),
+
+    #[error("Invalid NBT: {0}")]
+    Nbt(String),
+
     #[error("Invalid configuration: {0}")]
     InvalidConfig(String),
+
+    #[error("State file error: {0}")]
+    State(String),
+
+    #[error("Panel API error: {0}")]
+    Panel(String),
+
+    #[error("Kubernetes error: {0}")]
+    Kubernetes(String),
+
+    #[error("Grafana error: {0}")]
+    Grafana(String),
+
+    #[error("Metrics output error: {0}")]
+    Metrics(String),
+
+    #[error("MQTT error: {0}")]
+    Mqtt(String),
+
+    #[error("Server pin mismatch: {0}")]
+    PinMismatch(String),
+
+    #[error("Parser plugin error: {0}")]
+    Parser(String),
+
+    #[error("WebAssembly plugin error: {0}")]
+    Plugin(String),
+
+    #[error("Idempotency key conflict: {0}")]
+    KeyConflict(String),
+
+    #[error("Delivery unconfirmed: {0}")]
+    DeliveryUnconfirmed(String),
+
+    #[error("Server is crash looping: {0}")]
+    CrashLoop(String),
+
+    #[error("Rate limited: {0}")]
+    RateLimited(String),
+
+    #[error("Queue full: {0}")]
+    QueueFull(String),
+}
+
+impl RconError {
+    /// Stable machine-readable identifier for the error kind
+    pub fn code(&self) -> &'static str {
+        match self {
+            RconError::Network(_) => "network",
+            RconError::AddressResolution(_) => "address_resolution",
+            RconError::Timeout => "timeout",
+            RconError::AuthenticationFailed => "authentication_failed",
+            RconError::InvalidPacket(_) => "invalid_packet",
+            RconError::Protocol(_) => "protocol",
+            RconError::Disconnected => "disconnected",
+            RconError::CommandFailed(_) => "command_failed",
+            RconError::CommandDenied(_) => "command_denied",
+            RconError::Desynchronized(_) => "desynchronized",
+            RconError::ResponseTooLarge(_) => "response_too_large",
+            RconError::Script(_) => "script",
+            RconError::Nbt(_) => "invalid_nbt",
+            RconError::InvalidConfig(_) => "invalid_config",
+            RconError::State(_) => "state",
+            RconError::Panel(_) => "panel",
+            RconError::Kubernetes(_) => "kubernetes",
+            RconError::Grafana(_) => "grafana",
+            RconError::Metrics(_) => "metrics",
+            RconError::Mqtt(_) => "mqtt",
+            RconError::PinMismatch(_) => "pin_mismatch",
+            RconError::Parser(_) => "parser",
+            RconError::Plugin(_) => "plugin",
+            RconError::KeyConflict(_) => "key_conflict",
+            RconError::DeliveryUnconfirmed(_) => "delivery_unconfirmed",
+            RconError::CrashLoop(_) => "crash_loop",
+            RconError::RateLimited(_) => "rate_limited",
+            RconError::QueueFull(_) => "queue_full",
+        }
+    }
 }
 
 /// Result type alias for convenience
@@ -0,0 +1,334 @@
+//! Internal event bus
+//!
+//! Commands report what happens on a server as [`Event`]s on an
+//! [`EventBus`], and everything that records or forwards events subscribes
+//! to it: the latency history, `--metrics-out`, MQTT, and Grafana
+//! annotations each implement [`Subscriber`] and pick the events they care
+//! about, so a command emits an event once however many of them are
+//! configured.
+
+use crate::alert::AlertTransition;
+use crate::error::{RconError, Result};
+use crate::hooks::ClientHooks;
+use crate::parsers::PlayerList;
+use crate::sessions::{SessionEvent, SessionEventKind};
+use chrono::{DateTime, Utc};
+use std::fmt;
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+/// What happened
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EventKind {
+    /// Authenticated, initially or after the connection was lost
+    Connected,
+    /// The connection was lost or closed
+    Disconnected,
+    /// A command was answered
+    CommandExecuted { command: String, response: String },
+    /// A command failed, with the error's [`RconError::code`]
+    CommandFailed {
+        command: String,
+        code: String,
+        message: String,
+    },
+    /// A ping attempt: the round trip time, or the error code if it failed
+    Pinged {
+        latency: Option<Duration>,
+        error: Option<String>,
+    },
+    /// The player list was polled
+    PlayersCounted(PlayerList),
+    /// A player joined, left, or was online when tracking began
+    Session {
+        kind: SessionEventKind,
+        player: String,
+        /// Length of the session that ended, for leaves
+        duration: Option<Duration>,
+    },
+    /// An alert triggered or resolved
+    Alert {
+        transition: AlertTransition,
+        condition: String,
+        online: u32,
+        max: u32,
+    },
+    /// The world was saved
+    Saved,
+    /// The server accepts RCON logins, typically after a restart
+    Ready,
+    /// A script ran once the server was ready
+    ScriptFinished {
+        script: PathBuf,
+        total: usize,
+        failures: usize,
+    },
+    /// A playbook ran its steps on the server
+    PlaybookFinished {
+        playbook: PathBuf,
+        /// Steps run on the server
+        total: usize,
+        failures: usize,
+        /// Steps not run since a step before them failed
+        skipped: usize,
+    },
+    /// The server was stopped after being empty for `idle`
+    Stopped { idle: Duration },
+    /// The server was stopped for a restart after waiting `waited` for
+    /// players to leave, with `online` players left
+    Restarting { waited: Duration, online: u32 },
+    /// A maintenance window started or ended, announced with `message`
+    Maintenance { started: bool, message: String },
+}
+
+impl EventKind {
+    /// A failed command
+    pub fn command_failed(command: impl Into<String>, error: &RconError) -> Self {
+        Self::CommandFailed {
+            command: command.into(),
+            code: error.code().to_string(),
+            message: error.to_string(),
+        }
+    }
+
+    /// Short name, e.g. for the `event` field of messages
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Connected => "connected",
+            Self::Disconnected => "disconnected",
+            Self::CommandExecuted { .. } => "command",
+            Self::CommandFailed { .. } => "command_failed",
+            Self::Pinged { .. } => "ping",
+            Self::PlayersCounted(_) => "players",
+            Self::Session { kind, .. } => kind.as_str(),
+            Self::Alert { transition, .. } => transition.as_str(),
+            Self::Saved => "saved",
+            Self::Ready => "ready",
+            Self::ScriptFinished { .. } => "script",
+            Self::PlaybookFinished { .. } => "playbook",
+            Self::Stopped { .. } => "stopped",
+            Self::Restarting { .. } => "restarting",
+            Self::Maintenance { started: true, .. } => "maintenance_started",
+            Self::Maintenance { started: false, .. } => "maintenance_ended",
+        }
+    }
+}
+
+/// Something that happened on a server
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    /// Profile name or address of the server
+    pub server: String,
+    pub timestamp: DateTime<Utc>,
+    pub kind: EventKind,
+}
+
+impl Event {
+    /// An event happening now
+    pub fn new(server: impl Into<String>, kind: EventKind) -> Self {
+        Self {
+            server: server.into(),
+            timestamp: Utc::now(),
+            kind,
+        }
+    }
+
+    pub fn at(mut self, timestamp: DateTime<Utc>) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    /// A join or leave seen by a
+    /// [`SessionTracker`](crate::sessions::SessionTracker)
+    pub fn session(server: impl Into<String>, event: SessionEvent) -> Self {
+        let kind = EventKind::Session {
+            kind: event.kind,
+            player: event.player,
+            duration: event.duration,
+        };
+        Self::new(server, kind).at(event.timestamp)
+    }
+}
+
+/// Boxed future returned by [`Subscriber`] methods
+pub type SubscriberFuture<'a> = Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>>;
+
+/// Something that records or forwards events
+pub trait Subscriber: Send + Sync {
+    /// Handle an event, ignoring kinds that don't concern it
+    fn handle<'a>(&'a self, event: &'a Event) -> SubscriberFuture<'a>;
+
+    /// Finish up once no more events will come, e.g. flush queued messages
+    fn close(&self) -> SubscriberFuture<'_> {
+        Box::pin(async { Ok(()) })
+    }
+}
+
+/// Handler for errors returned by subscribers
+type ErrorHandler = Arc<dyn Fn(&RconError) + Send + Sync>;
+
+/// Sets up an [`EventBus`]
+pub struct EventBusBuilder {
+    server: String,
+    subscribers: Vec<Box<dyn Subscriber>>,
+    on_error: ErrorHandler,
+}
+
+impl EventBusBuilder {
+    /// Deliver events to `subscriber`, after those subscribed before
+    pub fn subscribe(mut self, subscriber: impl Subscriber + 'static) -> Self {
+        self.subscribers.push(Box::new(subscriber));
+        self
+    }
+
+    /// Report subscriber errors with `handler` instead of logging them
+    pub fn on_error<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(&RconError) + Send + Sync + 'static,
+    {
+        self.on_error = Arc::new(handler);
+        self
+    }
+
+    /// Start delivering events in the background
+    pub fn start(self) -> EventBus {
+        let (sender, mut receiver) = mpsc::unbounded_channel::<Option<Event>>();
+        let has_subscribers = !self.subscribers.is_empty();
+        let subscribers = self.subscribers;
+        let on_error = self.on_error;
+
+        let task = tokio::spawn(async move {
+            while let Some(Some(event)) = receiver.recv().await {
+                for subscriber in &subscribers {
+                    if let Err(e) = subscriber.handle(&event).await {
+                        on_error(&e);
+                    }
+                }
+            }
+            for subscriber in &subscribers {
+                if let Err(e) = subscriber.close().await {
+                    on_error(&e);
+                }
+            }
+        });
+
+        EventBus {
+            server: self.server,
+            sender,
+            has_subscribers,
+            task: Arc::new(Mutex::new(Some(task))),
+        }
+    }
+}
+
+impl fmt::Debug for EventBusBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventBusBuilder")
+            .field("server", &self.server)
+            .field("subscribers", &self.subscribers.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Delivers events to subscribers, in order and in the background
+///
+/// Emitting never waits for subscribers, and a subscriber's error is
+/// reported without failing the command or holding up the others. Call
+/// [`close`](Self::close) before exiting so every event is delivered.
+///
+/// ```
+/// use rcon_cli::events::{Event, EventBus, EventKind, Subscriber, SubscriberFuture};
+/// use std::sync::{Arc, Mutex};
+///
+/// struct Log(Arc<Mutex<Vec<String>>>);
+///
+/// impl Subscriber for Log {
+///     fn handle<'a>(&'a self, event: &'a Event) -> SubscriberFuture<'a> {
+///         let line = format!("{} {}", event.server, event.kind.name());
+///         self.0.lock().unwrap().push(line);
+///         Box::pin(async { Ok(()) })
+///     }
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let log = Arc::new(Mutex::new(Vec::new()));
+/// let events = EventBus::builder("survival").subscribe(Log(log.clone())).start();
+/// events.emit(EventKind::Saved);
+/// events.publish(Event::new("creative", EventKind::Ready));
+/// events.close().await;
+/// assert_eq!(*log.lock().unwrap(), ["survival saved", "creative ready"]);
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    /// Server of events emitted without naming one
+    server: String,
+    /// `None` asks the dispatcher to close the subscribers and stop
+    sender: mpsc::UnboundedSender<Option<Event>>,
+    has_subscribers: bool,
+    task: Arc<Mutex<Option<JoinHandle<()>>>>,
+}
+
+impl EventBus {
+    /// Set up a bus for events on `server`
+    pub fn builder(server: impl Into<String>) -> EventBusBuilder {
+        EventBusBuilder {
+            server: server.into(),
+            subscribers: Vec::new(),
+            on_error: Arc::new(|e| warn!("Event subscriber failed: {}", e)),
+        }
+    }
+
+    pub fn server(&self) -> &str {
+        &self.server
+    }
+
+    /// Whether anything listens, so events are worth producing
+    pub fn has_subscribers(&self) -> bool {
+        self.has_subscribers
+    }
+
+    /// Queue an event on this bus's server
+    pub fn emit(&self, kind: EventKind) {
+        self.publish(Event::new(self.server.as_str(), kind));
+    }
+
+    /// Queue an event; events after [`close`](Self::close) are dropped
+    pub fn publish(&self, event: Event) {
+        let _ = self.sender.send(Some(event));
+    }
+
+    /// Hooks emitting [`EventKind::Connected`] and
+    /// [`EventKind::Disconnected`] for a client's connection
+    pub fn client_hooks(&self) -> ClientHooks {
+        let connected = self.clone();
+        let reconnected = self.clone();
+        let disconnected = self.clone();
+        ClientHooks::new()
+            .on_connect(move |_| {
+                connected.emit(EventKind::Connected);
+                Box::pin(async { Ok(()) })
+            })
+            .on_reconnect(move |_| {
+                reconnected.emit(EventKind::Connected);
+                Box::pin(async { Ok(()) })
+            })
+            .on_disconnect(move |_| disconnected.emit(EventKind::Disconnected))
+    }
+
+    /// Deliver the queued events, then close the subscribers
+    pub async fn close(&self) {
+        let task = self.task.lock().unwrap_or_else(|e| e.into_inner()).take();
+        if let Some(task) = task {
+            let _ = self.sender.send(None);
+            let _ = task.await;
+        }
+    }
+}
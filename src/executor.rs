@@ -0,0 +1,53 @@
+//! Abstraction over anything that runs RCON commands
+//!
+//! Application code written against [`RconExecutor`] works with a live
+//! [`RconClient`], a [`LazyRconClient`], or a
+//! [`MockRconClient`](crate::mock::MockRconClient) in unit tests, including
+//! as a `Box<dyn RconExecutor>`.
+
+use crate::client::RconClient;
+use crate::error::Result;
+use crate::lazy::LazyRconClient;
+use std::future::Future;
+use std::pin::Pin;
+
+/// Boxed future returned by [`RconExecutor`] methods
+pub type ExecutorFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>>;
+
+/// Something that executes commands on a server
+///
+/// ```
+/// use rcon_cli::RconExecutor;
+///
+/// async fn online_count(server: &mut dyn RconExecutor) -> rcon_cli::Result<String> {
+///     let list = server.execute_command("list").await?;
+///     Ok(list.split_whitespace().nth(2).unwrap_or("0").to_string())
+/// }
+/// ```
+pub trait RconExecutor: Send {
+    /// Execute a command and return the response text
+    fn execute_command<'a>(&'a mut self, command: &'a str) -> ExecutorFuture<'a, String>;
+
+    /// Test connectivity by sending a harmless command
+    fn ping(&mut self) -> ExecutorFuture<'_, ()>;
+}
+
+impl RconExecutor for RconClient {
+    fn execute_command<'a>(&'a mut self, command: &'a str) -> ExecutorFuture<'a, String> {
+        Box::pin(RconClient::execute_command(self, command))
+    }
+
+    fn ping(&mut self) -> ExecutorFuture<'_, ()> {
+        Box::pin(RconClient::ping(self))
+    }
+}
+
+impl RconExecutor for LazyRconClient {
+    fn execute_command<'a>(&'a mut self, command: &'a str) -> ExecutorFuture<'a, String> {
+        Box::pin(LazyRconClient::execute_command(self, command))
+    }
+
+    fn ping(&mut self) -> ExecutorFuture<'_, ()> {
+        Box::pin(LazyRconClient::ping(self))
+    }
+}
@@ -0,0 +1,275 @@
+//! Output filters for `--jsonpath`, `--grep`, and `--lines`
+//!
+//! Filters run on everything written to the output destination (responses
+//! and records), one print at a time, in a fixed order: `--jsonpath`
+//! selects values from JSON output, every `--grep` then keeps the lines it
+//! matches, and `--lines` finally slices what is left. Informational
+//! messages are not filtered.
+
+use regex::Regex;
+use serde_json::Value;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+/// A `START:END` slice of lines, like Python's
+///
+/// Either end may be left out, and negative ends count from the last line,
+/// so `-5:` keeps the last five lines.
+///
+/// ```
+/// use rcon_cli::filter::LineRange;
+///
+/// let lines = ["a", "b", "c", "d"];
+/// assert_eq!("1:3".parse::<LineRange>().unwrap().slice(&lines), ["b", "c"]);
+/// assert_eq!("-1:".parse::<LineRange>().unwrap().slice(&lines), ["d"]);
+/// assert_eq!(":10".parse::<LineRange>().unwrap().slice(&lines), lines);
+/// assert!("3".parse::<LineRange>().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineRange {
+    pub start: Option<i64>,
+    pub end: Option<i64>,
+}
+
+impl LineRange {
+    pub fn slice<'a, T>(&self, lines: &'a [T]) -> &'a [T] {
+        let len = lines.len() as i64;
+        let resolve = |index: i64| {
+            let index = if index < 0 { len + index } else { index };
+            index.clamp(0, len) as usize
+        };
+        let start = self.start.map_or(0, resolve);
+        let end = self.end.map_or(lines.len(), resolve);
+        if start >= end {
+            &[]
+        } else {
+            &lines[start..end]
+        }
+    }
+}
+
+impl FromStr for LineRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (start, end) = s
+            .split_once(':')
+            .ok_or_else(|| format!("expected START:END, got '{}'", s))?;
+        let bound = |bound: &str| {
+            let bound = bound.trim();
+            if bound.is_empty() {
+                return Ok(None);
+            }
+            bound
+                .parse()
+                .map(Some)
+                .map_err(|_| format!("'{}' is not a line number", bound))
+        };
+        Ok(Self {
+            start: bound(start)?,
+            end: bound(end)?,
+        })
+    }
+}
+
+/// One step of a [`JsonPath`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Key(String),
+    /// Array index; negative ones count from the end
+    Index(i64),
+    Wildcard,
+}
+
+/// A JSONPath subset: `$` followed by `.key`, `['key']`, `[N]`, `[*]`,
+/// and `.*`
+///
+/// ```
+/// use rcon_cli::filter::JsonPath;
+///
+/// let path: JsonPath = "$.records[*].name".parse().unwrap();
+/// let value = serde_json::json!({"records": [{"name": "Alice"}, {"name": "Bob"}]});
+/// assert_eq!(path.select(&value), [&value["records"][0]["name"], &value["records"][1]["name"]]);
+/// assert!("$..name".parse::<JsonPath>().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonPath {
+    source: String,
+    segments: Vec<Segment>,
+}
+
+impl JsonPath {
+    /// Every value the path selects, in document order
+    pub fn select<'a>(&self, value: &'a Value) -> Vec<&'a Value> {
+        let mut selected = vec![value];
+        for segment in &self.segments {
+            selected = selected
+                .into_iter()
+                .flat_map(|value| -> Vec<&Value> {
+                    match (segment, value) {
+                        (Segment::Key(key), Value::Object(map)) => {
+                            map.get(key).into_iter().collect()
+                        }
+                        (Segment::Index(index), Value::Array(items)) => {
+                            let index = if *index < 0 {
+                                items.len() as i64 + index
+                            } else {
+                                *index
+                            };
+                            usize::try_from(index)
+                                .ok()
+                                .and_then(|index| items.get(index))
+                                .into_iter()
+                                .collect()
+                        }
+                        (Segment::Wildcard, Value::Array(items)) => items.iter().collect(),
+                        (Segment::Wildcard, Value::Object(map)) => map.values().collect(),
+                        _ => Vec::new(),
+                    }
+                })
+                .collect();
+        }
+        selected
+    }
+}
+
+impl fmt::Display for JsonPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl FromStr for JsonPath {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rest = s
+            .trim()
+            .strip_prefix('$')
+            .ok_or_else(|| format!("'{}' must start with $", s))?;
+        let mut chars = rest.chars().peekable();
+        let mut segments = Vec::new();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '.' => match chars.peek() {
+                    Some('.') => return Err("recursive descent (..) isn't supported".to_string()),
+                    Some('*') => {
+                        chars.next();
+                        segments.push(Segment::Wildcard);
+                    }
+                    _ => {
+                        let mut key = String::new();
+                        while let Some(&c) = chars.peek().filter(|c| !matches!(c, '.' | '[')) {
+                            key.push(c);
+                            chars.next();
+                        }
+                        if key.is_empty() {
+                            return Err(format!("'{}' has an empty key", s));
+                        }
+                        segments.push(Segment::Key(key));
+                    }
+                },
+                '[' => {
+                    let mut inner = String::new();
+                    let mut quote = None;
+                    loop {
+                        match chars.next() {
+                            None => return Err(format!("'{}' has an unclosed [", s)),
+                            Some(c) if Some(c) == quote => quote = None,
+                            Some(c @ ('\'' | '"')) if quote.is_none() && inner.is_empty() => {
+                                quote = Some(c);
+                                inner.push(c);
+                            }
+                            Some(']') if quote.is_none() => break,
+                            Some(c) => inner.push(c),
+                        }
+                    }
+                    let segment =
+                        match inner.as_str() {
+                            "*" => Segment::Wildcard,
+                            quoted if quoted.starts_with(['\'', '"']) => {
+                                Segment::Key(quoted[1..].to_string())
+                            }
+                            index => Segment::Index(index.trim().parse().map_err(|_| {
+                                format!("'[{}]' is not an index, key, or *", index)
+                            })?),
+                        };
+                    segments.push(segment);
+                }
+                c => return Err(format!("unexpected '{}' in '{}'", c, s)),
+            }
+        }
+
+        Ok(Self {
+            source: s.trim().to_string(),
+            segments,
+        })
+    }
+}
+
+/// The filters selected on the command line
+#[derive(Debug, Clone, Default)]
+pub struct OutputFilter {
+    pub jsonpath: Option<JsonPath>,
+    pub grep: Vec<Regex>,
+    pub lines: Option<LineRange>,
+}
+
+impl OutputFilter {
+    pub fn is_empty(&self) -> bool {
+        self.jsonpath.is_none() && self.grep.is_empty() && self.lines.is_none()
+    }
+
+    /// Filter one printed text, returning the lines left
+    ///
+    /// Selected strings are printed without quotes and other values as
+    /// compact JSON, one per line. Fails if `--jsonpath` is given but the
+    /// text isn't JSON.
+    ///
+    /// ```
+    /// use rcon_cli::filter::OutputFilter;
+    ///
+    /// let filter = OutputFilter {
+    ///     jsonpath: Some("$.records[*].name".parse().unwrap()),
+    ///     grep: vec![regex::Regex::new("^[AB]").unwrap()],
+    ///     lines: Some(":1".parse().unwrap()),
+    /// };
+    /// let output = r#"{"records":[{"name":"Steve"},{"name":"Bob"},{"name":"Alex"}]}"#;
+    /// assert_eq!(filter.apply(output).unwrap(), ["Bob"]);
+    /// assert!(filter.apply("There are 0 of a max of 20 players online").is_err());
+    /// ```
+    pub fn apply(&self, text: &str) -> Result<Vec<String>, String> {
+        let mut lines: Vec<String> = match &self.jsonpath {
+            Some(path) => {
+                let value: Value = serde_json::from_str(text)
+                    .map_err(|_| format!("--jsonpath {} needs JSON output", path))?;
+                path.select(&value)
+                    .into_iter()
+                    .map(|value| match value {
+                        Value::String(text) => text.clone(),
+                        value => value.to_string(),
+                    })
+                    .collect()
+            }
+            None => text.lines().map(String::from).collect(),
+        };
+        lines.retain(|line| {
+            let plain = strip_colors(line);
+            self.grep.iter().all(|pattern| pattern.is_match(&plain))
+        });
+        Ok(match self.lines {
+            Some(range) => range.slice(&lines).to_vec(),
+            None => lines,
+        })
+    }
+}
+
+/// Remove ANSI color sequences so highlighting can't split a match
+fn strip_colors(line: &str) -> std::borrow::Cow<'_, str> {
+    static COLORS: OnceLock<Regex> = OnceLock::new();
+    COLORS
+        .get_or_init(|| Regex::new("\x1b\\[[0-9;]*m").expect("valid pattern"))
+        .replace_all(line, "")
+}
@@ -0,0 +1,63 @@
+//! Corpus of real server responses
+//!
+//! `fixtures/responses.toml` collects responses of the commands the
+//! [`parsers`](crate::parsers) understand, from different versions and
+//! server software. The crate's golden tests parse every one of them; the
+//! corpus is embedded here so code built on the parsers, or on raw
+//! responses, can be checked against the same outputs:
+//!
+//! ```
+//! use rcon_cli::fixtures;
+//! use rcon_cli::parsers::parse_player_list;
+//!
+//! for fixture in fixtures::for_parser("player_list") {
+//!     let list = parse_player_list(&fixture.response).unwrap();
+//!     assert!(list.online <= list.max, "{}", fixture.name);
+//! }
+//! ```
+
+use serde::Deserialize;
+use std::sync::OnceLock;
+
+const CORPUS: &str = include_str!("../fixtures/responses.toml");
+
+/// A response as a server sent it over RCON
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Fixture {
+    /// Unique name, e.g. `list-vanilla-1.21.4`
+    pub name: String,
+    /// Parser the response is meant for, named after its `parse_` function,
+    /// e.g. `player_list`
+    pub parser: String,
+    /// Command that produced the response
+    pub command: String,
+    /// Server software and version, e.g. `Paper 1.20.4`
+    pub server: String,
+    pub response: String,
+}
+
+#[derive(Deserialize)]
+struct Corpus {
+    response: Vec<Fixture>,
+}
+
+/// Every response in the corpus
+pub fn all() -> &'static [Fixture] {
+    static FIXTURES: OnceLock<Vec<Fixture>> = OnceLock::new();
+    FIXTURES.get_or_init(|| {
+        toml::from_str::<Corpus>(CORPUS)
+            .expect("fixtures/responses.toml is valid")
+            .response
+    })
+}
+
+/// Responses meant for one parser
+pub fn for_parser(parser: &str) -> impl Iterator<Item = &'static Fixture> + '_ {
+    all().iter().filter(move |fixture| fixture.parser == parser)
+}
+
+/// A response by name
+pub fn get(name: &str) -> Option<&'static Fixture> {
+    all().iter().find(|fixture| fixture.name == name)
+}
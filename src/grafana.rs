@@ -0,0 +1,268 @@
+//! Grafana annotations for notable events
+//!
+//! With a `[grafana]` section in the config file, saves, restarts, script
+//! runs, automatic stops, and alerts on a command's
+//! [`EventBus`](crate::events::EventBus) are posted as annotations, so admin
+//! actions line up with the server's performance graphs:
+//!
+//! ```toml
+//! [grafana]
+//! url = "https://grafana.example.com"
+//! token = "glsa_..."
+//! dashboard_uid = "minecraft"
+//! ```
+
+use crate::alert::AlertTransition;
+use crate::cli::format_duration;
+use crate::error::{RconError, Result};
+use crate::events::{Event, EventKind, Subscriber, SubscriberFuture};
+use crate::t;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+/// How long posting an annotation may take
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The `[grafana]` section of the config file
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GrafanaConfig {
+    /// Base URL of the Grafana instance
+    pub url: String,
+    /// Service account token with the annotation writer permission
+    pub token: String,
+    /// Dashboard to annotate; without one, annotations belong to the
+    /// organization and show wherever an annotation query selects them
+    pub dashboard_uid: Option<String>,
+    /// Panel of the dashboard to annotate
+    pub panel_id: Option<u64>,
+    /// Added to every annotation
+    #[serde(default = "default_tags")]
+    pub tags: Vec<String>,
+}
+
+fn default_tags() -> Vec<String> {
+    vec!["rcon-cli".to_string()]
+}
+
+/// An event to mark on the graphs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Annotation {
+    pub text: String,
+    pub tags: Vec<String>,
+    pub time: DateTime<Utc>,
+}
+
+impl Annotation {
+    /// An annotation at the current time
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            tags: Vec::new(),
+            time: Utc::now(),
+        }
+    }
+
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// The annotation marking a notable event, if it is one
+    ///
+    /// Annotations are tagged with the kind of event, `save`, `ready`,
+    /// `script`, `playbook`, `stop`, `restart`, `maintenance`, or `alert` for triggered
+    /// alerts, and the server.
+    pub fn for_event(event: &Event) -> Option<Self> {
+        let server = event.server.as_str();
+        let (kind, text) = match &event.kind {
+            EventKind::Saved => ("save", t!("annotation-saved", server = server)),
+            EventKind::Ready => ("ready", t!("annotation-ready", server = server)),
+            EventKind::ScriptFinished {
+                script,
+                total,
+                failures,
+            } => (
+                "script",
+                t!(
+                    "annotation-script",
+                    server = server,
+                    script = script.display().to_string(),
+                    succeeded = total - failures,
+                    total = *total
+                ),
+            ),
+            EventKind::PlaybookFinished {
+                playbook,
+                total,
+                failures,
+                skipped,
+            } => (
+                "playbook",
+                t!(
+                    "annotation-playbook",
+                    server = server,
+                    playbook = playbook.display().to_string(),
+                    succeeded = total - failures - skipped,
+                    total = *total
+                ),
+            ),
+            EventKind::Stopped { idle } => (
+                "stop",
+                t!(
+                    "annotation-stopped",
+                    server = server,
+                    idle = format_duration(*idle)
+                ),
+            ),
+            EventKind::Restarting { waited, online } => (
+                "restart",
+                t!(
+                    "annotation-restarting",
+                    server = server,
+                    waited = format_duration(*waited),
+                    players = *online
+                ),
+            ),
+            EventKind::Maintenance { started, message } => (
+                "maintenance",
+                if *started {
+                    t!(
+                        "annotation-maintenance-started",
+                        server = server,
+                        message = message.as_str()
+                    )
+                } else {
+                    t!(
+                        "annotation-maintenance-ended",
+                        server = server,
+                        message = message.as_str()
+                    )
+                },
+            ),
+            EventKind::Alert {
+                transition: AlertTransition::Triggered,
+                condition,
+                online,
+                max,
+            } => (
+                "alert",
+                t!(
+                    "annotation-alert",
+                    server = server,
+                    condition = condition.as_str(),
+                    online = *online,
+                    max = *max
+                ),
+            ),
+            _ => return None,
+        };
+        let mut annotation = Self::new(text).tag(kind).tag(server);
+        annotation.time = event.timestamp;
+        Some(annotation)
+    }
+}
+
+fn grafana_error(message: impl Into<String>) -> RconError {
+    RconError::Grafana(message.into())
+}
+
+/// Posts annotations through the Grafana HTTP API
+#[derive(Debug, Clone)]
+pub struct GrafanaClient {
+    config: GrafanaConfig,
+    client: reqwest::Client,
+}
+
+impl GrafanaClient {
+    pub fn new(config: GrafanaConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        Self { config, client }
+    }
+
+    /// Request body for an annotation, with the configured dashboard and tags
+    ///
+    /// ```
+    /// use rcon_cli::grafana::{Annotation, GrafanaClient, GrafanaConfig};
+    ///
+    /// let grafana = GrafanaClient::new(GrafanaConfig {
+    ///     url: "https://grafana.example.com".to_string(),
+    ///     token: "glsa_...".to_string(),
+    ///     dashboard_uid: Some("minecraft".to_string()),
+    ///     panel_id: None,
+    ///     tags: vec!["rcon-cli".to_string()],
+    /// });
+    /// let body = grafana.body(&Annotation::new("World saved").tag("save"));
+    /// assert_eq!(body["dashboardUID"], "minecraft");
+    /// assert_eq!(body["tags"], serde_json::json!(["rcon-cli", "save"]));
+    /// ```
+    pub fn body(&self, annotation: &Annotation) -> Value {
+        let tags: Vec<&str> = self
+            .config
+            .tags
+            .iter()
+            .chain(&annotation.tags)
+            .map(String::as_str)
+            .collect();
+        let mut body = json!({
+            "time": annotation.time.timestamp_millis(),
+            "tags": tags,
+            "text": annotation.text,
+        });
+        if let Some(dashboard) = &self.config.dashboard_uid {
+            body["dashboardUID"] = dashboard.as_str().into();
+        }
+        if let Some(panel) = self.config.panel_id {
+            body["panelId"] = panel.into();
+        }
+        body
+    }
+
+    /// Post an annotation, returning its ID
+    pub async fn annotate(&self, annotation: &Annotation) -> Result<u64> {
+        let url = format!("{}/api/annotations", self.config.url.trim_end_matches('/'));
+        let request = self
+            .client
+            .post(&url)
+            .bearer_auth(&self.config.token)
+            .json(&self.body(annotation));
+        let fetch = async {
+            request
+                .send()
+                .await?
+                .error_for_status()?
+                .json::<Value>()
+                .await
+        };
+        let response = fetch.await.map_err(|e| {
+            // The cause, such as a refused connection, is only in the source
+            // chain
+            let e = e.without_url();
+            let mut message = e.to_string();
+            let mut source = std::error::Error::source(&e);
+            while let Some(cause) = source {
+                message = format!("{}: {}", message, cause);
+                source = cause.source();
+            }
+            grafana_error(format!("Request to {} failed: {}", url, message))
+        })?;
+        Ok(response["id"].as_u64().unwrap_or_default())
+    }
+}
+
+impl Subscriber for GrafanaClient {
+    /// Annotates notable events, see [`Annotation::for_event`]
+    fn handle<'a>(&'a self, event: &'a Event) -> SubscriberFuture<'a> {
+        Box::pin(async move {
+            if let Some(annotation) = Annotation::for_event(event) {
+                self.annotate(&annotation).await?;
+            }
+            Ok(())
+        })
+    }
+}
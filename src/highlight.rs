@@ -0,0 +1,206 @@
+//! Response highlighting driven by regex rules
+//!
+//! Rules come from the `[highlight]` section of the config file and are
+//! applied before the built-in defaults:
+//!
+//! ```toml
+//! [highlight]
+//! defaults = true
+//!
+//! [[highlight.rules]]
+//! pattern = "\\bdiamonds?\\b"
+//! style = "bright_cyan bold"
+//! ```
+
+use crate::error::{RconError, Result};
+use regex::Regex;
+use serde::Deserialize;
+
+/// The `[highlight]` section of the config file
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HighlightConfig {
+    /// Keep the built-in rules after the user's own
+    #[serde(default = "default_true")]
+    pub defaults: bool,
+    #[serde(default)]
+    pub rules: Vec<HighlightRule>,
+}
+
+impl Default for HighlightConfig {
+    fn default() -> Self {
+        Self {
+            defaults: true,
+            rules: Vec::new(),
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// A regex and the style applied to its matches
+///
+/// If the pattern has capture groups, only the first group is styled, which
+/// stands in for lookaround assertions the regex engine doesn't support.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HighlightRule {
+    pub pattern: String,
+    /// Space-separated color and attributes, e.g. `red bold`
+    pub style: String,
+}
+
+impl HighlightRule {
+    pub fn new(pattern: impl Into<String>, style: impl Into<String>) -> Self {
+        Self {
+            pattern: pattern.into(),
+            style: style.into(),
+        }
+    }
+}
+
+/// Built-in rules for errors, warnings, player names, and coordinates
+pub fn default_rules() -> Vec<HighlightRule> {
+    vec![
+        HighlightRule::new(
+            r"(?i)\b(?:error|exception|failed|unknown (?:command|item|player)|invalid|incorrect argument)\b",
+            "red bold",
+        ),
+        HighlightRule::new(r"(?i)\bwarn(?:ing)?\b", "yellow bold"),
+        HighlightRule::new(r"players online:", "green"),
+        // Player list entries, chat senders, and join/leave messages
+        HighlightRule::new(
+            r"(?:players online:|,)\s*([A-Za-z0-9_]{3,16})\b",
+            "bright_green",
+        ),
+        HighlightRule::new(r"<([A-Za-z0-9_]{3,16})>", "bright_green"),
+        HighlightRule::new(
+            r"\b([A-Za-z0-9_]{3,16}) (?:joined|left) the game\b",
+            "bright_green",
+        ),
+        HighlightRule::new(
+            r"-?\d+(?:\.\d+)?,? -?\d+(?:\.\d+)?,? -?\d+(?:\.\d+)?",
+            "cyan",
+        ),
+        HighlightRule::new(r"\b\d+\b", "yellow"),
+    ]
+}
+
+/// Compiled highlight rules
+#[derive(Debug, Clone)]
+pub struct Highlighter {
+    rules: Vec<(Regex, String)>,
+}
+
+impl Default for Highlighter {
+    fn default() -> Self {
+        Self::new(&default_rules()).expect("built-in highlight rules are valid")
+    }
+}
+
+impl Highlighter {
+    /// Compile rules, in order of precedence
+    pub fn new(rules: &[HighlightRule]) -> Result<Self> {
+        let rules = rules
+            .iter()
+            .map(|rule| {
+                let regex = Regex::new(&rule.pattern).map_err(|e| {
+                    RconError::InvalidConfig(format!(
+                        "Invalid highlight pattern '{}': {}",
+                        rule.pattern, e
+                    ))
+                })?;
+                Ok((regex, parse_style(&rule.style)?))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { rules })
+    }
+
+    /// Compile the configured rules, followed by the defaults unless disabled
+    pub fn from_config(config: &HighlightConfig) -> Result<Self> {
+        let mut rules = config.rules.clone();
+        if config.defaults {
+            rules.extend(default_rules());
+        }
+        Self::new(&rules)
+    }
+
+    /// Wrap matches in ANSI escape sequences
+    ///
+    /// Where matches overlap, the earlier rule wins.
+    pub fn apply(&self, text: &str) -> String {
+        let mut spans: Vec<(usize, usize, &str)> = Vec::new();
+        for (regex, style) in &self.rules {
+            for captures in regex.captures_iter(text) {
+                let Some(m) = captures.get(1).or_else(|| captures.get(0)) else {
+                    continue;
+                };
+                let overlaps = spans
+                    .iter()
+                    .any(|&(start, end, _)| m.start() < end && start < m.end());
+                if !m.is_empty() && !overlaps {
+                    spans.push((m.start(), m.end(), style));
+                }
+            }
+        }
+        spans.sort_by_key(|&(start, _, _)| start);
+
+        let mut highlighted = String::with_capacity(text.len());
+        let mut position = 0;
+        for (start, end, style) in spans {
+            highlighted.push_str(&text[position..start]);
+            highlighted.push_str(&format!("\x1b[{}m{}\x1b[0m", style, &text[start..end]));
+            position = end;
+        }
+        highlighted.push_str(&text[position..]);
+        highlighted
+    }
+}
+
+/// Translate a style such as `bright_red bold underline` into SGR parameters
+fn parse_style(style: &str) -> Result<String> {
+    let codes = style
+        .split_whitespace()
+        .map(|word| {
+            let code = match word.to_ascii_lowercase().as_str() {
+                "bold" => 1,
+                "dim" => 2,
+                "italic" => 3,
+                "underline" => 4,
+                "reverse" => 7,
+                "black" => 30,
+                "red" => 31,
+                "green" => 32,
+                "yellow" => 33,
+                "blue" => 34,
+                "magenta" => 35,
+                "cyan" => 36,
+                "white" => 37,
+                "bright_black" | "gray" | "grey" => 90,
+                "bright_red" => 91,
+                "bright_green" => 92,
+                "bright_yellow" => 93,
+                "bright_blue" => 94,
+                "bright_magenta" => 95,
+                "bright_cyan" => 96,
+                "bright_white" => 97,
+                _ => {
+                    return Err(RconError::InvalidConfig(format!(
+                        "Unknown highlight style '{}'",
+                        word
+                    )))
+                }
+            };
+            Ok(code.to_string())
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    if codes.is_empty() {
+        return Err(RconError::InvalidConfig(
+            "Highlight style cannot be empty".to_string(),
+        ));
+    }
+    Ok(codes.join(";"))
+}
@@ -0,0 +1,317 @@
+//! Latency history and availability reports
+//!
+//! `ping --record` appends every attempt to a CSV file, one
+//! `timestamp,target,latency_ms,error` row each, where failed attempts have
+//! an empty latency and the error code. `report` reads it back and
+//! summarizes availability, latency percentiles, and outages per target.
+//! [`History`] records the [`EventKind::Pinged`] events of an
+//! [`EventBus`](crate::events::EventBus).
+
+use crate::bulk::Table;
+use crate::error::{RconError, Result};
+use crate::events::{Event, EventKind, Subscriber, SubscriberFuture};
+use chrono::{DateTime, SecondsFormat, Utc};
+use std::collections::BTreeMap;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Header row of a history file
+const HEADER: &str = "timestamp,target,latency_ms,error";
+
+/// Default history file, `<data dir>/rcon-cli/latency.csv`
+pub fn default_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("rcon-cli").join("latency.csv"))
+}
+
+/// One ping attempt
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    pub timestamp: DateTime<Utc>,
+    /// Profile name or address the attempt went to
+    pub target: String,
+    /// Round trip time; `None` if the attempt failed
+    pub latency: Option<Duration>,
+    /// Error code of a failed attempt
+    pub error: Option<String>,
+}
+
+impl Sample {
+    pub fn success(target: impl Into<String>, latency: Duration) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            target: target.into(),
+            latency: Some(latency),
+            error: None,
+        }
+    }
+
+    /// A failed attempt, with the error code, e.g. [`RconError::code`]
+    pub fn failure(target: impl Into<String>, code: impl Into<String>) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            target: target.into(),
+            latency: None,
+            error: Some(code.into()),
+        }
+    }
+
+    fn to_csv(&self) -> String {
+        let target = if self.target.contains([',', '"', '\n']) {
+            format!("\"{}\"", self.target.replace('"', "\"\""))
+        } else {
+            self.target.clone()
+        };
+        format!(
+            "{},{},{},{}",
+            self.timestamp.to_rfc3339_opts(SecondsFormat::Millis, true),
+            target,
+            self.latency
+                .map(|latency| format!("{:.3}", latency.as_secs_f64() * 1000.0))
+                .unwrap_or_default(),
+            self.error.as_deref().unwrap_or_default()
+        )
+    }
+}
+
+impl From<Sample> for Event {
+    fn from(sample: Sample) -> Self {
+        let kind = EventKind::Pinged {
+            latency: sample.latency,
+            error: sample.error,
+        };
+        Event::new(sample.target, kind).at(sample.timestamp)
+    }
+}
+
+/// An append-only history file
+#[derive(Debug, Clone)]
+pub struct History {
+    path: PathBuf,
+}
+
+impl History {
+    /// Create the file and its directory if needed
+    ///
+    /// The header is written here, so concurrent recorders never race to
+    /// write it.
+    pub fn open(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+        if file.metadata()?.len() == 0 {
+            writeln!(file, "{}", HEADER)?;
+        }
+        Ok(Self { path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append a sample as a single write
+    pub fn record(&self, sample: &Sample) -> io::Result<()> {
+        let mut file = OpenOptions::new().append(true).open(&self.path)?;
+        file.write_all(format!("{}\n", sample.to_csv()).as_bytes())
+    }
+}
+
+impl Subscriber for History {
+    /// Records ping attempts
+    fn handle<'a>(&'a self, event: &'a Event) -> SubscriberFuture<'a> {
+        let result = match &event.kind {
+            EventKind::Pinged { latency, error } => {
+                let sample = Sample {
+                    timestamp: event.timestamp,
+                    target: event.server.clone(),
+                    latency: *latency,
+                    error: error.clone(),
+                };
+                self.record(&sample).map_err(|e| {
+                    RconError::InvalidConfig(format!(
+                        "Failed to write '{}': {}",
+                        self.path.display(),
+                        e
+                    ))
+                })
+            }
+            _ => Ok(()),
+        };
+        Box::pin(async move { result })
+    }
+}
+
+/// Read every sample from a history file
+///
+/// ```
+/// use rcon_cli::history::parse;
+///
+/// let samples = parse("timestamp,target,latency_ms,error\n\
+///     2026-01-01T00:00:00Z,survival,12.5,\n\
+///     2026-01-01T00:01:00Z,survival,,timeout\n").unwrap();
+/// assert_eq!(samples.len(), 2);
+/// assert!(samples[1].latency.is_none());
+/// ```
+pub fn parse(source: &str) -> Result<Vec<Sample>> {
+    let table = Table::parse(source, true)?;
+    let invalid = |line: usize, what: &str| {
+        RconError::InvalidConfig(format!("History row {}: invalid {}", line + 2, what))
+    };
+
+    table
+        .rows
+        .iter()
+        .enumerate()
+        .map(|(line, row)| {
+            let field = |index: usize| row.get(index).map(|f| f.trim()).unwrap_or_default();
+            let timestamp = DateTime::parse_from_rfc3339(field(0))
+                .map_err(|_| invalid(line, "timestamp"))?
+                .with_timezone(&Utc);
+            let latency = match field(2) {
+                "" => None,
+                ms => Some(
+                    ms.parse::<f64>()
+                        .ok()
+                        .filter(|ms| ms.is_finite() && *ms >= 0.0)
+                        .map(|ms| Duration::from_secs_f64(ms / 1000.0))
+                        .ok_or_else(|| invalid(line, "latency"))?,
+                ),
+            };
+            Ok(Sample {
+                timestamp,
+                target: field(1).to_string(),
+                latency,
+                error: Some(field(3)).filter(|e| !e.is_empty()).map(String::from),
+            })
+        })
+        .collect()
+}
+
+/// Read a history file
+pub fn load(path: impl AsRef<Path>) -> Result<Vec<Sample>> {
+    let path = path.as_ref();
+    let source = std::fs::read_to_string(path).map_err(|e| {
+        RconError::InvalidConfig(format!("Failed to read '{}': {}", path.display(), e))
+    })?;
+    parse(&source)
+}
+
+/// The longest stretch of failed attempts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Outage {
+    /// Time of the first failed attempt
+    pub start: DateTime<Utc>,
+    /// Until the next successful attempt, or the last attempt if none
+    /// followed
+    pub duration: Duration,
+    /// No successful attempt has followed yet
+    pub ongoing: bool,
+}
+
+/// Summary of one target's samples
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub target: String,
+    pub samples: usize,
+    pub failures: usize,
+    /// Latency percentiles of successful attempts: p50, p95, p99
+    pub percentiles: Option<[Duration; 3]>,
+    pub longest_outage: Option<Outage>,
+}
+
+impl Report {
+    /// Share of successful attempts in percent
+    pub fn availability(&self) -> f64 {
+        if self.samples == 0 {
+            return 0.0;
+        }
+        100.0 * (self.samples - self.failures) as f64 / self.samples as f64
+    }
+}
+
+/// Nearest-rank percentile of sorted values
+fn percentile(sorted: &[Duration], percent: usize) -> Duration {
+    let rank = (percent * sorted.len()).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+/// Summarize the samples taken at or after `since`, per target
+///
+/// ```
+/// use rcon_cli::history::{parse, summarize};
+///
+/// let samples = parse("timestamp,target,latency_ms,error\n\
+///     2026-01-01T00:00:00Z,lobby,10,\n\
+///     2026-01-01T00:01:00Z,lobby,,connection_refused\n\
+///     2026-01-01T00:02:00Z,lobby,,connection_refused\n\
+///     2026-01-01T00:03:00Z,lobby,30,\n").unwrap();
+/// let report = &summarize(&samples, None)[0];
+/// assert_eq!(report.availability(), 50.0);
+/// assert_eq!(report.longest_outage.unwrap().duration.as_secs(), 120);
+/// ```
+pub fn summarize(samples: &[Sample], since: Option<DateTime<Utc>>) -> Vec<Report> {
+    let mut by_target: BTreeMap<&str, Vec<&Sample>> = BTreeMap::new();
+    for sample in samples {
+        if since.is_none_or(|since| sample.timestamp >= since) {
+            by_target.entry(&sample.target).or_default().push(sample);
+        }
+    }
+
+    by_target
+        .into_iter()
+        .map(|(target, mut samples)| {
+            samples.sort_by_key(|sample| sample.timestamp);
+
+            let mut latencies: Vec<Duration> =
+                samples.iter().filter_map(|sample| sample.latency).collect();
+            latencies.sort();
+            let percentiles = (!latencies.is_empty())
+                .then(|| [50, 95, 99].map(|percent| percentile(&latencies, percent)));
+
+            let mut longest: Option<Outage> = None;
+            let mut started: Option<DateTime<Utc>> = None;
+            let last = samples.last().map(|sample| sample.timestamp);
+            for sample in &samples {
+                match (sample.latency, started) {
+                    (None, None) => started = Some(sample.timestamp),
+                    (Some(_), Some(start)) => {
+                        let outage = Outage {
+                            start,
+                            duration: (sample.timestamp - start).to_std().unwrap_or_default(),
+                            ongoing: false,
+                        };
+                        if longest.is_none_or(|longest| outage.duration > longest.duration) {
+                            longest = Some(outage);
+                        }
+                        started = None;
+                    }
+                    _ => {}
+                }
+            }
+            if let (Some(start), Some(last)) = (started, last) {
+                let outage = Outage {
+                    start,
+                    duration: (last - start).to_std().unwrap_or_default(),
+                    ongoing: true,
+                };
+                if longest.is_none_or(|longest| outage.duration >= longest.duration) {
+                    longest = Some(outage);
+                }
+            }
+
+            Report {
+                target: target.to_string(),
+                samples: samples.len(),
+                failures: samples.iter().filter(|s| s.latency.is_none()).count(),
+                percentiles,
+                longest_outage: longest,
+            }
+        })
+        .collect()
+}
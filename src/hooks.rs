@@ -0,0 +1,128 @@
+use crate::client::RconClient;
+use crate::error::Result;
+use std::fmt;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// Boxed future returned by asynchronous lifecycle hooks
+pub type HookFuture<'a> = Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>>;
+
+/// Hook invoked with the freshly authenticated client
+pub type ConnectHook = Arc<dyn for<'a> Fn(&'a mut RconClient) -> HookFuture<'a> + Send + Sync>;
+
+/// Hook invoked with the address of the server that was lost
+pub type DisconnectHook = Arc<dyn Fn(SocketAddr) + Send + Sync>;
+
+/// Callbacks fired on connection lifecycle transitions
+///
+/// `on_connect` runs after the initial connection is authenticated and
+/// `on_reconnect` after every successful [`RconClient::reconnect`], so
+/// session state such as gamerules can be re-applied without polling.
+/// An error returned by either hook is propagated to the caller.
+///
+/// ```rust,no_run
+/// use rcon_cli::RconClientBuilder;
+///
+/// # async fn example() -> rcon_cli::Result<()> {
+/// let client = RconClientBuilder::new()
+///     .address("127.0.0.1:25575".parse().unwrap())
+///     .password("secret")
+///     .on_reconnect(|client| {
+///         Box::pin(async move {
+///             client.execute_command("gamerule sendCommandFeedback false").await?;
+///             Ok(())
+///         })
+///     })
+///     .on_disconnect(|addr| eprintln!("lost connection to {}", addr))
+///     .connect()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct ClientHooks {
+    pub(crate) on_connect: Option<ConnectHook>,
+    pub(crate) on_disconnect: Option<DisconnectHook>,
+    pub(crate) on_reconnect: Option<ConnectHook>,
+}
+
+impl ClientHooks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the hook fired after the first successful authentication
+    pub fn on_connect<F>(mut self, hook: F) -> Self
+    where
+        F: for<'a> Fn(&'a mut RconClient) -> HookFuture<'a> + Send + Sync + 'static,
+    {
+        self.on_connect = Some(Arc::new(hook));
+        self
+    }
+
+    /// Set the hook fired when the connection is lost or torn down
+    pub fn on_disconnect<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(SocketAddr) + Send + Sync + 'static,
+    {
+        self.on_disconnect = Some(Arc::new(hook));
+        self
+    }
+
+    /// Set the hook fired after a successful reconnect and re-authentication
+    pub fn on_reconnect<F>(mut self, hook: F) -> Self
+    where
+        F: for<'a> Fn(&'a mut RconClient) -> HookFuture<'a> + Send + Sync + 'static,
+    {
+        self.on_reconnect = Some(Arc::new(hook));
+        self
+    }
+
+    /// Run `next`'s hooks after these ones
+    ///
+    /// A connect hook that fails skips the ones after it.
+    pub fn chain(self, next: ClientHooks) -> Self {
+        Self {
+            on_connect: chain_connect(self.on_connect, next.on_connect),
+            on_disconnect: match (self.on_disconnect, next.on_disconnect) {
+                (Some(first), Some(second)) => Some(Arc::new(move |address| {
+                    first(address);
+                    second(address);
+                })),
+                (first, second) => first.or(second),
+            },
+            on_reconnect: chain_connect(self.on_reconnect, next.on_reconnect),
+        }
+    }
+
+    /// Check if no hooks are registered
+    pub fn is_empty(&self) -> bool {
+        self.on_connect.is_none() && self.on_disconnect.is_none() && self.on_reconnect.is_none()
+    }
+}
+
+impl fmt::Debug for ClientHooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientHooks")
+            .field("on_connect", &self.on_connect.is_some())
+            .field("on_disconnect", &self.on_disconnect.is_some())
+            .field("on_reconnect", &self.on_reconnect.is_some())
+            .finish()
+    }
+}
+
+fn chain_connect(first: Option<ConnectHook>, second: Option<ConnectHook>) -> Option<ConnectHook> {
+    match (first, second) {
+        (Some(first), Some(second)) => Some(Arc::new(move |client: &mut RconClient| {
+            let first = first.clone();
+            let second = second.clone();
+            Box::pin(async move {
+                first(client).await?;
+                second(client).await
+            })
+        })),
+        (first, second) => first.or(second),
+    }
+}
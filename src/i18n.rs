@@ -0,0 +1,211 @@
+//! Localized messages of the command-line interface
+//!
+//! Messages are [Fluent](https://projectfluent.org) catalogs compiled into
+//! the binary from `locales/<lang>/cli.ftl`. English is the reference
+//! catalog and the fallback for anything a translation leaves out. Server
+//! responses and library error details are not translated.
+//!
+//! ```rust
+//! use rcon_cli::t;
+//!
+//! rcon_cli::i18n::init(Some("de"));
+//! let message = t!("players-online", online = 2, max = 20);
+//! assert_eq!(message, "2 von 20 Spielern online");
+//! ```
+
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::FluentResource;
+use regex::Regex;
+use std::sync::OnceLock;
+use tracing::warn;
+use unic_langid::LanguageIdentifier;
+
+pub use fluent_bundle::{FluentArgs, FluentValue};
+
+/// Locale used when nothing better matches
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// Catalogs compiled into the binary, reference catalog first
+const CATALOGS: &[(&str, &str)] = &[
+    ("en", include_str!("../locales/en/cli.ftl")),
+    ("de", include_str!("../locales/de/cli.ftl")),
+    ("es", include_str!("../locales/es/cli.ftl")),
+];
+
+static LOCALIZER: OnceLock<Localizer> = OnceLock::new();
+
+/// Translator for one locale, falling back to English
+struct Localizer {
+    locale: &'static str,
+    /// Selected locale first, then the fallback
+    bundles: Vec<FluentBundle<FluentResource>>,
+}
+
+impl Localizer {
+    fn new(locale: &'static str) -> Self {
+        let mut chain = vec![locale];
+        if locale != DEFAULT_LOCALE {
+            chain.push(DEFAULT_LOCALE);
+        }
+
+        let bundles = chain.into_iter().filter_map(bundle).collect();
+        Self { locale, bundles }
+    }
+
+    fn format(&self, id: &str, args: Option<&FluentArgs>) -> String {
+        for bundle in &self.bundles {
+            let Some(pattern) = bundle.get_message(id).and_then(|message| message.value()) else {
+                continue;
+            };
+
+            let mut errors = Vec::new();
+            let text = bundle.format_pattern(pattern, args, &mut errors);
+            if !errors.is_empty() {
+                warn!("Errors formatting message '{}': {:?}", id, errors);
+            }
+            return text.into_owned();
+        }
+
+        warn!("Missing message '{}'", id);
+        id.to_string()
+    }
+}
+
+/// Build the bundle for a catalog
+fn bundle(locale: &str) -> Option<FluentBundle<FluentResource>> {
+    let source = catalog(locale)?;
+    let resource =
+        FluentResource::try_new(source.to_string()).unwrap_or_else(|(resource, errors)| {
+            warn!("Errors parsing the '{}' catalog: {:?}", locale, errors);
+            resource
+        });
+    let langid: LanguageIdentifier = locale.parse().ok()?;
+
+    let mut bundle = FluentBundle::new_concurrent(vec![langid]);
+    // Unicode isolation marks show up as garbage in many terminals
+    bundle.set_use_isolating(false);
+    if let Err(errors) = bundle.add_resource(resource) {
+        warn!("Errors loading the '{}' catalog: {:?}", locale, errors);
+    }
+    Some(bundle)
+}
+
+/// Select the message locale
+///
+/// Uses `requested` if given, otherwise the first of `LC_ALL`,
+/// `LC_MESSAGES`, and `LANG` that is set. Only the first call has an
+/// effect; the selected locale is returned.
+pub fn init(requested: Option<&str>) -> &'static str {
+    LOCALIZER
+        .get_or_init(|| {
+            let requested = requested.map(str::to_string).or_else(locale_from_env);
+            Localizer::new(negotiate(requested.as_deref()))
+        })
+        .locale
+}
+
+/// The locale messages are shown in
+pub fn current_locale() -> &'static str {
+    init(None)
+}
+
+/// Read the locale from the POSIX environment variables
+pub fn locale_from_env() -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty())
+}
+
+/// Map a requested locale such as `de_DE.UTF-8` to an available catalog
+///
+/// Tries the full language tag, then just the language; anything else,
+/// including the `C` and `POSIX` locales, selects English.
+pub fn negotiate(requested: Option<&str>) -> &'static str {
+    let Some(requested) = requested else {
+        return DEFAULT_LOCALE;
+    };
+
+    // Strip the encoding and modifier: de_DE.UTF-8@euro -> de-DE
+    let tag = requested
+        .split(['.', '@'])
+        .next()
+        .unwrap_or_default()
+        .replace('_', "-");
+    let Ok(langid) = tag.parse::<LanguageIdentifier>() else {
+        return DEFAULT_LOCALE;
+    };
+
+    let full = langid.to_string();
+    let language = langid.language.as_str();
+    available_locales()
+        .find(|locale| locale.eq_ignore_ascii_case(&full))
+        .or_else(|| available_locales().find(|locale| locale.eq_ignore_ascii_case(language)))
+        .unwrap_or(DEFAULT_LOCALE)
+}
+
+/// Locales with a compiled-in catalog
+pub fn available_locales() -> impl Iterator<Item = &'static str> {
+    CATALOGS.iter().map(|(locale, _)| *locale)
+}
+
+/// Fluent source of a locale's catalog
+pub fn catalog(locale: &str) -> Option<&'static str> {
+    CATALOGS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(locale))
+        .map(|(_, source)| *source)
+}
+
+/// Message IDs defined in a Fluent source, in order
+pub fn message_ids(source: &str) -> Vec<&str> {
+    static MESSAGE: OnceLock<Regex> = OnceLock::new();
+    MESSAGE
+        .get_or_init(|| Regex::new(r"(?m)^([a-zA-Z][a-zA-Z0-9_-]*)\s*=").unwrap())
+        .captures_iter(source)
+        .filter_map(|captures| captures.get(1).map(|id| id.as_str()))
+        .collect()
+}
+
+/// Messages of the reference catalog that a locale doesn't translate
+///
+/// Returns `None` for locales without a catalog.
+pub fn missing_messages(locale: &str) -> Option<Vec<&'static str>> {
+    let translated = message_ids(catalog(locale)?);
+    let reference = catalog(DEFAULT_LOCALE).unwrap_or_default();
+    Some(
+        message_ids(reference)
+            .into_iter()
+            .filter(|id| !translated.contains(id))
+            .collect(),
+    )
+}
+
+/// Format a message in the current locale
+///
+/// Unknown IDs are returned as-is. Prefer the [`t!`](crate::t) macro.
+pub fn tr(id: &str, args: Option<&FluentArgs>) -> String {
+    LOCALIZER
+        .get_or_init(|| Localizer::new(DEFAULT_LOCALE))
+        .format(id, args)
+}
+
+/// Format a localized message, with optional named arguments
+///
+/// ```rust
+/// use rcon_cli::t;
+///
+/// assert_eq!(t!("interactive-goodbye"), "Goodbye!");
+/// assert_eq!(t!("ping-failed", attempt = 3), "Ping 3 failed");
+/// ```
+#[macro_export]
+macro_rules! t {
+    ($id:expr) => {
+        $crate::i18n::tr($id, None)
+    };
+    ($id:expr, $($name:ident = $value:expr),+ $(,)?) => {{
+        let mut args = $crate::i18n::FluentArgs::new();
+        $(args.set(stringify!($name), $value);)+
+        $crate::i18n::tr($id, Some(&args))
+    }};
+}
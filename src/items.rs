@@ -0,0 +1,392 @@
+//! Item IDs and `give` command syntax across Minecraft versions
+//!
+//! The registry in `data/items.txt` records when each item of the
+//! `minecraft` namespace was added and, for renamed items, when it was
+//! removed and what replaced it. Modded namespaces are not validated.
+
+use crate::arguments::resource_location;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+const REGISTRY: &str = include_str!("../data/items.txt");
+
+/// A Minecraft release such as 1.20.4
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct McVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl McVersion {
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// The flattening: first version with namespaced item IDs and NBT after the ID
+    pub const FLATTENING: McVersion = McVersion::new(1, 13, 0);
+
+    /// First version taking durations as ticks with optional units, e.g. `weather`
+    pub const TIME_UNITS: McVersion = McVersion::new(1, 19, 4);
+
+    /// First version with item components instead of NBT
+    pub const COMPONENTS: McVersion = McVersion::new(1, 20, 5);
+}
+
+impl FromStr for McVersion {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut parts = value.trim().split('.').map(str::parse::<u32>);
+        let invalid = || format!("Invalid Minecraft version '{}'", value);
+        let major = parts.next().and_then(Result::ok).ok_or_else(invalid)?;
+        let minor = parts.next().and_then(Result::ok).ok_or_else(invalid)?;
+        let patch = match parts.next() {
+            Some(patch) => patch.map_err(|_| invalid())?,
+            None => 0,
+        };
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+        Ok(Self::new(major, minor, patch))
+    }
+}
+
+impl fmt::Display for McVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.patch == 0 {
+            write!(f, "{}.{}", self.major, self.minor)
+        } else {
+            write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct RegistryEntry {
+    added: McVersion,
+    removed: Option<(McVersion, &'static str)>,
+}
+
+struct Registry {
+    items: HashMap<&'static str, RegistryEntry>,
+    /// Newest version the registry knows about
+    latest: McVersion,
+}
+
+fn registry() -> &'static Registry {
+    static REGISTRY_CELL: OnceLock<Registry> = OnceLock::new();
+    REGISTRY_CELL.get_or_init(|| {
+        let mut items = HashMap::new();
+        for line in REGISTRY.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&'static str> = line.split_whitespace().collect();
+            let version = |index: usize| fields.get(index).and_then(|v| v.parse().ok());
+            let (Some(id), Some(added)) = (fields.first(), version(1)) else {
+                continue;
+            };
+            let removed = version(2).zip(fields.get(3).copied());
+            items.insert(*id, RegistryEntry { added, removed });
+        }
+        let latest = items
+            .values()
+            .map(|entry| entry.added)
+            .max()
+            .unwrap_or(McVersion::FLATTENING);
+        Registry { items, latest }
+    })
+}
+
+/// Newest Minecraft version covered by the item registry
+pub fn latest_known_version() -> McVersion {
+    registry().latest
+}
+
+/// Why an item ID was rejected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ItemError {
+    /// Not a valid resource location
+    InvalidId(String),
+    /// Not in the registry, with the closest known ID
+    Unknown {
+        id: String,
+        suggestion: Option<String>,
+    },
+    /// Added after the server's version
+    TooNew { id: String, added: McVersion },
+    /// Renamed or removed before the server's version
+    Removed {
+        id: String,
+        removed: McVersion,
+        replacement: String,
+    },
+}
+
+impl fmt::Display for ItemError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ItemError::InvalidId(id) => write!(f, "'{}' is not a valid item ID", id),
+            ItemError::Unknown {
+                id,
+                suggestion: Some(suggestion),
+            } => write!(f, "Unknown item '{}' (did you mean '{}'?)", id, suggestion),
+            ItemError::Unknown { id, .. } => write!(f, "Unknown item '{}'", id),
+            ItemError::TooNew { id, added } => {
+                write!(f, "Item '{}' requires Minecraft {} or newer", id, added)
+            }
+            ItemError::Removed {
+                id,
+                removed,
+                replacement,
+            } => write!(
+                f,
+                "Item '{}' was renamed to '{}' in Minecraft {}",
+                id, replacement, removed
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ItemError {}
+
+/// Validate an item ID for a server version, returning it namespaced
+///
+/// Without a version the newest known one is assumed. IDs outside the
+/// `minecraft` namespace and servers older than 1.13 are only checked for
+/// syntax; on servers newer than the registry, unknown IDs are accepted.
+///
+/// ```
+/// use rcon_cli::items::{check_item, McVersion};
+///
+/// assert_eq!(check_item("diamond_sword", None).unwrap(), "minecraft:diamond_sword");
+/// assert!(check_item("mace", Some(McVersion::new(1, 20, 4))).is_err());
+/// assert!(check_item("diamond_swrod", None).is_err());
+/// ```
+pub fn check_item(id: &str, version: Option<McVersion>) -> Result<String, ItemError> {
+    let namespaced =
+        resource_location(id).map_err(|_| ItemError::InvalidId(id.trim().to_lowercase()))?;
+    let (namespace, path) = namespaced
+        .split_once(':')
+        .expect("resource locations are namespaced");
+
+    let registry = registry();
+    let version = version.unwrap_or(registry.latest);
+    if namespace != "minecraft" || version < McVersion::FLATTENING {
+        return Ok(namespaced);
+    }
+
+    match registry.items.get(path) {
+        Some(entry) if entry.added > version => Err(ItemError::TooNew {
+            id: namespaced,
+            added: entry.added,
+        }),
+        Some(RegistryEntry {
+            removed: Some((removed, replacement)),
+            ..
+        }) if *removed <= version => Err(ItemError::Removed {
+            id: namespaced,
+            removed: *removed,
+            replacement: format!("minecraft:{}", replacement),
+        }),
+        Some(_) => Ok(namespaced),
+        None if version > registry.latest => Ok(namespaced),
+        None => Err(ItemError::Unknown {
+            suggestion: closest_item(path, version).map(|item| format!("minecraft:{}", item)),
+            id: namespaced,
+        }),
+    }
+}
+
+/// The known item closest to `path` by edit distance, if any is close
+fn closest_item(path: &str, version: McVersion) -> Option<&'static str> {
+    registry()
+        .items
+        .iter()
+        .filter(|(_, entry)| {
+            entry.added <= version && entry.removed.is_none_or(|(removed, _)| removed > version)
+        })
+        .map(|(item, _)| (edit_distance(path, item), *item))
+        .filter(|(distance, item)| *distance <= 3.min(item.len() / 3 + 1))
+        .min()
+        .map(|(_, item)| item)
+}
+
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Vanilla names not following from the ID
+const DISPLAY_NAMES: &[(&str, &str)] = &[
+    ("beef", "Raw Beef"),
+    ("binding_curse", "Curse of Binding"),
+    ("chest_minecart", "Minecart with Chest"),
+    ("chicken", "Raw Chicken"),
+    ("cod", "Raw Cod"),
+    ("command_block_minecart", "Minecart with Command Block"),
+    ("cooked_beef", "Steak"),
+    ("experience_bottle", "Bottle o' Enchanting"),
+    ("filled_map", "Map"),
+    ("furnace_minecart", "Minecart with Furnace"),
+    ("hopper_minecart", "Minecart with Hopper"),
+    ("jack_o_lantern", "Jack o'Lantern"),
+    ("map", "Empty Map"),
+    ("mutton", "Raw Mutton"),
+    ("porkchop", "Raw Porkchop"),
+    ("rabbit", "Raw Rabbit"),
+    ("salmon", "Raw Salmon"),
+    ("sweeping", "Sweeping Edge"),
+    ("tnt", "TNT"),
+    ("tnt_minecart", "Minecart with TNT"),
+    ("vanishing_curse", "Curse of Vanishing"),
+];
+
+/// English display name of an item or enchantment ID
+///
+/// Names are derived from the ID's path, with the exceptions vanilla makes
+/// for some items; IDs that aren't resource locations are returned as is.
+///
+/// ```
+/// use rcon_cli::items::display_name;
+///
+/// assert_eq!(display_name("minecraft:totem_of_undying"), "Totem of Undying");
+/// assert_eq!(display_name("experience_bottle"), "Bottle o' Enchanting");
+/// assert_eq!(display_name("create:brass_ingot"), "Brass Ingot");
+/// ```
+pub fn display_name(id: &str) -> String {
+    let Ok(namespaced) = resource_location(id) else {
+        return id.to_string();
+    };
+    let (namespace, path) = namespaced
+        .split_once(':')
+        .expect("resource locations are namespaced");
+    let path = path.rsplit('/').next().unwrap_or(path);
+    if let Some((_, name)) = DISPLAY_NAMES
+        .iter()
+        .find(|(item, _)| namespace == "minecraft" && *item == path)
+    {
+        return name.to_string();
+    }
+
+    path.split('_')
+        .enumerate()
+        .map(|(index, word)| match word {
+            "a" | "and" | "in" | "of" | "on" | "the" | "with" if index > 0 => word.to_string(),
+            word => {
+                let mut chars = word.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_uppercase().chain(chars).collect())
+                    .unwrap_or_default()
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Assemble a `give` command in the syntax of `version`
+///
+/// `data` is NBT before 1.20.5 and item components since, already in SNBT
+/// form (see [`json_to_snbt`]). Without a version the newest syntax is used.
+///
+/// ```
+/// use rcon_cli::items::{give_command, McVersion};
+///
+/// let nbt = Some("{Unbreakable:1b}");
+/// assert_eq!(
+///     give_command("Steve", "minecraft:stick", 2, nbt, Some(McVersion::new(1, 16, 5))),
+///     "give Steve minecraft:stick{Unbreakable:1b} 2"
+/// );
+/// assert_eq!(
+///     give_command("Steve", "minecraft:stick", 2, nbt, Some(McVersion::new(1, 12, 2))),
+///     "give Steve minecraft:stick 2 0 {Unbreakable:1b}"
+/// );
+/// ```
+pub fn give_command(
+    player: &str,
+    item: &str,
+    count: u32,
+    data: Option<&str>,
+    version: Option<McVersion>,
+) -> String {
+    match (version, data) {
+        (Some(version), Some(data)) if version < McVersion::FLATTENING => {
+            format!("give {} {} {} 0 {}", player, item, count, data)
+        }
+        (_, Some(data)) => format!("give {} {}{} {}", player, item, data, count),
+        (_, None) => format!("give {} {} {}", player, item, count),
+    }
+}
+
+/// Convert item data given as JSON into SNBT for `give`
+///
+/// Before 1.20.5 the object becomes an NBT compound; since then each key is
+/// an item component, e.g. `{"minecraft:custom_name": "\"Excalibur\""}`.
+/// Numbers keep their JSON form, so typed suffixes such as `1b` need SNBT.
+pub fn json_to_snbt(value: &Value, version: Option<McVersion>) -> String {
+    match (value, version) {
+        (Value::Object(components), None) => components_snbt(components),
+        (Value::Object(components), Some(version)) if version >= McVersion::COMPONENTS => {
+            components_snbt(components)
+        }
+        (value, _) => snbt(value),
+    }
+}
+
+fn components_snbt(components: &serde_json::Map<String, Value>) -> String {
+    let components: Vec<String> = components
+        .iter()
+        .map(|(name, value)| format!("{}={}", name, snbt(value)))
+        .collect();
+    format!("[{}]", components.join(","))
+}
+
+fn snbt(value: &Value) -> String {
+    match value {
+        Value::Null => "{}".to_string(),
+        Value::Bool(flag) => flag.to_string(),
+        Value::Number(number) => number.to_string(),
+        Value::String(text) => quote(text),
+        Value::Array(values) => {
+            let values: Vec<String> = values.iter().map(snbt).collect();
+            format!("[{}]", values.join(","))
+        }
+        Value::Object(fields) => {
+            let fields: Vec<String> = fields
+                .iter()
+                .map(|(key, value)| {
+                    let plain = key
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || "_-.+".contains(c));
+                    let key = if plain { key.clone() } else { quote(key) };
+                    format!("{}:{}", key, snbt(value))
+                })
+                .collect();
+            format!("{{{}}}", fields.join(","))
+        }
+    }
+}
+
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
@@ -0,0 +1,300 @@
+//! Kubernetes service discovery
+//!
+//! A profile (or `--k8s`) can name the Service in front of a Minecraft pod
+//! instead of an address. The Service's RCON port and any load balancer
+//! address come from the cluster API, the password from a Secret. Without
+//! API access the cluster DNS name and the default port are used.
+
+use crate::config::Profile;
+use crate::error::{RconError, Result};
+use base64::Engine;
+use serde::Deserialize;
+use serde_json::Value;
+use std::fmt;
+use std::str::FromStr;
+
+/// Service account files mounted into every pod
+const SERVICE_ACCOUNT_DIR: &str = "/var/run/secrets/kubernetes.io/serviceaccount";
+
+/// Secret key used by the common Minecraft Helm chart
+const DEFAULT_SECRET_KEY: &str = "rcon-password";
+
+/// Port of a Service that names none of its ports `rcon`
+const DEFAULT_RCON_PORT: u16 = 25575;
+
+/// Where a profile's server is found in a cluster
+///
+/// ```toml
+/// [profiles.survival.k8s]
+/// service = "games/survival"
+/// secret = "survival-rcon:rcon-password"
+/// api = "http://127.0.0.1:8001"
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct K8sConfig {
+    /// Service as `namespace/name`
+    pub service: String,
+    /// Secret holding the password, as `name[:key]`
+    pub secret: Option<String>,
+    /// API server URL, e.g. a `kubectl proxy` [default: in-cluster service
+    /// account]
+    pub api: Option<String>,
+}
+
+/// A Service given as `namespace/name`, or just `name` in `default`
+///
+/// ```
+/// use rcon_cli::k8s::ServiceRef;
+///
+/// let service: ServiceRef = "games/survival".parse().unwrap();
+/// assert_eq!(service.namespace, "games");
+/// assert_eq!(service.name, "survival");
+/// assert_eq!("survival".parse::<ServiceRef>().unwrap().namespace, "default");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServiceRef {
+    pub namespace: String,
+    pub name: String,
+}
+
+impl FromStr for ServiceRef {
+    type Err = RconError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (namespace, name) = s.split_once('/').unwrap_or(("default", s));
+        if namespace.is_empty() || name.is_empty() || name.contains('/') {
+            return Err(RconError::InvalidConfig(format!(
+                "Invalid service '{}', expected namespace/name",
+                s
+            )));
+        }
+        Ok(Self {
+            namespace: namespace.to_string(),
+            name: name.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for ServiceRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.namespace, self.name)
+    }
+}
+
+/// A Secret key given as `name[:key]`, in the Service's namespace
+///
+/// ```
+/// use rcon_cli::k8s::SecretRef;
+///
+/// let secret: SecretRef = "survival-rcon".parse().unwrap();
+/// assert_eq!(secret.key, "rcon-password");
+/// assert_eq!("creds:password".parse::<SecretRef>().unwrap().key, "password");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecretRef {
+    pub name: String,
+    pub key: String,
+}
+
+impl FromStr for SecretRef {
+    type Err = RconError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (name, key) = s.split_once(':').unwrap_or((s, DEFAULT_SECRET_KEY));
+        if name.is_empty() || key.is_empty() {
+            return Err(RconError::InvalidConfig(format!(
+                "Invalid secret '{}', expected name[:key]",
+                s
+            )));
+        }
+        Ok(Self {
+            name: name.to_string(),
+            key: key.to_string(),
+        })
+    }
+}
+
+fn k8s_error(message: impl Into<String>) -> RconError {
+    RconError::Kubernetes(message.into())
+}
+
+/// Access to the cluster API
+pub struct K8sApi {
+    client: reqwest::Client,
+    base: String,
+    token: Option<String>,
+}
+
+impl K8sApi {
+    /// Talk to an API server without authentication, as behind
+    /// `kubectl proxy`
+    pub fn new(base: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base: base.into().trim_end_matches('/').to_string(),
+            token: None,
+        }
+    }
+
+    /// Use the pod's service account, if running inside a cluster
+    pub fn in_cluster() -> Result<Option<Self>> {
+        let (Ok(host), Ok(port)) = (
+            std::env::var("KUBERNETES_SERVICE_HOST"),
+            std::env::var("KUBERNETES_SERVICE_PORT"),
+        ) else {
+            return Ok(None);
+        };
+
+        let read = |file: &str| {
+            std::fs::read(format!("{}/{}", SERVICE_ACCOUNT_DIR, file)).map_err(|e| {
+                k8s_error(format!(
+                    "Failed to read the service account {}: {}",
+                    file, e
+                ))
+            })
+        };
+        let token = String::from_utf8_lossy(&read("token")?).trim().to_string();
+        let ca = reqwest::Certificate::from_pem(&read("ca.crt")?)
+            .map_err(|e| k8s_error(format!("Invalid cluster CA certificate: {}", e)))?;
+        let client = reqwest::Client::builder()
+            .add_root_certificate(ca)
+            .build()
+            .map_err(|e| k8s_error(e.to_string()))?;
+
+        let host = if host.contains(':') {
+            format!("[{}]", host)
+        } else {
+            host
+        };
+        Ok(Some(Self {
+            client,
+            base: format!("https://{}:{}", host, port),
+            token: Some(token),
+        }))
+    }
+
+    async fn get(&self, path: &str) -> Result<Value> {
+        let url = format!("{}{}", self.base, path);
+        let mut request = self.client.get(&url);
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        let fetch = async { request.send().await?.error_for_status()?.json().await };
+        fetch
+            .await
+            .map_err(|e| k8s_error(format!("Request to {} failed: {}", url, e.without_url())))
+    }
+
+    /// Read one key of a Secret
+    pub async fn secret(&self, namespace: &str, secret: &SecretRef) -> Result<String> {
+        let object = self
+            .get(&format!(
+                "/api/v1/namespaces/{}/secrets/{}",
+                namespace, secret.name
+            ))
+            .await?;
+        let encoded = object["data"][&secret.key].as_str().ok_or_else(|| {
+            k8s_error(format!(
+                "Secret '{}' has no key '{}'",
+                secret.name, secret.key
+            ))
+        })?;
+        let decoded = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .map_err(|e| k8s_error(format!("Secret '{}' is not base64: {}", secret.name, e)))?;
+        String::from_utf8(decoded)
+            .map(|password| password.trim_end_matches('\n').to_string())
+            .map_err(|_| k8s_error(format!("Secret '{}' is not UTF-8", secret.name)))
+    }
+
+    /// Find the address of a Service's RCON port
+    ///
+    /// The port named `rcon` wins, then one numbered 25575. Load balancers
+    /// are reached through their external address, everything else through
+    /// the cluster DNS name.
+    pub async fn service_address(&self, service: &ServiceRef) -> Result<String> {
+        let object = self
+            .get(&format!(
+                "/api/v1/namespaces/{}/services/{}",
+                service.namespace, service.name
+            ))
+            .await?;
+
+        let ports = object["spec"]["ports"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default();
+        let port = ports
+            .iter()
+            .find(|port| port["name"].as_str() == Some("rcon"))
+            .or_else(|| {
+                ports
+                    .iter()
+                    .find(|port| port["port"].as_u64() == Some(DEFAULT_RCON_PORT.into()))
+            })
+            .and_then(|port| port["port"].as_u64())
+            .ok_or_else(|| {
+                k8s_error(format!(
+                    "Service '{}' has no port named 'rcon' or numbered {}",
+                    service, DEFAULT_RCON_PORT
+                ))
+            })?;
+
+        let ingress = &object["status"]["loadBalancer"]["ingress"][0];
+        Ok(
+            match ingress["ip"].as_str().or(ingress["hostname"].as_str()) {
+                Some(host) if host.contains(':') => format!("[{}]:{}", host, port),
+                Some(host) => format!("{}:{}", host, port),
+                None => format!("{}:{}", cluster_dns_name(service), port),
+            },
+        )
+    }
+}
+
+/// Name the cluster DNS gives a Service
+pub fn cluster_dns_name(service: &ServiceRef) -> String {
+    format!("{}.{}.svc", service.name, service.namespace)
+}
+
+/// Fill in the address and password a profile leaves to Kubernetes
+///
+/// Settings given in the profile itself are kept. Without API access the
+/// Service's DNS name is used with the default port, and a Secret cannot be
+/// read.
+pub async fn complete_profile(profile: &Profile) -> Result<Profile> {
+    let mut profile = profile.clone();
+    let Some(k8s) = profile.k8s.clone() else {
+        return Ok(profile);
+    };
+    let service: ServiceRef = k8s.service.parse()?;
+    let secret = k8s
+        .secret
+        .as_deref()
+        .map(str::parse::<SecretRef>)
+        .transpose()?;
+
+    let api = match &k8s.api {
+        Some(base) => Some(K8sApi::new(base)),
+        None => K8sApi::in_cluster()?,
+    };
+    let Some(api) = api else {
+        if profile.password.is_none() && secret.is_some() {
+            return Err(k8s_error(
+                "Reading a Secret needs the cluster API: run inside the cluster or point --k8s-api at `kubectl proxy`",
+            ));
+        }
+        profile
+            .address
+            .get_or_insert_with(|| format!("{}:{}", cluster_dns_name(&service), DEFAULT_RCON_PORT));
+        return Ok(profile);
+    };
+
+    if profile.address.is_none() {
+        profile.address = Some(api.service_address(&service).await?);
+    }
+    if let (None, Some(secret)) = (&profile.password, &secret) {
+        profile.password = Some(api.secret(&service.namespace, secret).await?);
+    }
+    Ok(profile)
+}
@@ -0,0 +1,115 @@
+use crate::client::{CommandResponse, RconClient, RconConfig};
+use crate::error::{RconError, Result};
+use tracing::debug;
+
+/// RCON client handle that defers connecting until it is first used
+///
+/// The connection is dialed on the first command. If the server closed the
+/// connection while it sat idle, the next command transparently re-dials
+/// and re-authenticates before being sent. Idle closes are detected before
+/// sending (see [`ResyncPolicy::flush_stale`](crate::client::ResyncPolicy)),
+/// so a command is never sent twice; other errors that lose the connection,
+/// such as network errors or a malformed packet, are returned and the handle
+/// re-dials on the following command.
+pub struct LazyRconClient {
+    config: RconConfig,
+    client: Option<RconClient>,
+    needs_reconnect: bool,
+}
+
+impl LazyRconClient {
+    /// Create a handle without opening any connection
+    pub fn new(config: RconConfig) -> Self {
+        Self {
+            config,
+            client: None,
+            needs_reconnect: false,
+        }
+    }
+
+    /// Execute a command, connecting or re-dialing first if needed
+    pub async fn execute_command(&mut self, command: impl AsRef<str>) -> Result<String> {
+        self.execute_command_detailed(command)
+            .await
+            .map(|response| response.body)
+    }
+
+    /// Execute a command and report how the response was received
+    pub async fn execute_command_detailed(
+        &mut self,
+        command: impl AsRef<str>,
+    ) -> Result<CommandResponse> {
+        let command = command.as_ref();
+        let client = self.client().await?;
+
+        let result = match client.execute_command_detailed(command).await {
+            Err(RconError::Disconnected) => {
+                debug!("Idle connection was closed by the server, re-dialing");
+                client.reconnect().await?;
+                client.execute_command_detailed(command).await
+            }
+            other => other,
+        };
+
+        if matches!(result, Err(RconError::Network(_))) || client.is_closed() {
+            self.needs_reconnect = true;
+        }
+
+        result
+    }
+
+    /// Test connectivity, connecting first if needed
+    pub async fn ping(&mut self) -> Result<()> {
+        self.execute_command("list").await.map(|_| ())
+    }
+
+    /// Get the connected client, dialing or re-dialing the server if needed
+    pub async fn client(&mut self) -> Result<&mut RconClient> {
+        if self.needs_reconnect || self.client.as_ref().is_some_and(RconClient::is_closed) {
+            if let Some(client) = self.client.as_mut() {
+                client.reconnect().await?;
+            }
+            self.needs_reconnect = false;
+        }
+
+        match self.client {
+            Some(ref mut client) => Ok(client),
+            None => {
+                debug!(
+                    "Establishing deferred connection to {}",
+                    self.config.address
+                );
+                let client = RconClient::connect(self.config.clone()).await?;
+                Ok(self.client.insert(client))
+            }
+        }
+    }
+
+    /// Check if a connection has been established
+    pub fn is_connected(&self) -> bool {
+        self.client
+            .as_ref()
+            .is_some_and(|client| !client.is_closed())
+            && !self.needs_reconnect
+    }
+
+    /// Drop the current connection; the next command dials again
+    pub fn disconnect(&mut self) {
+        self.client = None;
+        self.needs_reconnect = false;
+    }
+
+    /// Close the current connection cleanly; the next command dials again
+    pub async fn close(&mut self) -> Result<()> {
+        self.needs_reconnect = false;
+        match self.client.take() {
+            Some(mut client) => client.close().await,
+            None => Ok(()),
+        }
+    }
+
+    /// Get the configuration used to dial the server
+    pub fn config(&self) -> &RconConfig {
+        &self.config
+    }
+}
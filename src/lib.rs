@@ -32,15 +32,85 @@
 //! }
 //! ```
 
+pub mod advancements;
+pub mod alert;
+pub mod arguments;
+pub mod assertions;
+pub mod bulk;
+pub mod chunks;
 pub mod cli;
 pub mod client;
+pub mod config;
+#[cfg(unix)]
+pub mod daemon;
+pub mod delivery;
+pub mod detect;
+pub mod dialect;
+pub mod doctor;
+pub mod dry_run;
 pub mod error;
+pub mod events;
+pub mod executor;
+pub mod filter;
+pub mod fixtures;
+pub mod grafana;
+pub mod highlight;
+pub mod history;
+pub mod hooks;
+pub mod i18n;
+pub mod items;
+pub mod k8s;
+pub mod lazy;
+pub mod lint;
+pub mod maintenance;
+pub mod metrics;
+pub mod mock;
+pub mod mock_server;
+pub mod mqtt;
+pub mod mux;
+pub mod nbt;
+pub mod net;
+pub mod normalize;
+pub mod outcome;
+pub mod panel;
+pub mod parser_plugins;
+pub mod parsers;
+pub mod pins;
+pub mod playbook;
+pub mod policy;
+#[cfg(any(feature = "deadpool", feature = "bb8"))]
+pub mod pool;
+pub mod progress;
 pub mod protocol;
+pub mod queue;
+pub mod redact;
+pub mod schema;
+pub mod script;
+pub mod scripting;
+pub mod sessions;
+pub mod shutdown;
+pub mod state_file;
+#[cfg(unix)]
+pub mod systemd;
+pub mod wait;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "web")]
+pub mod web;
+pub mod whitelist;
 
 // Re-export commonly used types
 pub use cli::{Cli, Commands, OutputFormat, OutputFormatter};
-pub use client::{RconClient, RconClientBuilder, RconConfig};
+pub use client::{
+    CommandResponse, RconClient, RconClientBuilder, RconConfig, ReconnectPolicy, ResyncPolicy,
+};
+pub use config::{ConfigFile, Profile};
+pub use detect::{Flavor, ServerInfo};
+pub use dialect::{Dialect, DialectKind};
 pub use error::{RconError, Result};
+pub use executor::RconExecutor;
+pub use hooks::ClientHooks;
+pub use lazy::LazyRconClient;
 pub use protocol::{packet_type, RconPacket};
 
 /// Library version
@@ -50,6 +120,8 @@ pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 pub const DEFAULT_PORT: u16 = 25575;
 
 /// Initialize logging for the library
+///
+/// Passwords and sensitive command arguments are masked, see [`redact`].
 pub fn init_logging(level: &str) -> Result<()> {
     use tracing_subscriber::{fmt, prelude::*, EnvFilter};
 
@@ -57,7 +129,12 @@ pub fn init_logging(level: &str) -> Result<()> {
         .map_err(|e| RconError::InvalidConfig(format!("Invalid log level: {}", e)))?;
 
     tracing_subscriber::registry()
-        .with(fmt::layer().with_target(false).with_thread_ids(false))
+        .with(
+            fmt::layer()
+                .with_target(false)
+                .with_thread_ids(false)
+                .with_writer(|| redact::RedactingWriter(std::io::stdout())),
+        )
         .with(filter)
         .try_init()
         .map_err(|e| RconError::InvalidConfig(format!("Failed to initialize logging: {}", e)))?;
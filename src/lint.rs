@@ -0,0 +1,164 @@
+//! Offline checks of RCON scripts
+//!
+//! `lint` reads scripts the way `on-ready` does and checks every command
+//! without connecting to a server: against a command tree, for malformed
+//! target selectors, for payloads longer than the dialect accepts, and
+//! against a command policy. It is meant for pre-commit hooks and CI in
+//! repositories that keep maintenance scripts.
+
+use crate::arguments::validate_entity;
+use crate::dialect::Dialect;
+use crate::policy::CommandPolicy;
+use crate::schema::{split_command, CommandSchema};
+use crate::script::ScriptLine;
+use std::fmt;
+use std::sync::Arc;
+
+/// What a [`LintIssue`] was found by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Check {
+    /// Unknown command or arguments that don't fit the command tree
+    Command,
+    /// A target selector that can't be parsed
+    Selector,
+    /// Longer than a request packet of the dialect may be
+    Length,
+    /// Rejected by the command policy
+    Policy,
+}
+
+impl Check {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Check::Command => "command",
+            Check::Selector => "selector",
+            Check::Length => "length",
+            Check::Policy => "policy",
+        }
+    }
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A problem with one line of a script
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintIssue {
+    /// 1-based line number in the script
+    pub line_number: usize,
+    pub check: Check,
+    pub message: String,
+}
+
+/// The checks applied to each command
+///
+/// ```
+/// use rcon_cli::dialect::MinecraftJava;
+/// use rcon_cli::lint::{Check, Linter};
+/// use rcon_cli::policy::CommandPolicy;
+/// use rcon_cli::schema::CommandSchema;
+/// use rcon_cli::script::parse_script;
+/// use std::sync::Arc;
+///
+/// let linter = Linter::new(Arc::new(MinecraftJava))
+///     .with_schema(CommandSchema::builtin())
+///     .with_policy(CommandPolicy::allow("maintenance", ["say", "save-all", "tp"]));
+/// let script = parse_script("# Nightly restart\nsay Restarting soon\nsave-all\ntp @q ~ ~ ~\nstop\n").unwrap();
+/// let issues = linter.lint(&script);
+///
+/// assert_eq!(issues.len(), 2);
+/// assert_eq!((issues[0].line_number, issues[0].check), (4, Check::Selector));
+/// assert_eq!((issues[1].line_number, issues[1].check), (5, Check::Policy));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Linter {
+    dialect: Arc<dyn Dialect>,
+    schema: Option<CommandSchema>,
+    policy: Option<CommandPolicy>,
+}
+
+impl Linter {
+    /// Check selectors and lengths only
+    pub fn new(dialect: Arc<dyn Dialect>) -> Self {
+        Self {
+            dialect,
+            schema: None,
+            policy: None,
+        }
+    }
+
+    /// Also check commands against a command tree
+    pub fn with_schema(mut self, schema: CommandSchema) -> Self {
+        self.schema = Some(schema);
+        self
+    }
+
+    /// Also check that a policy permits every command
+    pub fn with_policy(mut self, policy: CommandPolicy) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    /// Every problem with one command
+    ///
+    /// A malformed selector is reported on its own rather than again as an
+    /// argument the command tree doesn't accept.
+    pub fn check(&self, command: &str) -> Vec<(Check, String)> {
+        let command = command.trim();
+        let mut issues = Vec::new();
+
+        let bare = command.strip_prefix('/').unwrap_or(command);
+        for (_, token) in split_command(bare) {
+            if token.starts_with('@') {
+                if let Err(e) = validate_entity(token) {
+                    issues.push((Check::Selector, e));
+                }
+            }
+        }
+        if let Some(schema) = self.schema.as_ref().filter(|_| issues.is_empty()) {
+            if let Err(problem) = schema.validate(command) {
+                issues.push((Check::Command, problem.to_string()));
+            }
+        }
+
+        let length = self.dialect.encode(command).len();
+        let max = self.dialect.max_request_payload();
+        if length > max {
+            issues.push((
+                Check::Length,
+                format!(
+                    "{} bytes, but {} servers accept at most {}",
+                    length,
+                    self.dialect.name(),
+                    max
+                ),
+            ));
+        }
+
+        if let Some(policy) = &self.policy {
+            if let Err(e) = policy.check(command) {
+                issues.push((Check::Policy, e.to_string()));
+            }
+        }
+        issues
+    }
+
+    /// Every problem in a parsed script, in line order
+    pub fn lint(&self, script: &[ScriptLine]) -> Vec<LintIssue> {
+        script
+            .iter()
+            .flat_map(|line| {
+                self.check(&line.command)
+                    .into_iter()
+                    .map(|(check, message)| LintIssue {
+                        line_number: line.line_number,
+                        check,
+                        message,
+                    })
+            })
+            .collect()
+    }
+}
@@ -1,77 +1,1082 @@
-use clap::Parser;
+use chrono::{SecondsFormat, Utc};
+use clap::{Parser, ValueEnum};
+use futures_util::future::join_all;
+#[cfg(unix)]
+use rcon_cli::daemon::{default_socket_path, Daemon, DaemonClient, DaemonRequest};
+#[cfg(unix)]
+use rcon_cli::systemd;
+#[cfg(feature = "wasm")]
+use rcon_cli::wasm::PluginHost;
 use rcon_cli::{
-    cli::{Cli, Commands, OutputFormatter},
+    advancements::{complete_advancement, AdvancementError},
+    alert::{AlertRule, AlertState, AlertTransition, Comparison, Condition, Metric},
+    arguments::{
+        execute_as, join_command, join_lines, with_prefix, Destination, EffectDuration, Position,
+    },
+    bulk::{Table, Template},
+    chunks::{BlockColumn, ChunkArea, FORCELOAD_LIMIT},
+    cli::{
+        format_duration, parse_amount, parse_player, AddonsCommand, AdvancementAction, Cli,
+        Commands, DataCommand, EcoCommand, EffectCommand, ErrorReport, ForceloadCommand,
+        MaintenanceCommand, NetCommand, OutputFormatter, PermsAction, PermsHolder, QueueCommand,
+        Target, TimeCommand, WeatherKind, WhitelistCommand, XpCommand, DEFAULT_ADDRESS,
+        DEFAULT_TIMEOUT_SECS,
+    },
     client::RconConfig,
-    RconClient, RconError,
+    config::{ConfigFile, Profile},
+    delivery::{self, DeliveryLedger, DeliveryReport},
+    detect::TpsSource,
+    doctor::{diagnose, CheckStatus, DoctorOptions},
+    dry_run::DryRun,
+    events::{Event, EventBus, EventBusBuilder, EventKind},
+    grafana::{Annotation, GrafanaClient},
+    highlight::Highlighter,
+    history::{self, History, Sample},
+    i18n,
+    items::{
+        check_item, display_name, give_command, json_to_snbt, latest_known_version, McVersion,
+    },
+    k8s,
+    lint::Linter,
+    maintenance::{self, render_message, AuditLog, WindowEvent},
+    metrics::{MetricsTarget, MetricsWriter},
+    mqtt::MqttPublisher,
+    mux::{Priority, RateLimit},
+    nbt::{merge_commands, DataTarget, Tag},
+    normalize::strip_formatting,
+    outcome::ErrorClassifier,
+    packet_type, panel,
+    parser_plugins::ExternalParsers,
+    parsers::{
+        luckperms_lines, parse_balance, parse_balance_change, parse_banlist, parse_bukkit_tps,
+        parse_datapack_list, parse_difficulty, parse_effect_change, parse_forceload_change,
+        parse_forceload_list, parse_forceload_query, parse_forge_tps, parse_inventory,
+        parse_luckperms_info, parse_mod_list, parse_permission_check, parse_player_list,
+        parse_plugin_list, parse_plugin_version, parse_save_response, parse_seed,
+        parse_test_result, parse_tick_query, parse_time, parse_whitelist, parse_whitelist_toggle,
+        parse_world_border, parse_xp_change, parse_xp_query, Addon, AddonKind, PlayerList,
+        SaveStatus, XpUnit,
+    },
+    pins::{self, fingerprint, PinCheck, PinMode, PinPolicy, PinStore},
+    playbook::{Playbook, Step, StepStatus},
+    policy::{CommandPolicy, READ_ONLY_COMMANDS},
+    progress::ConnectPhase,
+    queue::{self, CommandQueue},
+    redact::{self, Redactor},
+    schema::CommandSchema,
+    script::{load_script, Severity},
+    scripting::{Outcome, ScriptEngine},
+    sessions::{SessionEvent, SessionTracker},
+    shutdown::Shutdown,
+    t,
+    wait::{wait_for_server, WaitOptions},
+    whitelist::{fetch_roster, load_roster, WhitelistDiff},
+    ClientHooks, RconClient, RconError, RconExecutor,
 };
-use std::io::{self, Write};
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{CompletionType, Editor, Helper};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::io::{self, Read};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(unix)]
+use std::sync::Arc;
+use std::sync::Mutex;
 use std::time::{Duration, Instant};
+#[cfg(unix)]
+use tokio::net::UnixListener;
+#[cfg(unix)]
+use tokio::signal::unix::{signal, SignalKind};
 use tokio::time::sleep;
 use tracing::info;
 
+/// Exit status when `--deadline` expires, matching coreutils `timeout`
+const DEADLINE_EXIT_CODE: i32 = 124;
+
+/// Commands printed so far by `--dry-run`
+static DRY_RUN_COMMANDS: AtomicUsize = AtomicUsize::new(0);
+
 #[tokio::main]
 async fn main() {
-    let result = run().await;
+    let cli = Cli::parse();
+    i18n::init(cli.lang.as_deref());
+
+    // Create output formatter
+    let formatter = cli.formatter().unwrap_or_else(|e| {
+        let path = cli.output.as_deref().unwrap_or(Path::new("-"));
+        let report = ErrorReport::new(
+            "output",
+            t!(
+                "output-open-failed",
+                path = path.display().to_string(),
+                error = e.to_string()
+            ),
+        );
+        let fallback = OutputFormatter::new(cli.format.clone(), false);
+        eprintln!("{}", fallback.format_failure(&report));
+        std::process::exit(1);
+    });
+
+    // Load server profiles; a missing default config file is not an error
+    let config_file = ConfigFile::load_or_default(cli.config.as_deref()).unwrap_or_else(|e| {
+        eprintln!("{}", formatter.format_rcon_error(&e));
+        std::process::exit(1);
+    });
+    redact::install(Redactor::from_config(&config_file.redact));
+    let parsers = load_parsers(&config_file, &formatter);
+    let formatter = match Highlighter::from_config(&config_file.highlight) {
+        Ok(highlighter) => formatter.with_highlighter(highlighter),
+        Err(e) => {
+            eprintln!("{}", formatter.format_rcon_error(&e));
+            std::process::exit(1);
+        }
+    }
+    .with_parsers(parsers);
+
+    let result = match cli.deadline {
+        Some(deadline) => {
+            match tokio::time::timeout(deadline, run(&cli, &config_file, &formatter)).await {
+                Ok(result) => result,
+                Err(_) => {
+                    let report = ErrorReport::new(
+                        "deadline_exceeded",
+                        t!(
+                            "deadline-exceeded",
+                            seconds = format!("{:.3}", deadline.as_secs_f64())
+                        ),
+                    );
+                    eprintln!("{}", formatter.format_failure(&report));
+                    std::process::exit(DEADLINE_EXIT_CODE);
+                }
+            }
+        }
+        None => run(&cli, &config_file, &formatter).await,
+    };
 
     if let Err(e) = result {
-        eprintln!("Error: {}", e);
+        // Dry runs end where the next step depends on what the server says
+        if cli.dry_run && DRY_RUN_COMMANDS.load(Ordering::Relaxed) > 0 {
+            formatter.progress(&t!("dry-run-stopped", error = e.to_string()));
+            return;
+        }
+        eprintln!("{}", render_error(&formatter, e.as_ref()));
         std::process::exit(1);
     }
 }
 
-async fn run() -> Result<(), Box<dyn std::error::Error>> {
-    let cli = Cli::parse();
+/// Fill in what a profile leaves to its hosting panel or Kubernetes
+async fn lookup_profile(profile: &Profile) -> Result<Profile, RconError> {
+    let profile = panel::complete_profile(profile).await?;
+    k8s::complete_profile(&profile).await
+}
+
+/// The selected profile, with what it leaves out looked up
+///
+/// Nothing is looked up when the flags give both the address and password.
+async fn complete_selected_profile(
+    cli: &Cli,
+    config_file: &ConfigFile,
+) -> Result<Profile, RconError> {
+    let profile = cli.selected_profile(config_file)?;
+    if cli.address.is_some() && cli.password.is_some() {
+        return Ok(profile);
+    }
+    lookup_profile(&profile).await
+}
+
+/// Resolve the target for a profile, looking up what it leaves out
+async fn resolve_profile_target(
+    cli: &Cli,
+    name: &str,
+    profile: &Profile,
+) -> Result<Target, String> {
+    let profile = lookup_profile(profile).await.map_err(|e| e.to_string())?;
+    cli.resolve_profile(name, &profile)
+}
+
+/// Render an error returned from `run` in the selected output format
+fn render_error(formatter: &OutputFormatter, error: &(dyn std::error::Error + 'static)) -> String {
+    if let Some(report) = error.downcast_ref::<ErrorReport>() {
+        formatter.format_failure(report)
+    } else if let Some(error) = error.downcast_ref::<RconError>() {
+        formatter.format_rcon_error(error)
+    } else {
+        formatter.format_error(&error.to_string())
+    }
+}
+
+/// Report invalid command-line arguments and exit
+fn exit_invalid_arguments(formatter: &OutputFormatter, error: impl std::fmt::Display) -> ! {
+    let report = ErrorReport::new(
+        "invalid_arguments",
+        t!("invalid-arguments", error = error.to_string()),
+    );
+    eprintln!("{}", formatter.format_failure(&report));
+    std::process::exit(1);
+}
 
+async fn run(
+    cli: &Cli,
+    config_file: &ConfigFile,
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
     // Validate CLI arguments
     if let Err(e) = cli.validate() {
-        eprintln!("Invalid arguments: {}", e);
-        std::process::exit(1);
+        exit_invalid_arguments(formatter, e);
     }
 
     // Initialize logging
     if let Err(e) = rcon_cli::init_logging(cli.log_level()) {
-        eprintln!("Failed to initialize logging: {}", e);
+        let report = ErrorReport::from(&e).context(t!("logging-init-failed"));
+        eprintln!("{}", formatter.format_failure(&report));
         // Continue anyway, logging is not critical
     }
 
-    // Create output formatter
-    let formatter = OutputFormatter::new(cli.format.clone(), cli.use_colors());
+    if let Commands::Ping {
+        count,
+        interval,
+        all_profiles: true,
+        record,
+        metrics_out,
+    } = &cli.command
+    {
+        let (metrics, formatter) = metrics_output(cli, metrics_out.as_ref(), formatter);
+        let events = ping_subscribers(
+            event_bus(String::new(), &formatter),
+            record,
+            metrics,
+            &formatter,
+        )
+        .start();
+        let result =
+            run_ping_all_profiles(cli, config_file, *count, *interval, &events, &formatter).await;
+        events.close().await;
+        return result;
+    }
+
+    if let Commands::Report {
+        since,
+        history,
+        target,
+    } = &cli.command
+    {
+        return run_report_command(*since, history.as_deref(), target.as_deref(), formatter);
+    }
+
+    if let Commands::Annotate { text, tags } = &cli.command {
+        return run_annotate_command(cli, config_file, text, tags, formatter).await;
+    }
+
+    if let Commands::Daemon {
+        rate_limit,
+        max_queue,
+        status,
+    } = &cli.command
+    {
+        if *status {
+            return run_daemon_status(cli, formatter).await;
+        }
+        let limits = DaemonLimits {
+            rate_limit: *rate_limit,
+            max_queue: max_queue.map(|max| max as usize),
+        };
+        return run_daemon(cli, config_file, limits, formatter).await;
+    }
+
+    if let Commands::Net { network, action } = &cli.command {
+        return run_net_command(cli, config_file, network.as_deref(), action, formatter).await;
+    }
+
+    if let Commands::Profiles = &cli.command {
+        return run_profiles_command(config_file, formatter);
+    }
+
+    if let Commands::Lint {
+        scripts,
+        schema,
+        no_schema,
+        allow,
+    } = &cli.command
+    {
+        let schema = (!no_schema).then(|| load_schema(schema.as_deref(), formatter));
+        return run_lint_command(cli, config_file, scripts, schema, allow, formatter);
+    }
+
+    if let Commands::Playbook {
+        file,
+        concurrency,
+        continue_on_error,
+        fail_on_error,
+    } = &cli.command
+    {
+        let options = PlaybookOptions {
+            concurrency: *concurrency as usize,
+            continue_on_error: *continue_on_error,
+            fail_on_error: *fail_on_error,
+        };
+        return run_playbook_command(cli, config_file, file, options, formatter).await;
+    }
+
+    if let Commands::Messages {
+        locale,
+        check,
+        list,
+    } = &cli.command
+    {
+        return run_messages_command(locale.as_deref(), check.as_deref(), *list, formatter);
+    }
+
+    if let Commands::Plugin { name: None, .. } = &cli.command {
+        return run_plugin_list(formatter);
+    }
+
+    // Dry runs need no server
+    if let Commands::Advancement {
+        action,
+        player,
+        advancement,
+        dry_run: true,
+    } = &cli.command
+    {
+        let command = advancement_command(*action, player, advancement)
+            .unwrap_or_else(|e| exit_invalid_arguments(formatter, e));
+        formatter.print_output(&formatter.format_command(&command))?;
+        return Ok(());
+    }
 
-    // Parse the address, converting localhost to 127.0.0.1
-    let address = cli
-        .parse_address()
-        .map_err(|e| {
-            eprintln!("Invalid address: {}", e);
+    // Merge flags with the selected profile, converting localhost to 127.0.0.1
+    let profile = complete_selected_profile(cli, config_file)
+        .await
+        .unwrap_or_else(|e| {
+            eprintln!("{}", formatter.format_rcon_error(&e));
             std::process::exit(1);
-        })
-        .unwrap();
+        });
+    let target = cli
+        .resolve_target(&profile)
+        .unwrap_or_else(|e| exit_invalid_arguments(formatter, e));
 
-    // Create RCON configuration
-    let config =
-        RconConfig::new(address, cli.password.clone()).with_timeout(cli.timeout_duration());
+    // Queueing works while the server is down, so only draining connects
+    if let Commands::Queue { file, action } = &cli.command {
+        if !matches!(action, QueueCommand::Drain { .. }) {
+            let queue = open_queue(file.as_deref(), formatter);
+            return run_queue_command(
+                &queue,
+                &target_label(cli, &target),
+                &target,
+                action,
+                formatter,
+            );
+        }
+    }
+
+    // Resolve the address and create the RCON configuration
+    let config = rcon_config(cli, &target, formatter)
+        .await
+        .unwrap_or_else(|e| {
+            eprintln!("{}", formatter.format_rcon_error(&e));
+            std::process::exit(1);
+        });
 
     info!("Starting RCON CLI v{}", rcon_cli::VERSION);
 
     // Execute the appropriate command
     match &cli.command {
-        Commands::Exec { command, show_time } => {
-            execute_single_command(&config, command, *show_time, &formatter).await?;
+        Commands::Exec {
+            command,
+            stdin_heredoc,
+            show_time,
+            no_daemon,
+            priority,
+            validate,
+            no_prefix,
+            fail_on_error,
+            schema,
+        } => {
+            let config = if *fail_on_error {
+                with_error_classifier(&config, config_file, &target, formatter)
+            } else {
+                config.clone()
+            };
+            let command = match command {
+                Some(command) => command.clone(),
+                None => {
+                    let mut source = String::new();
+                    io::stdin().read_to_string(&mut source)?;
+                    join_lines(source.lines())
+                }
+            };
+            if *stdin_heredoc && command.is_empty() {
+                exit_invalid_arguments(formatter, t!("heredoc-empty"));
+            }
+            if *validate {
+                if let Err(problem) = load_schema(schema.as_deref(), formatter).validate(&command) {
+                    formatter.progress(&t!("command-check-failed", problem = problem.to_string()));
+                }
+            }
+            let command = match &target.prefix {
+                Some(prefix) if !*no_prefix => with_prefix(prefix, &command),
+                _ => command,
+            };
+            let events = server_event_bus(cli, config_file, &target, formatter)
+                .await
+                .start();
+            if *no_daemon
+                || !execute_via_daemon(
+                    cli,
+                    &target,
+                    &config,
+                    &DaemonExec {
+                        command: &command,
+                        priority: *priority,
+                        show_time: *show_time,
+                    },
+                    Some(&events),
+                    formatter,
+                )
+                .await?
+            {
+                execute_single_command(&config, &command, *show_time, Some(&events), formatter)
+                    .await?;
+            }
+            events.close().await;
         }
         Commands::Interactive {
             prompt,
             history,
             history_size,
+            no_prefix,
+            schema,
         } => {
-            run_interactive_mode(&config, prompt, *history, *history_size, &formatter).await?;
+            let schema = load_schema(schema.as_deref(), formatter);
+            let prefix = target.prefix.as_deref().filter(|_| !*no_prefix);
+            run_interactive_mode(
+                &config,
+                prompt,
+                *history,
+                *history_size,
+                prefix,
+                schema,
+                formatter,
+            )
+            .await?;
         }
-        Commands::Ping { count, interval } => {
-            run_ping_command(&config, *count, *interval, &formatter).await?;
+        Commands::Ping {
+            count,
+            interval,
+            record,
+            metrics_out,
+            ..
+        } => {
+            let (metrics, formatter) = metrics_output(cli, metrics_out.as_ref(), formatter);
+            let events = server_event_bus(cli, config_file, &target, &formatter).await;
+            let events = ping_subscribers(events, record, metrics, &formatter).start();
+            let result = run_ping_command(&config, *count, *interval, &events, &formatter).await;
+            events.close().await;
+            result?;
         }
         Commands::Info { detailed } => {
-            run_info_command(&config, *detailed, &formatter).await?;
+            run_info_command(&config, *detailed, formatter).await?;
+        }
+        Commands::World { raw } => {
+            let mut client = connect_with_retry(&config, formatter).await?;
+            print_world_report(&config, &mut client, *raw, formatter).await?;
+        }
+        Commands::Players { raw: true, .. } => {
+            print_raw_responses(&config, &["list uuids".to_string()], formatter).await?;
+        }
+        Commands::Players { show_uuids, .. } => {
+            run_players_command(&config, *show_uuids, formatter).await?;
+        }
+        Commands::Banlist { ips, raw: true } => {
+            let command = if *ips {
+                "banlist ips"
+            } else {
+                "banlist players"
+            };
+            print_raw_responses(&config, &[command.to_string()], formatter).await?;
+        }
+        Commands::Banlist { ips, .. } => {
+            run_banlist_command(&config, *ips, formatter).await?;
+        }
+        Commands::Bulk {
+            template,
+            input,
+            no_header,
+            concurrency,
+            rate,
+        } => {
+            let table = load_table(input, !*no_header)?;
+            let template = Template::parse(template, &table.columns)?;
+            run_bulk_command(&config, &table, &template, *concurrency, *rate, formatter).await?;
+        }
+        Commands::Inventory { player, raw: true } => {
+            let command = format!("data get entity {} Inventory", player);
+            print_raw_responses(&config, &[command], formatter).await?;
+        }
+        Commands::Inventory { player, .. } => {
+            run_inventory_command(&config, player, formatter).await?;
+        }
+        Commands::Give {
+            player,
+            item,
+            count,
+            nbt,
+            mc_version,
+            force,
+        } => {
+            let options = GiveOptions {
+                player,
+                item,
+                count: *count,
+                nbt: nbt.as_deref(),
+                mc_version: *mc_version,
+                force: *force,
+            };
+            run_give_command(&config, &options, formatter).await?;
+        }
+        Commands::Advancement {
+            action,
+            player,
+            advancement,
+            ..
+        } => {
+            let command = advancement_command(*action, player, advancement)
+                .unwrap_or_else(|e| exit_invalid_arguments(formatter, e));
+            run_advancement_command(&config, &command, formatter).await?;
+        }
+        Commands::Seed { raw: true } => {
+            print_raw_responses(&config, &["seed".to_string()], formatter).await?;
+        }
+        Commands::Seed { .. } => {
+            let mut client = connect_with_retry(&config, formatter).await?;
+            let seed = execute_parsed(&mut client, "seed", parse_seed).await?;
+            formatter.print_output(&formatter.format_value("seed", seed.into()))?;
+        }
+        Commands::Tps { raw } => {
+            run_tps_command(&config, *raw, formatter).await?;
+        }
+        Commands::Time { action, raw } => {
+            let command = match action {
+                TimeCommand::Day => "time set day".to_string(),
+                TimeCommand::Noon => "time set noon".to_string(),
+                TimeCommand::Night => "time set night".to_string(),
+                TimeCommand::Midnight => "time set midnight".to_string(),
+                TimeCommand::Set { time } => format!("time set {}", time),
+                TimeCommand::Query { what } => format!("time query {}", what.as_str()),
+            };
+            let key = match action {
+                TimeCommand::Query { what } => what.as_str(),
+                _ => "time",
+            };
+            if *raw {
+                print_raw_responses(&config, &[command], formatter).await?;
+            } else {
+                run_time_command(&config, &command, key, formatter).await?;
+            }
+        }
+        Commands::Weather {
+            kind,
+            duration,
+            mc_version,
+        } => {
+            run_weather_command(&config, *kind, *duration, *mc_version, formatter).await?;
+        }
+        Commands::Effect { action, raw } => {
+            let command = match action {
+                EffectCommand::Give {
+                    target,
+                    effect,
+                    duration,
+                    amplifier,
+                    hide_particles,
+                } => {
+                    // Optional arguments are positional, so earlier ones get defaults
+                    let mut command = format!("effect give {} {}", target, effect);
+                    if duration.is_some() || *amplifier > 0 || *hide_particles {
+                        let duration = duration.unwrap_or(EffectDuration::DEFAULT);
+                        command.push_str(&format!(" {}", duration));
+                    }
+                    if *amplifier > 0 || *hide_particles {
+                        command.push_str(&format!(" {}", amplifier));
+                    }
+                    if *hide_particles {
+                        command.push_str(" true");
+                    }
+                    command
+                }
+                EffectCommand::Clear {
+                    target,
+                    effect: Some(effect),
+                } => format!("effect clear {} {}", target, effect),
+                EffectCommand::Clear {
+                    target,
+                    effect: None,
+                } => format!("effect clear {}", target),
+            };
+            if *raw {
+                print_raw_responses(&config, &[command], formatter).await?;
+            } else {
+                run_effect_command(&config, &command, formatter).await?;
+            }
+        }
+        Commands::Xp { action, raw } => {
+            let unit = |levels: bool| {
+                if levels {
+                    XpUnit::Levels
+                } else {
+                    XpUnit::Points
+                }
+            };
+            let command = match action {
+                XpCommand::Add {
+                    target,
+                    amount,
+                    levels,
+                } => format!("xp add {} {} {}", target, amount, unit(*levels).as_str()),
+                XpCommand::Set {
+                    target,
+                    amount,
+                    levels,
+                } => format!("xp set {} {} {}", target, amount, unit(*levels).as_str()),
+                XpCommand::Query { target, levels } => {
+                    format!("xp query {} {}", target, unit(*levels).as_str())
+                }
+            };
+            match action {
+                _ if *raw => print_raw_responses(&config, &[command], formatter).await?,
+                XpCommand::Query { .. } => {
+                    run_xp_query_command(&config, &command, formatter).await?
+                }
+                _ => run_xp_change_command(&config, &command, formatter).await?,
+            }
+        }
+        Commands::Eco { action, raw } => {
+            let targets = eco_targets(action)?;
+            if *raw {
+                let commands: Vec<String> = targets
+                    .iter()
+                    .map(|target| action.command(&target.player, target.amount))
+                    .collect();
+                print_raw_responses(&config, &commands, formatter).await?;
+            } else {
+                run_eco_command(&config, action, &targets, formatter).await?;
+            }
+        }
+        Commands::Perms { holder, raw } => {
+            let command = format!("{} {}", holder.command(), holder.action().arguments());
+            if *raw {
+                print_raw_responses(&config, &[command], formatter).await?;
+            } else {
+                run_perms_command(&config, holder, &command, formatter).await?;
+            }
+        }
+        Commands::Forceload { action, raw: true } => {
+            print_raw_responses(&config, &forceload_commands(action), formatter).await?;
+        }
+        Commands::Forceload { action, .. } => {
+            let area = |from: BlockColumn, to: Option<BlockColumn>| {
+                ChunkArea::between(from.chunk(), to.unwrap_or(from).chunk())
+            };
+            match action {
+                ForceloadCommand::Add {
+                    from,
+                    to,
+                    dimension,
+                } => {
+                    let area = area(*from, *to);
+                    run_forceload_change_command(
+                        &config,
+                        "add",
+                        area,
+                        dimension.as_deref(),
+                        formatter,
+                    )
+                    .await?;
+                }
+                ForceloadCommand::Remove {
+                    from: Some(from),
+                    to,
+                    all: false,
+                    dimension,
+                } => {
+                    let area = area(*from, *to);
+                    run_forceload_change_command(
+                        &config,
+                        "remove",
+                        area,
+                        dimension.as_deref(),
+                        formatter,
+                    )
+                    .await?;
+                }
+                ForceloadCommand::Remove { dimension, .. } => {
+                    let command =
+                        in_dimension(dimension.as_deref(), "forceload remove all".to_string());
+                    execute_single_command(&config, &command, false, None, formatter).await?;
+                }
+                ForceloadCommand::Query {
+                    from,
+                    to,
+                    dimension,
+                } => {
+                    let area = from.map(|from| area(from, *to));
+                    run_forceload_query_command(&config, area, dimension.as_deref(), formatter)
+                        .await?;
+                }
+            }
+        }
+        Commands::As {
+            selector,
+            command,
+            show_time,
+        } => {
+            let command = execute_as(selector, &join_command(command));
+            info!("Sending '{}'", command);
+            execute_single_command(&config, &command, *show_time, None, formatter).await?;
+        }
+        Commands::Tp {
+            who,
+            destination,
+            dimension,
+            confirm_loaded,
+        } => {
+            let destination = Destination::parse(destination)
+                .unwrap_or_else(|e| exit_invalid_arguments(formatter, e));
+            run_tp_command(
+                &config,
+                who,
+                &destination,
+                dimension.as_deref(),
+                *confirm_loaded,
+                formatter,
+            )
+            .await?;
+        }
+        Commands::Whitelist {
+            action:
+                WhitelistCommand::Sync {
+                    file,
+                    url,
+                    no_remove,
+                },
+        } => {
+            let roster = match (file, url) {
+                (Some(file), _) => load_roster(file)?,
+                (None, Some(url)) => fetch_roster(url).await?,
+                (None, None) => unreachable!("clap requires a roster file or --url"),
+            };
+            run_whitelist_sync_command(&config, &roster, *no_remove, formatter).await?;
+        }
+        Commands::Data {
+            action: DataCommand::Merge { target, json },
+        } => {
+            let target =
+                DataTarget::parse(target).unwrap_or_else(|e| exit_invalid_arguments(formatter, e));
+            let source = if json.as_os_str() == "-" {
+                let mut source = String::new();
+                io::stdin().read_to_string(&mut source)?;
+                source
+            } else {
+                std::fs::read_to_string(json).unwrap_or_else(|e| {
+                    exit_invalid_arguments(
+                        formatter,
+                        t!(
+                            "file-read-failed",
+                            path = json.display().to_string(),
+                            error = e.to_string()
+                        ),
+                    )
+                })
+            };
+            let value: serde_json::Value = serde_json::from_str(&source)
+                .unwrap_or_else(|e| exit_invalid_arguments(formatter, e.to_string()));
+            let data = Tag::from_json(&value)?;
+            let commands = merge_commands(&target, &data, config.dialect.max_request_payload())?;
+            run_data_merge_command(&config, &commands, formatter).await?;
+        }
+        Commands::Addons {
+            action: AddonsCommand::List { enabled_only },
+            raw,
+        } => {
+            run_addons_list_command(&config, *enabled_only, *raw, formatter).await?;
+        }
+        Commands::Sessions {
+            watch,
+            interval,
+            watch_for,
+            metrics_out,
+        } => {
+            let (metrics, formatter) = metrics_output(cli, metrics_out.as_ref(), formatter);
+            let mut events = server_event_bus(cli, config_file, &target, &formatter).await;
+            if let Some(metrics) = metrics {
+                events = events.subscribe(metrics);
+            }
+            let events = events.start();
+            let options = SessionsOptions {
+                watch: *watch,
+                interval: *interval,
+                watch_for: *watch_for,
+            };
+            let config = config
+                .clone()
+                .with_hooks(config.hooks.clone().chain(events.client_hooks()));
+            let result = run_sessions_command(&config, &options, &events, &formatter).await;
+            events.close().await;
+            result?;
+        }
+        Commands::Alert {
+            when,
+            hold,
+            exec,
+            interval,
+            once,
+            metrics_out,
+        } => {
+            let (metrics, formatter) = metrics_output(cli, metrics_out.as_ref(), formatter);
+            let mut events = server_event_bus(cli, config_file, &target, &formatter).await;
+            if let Some(metrics) = metrics {
+                events = events.subscribe(metrics);
+            }
+            let events = events.start();
+            let options = AlertOptions {
+                rule: AlertRule::new(*when, hold.unwrap_or_default()),
+                exec: exec.as_deref(),
+                interval: *interval,
+                once: *once,
+                events: &events,
+            };
+            let config = config
+                .clone()
+                .with_hooks(config.hooks.clone().chain(events.client_hooks()));
+            let result = run_alert_command(&config, &options, &formatter).await;
+            events.close().await;
+            result?;
+        }
+        Commands::Save {
+            verify,
+            timeout,
+            interval,
+        } => {
+            let events = server_event_bus(cli, config_file, &target, formatter)
+                .await
+                .start();
+            let result = run_save_command(&config, *verify, *timeout, *interval, formatter).await;
+            if result.is_ok() {
+                events.emit(EventKind::Saved);
+            }
+            events.close().await;
+            result?;
+        }
+        Commands::Maintenance { log, action } => {
+            let log = open_audit_log(log.as_deref(), formatter);
+            let server = target_label(cli, &target);
+            let window = MaintenanceWindow {
+                log: &log,
+                server: &server,
+                dry_run: cli.dry_run,
+            };
+            let events = server_event_bus(cli, config_file, &target, formatter)
+                .await
+                .start();
+            let result = match action {
+                MaintenanceCommand::Start {
+                    message,
+                    eta,
+                    ops,
+                    keep,
+                } => {
+                    let mut kept = keep.clone();
+                    if let Some(ops) = ops {
+                        kept.extend(load_roster(ops)?);
+                    }
+                    let message = message
+                        .clone()
+                        .unwrap_or_else(|| t!("maintenance-default-start"));
+                    run_maintenance_start(
+                        &config, &window, &message, *eta, &kept, &events, formatter,
+                    )
+                    .await
+                }
+                MaintenanceCommand::End { message } => {
+                    let message = message
+                        .clone()
+                        .unwrap_or_else(|| t!("maintenance-default-end"));
+                    run_maintenance_end(&config, &window, &message, &events, formatter).await
+                }
+            };
+            events.close().await;
+            result?;
+        }
+        Commands::Autostop {
+            idle,
+            warn,
+            interval,
+            message,
+            stop_command,
+        } => {
+            let events = server_event_bus(cli, config_file, &target, formatter)
+                .await
+                .start();
+            let options = AutostopOptions {
+                idle: *idle,
+                warn: *warn,
+                interval: *interval,
+                message,
+                stop_command,
+                events: &events,
+            };
+            let config = config
+                .clone()
+                .with_hooks(config.hooks.clone().chain(events.client_hooks()));
+            let result = run_autostop_command(&config, &options, formatter).await;
+            events.close().await;
+            result?;
+        }
+        Commands::Restart {
+            when_empty,
+            max_players,
+            max_wait,
+            warn,
+            interval,
+            message,
+            stop_command,
+        } => {
+            let events = server_event_bus(cli, config_file, &target, formatter)
+                .await
+                .start();
+            let options = RestartOptions {
+                max_players: if *when_empty { Some(0) } else { *max_players },
+                max_wait: *max_wait,
+                warn,
+                interval: *interval,
+                message,
+                stop_command,
+                events: &events,
+            };
+            let config = config
+                .clone()
+                .with_hooks(config.hooks.clone().chain(events.client_hooks()));
+            let result = run_restart_command(&config, &options, formatter).await;
+            events.close().await;
+            if let Err(code) = result? {
+                std::process::exit(code);
+            }
+        }
+        Commands::Doctor {
+            payload_limit,
+            fragmentation_command,
+        } => {
+            let options = DoctorOptions {
+                payload_limit: *payload_limit,
+                fragmentation_command: fragmentation_command.clone(),
+            };
+            run_doctor_command(&config, &options, formatter).await?;
         }
-        Commands::Players { show_uuids } => {
-            run_players_command(&config, *show_uuids, &formatter).await?;
+        Commands::Wait {
+            timeout,
+            interval,
+            crash_limit,
+            crash_window,
+        } => {
+            let events = server_event_bus(cli, config_file, &target, formatter)
+                .await
+                .start();
+            let options = WaitOptions::new(
+                Duration::from_secs(*timeout),
+                Duration::from_secs(*interval),
+            )
+            .with_crash_limit(*crash_limit, Duration::from_secs(*crash_window));
+            let result = wait_until_ready(&config, &options, formatter).await;
+            if result.is_ok() {
+                events.emit(EventKind::Ready);
+            }
+            events.close().await;
+            result?;
+        }
+        Commands::OnReady {
+            script,
+            timeout,
+            interval,
+            crash_limit,
+            crash_window,
+            continue_on_error,
+            fail_on_error,
+        } => {
+            let config = if *fail_on_error {
+                with_error_classifier(&config, config_file, &target, formatter)
+            } else {
+                config.clone()
+            };
+            let events = server_event_bus(cli, config_file, &target, formatter)
+                .await
+                .start();
+            let options = WaitOptions::new(
+                Duration::from_secs(*timeout),
+                Duration::from_secs(*interval),
+            )
+            .with_crash_limit(*crash_limit, Duration::from_secs(*crash_window));
+            let result = run_on_ready_command(
+                &config,
+                script,
+                &options,
+                *continue_on_error,
+                &events,
+                formatter,
+            )
+            .await;
+            events.close().await;
+            result?;
+        }
+        Commands::Queue {
+            file,
+            action: QueueCommand::Drain { wait },
+        } => {
+            let queue = open_queue(file.as_deref(), formatter);
+            let server = target_label(cli, &target);
+            run_queue_drain(&config, &queue, &server, *wait, cli.dry_run, formatter).await?;
+        }
+        Commands::Deliver {
+            key,
+            templates,
+            values,
+            ledger,
+            resend_unconfirmed,
+        } => {
+            let commands = render_templates(templates, values)?;
+            let ledger = open_ledger(ledger.as_deref(), formatter);
+            let delivery = DeliveryRequest {
+                key,
+                server: &target_label(cli, &target),
+                commands: &commands,
+                resend_unconfirmed: *resend_unconfirmed,
+            };
+            run_deliver_command(&config, &ledger, delivery, cli.dry_run, formatter).await?;
+        }
+        Commands::Eval { script, args } => {
+            run_eval_command(&config, script, args, formatter).await?;
+        }
+        Commands::Plugin {
+            name: Some(name),
+            args,
+        } => {
+            run_plugin_command(&config, name, args, formatter).await?;
+        }
+        Commands::Queue { .. } => unreachable!("handled before connecting"),
+        Commands::Daemon { .. }
+        | Commands::Net { .. }
+        | Commands::Profiles
+        | Commands::Report { .. }
+        | Commands::Annotate { .. }
+        | Commands::Lint { .. }
+        | Commands::Playbook { .. }
+        | Commands::Messages { .. }
+        | Commands::Plugin { name: None, .. } => {
+            unreachable!("handled before target resolution")
         }
     }
 
@@ -82,345 +1087,4700 @@ async fn execute_single_command(
     config: &RconConfig,
     command: &str,
     show_time: bool,
+    events: Option<&EventBus>,
     formatter: &OutputFormatter,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let mut client = connect_with_retry(config, formatter).await?;
 
-    let start_time = Instant::now();
+    formatter.print_expansion(command);
+    match client.execute_command_detailed(command).await {
+        Ok(response) => {
+            formatter.print_parsed_response(command, &response).await?;
+            if let Some(events) = events {
+                events.emit(EventKind::CommandExecuted {
+                    command: redact::command(command),
+                    response: response.body.clone(),
+                });
+            }
+
+            if show_time {
+                let elapsed = response.duration;
+                formatter.progress(&t!(
+                    "executed-in",
+                    ms = format!("{:.2}", elapsed.as_millis())
+                ));
+            }
+        }
+        Err(e) => {
+            let error_msg = formatter.format_rcon_error(&e);
+            eprintln!("{}", error_msg);
+            if let Some(events) = events {
+                events.emit(EventKind::command_failed(redact::command(command), &e));
+                events.close().await;
+            }
+            std::process::exit(1);
+        }
+    }
+
+    Ok(())
+}
+
+/// A command of `exec` to route through a daemon
+struct DaemonExec<'a> {
+    command: &'a str,
+    /// Queue priority in the daemon
+    priority: Option<Priority>,
+    show_time: bool,
+}
+
+/// Run a command through a running daemon
+///
+/// Returns `Ok(false)` without printing anything when no daemon is
+/// reachable, so the caller can fall back to a direct connection.
+#[cfg(unix)]
+async fn execute_via_daemon(
+    cli: &Cli,
+    target: &Target,
+    config: &RconConfig,
+    exec: &DaemonExec<'_>,
+    events: Option<&EventBus>,
+    formatter: &OutputFormatter,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let DaemonExec {
+        command,
+        priority,
+        show_time,
+    } = *exec;
+    // The daemon authenticates with a single password, so rotation setups
+    // keep connecting directly, as do dry runs and read-only targets
+    if cli.dry_run || target.read_only || !target.fallback_passwords.is_empty() {
+        return Ok(false);
+    }
+
+    let socket = cli.socket.clone().unwrap_or_else(default_socket_path);
+    let Ok(mut daemon) = DaemonClient::connect(&socket).await else {
+        return Ok(false);
+    };
+    info!("Routing command through daemon at {}", socket.display());
+
+    let request = DaemonRequest {
+        address: config.address,
+        alternate_addresses: config.alternate_addresses.clone(),
+        password: target.password.clone(),
+        dialect: target.dialect,
+        bind: target.bind,
+        command: command.to_string(),
+        priority,
+        timeout_ms: None,
+    };
 
-    match client.execute_command(command).await {
+    formatter.print_expansion(command);
+    let result = daemon
+        .execute(&request)
+        .await
+        .and_then(|response| config.check_response(response));
+    match result {
         Ok(response) => {
-            let formatted_response = formatter.format_response(&response);
-            println!("{}", formatted_response);
+            formatter.print_parsed_response(command, &response).await?;
+            if let Some(events) = events {
+                events.emit(EventKind::CommandExecuted {
+                    command: redact::command(command),
+                    response: response.body.clone(),
+                });
+            }
+
+            if show_time {
+                let elapsed = response.duration;
+                formatter.progress(&t!(
+                    "executed-in",
+                    ms = format!("{:.2}", elapsed.as_millis())
+                ));
+            }
+        }
+        Err(e) => {
+            eprintln!("{}", formatter.format_rcon_error(&e));
+            if let Some(events) = events {
+                events.emit(EventKind::command_failed(redact::command(command), &e));
+                events.close().await;
+            }
+            std::process::exit(1);
+        }
+    }
+
+    Ok(true)
+}
+
+#[cfg(not(unix))]
+async fn execute_via_daemon(
+    _cli: &Cli,
+    _target: &Target,
+    _config: &RconConfig,
+    _exec: &DaemonExec<'_>,
+    _events: Option<&EventBus>,
+    _formatter: &OutputFormatter,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    Ok(false)
+}
+
+/// How much of a server's connection the daemon lets clients take
+struct DaemonLimits {
+    rate_limit: Option<RateLimit>,
+    max_queue: Option<usize>,
+}
+
+#[cfg(unix)]
+async fn run_daemon(
+    cli: &Cli,
+    config_file: &ConfigFile,
+    limits: DaemonLimits,
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let timeout = Duration::from_secs(cli.timeout.unwrap_or(DEFAULT_TIMEOUT_SECS));
+    let daemon = Arc::new(
+        Daemon::new(timeout)
+            .with_rate_limit(limits.rate_limit)
+            .with_max_queue(limits.max_queue),
+    );
+    let servers = daemon_servers(cli, config_file, formatter).await?;
+    let count = servers.len();
+    daemon.set_servers(servers).await;
+
+    // Under systemd socket activation the socket unit owns the path, so
+    // only a socket created here is removed again
+    let (listener, socket, created) = match systemd::activated_unix_listener()? {
+        Some(listener) => {
+            let path = listener.local_addr()?.as_pathname().map(Path::to_path_buf);
+            (UnixListener::from_std(listener)?, path, None)
+        }
+        None => {
+            let path = cli.socket.clone().unwrap_or_else(default_socket_path);
+            (Daemon::bind(&path).await?, Some(path.clone()), Some(path))
+        }
+    };
+    formatter.progress(&t!(
+        "daemon-listening",
+        path = socket.map_or_else(|| "-".to_string(), |path| path.display().to_string()),
+        count = count
+    ));
+    // Catch signals before announcing readiness
+    let mut shutdown = Shutdown::listen()?;
+    let mut hangup = signal(SignalKind::hangup())?;
+    systemd::notify(&format!("READY=1\nSTATUS=Serving {} server(s)", count))?;
+
+    // Requests keep being served while the config is reloaded
+    let mut serving = tokio::spawn(
+        Arc::clone(&daemon).serve_listener(listener, async move { shutdown.requested().await }),
+    );
+    let result = loop {
+        tokio::select! {
+            result = &mut serving => break result?,
+            _ = hangup.recv() => reload_daemon(cli, &daemon, formatter).await?,
+        }
+    };
+    systemd::notify("STOPPING=1")?;
+    if let Some(path) = created {
+        let _ = std::fs::remove_file(path);
+    }
+    Ok(result?)
+}
+
+/// Configurations of the explicitly selected server and every usable profile
+///
+/// Profiles that cannot be used are skipped with a message; only the
+/// selected server is required.
+#[cfg(unix)]
+async fn daemon_servers(
+    cli: &Cli,
+    config_file: &ConfigFile,
+    formatter: &OutputFormatter,
+) -> Result<Vec<RconConfig>, Box<dyn std::error::Error>> {
+    let mut targets = Vec::new();
+    if cli.password.is_some() || cli.profile.is_some() {
+        let profile = complete_selected_profile(cli, config_file).await?;
+        targets.push(cli.resolve_target(&profile)?);
+    }
+    for (name, profile) in &config_file.profiles {
+        match resolve_profile_target(cli, name, profile).await {
+            Ok(target) => targets.push(target),
+            Err(e) => eprintln!(
+                "{}",
+                formatter.format_error(&t!(
+                    "daemon-skip-profile",
+                    name = name.as_str(),
+                    error = e.as_str()
+                ))
+            ),
+        }
+    }
+
+    let configs = join_all(
+        targets
+            .iter()
+            .map(|target| rcon_config(cli, target, formatter)),
+    )
+    .await;
+    Ok(configs
+        .into_iter()
+        .filter_map(|config| {
+            config
+                .inspect_err(|e| eprintln!("{}", formatter.format_rcon_error(e)))
+                .ok()
+        })
+        .collect())
+}
+
+/// Re-read the config file on SIGHUP and apply added and removed profiles
+///
+/// If the config cannot be loaded or the selected server resolved, the
+/// error is reported and the running servers are kept.
+#[cfg(unix)]
+async fn reload_daemon(cli: &Cli, daemon: &Daemon, formatter: &OutputFormatter) -> io::Result<()> {
+    systemd::notify("RELOADING=1")?;
+    let servers = match ConfigFile::load_or_default(cli.config.as_deref()) {
+        Ok(config_file) => daemon_servers(cli, &config_file, formatter).await,
+        Err(e) => Err(e.into()),
+    };
+    match servers {
+        Ok(servers) => {
+            let count = servers.len();
+            let (added, removed) = daemon.set_servers(servers).await;
+            formatter.progress(&t!(
+                "daemon-reloaded",
+                added = added,
+                removed = removed,
+                count = count
+            ));
+            systemd::notify(&format!("READY=1\nSTATUS=Serving {} server(s)", count))?;
+        }
+        Err(e) => {
+            let report = match e.downcast_ref::<RconError>() {
+                Some(e) => ErrorReport::from(e),
+                None => ErrorReport::new("invalid_config", e.to_string()),
+            };
+            let report = report.context(t!("daemon-reload-failed"));
+            eprintln!("{}", formatter.format_failure(&report));
+            systemd::notify("READY=1")?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+async fn run_daemon(
+    _cli: &Cli,
+    _config_file: &ConfigFile,
+    _limits: DaemonLimits,
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    eprintln!("{}", formatter.format_error(&t!("daemon-unsupported")));
+    std::process::exit(1);
+}
+
+/// Print the queues of a running daemon's connections
+#[cfg(unix)]
+async fn run_daemon_status(
+    cli: &Cli,
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let socket = cli.socket.clone().unwrap_or_else(default_socket_path);
+    let mut daemon = match DaemonClient::connect(&socket).await {
+        Ok(daemon) => daemon,
+        Err(e) => {
+            eprintln!(
+                "{}",
+                formatter.format_error(&t!(
+                    "daemon-not-running",
+                    path = socket.display().to_string(),
+                    error = e.to_string()
+                ))
+            );
+            std::process::exit(1);
+        }
+    };
+    let status = daemon.status().await?;
+
+    let millis = |us: u64| format!("{:.1}ms", us as f64 / 1000.0);
+    let rows: Vec<Vec<String>> = status
+        .servers
+        .iter()
+        .map(|server| {
+            let queue = &server.queue;
+            vec![
+                server.address.to_string(),
+                server
+                    .bind
+                    .map_or_else(|| "-".to_string(), |bind| bind.to_string()),
+                queue.queued.to_string(),
+                queue.sent.to_string(),
+                millis(queue.average_wait().as_micros() as u64),
+                millis(queue.max_wait_us),
+                queue.rate_limited.to_string(),
+                queue.queue_full.to_string(),
+                queue.timed_out.to_string(),
+            ]
+        })
+        .collect();
+
+    formatter.print_output(&formatter.format_records(
+        &[
+            "Server",
+            "Bind",
+            "Queued",
+            "Sent",
+            "Avg wait",
+            "Max wait",
+            "Rate limited",
+            "Queue full",
+            "Timed out",
+        ],
+        &rows,
+    ))?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+async fn run_daemon_status(
+    _cli: &Cli,
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    eprintln!("{}", formatter.format_error(&t!("daemon-unsupported")));
+    std::process::exit(1);
+}
+
+/// The command tree given with `--schema`, or the built-in one
+fn load_schema(path: Option<&Path>, formatter: &OutputFormatter) -> CommandSchema {
+    let Some(path) = path else {
+        return CommandSchema::builtin();
+    };
+    let json = std::fs::read_to_string(path).unwrap_or_else(|e| {
+        exit_invalid_arguments(
+            formatter,
+            t!(
+                "file-read-failed",
+                path = path.display().to_string(),
+                error = e.to_string()
+            ),
+        )
+    });
+    CommandSchema::from_json(&json).unwrap_or_else(|e| exit_invalid_arguments(formatter, e))
+}
+
+/// Commands handled by interactive mode itself
+const REPL_COMMANDS: [&str; 5] = ["exit", "help", "quit", "reconnect", "status"];
+
+/// Completes interactive input from the command tree and plugins
+struct ReplHelper {
+    schema: CommandSchema,
+    #[cfg(feature = "wasm")]
+    plugins: Option<Arc<PluginHost>>,
+}
+
+impl Completer for ReplHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &rustyline::Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let line = &line[..pos];
+        // Complete commands sent without the prefix like any other
+        let bang = usize::from(line.starts_with('!'));
+        let line = &line[bang..];
+        #[allow(unused_mut)]
+        let (mut start, mut candidates) = self.schema.complete(line);
+        #[cfg(feature = "wasm")]
+        if let Some(plugins) = &self.plugins {
+            let word_start = line.rfind(char::is_whitespace).map_or(0, |i| i + 1);
+            let word = &line[word_start..];
+            let offered: Vec<String> = plugins
+                .completions(line)
+                .into_iter()
+                .filter(|candidate| candidate.starts_with(word))
+                .collect();
+            if candidates.is_empty() {
+                start = word_start;
+            }
+            if start == word_start {
+                candidates.extend(offered);
+                candidates.sort();
+                candidates.dedup();
+            }
+        }
+        if !line.trim_start().contains(char::is_whitespace) {
+            let word = line.trim_start();
+            candidates.extend(
+                REPL_COMMANDS
+                    .iter()
+                    .filter(|command| command.starts_with(word))
+                    .map(|command| command.to_string()),
+            );
+            candidates.sort();
+            candidates.dedup();
+        }
+        // A unique completion is a whole word, ready for the next one
+        if let [candidate] = candidates.as_mut_slice() {
+            candidate.push(' ');
+        }
+        Ok((start + bang, candidates))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl rustyline::highlight::Highlighter for ReplHelper {}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
+
+/// Add the console's editing keys that rustyline leaves unbound on Windows
+///
+/// Ctrl+Z on an empty line ends input as in `cmd.exe`, and Ctrl+Backspace
+/// and Ctrl+Delete delete the previous and next word.
+#[cfg(windows)]
+fn bind_windows_keys(editor: &mut Editor<ReplHelper, DefaultHistory>) {
+    use rustyline::{At, Cmd, KeyCode, KeyEvent, Modifiers, Movement, Word};
+
+    editor.bind_sequence(KeyEvent::ctrl('Z'), Cmd::EndOfFile);
+    editor.bind_sequence(
+        KeyEvent(KeyCode::Backspace, Modifiers::CTRL),
+        Cmd::Kill(Movement::BackwardWord(1, Word::Emacs)),
+    );
+    editor.bind_sequence(
+        KeyEvent(KeyCode::Delete, Modifiers::CTRL),
+        Cmd::Kill(Movement::ForwardWord(1, At::Start, Word::Emacs)),
+    );
+}
+
+/// Prompt for the lines after one ending in `\`
+const CONTINUATION_PROMPT: &str = "... ";
+
+async fn run_interactive_mode(
+    config: &RconConfig,
+    prompt: &str,
+    history: bool,
+    history_size: usize,
+    prefix: Option<&str>,
+    schema: CommandSchema,
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = connect_with_retry(config, formatter).await?;
+
+    let mut editor = Editor::<ReplHelper, DefaultHistory>::with_config(
+        rustyline::Config::builder()
+            .max_history_size(history_size)?
+            .history_ignore_dups(true)?
+            .completion_type(CompletionType::List)
+            .build(),
+    )?;
+    editor.set_helper(Some(ReplHelper {
+        schema,
+        #[cfg(feature = "wasm")]
+        plugins: formatter.parsers().wasm().cloned(),
+    }));
+    #[cfg(windows)]
+    bind_windows_keys(&mut editor);
+    let history_path = history
+        .then(|| dirs::home_dir().map(|home| home.join(".rcon_history")))
+        .flatten();
+    if let Some(path) = &history_path {
+        // A missing history file just means there is no history yet
+        let _ = editor.load_history(path);
+    }
+
+    formatter.info(&t!("interactive-welcome"));
+    if let Some(prefix) = prefix {
+        formatter.info(&t!("interactive-prefix", prefix = prefix));
+    }
+
+    // Lines ending in `\` continue on the next one
+    let mut continued: Vec<String> = Vec::new();
+    loop {
+        let line_prompt = if continued.is_empty() {
+            prompt
+        } else {
+            CONTINUATION_PROMPT
+        };
+        match editor.readline(line_prompt) {
+            // Ctrl-C drops an unfinished command instead of quitting
+            Err(ReadlineError::Interrupted) if !continued.is_empty() => continued.clear(),
+            Err(ReadlineError::Eof | ReadlineError::Interrupted) => break,
+            Ok(line) => {
+                if let Some(head) = line.trim_end().strip_suffix('\\') {
+                    continued.push(head.to_string());
+                    continue;
+                }
+                continued.push(line);
+                let input = join_lines(continued.iter().map(String::as_str));
+                continued.clear();
+                let input = input.as_str();
+
+                if input.is_empty() {
+                    continue;
+                }
+                // Passwords typed into login commands stay out of the file
+                if !redact::is_sensitive(input) {
+                    editor.add_history_entry(input)?;
+                }
+
+                if input == "quit" || input == "exit" {
+                    break;
+                }
+
+                // Handle special commands
+                match input {
+                    "help" => {
+                        show_interactive_help(formatter);
+                        continue;
+                    }
+                    "status" => {
+                        show_connection_status(&mut client, formatter).await;
+                        continue;
+                    }
+                    "reconnect" => {
+                        match client.reconnect().await {
+                            Ok(_) => {
+                                println!("{}", formatter.format_info(&t!("reconnected")));
+                            }
+                            Err(e) => {
+                                eprintln!("{}", formatter.format_rcon_error(&e));
+                            }
+                        }
+                        continue;
+                    }
+                    _ => {}
+                }
+
+                // A leading `!` sends the command without the prefix
+                let command = match (input.strip_prefix('!'), prefix) {
+                    (Some(command), _) => command.trim().to_string(),
+                    (None, Some(prefix)) => with_prefix(prefix, input),
+                    (None, None) => input.to_string(),
+                };
+                formatter.print_expansion(&command);
+
+                // Execute the command
+                match client.execute_command_detailed(&command).await {
+                    Ok(response) => {
+                        if !response.body.is_empty() {
+                            formatter.print_parsed_response(&command, &response).await?;
+                        }
+                    }
+                    Err(e @ (RconError::Network(_) | RconError::Disconnected)) => {
+                        let report = ErrorReport::from(&e)
+                            .context(t!("connection-lost"))
+                            .retrying();
+                        if !formatter.is_quiet() {
+                            eprintln!("{}", formatter.format_failure(&report));
+                        }
+
+                        match client.reconnect().await {
+                            Ok(_) => {
+                                formatter.progress(&t!("reconnected-retrying"));
+
+                                match client.execute_command_detailed(&command).await {
+                                    Ok(response) => {
+                                        if !response.body.is_empty() {
+                                            formatter
+                                                .print_parsed_response(&command, &response)
+                                                .await?;
+                                        }
+                                    }
+                                    Err(e) => {
+                                        eprintln!("{}", formatter.format_rcon_error(&e));
+                                    }
+                                }
+                            }
+                            Err(e) => {
+                                let report = ErrorReport::from(&e).context(t!("reconnect-failed"));
+                                eprintln!("{}", formatter.format_failure(&report));
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        eprintln!("{}", formatter.format_rcon_error(&e));
+                    }
+                }
+            }
+            Err(e) => {
+                eprintln!(
+                    "{}",
+                    formatter.format_error(&t!("input-error", error = e.to_string()))
+                );
+                break;
+            }
+        }
+    }
+
+    if let Some(path) = &history_path {
+        if let Err(e) = editor.save_history(path) {
+            formatter.progress(&t!(
+                "history-save-failed",
+                path = path.display().to_string(),
+                error = e.to_string()
+            ));
+        }
+    }
+
+    formatter.info(&t!("interactive-goodbye"));
+    Ok(())
+}
+
+async fn run_ping_command(
+    config: &RconConfig,
+    count: u32,
+    interval: u64,
+    events: &EventBus,
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let label = events.server();
+    let mut client = match connect_with_retry(config, formatter).await {
+        Ok(client) => client,
+        Err(e) => {
+            if let Some(report) = e.downcast_ref::<ErrorReport>() {
+                events.publish(Sample::failure(label, report.code).into());
+            }
+            return Err(e);
+        }
+    };
+    let interval_duration = Duration::from_secs(interval);
+
+    formatter.info(&t!(
+        "ping-start",
+        address = config.address.to_string(),
+        count = count
+    ));
+
+    let mut successful_pings = 0;
+    let mut total_time = Duration::ZERO;
+    let mut lost = false;
+
+    for i in 1..=count {
+        let start_time = Instant::now();
+
+        // Recorded runs track availability, so they come back after an outage
+        let result = match lost && events.has_subscribers() {
+            true => match client.reconnect().await {
+                Ok(()) => client.ping().await,
+                Err(e) => Err(e),
+            },
+            false => client.ping().await,
+        };
+        lost = result.is_err();
+
+        match result {
+            Ok(_) => {
+                let elapsed = start_time.elapsed();
+                total_time += elapsed;
+                successful_pings += 1;
+                let ping_info = t!(
+                    "ping-success",
+                    attempt = i,
+                    ms = format!("{:.2}", elapsed.as_millis())
+                );
+                formatter.info(&ping_info);
+                events.publish(Sample::success(label, elapsed).into());
+            }
+            Err(e) => {
+                let report = ErrorReport::from(&e).context(t!("ping-failed", attempt = i));
+                eprintln!("{}", formatter.format_failure(&report));
+                events.publish(Sample::failure(label, e.code()).into());
+            }
+        }
+
+        if i < count {
+            sleep(interval_duration).await;
+        }
+    }
+
+    // Print summary
+    let success_rate = (successful_pings as f64 / count as f64) * 100.0;
+    let avg_time = if successful_pings > 0 {
+        total_time.as_millis() as f64 / successful_pings as f64
+    } else {
+        0.0
+    };
+
+    let summary = t!(
+        "ping-summary",
+        successful = successful_pings,
+        count = count,
+        rate = format!("{:.1}", success_rate),
+        ms = format!("{:.2}", avg_time)
+    );
+    formatter.print_output(&formatter.format_info(&summary))?;
+
+    Ok(())
+}
+
+/// Resolve a target's address and build its client configuration
+///
+/// DNS lookups share the connection timeout so a stalled resolver can't
+/// hang the invocation. With `--dry-run` the configuration prints each
+/// command instead of connecting.
+async fn rcon_config(
+    cli: &Cli,
+    target: &Target,
+    formatter: &OutputFormatter,
+) -> Result<RconConfig, RconError> {
+    // Dry runs don't connect, so there is nothing to wait for
+    let progress = if cli.dry_run {
+        None
+    } else {
+        formatter.connect_progress(&target.address)
+    };
+    let report = |phase| {
+        if let Some(progress) = &progress {
+            progress.report(phase);
+        }
+    };
+
+    report(ConnectPhase::Resolving);
+    let resolve = RconConfig::resolve(&target.address, target.password.clone());
+    let resolved = tokio::time::timeout(target.timeout, resolve)
+        .await
+        .map_err(|_| RconError::AddressResolution(format!("{}: lookup timed out", target.address)))
+        .and_then(|resolved| resolved);
+    if resolved.is_err() {
+        report(ConnectPhase::Failed);
+    }
+    let config = resolved?
+        .with_timeout(target.timeout)
+        .with_dialect(target.dialect.dialect())
+        .with_fallback_passwords(target.fallback_passwords.iter().cloned())
+        .with_reconnect_policy(cli.reconnect_policy());
+
+    let config = match target.bind {
+        Some(local_addr) => config.with_local_addr(local_addr),
+        None => config,
+    };
+    let config = if target.read_only {
+        config.with_policy(CommandPolicy::read_only())
+    } else {
+        config
+    };
+    let config = match progress {
+        Some(progress) => config.with_progress(progress),
+        None => config,
+    };
+    if !cli.dry_run {
+        return match (&target.pin, &target.profile) {
+            (Some(policy), Some(server)) => pin_connections(cli, server, policy, config, formatter),
+            _ => Ok(config),
+        };
+    }
+
+    // Login and terminator packets are left out
+    let formatter = formatter.clone();
+    Ok(config.with_dry_run(DryRun::new(move |packet, _bytes| {
+        if packet.packet_type == packet_type::EXECCOMMAND {
+            DRY_RUN_COMMANDS.fetch_add(1, Ordering::Relaxed);
+            let _ = formatter.print_output(&formatter.format_command(&packet.payload));
+        }
+    })))
+}
+
+/// Check a pinned profile's addresses before the password is sent, and
+/// its identity after every login
+fn pin_connections(
+    cli: &Cli,
+    server: &str,
+    policy: &PinPolicy,
+    config: RconConfig,
+    formatter: &OutputFormatter,
+) -> Result<RconConfig, RconError> {
+    let path =
+        pins::default_path().ok_or_else(|| RconError::InvalidConfig(t!("pin-no-default")))?;
+    let store = PinStore::new(path);
+    let accept = cli.accept_pin_change;
+
+    let seen: Vec<SocketAddr> = config.addresses().collect();
+    let list = |addresses: &[SocketAddr]| {
+        addresses
+            .iter()
+            .map(SocketAddr::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    match store.check_addresses(server, &seen)? {
+        PinCheck::New => formatter.progress(&t!(
+            "pin-new-addresses",
+            server = server,
+            addresses = list(&seen)
+        )),
+        PinCheck::Unchanged => {}
+        PinCheck::Changed => {
+            let pinned = store.get(server)?.unwrap_or_default().addresses;
+            let message = t!(
+                "pin-addresses-changed",
+                server = server,
+                seen = list(&seen),
+                pinned = list(&pinned)
+            );
+            if pin_changed(accept, policy.mode, message, formatter)? {
+                store.update(server, |pin| pin.addresses = seen.clone())?;
+            }
+        }
+    }
+
+    let identity = IdentityPin {
+        store,
+        server: server.to_string(),
+        policy: policy.clone(),
+        accept,
+        formatter: formatter.clone(),
+    };
+    let on_reconnect = identity.clone();
+    let hooks = ClientHooks::new()
+        .on_connect(move |client| Box::pin(identity.clone().check(client)))
+        .on_reconnect(move |client| Box::pin(on_reconnect.clone().check(client)));
+    let hooks = config.hooks.clone().chain(hooks);
+    Ok(config.with_hooks(hooks))
+}
+
+/// Post-login check of a pinned profile's identity
+#[derive(Clone)]
+struct IdentityPin {
+    store: PinStore,
+    server: String,
+    policy: PinPolicy,
+    accept: bool,
+    formatter: OutputFormatter,
+}
+
+impl IdentityPin {
+    async fn check(self, client: &mut RconClient) -> Result<(), RconError> {
+        let seen = fingerprint(&client.execute_command(&self.policy.command).await?);
+        match self.store.check_identity(&self.server, &seen)? {
+            PinCheck::New => self.formatter.progress(&t!(
+                "pin-new-identity",
+                server = self.server.as_str(),
+                fingerprint = seen.as_str()
+            )),
+            PinCheck::Unchanged => {}
+            PinCheck::Changed => {
+                let pinned = self.store.get(&self.server)?.and_then(|pin| pin.identity);
+                let message = t!(
+                    "pin-identity-changed",
+                    server = self.server.as_str(),
+                    command = self.policy.command.as_str(),
+                    seen = seen.as_str(),
+                    pinned = pinned.unwrap_or_default()
+                );
+                if pin_changed(self.accept, self.policy.mode, message, &self.formatter)? {
+                    self.store
+                        .update(&self.server, |pin| pin.identity = Some(seen))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Report a pin that no longer matches, returning whether to pin what was
+/// seen instead
+///
+/// Fails in strict mode unless `--accept-pin-change` was given.
+fn pin_changed(
+    accept: bool,
+    mode: PinMode,
+    message: String,
+    formatter: &OutputFormatter,
+) -> Result<bool, RconError> {
+    if accept {
+        formatter.warn(&t!("pin-accepted", message = message));
+        return Ok(true);
+    }
+    match mode {
+        PinMode::Strict => Err(RconError::PinMismatch(message)),
+        PinMode::Warn => {
+            formatter.warn(&message);
+            Ok(false)
+        }
+    }
+}
+
+/// Latency samples collected while pinging one server
+struct PingStats {
+    sent: u32,
+    samples: Vec<Duration>,
+    error: Option<String>,
+}
+
+impl PingStats {
+    fn average(&self) -> Option<Duration> {
+        let total: Duration = self.samples.iter().sum();
+        (!self.samples.is_empty()).then(|| total / self.samples.len() as u32)
+    }
+}
+
+/// Connect once and collect `count` ping samples without printing anything
+async fn collect_ping_stats(
+    config: RconConfig,
+    count: u32,
+    interval: u64,
+    name: &str,
+    events: &EventBus,
+) -> PingStats {
+    let mut stats = PingStats {
+        sent: 0,
+        samples: Vec::new(),
+        error: None,
+    };
+
+    let mut client = match RconClient::connect(config).await {
+        Ok(client) => client,
+        Err(e) => {
+            events.publish(Sample::failure(name, e.code()).into());
+            stats.sent = count;
+            stats.error = Some(e.to_string());
+            return stats;
+        }
+    };
+
+    for i in 1..=count {
+        stats.sent += 1;
+        let start_time = Instant::now();
+        match client.ping().await {
+            Ok(_) => {
+                let elapsed = start_time.elapsed();
+                events.publish(Sample::success(name, elapsed).into());
+                stats.samples.push(elapsed);
+            }
+            Err(e) => {
+                events.publish(Sample::failure(name, e.code()).into());
+                stats.error = Some(e.to_string());
+            }
+        }
+
+        if i < count {
+            sleep(Duration::from_secs(interval)).await;
+        }
+    }
+
+    stats
+}
+
+async fn run_ping_all_profiles(
+    cli: &Cli,
+    config_file: &ConfigFile,
+    count: u32,
+    interval: u64,
+    events: &EventBus,
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if config_file.profiles.is_empty() {
+        eprintln!("{}", formatter.format_error(&t!("no-profiles")));
+        std::process::exit(1);
+    }
+
+    formatter.progress(&t!(
+        "ping-profiles-start",
+        profiles = config_file.profiles.len(),
+        count = count
+    ));
+
+    let pings = config_file
+        .profiles
+        .iter()
+        .map(|(name, profile)| async move {
+            match resolve_profile_target(cli, name, profile).await {
+                Ok(target) => {
+                    let stats = match rcon_config(cli, &target, formatter).await {
+                        Ok(config) => {
+                            collect_ping_stats(config, count, interval, name, events).await
+                        }
+                        Err(e) => PingStats {
+                            sent: count,
+                            samples: Vec::new(),
+                            error: Some(e.to_string()),
+                        },
+                    };
+                    (name, target.address, stats)
+                }
+                Err(e) => {
+                    let stats = PingStats {
+                        sent: 0,
+                        samples: Vec::new(),
+                        error: Some(e),
+                    };
+                    (name, "-".to_string(), stats)
+                }
+            }
+        });
+    let mut results = join_all(pings).await;
+
+    // Fastest first, unreachable servers last
+    results.sort_by_key(|(_, _, stats)| stats.average().unwrap_or(Duration::MAX));
+
+    let format_ms = |duration: Option<Duration>| {
+        duration
+            .map(|d| format!("{:.2}ms", d.as_secs_f64() * 1000.0))
+            .unwrap_or_else(|| "-".to_string())
+    };
+
+    let rows: Vec<Vec<String>> = results
+        .iter()
+        .map(|(name, address, stats)| {
+            let loss = if stats.sent > 0 {
+                100.0 * (stats.sent as usize - stats.samples.len()) as f64 / stats.sent as f64
+            } else {
+                100.0
+            };
+            vec![
+                name.to_string(),
+                address.clone(),
+                format!("{}/{}", stats.samples.len(), stats.sent),
+                format!("{:.1}%", loss),
+                format_ms(stats.samples.iter().min().copied()),
+                format_ms(stats.average()),
+                format_ms(stats.samples.iter().max().copied()),
+                stats.error.clone().unwrap_or_default(),
+            ]
+        })
+        .collect();
+
+    formatter.print_output(&formatter.format_records(
+        &[
+            "Profile", "Address", "Ok", "Loss", "Min", "Avg", "Max", "Error",
+        ],
+        &rows,
+    ))?;
+
+    Ok(())
+}
+
+/// Open the history file given to `--record` or `--history`, or the default
+/// one, exiting if it cannot be opened
+fn open_history(path: Option<&Path>, formatter: &OutputFormatter) -> History {
+    let Some(path) = path.map(Path::to_path_buf).or_else(history::default_path) else {
+        let report = ErrorReport::new("invalid_arguments", t!("history-no-default"));
+        eprintln!("{}", formatter.format_failure(&report));
+        std::process::exit(2);
+    };
+    History::open(&path).unwrap_or_else(|e| {
+        let report = ErrorReport::new("invalid_config", e.to_string())
+            .context(t!("history-open-failed", path = path.display().to_string()));
+        eprintln!("{}", formatter.format_failure(&report));
+        std::process::exit(1);
+    })
+}
+
+/// Open the queue file given to `queue --file`, or the default one,
+/// exiting if it cannot be opened
+fn open_queue(path: Option<&Path>, formatter: &OutputFormatter) -> CommandQueue {
+    let Some(path) = path.map(Path::to_path_buf).or_else(queue::default_path) else {
+        let report = ErrorReport::new("invalid_arguments", t!("queue-no-default"));
+        eprintln!("{}", formatter.format_failure(&report));
+        std::process::exit(2);
+    };
+    CommandQueue::open(&path).unwrap_or_else(|e| {
+        let report = ErrorReport::from(&e)
+            .context(t!("queue-open-failed", path = path.display().to_string()));
+        eprintln!("{}", formatter.format_failure(&report));
+        std::process::exit(1);
+    })
+}
+
+/// `queue push` and `queue list`, which don't connect
+fn run_queue_command(
+    queue: &CommandQueue,
+    server: &str,
+    target: &Target,
+    action: &QueueCommand,
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match action {
+        QueueCommand::Push { command } => {
+            let command = command.trim().trim_start_matches('/');
+            if command.is_empty() {
+                exit_invalid_arguments(formatter, t!("queue-empty-command"));
+            }
+            // Refuse now rather than on every drain
+            if target.read_only {
+                CommandPolicy::read_only().check(command)?;
+            }
+            queue.push(server, command)?;
+            let pending = queue
+                .entries()?
+                .iter()
+                .filter(|entry| entry.server == server)
+                .count();
+            formatter.info(&t!("queue-pushed", server = server, count = pending));
+        }
+        QueueCommand::List { all } => {
+            let rows: Vec<Vec<String>> = queue
+                .entries()?
+                .into_iter()
+                .filter(|entry| *all || entry.server == server)
+                .map(|entry| {
+                    vec![
+                        entry.queued_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+                        entry.server,
+                        entry.command,
+                    ]
+                })
+                .collect();
+            formatter
+                .print_output(&formatter.format_records(&["Queued", "Server", "Command"], &rows))?;
+        }
+        QueueCommand::Drain { .. } => unreachable!("drains connect"),
+    }
+    Ok(())
+}
+
+/// `queue drain`: send the server's queued commands, waiting for it first
+/// with `--wait`
+///
+/// Dry runs print the commands but leave them queued.
+async fn run_queue_drain(
+    config: &RconConfig,
+    queue: &CommandQueue,
+    server: &str,
+    wait: Option<u64>,
+    dry_run: bool,
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let pending = queue
+        .entries()?
+        .into_iter()
+        .filter(|entry| entry.server == server)
+        .count();
+    if pending == 0 {
+        formatter.info(&t!("queue-empty", server = server));
+        return Ok(());
+    }
+
+    let mut client = match wait {
+        Some(seconds) => {
+            let options = WaitOptions::new(Duration::from_secs(seconds), Duration::from_secs(5));
+            wait_until_ready(config, &options, formatter).await?
+        }
+        None => connect_with_retry(config, formatter).await?,
+    };
+
+    if dry_run {
+        for entry in queue
+            .entries()?
+            .iter()
+            .filter(|entry| entry.server == server)
+        {
+            client.execute_command(&entry.command).await?;
+        }
+        return Ok(());
+    }
+
+    let mut sent = 0;
+    let result = queue
+        .drain(server, &mut client, |_, response| {
+            sent += 1;
+            if !response.is_empty() {
+                // A closed stdout shouldn't stop the drain halfway
+                let _ = formatter.print_response(response);
+            }
+        })
+        .await;
+    if let Err(e) = result {
+        let report = ErrorReport::from(&e).context(t!(
+            "queue-drain-stopped",
+            server = server,
+            sent = sent,
+            remaining = pending - sent
+        ));
+        eprintln!("{}", formatter.format_failure(&report));
+        std::process::exit(1);
+    }
+    formatter.info(&t!("queue-drained", server = server, count = sent));
+    Ok(())
+}
+
+fn open_audit_log(path: Option<&Path>, formatter: &OutputFormatter) -> AuditLog {
+    let Some(path) = path
+        .map(Path::to_path_buf)
+        .or_else(maintenance::default_path)
+    else {
+        let report = ErrorReport::new("invalid_arguments", t!("maintenance-no-default"));
+        eprintln!("{}", formatter.format_failure(&report));
+        std::process::exit(2);
+    };
+    AuditLog::open(&path).unwrap_or_else(|e| {
+        let report = ErrorReport::from(&e).context(t!(
+            "maintenance-open-failed",
+            path = path.display().to_string()
+        ));
+        eprintln!("{}", formatter.format_failure(&report));
+        std::process::exit(1);
+    })
+}
+
+/// Where `maintenance` records its window
+struct MaintenanceWindow<'a> {
+    log: &'a AuditLog,
+    server: &'a str,
+    /// Dry runs record nothing
+    dry_run: bool,
+}
+
+async fn run_maintenance_start(
+    config: &RconConfig,
+    window: &MaintenanceWindow<'_>,
+    template: &str,
+    eta: Option<Duration>,
+    kept: &[String],
+    events: &EventBus,
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let server = window.server;
+    if let Some(open) = window.log.open_window(server)? {
+        let since = open.started_at.to_rfc3339();
+        let message = t!("maintenance-already-open", server = server, since = since);
+        return Err(ErrorReport::new("invalid_arguments", message).into());
+    }
+    let eta = eta.map(format_duration).unwrap_or_default();
+    let message = render_message(template, &[("server", server), ("eta", &eta)])?;
+
+    let mut client = connect_with_retry(config, formatter).await?;
+    let response = client.execute_command("whitelist on").await?;
+    let whitelist_was_on = parse_whitelist_toggle(&response).is_some_and(|toggle| !toggle.changed);
+    let status = if whitelist_was_on {
+        t!("maintenance-whitelist-already-on")
+    } else {
+        t!("maintenance-whitelist-on")
+    };
+    let mut rows = vec![vec!["whitelist".to_string(), status]];
+
+    // Once the whitelist is on the window is recorded, even if kicking fails,
+    // so `end` can open the server again
+    let mut kicked = Vec::new();
+    let result = async {
+        client.execute_command(&format!("say {}", message)).await?;
+        rows.push(vec!["broadcast".to_string(), message.clone()]);
+        let response = client.execute_command("list").await?;
+        let players = match parse_player_list(&response) {
+            Some(list) => list.players,
+            // Dry runs get no player list
+            None if window.dry_run => Vec::new(),
+            None => {
+                return Err(RconError::CommandFailed(t!(
+                    "player-list-unrecognized",
+                    response = response.as_str()
+                )))
+            }
+        };
+        for player in players {
+            let name = player.name;
+            if kept.iter().any(|kept| kept.eq_ignore_ascii_case(&name)) {
+                rows.push(vec!["keep".to_string(), name]);
+                continue;
+            }
+            client
+                .execute_command(&format!("kick {} {}", name, message))
+                .await?;
+            rows.push(vec!["kick".to_string(), name.clone()]);
+            kicked.push(name);
+        }
+        Ok(())
+    }
+    .await;
+
+    if !window.dry_run {
+        let started = WindowEvent::Started {
+            message: message.clone(),
+            whitelist_was_on,
+            kicked,
+        };
+        window.log.record(server, started)?;
+    }
+    events.emit(EventKind::Maintenance {
+        started: true,
+        message,
+    });
+    formatter.print_output(&formatter.format_records(&["Step", "Detail"], &rows))?;
+    Ok(result?)
+}
+
+async fn run_maintenance_end(
+    config: &RconConfig,
+    window: &MaintenanceWindow<'_>,
+    template: &str,
+    events: &EventBus,
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let server = window.server;
+    let Some(open) = window.log.open_window(server)? else {
+        let message = t!("maintenance-not-open", server = server);
+        return Err(ErrorReport::new("invalid_arguments", message).into());
+    };
+    let elapsed = (Utc::now() - open.started_at).to_std().unwrap_or_default();
+    let duration = format_duration(elapsed);
+    let message = render_message(template, &[("server", server), ("duration", &duration)])?;
+
+    let mut client = connect_with_retry(config, formatter).await?;
+    let status = if open.whitelist_was_on {
+        t!("maintenance-whitelist-left-on")
+    } else {
+        client.execute_command("whitelist off").await?;
+        t!("maintenance-whitelist-off")
+    };
+    client.execute_command(&format!("say {}", message)).await?;
+
+    if !window.dry_run {
+        let ended = WindowEvent::Ended {
+            message: message.clone(),
+        };
+        window.log.record(server, ended)?;
+    }
+    let rows = vec![
+        vec!["whitelist".to_string(), status],
+        vec!["broadcast".to_string(), message.clone()],
+    ];
+    events.emit(EventKind::Maintenance {
+        started: false,
+        message,
+    });
+    formatter.print_output(&formatter.format_records(&["Step", "Detail"], &rows))?;
+    Ok(())
+}
+
+/// The reward commands of `deliver`, with the `--set` values filled in
+fn render_templates(
+    templates: &[String],
+    values: &[(String, String)],
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let (names, row): (Vec<String>, Vec<String>) = values.iter().cloned().unzip();
+    templates
+        .iter()
+        .map(|template| {
+            let missing = || {
+                ErrorReport::new(
+                    "invalid_arguments",
+                    t!("deliver-missing-value", template = template.as_str()),
+                )
+            };
+            let parsed = Template::parse(template, &names).map_err(|_| missing())?;
+            match parsed.max_column() {
+                Some(index) if index >= row.len() => Err(missing().into()),
+                _ => Ok(parsed.render(&row)),
+            }
+        })
+        .collect()
+}
+
+fn open_ledger(path: Option<&Path>, formatter: &OutputFormatter) -> DeliveryLedger {
+    let Some(path) = path.map(Path::to_path_buf).or_else(delivery::default_path) else {
+        let report = ErrorReport::new("invalid_arguments", t!("deliver-no-default"));
+        eprintln!("{}", formatter.format_failure(&report));
+        std::process::exit(2);
+    };
+    DeliveryLedger::open(&path).unwrap_or_else(|e| {
+        let report = ErrorReport::from(&e)
+            .context(t!("deliver-open-failed", path = path.display().to_string()));
+        eprintln!("{}", formatter.format_failure(&report));
+        std::process::exit(1);
+    })
+}
+
+/// What `deliver` was asked to send
+struct DeliveryRequest<'a> {
+    key: &'a str,
+    server: &'a str,
+    commands: &'a [String],
+    resend_unconfirmed: bool,
+}
+
+async fn run_deliver_command(
+    config: &RconConfig,
+    ledger: &DeliveryLedger,
+    request: DeliveryRequest<'_>,
+    dry_run: bool,
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let key = request.key;
+    // A delivered key needs no connection, so retries succeed while the
+    // server is down
+    let recorded = ledger.delivery(key)?;
+    let delivered = recorded.as_ref().is_some_and(|delivery| {
+        delivery.is_delivered()
+            && delivery.server == request.server
+            && delivery.commands == request.commands
+    });
+
+    let report = if delivered {
+        DeliveryReport {
+            delivery: recorded.expect("delivered keys are recorded"),
+            sent: Vec::new(),
+        }
+    } else {
+        let mut client = connect_with_retry(config, formatter).await?;
+        if dry_run {
+            for command in request.commands {
+                client.execute_command(command).await?;
+            }
+            return Ok(());
+        }
+        let result = ledger
+            .deliver(
+                key,
+                request.server,
+                request.commands,
+                &mut client,
+                request.resend_unconfirmed,
+            )
+            .await;
+        match result {
+            Ok(report) => report,
+            Err(e) => {
+                let context = match e {
+                    RconError::DeliveryUnconfirmed(_) => t!("deliver-unconfirmed", key = key),
+                    RconError::KeyConflict(_) => t!("deliver-conflict"),
+                    _ => t!("deliver-stopped", key = key),
+                };
+                let report = ErrorReport::from(&e).context(context);
+                eprintln!("{}", formatter.format_failure(&report));
+                std::process::exit(1);
+            }
+        }
+    };
+
+    if report.sent.is_empty() {
+        formatter.info(&t!("deliver-already", key = key));
+    }
+    let delivery = &report.delivery;
+    let rows: Vec<Vec<String>> = delivery
+        .commands
+        .iter()
+        .enumerate()
+        .map(|(index, command)| {
+            let status = if report.sent.contains(&index) {
+                "sent"
+            } else {
+                "skipped"
+            };
+            let response = delivery.responses.get(&index).map_or("", String::as_str);
+            vec![
+                command.clone(),
+                status.to_string(),
+                strip_formatting(response).trim().to_string(),
+            ]
+        })
+        .collect();
+    formatter.print_output(&formatter.format_records(&["Command", "Status", "Response"], &rows))?;
+    Ok(())
+}
+
+/// Set up `--metrics-out`
+///
+/// Line protocol on stdout moves the usual output to stderr, unless it
+/// already goes to an `--output` file.
+fn metrics_output(
+    cli: &Cli,
+    target: Option<&MetricsTarget>,
+    formatter: &OutputFormatter,
+) -> (Option<MetricsWriter>, OutputFormatter) {
+    let formatter = match target {
+        Some(MetricsTarget::Stdout) if cli.output.is_none() => {
+            formatter.clone().with_output(Box::new(io::stderr()))
+        }
+        _ => formatter.clone(),
+    };
+    (target.cloned().map(MetricsWriter::new), formatter)
+}
+
+/// Treat responses reporting errors as failures, by the target's dialect
+/// and its `[errors]` section
+fn with_error_classifier(
+    config: &RconConfig,
+    config_file: &ConfigFile,
+    target: &Target,
+    formatter: &OutputFormatter,
+) -> RconConfig {
+    let errors = config_file
+        .errors
+        .get(&target.dialect)
+        .cloned()
+        .unwrap_or_default();
+    match ErrorClassifier::from_config(target.dialect.dialect().as_ref(), &errors) {
+        Ok(classifier) => config.clone().with_error_classifier(classifier),
+        Err(e) => {
+            eprintln!("{}", formatter.format_rcon_error(&e));
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Name of the server in recorded samples and metrics: the profile, or the
+/// address without one
+fn target_label(cli: &Cli, target: &Target) -> String {
+    cli.profile
+        .clone()
+        .unwrap_or_else(|| target.address.clone())
+}
+
+/// Event bus of a command, reporting subscriber failures on stderr instead
+/// of failing the command
+fn event_bus(server: String, formatter: &OutputFormatter) -> EventBusBuilder {
+    let formatter = formatter.clone();
+    EventBus::builder(server)
+        .on_error(move |e| eprintln!("{}", formatter.format_failure(&ErrorReport::from(e))))
+}
+
+/// Event bus of a command on `target`, with the `[mqtt]` broker and
+/// `[grafana]` annotations subscribed if configured
+///
+/// An unreachable broker is reported without failing the command.
+async fn server_event_bus(
+    cli: &Cli,
+    config_file: &ConfigFile,
+    target: &Target,
+    formatter: &OutputFormatter,
+) -> EventBusBuilder {
+    labelled_event_bus(cli, config_file, target_label(cli, target), formatter).await
+}
+
+/// Event bus of a command on the server labelled `server`, see
+/// [`server_event_bus`]
+async fn labelled_event_bus(
+    cli: &Cli,
+    config_file: &ConfigFile,
+    server: String,
+    formatter: &OutputFormatter,
+) -> EventBusBuilder {
+    let mut events = event_bus(server.clone(), formatter);
+    // Dry runs observe nothing worth publishing
+    if cli.dry_run {
+        return events;
+    }
+    if let Some(config) = &config_file.mqtt {
+        match MqttPublisher::connect(config, &server).await {
+            Ok(mqtt) => events = events.subscribe(mqtt),
+            Err(e) => eprintln!("{}", formatter.format_failure(&ErrorReport::from(&e))),
+        }
+    }
+    if let Some(config) = &config_file.grafana {
+        events = events.subscribe(GrafanaClient::new(config.clone()));
+    }
+    events
+}
+
+/// Subscribe the `--record` history and `--metrics-out` of a ping
+fn ping_subscribers(
+    mut events: EventBusBuilder,
+    record: &Option<Option<PathBuf>>,
+    metrics: Option<MetricsWriter>,
+    formatter: &OutputFormatter,
+) -> EventBusBuilder {
+    if let Some(path) = record {
+        events = events.subscribe(open_history(path.as_deref(), formatter));
+    }
+    if let Some(metrics) = metrics {
+        events = events.subscribe(metrics);
+    }
+    events
+}
+
+/// Post an annotation given on the command line
+async fn run_annotate_command(
+    cli: &Cli,
+    config_file: &ConfigFile,
+    text: &str,
+    tags: &[String],
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(config) = &config_file.grafana else {
+        exit_invalid_arguments(formatter, t!("grafana-not-configured"));
+    };
+    let mut annotation = Annotation::new(text);
+    for tag in tags.iter().chain(&cli.profile) {
+        annotation = annotation.tag(tag.as_str());
+    }
+    if cli.dry_run {
+        formatter.progress(&t!("annotation-dry-run", text = text));
+        return Ok(());
+    }
+
+    let id = GrafanaClient::new(config.clone())
+        .annotate(&annotation)
+        .await?;
+    formatter.info(&t!("annotation-posted", id = id));
+    Ok(())
+}
+
+/// Summarize recorded ping samples
+fn run_report_command(
+    since: Option<Duration>,
+    path: Option<&Path>,
+    target: Option<&str>,
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(path) = path.map(Path::to_path_buf).or_else(history::default_path) else {
+        let report = ErrorReport::new("invalid_arguments", t!("history-no-default"));
+        eprintln!("{}", formatter.format_failure(&report));
+        std::process::exit(2);
+    };
+    let mut samples = match history::load(&path) {
+        Ok(samples) => samples,
+        Err(e) => {
+            eprintln!("{}", formatter.format_failure(&ErrorReport::from(&e)));
+            std::process::exit(1);
+        }
+    };
+    if let Some(target) = target {
+        samples.retain(|sample| sample.target == target);
+    }
+
+    let since = since
+        .and_then(|since| chrono::Duration::from_std(since).ok())
+        .map(|since| Utc::now() - since);
+    let reports = history::summarize(&samples, since);
+    if reports.is_empty() {
+        formatter.progress(&t!("report-no-samples", path = path.display().to_string()));
+        return Ok(());
+    }
+
+    let rows: Vec<Vec<String>> = reports
+        .iter()
+        .map(|report| {
+            let percentile = |index: usize| {
+                report
+                    .percentiles
+                    .map(|p| format!("{:.2}ms", p[index].as_secs_f64() * 1000.0))
+                    .unwrap_or_else(|| "-".to_string())
+            };
+            let (outage, outage_start) = match report.longest_outage {
+                Some(outage) => (
+                    match outage.ongoing {
+                        true => format!(
+                            "{} ({})",
+                            format_duration(outage.duration),
+                            t!("report-ongoing")
+                        ),
+                        false => format_duration(outage.duration),
+                    },
+                    outage.start.to_rfc3339_opts(SecondsFormat::Secs, true),
+                ),
+                None => ("-".to_string(), "-".to_string()),
+            };
+            vec![
+                report.target.clone(),
+                report.samples.to_string(),
+                format!("{:.2}%", report.availability()),
+                percentile(0),
+                percentile(1),
+                percentile(2),
+                outage,
+                outage_start,
+            ]
+        })
+        .collect();
+
+    formatter.print_output(&formatter.format_records(
+        &[
+            "Target",
+            "Samples",
+            "Availability",
+            "p50",
+            "p95",
+            "p99",
+            "Longest outage",
+            "Outage start",
+        ],
+        &rows,
+    ))?;
+
+    Ok(())
+}
+
+async fn run_info_command(
+    config: &RconConfig,
+    detailed: bool,
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = connect_with_retry(config, formatter).await?;
+
+    for command in ["list", "version"] {
+        match client.execute_command(command).await {
+            Ok(response) => {
+                let section_header =
+                    formatter.format_info(&format!("=== {} ===", command.to_uppercase()));
+                formatter.print_output(&section_header)?;
+                formatter.print_response(&response)?;
+                formatter.print_output("")?;
+            }
+            Err(e) => {
+                let report = ErrorReport::from(&e).context(t!("info-failed", command = command));
+                eprintln!("{}", formatter.format_failure(&report));
+            }
+        }
+    }
+
+    if detailed {
+        print_world_report(config, &mut client, false, formatter).await?;
+    }
+    Ok(())
+}
+
+/// Inline predicates telling the weather, understood since 1.20.5
+const RAINING: &str = r#"execute if predicate {condition:"minecraft:weather_check",raining:true}"#;
+const THUNDERING: &str =
+    r#"execute if predicate {condition:"minecraft:weather_check",thundering:true}"#;
+
+/// Query the world's settings and state at once and print them as a record,
+/// or print the responses as they are with `raw`
+///
+/// Commands the configured policy forbids, like `difficulty` in read-only
+/// mode, are skipped and their values reported as unknown.
+async fn print_world_report(
+    config: &RconConfig,
+    client: &mut RconClient,
+    raw: bool,
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let commands = [
+        "seed",
+        "difficulty",
+        "worldborder get",
+        "time query day",
+        "time query daytime",
+        RAINING,
+        THUNDERING,
+    ];
+    let permitted = |command: &&str| {
+        config
+            .policy
+            .as_ref()
+            .is_none_or(|policy| policy.permits(command))
+    };
+    let sent: Vec<&str> = commands.iter().copied().filter(permitted).collect();
+    info!("Sending {} commands", sent.len());
+    let responses = client.execute_pipelined(&sent).await?;
+    if raw {
+        for response in &responses {
+            formatter.print_response(response)?;
+        }
+        return Ok(());
+    }
+    let mut responses = responses.into_iter();
+    let responses = commands.map(|command| {
+        permitted(&command)
+            .then(|| responses.next())
+            .flatten()
+            .unwrap_or_default()
+    });
+    let [seed, difficulty, border, day, daytime, raining, thundering] = responses;
+
+    let weather = match (parse_test_result(&thundering), parse_test_result(&raining)) {
+        (Some(true), _) => Some(WeatherKind::Thunder),
+        (_, Some(true)) => Some(WeatherKind::Rain),
+        (Some(false), Some(false)) => Some(WeatherKind::Clear),
+        _ => None,
+    };
+    let cell = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+    let row = vec![
+        cell(parse_seed(&seed).map(|seed| seed.to_string())),
+        cell(parse_difficulty(&difficulty)),
+        cell(parse_world_border(&border).map(|width| width.to_string())),
+        cell(parse_time(&day).map(|day| day.to_string())),
+        cell(parse_time(&daytime).map(|time| time.to_string())),
+        cell(weather.map(|weather| weather.as_str().to_string())),
+    ];
+    formatter.print_output(&formatter.format_records(
+        &["Seed", "Difficulty", "Border", "Day", "Time", "Weather"],
+        &[row],
+    ))?;
+    Ok(())
+}
+
+async fn run_players_command(
+    config: &RconConfig,
+    show_uuids: bool,
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = connect_with_retry(config, formatter).await?;
+
+    let response = match client.execute_command("list uuids").await {
+        Ok(response) => response,
+        Err(_) => {
+            // Fallback to basic list command
+            match client.execute_command("list").await {
+                Ok(response) => response,
+                Err(e) => {
+                    let error_msg = formatter.format_rcon_error(&e);
+                    eprintln!("{}", error_msg);
+                    std::process::exit(1);
+                }
+            }
+        }
+    };
+
+    // Servers with unfamiliar output get the raw response
+    let Some(list) = parse_player_list(&response) else {
+        formatter.print_response(&response)?;
+        return Ok(());
+    };
+
+    formatter.info(&t!("players-online", online = list.online, max = list.max));
+    let rows: Vec<Vec<String>> = list
+        .players
+        .into_iter()
+        .map(|player| {
+            let mut row = vec![player.name];
+            if show_uuids {
+                row.push(player.uuid.unwrap_or_else(|| "-".to_string()));
+            }
+            row
+        })
+        .collect();
+    let headers: &[&str] = if show_uuids {
+        &["Name", "UUID"]
+    } else {
+        &["Name"]
+    };
+    formatter.print_output(&formatter.format_records(headers, &rows))?;
+
+    Ok(())
+}
+
+async fn run_banlist_command(
+    config: &RconConfig,
+    ips: bool,
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = connect_with_retry(config, formatter).await?;
+    let command = if ips {
+        "banlist ips"
+    } else {
+        "banlist players"
+    };
+
+    let response = match client.execute_command(command).await {
+        Ok(response) => response,
+        Err(e) => {
+            eprintln!("{}", formatter.format_rcon_error(&e));
+            std::process::exit(1);
+        }
+    };
+
+    let Some(bans) = parse_banlist(&response) else {
+        formatter.print_response(&response)?;
+        return Ok(());
+    };
+
+    formatter.info(&t!("ban-count", count = bans.len()));
+    let rows: Vec<Vec<String>> = bans
+        .into_iter()
+        .map(|ban| vec![ban.target, ban.source, ban.reason])
+        .collect();
+    let target = if ips { "Address" } else { "Name" };
+    formatter.print_output(&formatter.format_records(&[target, "Source", "Reason"], &rows))?;
+
+    Ok(())
+}
+
+/// CSV input from a file, or stdin for `-`
+fn load_table(input: &Path, header: bool) -> Result<Table, Box<dyn std::error::Error>> {
+    if input.as_os_str() == "-" {
+        let mut source = String::new();
+        io::stdin().read_to_string(&mut source)?;
+        Ok(Table::parse(&source, header)?)
+    } else {
+        Ok(Table::load(input, header)?)
+    }
+}
+
+/// A player an `eco` subcommand applies to
+struct EcoTarget {
+    /// 1-based line in the CSV input, counting the header
+    line: Option<usize>,
+    player: String,
+    /// Zero for `balance`
+    amount: f64,
+}
+
+/// The players and amounts from the arguments or the CSV input
+///
+/// Every row is checked before anything is sent, so a typo can't leave a
+/// batch of payments half done.
+fn eco_targets(action: &EcoCommand) -> Result<Vec<EcoTarget>, Box<dyn std::error::Error>> {
+    let Some((input, header)) = action.input() else {
+        let (player, amount) = match action {
+            EcoCommand::Give { player, amount, .. } | EcoCommand::Take { player, amount, .. } => {
+                (player, *amount)
+            }
+            EcoCommand::Balance { player, .. } => (player, None),
+        };
+        return Ok(vec![EcoTarget {
+            line: None,
+            player: player.clone().expect("required without --input"),
+            amount: amount.unwrap_or_default(),
+        }]);
+    };
+
+    let table = load_table(input, header)?;
+    let player_column = table.column("player", 0)?;
+    let amount_column = if action.takes_amount() {
+        Some(table.column("amount", 1)?)
+    } else {
+        None
+    };
+    let header_lines = usize::from(!table.columns.is_empty());
+    table
+        .rows
+        .iter()
+        .enumerate()
+        .map(|(index, row)| {
+            let line = index + 1 + header_lines;
+            let field = |column: usize| row.get(column).map_or("", |field| field.trim());
+            let invalid = |e: String| RconError::InvalidConfig(format!("Line {}: {}", line, e));
+            let player = parse_player(field(player_column)).map_err(invalid)?;
+            let amount = match amount_column {
+                Some(column) => parse_amount(field(column)).map_err(invalid)?,
+                None => 0.0,
+            };
+            Ok(EcoTarget {
+                line: Some(line),
+                player,
+                amount,
+            })
+        })
+        .collect()
+}
+
+async fn run_eco_command(
+    config: &RconConfig,
+    action: &EcoCommand,
+    targets: &[EcoTarget],
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = connect_with_retry(config, formatter).await?;
+    let headers: &[&str] = if action.takes_amount() {
+        &["Player", "Amount", "Balance"]
+    } else {
+        &["Player", "Balance"]
+    };
+    let row = |response: &str| {
+        if action.takes_amount() {
+            parse_balance_change(response).map(|change| {
+                vec![
+                    change.player,
+                    change.amount.to_string(),
+                    change.balance.to_string(),
+                ]
+            })
+        } else {
+            parse_balance(response).map(|balance| vec![balance.player, balance.balance.to_string()])
+        }
+    };
+
+    let mut rows = Vec::new();
+    let mut failures = Vec::new();
+    for (done, target) in targets.iter().enumerate() {
+        let command = action.command(&target.player, target.amount);
+        info!("Sending '{}'", command);
+        let result = client.execute_command(&command).await;
+        let failure = match result.as_deref().map(|response| (row(response), response)) {
+            Ok((Some(row), _)) => {
+                rows.push(row);
+                None
+            }
+            Ok((None, response)) => Some(strip_formatting(response).trim().to_string()),
+            Err(e) => Some(e.to_string()),
+        };
+        if let Some(error) = failure {
+            // A single player fails like any other wrapper command
+            let Some(line) = target.line else {
+                return Err(ErrorReport::new(
+                    "command_failed",
+                    t!("unexpected-response", command = command, response = error),
+                )
+                .into());
+            };
+            failures.push(vec![line.to_string(), target.player.clone(), error]);
+            if result.is_err() {
+                let _ = client.reconnect().await;
+            }
+        }
+        formatter.progress_bar(done + 1, targets.len(), failures.len());
+    }
+
+    formatter.print_output(&formatter.format_records(headers, &rows))?;
+    if failures.is_empty() {
+        return Ok(());
+    }
+    formatter.print_output(&formatter.format_records(&["Line", "Player", "Error"], &failures))?;
+    std::process::exit(1);
+}
+
+/// A row that could not be sent or failed on the server
+struct BulkFailure {
+    /// 1-based line of the row in the input, counting the header
+    line: usize,
+    command: String,
+    error: String,
+}
+
+async fn run_bulk_command(
+    config: &RconConfig,
+    table: &Table,
+    template: &Template,
+    concurrency: u32,
+    rate: Option<f64>,
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let header_lines = usize::from(!table.columns.is_empty());
+    let required = template.max_column().map_or(0, |index| index + 1);
+    let total = table.rows.len();
+
+    let failures = Mutex::new(Vec::new());
+    let mut queue = VecDeque::new();
+    for (index, row) in table.rows.iter().enumerate() {
+        let line = index + 1 + header_lines;
+        let command = template.render(row);
+        if row.len() < required {
+            failures.lock().unwrap().push(BulkFailure {
+                line,
+                command,
+                error: t!("bulk-short-row", fields = row.len(), required = required),
+            });
+        } else {
+            queue.push_back((line, command));
+        }
+    }
+
+    // The first connection must succeed; extra ones are a best effort
+    let mut clients = vec![connect_with_retry(config, formatter).await?];
+    for _ in 1..concurrency.min(queue.len().max(1) as u32) {
+        match RconClient::connect(config.clone()).await {
+            Ok(client) => clients.push(client),
+            Err(e) => {
+                let report = ErrorReport::from(&e).context(t!("bulk-extra-connection-failed"));
+                eprintln!("{}", formatter.format_failure(&report));
+                break;
+            }
+        }
+    }
+
+    formatter.progress(&t!(
+        "bulk-start",
+        count = queue.len(),
+        connections = clients.len()
+    ));
+
+    let queue = Mutex::new(queue);
+    let limiter = rate.map(|rate| {
+        let mut ticks = tokio::time::interval(Duration::from_secs_f64(1.0 / rate));
+        ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        tokio::sync::Mutex::new(ticks)
+    });
+    let done = AtomicUsize::new(total - queue.lock().unwrap().len());
+    let failed = AtomicUsize::new(failures.lock().unwrap().len());
+    formatter.progress_bar(
+        done.load(Ordering::Relaxed),
+        total,
+        failed.load(Ordering::Relaxed),
+    );
+
+    let workers = clients.into_iter().map(|mut client| {
+        let (queue, limiter, failures) = (&queue, &limiter, &failures);
+        let (done, failed) = (&done, &failed);
+        async move {
+            loop {
+                let Some((line, command)) = queue.lock().unwrap().pop_front() else {
+                    break;
+                };
+                if let Some(limiter) = limiter {
+                    limiter.lock().await.tick().await;
+                }
+                formatter.print_expansion(&command);
+
+                if let Err(e) = client.execute_command(&command).await {
+                    failures.lock().unwrap().push(BulkFailure {
+                        line,
+                        command,
+                        error: e.to_string(),
+                    });
+                    failed.fetch_add(1, Ordering::Relaxed);
+                    let _ = client.reconnect().await;
+                }
+                let done = done.fetch_add(1, Ordering::Relaxed) + 1;
+                formatter.progress_bar(done, total, failed.load(Ordering::Relaxed));
+            }
+        }
+    });
+    join_all(workers).await;
+
+    let mut failures = failures.into_inner().unwrap();
+    formatter.info(&t!(
+        "bulk-complete",
+        succeeded = total - failures.len(),
+        total = total
+    ));
+    if failures.is_empty() {
+        return Ok(());
+    }
+
+    failures.sort_by_key(|failure| failure.line);
+    let rows: Vec<Vec<String>> = failures
+        .into_iter()
+        .map(|failure| vec![failure.line.to_string(), failure.command, failure.error])
+        .collect();
+    formatter.print_output(&formatter.format_records(&["Line", "Command", "Error"], &rows))?;
+    std::process::exit(1);
+}
+
+/// The server's Minecraft version, unless given with `--mc-version`
+///
+/// Servers that don't report it are taken for the oldest release having all
+/// their commands, which is enough to pick the command syntax. `None` means
+/// the newest syntax, as the progress message says.
+async fn detect_mc_version(
+    client: &mut RconClient,
+    mc_version: Option<McVersion>,
+    formatter: &OutputFormatter,
+) -> Result<Option<McVersion>, RconError> {
+    if mc_version.is_some() {
+        return Ok(mc_version);
+    }
+    let server = client.server_info().await?;
+    match (server.version, server.min_version) {
+        (None, Some(version)) => {
+            formatter.progress(&t!("mc-version-guessed", version = version.to_string()))
+        }
+        (None, None) => formatter.progress(&t!(
+            "mc-version-unknown",
+            version = latest_known_version().to_string()
+        )),
+        _ => {}
+    }
+    Ok(server.assumed_version())
+}
+
+struct GiveOptions<'a> {
+    player: &'a str,
+    item: &'a str,
+    count: u32,
+    nbt: Option<&'a Path>,
+    mc_version: Option<McVersion>,
+    force: bool,
+}
+
+async fn run_give_command(
+    config: &RconConfig,
+    options: &GiveOptions<'_>,
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = connect_with_retry(config, formatter).await?;
+
+    let version = detect_mc_version(&mut client, options.mc_version, formatter).await?;
+
+    // A guessed version is only a lower bound, too old to rule out new items
+    let reported = match options.mc_version {
+        Some(version) => Some(version),
+        None => client.server_info().await?.version,
+    };
+    let item = match check_item(options.item, reported) {
+        Ok(item) => item,
+        Err(_) if options.force => options.item.to_string(),
+        Err(e) => return Err(Box::new(ErrorReport::new("invalid_item", e.to_string()))),
+    };
+
+    let data = match options.nbt {
+        Some(path) => {
+            let source = std::fs::read_to_string(path).map_err(|e| {
+                ErrorReport::new(
+                    "invalid_config",
+                    t!(
+                        "file-read-failed",
+                        path = path.display().to_string(),
+                        error = e.to_string()
+                    ),
+                )
+            })?;
+            // JSON is converted to SNBT; anything else is taken as SNBT already
+            Some(match serde_json::from_str(&source) {
+                Ok(value) => json_to_snbt(&value, version),
+                Err(_) => source.trim().to_string(),
+            })
+        }
+        None => None,
+    };
+
+    let command = give_command(
+        options.player,
+        &item,
+        options.count,
+        data.as_deref(),
+        version,
+    );
+    info!("Sending '{}'", command);
+    let response = client.execute_command(&command).await?;
+
+    // "Gave" since 1.13, "Given" before
+    if response.starts_with("Gave ") || response.starts_with("Given ") {
+        formatter.print_response(&response)?;
+        Ok(())
+    } else {
+        Err(Box::new(ErrorReport::new(
+            "command_failed",
+            t!(
+                "unexpected-response",
+                command = command.as_str(),
+                response = response.as_str()
+            ),
+        )))
+    }
+}
+
+/// Assemble `advancement grant|revoke`, completing a partial advancement ID
+fn advancement_command(
+    action: AdvancementAction,
+    player: &str,
+    advancement: &str,
+) -> Result<String, AdvancementError> {
+    let selection = if advancement.eq_ignore_ascii_case("everything") {
+        "everything".to_string()
+    } else {
+        format!("only {}", complete_advancement(advancement)?)
+    };
+    Ok(format!(
+        "advancement {} {} {}",
+        action.as_str(),
+        player,
+        selection
+    ))
+}
+
+async fn run_advancement_command(
+    config: &RconConfig,
+    command: &str,
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = connect_with_retry(config, formatter).await?;
+    info!("Sending '{}'", command);
+    let response = client.execute_command(command).await?;
+
+    if response.starts_with("Granted ") || response.starts_with("Revoked ") {
+        formatter.print_response(&response)?;
+        Ok(())
+    } else {
+        Err(Box::new(ErrorReport::new(
+            "command_failed",
+            t!(
+                "unexpected-response",
+                command = command,
+                response = response.as_str()
+            ),
+        )))
+    }
+}
+
+/// Send a command and parse its response, failing on anything unrecognized
+///
+/// Error messages such as "No player was found" don't parse, so they are
+/// reported as failures rather than printed as results.
+async fn execute_parsed<T>(
+    client: &mut dyn RconExecutor,
+    command: &str,
+    parse: fn(&str) -> Option<T>,
+) -> Result<T, Box<dyn std::error::Error>> {
+    info!("Sending '{}'", command);
+    let response = client.execute_command(command).await?;
+    parse(&response).ok_or_else(|| {
+        ErrorReport::new(
+            "command_failed",
+            t!(
+                "unexpected-response",
+                command = command,
+                response = response.as_str()
+            ),
+        )
+        .into()
+    })
+}
+
+/// Send commands and print their responses as they are, for `--raw`
+async fn print_raw_responses(
+    config: &RconConfig,
+    commands: &[String],
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = connect_with_retry(config, formatter).await?;
+    for command in commands {
+        info!("Sending '{}'", command);
+        formatter.print_response(&client.execute_command(command).await?)?;
+    }
+    Ok(())
+}
+
+async fn run_inventory_command(
+    config: &RconConfig,
+    player: &str,
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = connect_with_retry(config, formatter).await?;
+    let command = format!("data get entity {} Inventory", player);
+    let items = execute_parsed(&mut client, &command, parse_inventory).await?;
+
+    let rows: Vec<Vec<String>> = items
+        .into_iter()
+        .map(|item| {
+            let enchantments: Vec<String> = item
+                .enchantments
+                .iter()
+                .map(|(id, level)| format!("{} {}", display_name(id), level))
+                .collect();
+            vec![
+                item.slot_name(),
+                display_name(&item.id),
+                item.id,
+                item.count.to_string(),
+                enchantments.join(", "),
+            ]
+        })
+        .collect();
+    formatter.print_output(
+        &formatter.format_records(&["Slot", "Item", "ID", "Count", "Enchantments"], &rows),
+    )?;
+    Ok(())
+}
+
+async fn run_tps_command(
+    config: &RconConfig,
+    raw: bool,
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = connect_with_retry(config, formatter).await?;
+    let server = client.server_info().await?;
+    let Some(source) = server.flavor.tps_source(server.assumed_version()) else {
+        return Err(ErrorReport::new(
+            "unsupported",
+            t!("tps-unsupported", flavor = server.flavor.name()),
+        )
+        .into());
+    };
+
+    let command = source.command();
+    if raw {
+        info!("Sending '{}'", command);
+        formatter.print_response(&client.execute_command(command).await?)?;
+        return Ok(());
+    }
+    let samples = match source {
+        TpsSource::Bukkit => execute_parsed(&mut client, command, parse_bukkit_tps).await?,
+        TpsSource::Forge | TpsSource::NeoForge => {
+            execute_parsed(&mut client, command, parse_forge_tps).await?
+        }
+        TpsSource::TickQuery => {
+            vec![execute_parsed(&mut client, command, parse_tick_query).await?]
+        }
+    };
+
+    let rows: Vec<Vec<String>> = samples
+        .into_iter()
+        .map(|sample| {
+            vec![
+                sample.label,
+                format!("{:.2}", sample.tps),
+                sample
+                    .mspt
+                    .map(|mspt| format!("{:.2}", mspt))
+                    .unwrap_or_default(),
+            ]
+        })
+        .collect();
+    formatter.print_output(&formatter.format_records(&["Window", "TPS", "MSPT"], &rows))?;
+    Ok(())
+}
+
+async fn run_time_command(
+    config: &RconConfig,
+    command: &str,
+    key: &str,
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = connect_with_retry(config, formatter).await?;
+    let time = execute_parsed(&mut client, command, parse_time).await?;
+    formatter.print_output(&formatter.format_value(key, time.into()))?;
+    Ok(())
+}
+
+async fn run_weather_command(
+    config: &RconConfig,
+    kind: WeatherKind,
+    duration: Option<Duration>,
+    mc_version: Option<McVersion>,
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = connect_with_retry(config, formatter).await?;
+
+    let command = match duration {
+        Some(duration) => {
+            // Plain numbers were seconds before 1.19.4 and are ticks since
+            let version = detect_mc_version(&mut client, mc_version, formatter).await?;
+            let seconds = duration.as_secs().max(1);
+            match version {
+                Some(version) if version < McVersion::TIME_UNITS => {
+                    format!("weather {} {}", kind.as_str(), seconds)
+                }
+                _ => format!("weather {} {}s", kind.as_str(), seconds),
+            }
+        }
+        None => format!("weather {}", kind.as_str()),
+    };
+    info!("Sending '{}'", command);
+    let response = client.execute_command(&command).await?;
+
+    // "Set the weather to" since 1.13, "Changing to" before
+    if response.starts_with("Set the weather to") || response.starts_with("Changing to") {
+        formatter.print_response(&response)?;
+        Ok(())
+    } else {
+        Err(Box::new(ErrorReport::new(
+            "command_failed",
+            t!(
+                "unexpected-response",
+                command = command.as_str(),
+                response = response.as_str()
+            ),
+        )))
+    }
+}
+
+async fn run_effect_command(
+    config: &RconConfig,
+    command: &str,
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = connect_with_retry(config, formatter).await?;
+    let change = execute_parsed(&mut client, command, parse_effect_change).await?;
+
+    let row = vec![
+        change.effect.unwrap_or_else(|| "all".to_string()),
+        change.target.name().unwrap_or("-").to_string(),
+        change.target.count().to_string(),
+    ];
+    formatter.print_output(&formatter.format_records(&["Effect", "Target", "Count"], &[row]))?;
+    Ok(())
+}
+
+async fn run_xp_change_command(
+    config: &RconConfig,
+    command: &str,
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = connect_with_retry(config, formatter).await?;
+    let change = execute_parsed(&mut client, command, parse_xp_change).await?;
+
+    let row = vec![
+        change.target.name().unwrap_or("-").to_string(),
+        change.target.count().to_string(),
+        change.amount.to_string(),
+        change.unit.as_str().to_string(),
+    ];
+    formatter
+        .print_output(&formatter.format_records(&["Target", "Count", "Amount", "Unit"], &[row]))?;
+    Ok(())
+}
+
+async fn run_xp_query_command(
+    config: &RconConfig,
+    command: &str,
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = connect_with_retry(config, formatter).await?;
+    let query = execute_parsed(&mut client, command, parse_xp_query).await?;
+
+    let row = vec![
+        query.player,
+        query.amount.to_string(),
+        query.unit.as_str().to_string(),
+    ];
+    formatter.print_output(&formatter.format_records(&["Player", "Amount", "Unit"], &[row]))?;
+    Ok(())
+}
+
+async fn run_perms_command(
+    config: &RconConfig,
+    holder: &PermsHolder,
+    command: &str,
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = connect_with_retry(config, formatter).await?;
+    let join = |groups: &[String]| match groups {
+        [] => "-".to_string(),
+        groups => groups.join(", "),
+    };
+    match (holder, holder.action()) {
+        (PermsHolder::User { .. }, PermsAction::Info) => {
+            let user = execute_parsed(&mut client, command, parse_luckperms_info).await?;
+            let row = vec![
+                user.name,
+                user.uuid.unwrap_or_else(|| "-".to_string()),
+                user.online
+                    .map_or("-", |online| if online { "yes" } else { "no" })
+                    .to_string(),
+                user.primary_group.unwrap_or_else(|| "-".to_string()),
+                join(&user.parents),
+                join(&user.temporary_parents),
+            ];
+            let headers = [
+                "User",
+                "UUID",
+                "Online",
+                "Primary group",
+                "Groups",
+                "Temporary",
+            ];
+            formatter.print_output(&formatter.format_records(&headers, &[row]))?;
+        }
+        (PermsHolder::Group { .. }, PermsAction::Info) => {
+            let group = execute_parsed(&mut client, command, parse_luckperms_info).await?;
+            let row = vec![
+                group.name,
+                group
+                    .weight
+                    .map_or("-".to_string(), |weight| weight.to_string()),
+                join(&group.parents),
+                join(&group.temporary_parents),
+            ];
+            let headers = ["Group", "Weight", "Parents", "Temporary"];
+            formatter.print_output(&formatter.format_records(&headers, &[row]))?;
+        }
+        (
+            PermsHolder::User { name, .. } | PermsHolder::Group { name, .. },
+            PermsAction::Check { .. },
+        ) => {
+            let check = execute_parsed(&mut client, command, parse_permission_check).await?;
+            let row = vec![
+                name.clone(),
+                check.permission,
+                check.result.as_str().to_string(),
+            ];
+            formatter.print_output(
+                &formatter.format_records(&["Holder", "Permission", "Result"], &[row]),
+            )?;
+        }
+        _ => {
+            info!("Sending '{}'", command);
+            let response = client.execute_command(command).await?;
+            formatter.print_response(&luckperms_lines(&response).join("\n"))?;
+        }
+    }
+    Ok(())
+}
+
+/// Run `command` in another dimension, if one is given
+fn in_dimension(dimension: Option<&str>, command: String) -> String {
+    match dimension {
+        Some(dimension) => format!("execute in {} run {}", dimension, command),
+        None => command,
+    }
+}
+
+/// Every command a `forceload` subcommand sends, for `--raw`
+fn forceload_commands(action: &ForceloadCommand) -> Vec<String> {
+    let area = |from: &BlockColumn, to: &Option<BlockColumn>| {
+        ChunkArea::between(from.chunk(), to.unwrap_or(*from).chunk())
+    };
+    let (commands, dimension) = match action {
+        ForceloadCommand::Add {
+            from,
+            to,
+            dimension,
+        } => (
+            area(from, to)
+                .split(FORCELOAD_LIMIT)
+                .iter()
+                .map(|tile| format!("forceload add {}", tile.block_args()))
+                .collect(),
+            dimension,
+        ),
+        ForceloadCommand::Remove {
+            from: Some(from),
+            to,
+            all: false,
+            dimension,
+        } => (
+            area(from, to)
+                .split(FORCELOAD_LIMIT)
+                .iter()
+                .map(|tile| format!("forceload remove {}", tile.block_args()))
+                .collect(),
+            dimension,
+        ),
+        ForceloadCommand::Remove { dimension, .. } => {
+            (vec!["forceload remove all".to_string()], dimension)
+        }
+        ForceloadCommand::Query {
+            from: Some(from),
+            to,
+            dimension,
+        } => (
+            area(from, to)
+                .chunks()
+                .map(|chunk| format!("forceload query {}", chunk.block_args()))
+                .collect(),
+            dimension,
+        ),
+        ForceloadCommand::Query { dimension, .. } => {
+            (vec!["forceload query".to_string()], dimension)
+        }
+    };
+    commands
+        .into_iter()
+        .map(|command: String| in_dimension(dimension.as_deref(), command))
+        .collect()
+}
+
+/// Add or remove force-loaded chunks, in tiles the server accepts
+async fn run_forceload_change_command(
+    config: &RconConfig,
+    verb: &str,
+    area: ChunkArea,
+    dimension: Option<&str>,
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let tiles = area.split(FORCELOAD_LIMIT);
+    let mut client = connect_with_retry(config, formatter).await?;
+
+    let mut rows = Vec::new();
+    let mut failures = Vec::new();
+    let mut changed = 0;
+    for (index, tile) in tiles.iter().enumerate() {
+        let command = in_dimension(
+            dimension,
+            format!("forceload {} {}", verb, tile.block_args()),
+        );
+        info!("Sending '{}'", command);
+        match client.execute_command(&command).await {
+            Ok(response) => match parse_forceload_change(&response) {
+                Some(count) => {
+                    changed += u64::from(count);
+                    rows.push(vec![
+                        tile.min.to_string(),
+                        tile.max.to_string(),
+                        tile.chunk_count().to_string(),
+                        count.to_string(),
+                    ]);
+                }
+                None => failures.push(ErrorReport::new(
+                    "command_failed",
+                    t!(
+                        "unexpected-response",
+                        command = command.as_str(),
+                        response = response.as_str()
+                    ),
+                )),
+            },
+            Err(e) => failures.push(ErrorReport::from(&e).context(command)),
+        }
+        if tiles.len() > 1 {
+            formatter.progress_bar(index + 1, tiles.len(), failures.len());
+        }
+    }
+
+    formatter
+        .print_output(&formatter.format_records(&["From", "To", "Chunks", "Changed"], &rows))?;
+    formatter.info(&t!(
+        "forceload-changed",
+        changed = changed,
+        total = area.chunk_count(),
+        commands = tiles.len()
+    ));
+    if !failures.is_empty() {
+        for report in &failures {
+            eprintln!("{}", formatter.format_failure(report));
+        }
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Query force-loading chunk by chunk, or list every force-loaded chunk
+async fn run_forceload_query_command(
+    config: &RconConfig,
+    area: Option<ChunkArea>,
+    dimension: Option<&str>,
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = connect_with_retry(config, formatter).await?;
+
+    let Some(area) = area else {
+        let command = in_dimension(dimension, "forceload query".to_string());
+        let chunks = execute_parsed(&mut client, &command, parse_forceload_list).await?;
+        let rows: Vec<Vec<String>> = chunks
+            .into_iter()
+            .map(|(x, z)| vec![x.to_string(), z.to_string()])
+            .collect();
+        formatter.print_output(&formatter.format_records(&["X", "Z"], &rows))?;
+        return Ok(());
+    };
+
+    let total = area.chunk_count() as usize;
+    let mut rows = Vec::new();
+    let mut forced = 0;
+    for (index, chunk) in area.chunks().enumerate() {
+        let command = in_dimension(dimension, format!("forceload query {}", chunk.block_args()));
+        let status = execute_parsed(&mut client, &command, parse_forceload_query).await?;
+        forced += usize::from(status.forced);
+        rows.push(vec![
+            chunk.x.to_string(),
+            chunk.z.to_string(),
+            status.forced.to_string(),
+        ]);
+        if total > 1 {
+            formatter.progress_bar(index + 1, total, 0);
+        }
+    }
+
+    formatter.print_output(&formatter.format_records(&["X", "Z", "Forced"], &rows))?;
+    formatter.info(&t!("forceload-queried", forced = forced, total = total));
+    Ok(())
+}
+
+async fn run_tp_command(
+    config: &RconConfig,
+    who: &str,
+    destination: &Destination,
+    dimension: Option<&str>,
+    confirm_loaded: bool,
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let invalid = |error: String| {
+        Box::new(ErrorReport::new(
+            "invalid_arguments",
+            t!("invalid-arguments", error = error),
+        ))
+    };
+    let position = match destination {
+        Destination::Position(position) => Some(position),
+        Destination::Entity(_) if dimension.is_some() || confirm_loaded => {
+            return Err(invalid(t!("tp-needs-coordinates")));
+        }
+        Destination::Entity(_) => None,
+    };
+    let in_dimension = |command: String| in_dimension(dimension, command);
+
+    let mut client = connect_with_retry(config, formatter).await?;
+
+    if confirm_loaded {
+        let (x, z) = position
+            .and_then(Position::block_column)
+            .ok_or_else(|| invalid(t!("tp-confirm-needs-absolute")))?;
+        let command = in_dimension(format!("forceload query {} {}", x, z));
+        let response = client.execute_command(&command).await?;
+        let Some(status) = parse_forceload_query(&response) else {
+            return Err(Box::new(ErrorReport::new(
+                "unexpected_response",
+                t!(
+                    "unexpected-response",
+                    command = command.as_str(),
+                    response = response.as_str()
+                ),
+            )));
+        };
+        if !status.forced {
+            return Err(Box::new(ErrorReport::new(
+                "chunk_not_loaded",
+                t!(
+                    "tp-chunk-not-loaded",
+                    x = status.chunk.0,
+                    z = status.chunk.1,
+                    dimension = status.dimension.as_str()
+                ),
+            )));
+        }
+    }
+
+    let command = in_dimension(format!("tp {} {}", who, destination));
+    info!("Sending '{}'", command);
+    let response = client.execute_command(&command).await?;
+
+    // "Teleported" since 1.13, "Teleported"/"Teleporting" in older releases
+    if response.starts_with("Teleported ") || response.starts_with("Teleporting ") {
+        formatter.print_response(&response)?;
+        Ok(())
+    } else {
+        Err(Box::new(ErrorReport::new(
+            "command_failed",
+            t!(
+                "unexpected-response",
+                command = command.as_str(),
+                response = response.as_str()
+            ),
+        )))
+    }
+}
+
+/// Send `data merge` commands, stopping at the first the server rejects
+async fn run_data_merge_command(
+    config: &RconConfig,
+    commands: &[String],
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = connect_with_retry(config, formatter).await?;
+    for command in commands {
+        info!("Sending '{}'", command);
+        let response = client.execute_command(command).await?;
+        // "Nothing changed" when the target already holds the data
+        if !response.starts_with("Modified ") && !response.starts_with("Nothing changed") {
+            return Err(Box::new(ErrorReport::new(
+                "command_failed",
+                t!(
+                    "unexpected-response",
+                    command = command.as_str(),
+                    response = response.as_str()
+                ),
+            )));
+        }
+        formatter.print_response(&response)?;
+    }
+    Ok(())
+}
+
+async fn run_whitelist_sync_command(
+    config: &RconConfig,
+    roster: &[String],
+    no_remove: bool,
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = connect_with_retry(config, formatter).await?;
+    let response = client.execute_command("whitelist list").await?;
+    let Some(current) = parse_whitelist(&response) else {
+        return Err(Box::new(ErrorReport::new(
+            "unexpected_response",
+            t!(
+                "unexpected-response",
+                command = "whitelist list",
+                response = response.as_str()
+            ),
+        )));
+    };
+
+    let mut diff = WhitelistDiff::between(&current, roster);
+    if no_remove {
+        diff.remove.clear();
+    }
+    if diff.is_empty() {
+        formatter.info(&t!("whitelist-in-sync", count = current.len()));
+        return Ok(());
+    }
+
+    let rows: Vec<Vec<String>> = diff
+        .remove
+        .iter()
+        .map(|name| vec!["remove".to_string(), name.clone()])
+        .chain(
+            diff.add
+                .iter()
+                .map(|name| vec!["add".to_string(), name.clone()]),
+        )
+        .collect();
+    formatter.print_output(&formatter.format_records(&["Action", "Player"], &rows))?;
+
+    let mut failed = 0;
+    for command in diff.commands() {
+        if let Err(e) = client.execute_command(&command).await {
+            let report = ErrorReport::from(&e).context(command);
+            eprintln!("{}", formatter.format_failure(&report));
+            failed += 1;
+        }
+    }
+
+    formatter.info(&t!(
+        "whitelist-synced",
+        added = diff.add.len(),
+        removed = diff.remove.len()
+    ));
+    if failed > 0 {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Parser for the response of an add-on listing command
+type AddonParser = fn(&str) -> Option<Vec<Addon>>;
+
+async fn run_addons_list_command(
+    config: &RconConfig,
+    enabled_only: bool,
+    raw: bool,
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = connect_with_retry(config, formatter).await?;
+    let flavor = client.server_info().await?.flavor;
+
+    let mut sources: Vec<(&str, AddonParser)> = vec![("datapack list", parse_datapack_list)];
+    if flavor.has_plugins() {
+        sources.push(("plugins", parse_plugin_list));
+    }
+    if let Some(command) = flavor.mod_list_command() {
+        sources.push((command, parse_mod_list));
+    }
+
+    let mut addons = Vec::new();
+    for (command, parse) in sources {
+        let response = client.execute_command(command).await?;
+        if raw {
+            formatter.print_response(&response)?;
+            continue;
+        }
+        match parse(&response) {
+            Some(found) => addons.extend(found),
+            None => {
+                let report = ErrorReport::new(
+                    "unexpected_response",
+                    t!(
+                        "unexpected-response",
+                        command = command,
+                        response = response.as_str()
+                    ),
+                );
+                eprintln!("{}", formatter.format_failure(&report));
+            }
+        }
+    }
+
+    if enabled_only {
+        addons.retain(|addon| addon.enabled);
+    }
+
+    // The plugin listing has no versions, so ask for each one
+    for addon in &mut addons {
+        if addon.kind == AddonKind::Plugin {
+            let response = client
+                .execute_command(&format!("version {}", addon.name))
+                .await?;
+            addon.version = parse_plugin_version(&response);
+        }
+    }
+
+    formatter.info(&t!(
+        "addons-count",
+        count = addons.len(),
+        flavor = flavor.name()
+    ));
+    let rows: Vec<Vec<String>> = addons
+        .into_iter()
+        .map(|addon| {
+            vec![
+                addon.name,
+                addon.kind.as_str().to_string(),
+                addon.version.unwrap_or_default(),
+                if addon.enabled { "yes" } else { "no" }.to_string(),
+            ]
+        })
+        .collect();
+    formatter
+        .print_output(&formatter.format_records(&["Name", "Type", "Version", "Enabled"], &rows))?;
+
+    Ok(())
+}
+
+/// Settings of the sessions command
+struct SessionsOptions {
+    watch: bool,
+    interval: Duration,
+    watch_for: Option<Duration>,
+}
+
+async fn run_sessions_command(
+    config: &RconConfig,
+    options: &SessionsOptions,
+    events: &EventBus,
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let SessionsOptions {
+        watch,
+        interval,
+        watch_for,
+    } = *options;
+
+    let mut client = connect_with_retry(config, formatter).await?;
+    let mut tracker = SessionTracker::new();
+    let started = Instant::now();
+
+    if watch {
+        formatter.progress(&t!(
+            "sessions-watching",
+            address = config.address.to_string(),
+            interval = format_duration(interval)
+        ));
+    }
+
+    let mut shutdown = Shutdown::listen()?;
+
+    loop {
+        if let Some(list) = poll_player_list(&mut client, formatter).await {
+            events.emit(EventKind::PlayersCounted(list.clone()));
+            let players = list.players.into_iter().map(|player| player.name);
+            for event in tracker.observe(players, Utc::now()) {
+                formatter.print_output(&format_session_event(formatter, &event))?;
+                events.publish(Event::session(events.server(), event));
+            }
+        }
+
+        if !watch {
+            let _ = client.close().await;
+            return Ok(());
+        }
+
+        let mut pause = interval;
+        if let Some(watch_for) = watch_for {
+            let remaining = watch_for.saturating_sub(started.elapsed());
+            if remaining.is_zero() {
+                break;
+            }
+            pause = pause.min(remaining);
+        }
+
+        if !shutdown.sleep(pause).await {
+            break;
+        }
+    }
+    let _ = client.close().await;
+
+    let summary = tracker.summary(Utc::now());
+    formatter.info(&t!(
+        "sessions-summary",
+        duration = format_duration(started.elapsed()),
+        players = summary.len()
+    ));
+    let rows: Vec<Vec<String>> = summary
+        .into_iter()
+        .map(|player| {
+            vec![
+                player.player,
+                player.sessions.to_string(),
+                format_duration(player.total),
+                format_duration(player.longest),
+                if player.online { "yes" } else { "no" }.to_string(),
+            ]
+        })
+        .collect();
+    formatter.print_output(
+        &formatter.format_records(&["Player", "Sessions", "Total", "Longest", "Online"], &rows),
+    )?;
+
+    Ok(())
+}
+
+/// Fetch and parse the player list, reporting failures on stderr
+///
+/// A failed poll reconnects so the next one can succeed once the server is
+/// back.
+async fn poll_player_list(
+    client: &mut RconClient,
+    formatter: &OutputFormatter,
+) -> Option<PlayerList> {
+    match client.execute_command("list").await {
+        Ok(response) => {
+            let list = parse_player_list(&response);
+            if list.is_none() {
+                let report = ErrorReport::new(
+                    "unexpected_response",
+                    t!("player-list-unrecognized", response = response.as_str()),
+                );
+                eprintln!("{}", formatter.format_failure(&report));
+            }
+            list
+        }
+        Err(e) => {
+            let report = ErrorReport::from(&e).context(t!("player-list-poll-failed"));
+            eprintln!("{}", formatter.format_failure(&report));
+            let _ = client.reconnect().await;
+            None
+        }
+    }
+}
+
+/// Render a join or leave as a line of the event stream
+fn format_session_event(formatter: &OutputFormatter, event: &SessionEvent) -> String {
+    let mut fields = serde_json::json!({ "player": event.player });
+    let message = match event.duration {
+        Some(duration) => {
+            fields["duration_secs"] = duration.as_secs().into();
+            t!(
+                "sessions-ended",
+                player = event.player.as_str(),
+                duration = format_duration(duration)
+            )
+        }
+        None => event.player.clone(),
+    };
+    formatter.format_event(event.kind.as_str(), &message, event.timestamp, fields)
+}
+
+/// Settings of the alert command
+struct AlertOptions<'a> {
+    rule: AlertRule,
+    exec: Option<&'a str>,
+    interval: Duration,
+    once: bool,
+    events: &'a EventBus,
+}
+
+async fn run_alert_command(
+    config: &RconConfig,
+    options: &AlertOptions<'_>,
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let AlertOptions {
+        rule,
+        exec,
+        interval,
+        once,
+        events,
+    } = *options;
+
+    let mut client = connect_with_retry(config, formatter).await?;
+    let mut state = AlertState::new(rule);
+    let condition = rule.condition.to_string();
+
+    formatter.progress(&t!(
+        "alert-watching",
+        condition = condition.as_str(),
+        hold = format_duration(rule.hold),
+        interval = format_duration(interval)
+    ));
+
+    let mut shutdown = Shutdown::listen()?;
+
+    loop {
+        if let Some(list) = poll_player_list(&mut client, formatter).await {
+            events.emit(EventKind::PlayersCounted(list.clone()));
+
+            if let Some(transition) = state.observe(&list, Instant::now()) {
+                let id = match transition {
+                    AlertTransition::Triggered => "alert-triggered",
+                    AlertTransition::Resolved => "alert-resolved",
+                };
+                let message = i18n::tr(
+                    id,
+                    Some(&i18n::FluentArgs::from_iter([
+                        ("condition", i18n::FluentValue::from(condition.as_str())),
+                        ("online", list.online.into()),
+                        ("max", list.max.into()),
+                    ])),
+                );
+                let fields = serde_json::json!({
+                    "condition": condition,
+                    "players": list.online,
+                    "max": list.max,
+                });
+                let now = Utc::now();
+                let event = EventKind::Alert {
+                    transition,
+                    condition: condition.clone(),
+                    online: list.online,
+                    max: list.max,
+                };
+                events.publish(Event::new(events.server(), event).at(now));
+                formatter.print_output(&formatter.format_event(
+                    transition.as_str(),
+                    &message,
+                    now,
+                    fields,
+                ))?;
+
+                if transition == AlertTransition::Triggered {
+                    let succeeded = match exec {
+                        Some(command) => {
+                            run_alert_exec(command, config, &condition, &list, formatter).await
+                        }
+                        None => true,
+                    };
+                    if once {
+                        let _ = client.close().await;
+                        if !succeeded {
+                            events.close().await;
+                            std::process::exit(1);
+                        }
+                        return Ok(());
+                    }
+                }
+            }
+        }
+
+        if !shutdown.sleep(interval).await {
+            break;
+        }
+    }
+
+    let _ = client.close().await;
+    Ok(())
+}
+
+/// Run an alert's --exec command through the shell, returning whether it succeeded
+///
+/// The command sees the alert through `RCON_ALERT_*` environment variables.
+async fn run_alert_exec(
+    command: &str,
+    config: &RconConfig,
+    condition: &str,
+    list: &PlayerList,
+    formatter: &OutputFormatter,
+) -> bool {
+    let mut shell = if cfg!(windows) {
+        let mut shell = tokio::process::Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = tokio::process::Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    shell
+        .arg(command)
+        .env("RCON_ALERT_CONDITION", condition)
+        .env("RCON_ALERT_PLAYERS", list.online.to_string())
+        .env("RCON_ALERT_MAX_PLAYERS", list.max.to_string())
+        .env("RCON_ALERT_ADDRESS", config.address.to_string());
+
+    let error = match shell.status().await {
+        Ok(status) if status.success() => return true,
+        Ok(status) => t!("alert-exec-status", status = status.to_string()),
+        Err(e) => t!("alert-exec-failed", error = e.to_string()),
+    };
+    let report = ErrorReport::new("exec", error);
+    eprintln!("{}", formatter.format_failure(&report));
+    false
+}
+
+/// Flushes pending chunk writes to disk before answering
+const SAVE_COMMAND: &str = "save-all flush";
+
+/// Exit status of `save --verify` when the server doesn't confirm the save
+const SAVE_FAILED_EXIT_CODE: i32 = 2;
+
+async fn run_save_command(
+    config: &RconConfig,
+    verify: bool,
+    timeout: Duration,
+    interval: Duration,
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = connect_with_retry(config, formatter).await?;
+    if !verify {
+        let response = client.execute_command(SAVE_COMMAND).await?;
+        formatter.print_response(&response)?;
+        return Ok(());
+    }
+
+    let started = Instant::now();
+    let timed_out = || -> ! {
+        let report = ErrorReport::new(
+            "timeout",
+            t!(
+                "save-timeout",
+                seconds = format!("{:.1}", timeout.as_secs_f64())
+            ),
+        );
+        eprintln!("{}", formatter.format_failure(&report));
+        std::process::exit(DEADLINE_EXIT_CODE);
+    };
+
+    loop {
+        let remaining = timeout.saturating_sub(started.elapsed());
+        let response =
+            match tokio::time::timeout(remaining, client.execute_command(SAVE_COMMAND)).await {
+                Ok(response) => response?,
+                Err(_) => timed_out(),
+            };
+
+        let report = match parse_save_response(&response) {
+            Some(SaveStatus::Saved) => {
+                formatter.print_response(&response)?;
+                formatter.info(&t!(
+                    "save-confirmed",
+                    ms = started.elapsed().as_millis().to_string()
+                ));
+                return Ok(());
+            }
+            Some(SaveStatus::Started) => {
+                formatter.progress(&t!("save-pending"));
+                if started.elapsed() + interval >= timeout {
+                    timed_out();
+                }
+                sleep(interval).await;
+                continue;
+            }
+            Some(SaveStatus::Failed(reason)) => {
+                ErrorReport::new("save_failed", t!("save-failed", reason = reason))
+            }
+            None => ErrorReport::new(
+                "unexpected_response",
+                t!("save-unrecognized", response = response.as_str()),
+            ),
+        };
+        eprintln!("{}", formatter.format_failure(&report));
+        std::process::exit(SAVE_FAILED_EXIT_CODE);
+    }
+}
+
+struct AutostopOptions<'a> {
+    idle: Duration,
+    warn: Duration,
+    interval: Duration,
+    message: &'a str,
+    stop_command: &'a str,
+    events: &'a EventBus,
+}
+
+/// Points of the countdown, as time remaining, at which warnings are broadcast
+const AUTOSTOP_WARNINGS: [Duration; 7] = [
+    Duration::from_secs(3600),
+    Duration::from_secs(1800),
+    Duration::from_secs(600),
+    Duration::from_secs(300),
+    Duration::from_secs(60),
+    Duration::from_secs(30),
+    Duration::from_secs(10),
+];
+
+async fn run_autostop_command(
+    config: &RconConfig,
+    options: &AutostopOptions<'_>,
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = connect_with_retry(config, formatter).await?;
+
+    formatter.progress(&t!(
+        "autostop-watching",
+        idle = format_duration(options.idle),
+        warn = format_duration(options.warn)
+    ));
+
+    let mut shutdown = Shutdown::listen()?;
+    let result = autostop(&mut client, options, &mut shutdown, formatter).await;
+    let _ = client.close().await;
+    result
+}
+
+async fn autostop(
+    client: &mut RconClient,
+    options: &AutostopOptions<'_>,
+    shutdown: &mut Shutdown,
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let empty = Condition {
+        metric: Metric::Players,
+        comparison: Comparison::Equal,
+        threshold: 0,
+    };
+    let warn = options.warn.min(options.idle);
+
+    loop {
+        let mut idle = AlertState::new(AlertRule::new(empty, options.idle - warn));
+        loop {
+            if let Some(list) = poll_player_list(client, formatter).await {
+                if idle.observe(&list, Instant::now()) == Some(AlertTransition::Triggered) {
+                    break;
+                }
+            }
+            if !shutdown.sleep(options.interval).await {
+                return Ok(());
+            }
+        }
+
+        match autostop_countdown(client, options, warn, shutdown, formatter).await? {
+            Countdown::Elapsed => break,
+            Countdown::PlayerJoined => {}
+            Countdown::Interrupted => return Ok(()),
+        }
+    }
+
+    print_event(
+        formatter,
+        "stop",
+        &t!("autostop-stopping", command = options.stop_command),
+    )?;
+    match client.execute_command(options.stop_command).await {
+        // The server may close the connection before answering
+        Ok(_) | Err(RconError::Disconnected) | Err(RconError::Network(_)) => {}
+        Err(e) => return Err(e.into()),
+    }
+    options
+        .events
+        .emit(EventKind::Stopped { idle: options.idle });
+    Ok(())
+}
+
+/// How an autostop countdown ended
+enum Countdown {
+    Elapsed,
+    PlayerJoined,
+    Interrupted,
+}
+
+/// Broadcast the shutdown countdown
+async fn autostop_countdown(
+    client: &mut RconClient,
+    options: &AutostopOptions<'_>,
+    warn: Duration,
+    shutdown: &mut Shutdown,
+    formatter: &OutputFormatter,
+) -> Result<Countdown, Box<dyn std::error::Error>> {
+    let deadline = Instant::now() + warn;
+    let warnings = std::iter::once(warn)
+        .chain(AUTOSTOP_WARNINGS.into_iter().filter(|mark| *mark < warn))
+        .filter(|mark| !mark.is_zero());
+
+    for remaining in warnings.chain(std::iter::once(Duration::ZERO)) {
+        let until = deadline - remaining;
+        let Some(online) =
+            wait_while_empty(client, until, options.interval, shutdown, formatter).await
+        else {
+            // Players were warned, so tell them the server stays up
+            let _ = client.execute_command("say Shutdown cancelled").await;
+            return Ok(Countdown::Interrupted);
+        };
+        if online > 0 {
+            print_event(
+                formatter,
+                "cancel",
+                &t!("autostop-cancelled", players = online),
+            )?;
+            let _ = client
+                .execute_command("say Shutdown cancelled, welcome back!")
+                .await;
+            return Ok(Countdown::PlayerJoined);
+        }
+        if remaining.is_zero() {
+            break;
+        }
+
+        let message = options
+            .message
+            .replace("{remaining}", &format_duration(remaining));
+        print_event(formatter, "warn", &message)?;
+        if let Err(e) = client.execute_command(&format!("say {}", message)).await {
+            let report = ErrorReport::from(&e);
+            eprintln!("{}", formatter.format_failure(&report));
+        }
+    }
+
+    Ok(Countdown::Elapsed)
+}
+
+/// Poll until `until`, returning the player count as soon as anyone is online
+///
+/// Returns `None` if a shutdown is requested first.
+async fn wait_while_empty(
+    client: &mut RconClient,
+    until: Instant,
+    interval: Duration,
+    shutdown: &mut Shutdown,
+    formatter: &OutputFormatter,
+) -> Option<u32> {
+    loop {
+        if let Some(list) = poll_player_list(client, formatter).await {
+            if list.online > 0 {
+                return Some(list.online);
+            }
+        }
+        let now = Instant::now();
+        if now >= until {
+            return Some(0);
+        }
+        if !shutdown.sleep(interval.min(until - now)).await {
+            return None;
+        }
+    }
+}
+
+struct RestartOptions<'a> {
+    /// Player count to wait for, if any
+    max_players: Option<u32>,
+    max_wait: Option<Duration>,
+    warn: &'a [Duration],
+    interval: Duration,
+    message: &'a str,
+    stop_command: &'a str,
+    events: &'a EventBus,
+}
+
+/// Exit status of `restart` when cancelled before the server was stopped
+const RESTART_CANCELLED_EXIT_CODE: i32 = 3;
+
+/// Run `restart`, returning the exit status if the server wasn't stopped
+async fn run_restart_command(
+    config: &RconConfig,
+    options: &RestartOptions<'_>,
+    formatter: &OutputFormatter,
+) -> Result<Result<(), i32>, Box<dyn std::error::Error>> {
+    let mut client = connect_with_retry(config, formatter).await?;
+    let mut shutdown = Shutdown::listen()?;
+    let result = restart(&mut client, options, &mut shutdown, formatter).await;
+    let _ = client.close().await;
+    result
+}
+
+async fn restart(
+    client: &mut RconClient,
+    options: &RestartOptions<'_>,
+    shutdown: &mut Shutdown,
+    formatter: &OutputFormatter,
+) -> Result<Result<(), i32>, Box<dyn std::error::Error>> {
+    let started = Instant::now();
+    let deadline = options.max_wait.map(|max_wait| started + max_wait);
+
+    let mut online = None;
+    if let Some(max_players) = options.max_players {
+        formatter.progress(&match options.max_wait {
+            Some(max_wait) => t!(
+                "restart-waiting-until",
+                players = max_players,
+                duration = format_duration(max_wait)
+            ),
+            None => t!("restart-waiting", players = max_players),
+        });
+        online = match wait_for_players(
+            client,
+            max_players,
+            deadline,
+            options.interval,
+            shutdown,
+            formatter,
+        )
+        .await
+        {
+            Wait::Reached(count) => {
+                print_event_with(
+                    formatter,
+                    "ready",
+                    &t!("restart-players-left", players = count),
+                    serde_json::json!({ "online": count }),
+                )?;
+                Some(count)
+            }
+            Wait::Deadline(count) => {
+                print_event_with(
+                    formatter,
+                    "deadline",
+                    &t!(
+                        "restart-deadline",
+                        duration = format_duration(started.elapsed())
+                    ),
+                    serde_json::json!({ "online": count }),
+                )?;
+                count
+            }
+            Wait::Interrupted => return Ok(Err(RESTART_CANCELLED_EXIT_CODE)),
+        };
+    }
+
+    // Nobody to warn when the server is empty
+    if online != Some(0) {
+        let cancelled = !restart_countdown(client, options, shutdown, formatter).await?;
+        if cancelled {
+            let _ = client.execute_command("say Restart cancelled").await;
+            return Ok(Err(RESTART_CANCELLED_EXIT_CODE));
+        }
+    }
+
+    let response = client.execute_command(SAVE_COMMAND).await?;
+    match parse_save_response(&response) {
+        Some(SaveStatus::Failed(reason)) => {
+            let report = ErrorReport::new("save_failed", t!("save-failed", reason = reason));
+            eprintln!("{}", formatter.format_failure(&report));
+            let _ = client.execute_command("say Restart cancelled").await;
+            return Ok(Err(SAVE_FAILED_EXIT_CODE));
+        }
+        Some(_) => {
+            print_event_with(
+                formatter,
+                "save",
+                &response,
+                serde_json::json!({ "response": response }),
+            )?;
+            options.events.emit(EventKind::Saved);
+        }
+        // The stop command saves as well, so carry on
+        None => formatter.info(&t!("save-unrecognized", response = response.as_str())),
+    }
+
+    let online = poll_player_list(client, formatter)
+        .await
+        .map_or(online.unwrap_or(0), |list| list.online);
+    print_event_with(
+        formatter,
+        "stop",
+        &t!("restart-stopping", command = options.stop_command),
+        serde_json::json!({ "command": options.stop_command, "online": online }),
+    )?;
+    match client.execute_command(options.stop_command).await {
+        // The server may close the connection before answering
+        Ok(_) | Err(RconError::Disconnected) | Err(RconError::Network(_)) => {}
+        Err(e) => return Err(e.into()),
+    }
+    options.events.emit(EventKind::Restarting {
+        waited: started.elapsed(),
+        online,
+    });
+    Ok(Ok(()))
+}
+
+/// Broadcast the restart countdown at each `--warn` mark
+///
+/// Ends early once nobody is online. Returns false if a shutdown is
+/// requested first.
+async fn restart_countdown(
+    client: &mut RconClient,
+    options: &RestartOptions<'_>,
+    shutdown: &mut Shutdown,
+    formatter: &OutputFormatter,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let mut marks = options.warn.to_vec();
+    marks.sort_unstable_by(|a, b| b.cmp(a));
+    marks.dedup();
+    let Some(&length) = marks.first() else {
+        return Ok(true);
+    };
+    let deadline = Instant::now() + length;
+
+    for remaining in marks {
+        match wait_for_players(
+            client,
+            0,
+            Some(deadline - remaining),
+            options.interval,
+            shutdown,
+            formatter,
+        )
+        .await
+        {
+            Wait::Reached(_) => {
+                print_event_with(
+                    formatter,
+                    "ready",
+                    &t!("restart-players-left", players = 0),
+                    serde_json::json!({ "online": 0 }),
+                )?;
+                return Ok(true);
+            }
+            Wait::Deadline(_) => {}
+            Wait::Interrupted => return Ok(false),
+        }
+
+        let message = options
+            .message
+            .replace("{remaining}", &format_duration(remaining));
+        print_event_with(
+            formatter,
+            "warn",
+            &message,
+            serde_json::json!({ "message": message, "remaining_secs": remaining.as_secs() }),
+        )?;
+        if let Err(e) = client.execute_command(&format!("say {}", message)).await {
+            let report = ErrorReport::from(&e);
+            eprintln!("{}", formatter.format_failure(&report));
+        }
+    }
+
+    match wait_for_players(
+        client,
+        0,
+        Some(deadline),
+        options.interval,
+        shutdown,
+        formatter,
+    )
+    .await
+    {
+        Wait::Interrupted => Ok(false),
+        Wait::Reached(_) | Wait::Deadline(_) => Ok(true),
+    }
+}
+
+/// How waiting for players to leave ended
+enum Wait {
+    /// At most the wanted number of players are online
+    Reached(u32),
+    /// The deadline passed first, with the last player count seen
+    Deadline(Option<u32>),
+    Interrupted,
+}
 
-            if show_time {
-                let elapsed = start_time.elapsed();
-                let time_info =
-                    formatter.format_info(&format!("Executed in {:.2}ms", elapsed.as_millis()));
-                eprintln!("{}", time_info);
+/// Poll until at most `max_players` are online or `until` has passed
+async fn wait_for_players(
+    client: &mut RconClient,
+    max_players: u32,
+    until: Option<Instant>,
+    interval: Duration,
+    shutdown: &mut Shutdown,
+    formatter: &OutputFormatter,
+) -> Wait {
+    let mut online = None;
+    loop {
+        if let Some(list) = poll_player_list(client, formatter).await {
+            if list.online <= max_players {
+                return Wait::Reached(list.online);
             }
+            online = Some(list.online);
         }
-        Err(e) => {
-            let error_msg = formatter.format_error(&e.to_string());
-            eprintln!("{}", error_msg);
-            std::process::exit(1);
+        let now = Instant::now();
+        let sleep = match until {
+            Some(until) if now >= until => return Wait::Deadline(online),
+            Some(until) => interval.min(until - now),
+            None => interval,
+        };
+        if !shutdown.sleep(sleep).await {
+            return Wait::Interrupted;
         }
     }
+}
 
-    Ok(())
+fn print_event(formatter: &OutputFormatter, event: &str, message: &str) -> io::Result<()> {
+    print_event_with(formatter, event, message, serde_json::json!({}))
 }
 
-async fn run_interactive_mode(
+/// Print an event with fields for JSON output
+fn print_event_with(
+    formatter: &OutputFormatter,
+    event: &str,
+    message: &str,
+    fields: serde_json::Value,
+) -> io::Result<()> {
+    formatter.print_output(&formatter.format_event(event, message, Utc::now(), fields))
+}
+
+async fn run_doctor_command(
     config: &RconConfig,
-    prompt: &str,
-    _history: bool,
-    _history_size: usize,
+    options: &DoctorOptions,
     formatter: &OutputFormatter,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let mut client = connect_with_retry(config, formatter).await?;
+    formatter.progress(&t!("doctor-start", address = config.address.to_string()));
+    let report = diagnose(config, options).await;
 
-    println!(
-        "{}",
-        formatter
-            .format_info("Entering interactive mode. Type 'quit', 'exit', or Ctrl+C to leave.")
-    );
+    let rows: Vec<Vec<String>> = report
+        .checks
+        .iter()
+        .map(|check| {
+            vec![
+                check.name.to_string(),
+                check.status.to_string(),
+                check.detail.clone(),
+            ]
+        })
+        .collect();
+    formatter.print_output(&formatter.format_records(&["Check", "Status", "Detail"], &rows))?;
 
-    loop {
-        print!("{}", prompt);
-        io::stdout().flush()?;
+    formatter.info(&t!(
+        "doctor-summary",
+        ok = report.count(CheckStatus::Ok),
+        warnings = report.count(CheckStatus::Warning),
+        failed = report.count(CheckStatus::Failed),
+        skipped = report.count(CheckStatus::Skipped)
+    ));
 
-        let mut input = String::new();
-        match io::stdin().read_line(&mut input) {
-            Ok(0) => break, // EOF
-            Ok(_) => {
-                let input = input.trim();
+    if !report.is_healthy() {
+        std::process::exit(1);
+    }
 
-                if input.is_empty() {
-                    continue;
-                }
+    Ok(())
+}
 
-                if input == "quit" || input == "exit" {
-                    break;
-                }
+/// Connect to a configured profile and run one command
+///
+/// The profile's command prefix is applied for user-given commands only.
+async fn run_on_profile(
+    cli: &Cli,
+    config_file: &ConfigFile,
+    name: &str,
+    command: &str,
+    prefixed: bool,
+    formatter: &OutputFormatter,
+) -> Result<String, String> {
+    let profile = config_file.profile(name).map_err(|e| e.to_string())?;
+    let target = resolve_profile_target(cli, name, profile).await?;
+    let config = rcon_config(cli, &target, formatter)
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut client = RconClient::connect(config)
+        .await
+        .map_err(|e| e.to_string())?;
+    let command = match &target.prefix {
+        Some(prefix) if prefixed => with_prefix(prefix, command),
+        _ => command.to_string(),
+    };
+    client
+        .execute_command(&command)
+        .await
+        .map_err(|e| e.to_string())
+}
 
-                // Handle special commands
-                match input {
-                    "help" => {
-                        show_interactive_help(formatter);
-                        continue;
-                    }
-                    "status" => {
-                        show_connection_status(&mut client, formatter).await;
-                        continue;
-                    }
-                    "reconnect" => {
-                        match reconnect(&mut client, config, formatter).await {
-                            Ok(_) => {
-                                println!("{}", formatter.format_info("Reconnected successfully"));
-                            }
-                            Err(e) => {
-                                eprintln!("{}", formatter.format_error(&e.to_string()));
-                            }
-                        }
-                        continue;
+async fn run_net_command(
+    cli: &Cli,
+    config_file: &ConfigFile,
+    network: Option<&str>,
+    action: &NetCommand,
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (_, network) = config_file
+        .network(network)
+        .unwrap_or_else(|e| exit_invalid_arguments(formatter, e));
+
+    match action {
+        NetCommand::Exec {
+            servers,
+            proxy,
+            command,
+        } => {
+            let names: Vec<&str> = if *proxy {
+                let proxy = network
+                    .proxy
+                    .as_deref()
+                    .unwrap_or_else(|| exit_invalid_arguments(formatter, t!("net-no-proxy")));
+                vec![proxy]
+            } else if servers.is_empty() {
+                network.servers.iter().map(String::as_str).collect()
+            } else {
+                for server in servers {
+                    if !network.servers.contains(server) {
+                        exit_invalid_arguments(
+                            formatter,
+                            t!(
+                                "net-unknown-server",
+                                server = server.as_str(),
+                                servers = network.servers.join(", ")
+                            ),
+                        );
                     }
-                    _ => {}
                 }
+                servers.iter().map(String::as_str).collect()
+            };
 
-                // Execute the command
-                match client.execute_command(input).await {
-                    Ok(response) => {
-                        if !response.is_empty() {
-                            let formatted_response = formatter.format_response(&response);
-                            println!("{}", formatted_response);
-                        }
-                    }
-                    Err(RconError::Network(_)) | Err(RconError::Disconnected) => {
-                        eprintln!(
-                            "{}",
-                            formatter.format_error("Connection lost. Attempting to reconnect...")
-                        );
+            let runs = names.iter().map(|name| async move {
+                (
+                    *name,
+                    run_on_profile(cli, config_file, name, command, true, formatter).await,
+                )
+            });
+            let results = join_all(runs).await;
 
-                        match reconnect(&mut client, config, formatter).await {
-                            Ok(_) => {
-                                eprintln!(
-                                    "{}",
-                                    formatter.format_info("Reconnected. Retrying command...")
-                                );
+            // A single server answers like `exec`
+            if let [(name, result)] = results.as_slice() {
+                return match result {
+                    Ok(response) => Ok(formatter.print_response(response)?),
+                    Err(e) => Err(ErrorReport::new("command_failed", e.as_str())
+                        .context(name)
+                        .into()),
+                };
+            }
 
-                                match client.execute_command(input).await {
-                                    Ok(response) => {
-                                        if !response.is_empty() {
-                                            let formatted_response =
-                                                formatter.format_response(&response);
-                                            println!("{}", formatted_response);
-                                        }
-                                    }
-                                    Err(e) => {
-                                        eprintln!("{}", formatter.format_error(&e.to_string()));
-                                    }
-                                }
-                            }
-                            Err(e) => {
-                                eprintln!(
-                                    "{}",
-                                    formatter.format_error(&format!("Failed to reconnect: {}", e))
-                                );
-                            }
-                        }
+            let failed = results.iter().filter(|(_, result)| result.is_err()).count();
+            let rows: Vec<Vec<String>> = results
+                .into_iter()
+                .map(|(name, result)| match result {
+                    Ok(response) => vec![name.to_string(), response, String::new()],
+                    Err(e) => vec![name.to_string(), String::new(), e],
+                })
+                .collect();
+            formatter
+                .print_output(&formatter.format_records(&["Server", "Response", "Error"], &rows))?;
+            if failed > 0 {
+                return Err(ErrorReport::new(
+                    "command_failed",
+                    t!("net-exec-failed", failed = failed, total = rows.len()),
+                )
+                .into());
+            }
+        }
+        NetCommand::Players => {
+            let lists = network.servers.iter().map(|name| async move {
+                let list = run_on_profile(cli, config_file, name, "list", false, formatter)
+                    .await
+                    .and_then(|response| {
+                        parse_player_list(&response).ok_or_else(|| {
+                            t!("player-list-unrecognized", response = response.as_str())
+                        })
+                    });
+                (name, list)
+            });
+
+            let mut rows = Vec::new();
+            let mut answered = 0;
+            for (name, list) in join_all(lists).await {
+                match list {
+                    Ok(list) => {
+                        answered += 1;
+                        rows.extend(
+                            list.players
+                                .into_iter()
+                                .map(|player| vec![player.name, name.clone()]),
+                        );
                     }
                     Err(e) => {
-                        eprintln!("{}", formatter.format_error(&e.to_string()));
+                        let report = ErrorReport::new("command_failed", e).context(name);
+                        eprintln!("{}", formatter.format_failure(&report));
                     }
                 }
             }
-            Err(e) => {
-                eprintln!("{}", formatter.format_error(&format!("Input error: {}", e)));
-                break;
+            if answered == 0 && !network.servers.is_empty() {
+                std::process::exit(1);
             }
+
+            formatter.info(&t!("net-players", players = rows.len(), servers = answered));
+            formatter.print_output(&formatter.format_records(&["Player", "Server"], &rows))?;
         }
     }
-
-    println!("{}", formatter.format_info("Goodbye!"));
     Ok(())
 }
 
-async fn run_ping_command(
-    config: &RconConfig,
-    count: u32,
-    interval: u64,
+/// Check scripts without connecting, exiting 1 if there are problems
+///
+/// The policy and dialect come from the flags and the selected profile.
+fn run_lint_command(
+    cli: &Cli,
+    config_file: &ConfigFile,
+    scripts: &[PathBuf],
+    schema: Option<CommandSchema>,
+    allow: &[String],
     formatter: &OutputFormatter,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let mut client = connect_with_retry(config, formatter).await?;
-    let interval_duration = Duration::from_secs(interval);
+    let profile = cli.selected_profile(config_file)?;
+    let dialect = cli.dialect.or(profile.dialect).unwrap_or_default();
+    let mut linter = Linter::new(dialect.dialect());
+    if let Some(schema) = schema {
+        linter = linter.with_schema(schema);
+    }
+    let read_only = cli.read_only || profile.read_only;
+    if !allow.is_empty() {
+        let queries = READ_ONLY_COMMANDS.iter().filter(|_| read_only);
+        let allowed = allow.iter().map(String::as_str).chain(queries.copied());
+        linter = linter.with_policy(CommandPolicy::allow("the --allow list", allowed));
+    } else if read_only {
+        linter = linter.with_policy(CommandPolicy::read_only());
+    }
 
-    println!(
-        "{}",
-        formatter.format_info(&format!("Pinging {} {} time(s)", config.address, count))
-    );
+    let mut rows = Vec::new();
+    let mut commands = 0;
+    for path in scripts {
+        let script = if path
+            .extension()
+            .is_some_and(|extension| extension == "toml")
+        {
+            Playbook::load(path)?.script_lines()
+        } else {
+            load_script(path)?
+        };
+        commands += script.len();
+        rows.extend(linter.lint(&script).into_iter().map(|issue| {
+            vec![
+                path.display().to_string(),
+                issue.line_number.to_string(),
+                issue.check.to_string(),
+                issue.message,
+            ]
+        }));
+    }
 
-    let mut successful_pings = 0;
-    let mut total_time = Duration::ZERO;
+    if rows.is_empty() {
+        formatter.info(&t!(
+            "lint-clean",
+            commands = commands,
+            scripts = scripts.len()
+        ));
+        return Ok(());
+    }
+    formatter
+        .print_output(&formatter.format_records(&["File", "Line", "Check", "Problem"], &rows))?;
+    formatter.progress(&t!("lint-problems", count = rows.len()));
+    std::process::exit(1);
+}
 
-    for i in 1..=count {
-        let start_time = Instant::now();
+/// Run a Rhai script, printing the value it ends with
+///
+/// The script is read before connecting, so a wrong path fails fast. An
+/// `exit(code)` in the script exits with that code.
+async fn run_eval_command(
+    config: &RconConfig,
+    script: &Path,
+    args: &[String],
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let source = std::fs::read_to_string(script).unwrap_or_else(|e| {
+        exit_invalid_arguments(
+            formatter,
+            t!(
+                "file-read-failed",
+                path = script.display().to_string(),
+                error = e.to_string()
+            ),
+        )
+    });
+    let client = connect_with_retry(config, formatter).await?;
+    let engine = ScriptEngine::new(client).with_shutdown(Shutdown::listen()?);
 
-        match client.ping().await {
-            Ok(_) => {
-                let elapsed = start_time.elapsed();
-                total_time += elapsed;
-                successful_pings += 1;
+    let args = args.to_vec();
+    let outcome = tokio::task::spawn_blocking(move || engine.run(&source, args))
+        .await?
+        .map_err(|e| ErrorReport::from(&e).context(script.display().to_string()))?;
+    match outcome {
+        Outcome::Finished(value) if value.is_unit() => {}
+        Outcome::Finished(value) => formatter.print_output(&value.to_string())?,
+        Outcome::Exited(code) => std::process::exit(code),
+        Outcome::Interrupted => formatter.progress(&t!("eval-interrupted")),
+    }
+    Ok(())
+}
 
-                let ping_info = format!("Ping {}: Connected in {:.2}ms", i, elapsed.as_millis());
-                println!("{}", formatter.format_info(&ping_info));
-            }
-            Err(e) => {
-                let error_msg = format!("Ping {}: Failed - {}", i, e);
-                eprintln!("{}", formatter.format_error(&error_msg));
-            }
+/// Response parsers from the config file
+///
+/// Exits if a WebAssembly plugin can't be loaded; without the `wasm`
+/// feature, configured plugins are only warned about.
+fn load_parsers(config_file: &ConfigFile, formatter: &OutputFormatter) -> ExternalParsers {
+    let parsers = ExternalParsers::new(&config_file.parsers);
+    if config_file.plugins.is_empty() {
+        return parsers;
+    }
+    #[cfg(feature = "wasm")]
+    match PluginHost::load(&config_file.plugins) {
+        Ok(host) => parsers.with_wasm(Some(Arc::new(host))),
+        Err(e) => {
+            eprintln!("{}", formatter.format_rcon_error(&e));
+            std::process::exit(1);
         }
+    }
+    #[cfg(not(feature = "wasm"))]
+    {
+        formatter.warn(&t!("plugins-unsupported"));
+        parsers
+    }
+}
 
-        if i < count {
-            sleep(interval_duration).await;
+/// List the subcommands of the WebAssembly plugins
+#[cfg(feature = "wasm")]
+fn run_plugin_list(formatter: &OutputFormatter) -> Result<(), Box<dyn std::error::Error>> {
+    let rows: Vec<Vec<String>> = formatter
+        .parsers()
+        .wasm()
+        .into_iter()
+        .flat_map(|host| host.plugins())
+        .filter_map(|plugin| {
+            let (name, about) = plugin.subcommand()?;
+            Some(vec![
+                name.to_string(),
+                plugin.name().to_string(),
+                about.to_string(),
+            ])
+        })
+        .collect();
+    if rows.is_empty() {
+        formatter.info(&t!("plugins-none"));
+        return Ok(());
+    }
+    formatter.print_output(&formatter.format_records(&["Subcommand", "Plugin", "About"], &rows))?;
+    Ok(())
+}
+
+#[cfg(not(feature = "wasm"))]
+fn run_plugin_list(formatter: &OutputFormatter) -> Result<(), Box<dyn std::error::Error>> {
+    eprintln!("{}", formatter.format_error(&t!("plugins-unsupported")));
+    std::process::exit(1);
+}
+
+/// Send the commands a plugin's subcommand produces for `args`
+///
+/// The plugin runs before connecting, so invalid arguments fail fast.
+#[cfg(feature = "wasm")]
+async fn run_plugin_command(
+    config: &RconConfig,
+    name: &str,
+    args: &[String],
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let host = formatter.parsers().wasm();
+    let Some(plugin) = host.and_then(|host| host.subcommand(name)) else {
+        let available: Vec<&str> = host
+            .into_iter()
+            .flat_map(|host| host.plugins())
+            .filter_map(|plugin| plugin.subcommand().map(|(name, _)| name))
+            .collect();
+        exit_invalid_arguments(
+            formatter,
+            t!(
+                "plugin-unknown",
+                name = name,
+                available = available.join(", ")
+            ),
+        );
+    };
+    let commands = plugin.run_subcommand(args)?;
+
+    let mut client = connect_with_retry(config, formatter).await?;
+    for command in &commands {
+        formatter.print_expansion(command);
+        let response = client.execute_command_detailed(command).await?;
+        if !response.body.is_empty() {
+            formatter.print_parsed_response(command, &response).await?;
         }
     }
+    Ok(())
+}
 
-    // Print summary
-    let success_rate = (successful_pings as f64 / count as f64) * 100.0;
-    let avg_time = if successful_pings > 0 {
-        total_time.as_millis() as f64 / successful_pings as f64
-    } else {
-        0.0
+#[cfg(not(feature = "wasm"))]
+async fn run_plugin_command(
+    _config: &RconConfig,
+    _name: &str,
+    _args: &[String],
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    eprintln!("{}", formatter.format_error(&t!("plugins-unsupported")));
+    std::process::exit(1);
+}
+
+/// List configured profiles without connecting to any of them
+fn run_profiles_command(
+    config_file: &ConfigFile,
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let rows: Vec<Vec<String>> = config_file
+        .profiles
+        .iter()
+        .map(|(name, profile)| {
+            let dialect = profile.dialect.unwrap_or_default();
+            vec![
+                name.clone(),
+                match (&profile.address, &profile.panel) {
+                    (Some(address), _) => address.clone(),
+                    (None, Some(panel)) => format!("via {}", panel.url),
+                    (None, None) => DEFAULT_ADDRESS.to_string(),
+                },
+                dialect
+                    .to_possible_value()
+                    .map(|value| value.get_name().to_string())
+                    .unwrap_or_default(),
+                format!("{}s", profile.timeout.unwrap_or(DEFAULT_TIMEOUT_SECS)),
+                profile.bind.clone().unwrap_or_else(|| "-".to_string()),
+                profile.prefix.clone().unwrap_or_else(|| "-".to_string()),
+            ]
+        })
+        .collect();
+
+    formatter.print_output(&formatter.format_records(
+        &["Profile", "Address", "Dialect", "Timeout", "Bind", "Prefix"],
+        &rows,
+    ))?;
+
+    Ok(())
+}
+
+/// Print a translation catalog, or check one against the reference catalog
+fn run_messages_command(
+    locale: Option<&str>,
+    check: Option<&str>,
+    list: bool,
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let unknown_locale = |locale: &str| {
+        let available: Vec<_> = i18n::available_locales().collect();
+        ErrorReport::new(
+            "invalid_arguments",
+            t!(
+                "unknown-locale",
+                locale = locale,
+                available = available.join(", ")
+            ),
+        )
     };
 
-    let summary = format!(
-        "Summary: {}/{} successful ({:.1}%), average: {:.2}ms",
-        successful_pings, count, success_rate, avg_time
-    );
-    println!("{}", formatter.format_info(&summary));
+    if list {
+        let rows: Vec<Vec<String>> = i18n::available_locales()
+            .map(|locale| {
+                let missing = i18n::missing_messages(locale).unwrap_or_default();
+                vec![locale.to_string(), missing.len().to_string()]
+            })
+            .collect();
+        formatter.print_output(&formatter.format_records(&["Locale", "Missing"], &rows))?;
+        return Ok(());
+    }
+
+    if let Some(check) = check {
+        let missing = i18n::missing_messages(check).ok_or_else(|| unknown_locale(check))?;
+        if missing.is_empty() {
+            formatter.info(&t!("messages-complete", locale = check));
+            return Ok(());
+        }
+
+        formatter.progress(&t!(
+            "messages-missing",
+            count = missing.len(),
+            locale = check
+        ));
+        formatter.print_output(&missing.join("\n"))?;
+        std::process::exit(1);
+    }
+
+    let locale = locale.unwrap_or(i18n::DEFAULT_LOCALE);
+    let catalog = i18n::catalog(locale).ok_or_else(|| unknown_locale(locale))?;
+    formatter.print_output(catalog.trim_end())?;
 
     Ok(())
 }
 
-async fn run_info_command(
+/// Exit status of `wait` and `on-ready` when the server is crash looping
+const CRASH_LOOP_EXIT_CODE: i32 = 4;
+
+/// Exit status of `on-ready` when a `[critical]` line of the script failed
+const SCRIPT_ABORTED_EXIT_CODE: i32 = 5;
+
+async fn wait_until_ready(
     config: &RconConfig,
-    detailed: bool,
+    options: &WaitOptions,
     formatter: &OutputFormatter,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let mut client = connect_with_retry(config, formatter).await?;
+) -> Result<RconClient, Box<dyn std::error::Error>> {
+    let timeout = options.timeout.as_secs();
+    formatter.progress(&t!(
+        "wait-start",
+        seconds = timeout,
+        address = config.address.to_string()
+    ));
 
-    // Get basic server info
-    let commands = if detailed {
-        vec!["list", "version", "seed", "difficulty", "gamerule"]
-    } else {
-        vec!["list", "version"]
+    let result = wait_for_server(config, options, |attempt| {
+        let key = if attempt.accepted {
+            "wait-attempt-dropped"
+        } else {
+            "wait-attempt"
+        };
+        formatter.progress(&t!(
+            key,
+            attempt = attempt.attempt,
+            seconds = attempt.elapsed.as_secs(),
+            error = attempt.error.to_string()
+        ));
+        if attempt.crashed {
+            formatter.progress(&t!(
+                "wait-crashed",
+                crashes = attempt.crashes,
+                window = options.crash_window.as_secs()
+            ));
+        }
+    })
+    .await;
+
+    match result {
+        Ok(client) => {
+            formatter.info(&t!("wait-ready", address = config.address.to_string()));
+            Ok(client)
+        }
+        Err(RconError::Timeout) => {
+            let report = ErrorReport::new(
+                RconError::Timeout.code(),
+                t!("wait-timeout", seconds = timeout),
+            );
+            eprintln!("{}", formatter.format_failure(&report));
+            std::process::exit(1);
+        }
+        Err(e @ RconError::CrashLoop(_)) => {
+            let report = ErrorReport::from(&e).context(t!("wait-crash-loop"));
+            eprintln!("{}", formatter.format_failure(&report));
+            std::process::exit(CRASH_LOOP_EXIT_CODE);
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+async fn run_on_ready_command(
+    config: &RconConfig,
+    script: &Path,
+    wait: &WaitOptions,
+    continue_on_error: bool,
+    events: &EventBus,
+    formatter: &OutputFormatter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Load the script up front so a typo fails fast instead of after the wait
+    let lines = load_script(script)?;
+    let mut client = wait_until_ready(config, wait, formatter).await?;
+    events.emit(EventKind::Ready);
+    let finished = |failures| EventKind::ScriptFinished {
+        script: script.to_path_buf(),
+        total: lines.len(),
+        failures,
     };
+    let (mut succeeded, mut warnings, mut ignored, mut failures) = (0, 0, 0, 0);
+    let mut stopped = None;
 
-    for command in commands {
-        match client.execute_command(command).await {
+    for line in &lines {
+        formatter.print_expansion(&line.command);
+        let e = match client.execute_command_detailed(&line.command).await {
             Ok(response) => {
-                let section_header =
-                    formatter.format_info(&format!("=== {} ===", command.to_uppercase()));
-                println!("{}", section_header);
-
-                let formatted_response = formatter.format_response(&response);
-                println!("{}", formatted_response);
-                println!();
+                succeeded += 1;
+                if !response.body.is_empty() {
+                    formatter
+                        .print_parsed_response(&line.command, &response)
+                        .await?;
+                }
+                continue;
             }
-            Err(e) => {
-                let error_msg = format!("Failed to get {}: {}", command, e);
-                eprintln!("{}", formatter.format_error(&error_msg));
+            Err(e) => e,
+        };
+        let (line_number, command) = (line.line_number, line.command.as_str());
+        match line.severity {
+            Severity::IgnoreErrors => {
+                ignored += 1;
+                formatter.progress(&t!(
+                    "script-line-ignored",
+                    line = line_number,
+                    command = command,
+                    error = e.to_string()
+                ));
+            }
+            Severity::WarnOnly => {
+                warnings += 1;
+                formatter.warn(&t!(
+                    "script-line-warning",
+                    line = line_number,
+                    command = command,
+                    error = e.to_string()
+                ));
+            }
+            Severity::Normal | Severity::Critical => {
+                failures += 1;
+                let critical = line.severity == Severity::Critical;
+                let context = if critical {
+                    t!(
+                        "script-critical-failed",
+                        line = line_number,
+                        command = command
+                    )
+                } else {
+                    t!("script-line-failed", line = line_number, command = command)
+                };
+                eprintln!(
+                    "{}",
+                    formatter.format_failure(&ErrorReport::from(&e).context(context))
+                );
+                if critical {
+                    stopped = Some((line_number, SCRIPT_ABORTED_EXIT_CODE));
+                    break;
+                }
+                if !continue_on_error {
+                    stopped = Some((line_number, 1));
+                    break;
+                }
             }
         }
     }
 
+    let summary = match stopped {
+        Some((line, _)) => t!(
+            "script-stopped",
+            line = line,
+            succeeded = succeeded,
+            total = lines.len(),
+            warnings = warnings,
+            ignored = ignored
+        ),
+        None => t!(
+            "script-complete",
+            succeeded = succeeded,
+            total = lines.len(),
+            warnings = warnings,
+            ignored = ignored
+        ),
+    };
+    formatter.info(&summary);
+    events.emit(finished(failures));
+
+    let code = match stopped {
+        Some((_, code)) => code,
+        None if failures > 0 => 1,
+        None => 0,
+    };
+    if code != 0 {
+        events.close().await;
+        std::process::exit(code);
+    }
+
     Ok(())
 }
 
-async fn run_players_command(
-    config: &RconConfig,
-    _show_uuids: bool,
+struct PlaybookOptions {
+    concurrency: usize,
+    continue_on_error: bool,
+    fail_on_error: bool,
+}
+
+/// Run a playbook's steps, or print its execution plan with --dry-run
+async fn run_playbook_command(
+    cli: &Cli,
+    config_file: &ConfigFile,
+    path: &Path,
+    options: PlaybookOptions,
     formatter: &OutputFormatter,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let mut client = connect_with_retry(config, formatter).await?;
+    let playbook = Playbook::load(path)?;
+    let selected = cli
+        .profile
+        .clone()
+        .or_else(|| cli.address.clone())
+        .unwrap_or_else(|| "-".to_string());
+    let server = |step: &Step| step.profile.clone().unwrap_or_else(|| selected.clone());
 
-    match client.execute_command("list uuids").await {
-        Ok(response) => {
-            let formatted_response = formatter.format_response(&response);
-            println!("{}", formatted_response);
+    if cli.dry_run {
+        let stages = playbook.stages();
+        formatter.progress(&t!(
+            "playbook-plan",
+            steps = playbook.steps().len(),
+            stages = stages.len(),
+            concurrency = options.concurrency
+        ));
+        let rows: Vec<Vec<String>> = stages
+            .iter()
+            .enumerate()
+            .flat_map(|(stage, steps)| {
+                steps.iter().map(move |step| {
+                    vec![
+                        (stage + 1).to_string(),
+                        step.name.clone(),
+                        server(step),
+                        step.depends_on.join(", "),
+                        step.severity.to_string(),
+                        step.commands.join("; "),
+                    ]
+                })
+            })
+            .collect();
+        formatter.print_output(&formatter.format_records(
+            &["Stage", "Step", "Server", "After", "Severity", "Commands"],
+            &rows,
+        ))?;
+        return Ok(());
+    }
+
+    // Resolve every server up front, so a typo fails before any step runs;
+    // steps on the same server share its event bus, with or without profile
+    let mut servers = HashMap::new();
+    let mut buses = BTreeMap::new();
+    for step in playbook.steps() {
+        if servers.contains_key(&step.profile) {
+            continue;
         }
-        Err(_) => {
-            // Fallback to basic list command
-            match client.execute_command("list").await {
-                Ok(response) => {
-                    let formatted_response = formatter.format_response(&response);
-                    println!("{}", formatted_response);
-                }
-                Err(e) => {
-                    let error_msg = formatter.format_error(&e.to_string());
-                    eprintln!("{}", error_msg);
-                    std::process::exit(1);
-                }
+        let target = match &step.profile {
+            Some(name) => {
+                let profile = config_file.profile(name)?;
+                resolve_profile_target(cli, name, profile)
+                    .await
+                    .unwrap_or_else(|e| exit_invalid_arguments(formatter, e))
             }
+            None => {
+                let profile = complete_selected_profile(cli, config_file).await?;
+                cli.resolve_target(&profile)
+                    .unwrap_or_else(|e| exit_invalid_arguments(formatter, e))
+            }
+        };
+        let config = rcon_config(cli, &target, formatter).await?;
+        let config = if options.fail_on_error {
+            with_error_classifier(&config, config_file, &target, formatter)
+        } else {
+            config
+        };
+        let label = match &step.profile {
+            Some(name) => name.clone(),
+            None => target_label(cli, &target),
+        };
+        if !buses.contains_key(&label) {
+            let events = labelled_event_bus(cli, config_file, label.clone(), formatter)
+                .await
+                .start();
+            buses.insert(label.clone(), events);
         }
+        let hooks = config.hooks.clone().chain(buses[&label].client_hooks());
+        let config = config.with_hooks(hooks);
+        servers.insert(step.profile.clone(), (target.prefix, config, label));
+    }
+
+    let statuses = playbook
+        .run(options.concurrency, options.continue_on_error, |step| {
+            let (prefix, config, label) = &servers[&step.profile];
+            let events = &buses[label];
+            async move {
+                formatter.progress(&t!("playbook-step-start", step = step.name.as_str()));
+                let mut client = connect_with_retry(config, formatter).await?;
+                let result =
+                    run_playbook_step(&mut client, step, prefix.as_deref(), events, formatter)
+                        .await;
+                let _ = client.close().await;
+                result.map_err(Box::<dyn std::error::Error>::from)
+            }
+        })
+        .await;
+
+    let (mut succeeded, mut warnings, mut ignored, mut failed, mut skipped) = (0, 0, 0, 0, 0);
+    let mut critical = None;
+    let rows: Vec<Vec<String>> = playbook
+        .steps()
+        .iter()
+        .zip(&statuses)
+        .map(|(step, status)| {
+            let (status, response, error) = match status {
+                StepStatus::Succeeded(response) => {
+                    succeeded += 1;
+                    ("succeeded", response.clone(), String::new())
+                }
+                StepStatus::Warned(e) => {
+                    warnings += 1;
+                    ("warned", String::new(), e.to_string())
+                }
+                StepStatus::Ignored(e) => {
+                    ignored += 1;
+                    ("ignored", String::new(), e.to_string())
+                }
+                StepStatus::Failed(e) => {
+                    failed += 1;
+                    if step.severity == Severity::Critical {
+                        critical = Some(step.name.as_str());
+                    }
+                    ("failed", String::new(), e.to_string())
+                }
+                StepStatus::Skipped => {
+                    skipped += 1;
+                    ("skipped", String::new(), String::new())
+                }
+            };
+            vec![
+                step.name.clone(),
+                server(step),
+                status.to_string(),
+                response,
+                error,
+            ]
+        })
+        .collect();
+    formatter.print_output(
+        &formatter.format_records(&["Step", "Server", "Status", "Response", "Error"], &rows),
+    )?;
+    for (label, events) in &buses {
+        let on_server = || {
+            playbook
+                .steps()
+                .iter()
+                .zip(&statuses)
+                .filter(|(step, _)| servers[&step.profile].2 == *label)
+        };
+        events.emit(EventKind::PlaybookFinished {
+            playbook: path.to_path_buf(),
+            total: on_server().count(),
+            failures: on_server()
+                .filter(|(_, status)| matches!(status, StepStatus::Failed(_)))
+                .count(),
+            skipped: on_server()
+                .filter(|(_, status)| matches!(status, StepStatus::Skipped))
+                .count(),
+        });
+        events.close().await;
     }
+    formatter.progress(&t!(
+        "playbook-complete",
+        succeeded = succeeded,
+        total = rows.len(),
+        warnings = warnings,
+        ignored = ignored,
+        skipped = skipped
+    ));
 
+    if let Some(step) = critical {
+        let report = ErrorReport::new(
+            "command_failed",
+            t!("playbook-critical-failed", step = step),
+        );
+        eprintln!("{}", formatter.format_failure(&report));
+        std::process::exit(SCRIPT_ABORTED_EXIT_CODE);
+    }
+    if failed > 0 {
+        return Err(ErrorReport::new(
+            "command_failed",
+            t!("playbook-failed", failed = failed, total = rows.len()),
+        )
+        .into());
+    }
     Ok(())
 }
 
-async fn connect_with_retry(
-    config: &RconConfig,
+/// Send a playbook step's commands in order, returning their responses
+async fn run_playbook_step(
+    client: &mut RconClient,
+    step: &Step,
+    prefix: Option<&str>,
+    events: &EventBus,
     formatter: &OutputFormatter,
-) -> Result<RconClient, Box<dyn std::error::Error>> {
-    const MAX_RETRIES: u32 = 3;
-    const RETRY_DELAY: Duration = Duration::from_secs(1);
-
-    for attempt in 1..=MAX_RETRIES {
-        match RconClient::connect(config.clone()).await {
-            Ok(client) => {
-                if attempt > 1 {
-                    let success_msg = formatter.format_info("Connected successfully");
-                    eprintln!("{}", success_msg);
+) -> Result<String, RconError> {
+    let mut responses = Vec::new();
+    for command in &step.commands {
+        let command = match prefix {
+            Some(prefix) => with_prefix(prefix, command),
+            None => command.clone(),
+        };
+        formatter.print_expansion(&command);
+        match client.execute_command_detailed(&command).await {
+            Ok(response) => {
+                events.emit(EventKind::CommandExecuted {
+                    command: redact::command(&command),
+                    response: response.body.clone(),
+                });
+                if !response.body.is_empty() {
+                    responses.push(response.body);
                 }
-                return Ok(client);
             }
             Err(e) => {
-                if attempt < MAX_RETRIES {
-                    let retry_msg =
-                        format!("Connection attempt {} failed: {}. Retrying...", attempt, e);
-                    eprintln!("{}", formatter.format_error(&retry_msg));
-                    sleep(RETRY_DELAY).await;
-                } else {
-                    return Err(e.into());
-                }
+                events.emit(EventKind::command_failed(redact::command(&command), &e));
+                return Err(e);
             }
         }
     }
-
-    unreachable!()
+    Ok(responses.join("\n"))
 }
 
-async fn reconnect(
-    client: &mut RconClient,
+async fn connect_with_retry(
     config: &RconConfig,
-    _formatter: &OutputFormatter,
-) -> Result<(), Box<dyn std::error::Error>> {
-    *client = RconClient::connect(config.clone()).await?;
-    Ok(())
+    formatter: &OutputFormatter,
+) -> Result<RconClient, Box<dyn std::error::Error>> {
+    let mut retries = 0;
+    let result = RconClient::connect_with_retry(config.clone(), |e, retry, _delay| {
+        retries = retry;
+        if !formatter.is_quiet() {
+            let report = ErrorReport::from(e)
+                .with_retries(retry - 1)
+                .context(t!("connect-attempt-failed", attempt = retry))
+                .retrying();
+            eprintln!("{}", formatter.format_failure(&report));
+        }
+    })
+    .await;
+
+    match result {
+        Ok(client) => {
+            if retries > 0 {
+                formatter.progress(&t!("connected"));
+            }
+            Ok(client)
+        }
+        // Retrying can't fix a configuration error or a changed server
+        Err(e @ (RconError::InvalidConfig(_) | RconError::PinMismatch(_))) => Err(e.into()),
+        Err(e) => Err(ErrorReport::from(&e).with_retries(retries).into()),
+    }
 }
 
 async fn show_connection_status(client: &mut RconClient, formatter: &OutputFormatter) {
     let status = if client.is_connected().await {
-        "Connected"
+        t!("status-connected")
     } else {
-        "Disconnected"
+        t!("status-disconnected")
     };
 
-    let status_msg = format!(
-        "Connection status: {} ({})",
-        status,
-        client.server_address()
+    let status_msg = t!(
+        "connection-status",
+        status = status,
+        address = client.server_address().to_string()
     );
     println!("{}", formatter.format_info(&status_msg));
 }
 
 fn show_interactive_help(formatter: &OutputFormatter) {
-    let help_text = r#"
-Interactive Mode Commands:
-  help         Show this help message
-  status       Show connection status
-  reconnect    Reconnect to the server
-  quit/exit    Leave interactive mode
-
-Any other input will be sent as a command to the server.
-
-Common Minecraft commands:
-  list         Show online players
-  time set day Set time to day
-  weather clear Clear weather
-  gamemode creative <player>  Set player to creative mode
-  tp <player1> <player2>      Teleport player1 to player2
-"#;
-
-    println!("{}", formatter.format_info(help_text));
+    println!("{}", formatter.format_info(&t!("interactive-help")));
 }
@@ -0,0 +1,173 @@
+//! Maintenance windows and their audit log
+//!
+//! `maintenance start` turns the whitelist on, announces the window, and
+//! kicks every player who isn't kept online; operators may join past the
+//! whitelist, so they can keep working. `maintenance end` turns the
+//! whitelist off again, unless it was on before the window, and announces
+//! the end.
+//!
+//! Both are recorded in an audit log, JSON Lines with one entry per start
+//! and end, which `end` reads to restore the whitelist as it was.
+
+use crate::bulk::Template;
+use crate::error::{RconError, Result};
+use crate::state_file::StateFile;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Default audit log, `<data dir>/rcon-cli/maintenance.jsonl`
+pub fn default_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("rcon-cli").join("maintenance.jsonl"))
+}
+
+/// Placeholders of maintenance messages
+pub const PLACEHOLDERS: &[&str] = &["server", "eta", "duration"];
+
+/// Fill in a message's `{server}`, `{eta}`, and `{duration}`
+///
+/// Placeholders without a value render as empty.
+///
+/// ```
+/// use rcon_cli::maintenance::render_message;
+///
+/// let values = [("server", "survival"), ("duration", "12m 3s")];
+/// let message = render_message("{server} is back after {duration}", &values);
+/// assert_eq!(message.unwrap(), "survival is back after 12m 3s");
+/// assert!(render_message("Back at {time}", &[]).is_err());
+/// ```
+pub fn render_message(template: &str, values: &[(&str, &str)]) -> Result<String> {
+    let columns: Vec<String> = PLACEHOLDERS.iter().map(|name| name.to_string()).collect();
+    let template = Template::parse(template, &columns).map_err(|_| {
+        RconError::InvalidConfig(format!(
+            "Unknown placeholder in '{}' (available: {})",
+            template,
+            PLACEHOLDERS
+                .iter()
+                .map(|name| format!("{{{}}}", name))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))
+    })?;
+    let row: Vec<String> = PLACEHOLDERS
+        .iter()
+        .map(|name| {
+            values
+                .iter()
+                .find(|(placeholder, _)| placeholder == name)
+                .map_or_else(String::new, |(_, value)| value.to_string())
+        })
+        .collect();
+    Ok(template.render(&row))
+}
+
+/// One line of the audit log
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub at: DateTime<Utc>,
+    /// Profile name or address
+    pub server: String,
+    #[serde(flatten)]
+    pub event: WindowEvent,
+}
+
+/// The start or end of a window
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum WindowEvent {
+    Started {
+        message: String,
+        /// Whether the whitelist was on before, so `end` leaves it on
+        whitelist_was_on: bool,
+        kicked: Vec<String>,
+    },
+    Ended {
+        message: String,
+    },
+}
+
+/// A window that hasn't ended
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Window {
+    pub server: String,
+    pub started_at: DateTime<Utc>,
+    pub whitelist_was_on: bool,
+    pub kicked: Vec<String>,
+}
+
+/// An audit log file
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    file: StateFile,
+}
+
+impl AuditLog {
+    /// Create the file and its directory if needed
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        Ok(Self {
+            file: StateFile::create("Audit log", path)?,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        self.file.path()
+    }
+
+    /// Append an entry for `server`, returning once it is on disk
+    pub fn record(&self, server: &str, event: WindowEvent) -> Result<()> {
+        let _lock = self.file.lock()?;
+        self.file.append_line(&AuditEntry {
+            at: Utc::now(),
+            server: server.to_string(),
+            event,
+        })
+    }
+
+    /// Every entry, oldest first
+    pub fn entries(&self) -> Result<Vec<AuditEntry>> {
+        let _lock = self.file.lock()?;
+        self.file.read_lines()
+    }
+
+    /// The window open on `server`, if its last entry is a start
+    ///
+    /// ```
+    /// use rcon_cli::maintenance::{AuditLog, WindowEvent};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let path = std::env::temp_dir().join(format!("rcon-audit-{}.jsonl", std::process::id()));
+    /// let log = AuditLog::open(&path)?;
+    /// log.record("survival", WindowEvent::Started {
+    ///     message: "Back soon".to_string(),
+    ///     whitelist_was_on: false,
+    ///     kicked: vec!["Steve".to_string()],
+    /// })?;
+    /// assert_eq!(log.open_window("survival")?.unwrap().kicked, ["Steve"]);
+    ///
+    /// log.record("survival", WindowEvent::Ended { message: "Welcome back".to_string() })?;
+    /// assert!(log.open_window("survival")?.is_none());
+    /// # std::fs::remove_file(&path).ok();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn open_window(&self, server: &str) -> Result<Option<Window>> {
+        let entries = self.entries()?;
+        let last = entries
+            .into_iter()
+            .rev()
+            .find(|entry| entry.server == server);
+        Ok(last.and_then(|entry| match entry.event {
+            WindowEvent::Started {
+                whitelist_was_on,
+                kicked,
+                ..
+            } => Some(Window {
+                server: entry.server,
+                started_at: entry.at,
+                whitelist_was_on,
+                kicked,
+            }),
+            WindowEvent::Ended { .. } => None,
+        }))
+    }
+}
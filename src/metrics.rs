@@ -0,0 +1,393 @@
+//! InfluxDB line protocol output
+//!
+//! `--metrics-out` sends what ping, sessions, and alert measure to an
+//! InfluxDB `/write` endpoint, or prints it for Telegraf's `exec` and
+//! `execd` inputs, so TIG stacks can ingest RCON metrics without a
+//! Prometheus exporter. [`MetricsWriter`] writes the measured events of an
+//! [`EventBus`](crate::events::EventBus), see [`event_point`].
+
+use crate::alert::AlertTransition;
+use crate::error::{RconError, Result};
+use crate::events::{Event, EventKind, Subscriber, SubscriberFuture};
+use chrono::{DateTime, Utc};
+use reqwest::Url;
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Default port of the InfluxDB HTTP API
+pub const DEFAULT_INFLUX_PORT: u16 = 8086;
+
+/// How long a write may take before it is given up
+const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Where metrics are written
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MetricsTarget {
+    /// Line protocol on stdout
+    Stdout,
+    /// The InfluxDB 1.x `/write` endpoint, which InfluxDB 2 and 3 serve as
+    /// well
+    Influx(Url),
+}
+
+impl FromStr for MetricsTarget {
+    type Err = RconError;
+
+    /// Parse `-` or `influx://[user:password@]host[:port]/database`
+    ///
+    /// `influxs://` uses HTTPS. Query parameters such as `rp` are passed on
+    /// to the endpoint.
+    ///
+    /// ```
+    /// use rcon_cli::metrics::MetricsTarget;
+    ///
+    /// let target: MetricsTarget = "influx://metrics.local/minecraft?rp=week".parse().unwrap();
+    /// let MetricsTarget::Influx(url) = target else { unreachable!() };
+    /// assert_eq!(url.as_str(), "http://metrics.local:8086/write?db=minecraft&rp=week");
+    /// ```
+    fn from_str(value: &str) -> Result<Self> {
+        if value == "-" {
+            return Ok(Self::Stdout);
+        }
+        let invalid = |reason: &str| {
+            RconError::InvalidConfig(format!("invalid metrics target '{}': {}", value, reason))
+        };
+
+        let url = Url::parse(value).map_err(|e| invalid(&e.to_string()))?;
+        let scheme = match url.scheme() {
+            "influx" => "http",
+            "influxs" => "https",
+            _ => return Err(invalid("expected '-' or an influx:// URL")),
+        };
+        let host = url.host_str().ok_or_else(|| invalid("missing host"))?;
+        let database = url.path().trim_matches('/');
+        if database.is_empty() || database.contains('/') {
+            return Err(invalid(
+                "expected the database as the path, e.g. /minecraft",
+            ));
+        }
+
+        let mut write = Url::parse(&format!(
+            "{}://{}:{}/write",
+            scheme,
+            host,
+            url.port().unwrap_or(DEFAULT_INFLUX_PORT)
+        ))
+        .map_err(|e| invalid(&e.to_string()))?;
+        {
+            let mut query = write.query_pairs_mut();
+            query.append_pair("db", database);
+            query.extend_pairs(url.query_pairs());
+            if !url.username().is_empty() {
+                query.append_pair("u", url.username());
+            }
+            if let Some(password) = url.password() {
+                query.append_pair("p", password);
+            }
+        }
+        Ok(Self::Influx(write))
+    }
+}
+
+impl fmt::Display for MetricsTarget {
+    /// Shows where metrics go without any credentials
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Stdout => write!(f, "stdout"),
+            Self::Influx(url) => {
+                write!(f, "{}", url.host_str().unwrap_or_default())?;
+                if let Some(port) = url.port() {
+                    write!(f, ":{}", port)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Value of a field
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldValue {
+    Float(f64),
+    Integer(i64),
+    Boolean(bool),
+    String(String),
+}
+
+impl From<f64> for FieldValue {
+    fn from(value: f64) -> Self {
+        Self::Float(value)
+    }
+}
+
+impl From<i64> for FieldValue {
+    fn from(value: i64) -> Self {
+        Self::Integer(value)
+    }
+}
+
+impl From<u32> for FieldValue {
+    fn from(value: u32) -> Self {
+        Self::Integer(value.into())
+    }
+}
+
+impl From<bool> for FieldValue {
+    fn from(value: bool) -> Self {
+        Self::Boolean(value)
+    }
+}
+
+impl From<&str> for FieldValue {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_string())
+    }
+}
+
+impl From<String> for FieldValue {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+/// One line of line protocol
+///
+/// ```
+/// use chrono::DateTime;
+/// use rcon_cli::metrics::Point;
+///
+/// let point = Point::new("rcon_players")
+///     .tag("target", "lobby 1")
+///     .field("online", 3u32)
+///     .field("latency_ms", 0.5)
+///     .at(DateTime::from_timestamp(1_700_000_000, 0).unwrap());
+/// assert_eq!(
+///     point.to_string(),
+///     r"rcon_players,target=lobby\ 1 online=3i,latency_ms=0.5 1700000000000000000"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Point {
+    measurement: String,
+    tags: Vec<(String, String)>,
+    fields: Vec<(String, FieldValue)>,
+    timestamp: DateTime<Utc>,
+}
+
+impl Point {
+    /// A point taken now
+    pub fn new(measurement: impl Into<String>) -> Self {
+        Self {
+            measurement: measurement.into(),
+            tags: Vec::new(),
+            fields: Vec::new(),
+            timestamp: Utc::now(),
+        }
+    }
+
+    /// Add a tag; empty values are left out, as line protocol has no way to
+    /// write them
+    pub fn tag(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        let value = value.into();
+        if !value.is_empty() {
+            self.tags.push((key.into(), value));
+        }
+        self
+    }
+
+    /// Add a field; NaN and infinite floats are left out
+    pub fn field(mut self, key: impl Into<String>, value: impl Into<FieldValue>) -> Self {
+        let value = value.into();
+        if !matches!(value, FieldValue::Float(float) if !float.is_finite()) {
+            self.fields.push((key.into(), value));
+        }
+        self
+    }
+
+    pub fn at(mut self, timestamp: DateTime<Utc>) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+}
+
+/// Escape the characters line protocol gives a meaning in names
+fn escape(text: &str, special: &[char]) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if special.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const KEY: &[char] = &[',', '=', ' '];
+
+        write!(f, "{}", escape(&self.measurement, &[',', ' ']))?;
+        for (key, value) in &self.tags {
+            write!(f, ",{}={}", escape(key, KEY), escape(value, KEY))?;
+        }
+        for (index, (key, value)) in self.fields.iter().enumerate() {
+            let separator = if index == 0 { ' ' } else { ',' };
+            write!(f, "{}{}=", separator, escape(key, KEY))?;
+            match value {
+                FieldValue::Float(value) => write!(f, "{}", value)?,
+                FieldValue::Integer(value) => write!(f, "{}i", value)?,
+                FieldValue::Boolean(value) => write!(f, "{}", value)?,
+                FieldValue::String(value) => write!(f, "\"{}\"", escape(value, &['"', '\\']))?,
+            }
+        }
+        write!(
+            f,
+            " {}",
+            self.timestamp.timestamp_nanos_opt().unwrap_or_default()
+        )
+    }
+}
+
+/// Writes points to a [`MetricsTarget`]
+#[derive(Debug, Clone)]
+pub struct MetricsWriter {
+    target: MetricsTarget,
+    client: reqwest::Client,
+}
+
+impl MetricsWriter {
+    pub fn new(target: MetricsTarget) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(WRITE_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        Self { target, client }
+    }
+
+    pub fn target(&self) -> &MetricsTarget {
+        &self.target
+    }
+
+    /// Write points in one batch; points without fields are skipped
+    pub async fn write(&self, points: &[Point]) -> Result<()> {
+        let body: String = points
+            .iter()
+            .filter(|point| !point.fields.is_empty())
+            .map(|point| format!("{}\n", point))
+            .collect();
+        if body.is_empty() {
+            return Ok(());
+        }
+
+        match &self.target {
+            MetricsTarget::Stdout => {
+                let mut stdout = io::stdout().lock();
+                stdout.write_all(body.as_bytes())?;
+                stdout.flush()?;
+                Ok(())
+            }
+            MetricsTarget::Influx(url) => {
+                let write = async {
+                    self.client
+                        .post(url.clone())
+                        .body(body)
+                        .send()
+                        .await?
+                        .error_for_status()
+                };
+                write.await.map(|_| ()).map_err(|e| {
+                    // The cause, such as a refused connection, is only in
+                    // the source chain
+                    let e = e.without_url();
+                    let mut message = e.to_string();
+                    let mut source = std::error::Error::source(&e);
+                    while let Some(cause) = source {
+                        message = format!("{}: {}", message, cause);
+                        source = cause.source();
+                    }
+                    RconError::Metrics(format!("Write to {} failed: {}", self.target, message))
+                })
+            }
+        }
+    }
+}
+
+/// The point measuring an event, if it is measured
+///
+/// Pings are `rcon_ping`, polled player counts `rcon_players`, joins and
+/// leaves `rcon_session`, and alert transitions `rcon_alert`, each tagged
+/// with the server as `target`.
+///
+/// ```
+/// use rcon_cli::events::{Event, EventKind};
+/// use rcon_cli::metrics::event_point;
+/// use std::time::Duration;
+///
+/// let ping = EventKind::Pinged { latency: Some(Duration::from_millis(12)), error: None };
+/// let point = event_point(&Event::new("lobby", ping)).unwrap();
+/// assert!(point.to_string().starts_with("rcon_ping,target=lobby success=true,latency_ms=12 "));
+/// assert!(event_point(&Event::new("lobby", EventKind::Saved)).is_none());
+/// ```
+pub fn event_point(event: &Event) -> Option<Point> {
+    let point = |measurement: &str| {
+        Point::new(measurement)
+            .tag("target", event.server.as_str())
+            .at(event.timestamp)
+    };
+    match &event.kind {
+        EventKind::Pinged { latency, error } => {
+            let mut point = point("rcon_ping").field("success", latency.is_some());
+            if let Some(latency) = latency {
+                // Microsecond resolution, like the history file
+                let ms = (latency.as_secs_f64() * 1_000_000.0).round() / 1000.0;
+                point = point.field("latency_ms", ms);
+            }
+            if let Some(error) = error {
+                point = point.field("error", error.as_str());
+            }
+            Some(point)
+        }
+        EventKind::PlayersCounted(list) => Some(
+            point("rcon_players")
+                .field("online", list.online)
+                .field("max", list.max),
+        ),
+        EventKind::Session {
+            kind,
+            player,
+            duration,
+        } => {
+            let mut point = point("rcon_session")
+                .tag("player", player.as_str())
+                .field("event", kind.as_str());
+            if let Some(duration) = duration {
+                point = point.field("duration_secs", duration.as_secs() as i64);
+            }
+            Some(point)
+        }
+        EventKind::Alert {
+            transition,
+            condition,
+            ..
+        } => Some(
+            point("rcon_alert")
+                .field("condition", condition.as_str())
+                .field("triggered", *transition == AlertTransition::Triggered),
+        ),
+        _ => None,
+    }
+}
+
+impl Subscriber for MetricsWriter {
+    /// Writes the points of measured events
+    fn handle<'a>(&'a self, event: &'a Event) -> SubscriberFuture<'a> {
+        Box::pin(async move {
+            match event_point(event) {
+                Some(point) => self.write(&[point]).await,
+                None => Ok(()),
+            }
+        })
+    }
+}
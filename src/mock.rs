@@ -0,0 +1,152 @@
+//! Programmable stand-in for a client in unit tests
+//!
+//! [`MockRconClient`] implements [`RconExecutor`] without any socket, so
+//! code written against the trait can be tested with canned responses and
+//! errors and no server running. To exercise the wire protocol as well, use a
+//! [`MockServer`](crate::mock_server::MockServer) instead.
+
+use crate::error::{RconError, Result};
+use crate::executor::{ExecutorFuture, RconExecutor};
+use crate::mock_server::UNKNOWN_COMMAND;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+/// Handler for commands without a canned response
+type Handler = Box<dyn FnMut(&str) -> Result<String> + Send>;
+
+/// Client with programmable responses
+///
+/// Canned responses match commands exactly. A command queued with several
+/// responses gets them in turn, repeating the last one; anything else goes
+/// to the handler, which answers like a vanilla server to unknown commands
+/// by default.
+///
+/// ```
+/// use rcon_cli::mock::MockRconClient;
+/// use rcon_cli::{RconError, RconExecutor};
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let mut client = MockRconClient::new()
+///     .respond("list", "There are 0 of a max of 20 players online: ")
+///     .respond("list", "There are 1 of a max of 20 players online: Steve")
+///     .fail("stop", || RconError::Disconnected);
+///
+/// assert!(client.execute_command("list").await.unwrap().starts_with("There are 0"));
+/// assert!(client.execute_command("list").await.unwrap().ends_with("Steve"));
+/// assert!(client.execute_command("stop").await.is_err());
+/// assert_eq!(client.received(), ["list", "list", "stop"]);
+/// # }
+/// ```
+pub struct MockRconClient {
+    responses: HashMap<String, VecDeque<Response>>,
+    handler: Handler,
+    received: Vec<String>,
+}
+
+enum Response {
+    Text(String),
+    Error(Box<dyn Fn() -> RconError + Send>),
+}
+
+impl Response {
+    fn render(&self) -> Result<String> {
+        match self {
+            Self::Text(text) => Ok(text.clone()),
+            Self::Error(error) => Err(error()),
+        }
+    }
+}
+
+impl MockRconClient {
+    pub fn new() -> Self {
+        Self {
+            responses: HashMap::new(),
+            handler: Box::new(|_| Ok(UNKNOWN_COMMAND.to_string())),
+            received: Vec::new(),
+        }
+    }
+
+    /// Answer `command` with `response`, after any responses queued before
+    pub fn respond(mut self, command: impl Into<String>, response: impl Into<String>) -> Self {
+        self.queue(command.into(), Response::Text(response.into()));
+        self
+    }
+
+    /// Fail `command` with the error `error` builds, after any responses
+    /// queued before
+    pub fn fail<F>(mut self, command: impl Into<String>, error: F) -> Self
+    where
+        F: Fn() -> RconError + Send + 'static,
+    {
+        self.queue(command.into(), Response::Error(Box::new(error)));
+        self
+    }
+
+    /// Answer commands without a canned response with `handler`
+    pub fn respond_with<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut(&str) -> Result<String> + Send + 'static,
+    {
+        self.handler = Box::new(handler);
+        self
+    }
+
+    /// Commands executed so far, in order
+    pub fn received(&self) -> &[String] {
+        &self.received
+    }
+
+    fn queue(&mut self, command: String, response: Response) {
+        self.responses
+            .entry(command)
+            .or_default()
+            .push_back(response);
+    }
+
+    fn answer(&mut self, command: &str) -> Result<String> {
+        self.received.push(command.to_string());
+
+        let Some(queue) = self.responses.get_mut(command) else {
+            return (self.handler)(command);
+        };
+        // The last response repeats
+        if queue.len() > 1 {
+            if let Some(response) = queue.pop_front() {
+                return response.render();
+            }
+        }
+        match queue.front() {
+            Some(response) => response.render(),
+            None => (self.handler)(command),
+        }
+    }
+}
+
+impl Default for MockRconClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for MockRconClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MockRconClient")
+            .field("commands", &self.responses.keys().collect::<Vec<_>>())
+            .field("received", &self.received)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RconExecutor for MockRconClient {
+    fn execute_command<'a>(&'a mut self, command: &'a str) -> ExecutorFuture<'a, String> {
+        let result = self.answer(command);
+        Box::pin(async move { result })
+    }
+
+    /// Executes `list` like a real client does
+    fn ping(&mut self) -> ExecutorFuture<'_, ()> {
+        let result = self.answer("list").map(|_| ());
+        Box::pin(async move { result })
+    }
+}
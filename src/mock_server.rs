@@ -0,0 +1,234 @@
+//! In-process RCON server for integration tests
+//!
+//! Speaks the Minecraft dialect on a local port with canned responses, and
+//! records every command it receives so tests can check what was sent.
+//! See [`assertions::rcon_test`](crate::assertions::rcon_test) for the usual
+//! way to use it.
+
+use crate::client::RconConfig;
+use crate::protocol::{
+    packet_type, validate_packet_length, RconPacket, MAX_PACKET_LENGTH, MAX_RESPONSE_PAYLOAD_SIZE,
+};
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+/// What vanilla servers answer to commands they don't know
+pub const UNKNOWN_COMMAND: &str = "Unknown or incomplete command, see below for error";
+
+/// Request ID of a rejected login, as sent by the server
+const AUTH_FAILED_ID: i32 = -1;
+
+/// Canned responses of a mock server
+///
+/// ```
+/// use rcon_cli::mock_server::MockServer;
+///
+/// # #[tokio::main]
+/// # async fn main() -> rcon_cli::Result<()> {
+/// let server = MockServer::new("secret")
+///     .respond("list", "There are 0 of a max of 20 players online: ")
+///     .start()
+///     .await?;
+///
+/// let mut client = rcon_cli::RconClient::connect(server.config()).await?;
+/// assert!(client.execute_command("list").await?.starts_with("There are 0"));
+/// assert_eq!(server.received(), ["list"]);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct MockServer {
+    password: String,
+    responses: HashMap<String, String>,
+    garbled: HashSet<String>,
+    fallback: String,
+}
+
+impl MockServer {
+    /// A server accepting `password` that knows no commands yet
+    pub fn new(password: impl Into<String>) -> Self {
+        Self {
+            password: password.into(),
+            responses: HashMap::new(),
+            garbled: HashSet::new(),
+            fallback: UNKNOWN_COMMAND.to_string(),
+        }
+    }
+
+    /// Answer `command` (matched exactly) with `response`
+    pub fn respond(mut self, command: impl Into<String>, response: impl Into<String>) -> Self {
+        self.responses.insert(command.into(), response.into());
+        self
+    }
+
+    /// Answer `command` with a packet whose length prefix is out of range
+    ///
+    /// The rest of the packet follows as usual, so a client that keeps
+    /// reading the connection takes it for the next packet's length.
+    pub fn garble(mut self, command: impl Into<String>) -> Self {
+        self.garbled.insert(command.into());
+        self
+    }
+
+    /// Answer every other command with `response` [default: vanilla's
+    /// unknown command message]
+    pub fn fallback(mut self, response: impl Into<String>) -> Self {
+        self.fallback = response.into();
+        self
+    }
+
+    /// Listen on a free local port
+    ///
+    /// The server runs until the returned handle is dropped.
+    pub async fn start(self) -> io::Result<MockServerHandle> {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await?;
+        let address = listener.local_addr()?;
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let password = self.password.clone();
+
+        let server = Arc::new(self);
+        let log = received.clone();
+        let task = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(serve(stream, server.clone(), log.clone()));
+            }
+        });
+
+        Ok(MockServerHandle {
+            address,
+            password,
+            received,
+            task,
+        })
+    }
+
+    fn response(&self, command: &str) -> &str {
+        self.responses.get(command).unwrap_or(&self.fallback)
+    }
+}
+
+/// A running mock server, stopped when dropped
+pub struct MockServerHandle {
+    address: SocketAddr,
+    password: String,
+    received: Arc<Mutex<Vec<String>>>,
+    task: JoinHandle<()>,
+}
+
+impl MockServerHandle {
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Client configuration for connecting to this server
+    pub fn config(&self) -> RconConfig {
+        RconConfig::new(self.address, self.password.clone())
+    }
+
+    /// Commands received so far, in order, across all connections
+    pub fn received(&self) -> Vec<String> {
+        self.received
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+}
+
+impl Drop for MockServerHandle {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Handle one client connection until it closes or misbehaves
+async fn serve(mut stream: TcpStream, server: Arc<MockServer>, received: Arc<Mutex<Vec<String>>>) {
+    let mut authenticated = false;
+
+    while let Ok(packet) = read_packet(&mut stream).await {
+        let replies = match packet.packet_type {
+            packet_type::AUTH => {
+                authenticated = packet.payload == server.password;
+                let request_id = if authenticated {
+                    packet.request_id
+                } else {
+                    AUTH_FAILED_ID
+                };
+                vec![encode(request_id, packet_type::EXECCOMMAND, b"")]
+            }
+            // Vanilla servers drop unauthenticated connections
+            _ if !authenticated => return,
+            packet_type::EXECCOMMAND => {
+                let response = server.response(&packet.payload).as_bytes();
+                let replies = if server.garbled.contains(&packet.payload) {
+                    vec![garbled(packet.request_id, response)]
+                } else {
+                    fragments(packet.request_id, response)
+                };
+                received
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .push(packet.payload);
+                replies
+            }
+            // Echo anything else, which serves as a terminator for clients
+            // that mirror packets
+            _ => vec![encode(packet.request_id, packet_type::RESPONSE_VALUE, b"")],
+        };
+
+        for reply in replies {
+            if stream.write_all(&reply).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+async fn read_packet(stream: &mut TcpStream) -> crate::Result<RconPacket> {
+    let mut length = [0u8; 4];
+    stream.read_exact(&mut length).await?;
+    let packet_length = validate_packet_length(i32::from_le_bytes(length))?;
+
+    let mut data = vec![0u8; packet_length + 4];
+    data[..4].copy_from_slice(&length);
+    stream.read_exact(&mut data[4..]).await?;
+    RconPacket::from_bytes(&data)
+}
+
+/// Split a response into packets of at most 4096 payload bytes
+///
+/// A response filling its last packet exactly is followed by an empty one,
+/// so clients relying on short fragments see where it ends.
+fn fragments(request_id: i32, response: &[u8]) -> Vec<Vec<u8>> {
+    let mut packets: Vec<Vec<u8>> = response
+        .chunks(MAX_RESPONSE_PAYLOAD_SIZE)
+        .map(|chunk| encode(request_id, packet_type::RESPONSE_VALUE, chunk))
+        .collect();
+    if response.len().is_multiple_of(MAX_RESPONSE_PAYLOAD_SIZE) {
+        packets.push(encode(request_id, packet_type::RESPONSE_VALUE, b""));
+    }
+    packets
+}
+
+/// Encode a response packet with a length prefix no client accepts
+fn garbled(request_id: i32, response: &[u8]) -> Vec<u8> {
+    let mut packet = encode(request_id, packet_type::RESPONSE_VALUE, response);
+    packet[..4].copy_from_slice(&(MAX_PACKET_LENGTH as i32 + 1).to_le_bytes());
+    packet
+}
+
+/// Encode a server packet, which may exceed the client request size limit
+fn encode(request_id: i32, packet_type: i32, payload: &[u8]) -> Vec<u8> {
+    let length = (4 + 4 + payload.len() + 2) as i32;
+    let mut packet = Vec::with_capacity(4 + length as usize);
+    packet.extend_from_slice(&length.to_le_bytes());
+    packet.extend_from_slice(&request_id.to_le_bytes());
+    packet.extend_from_slice(&packet_type.to_le_bytes());
+    packet.extend_from_slice(payload);
+    packet.extend_from_slice(&[0, 0]);
+    packet
+}
@@ -0,0 +1,374 @@
+//! MQTT publishing of events and metrics
+//!
+//! With an `[mqtt]` section in the config file, the events of a command's
+//! [`EventBus`](crate::events::EventBus) are published as JSON, for home
+//! automation and IoT style dashboards:
+//!
+//! ```toml
+//! [mqtt]
+//! broker = "broker.local:8883"
+//! topic_prefix = "minecraft"
+//! tls = true
+//! username = "rcon"
+//! password = "secret"
+//! ```
+//!
+//! Messages go to `<prefix>/<server>/<topic>`, where the server is the
+//! profile name or address; see [`Topic`].
+
+use crate::error::{RconError, Result};
+use crate::events::{Event, EventKind, Subscriber, SubscriberFuture};
+use crate::net::split_host_port;
+use rumqttc::{
+    AsyncClient, Event as MqttEvent, MqttOptions, Outgoing, Packet, QoS, TlsConfiguration,
+    Transport,
+};
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::CertificateDer;
+use rustls::{ClientConfig, RootCertStore};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, timeout};
+use tracing::warn;
+
+/// Broker port without TLS
+pub const DEFAULT_PORT: u16 = 1883;
+
+/// Broker port with TLS
+pub const DEFAULT_TLS_PORT: u16 = 8883;
+
+/// How long connecting to the broker may take
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long queued messages get to go out when closing
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Pause before reconnecting after losing the broker
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// Messages that can be waiting to go out
+const QUEUE_CAPACITY: usize = 64;
+
+/// The `[mqtt]` section of the config file
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MqttConfig {
+    /// Broker as `host` or `host:port` [default port: 1883, or 8883 with
+    /// TLS]
+    pub broker: String,
+    /// First level of every topic
+    #[serde(default = "default_topic_prefix")]
+    pub topic_prefix: String,
+    /// Connect with TLS, verifying the broker against the public roots or
+    /// `ca_file`
+    #[serde(default)]
+    pub tls: bool,
+    /// PEM file with the certificates to trust instead of the public roots,
+    /// e.g. for a broker with a self-signed certificate
+    pub ca_file: Option<PathBuf>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// [default: `rcon-cli-<process id>`]
+    pub client_id: Option<String>,
+}
+
+fn default_topic_prefix() -> String {
+    "rcon-cli".to_string()
+}
+
+/// What a message is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Topic {
+    /// Player count and names, retained so dashboards show the last count
+    /// right away
+    Players,
+    /// Joins, leaves, alert transitions, connection changes, saves, script
+    /// and playbook runs, and stops
+    Events,
+    /// Commands executed and their responses or errors
+    Commands,
+}
+
+impl Topic {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Topic::Players => "players",
+            Topic::Events => "events",
+            Topic::Commands => "commands",
+        }
+    }
+
+    fn retained(self) -> bool {
+        self == Topic::Players
+    }
+}
+
+fn mqtt_error(message: impl Into<String>) -> RconError {
+    RconError::Mqtt(message.into())
+}
+
+/// TLS settings trusting `ca_file`, or the public roots without one
+fn tls_config(config: &MqttConfig) -> Result<ClientConfig> {
+    let mut roots = RootCertStore::empty();
+    match &config.ca_file {
+        Some(path) => {
+            let invalid = |e: &dyn std::fmt::Display| {
+                mqtt_error(format!("Invalid CA file '{}': {}", path.display(), e))
+            };
+            for certificate in CertificateDer::pem_file_iter(path).map_err(|e| invalid(&e))? {
+                roots
+                    .add(certificate.map_err(|e| invalid(&e))?)
+                    .map_err(|e| invalid(&e))?;
+            }
+        }
+        None => roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned()),
+    }
+
+    Ok(
+        ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .map_err(|e| mqtt_error(e.to_string()))?
+            .with_root_certificates(roots)
+            .with_no_client_auth(),
+    )
+}
+
+/// A broker connection publishing on behalf of one server
+///
+/// Messages are queued and sent in the background, which also reconnects
+/// after losing the broker. Call [`close`](Self::close) before exiting so
+/// queued messages go out.
+#[derive(Debug)]
+pub struct MqttPublisher {
+    client: AsyncClient,
+    /// `<prefix>/<server>`
+    base: String,
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl MqttPublisher {
+    /// Connect to the broker, publishing under the topics of `server`
+    pub async fn connect(config: &MqttConfig, server: &str) -> Result<Self> {
+        let default_port = if config.tls {
+            DEFAULT_TLS_PORT
+        } else {
+            DEFAULT_PORT
+        };
+        let (host, port) = split_host_port(&config.broker, default_port)?;
+        let client_id = config
+            .client_id
+            .clone()
+            .unwrap_or_else(|| format!("rcon-cli-{}", std::process::id()));
+
+        let mut options = MqttOptions::new(client_id, host, port);
+        options.set_keep_alive(Duration::from_secs(30));
+        if let Some(username) = &config.username {
+            options.set_credentials(username, config.password.as_deref().unwrap_or_default());
+        }
+        if config.tls {
+            options.set_transport(Transport::tls_with_config(TlsConfiguration::Rustls(
+                Arc::new(tls_config(config)?),
+            )));
+        }
+
+        let (client, mut eventloop) = AsyncClient::new(options, QUEUE_CAPACITY);
+        let connected = timeout(CONNECT_TIMEOUT, async {
+            loop {
+                if let MqttEvent::Incoming(Packet::ConnAck(_)) = eventloop.poll().await? {
+                    return Ok::<_, rumqttc::ConnectionError>(());
+                }
+            }
+        })
+        .await;
+        let failure = match connected {
+            Ok(Ok(())) => None,
+            Ok(Err(e)) => Some(e.to_string()),
+            Err(_) => Some("timed out".to_string()),
+        };
+        if let Some(failure) = failure {
+            return Err(mqtt_error(format!(
+                "Connecting to {} failed: {}",
+                config.broker, failure
+            )));
+        }
+
+        let broker = config.broker.clone();
+        let task = tokio::spawn(async move {
+            loop {
+                match eventloop.poll().await {
+                    Ok(MqttEvent::Outgoing(Outgoing::Disconnect)) => break,
+                    Ok(_) => {}
+                    Err(e) => {
+                        warn!("MQTT connection to {} lost: {}", broker, e);
+                        sleep(RECONNECT_DELAY).await;
+                    }
+                }
+            }
+        });
+
+        // Wildcards and separators in the name would change the topic's
+        // structure
+        let server = server.replace(['/', '+', '#'], "_");
+        Ok(Self {
+            client,
+            base: format!("{}/{}", config.topic_prefix.trim_end_matches('/'), server),
+            task: Mutex::new(Some(task)),
+        })
+    }
+
+    /// Full name of a topic, e.g. `rcon-cli/survival/players`
+    pub fn topic(&self, topic: Topic) -> String {
+        format!("{}/{}", self.base, topic.as_str())
+    }
+
+    /// Queue a JSON message
+    pub async fn publish(&self, topic: Topic, payload: &Value) -> Result<()> {
+        self.client
+            .publish(
+                self.topic(topic),
+                QoS::AtMostOnce,
+                topic.retained(),
+                payload.to_string(),
+            )
+            .await
+            .map_err(|e| mqtt_error(e.to_string()))
+    }
+
+    /// Send queued messages and disconnect
+    pub async fn close(&self) {
+        let task = self.task.lock().unwrap_or_else(|e| e.into_inner()).take();
+        let Some(task) = task else {
+            return;
+        };
+        if self.client.disconnect().await.is_ok() {
+            let _ = timeout(CLOSE_TIMEOUT, task).await;
+        } else {
+            task.abort();
+        }
+    }
+}
+
+/// The message announcing an event, if it is published
+///
+/// ```
+/// use rcon_cli::events::{Event, EventKind};
+/// use rcon_cli::mqtt::{event_message, Topic};
+///
+/// let saved = Event::new("survival", EventKind::Saved);
+/// let (topic, payload) = event_message(&saved).unwrap();
+/// assert_eq!(topic, Topic::Events);
+/// assert_eq!(payload["event"], "saved");
+/// ```
+pub fn event_message(event: &Event) -> Option<(Topic, Value)> {
+    let timestamp = event.timestamp.to_rfc3339();
+    let (topic, mut payload) = match &event.kind {
+        EventKind::PlayersCounted(list) => {
+            let players: Vec<&str> = list
+                .players
+                .iter()
+                .map(|player| player.name.as_str())
+                .collect();
+            let payload = json!({
+                "online": list.online,
+                "max": list.max,
+                "players": players,
+            });
+            (Topic::Players, payload)
+        }
+        EventKind::CommandExecuted { command, response } => {
+            let payload = json!({ "command": command, "response": response });
+            (Topic::Commands, payload)
+        }
+        EventKind::CommandFailed {
+            command,
+            code,
+            message,
+        } => {
+            let payload = json!({ "command": command, "error": message, "code": code });
+            (Topic::Commands, payload)
+        }
+        EventKind::Pinged { .. } => return None,
+        EventKind::Session {
+            player, duration, ..
+        } => {
+            let mut payload = json!({ "player": player });
+            if let Some(duration) = duration {
+                payload["duration_secs"] = duration.as_secs().into();
+            }
+            (Topic::Events, payload)
+        }
+        EventKind::Alert {
+            condition,
+            online,
+            max,
+            ..
+        } => {
+            let payload = json!({ "condition": condition, "players": online, "max": max });
+            (Topic::Events, payload)
+        }
+        EventKind::ScriptFinished {
+            script,
+            total,
+            failures,
+        } => {
+            let payload = json!({
+                "script": script.display().to_string(),
+                "total": total,
+                "failures": failures,
+            });
+            (Topic::Events, payload)
+        }
+        EventKind::PlaybookFinished {
+            playbook,
+            total,
+            failures,
+            skipped,
+        } => {
+            let payload = json!({
+                "playbook": playbook.display().to_string(),
+                "total": total,
+                "failures": failures,
+                "skipped": skipped,
+            });
+            (Topic::Events, payload)
+        }
+        EventKind::Stopped { idle } => (Topic::Events, json!({ "idle_secs": idle.as_secs() })),
+        EventKind::Restarting { waited, online } => (
+            Topic::Events,
+            json!({ "waited_secs": waited.as_secs(), "online": online }),
+        ),
+        EventKind::Maintenance { message, .. } => (Topic::Events, json!({ "message": message })),
+        EventKind::Connected | EventKind::Disconnected | EventKind::Saved | EventKind::Ready => {
+            (Topic::Events, json!({}))
+        }
+    };
+    if topic == Topic::Events {
+        payload["event"] = event.kind.name().into();
+    }
+    payload["timestamp"] = timestamp.into();
+    Some((topic, payload))
+}
+
+impl Subscriber for MqttPublisher {
+    /// Publishes events under this publisher's server, whichever server
+    /// they name
+    fn handle<'a>(&'a self, event: &'a Event) -> SubscriberFuture<'a> {
+        Box::pin(async move {
+            match event_message(event) {
+                Some((topic, payload)) => self.publish(topic, &payload).await,
+                None => Ok(()),
+            }
+        })
+    }
+
+    fn close(&self) -> SubscriberFuture<'_> {
+        Box::pin(async move {
+            MqttPublisher::close(self).await;
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,641 @@
+//! Many logical clients sharing one RCON connection
+//!
+//! Many RCON plugins allow a single connection at a time, so bridges like
+//! the [daemon](crate::daemon) and the [HTTP endpoints](crate::web) send
+//! every client's commands over one connection. A [`Multiplexer`] queues
+//! them per client and takes turns round-robin, so a client sending a burst
+//! of commands delays the others by at most one command each. An optional
+//! [`RateLimit`] rejects a client's commands beyond its budget with
+//! [`RconError::RateLimited`] instead of queuing them.
+//!
+//! Each command also has a [`Priority`]. Waiting commands of a higher
+//! priority go first, so a health check or `stop` isn't stuck behind
+//! thousands of low priority whitelist entries; clients take turns within
+//! each priority.
+//!
+//! A command may also have a deadline, usually the caller's own timeout.
+//! It fails with [`RconError::Timeout`] once the deadline passes, whether
+//! it's still waiting or already sent, so commands of callers that gave up
+//! don't pile up. A command abandoned after it was sent, by its deadline or
+//! by its caller dropping the future, may still run on the server; its
+//! response would arrive late, so the connection is re-dialed first.
+//!
+//! With a maximum queue length, a command arriving while that many wait
+//! fails right away with [`RconError::QueueFull`], bounding the backlog
+//! under load. [`Multiplexer::stats`] reports the queue's depth, wait times,
+//! and rejections, which are also recorded through the
+//! [`metrics`](https://docs.rs/metrics) facade for whichever recorder the
+//! application installs:
+//!
+//! | Metric | Kind | Labels |
+//! |---|---|---|
+//! | `rcon_queue_depth` | gauge | `server` |
+//! | `rcon_queue_wait_seconds` | histogram | `server` |
+//! | `rcon_commands_sent_total` | counter | `server` |
+//! | `rcon_commands_rejected_total` | counter | `server`, `reason` (`rate_limited`, `queue_full`, `timeout`) |
+
+use crate::cli::{format_duration, parse_duration};
+use crate::client::CommandResponse;
+use crate::error::{RconError, Result};
+use crate::lazy::LazyRconClient;
+use clap::ValueEnum;
+use metrics::{counter, gauge, histogram};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{oneshot, Mutex};
+use tokio::time::timeout_at;
+use tracing::debug;
+
+/// Commands a client may send per period, e.g. `20/s` or `100/5m`
+///
+/// A client may use the whole budget at once; it refills evenly over the
+/// period.
+///
+/// ```
+/// use rcon_cli::mux::RateLimit;
+/// use std::time::Duration;
+///
+/// let limit: RateLimit = "100/5m".parse().unwrap();
+/// assert_eq!(limit, RateLimit::new(100, Duration::from_secs(300)));
+/// assert_eq!("20/s".parse::<RateLimit>().unwrap().to_string(), "20/1s");
+/// assert!("0/s".parse::<RateLimit>().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub commands: u32,
+    pub per: Duration,
+}
+
+impl RateLimit {
+    pub fn new(commands: u32, per: Duration) -> Self {
+        Self { commands, per }
+    }
+}
+
+impl FromStr for RateLimit {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        let invalid = || format!("Invalid rate limit '{}' (expected e.g. 20/s)", value);
+        let (commands, per) = value.trim().split_once('/').ok_or_else(invalid)?;
+        let commands: u32 = commands.trim().parse().map_err(|_| invalid())?;
+        if commands == 0 {
+            return Err("A rate limit must allow at least 1 command".to_string());
+        }
+        // A bare unit means one of it
+        let per = per.trim();
+        let per = if per.starts_with(|c: char| c.is_ascii_digit()) {
+            parse_duration(per)?
+        } else {
+            parse_duration(&format!("1{}", per))?
+        };
+        Ok(Self::new(commands, per))
+    }
+}
+
+impl fmt::Display for RateLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.commands, format_duration(self.per))
+    }
+}
+
+/// How urgently a command is sent when others are waiting
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    ValueEnum,
+    Serialize,
+    Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    /// Ahead of everything else, e.g. health checks
+    High,
+    #[default]
+    Normal,
+    /// Only when nothing else is waiting, e.g. bulk imports
+    Low,
+}
+
+impl Priority {
+    /// Priority of a command unless one is given: commands stopping the
+    /// server are high, everything else normal
+    ///
+    /// ```
+    /// use rcon_cli::mux::Priority;
+    ///
+    /// assert_eq!(Priority::of("stop"), Priority::High);
+    /// assert_eq!(Priority::of("/END"), Priority::High);
+    /// assert_eq!(Priority::of("whitelist add Steve"), Priority::Normal);
+    /// ```
+    pub fn of(command: &str) -> Self {
+        let name = command
+            .trim_start()
+            .trim_start_matches('/')
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match name.as_str() {
+            // `end` stops BungeeCord and Velocity proxies
+            "stop" | "restart" | "end" | "shutdown" => Priority::High,
+            _ => Priority::Normal,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Priority::High => "high",
+            Priority::Normal => "normal",
+            Priority::Low => "low",
+        }
+    }
+
+    fn lane(self) -> usize {
+        self as usize
+    }
+}
+
+/// Commands left in a client's budget
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Waiting commands of one priority
+#[derive(Default)]
+struct Lane {
+    /// Waiting commands per client, oldest first
+    queues: HashMap<String, VecDeque<oneshot::Sender<()>>>,
+    /// Clients with waiting commands, in the order of their next turns
+    turns: VecDeque<String>,
+}
+
+impl Lane {
+    fn push(&mut self, client: &str, sender: oneshot::Sender<()>) {
+        match self.queues.get_mut(client) {
+            Some(queue) => queue.push_back(sender),
+            None => {
+                self.queues
+                    .insert(client.to_string(), VecDeque::from([sender]));
+                self.turns.push_back(client.to_string());
+            }
+        }
+    }
+
+    /// Hand the turn to the next waiting command, returning false if none
+    /// is left
+    fn hand_over(&mut self) -> bool {
+        while let Some(client) = self.turns.pop_front() {
+            let Some(queue) = self.queues.get_mut(&client) else {
+                continue;
+            };
+            let sender = queue.pop_front();
+            if queue.is_empty() {
+                self.queues.remove(&client);
+            } else {
+                self.turns.push_back(client);
+            }
+            // Commands whose callers gave up are skipped
+            if sender.is_some_and(|sender| sender.send(()).is_ok()) {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// Whose turn it is on the connection
+#[derive(Default)]
+struct Schedule {
+    /// A command is being sent, or the turn was handed to a waiting one
+    busy: bool,
+    /// Waiting commands by priority, highest first
+    lanes: [Lane; 3],
+    buckets: HashMap<String, Bucket>,
+    stats: QueueStats,
+}
+
+/// Why a command was turned away
+#[derive(Debug, Clone, Copy)]
+enum Rejection {
+    RateLimited,
+    QueueFull,
+    Timeout,
+}
+
+impl Rejection {
+    fn as_str(self) -> &'static str {
+        match self {
+            Rejection::RateLimited => "rate_limited",
+            Rejection::QueueFull => "queue_full",
+            Rejection::Timeout => "timeout",
+        }
+    }
+}
+
+/// The queue of a [`Multiplexer`] and what happened to its commands
+///
+/// Counts are totals since the multiplexer was created.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueueStats {
+    /// Commands waiting for their turn
+    pub queued: usize,
+    /// Commands sent to the server, whether or not they succeeded
+    pub sent: u64,
+    /// Commands rejected by the rate limit
+    pub rate_limited: u64,
+    /// Commands rejected because the queue was full
+    pub queue_full: u64,
+    /// Commands whose deadline passed before they were answered
+    pub timed_out: u64,
+    /// Time sent commands waited for their turn, in microseconds
+    pub total_wait_us: u64,
+    /// Longest time a command waited for its turn, in microseconds
+    pub max_wait_us: u64,
+}
+
+impl QueueStats {
+    /// Mean time sent commands waited for their turn
+    ///
+    /// ```
+    /// use rcon_cli::mux::QueueStats;
+    /// use std::time::Duration;
+    ///
+    /// let stats = QueueStats { sent: 4, total_wait_us: 2_000, ..Default::default() };
+    /// assert_eq!(stats.average_wait(), Duration::from_micros(500));
+    /// assert_eq!(QueueStats::default().average_wait(), Duration::ZERO);
+    /// ```
+    pub fn average_wait(&self) -> Duration {
+        match self.sent {
+            0 => Duration::ZERO,
+            sent => Duration::from_micros(self.total_wait_us / sent),
+        }
+    }
+}
+
+/// One connection shared fairly by many clients
+///
+/// Cloning is cheap and shares the connection.
+///
+/// ```
+/// use rcon_cli::lazy::LazyRconClient;
+/// use rcon_cli::mux::{Multiplexer, RateLimit};
+/// use rcon_cli::{RconConfig, RconError};
+/// use std::time::Duration;
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let config = RconConfig::new("127.0.0.1:1".parse().unwrap(), "secret");
+/// let mux = Multiplexer::new(LazyRconClient::new(config))
+///     .with_rate_limit(Some(RateLimit::new(1, Duration::from_secs(60))));
+///
+/// // The first command uses up the budget, whether or not it succeeds
+/// assert!(mux.execute_command("panel", "list").await.is_err());
+/// let limited = mux.execute_command("panel", "list").await;
+/// assert!(matches!(limited, Err(RconError::RateLimited(_))));
+/// // Other clients have budgets of their own
+/// assert!(!matches!(mux.execute_command("bot", "list").await, Err(RconError::RateLimited(_))));
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Multiplexer {
+    client: Arc<Mutex<LazyRconClient>>,
+    schedule: Arc<StdMutex<Schedule>>,
+    /// A command was abandoned after it was sent
+    abandoned: Arc<AtomicBool>,
+    rate_limit: Option<RateLimit>,
+    max_queue: Option<usize>,
+    address: SocketAddr,
+}
+
+impl Multiplexer {
+    pub fn new(client: LazyRconClient) -> Self {
+        Self {
+            address: client.config().address,
+            client: Arc::new(Mutex::new(client)),
+            schedule: Arc::new(StdMutex::new(Schedule::default())),
+            abandoned: Arc::new(AtomicBool::new(false)),
+            rate_limit: None,
+            max_queue: None,
+        }
+    }
+
+    /// Limit the commands of each client
+    pub fn with_rate_limit(mut self, rate_limit: Option<RateLimit>) -> Self {
+        self.rate_limit = rate_limit;
+        self
+    }
+
+    /// Reject commands arriving while `max_queue` others wait
+    pub fn with_max_queue(mut self, max_queue: Option<usize>) -> Self {
+        self.max_queue = max_queue;
+        self
+    }
+
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// The queue's current depth and totals so far
+    pub fn stats(&self) -> QueueStats {
+        self.schedule
+            .lock()
+            .expect("schedule lock poisoned")
+            .stats
+            .clone()
+    }
+
+    /// Execute a command for `client` once it's the client's turn, at the
+    /// command's default [`Priority`]
+    pub async fn execute_command(&self, client: &str, command: &str) -> Result<String> {
+        self.execute_command_detailed(client, command)
+            .await
+            .map(|response| response.body)
+    }
+
+    /// Execute a command for `client` and report how the response was
+    /// received
+    pub async fn execute_command_detailed(
+        &self,
+        client: &str,
+        command: &str,
+    ) -> Result<CommandResponse> {
+        self.execute_with_priority(client, command, Priority::of(command))
+            .await
+    }
+
+    /// Execute a command for `client` ahead of or after others by
+    /// `priority`
+    pub async fn execute_with_priority(
+        &self,
+        client: &str,
+        command: &str,
+        priority: Priority,
+    ) -> Result<CommandResponse> {
+        self.execute_with_deadline(client, command, priority, None)
+            .await
+    }
+
+    /// Execute a command for `client`, giving up once `deadline` passes
+    ///
+    /// ```
+    /// use rcon_cli::lazy::LazyRconClient;
+    /// use rcon_cli::mux::{Multiplexer, Priority};
+    /// use rcon_cli::{RconConfig, RconError};
+    /// use std::time::{Duration, Instant};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let config = RconConfig::new("127.0.0.1:1".parse().unwrap(), "secret");
+    /// let mux = Multiplexer::new(LazyRconClient::new(config));
+    ///
+    /// // A deadline already past fails without waiting or sending
+    /// let past = Instant::now() - Duration::from_secs(1);
+    /// let result = mux.execute_with_deadline("panel", "list", Priority::Normal, Some(past)).await;
+    /// assert!(matches!(result, Err(RconError::Timeout)));
+    /// # }
+    /// ```
+    pub async fn execute_with_deadline(
+        &self,
+        client: &str,
+        command: &str,
+        priority: Priority,
+        deadline: Option<Instant>,
+    ) -> Result<CommandResponse> {
+        if deadline.is_some_and(|deadline| deadline <= Instant::now()) {
+            return Err(self.time_out());
+        }
+        self.admit(client)?;
+        let execution = async {
+            let _turn = self.turn(client, priority).await?;
+            let mut connection = self.client.lock().await;
+            if self.abandoned.swap(false, Ordering::SeqCst) {
+                if let Err(e) = connection.close().await {
+                    debug!("Failed to close an abandoned connection: {}", e);
+                }
+            }
+            let mut sending = Sending {
+                abandoned: &self.abandoned,
+                done: false,
+            };
+            let result = connection.execute_command_detailed(command).await;
+            sending.done = true;
+            result
+        };
+        match deadline {
+            Some(deadline) => timeout_at(deadline.into(), execution)
+                .await
+                .unwrap_or_else(|_| Err(self.time_out())),
+            None => execution.await,
+        }
+    }
+
+    /// Connect now instead of on the first command
+    pub async fn connect(&self) -> Result<()> {
+        self.client.lock().await.client().await.map(|_| ())
+    }
+
+    /// Close the connection; the next command re-dials
+    pub async fn close(&self) -> Result<()> {
+        self.client.lock().await.close().await
+    }
+
+    /// Take a command from `client`'s budget
+    fn admit(&self, client: &str) -> Result<()> {
+        let Some(limit) = self.rate_limit else {
+            return Ok(());
+        };
+        let now = Instant::now();
+        let capacity = f64::from(limit.commands);
+        let refill = |bucket: &Bucket| {
+            let refilled = now.duration_since(bucket.updated).as_secs_f64()
+                / limit.per.as_secs_f64()
+                * capacity;
+            (bucket.tokens + refilled).min(capacity)
+        };
+
+        let mut schedule = self.schedule.lock().expect("schedule lock poisoned");
+        let schedule = &mut *schedule;
+        // Full buckets are the same as none
+        schedule
+            .buckets
+            .retain(|name, bucket| name == client || refill(bucket) < capacity);
+        let bucket = schedule
+            .buckets
+            .entry(client.to_string())
+            .or_insert(Bucket {
+                tokens: capacity,
+                updated: now,
+            });
+        bucket.tokens = refill(bucket);
+        bucket.updated = now;
+        if bucket.tokens < 1.0 {
+            self.count_rejection(&mut schedule.stats, Rejection::RateLimited);
+            return Err(RconError::RateLimited(format!(
+                "'{}' exceeded its limit of {}",
+                client, limit
+            )));
+        }
+        bucket.tokens -= 1.0;
+        Ok(())
+    }
+
+    /// Wait until the connection is free and it's `client`'s turn
+    async fn turn(&self, client: &str, priority: Priority) -> Result<Turn<'_>> {
+        let queued = Instant::now();
+        let receiver = {
+            let mut schedule = self.schedule.lock().expect("schedule lock poisoned");
+            if !schedule.busy {
+                schedule.busy = true;
+                self.record_wait(&mut schedule.stats, Duration::ZERO);
+                return Ok(Turn { mux: self });
+            }
+            if let Some(max_queue) = self.max_queue {
+                if schedule.stats.queued >= max_queue {
+                    self.count_rejection(&mut schedule.stats, Rejection::QueueFull);
+                    return Err(RconError::QueueFull(format!(
+                        "{} already has {} waiting",
+                        self.address, schedule.stats.queued
+                    )));
+                }
+            }
+            let (sender, receiver) = oneshot::channel();
+            schedule.lanes[priority.lane()].push(client, sender);
+            schedule.stats.queued += 1;
+            self.record_depth(&schedule.stats);
+            receiver
+        };
+
+        let mut waiting = Waiting {
+            receiver: Some(receiver),
+            mux: self,
+        };
+        if let Some(receiver) = waiting.receiver.as_mut() {
+            // The schedule outlives every waiting command, so the turn is
+            // always handed over before the sender is dropped
+            let _ = receiver.await;
+        }
+        waiting.receiver = None;
+        let mut schedule = self.schedule.lock().expect("schedule lock poisoned");
+        self.record_wait(&mut schedule.stats, queued.elapsed());
+        Ok(Turn { mux: self })
+    }
+
+    /// Hand the connection to the next command by priority and turn, or
+    /// mark it free
+    fn release(&self) {
+        let mut schedule = self.schedule.lock().expect("schedule lock poisoned");
+        if schedule.lanes.iter_mut().any(Lane::hand_over) {
+            schedule.stats.queued -= 1;
+            self.record_depth(&schedule.stats);
+        } else {
+            schedule.busy = false;
+        }
+    }
+
+    /// Forget a waiting command whose caller gave up
+    fn withdraw(&self) {
+        let mut schedule = self.schedule.lock().expect("schedule lock poisoned");
+        schedule.stats.queued -= 1;
+        self.record_depth(&schedule.stats);
+    }
+
+    /// Count a command given its turn after waiting for `waited`
+    fn record_wait(&self, stats: &mut QueueStats, waited: Duration) {
+        let waited_us = waited.as_micros() as u64;
+        stats.sent += 1;
+        stats.total_wait_us += waited_us;
+        stats.max_wait_us = stats.max_wait_us.max(waited_us);
+        let server = self.address.to_string();
+        histogram!("rcon_queue_wait_seconds", "server" => server.clone())
+            .record(waited.as_secs_f64());
+        counter!("rcon_commands_sent_total", "server" => server).increment(1);
+    }
+
+    fn record_depth(&self, stats: &QueueStats) {
+        gauge!("rcon_queue_depth", "server" => self.address.to_string()).set(stats.queued as f64);
+    }
+
+    fn count_rejection(&self, stats: &mut QueueStats, rejection: Rejection) {
+        match rejection {
+            Rejection::RateLimited => stats.rate_limited += 1,
+            Rejection::QueueFull => stats.queue_full += 1,
+            Rejection::Timeout => stats.timed_out += 1,
+        }
+        counter!(
+            "rcon_commands_rejected_total",
+            "server" => self.address.to_string(),
+            "reason" => rejection.as_str()
+        )
+        .increment(1);
+    }
+
+    /// Count a command whose deadline passed
+    fn time_out(&self) -> RconError {
+        let mut schedule = self.schedule.lock().expect("schedule lock poisoned");
+        self.count_rejection(&mut schedule.stats, Rejection::Timeout);
+        RconError::Timeout
+    }
+}
+
+/// The connection, held until dropped
+struct Turn<'a> {
+    mux: &'a Multiplexer,
+}
+
+impl Drop for Turn<'_> {
+    fn drop(&mut self) {
+        self.mux.release();
+    }
+}
+
+/// A command being sent, marking the connection for re-dialing if it's
+/// dropped before the response arrived
+struct Sending<'a> {
+    abandoned: &'a AtomicBool,
+    done: bool,
+}
+
+impl Drop for Sending<'_> {
+    fn drop(&mut self) {
+        if !self.done {
+            self.abandoned.store(true, Ordering::SeqCst);
+        }
+    }
+}
+
+/// A command waiting for its turn
+///
+/// If the caller gives up right after the turn was handed to it, the turn
+/// is passed on instead of being lost.
+struct Waiting<'a> {
+    receiver: Option<oneshot::Receiver<()>>,
+    mux: &'a Multiplexer,
+}
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        if let Some(mut receiver) = self.receiver.take() {
+            receiver.close();
+            if receiver.try_recv().is_ok() {
+                self.mux.release();
+            } else {
+                self.mux.withdraw();
+            }
+        }
+    }
+}
@@ -0,0 +1,615 @@
+//! NBT values and their text form, SNBT
+//!
+//! Commands like `data merge` take NBT as SNBT, and `data get` answers with
+//! it. A [`Tag`] is parsed from SNBT with [`Tag::parse`], built from JSON
+//! with [`Tag::from_json`], and displays as SNBT again.
+
+use crate::arguments::{resource_location, validate_entity, Position};
+use crate::error::{RconError, Result};
+use serde_json::Value;
+use std::fmt;
+
+/// An NBT value
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tag {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    String(String),
+    /// Elements are all of one type
+    List(Vec<Tag>),
+    /// Fields in their original order
+    Compound(Vec<(String, Tag)>),
+    ByteArray(Vec<i8>),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+fn nbt_error(message: impl Into<String>) -> RconError {
+    RconError::Nbt(message.into())
+}
+
+impl Tag {
+    /// Name of the tag's type, as in error messages
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Tag::Byte(_) => "byte",
+            Tag::Short(_) => "short",
+            Tag::Int(_) => "int",
+            Tag::Long(_) => "long",
+            Tag::Float(_) => "float",
+            Tag::Double(_) => "double",
+            Tag::String(_) => "string",
+            Tag::List(_) => "list",
+            Tag::Compound(_) => "compound",
+            Tag::ByteArray(_) => "byte array",
+            Tag::IntArray(_) => "int array",
+            Tag::LongArray(_) => "long array",
+        }
+    }
+
+    /// A field of a compound
+    pub fn get(&self, key: &str) -> Option<&Tag> {
+        match self {
+            Tag::Compound(fields) => fields
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    /// The value of a numeric tag
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Tag::Byte(value) => Some(value.into()),
+            Tag::Short(value) => Some(value.into()),
+            Tag::Int(value) => Some(value.into()),
+            Tag::Long(value) => Some(value as f64),
+            Tag::Float(value) => Some(value.into()),
+            Tag::Double(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Tag::String(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    /// Convert JSON, which has no byte, short, or float types
+    ///
+    /// Booleans become bytes, whole numbers ints or longs, and other
+    /// numbers doubles. A list's numbers share the widest of their types;
+    /// lists mixing other types and `null` have no NBT form.
+    ///
+    /// ```
+    /// use rcon_cli::nbt::Tag;
+    /// use serde_json::json;
+    ///
+    /// let tag = Tag::from_json(&json!({"NoAI": true, "Motion": [0, 0.5, 0]})).unwrap();
+    /// assert_eq!(tag.to_string(), "{Motion:[0.0d,0.5d,0.0d],NoAI:1b}");
+    /// assert!(Tag::from_json(&json!({"Tags": ["a", 1]})).is_err());
+    /// ```
+    pub fn from_json(value: &Value) -> Result<Self> {
+        from_json(value, "")
+    }
+
+    /// Parse SNBT as printed by `data get` or accepted by commands
+    ///
+    /// ```
+    /// use rcon_cli::nbt::Tag;
+    ///
+    /// let tag = Tag::parse(r#"{Health:20.0f,Tags:["vip"],id:"minecraft:zombie"}"#).unwrap();
+    /// assert_eq!(tag.get("Health"), Some(&Tag::Float(20.0)));
+    /// assert_eq!(tag.get("id").and_then(Tag::as_str), Some("minecraft:zombie"));
+    /// assert!(Tag::parse("{Health:20.0f").is_err());
+    /// ```
+    pub fn parse(snbt: &str) -> Result<Self> {
+        let mut parser = Parser {
+            source: snbt,
+            position: 0,
+        };
+        let tag = parser.value()?;
+        parser.skip_whitespace();
+        if parser.position < snbt.len() {
+            return Err(parser.error("trailing characters"));
+        }
+        Ok(tag)
+    }
+}
+
+/// Numeric types a JSON list's numbers can share, narrowest first
+fn widen(a: &Tag, b: &Tag) -> Option<u8> {
+    let rank = |tag: &Tag| match tag {
+        Tag::Byte(_) => Some(0),
+        Tag::Int(_) => Some(1),
+        Tag::Long(_) => Some(2),
+        Tag::Double(_) => Some(3),
+        _ => None,
+    };
+    Some(rank(a)?.max(rank(b)?))
+}
+
+fn from_json(value: &Value, path: &str) -> Result<Tag> {
+    let at = |path: &str| {
+        if path.is_empty() {
+            String::new()
+        } else {
+            format!(" at '{}'", path)
+        }
+    };
+    Ok(match value {
+        Value::Null => return Err(nbt_error(format!("null has no NBT form{}", at(path)))),
+        Value::Bool(flag) => Tag::Byte(i8::from(*flag)),
+        Value::Number(number) => match (number.as_i64(), number.as_f64()) {
+            (Some(whole), _) => match i32::try_from(whole) {
+                Ok(int) => Tag::Int(int),
+                Err(_) => Tag::Long(whole),
+            },
+            (None, Some(float)) if number.is_f64() => Tag::Double(float),
+            _ => return Err(nbt_error(format!("{} is out of range{}", number, at(path)))),
+        },
+        Value::String(text) => Tag::String(text.clone()),
+        Value::Array(values) => {
+            let elements = values
+                .iter()
+                .enumerate()
+                .map(|(index, value)| from_json(value, &format!("{}[{}]", path, index)))
+                .collect::<Result<Vec<_>>>()?;
+            let mut rank = None;
+            for pair in elements.windows(2) {
+                let (a, b) = (&pair[0], &pair[1]);
+                if std::mem::discriminant(a) == std::mem::discriminant(b) {
+                    continue;
+                }
+                rank = Some(rank.max(widen(a, b)).ok_or_else(|| {
+                    nbt_error(format!(
+                        "a list can't mix {} and {} values{}",
+                        a.type_name(),
+                        b.type_name(),
+                        at(path)
+                    ))
+                })?);
+            }
+            let elements = match rank {
+                None => elements,
+                Some(rank) => elements
+                    .iter()
+                    .map(|element| {
+                        let value = element.as_f64().unwrap_or_default();
+                        match (rank, element) {
+                            (3, _) => Tag::Double(value),
+                            (2, Tag::Long(long)) => Tag::Long(*long),
+                            (2, _) => Tag::Long(value as i64),
+                            (_, _) => Tag::Int(value as i32),
+                        }
+                    })
+                    .collect(),
+            };
+            Tag::List(elements)
+        }
+        Value::Object(fields) => Tag::Compound(
+            fields
+                .iter()
+                .map(|(key, value)| {
+                    let path = if path.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{}.{}", path, key)
+                    };
+                    Ok((key.clone(), from_json(value, &path)?))
+                })
+                .collect::<Result<_>>()?,
+        ),
+    })
+}
+
+/// Characters of unquoted strings and keys
+fn is_unquoted(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "_-.+".contains(c)
+}
+
+fn write_quoted(f: &mut fmt::Formatter<'_>, text: &str) -> fmt::Result {
+    write!(f, "\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn write_elements<T>(
+    f: &mut fmt::Formatter<'_>,
+    prefix: &str,
+    elements: &[T],
+    write: impl Fn(&mut fmt::Formatter<'_>, &T) -> fmt::Result,
+) -> fmt::Result {
+    write!(f, "[{}", prefix)?;
+    for (index, element) in elements.iter().enumerate() {
+        if index > 0 {
+            f.write_str(",")?;
+        }
+        write(f, element)?;
+    }
+    f.write_str("]")
+}
+
+/// Doubles and floats keep a decimal point, as `1.0` rather than `1`
+fn decimal(value: f64) -> String {
+    let text = value.to_string();
+    if text.contains(['.', 'e', 'N', 'i']) {
+        text
+    } else {
+        format!("{}.0", text)
+    }
+}
+
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Tag::Byte(value) => write!(f, "{}b", value),
+            Tag::Short(value) => write!(f, "{}s", value),
+            Tag::Int(value) => write!(f, "{}", value),
+            Tag::Long(value) => write!(f, "{}L", value),
+            Tag::Float(value) => write!(f, "{}f", decimal((*value).into())),
+            Tag::Double(value) => write!(f, "{}d", decimal(*value)),
+            Tag::String(text) => write_quoted(f, text),
+            Tag::List(elements) => write_elements(f, "", elements, |f, tag| write!(f, "{}", tag)),
+            Tag::Compound(fields) => {
+                f.write_str("{")?;
+                for (index, (key, value)) in fields.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(",")?;
+                    }
+                    if !key.is_empty() && key.chars().all(is_unquoted) {
+                        f.write_str(key)?;
+                    } else {
+                        write_quoted(f, key)?;
+                    }
+                    write!(f, ":{}", value)?;
+                }
+                f.write_str("}")
+            }
+            Tag::ByteArray(values) => write_elements(f, "B;", values, |f, v| write!(f, "{}b", v)),
+            Tag::IntArray(values) => write_elements(f, "I;", values, |f, v| write!(f, "{}", v)),
+            Tag::LongArray(values) => write_elements(f, "L;", values, |f, v| write!(f, "{}L", v)),
+        }
+    }
+}
+
+/// Recursive descent over SNBT
+struct Parser<'a> {
+    source: &'a str,
+    /// Byte offset of the next character
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> RconError {
+        nbt_error(format!("{} at position {}", message, self.position))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.source[self.position..].chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.source[self.position..];
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    /// Consume `expected` after optional whitespace
+    fn expect(&mut self, expected: char) -> Result<()> {
+        self.skip_whitespace();
+        if self.peek() != Some(expected) {
+            return Err(self.error(&format!("expected '{}'", expected)));
+        }
+        self.position += expected.len_utf8();
+        Ok(())
+    }
+
+    /// Consume `c` after optional whitespace, if it comes next
+    fn accept(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        let accepted = self.peek() == Some(c);
+        if accepted {
+            self.position += c.len_utf8();
+        }
+        accepted
+    }
+
+    fn value(&mut self) -> Result<Tag> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') => self.compound(),
+            Some('[') => self.list(),
+            Some('"' | '\'') => Ok(Tag::String(self.quoted()?)),
+            Some(_) => {
+                let token = self.unquoted()?;
+                Ok(literal(token))
+            }
+            None => Err(self.error("expected a value")),
+        }
+    }
+
+    fn compound(&mut self) -> Result<Tag> {
+        self.expect('{')?;
+        let mut fields = Vec::new();
+        if self.accept('}') {
+            return Ok(Tag::Compound(fields));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = match self.peek() {
+                Some('"' | '\'') => self.quoted()?,
+                _ => self.unquoted()?.to_string(),
+            };
+            self.expect(':')?;
+            fields.push((key, self.value()?));
+            if self.accept('}') {
+                return Ok(Tag::Compound(fields));
+            }
+            self.expect(',')?;
+        }
+    }
+
+    fn list(&mut self) -> Result<Tag> {
+        self.expect('[')?;
+        let rest = &self.source[self.position..];
+        let array = ["B;", "I;", "L;"]
+            .into_iter()
+            .find(|prefix| rest.starts_with(prefix));
+        if let Some(prefix) = array {
+            self.position += prefix.len();
+        }
+
+        let mut elements = Vec::new();
+        if !self.accept(']') {
+            loop {
+                elements.push(self.value()?);
+                if self.accept(']') {
+                    break;
+                }
+                self.expect(',')?;
+            }
+        }
+
+        let mismatch = |tag: &Tag| {
+            nbt_error(format!(
+                "unexpected {} in a {}",
+                tag.type_name(),
+                match array {
+                    Some("B;") => "byte array",
+                    Some("I;") => "int array",
+                    Some(_) => "long array",
+                    None => "list",
+                }
+            ))
+        };
+        match array {
+            Some("B;") => elements
+                .iter()
+                .map(|tag| match tag {
+                    Tag::Byte(value) => Ok(*value),
+                    tag => Err(mismatch(tag)),
+                })
+                .collect::<Result<_>>()
+                .map(Tag::ByteArray),
+            Some("I;") => elements
+                .iter()
+                .map(|tag| match tag {
+                    Tag::Int(value) => Ok(*value),
+                    tag => Err(mismatch(tag)),
+                })
+                .collect::<Result<_>>()
+                .map(Tag::IntArray),
+            Some(_) => elements
+                .iter()
+                .map(|tag| match tag {
+                    Tag::Long(value) => Ok(*value),
+                    tag => Err(mismatch(tag)),
+                })
+                .collect::<Result<_>>()
+                .map(Tag::LongArray),
+            None => {
+                if let Some(other) = elements.iter().find(|tag| {
+                    std::mem::discriminant(*tag) != std::mem::discriminant(&elements[0])
+                }) {
+                    return Err(mismatch(other));
+                }
+                Ok(Tag::List(elements))
+            }
+        }
+    }
+
+    fn quoted(&mut self) -> Result<String> {
+        let quote = self.peek().unwrap_or('"');
+        self.position += 1;
+        let mut text = String::new();
+        let mut chars = self.source[self.position..].char_indices();
+        while let Some((offset, c)) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some((_, escaped)) => text.push(escaped),
+                    None => break,
+                },
+                c if c == quote => {
+                    self.position += offset + 1;
+                    return Ok(text);
+                }
+                c => text.push(c),
+            }
+        }
+        self.position = self.source.len();
+        Err(self.error("unterminated string"))
+    }
+
+    fn unquoted(&mut self) -> Result<&str> {
+        let rest = &self.source[self.position..];
+        let length = rest.find(|c| !is_unquoted(c)).unwrap_or(rest.len());
+        if length == 0 {
+            return Err(self.error("expected a value"));
+        }
+        self.position += length;
+        Ok(&rest[..length])
+    }
+}
+
+/// A number, boolean, or unquoted string
+fn literal(token: &str) -> Tag {
+    let lowercase = token.to_ascii_lowercase();
+    let (number, suffix) = match lowercase.char_indices().last() {
+        Some((index, suffix @ ('b' | 's' | 'l' | 'f' | 'd'))) => (&token[..index], Some(suffix)),
+        _ => (token, None),
+    };
+    let numeric = !number.is_empty()
+        && number
+            .trim_start_matches(['-', '+'])
+            .starts_with(|c: char| c.is_ascii_digit() || c == '.');
+    let tag = match (numeric, suffix) {
+        (false, _) => None,
+        (true, Some('b')) => number.parse().ok().map(Tag::Byte),
+        (true, Some('s')) => number.parse().ok().map(Tag::Short),
+        (true, Some('l')) => number.parse().ok().map(Tag::Long),
+        (true, Some('f')) => number.parse().ok().map(Tag::Float),
+        (true, Some(_)) => number.parse().ok().map(Tag::Double),
+        (true, None) if number.contains(['.', 'e', 'E']) => number.parse().ok().map(Tag::Double),
+        (true, None) => number.parse().ok().map(Tag::Int),
+    };
+    tag.unwrap_or_else(|| match lowercase.as_str() {
+        "true" => Tag::Byte(1),
+        "false" => Tag::Byte(0),
+        _ => Tag::String(token.to_string()),
+    })
+}
+
+/// What `data` commands change
+#[derive(Debug, Clone, PartialEq)]
+pub enum DataTarget {
+    Entity(String),
+    Block(Position),
+    /// Command storage, by resource location
+    Storage(String),
+}
+
+impl DataTarget {
+    /// Parse `entity <target>`, `block <x> <y> <z>`, or `storage <id>`
+    ///
+    /// ```
+    /// use rcon_cli::nbt::DataTarget;
+    ///
+    /// let args = |values: &[&str]| values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+    /// let block = DataTarget::parse(&args(&["block", "10", "64", "-3"])).unwrap();
+    /// assert_eq!(block.to_string(), "block 10 64 -3");
+    /// assert!(DataTarget::parse(&args(&["entity", "@e[type=zombie", "x"])).is_err());
+    /// ```
+    pub fn parse(values: &[String]) -> std::result::Result<Self, String> {
+        let words: Vec<&str> = values.iter().map(String::as_str).collect();
+        match words.as_slice() {
+            ["entity", target] => {
+                validate_entity(target)?;
+                Ok(DataTarget::Entity(target.to_string()))
+            }
+            ["block", x, y, z] => Position::parse(x, y, z).map(DataTarget::Block),
+            ["storage", id] => resource_location(id).map(DataTarget::Storage),
+            _ => Err("Expected entity <TARGET>, block <X> <Y> <Z>, or storage <ID>".to_string()),
+        }
+    }
+}
+
+impl fmt::Display for DataTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataTarget::Entity(target) => write!(f, "entity {}", target),
+            DataTarget::Block(position) => write!(f, "block {}", position),
+            DataTarget::Storage(id) => write!(f, "storage {}", id),
+        }
+    }
+}
+
+/// A field of a compound
+type Field = (String, Tag);
+
+/// `data merge` commands merging a compound into a target, each at most
+/// `max_length` bytes long
+///
+/// A compound too long for one command is split across several by its
+/// fields; since merging is recursive for compounds, nested compounds are
+/// split too. Other values, like lists, replace what was there and can't be
+/// split.
+///
+/// ```
+/// use rcon_cli::nbt::{merge_commands, DataTarget, Tag};
+///
+/// let target = DataTarget::Storage("minecraft:notes".to_string());
+/// let data = Tag::parse(r#"{a:"first",b:{c:"second",d:"third"}}"#).unwrap();
+/// let commands = merge_commands(&target, &data, 52).unwrap();
+/// assert_eq!(commands, [
+///     r#"data merge storage minecraft:notes {a:"first"}"#,
+///     r#"data merge storage minecraft:notes {b:{c:"second"}}"#,
+///     r#"data merge storage minecraft:notes {b:{d:"third"}}"#,
+/// ]);
+/// ```
+pub fn merge_commands(target: &DataTarget, data: &Tag, max_length: usize) -> Result<Vec<String>> {
+    let Tag::Compound(fields) = data else {
+        return Err(nbt_error(format!(
+            "data merge takes a compound, not a {}",
+            data.type_name()
+        )));
+    };
+    let command =
+        |fields: &[Field]| format!("data merge {} {}", target, Tag::Compound(fields.to_vec()));
+    let fits = |fields: &[Field]| command(fields).len() <= max_length;
+    Ok(split_fields(fields, &fits, "")?
+        .iter()
+        .map(|fields| command(fields))
+        .collect())
+}
+
+/// Group fields into the fewest compounds that `fits` accepts, in order
+fn split_fields(
+    fields: &[Field],
+    fits: &dyn Fn(&[Field]) -> bool,
+    path: &str,
+) -> Result<Vec<Vec<Field>>> {
+    let mut parts = Vec::new();
+    let mut current: Vec<Field> = Vec::new();
+    for field in fields {
+        current.push(field.clone());
+        if fits(&current) {
+            continue;
+        }
+        current.pop();
+        if !current.is_empty() {
+            parts.push(std::mem::take(&mut current));
+        }
+        if fits(std::slice::from_ref(field)) {
+            current.push(field.clone());
+            continue;
+        }
+
+        let (key, value) = field;
+        let path = if path.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", path, key)
+        };
+        let Tag::Compound(nested) = value else {
+            return Err(nbt_error(format!(
+                "'{}' is too long for one command and, as a {}, can't be split",
+                path,
+                value.type_name()
+            )));
+        };
+        let wrap = |nested: &[Field]| vec![(key.clone(), Tag::Compound(nested.to_vec()))];
+        let nested_fits = |nested: &[Field]| fits(&wrap(nested));
+        for nested in split_fields(nested, &nested_fits, &path)? {
+            parts.push(wrap(&nested));
+        }
+    }
+    if !current.is_empty() {
+        parts.push(current);
+    }
+    Ok(parts)
+}
@@ -0,0 +1,184 @@
+//! Address parsing, name resolution, and dual-stack connection setup
+
+use crate::error::{RconError, Result};
+use futures_util::stream::{FuturesUnordered, StreamExt};
+use std::net::{IpAddr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+use tokio::net::{TcpSocket, TcpStream};
+use tracing::debug;
+
+/// Head start given to each connection attempt before the next one begins
+///
+/// Matches the recommended default of RFC 8305 ("Happy Eyeballs").
+pub const CONNECTION_ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
+/// Split an address into host and port
+///
+/// Accepts `host:port`, `[ipv6]:port`, and a bare host or IPv6 literal, in
+/// which case `default_port` is used.
+pub fn split_host_port(address: &str, default_port: u16) -> Result<(String, u16)> {
+    let invalid = |reason: &str| {
+        RconError::InvalidConfig(format!("Invalid address '{}': {}", address, reason))
+    };
+    let parse_port = |port: &str| port.parse::<u16>().map_err(|_| invalid("invalid port"));
+
+    let (host, port) = if let Some(rest) = address.strip_prefix('[') {
+        let (host, tail) = rest
+            .split_once(']')
+            .ok_or_else(|| invalid("missing closing ']'"))?;
+        host.parse::<Ipv6Addr>()
+            .map_err(|_| invalid("brackets must enclose an IPv6 address"))?;
+        let port = match tail {
+            "" => default_port,
+            _ => parse_port(
+                tail.strip_prefix(':')
+                    .ok_or_else(|| invalid("expected ':' after ']'"))?,
+            )?,
+        };
+        (host, port)
+    } else if address.parse::<Ipv6Addr>().is_ok() {
+        (address, default_port)
+    } else {
+        match address.rsplit_once(':') {
+            Some((host, _)) if host.contains(':') => {
+                return Err(invalid("IPv6 addresses with a port must use [addr]:port"));
+            }
+            Some((host, port)) => (host, parse_port(port)?),
+            None => (address, default_port),
+        }
+    };
+
+    if host.is_empty() {
+        return Err(invalid("missing host"));
+    }
+
+    Ok((host.to_string(), port))
+}
+
+/// Resolve an address to every socket address it refers to
+///
+/// IP literals are returned as-is. Host names are looked up via DNS and the
+/// results are ordered for [`connect`], alternating between address families
+/// starting with the resolver's first preference.
+pub async fn resolve(address: &str, default_port: u16) -> Result<Vec<SocketAddr>> {
+    let (host, port) = split_host_port(address, default_port)?;
+
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        return Ok(vec![SocketAddr::new(ip, port)]);
+    }
+
+    let resolved: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), port))
+        .await
+        .map_err(|e| RconError::AddressResolution(format!("{}: {}", host, e)))?
+        .collect();
+
+    if resolved.is_empty() {
+        return Err(RconError::AddressResolution(format!(
+            "{}: no addresses found",
+            host
+        )));
+    }
+
+    debug!("Resolved {} to {:?}", host, resolved);
+    Ok(interleave_families(resolved))
+}
+
+/// Reorder addresses so IPv6 and IPv4 alternate, keeping the first family first
+fn interleave_families(addresses: Vec<SocketAddr>) -> Vec<SocketAddr> {
+    let Some(first) = addresses.first() else {
+        return addresses;
+    };
+    let preferred_v6 = first.is_ipv6();
+    let (preferred, other): (Vec<_>, Vec<_>) = addresses
+        .into_iter()
+        .partition(|address| address.is_ipv6() == preferred_v6);
+
+    let mut ordered = Vec::with_capacity(preferred.len() + other.len());
+    let mut preferred = preferred.into_iter();
+    let mut other = other.into_iter();
+    loop {
+        match (preferred.next(), other.next()) {
+            (None, None) => break,
+            (a, b) => ordered.extend(a.into_iter().chain(b)),
+        }
+    }
+    ordered
+}
+
+/// Connect to the first reachable address
+///
+/// Attempts are staggered by [`CONNECTION_ATTEMPT_DELAY`] and raced against
+/// each other, so an unreachable address family costs a short delay instead
+/// of a full connect timeout. With a local address, only candidates of the
+/// same family are tried. The caller is responsible for the overall timeout.
+pub async fn connect(
+    addresses: &[SocketAddr],
+    local_addr: Option<SocketAddr>,
+) -> Result<TcpStream> {
+    let mut candidates = addresses
+        .iter()
+        .copied()
+        .filter(|address| local_addr.is_none_or(|local| local.is_ipv4() == address.is_ipv4()))
+        .peekable();
+
+    let Some(first) = candidates.next() else {
+        return Err(match (local_addr, addresses.first()) {
+            (Some(local), Some(address)) => RconError::InvalidConfig(format!(
+                "Cannot reach {} from local address {}: address families differ",
+                address, local
+            )),
+            _ => RconError::InvalidConfig("No server address to connect to".to_string()),
+        });
+    };
+
+    let mut in_flight = FuturesUnordered::new();
+    in_flight.push(attempt(first, local_addr));
+    let last_error = loop {
+        tokio::select! {
+            Some((address, result)) = in_flight.next() => match result {
+                Ok(stream) => {
+                    debug!("Connected to {}", address);
+                    return Ok(stream);
+                }
+                Err(e) => {
+                    debug!("Connection attempt to {} failed: {}", address, e);
+                    if let Some(next) = candidates.next() {
+                        in_flight.push(attempt(next, local_addr));
+                    } else if in_flight.is_empty() {
+                        break e;
+                    }
+                }
+            },
+            _ = tokio::time::sleep(CONNECTION_ATTEMPT_DELAY), if candidates.peek().is_some() => {
+                if let Some(next) = candidates.next() {
+                    debug!("Starting parallel connection attempt to {}", next);
+                    in_flight.push(attempt(next, local_addr));
+                }
+            }
+        }
+    };
+
+    Err(RconError::Network(last_error))
+}
+
+/// A single connection attempt, tagged with its address
+async fn attempt(
+    address: SocketAddr,
+    local_addr: Option<SocketAddr>,
+) -> (SocketAddr, std::io::Result<TcpStream>) {
+    let result = match local_addr {
+        Some(local_addr) => {
+            let socket = if address.is_ipv4() {
+                TcpSocket::new_v4()
+            } else {
+                TcpSocket::new_v6()
+            };
+            match socket.and_then(|socket| socket.bind(local_addr).map(|_| socket)) {
+                Ok(socket) => socket.connect(address).await,
+                Err(e) => Err(e),
+            }
+        }
+        None => TcpStream::connect(address).await,
+    };
+    (address, result)
+}
@@ -0,0 +1,131 @@
+//! Normalization of response phrasing across server versions
+//!
+//! Minecraft rewords command output between releases, and server software
+//! like Bukkit or EssentialsX replaces some messages outright. [`normalize`]
+//! rewrites the known variants listed in [`RULES`] into the phrasing of
+//! current vanilla releases, so the parsers only need to understand that.
+//! `fixtures/normalize.toml` holds responses from the versions the rules
+//! cover, with the text each normalizes to.
+
+use regex::Regex;
+use std::borrow::Cow;
+use std::sync::OnceLock;
+
+/// A phrasing rewritten into the current one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rule {
+    pub name: &'static str,
+    /// Where the phrasing comes from
+    pub source: &'static str,
+    /// Regex matching the phrasing
+    pub pattern: &'static str,
+    /// Current phrasing, with `${n}` standing for the pattern's groups
+    pub replacement: &'static str,
+}
+
+/// The phrasings [`normalize`] rewrites, applied in order
+pub const RULES: &[Rule] = &[
+    Rule {
+        name: "list",
+        source: "Java Edition before 1.13, Bukkit",
+        pattern: r"There are (\d+)/(\d+) players online:",
+        replacement: "There are ${1} of a max of ${2} players online:",
+    },
+    Rule {
+        name: "list",
+        source: "EssentialsX",
+        pattern: r"There are (\d+) out of maximum (\d+) players online\.",
+        replacement: "There are ${1} of a max of ${2} players online:",
+    },
+    Rule {
+        name: "banlist",
+        source: "Java Edition before 1.13",
+        pattern: r"There are (\d+) total banned (?:players|IP addresses):",
+        replacement: "There are ${1} ban(s):",
+    },
+    Rule {
+        name: "whitelist",
+        source: "Java Edition before 1.13",
+        pattern: r"There are (\d+) \(out of \d+ seen\) whitelisted players:",
+        replacement: "There are ${1} whitelisted player(s):",
+    },
+    Rule {
+        name: "time",
+        source: "Java Edition before 1.13",
+        pattern: r"\bTime is (-?\d+)",
+        replacement: "The time is ${1}",
+    },
+    Rule {
+        name: "worldborder",
+        source: "Java Edition before 1.13",
+        pattern: r"^World border is currently (\d+(?:\.\d+)?) blocks wide",
+        replacement: "The world border is currently ${1} block(s) wide",
+    },
+    Rule {
+        name: "seed",
+        source: "Java Edition before 1.16",
+        pattern: r"Seed: (-?\d+)",
+        replacement: "Seed: [${1}]",
+    },
+    Rule {
+        name: "save",
+        source: "Java Edition before 1.13",
+        pattern: r"Saved the world",
+        replacement: "Saved the game",
+    },
+    Rule {
+        name: "unknown-command",
+        source: "Java Edition before 1.13, Bukkit",
+        pattern: r#"Unknown command\. Type "/help" for help\."#,
+        replacement: "Unknown or incomplete command, see below for error",
+    },
+    Rule {
+        name: "player-not-found",
+        source: "Java Edition before 1.13",
+        pattern: r"That player cannot be found",
+        replacement: "No player was found",
+    },
+];
+
+/// Compiled [`RULES`]
+fn rules() -> &'static [(Regex, &'static str)] {
+    static RULES_COMPILED: OnceLock<Vec<(Regex, &'static str)>> = OnceLock::new();
+    RULES_COMPILED.get_or_init(|| {
+        RULES
+            .iter()
+            .map(|rule| (Regex::new(rule.pattern).unwrap(), rule.replacement))
+            .collect()
+    })
+}
+
+/// Strip `§` formatting codes, which some servers leave in over RCON
+pub fn strip_formatting(text: &str) -> Cow<'_, str> {
+    static CODE: OnceLock<Regex> = OnceLock::new();
+    CODE.get_or_init(|| Regex::new(r"§[0-9a-fk-orA-FK-OR]").unwrap())
+        .replace_all(text, "")
+}
+
+/// Rewrite a response into current vanilla phrasing, without formatting
+/// codes
+///
+/// Responses already phrased that way are returned unchanged.
+///
+/// ```
+/// use rcon_cli::normalize::normalize;
+///
+/// assert_eq!(
+///     normalize("There are 1/20 players online:Alice"),
+///     "There are 1 of a max of 20 players online:Alice"
+/// );
+/// assert_eq!(normalize("§6Seed: 42"), "Seed: [42]");
+/// assert_eq!(normalize("The time is 6000"), "The time is 6000");
+/// ```
+pub fn normalize(response: &str) -> Cow<'_, str> {
+    rules().iter().fold(
+        strip_formatting(response),
+        |text, (pattern, replacement)| match pattern.replace_all(&text, *replacement) {
+            Cow::Borrowed(_) => text,
+            Cow::Owned(replaced) => Cow::Owned(replaced),
+        },
+    )
+}
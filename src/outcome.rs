@@ -0,0 +1,221 @@
+//! Errors servers report as ordinary responses
+//!
+//! RCON has no error status: a mistyped or rejected command is answered
+//! like any other, e.g. Minecraft's `Unknown or incomplete command, see
+//! below for error`. An [`ErrorClassifier`] recognizes such responses by
+//! the patterns of the server's [`Dialect`], plus any from the `[errors]`
+//! section of the config file, and tells what a response means as a
+//! [`CommandOutcome`]. Clients configured with
+//! [`RconConfig::with_error_classifier`](crate::RconConfig::with_error_classifier)
+//! return these responses as [`RconError::CommandFailed`].
+
+use crate::client::CommandResponse;
+use crate::dialect::Dialect;
+use crate::error::{RconError, Result};
+use regex::{Regex, RegexBuilder};
+use serde::Deserialize;
+
+/// A phrasing meaning the server rejected a command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ErrorPattern {
+    pub name: &'static str,
+    /// Regex matching the phrasing, anchored per line with `^` and `$`
+    pub pattern: &'static str,
+}
+
+/// Errors of Minecraft: Java Edition and its forks
+pub const MINECRAFT_ERRORS: &[ErrorPattern] = &[
+    ErrorPattern {
+        name: "unknown-command",
+        pattern: r"^Unknown or incomplete command",
+    },
+    ErrorPattern {
+        name: "unknown-command",
+        pattern: r#"^Unknown command\. Type "/help" for help\."#,
+    },
+    ErrorPattern {
+        name: "incorrect-argument",
+        pattern: r"^Incorrect argument for command",
+    },
+    // Brigadier marks where parsing failed, e.g. "Invalid integer '5x'"
+    ErrorPattern {
+        name: "invalid-argument",
+        pattern: r"<--\[HERE\]$",
+    },
+    ErrorPattern {
+        name: "no-permission",
+        pattern: r"^I'm sorry, but you do not have permission",
+    },
+    ErrorPattern {
+        name: "no-target",
+        pattern: r"^No (?:player|entity) was found$",
+    },
+];
+
+/// Errors of Source engine servers
+pub const SOURCE_ERRORS: &[ErrorPattern] = &[ErrorPattern {
+    name: "unknown-command",
+    pattern: r#"^Unknown command "[^"]*""#,
+}];
+
+/// Errors of Factorio
+pub const FACTORIO_ERRORS: &[ErrorPattern] = &[ErrorPattern {
+    name: "unknown-command",
+    pattern: r"^Unknown command",
+}];
+
+/// Name of patterns from the config file
+const CUSTOM: &str = "custom";
+
+/// An `[errors.<dialect>]` section of the config file
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ErrorConfig {
+    /// Keep the dialect's built-in patterns next to the user's own
+    #[serde(default = "default_true")]
+    pub defaults: bool,
+    #[serde(default)]
+    pub patterns: Vec<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for ErrorConfig {
+    fn default() -> Self {
+        Self {
+            defaults: true,
+            patterns: Vec::new(),
+        }
+    }
+}
+
+/// What a response says about its command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandOutcome {
+    Succeeded,
+    /// The response reports an error
+    Failed {
+        /// Name of the matching pattern, e.g. `unknown-command`
+        kind: String,
+        /// The line of the response reporting it
+        message: String,
+    },
+}
+
+impl CommandOutcome {
+    pub fn is_success(&self) -> bool {
+        matches!(self, CommandOutcome::Succeeded)
+    }
+}
+
+/// Recognizes responses reporting errors
+///
+/// ```
+/// use rcon_cli::dialect::MinecraftJava;
+/// use rcon_cli::outcome::{CommandOutcome, ErrorClassifier};
+///
+/// let classifier = ErrorClassifier::for_dialect(&MinecraftJava);
+/// assert!(classifier.classify("There are 0 of a max of 20 players online:").is_success());
+///
+/// let outcome = classifier.classify("Unknown or incomplete command, see below for error");
+/// assert!(matches!(outcome, CommandOutcome::Failed { kind, .. } if kind == "unknown-command"));
+///
+/// let outcome = classifier.classify("Invalid integer '5x'...give Steve diamond 5x<--[HERE]");
+/// assert!(matches!(outcome, CommandOutcome::Failed { kind, .. } if kind == "invalid-argument"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ErrorClassifier {
+    patterns: Vec<(String, Regex)>,
+}
+
+impl ErrorClassifier {
+    /// The built-in patterns of a dialect
+    pub fn for_dialect(dialect: &dyn Dialect) -> Self {
+        let patterns = dialect
+            .error_patterns()
+            .iter()
+            .map(|pattern| {
+                let regex = compile(pattern.pattern).expect("built-in error patterns compile");
+                (pattern.name.to_string(), regex)
+            })
+            .collect();
+        Self { patterns }
+    }
+
+    /// The dialect's patterns as configured in its `[errors]` section
+    ///
+    /// ```
+    /// use rcon_cli::dialect::MinecraftJava;
+    /// use rcon_cli::outcome::{ErrorClassifier, ErrorConfig};
+    ///
+    /// let config = ErrorConfig { defaults: false, patterns: vec!["^Error: ".to_string()] };
+    /// let classifier = ErrorClassifier::from_config(&MinecraftJava, &config).unwrap();
+    /// assert!(!classifier.classify("Error: no such warp").is_success());
+    /// assert!(classifier.classify("Unknown or incomplete command").is_success());
+    /// ```
+    pub fn from_config(dialect: &dyn Dialect, config: &ErrorConfig) -> Result<Self> {
+        let classifier = if config.defaults {
+            Self::for_dialect(dialect)
+        } else {
+            Self::default()
+        };
+        config
+            .patterns
+            .iter()
+            .try_fold(classifier, |classifier, pattern| {
+                classifier.with_pattern(CUSTOM, pattern)
+            })
+    }
+
+    /// Also treat responses matching `pattern` as errors of kind `name`
+    pub fn with_pattern(mut self, name: impl Into<String>, pattern: &str) -> Result<Self> {
+        let regex = compile(pattern).map_err(|e| {
+            RconError::InvalidConfig(format!("Invalid error pattern '{}': {}", pattern, e))
+        })?;
+        self.patterns.push((name.into(), regex));
+        Ok(self)
+    }
+
+    /// Tell whether a response reports an error, by the first matching
+    /// pattern
+    pub fn classify(&self, response: &str) -> CommandOutcome {
+        for (name, regex) in &self.patterns {
+            if let Some(found) = regex.find(response) {
+                let start = response[..found.start()].rfind('\n').map_or(0, |i| i + 1);
+                let end = response[found.end()..]
+                    .find('\n')
+                    .map_or(response.len(), |i| found.end() + i);
+                return CommandOutcome::Failed {
+                    kind: name.clone(),
+                    message: response[start..end].trim().to_string(),
+                };
+            }
+        }
+        CommandOutcome::Succeeded
+    }
+
+    /// Pass a response on, or fail with [`RconError::CommandFailed`] if it
+    /// reports an error
+    pub fn check(&self, response: CommandResponse) -> Result<CommandResponse> {
+        self.check_body(&response.body)?;
+        Ok(response)
+    }
+
+    /// Fail with [`RconError::CommandFailed`] if a response's text reports
+    /// an error
+    pub fn check_body(&self, body: &str) -> Result<()> {
+        match self.classify(body) {
+            CommandOutcome::Succeeded => Ok(()),
+            CommandOutcome::Failed { kind, message } => {
+                Err(RconError::CommandFailed(format!("{} ({})", message, kind)))
+            }
+        }
+    }
+}
+
+/// Compile a pattern with `^` and `$` matching at line breaks
+fn compile(pattern: &str) -> std::result::Result<Regex, regex::Error> {
+    RegexBuilder::new(pattern).multi_line(true).build()
+}
@@ -0,0 +1,253 @@
+//! Connection details from hosting panels
+//!
+//! Game server hosts running Pterodactyl or PufferPanel hand out an API key
+//! rather than RCON credentials. A profile with a `panel` table leaves its
+//! address and password out and has them looked up: the host comes from the
+//! server's allocation, the RCON port and password from its
+//! `server.properties`.
+
+use crate::config::Profile;
+use crate::error::{RconError, Result};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Port used when `server.properties` does not set `rcon.port`
+const DEFAULT_RCON_PORT: u16 = 25575;
+
+/// Hosting panel software
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PanelKind {
+    Pterodactyl,
+    PufferPanel,
+}
+
+/// Where a profile's connection details are looked up
+///
+/// ```toml
+/// [profiles.hosted.panel]
+/// kind = "pterodactyl"
+/// url = "https://panel.example.com"
+/// server = "1a2b3c4d"
+/// api_key = "ptlc_..."
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PanelConfig {
+    pub kind: PanelKind,
+    /// Base URL of the panel
+    pub url: String,
+    /// Server UUID or short identifier
+    pub server: String,
+    /// Client API key; `<client id>:<client secret>` for PufferPanel
+    pub api_key: String,
+}
+
+/// Connection details reported by a panel
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PanelDetails {
+    /// Address in `host:port` form
+    pub address: String,
+    pub password: String,
+}
+
+/// Parse the `key=value` lines of a `server.properties` file
+///
+/// ```
+/// use rcon_cli::panel::parse_properties;
+///
+/// let properties = parse_properties("#Minecraft server properties\nenable-rcon=true\nrcon.password=s\\=cret\n");
+/// assert_eq!(properties["enable-rcon"], "true");
+/// assert_eq!(properties["rcon.password"], "s=cret");
+/// ```
+pub fn parse_properties(source: &str) -> BTreeMap<String, String> {
+    source
+        .lines()
+        .map(str::trim_start)
+        .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with('!'))
+        .filter_map(|line| {
+            let (key, value) = line.split_once('=').or_else(|| line.split_once(':'))?;
+            Some((
+                key.trim().to_string(),
+                value.trim().replace("\\=", "=").replace("\\:", ":"),
+            ))
+        })
+        .collect()
+}
+
+fn panel_error(message: impl Into<String>) -> RconError {
+    RconError::Panel(message.into())
+}
+
+/// Send a request and read the response body as JSON
+async fn json(request: reqwest::RequestBuilder, url: &str) -> Result<Value> {
+    let fetch = async { request.send().await?.error_for_status()?.json().await };
+    fetch
+        .await
+        .map_err(|e| panel_error(format!("Request to {} failed: {}", url, e.without_url())))
+}
+
+/// Send a request and read the response body as text
+async fn text(request: reqwest::RequestBuilder, url: &str) -> Result<String> {
+    let fetch = async { request.send().await?.error_for_status()?.text().await };
+    fetch
+        .await
+        .map_err(|e| panel_error(format!("Request to {} failed: {}", url, e.without_url())))
+}
+
+/// Build the address from the panel's host and the RCON settings in
+/// `server.properties`
+fn details_from_properties(host: &str, properties: &str) -> Result<PanelDetails> {
+    let properties = parse_properties(properties);
+    if properties.get("enable-rcon").map(String::as_str) != Some("true") {
+        return Err(panel_error("RCON is not enabled in server.properties"));
+    }
+    let port = match properties.get("rcon.port") {
+        Some(port) => port
+            .parse::<u16>()
+            .map_err(|_| panel_error(format!("Invalid rcon.port '{}'", port)))?,
+        None => DEFAULT_RCON_PORT,
+    };
+    let password = properties
+        .get("rcon.password")
+        .filter(|password| !password.is_empty())
+        .ok_or_else(|| panel_error("server.properties sets no rcon.password"))?;
+
+    let address = if host.contains(':') {
+        format!("[{}]:{}", host, port)
+    } else {
+        format!("{}:{}", host, port)
+    };
+    Ok(PanelDetails {
+        address,
+        password: password.clone(),
+    })
+}
+
+/// The host from a panel URL, for servers bound to all interfaces
+fn url_host(url: &str) -> Result<String> {
+    reqwest::Url::parse(url)
+        .ok()
+        .and_then(|url| {
+            url.host_str()
+                .map(|host| host.trim_matches(['[', ']']).to_string())
+        })
+        .ok_or_else(|| panel_error(format!("Invalid panel URL '{}'", url)))
+}
+
+/// Use the panel's host in place of a wildcard bind address
+fn public_host(ip: &str, panel_url: &str) -> Result<String> {
+    match ip {
+        "" | "0.0.0.0" | "::" => url_host(panel_url),
+        ip => Ok(ip.to_string()),
+    }
+}
+
+async fn fetch_pterodactyl(client: &reqwest::Client, panel: &PanelConfig) -> Result<PanelDetails> {
+    let base = format!(
+        "{}/api/client/servers/{}",
+        panel.url.trim_end_matches('/'),
+        panel.server
+    );
+
+    let server = json(
+        client
+            .get(&base)
+            .bearer_auth(&panel.api_key)
+            .header("Accept", "application/json"),
+        &base,
+    )
+    .await?;
+
+    // The default allocation is where the game port is bound
+    let allocations = server["attributes"]["relationships"]["allocations"]["data"]
+        .as_array()
+        .ok_or_else(|| panel_error("Server has no allocations"))?;
+    let allocation = allocations
+        .iter()
+        .map(|allocation| &allocation["attributes"])
+        .find(|attributes| attributes["is_default"].as_bool() == Some(true))
+        .or_else(|| {
+            allocations
+                .first()
+                .map(|allocation| &allocation["attributes"])
+        })
+        .ok_or_else(|| panel_error("Server has no allocations"))?;
+    let ip = allocation["ip_alias"]
+        .as_str()
+        .or_else(|| allocation["ip"].as_str())
+        .unwrap_or_default();
+    let host = public_host(ip, &panel.url)?;
+
+    let url = format!("{}/files/contents?file=%2Fserver.properties", base);
+    let properties = text(client.get(&url).bearer_auth(&panel.api_key), &url).await?;
+    details_from_properties(&host, &properties)
+}
+
+async fn fetch_pufferpanel(client: &reqwest::Client, panel: &PanelConfig) -> Result<PanelDetails> {
+    let base = panel.url.trim_end_matches('/');
+    let (client_id, client_secret) = panel.api_key.split_once(':').ok_or_else(|| {
+        panel_error("PufferPanel API keys have the form <client id>:<client secret>")
+    })?;
+
+    // OAuth2 client credentials grant
+    let url = format!("{}/oauth2/token", base);
+    let token = json(
+        client.post(&url).form(&[
+            ("grant_type", "client_credentials"),
+            ("client_id", client_id),
+            ("client_secret", client_secret),
+        ]),
+        &url,
+    )
+    .await?;
+    let token = token["access_token"]
+        .as_str()
+        .ok_or_else(|| panel_error("The panel issued no access token"))?;
+
+    let url = format!("{}/api/servers/{}", base, panel.server);
+    let server = json(client.get(&url).bearer_auth(token), &url).await?;
+    let server = &server["server"];
+    let ip = match server["ip"].as_str() {
+        Some("" | "0.0.0.0" | "::") | None => server["node"]["publicHost"].as_str(),
+        ip => ip,
+    };
+    let host = public_host(ip.unwrap_or_default(), &panel.url)?;
+
+    let url = format!(
+        "{}/api/servers/{}/file/server.properties",
+        base, panel.server
+    );
+    let properties = text(client.get(&url).bearer_auth(token), &url).await?;
+    details_from_properties(&host, &properties)
+}
+
+/// Look up a server's RCON address and password through the panel API
+pub async fn fetch_details(panel: &PanelConfig) -> Result<PanelDetails> {
+    let client = reqwest::Client::new();
+    match panel.kind {
+        PanelKind::Pterodactyl => fetch_pterodactyl(&client, panel).await,
+        PanelKind::PufferPanel => fetch_pufferpanel(&client, panel).await,
+    }
+}
+
+/// Fill in the address and password a profile leaves to its panel
+///
+/// Settings given in the profile itself are kept; profiles without a panel,
+/// or with nothing left to look up, are returned as they are without
+/// contacting it.
+pub async fn complete_profile(profile: &Profile) -> Result<Profile> {
+    let mut profile = profile.clone();
+    let Some(panel) = &profile.panel else {
+        return Ok(profile);
+    };
+    if profile.address.is_some() && profile.password.is_some() {
+        return Ok(profile);
+    }
+
+    let details = fetch_details(panel).await?;
+    profile.address.get_or_insert(details.address);
+    profile.password.get_or_insert(details.password);
+    Ok(profile)
+}
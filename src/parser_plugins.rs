@@ -0,0 +1,168 @@
+//! Response parsers run as external programs
+//!
+//! Commands of mods and plugins (EssentialsX, LuckPerms, ...) that the
+//! built-in [`parsers`](crate::parsers) don't know can be parsed by any
+//! executable, configured per command pattern in the config file:
+//!
+//! ```toml
+//! [[parsers]]
+//! command = "lp user * info"
+//! program = "/usr/local/bin/lp-parse"
+//! args = ["user-info"]
+//! timeout = 5
+//! ```
+//!
+//! Patterns match a command's leading words like [`CommandPolicy`] entries,
+//! and the first matching parser is used. The program gets the raw response
+//! on stdin and the command in `RCON_COMMAND`, and must print one JSON value
+//! on stdout and exit successfully within `timeout` seconds (default 5).
+//!
+//! With the `wasm` feature, commands no configured program matches are
+//! offered to the [WebAssembly plugins](crate::wasm) as well.
+
+use crate::error::{RconError, Result};
+use crate::policy::CommandPolicy;
+use serde::Deserialize;
+use serde_json::Value;
+use std::path::PathBuf;
+use std::process::Stdio;
+#[cfg(feature = "wasm")]
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+/// How long a parser may run when its entry sets no `timeout`
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A `[[parsers]]` entry of the config file
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ParserConfig {
+    /// Leading words of the commands to parse; `*` matches any word
+    pub command: String,
+    pub program: PathBuf,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Seconds the program may run
+    pub timeout: Option<u64>,
+}
+
+impl ParserConfig {
+    /// Run the program on a response, returning the JSON it printed
+    pub async fn run(&self, command: &str, response: &str) -> Result<Value> {
+        let program = self.program.display();
+        let error = |message: String| RconError::Parser(format!("{}: {}", program, message));
+
+        let mut child = Command::new(&self.program)
+            .args(&self.args)
+            .env("RCON_COMMAND", command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| error(e.to_string()))?;
+
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let input = response.to_string();
+        // Written concurrently, so a program answering before it read
+        // everything can't block on a full pipe
+        let writer = tokio::spawn(async move {
+            // A program may exit without reading its input
+            let _ = stdin.write_all(input.as_bytes()).await;
+        });
+
+        let timeout = self.timeout.map_or(DEFAULT_TIMEOUT, Duration::from_secs);
+        let output = tokio::time::timeout(timeout, child.wait_with_output())
+            .await
+            .map_err(|_| error(format!("no result within {}s", timeout.as_secs())))?
+            .map_err(|e| error(e.to_string()))?;
+        writer.abort();
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(error(match stderr.trim() {
+                "" => output.status.to_string(),
+                stderr => format!("{} ({})", output.status, stderr),
+            }));
+        }
+        serde_json::from_slice(&output.stdout)
+            .map_err(|e| error(format!("output is not JSON: {}", e)))
+    }
+}
+
+/// The configured parsers
+///
+/// ```
+/// use rcon_cli::parser_plugins::{ExternalParsers, ParserConfig};
+///
+/// let parsers = ExternalParsers::new(&[ParserConfig {
+///     command: "lp user * info".to_string(),
+///     program: "lp-parse".into(),
+///     args: Vec::new(),
+///     timeout: None,
+/// }]);
+/// assert!(parsers.find("lp user Steve info").is_some());
+/// assert!(parsers.find("lp user Steve permission set fly").is_none());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ExternalParsers {
+    parsers: Vec<(CommandPolicy, ParserConfig)>,
+    #[cfg(feature = "wasm")]
+    wasm: Option<Arc<crate::wasm::PluginHost>>,
+}
+
+impl ExternalParsers {
+    pub fn new(configs: &[ParserConfig]) -> Self {
+        Self {
+            parsers: configs
+                .iter()
+                .map(|config| {
+                    let pattern = CommandPolicy::allow(config.command.clone(), [&config.command]);
+                    (pattern, config.clone())
+                })
+                .collect(),
+            #[cfg(feature = "wasm")]
+            wasm: None,
+        }
+    }
+
+    /// Fall back to WebAssembly plugins for commands no program matches
+    #[cfg(feature = "wasm")]
+    pub fn with_wasm(mut self, host: Option<Arc<crate::wasm::PluginHost>>) -> Self {
+        self.wasm = host;
+        self
+    }
+
+    /// The WebAssembly plugins, if any are loaded
+    #[cfg(feature = "wasm")]
+    pub fn wasm(&self) -> Option<&Arc<crate::wasm::PluginHost>> {
+        self.wasm.as_ref()
+    }
+
+    /// Whether no program is configured
+    pub fn is_empty(&self) -> bool {
+        self.parsers.is_empty()
+    }
+
+    /// The first parser for `command`, if any
+    pub fn find(&self, command: &str) -> Option<&ParserConfig> {
+        self.parsers
+            .iter()
+            .find(|(pattern, _)| pattern.permits(command))
+            .map(|(_, config)| config)
+    }
+
+    /// Parse a response with the parser for its command, if there is one
+    pub async fn parse(&self, command: &str, response: &str) -> Option<Result<Value>> {
+        if let Some(parser) = self.find(command) {
+            return Some(parser.run(command, response).await);
+        }
+        #[cfg(feature = "wasm")]
+        if let Some(host) = &self.wasm {
+            return host.parse_response(command, response);
+        }
+        None
+    }
+}
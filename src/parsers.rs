@@ -0,0 +1,1340 @@
+//! Parsers turning common Minecraft command output into structured records
+//!
+//! Vanilla servers concatenate multi-message output without separators over
+//! RCON, so the patterns here anchor on the fixed phrases of each message
+//! rather than on line breaks. Responses pass through
+//! [`normalize`](crate::normalize::normalize) first where phrasing differs
+//! between versions, so the patterns only match current vanilla phrasing.
+
+use crate::nbt::Tag;
+use crate::normalize::{normalize, strip_formatting};
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// Output of `list` or `list uuids`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerList {
+    pub online: u32,
+    pub max: u32,
+    pub players: Vec<PlayerEntry>,
+}
+
+/// A player from a player list
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerEntry {
+    pub name: String,
+    /// Only present in `list uuids` output
+    pub uuid: Option<String>,
+}
+
+/// A single entry of `banlist`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BanEntry {
+    /// Banned player name or IP address
+    pub target: String,
+    /// Who issued the ban
+    pub source: String,
+    pub reason: String,
+}
+
+fn regex(cell: &'static OnceLock<Regex>, pattern: &str) -> &'static Regex {
+    cell.get_or_init(|| Regex::new(pattern).unwrap())
+}
+
+/// Parse the response of `list` or `list uuids`
+///
+/// Understands the current "There are N of a max of M players online" form
+/// and those [`normalize`] rewrites to it, like the pre-1.13 "There are N/M
+/// players online".
+pub fn parse_player_list(response: &str) -> Option<PlayerList> {
+    static HEADER: OnceLock<Regex> = OnceLock::new();
+    static ENTRY: OnceLock<Regex> = OnceLock::new();
+
+    let response = normalize(response);
+    let header = regex(
+        &HEADER,
+        r"There are (\d+) of a max of (\d+) players online:\s*(.*)",
+    );
+    let entry = regex(&ENTRY, r"^(\S+)(?: \(([0-9a-fA-F-]{36})\))?$");
+
+    let captures = header.captures(response.trim())?;
+    let online = captures[1].parse().ok()?;
+    let max = captures[2].parse().ok()?;
+
+    let players = captures[3]
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| match entry.captures(name) {
+            Some(parts) => PlayerEntry {
+                name: parts[1].to_string(),
+                uuid: parts.get(2).map(|uuid| uuid.as_str().to_string()),
+            },
+            None => PlayerEntry {
+                name: name.to_string(),
+                uuid: None,
+            },
+        })
+        .collect();
+
+    Some(PlayerList {
+        online,
+        max,
+        players,
+    })
+}
+
+/// Parse the response of `banlist`, `banlist players`, or `banlist ips`
+///
+/// Returns `None` if the response isn't recognizable as a ban list. Lists
+/// from before 1.13 only name the targets, leaving source and reason empty.
+///
+/// ```
+/// use rcon_cli::parsers::parse_banlist;
+///
+/// let bans = parse_banlist("There are 1 ban(s):Griefer was banned by Server: Griefing").unwrap();
+/// assert_eq!((bans[0].target.as_str(), bans[0].reason.as_str()), ("Griefer", "Griefing"));
+///
+/// let bans = parse_banlist("There are 2 total banned players:Griefer and Spammer").unwrap();
+/// assert_eq!(bans[1].target, "Spammer");
+/// ```
+pub fn parse_banlist(response: &str) -> Option<Vec<BanEntry>> {
+    static HEADER: OnceLock<Regex> = OnceLock::new();
+    static ENTRY: OnceLock<Regex> = OnceLock::new();
+    static SEPARATOR: OnceLock<Regex> = OnceLock::new();
+
+    let response = normalize(response);
+    let response = response.trim();
+    if response.starts_with("There are no bans") {
+        return Some(Vec::new());
+    }
+
+    let header = regex(&HEADER, r"^There (?:are|is) \d+ bans?(?:\(s\))?:\s*");
+    let body = &response[header.find(response)?.end()..];
+
+    // A target is a player name or IPv4 address; the reason runs until the
+    // next target or the end of the response
+    let entry = regex(
+        &ENTRY,
+        r"(\d{1,3}(?:\.\d{1,3}){3}|[A-Za-z0-9_]{1,16}) was banned by ([^:]+): ",
+    );
+    let matches: Vec<_> = entry.captures_iter(body).collect();
+    if matches.is_empty() {
+        let bans = regex(&SEPARATOR, r",|\s+and\s+")
+            .split(body)
+            .map(str::trim)
+            .filter(|target| !target.is_empty())
+            .map(|target| BanEntry {
+                target: target.to_string(),
+                source: String::new(),
+                reason: String::new(),
+            })
+            .collect();
+        return Some(bans);
+    }
+
+    let bans = matches
+        .iter()
+        .enumerate()
+        .map(|(index, captures)| {
+            let whole = captures.get(0).unwrap();
+            let reason_end = matches
+                .get(index + 1)
+                .map_or(body.len(), |next| next.get(0).unwrap().start());
+            BanEntry {
+                target: captures[1].to_string(),
+                source: captures[2].trim().to_string(),
+                reason: body[whole.end()..reason_end].trim().to_string(),
+            }
+        })
+        .collect();
+
+    Some(bans)
+}
+
+/// Parse the response of `whitelist list` into player names
+///
+/// ```
+/// use rcon_cli::parsers::parse_whitelist;
+///
+/// let names = parse_whitelist("There are 3 whitelisted player(s): Alice, Bob, Carol").unwrap();
+/// assert_eq!(names, ["Alice", "Bob", "Carol"]);
+///
+/// // Before 1.13, the last name is joined with "and"
+/// let names = parse_whitelist("There are 2 (out of 5 seen) whitelisted players:Alice and Bob");
+/// assert_eq!(names.unwrap(), ["Alice", "Bob"]);
+/// ```
+pub fn parse_whitelist(response: &str) -> Option<Vec<String>> {
+    static HEADER: OnceLock<Regex> = OnceLock::new();
+    static SEPARATOR: OnceLock<Regex> = OnceLock::new();
+
+    let response = normalize(response);
+    let response = response.trim();
+    if response.starts_with("There are no whitelisted players") {
+        return Some(Vec::new());
+    }
+
+    let header = regex(
+        &HEADER,
+        r"There (?:are|is) \d+ whitelisted players?(?:\(s\))?:\s*",
+    );
+    let body = &response[header.find(response)?.end()..];
+    Some(
+        regex(&SEPARATOR, r",|\s+and\s+")
+            .split(body)
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .collect(),
+    )
+}
+
+/// Outcome of `save-all`, as reported in its response
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SaveStatus {
+    /// The server confirmed that the save finished
+    Saved,
+    /// The save started but the response doesn't say it finished
+    Started,
+    /// The server reported an error, with its message
+    Failed(String),
+}
+
+/// Parse the response of `save-all` or `save-all flush`
+///
+/// Returns `None` if the response doesn't mention saving at all, e.g. when
+/// the command is unknown to the server.
+///
+/// ```
+/// use rcon_cli::parsers::{parse_save_response, SaveStatus};
+///
+/// let response = "Saving the game (this may take a moment!)Saved the game";
+/// assert_eq!(parse_save_response(response), Some(SaveStatus::Saved));
+/// ```
+pub fn parse_save_response(response: &str) -> Option<SaveStatus> {
+    static FAILED: OnceLock<Regex> = OnceLock::new();
+
+    let response = normalize(response);
+    // "Unable to save the game" since 1.13, "Saving failed: ..." before
+    let failed = regex(
+        &FAILED,
+        r"(?i)(unable to save the game.*|saving failed.*|could not save.*)",
+    );
+    if let Some(captures) = failed.captures(&response) {
+        return Some(SaveStatus::Failed(captures[1].trim().to_string()));
+    }
+
+    let lowercase = response.to_lowercase();
+    if lowercase.contains("saved the game") {
+        Some(SaveStatus::Saved)
+    } else if lowercase.contains("saving") {
+        Some(SaveStatus::Started)
+    } else {
+        None
+    }
+}
+
+/// What kind of extension an [`Addon`] is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddonKind {
+    DataPack,
+    Plugin,
+    Mod,
+}
+
+impl AddonKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AddonKind::DataPack => "datapack",
+            AddonKind::Plugin => "plugin",
+            AddonKind::Mod => "mod",
+        }
+    }
+}
+
+/// A data pack, plugin, or mod installed on the server
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Addon {
+    pub name: String,
+    pub kind: AddonKind,
+    pub version: Option<String>,
+    pub enabled: bool,
+}
+
+/// Parse the response of `datapack list`
+///
+/// Packs listed as available but not enabled are reported as disabled.
+///
+/// ```
+/// use rcon_cli::parsers::parse_datapack_list;
+///
+/// let response = "There are 2 data pack(s) enabled: [vanilla (built-in)], \
+///     [file/trees.zip (world)]There are no more data packs available";
+/// let packs = parse_datapack_list(response).unwrap();
+/// assert_eq!(packs[1].name, "file/trees.zip");
+/// assert!(packs[1].enabled);
+/// ```
+pub fn parse_datapack_list(response: &str) -> Option<Vec<Addon>> {
+    static SECTION: OnceLock<Regex> = OnceLock::new();
+    static ENTRY: OnceLock<Regex> = OnceLock::new();
+
+    let section = regex(
+        &SECTION,
+        r"There (?:are|is) (?:\d+|no) (?:more )?data packs?(?:\(s\))? (enabled|available)",
+    );
+    let entry = regex(&ENTRY, r"\[([^\]]+?)(?: \([^()]*\))?\]");
+
+    let sections: Vec<_> = section.captures_iter(response).collect();
+    if sections.is_empty() {
+        return None;
+    }
+
+    let mut packs = Vec::new();
+    for (index, captures) in sections.iter().enumerate() {
+        let start = captures.get(0).unwrap().end();
+        let end = sections
+            .get(index + 1)
+            .map_or(response.len(), |next| next.get(0).unwrap().start());
+        let enabled = &captures[1] == "enabled";
+
+        packs.extend(
+            entry
+                .captures_iter(&response[start..end])
+                .map(|pack| Addon {
+                    name: pack[1].to_string(),
+                    kind: AddonKind::DataPack,
+                    version: None,
+                    enabled,
+                }),
+        );
+    }
+
+    Some(packs)
+}
+
+/// Parse the response of `plugins` on Bukkit-based servers
+///
+/// Handles both the Spigot `Plugins (N): a, b` form and Paper's sectioned
+/// list. Plugins highlighted in red are reported as disabled. The listing
+/// has no versions; see [`parse_plugin_version`].
+pub fn parse_plugin_list(response: &str) -> Option<Vec<Addon>> {
+    static HEADER: OnceLock<Regex> = OnceLock::new();
+    static SECTION: OnceLock<Regex> = OnceLock::new();
+
+    let header = regex(&HEADER, r"Plugins \(\d+\):");
+    let section = regex(&SECTION, r"(?:Bukkit|Paper) Plugins(?: \(\d+\))?:");
+
+    let body = &response[header.find(response)?.end()..];
+    let body = section.replace_all(body, ",");
+
+    let plugins = body
+        .split([',', '\n'])
+        .filter_map(|entry| {
+            let enabled = !entry.contains("§c");
+            let name = strip_formatting(entry);
+            let name = name
+                .trim()
+                .trim_start_matches('-')
+                .trim()
+                .trim_end_matches('*');
+            (!name.is_empty()).then(|| Addon {
+                name: name.to_string(),
+                kind: AddonKind::Plugin,
+                version: None,
+                enabled,
+            })
+        })
+        .collect();
+
+    Some(plugins)
+}
+
+/// Parse the version from the response of `version <plugin>`
+pub fn parse_plugin_version(response: &str) -> Option<String> {
+    static VERSION: OnceLock<Regex> = OnceLock::new();
+
+    let response = strip_formatting(response);
+    regex(&VERSION, r"\S+ version (\S+)")
+        .captures(&response)
+        .map(|captures| captures[1].to_string())
+}
+
+/// Parse the response of `forge mods` or `neoforge mods`
+///
+/// Entries are expected as `modid version`, separated by commas or lines.
+pub fn parse_mod_list(response: &str) -> Option<Vec<Addon>> {
+    static HEADER: OnceLock<Regex> = OnceLock::new();
+
+    let header = regex(&HEADER, r"(?i)mod list:?");
+    let body = strip_formatting(&response[header.find(response)?.end()..]);
+
+    let mods = body
+        .split([',', '\n'])
+        .filter_map(|entry| {
+            let entry = entry.trim().trim_start_matches('-').trim();
+            if entry.is_empty() {
+                return None;
+            }
+            let (name, version) = match entry.rsplit_once(char::is_whitespace) {
+                Some((name, version)) if version.starts_with(|c: char| c.is_ascii_digit()) => {
+                    (name.trim(), Some(version.to_string()))
+                }
+                _ => (entry, None),
+            };
+            Some(Addon {
+                name: name.to_string(),
+                kind: AddonKind::Mod,
+                version,
+                enabled: true,
+            })
+        })
+        .collect();
+
+    Some(mods)
+}
+
+/// Parse the world seed from the response of `seed`
+///
+/// The seed is wrapped in brackets (a copyable chat component) since 1.13
+/// and may carry `§` formatting codes on modded servers.
+///
+/// ```
+/// use rcon_cli::parsers::parse_seed;
+///
+/// assert_eq!(parse_seed("Seed: [-4172144997902289642]"), Some(-4172144997902289642));
+/// assert_eq!(parse_seed("Seed: 12345"), Some(12345));
+/// ```
+pub fn parse_seed(response: &str) -> Option<i64> {
+    static SEED: OnceLock<Regex> = OnceLock::new();
+
+    let seed = regex(&SEED, r"Seed: \[\s*(-?\d+)\s*\]");
+    seed.captures(&normalize(response))?[1].parse().ok()
+}
+
+/// Parse the number in the response of `time query` or `time set`
+///
+/// ```
+/// use rcon_cli::parsers::parse_time;
+///
+/// assert_eq!(parse_time("The time is 13000"), Some(13000));
+/// assert_eq!(parse_time("Set the time to 1000"), Some(1000));
+/// ```
+pub fn parse_time(response: &str) -> Option<i64> {
+    static TIME: OnceLock<Regex> = OnceLock::new();
+
+    let time = regex(&TIME, r"(?:The time is|Set the time to) (-?\d+)");
+    time.captures(&normalize(response))?[1].parse().ok()
+}
+
+/// Parse the response of `difficulty` without arguments
+///
+/// ```
+/// use rcon_cli::parsers::parse_difficulty;
+///
+/// assert_eq!(parse_difficulty("The difficulty is Hard").as_deref(), Some("Hard"));
+/// ```
+pub fn parse_difficulty(response: &str) -> Option<String> {
+    static DIFFICULTY: OnceLock<Regex> = OnceLock::new();
+
+    let difficulty = regex(&DIFFICULTY, r"^The difficulty is (\w+)");
+    Some(difficulty.captures(strip_formatting(response).trim())?[1].to_string())
+}
+
+/// Parse the width in blocks from the response of `worldborder get`
+///
+/// ```
+/// use rcon_cli::parsers::parse_world_border;
+///
+/// let response = "The world border is currently 59999968 block(s) wide";
+/// assert_eq!(parse_world_border(response), Some(59999968.0));
+/// ```
+pub fn parse_world_border(response: &str) -> Option<f64> {
+    static BORDER: OnceLock<Regex> = OnceLock::new();
+
+    let border = regex(
+        &BORDER,
+        r"The world border is currently (\d+(?:\.\d+)?) block\(s\) wide",
+    );
+    border.captures(&normalize(response))?[1].parse().ok()
+}
+
+/// Parse the outcome of `execute if|unless ...` without a `run` clause
+///
+/// Returns `None` for errors, e.g. when the server doesn't understand the
+/// condition.
+///
+/// ```
+/// use rcon_cli::parsers::parse_test_result;
+///
+/// assert_eq!(parse_test_result("Test passed, count: 3"), Some(true));
+/// assert_eq!(parse_test_result("Test failed"), Some(false));
+/// ```
+pub fn parse_test_result(response: &str) -> Option<bool> {
+    let response = strip_formatting(response);
+    if response.starts_with("Test passed") {
+        Some(true)
+    } else if response.starts_with("Test failed") {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// Force-load state of one chunk, as reported by `forceload query <x> <z>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkStatus {
+    /// Chunk coordinates
+    pub chunk: (i64, i64),
+    pub dimension: String,
+    pub forced: bool,
+}
+
+/// Parse the response of `forceload query <x> <z>`
+///
+/// ```
+/// use rcon_cli::parsers::parse_forceload_query;
+///
+/// let status = parse_forceload_query(
+///     "Chunk at [3, -2] in minecraft:overworld is not marked for force loading",
+/// )
+/// .unwrap();
+/// assert_eq!(status.chunk, (3, -2));
+/// assert!(!status.forced);
+/// ```
+pub fn parse_forceload_query(response: &str) -> Option<ChunkStatus> {
+    static QUERY: OnceLock<Regex> = OnceLock::new();
+
+    let query = regex(
+        &QUERY,
+        r"Chunk at \[(-?\d+), (-?\d+)\] in (\S+) is (not )?marked for force loading",
+    );
+    let captures = query.captures(response)?;
+    Some(ChunkStatus {
+        chunk: (captures[1].parse().ok()?, captures[2].parse().ok()?),
+        dimension: captures[3].to_string(),
+        forced: captures.get(4).is_none(),
+    })
+}
+
+/// Who a command applied to: one named entity or a number of them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Affected {
+    Entity(String),
+    Count(u32),
+}
+
+impl Affected {
+    fn parse(target: &str, count_suffix: Option<&str>) -> Option<Self> {
+        match count_suffix {
+            Some(_) => target.parse().ok().map(Affected::Count),
+            None => Some(Affected::Entity(target.to_string())),
+        }
+    }
+
+    /// Name of the entity, if a single one was named
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            Affected::Entity(name) => Some(name),
+            Affected::Count(_) => None,
+        }
+    }
+
+    /// Number of entities affected
+    pub fn count(&self) -> u32 {
+        match self {
+            Affected::Entity(_) => 1,
+            Affected::Count(count) => *count,
+        }
+    }
+}
+
+/// Unit of an experience amount
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XpUnit {
+    Points,
+    Levels,
+}
+
+impl XpUnit {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            XpUnit::Points => "points",
+            XpUnit::Levels => "levels",
+        }
+    }
+
+    fn parse(unit: &str) -> Option<Self> {
+        match unit {
+            "points" => Some(XpUnit::Points),
+            "levels" => Some(XpUnit::Levels),
+            _ => None,
+        }
+    }
+}
+
+/// Experience of one player, as reported by `xp query`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XpQuery {
+    pub player: String,
+    pub amount: u32,
+    pub unit: XpUnit,
+}
+
+/// Parse the response of `xp query <player> points|levels`
+///
+/// ```
+/// use rcon_cli::parsers::{parse_xp_query, XpUnit};
+///
+/// let query = parse_xp_query("Steve has 27 experience levels").unwrap();
+/// assert_eq!((query.player.as_str(), query.amount, query.unit), ("Steve", 27, XpUnit::Levels));
+/// ```
+pub fn parse_xp_query(response: &str) -> Option<XpQuery> {
+    static QUERY: OnceLock<Regex> = OnceLock::new();
+
+    let query = regex(&QUERY, r"^(\S+) has (\d+) experience (points|levels)");
+    let captures = query.captures(response.trim())?;
+    Some(XpQuery {
+        player: captures[1].to_string(),
+        amount: captures[2].parse().ok()?,
+        unit: XpUnit::parse(&captures[3])?,
+    })
+}
+
+/// Result of `xp add` or `xp set`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XpChange {
+    /// Amount given (negative when taken) or set
+    pub amount: i64,
+    pub unit: XpUnit,
+    pub target: Affected,
+}
+
+/// Parse the response of `xp add` or `xp set`
+///
+/// Returns `None` for error messages such as "No player was found".
+pub fn parse_xp_change(response: &str) -> Option<XpChange> {
+    static CHANGE: OnceLock<Regex> = OnceLock::new();
+
+    let change = regex(
+        &CHANGE,
+        r"^(?:Gave|Set) (-?\d+) experience (points|levels) (?:to|on) (\S+)( players)?$",
+    );
+    let captures = change.captures(response.trim())?;
+    Some(XpChange {
+        amount: captures[1].parse().ok()?,
+        unit: XpUnit::parse(&captures[2])?,
+        target: Affected::parse(&captures[3], captures.get(4).map(|m| m.as_str()))?,
+    })
+}
+
+/// Result of `effect give` or `effect clear`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EffectChange {
+    /// Display name of the effect, or `None` when every effect was cleared
+    pub effect: Option<String>,
+    pub target: Affected,
+}
+
+/// Parse the response of `effect give` or `effect clear`
+///
+/// ```
+/// use rcon_cli::parsers::{parse_effect_change, Affected};
+///
+/// let change = parse_effect_change("Applied effect Speed to 3 targets").unwrap();
+/// assert_eq!(change.effect.as_deref(), Some("Speed"));
+/// assert_eq!(change.target, Affected::Count(3));
+/// ```
+pub fn parse_effect_change(response: &str) -> Option<EffectChange> {
+    static APPLIED: OnceLock<Regex> = OnceLock::new();
+    static CLEARED: OnceLock<Regex> = OnceLock::new();
+
+    let response = response.trim();
+    let applied = regex(
+        &APPLIED,
+        r"^(?:Applied|Removed) effect (.+) (?:to|from) (\S+)( targets)?$",
+    );
+    let cleared = regex(&CLEARED, r"^Removed every effect from (\S+)( targets)?$");
+    if let Some(captures) = applied.captures(response) {
+        return Some(EffectChange {
+            effect: Some(captures[1].to_string()),
+            target: Affected::parse(&captures[2], captures.get(3).map(|m| m.as_str()))?,
+        });
+    }
+    let captures = cleared.captures(response)?;
+    Some(EffectChange {
+        effect: None,
+        target: Affected::parse(&captures[1], captures.get(2).map(|m| m.as_str()))?,
+    })
+}
+
+/// Number of chunks changed by `forceload add` or `forceload remove`
+///
+/// Zero when the server reports that nothing changed, e.g. because the
+/// chunks were already force-loaded; `None` for unrecognized responses
+/// such as the "Too many chunks" error.
+///
+/// ```
+/// use rcon_cli::parsers::parse_forceload_change;
+///
+/// let response = "Marked 256 chunks in minecraft:overworld from [0, 0] to [15, 15] to be force loaded";
+/// assert_eq!(parse_forceload_change(response), Some(256));
+/// assert_eq!(parse_forceload_change("No chunks were marked for force loading"), Some(0));
+/// ```
+pub fn parse_forceload_change(response: &str) -> Option<u32> {
+    static SINGLE: OnceLock<Regex> = OnceLock::new();
+    static MULTIPLE: OnceLock<Regex> = OnceLock::new();
+
+    let response = response.trim();
+    if response.starts_with("No chunks were marked")
+        || response.starts_with("No chunks were removed")
+    {
+        return Some(0);
+    }
+    let single = regex(
+        &SINGLE,
+        r"^(?:Marked|Unmarked) chunk \[-?\d+, -?\d+\] in \S+",
+    );
+    if single.is_match(response) {
+        return Some(1);
+    }
+    let multiple = regex(&MULTIPLE, r"^(?:Marked|Unmarked) (\d+) chunks in \S+");
+    multiple.captures(response)?[1].parse().ok()
+}
+
+/// Parse the chunk list of `forceload query` without coordinates
+///
+/// ```
+/// use rcon_cli::parsers::parse_forceload_list;
+///
+/// let response = "There are 2 force loaded chunks in minecraft:overworld at: [0, 0], [-1, 3]";
+/// assert_eq!(parse_forceload_list(response), Some(vec![(0, 0), (-1, 3)]));
+/// ```
+pub fn parse_forceload_list(response: &str) -> Option<Vec<(i64, i64)>> {
+    static HEADER: OnceLock<Regex> = OnceLock::new();
+    static CHUNK: OnceLock<Regex> = OnceLock::new();
+
+    let response = response.trim();
+    if response.starts_with("No force loaded chunks were found") {
+        return Some(Vec::new());
+    }
+    let header = regex(
+        &HEADER,
+        r"^There (?:is|are) \d+ force loaded chunks? in \S+ at:\s*",
+    );
+    let body = &response[header.find(response)?.end()..];
+    let chunk = regex(&CHUNK, r"\[(-?\d+), (-?\d+)\]");
+    chunk
+        .captures_iter(body)
+        .map(|captures| Some((captures[1].parse().ok()?, captures[2].parse().ok()?)))
+        .collect()
+}
+
+/// Server software and version, as reported by `version`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerVersion {
+    /// Server software, e.g. `Paper` or `Vanilla`
+    pub flavor: String,
+    /// Game version, if the response includes one
+    pub version: Option<String>,
+}
+
+/// Known server implementations, checked in order against `version` output
+const FLAVORS: &[&str] = &[
+    "Folia",
+    "Purpur",
+    "Pufferfish",
+    "Paper",
+    "Spigot",
+    "CraftBukkit",
+    "NeoForge",
+    "Forge",
+    "Fabric",
+    "Quilt",
+    "Sponge",
+    "Velocity",
+    "Waterfall",
+    "BungeeCord",
+];
+
+/// Identify the server software from the response of `version`
+///
+/// Vanilla servers reject the command, which is reported as `Vanilla`
+/// without a version. Returns `None` for unrecognized responses.
+pub fn parse_server_version(response: &str) -> Option<ServerVersion> {
+    static MC_VERSION: OnceLock<Regex> = OnceLock::new();
+    static ANY_VERSION: OnceLock<Regex> = OnceLock::new();
+
+    let response = normalize(response);
+    let response = response.trim();
+    if response.starts_with("Unknown or incomplete command") {
+        return Some(ServerVersion {
+            flavor: "Vanilla".to_string(),
+            version: None,
+        });
+    }
+
+    let lowercase = response.to_lowercase();
+    let flavor = FLAVORS
+        .iter()
+        .find(|flavor| lowercase.contains(&flavor.to_lowercase()))?;
+
+    // Prefer the Minecraft version over the build number of the fork
+    let version = regex(&MC_VERSION, r"MC: (\d+\.\d+(?:\.\d+)?)")
+        .captures(response)
+        .or_else(|| regex(&ANY_VERSION, r"(\d+\.\d+(?:\.\d+)?)").captures(response))
+        .map(|captures| captures[1].to_string());
+
+    Some(ServerVersion {
+        flavor: flavor.to_string(),
+        version,
+    })
+}
+
+/// Ticks per second over a time window or in one dimension
+#[derive(Debug, Clone, PartialEq)]
+pub struct TpsSample {
+    /// Averaging window such as `1m`, a dimension, `overall` across
+    /// dimensions, or `current` for vanilla's recent average
+    pub label: String,
+    pub tps: f64,
+    /// Milliseconds per tick, where reported
+    pub mspt: Option<f64>,
+}
+
+/// Parse the response of Bukkit's `tps`
+///
+/// Paper marks values capped at 20 with a `*`.
+///
+/// ```
+/// use rcon_cli::parsers::parse_bukkit_tps;
+///
+/// let samples = parse_bukkit_tps("§6TPS from last 1m, 5m, 15m: §a*20.0, §a19.87, §e17.5").unwrap();
+/// assert_eq!(samples.len(), 3);
+/// assert_eq!((samples[1].label.as_str(), samples[1].tps), ("5m", 19.87));
+/// ```
+pub fn parse_bukkit_tps(response: &str) -> Option<Vec<TpsSample>> {
+    static TPS: OnceLock<Regex> = OnceLock::new();
+
+    let response = strip_formatting(response);
+    let captures = regex(&TPS, r"TPS from last ([^:]+):\s*([^\n]+)").captures(&response)?;
+    let samples: Vec<TpsSample> = captures[1]
+        .split(',')
+        .zip(captures[2].split(','))
+        .map(|(label, value)| {
+            Some(TpsSample {
+                label: label.trim().to_string(),
+                tps: value.trim().trim_start_matches('*').parse().ok()?,
+                mspt: None,
+            })
+        })
+        .collect::<Option<_>>()?;
+    (!samples.is_empty()).then_some(samples)
+}
+
+/// Parse the per-dimension response of `forge tps` or `neoforge tps`
+///
+/// ```
+/// use rcon_cli::parsers::parse_forge_tps;
+///
+/// let forge = "Dim minecraft:overworld (minecraft:overworld): Mean tick time: 2.500 ms. \
+///     Mean TPS: 20.000Overall: Mean tick time: 2.500 ms. Mean TPS: 20.000";
+/// let samples = parse_forge_tps(forge).unwrap();
+/// assert_eq!(samples[0].label, "minecraft:overworld");
+/// assert_eq!(samples[1].label, "overall");
+///
+/// let neoforge = "minecraft:overworld: 19.500 TPS (51.282 ms/tick)Overall: 19.500 TPS (51.282 ms/tick)";
+/// assert_eq!(parse_forge_tps(neoforge).unwrap()[0].mspt, Some(51.282));
+/// ```
+pub fn parse_forge_tps(response: &str) -> Option<Vec<TpsSample>> {
+    static FORGE: OnceLock<Regex> = OnceLock::new();
+    static NEOFORGE: OnceLock<Regex> = OnceLock::new();
+
+    let sample = |label: &str, tps: &str, mspt: &str| {
+        Some(TpsSample {
+            label: match label {
+                "Overall" => "overall".to_string(),
+                label => label.to_string(),
+            },
+            tps: tps.parse().ok()?,
+            mspt: Some(mspt.parse().ok()?),
+        })
+    };
+    let forge = regex(
+        &FORGE,
+        r"(?:Dim\s+(\S+)(?: \([^)]*\))?\s*|(Overall)):\s*Mean tick time: ([\d.]+) ms\. Mean TPS: (\d+(?:\.\d+)?)",
+    );
+    let mut samples: Vec<TpsSample> = forge
+        .captures_iter(response)
+        .filter_map(|captures| {
+            let label = captures.get(1).or(captures.get(2))?.as_str();
+            sample(label, &captures[4], &captures[3])
+        })
+        .collect();
+    if samples.is_empty() {
+        let neoforge = regex(
+            &NEOFORGE,
+            r"([a-z0-9_.-]+:[a-z0-9_./-]+|Overall): (\d+(?:\.\d+)?) TPS \(([\d.]+) ms/tick\)",
+        );
+        samples = neoforge
+            .captures_iter(response)
+            .filter_map(|captures| sample(&captures[1], &captures[2], &captures[3]))
+            .collect();
+    }
+    (!samples.is_empty()).then_some(samples)
+}
+
+/// Parse the response of vanilla `tick query` (1.20.3 and later)
+///
+/// The server averages recent ticks; it runs at the target rate unless a
+/// tick takes longer than the target allows.
+///
+/// ```
+/// use rcon_cli::parsers::parse_tick_query;
+///
+/// let response = "The game is running normallyTarget tick rate: 20.0 per second.\n\
+///     Average time per tick: 62.5ms (Target: 50.0ms)Percentiles: P50: 60.1ms P95: 70.2ms P99: 80.3ms, sample: 100";
+/// let sample = parse_tick_query(response).unwrap();
+/// assert_eq!((sample.tps, sample.mspt), (16.0, Some(62.5)));
+/// ```
+pub fn parse_tick_query(response: &str) -> Option<TpsSample> {
+    static RATE: OnceLock<Regex> = OnceLock::new();
+    static AVERAGE: OnceLock<Regex> = OnceLock::new();
+
+    let rate: f64 = regex(&RATE, r"Target tick rate: ([\d.]+) per second").captures(response)?[1]
+        .parse()
+        .ok()?;
+    let mspt: Option<f64> = regex(&AVERAGE, r"Average time per tick: ([\d.]+)ms")
+        .captures(response)
+        .and_then(|captures| captures[1].parse().ok());
+    let tps = if response.contains("The game is frozen") {
+        0.0
+    } else {
+        match mspt {
+            Some(mspt) if mspt > 0.0 => rate.min(1000.0 / mspt),
+            _ => rate,
+        }
+    };
+    Some(TpsSample {
+        label: "current".to_string(),
+        tps,
+        mspt,
+    })
+}
+
+/// An item stack from a player's `Inventory` data
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InventoryItem {
+    /// 0-8 hotbar, 9-35 main inventory, and before 1.21.5 also 100-103
+    /// armor (feet to head) and -106 offhand
+    pub slot: i32,
+    pub id: String,
+    pub count: u32,
+    /// Enchantment IDs and levels, including those stored in enchanted books
+    pub enchantments: Vec<(String, u32)>,
+}
+
+impl InventoryItem {
+    /// Name of the slot, e.g. `hotbar 1`, `inventory 10`, or `offhand`
+    pub fn slot_name(&self) -> String {
+        match self.slot {
+            0..=8 => format!("hotbar {}", self.slot + 1),
+            9..=35 => format!("inventory {}", self.slot - 8),
+            100 => "feet".to_string(),
+            101 => "legs".to_string(),
+            102 => "chest".to_string(),
+            103 => "head".to_string(),
+            -106 => "offhand".to_string(),
+            slot => slot.to_string(),
+        }
+    }
+}
+
+/// Parse the response of `data get entity <player> Inventory`
+///
+/// Items are read in the NBT layout of 1.13 and later, with counts and
+/// enchantments under `tag` before 1.20.5 and as item components since.
+///
+/// ```
+/// use rcon_cli::parsers::parse_inventory;
+///
+/// let response = r#"Steve has the following entity data: [{Slot: 0b, id: "minecraft:diamond_sword", count: 1, components: {"minecraft:enchantments": {"minecraft:sharpness": 5}}}, {Slot: 9b, id: "minecraft:torch", count: 64}]"#;
+/// let items = parse_inventory(response).unwrap();
+/// assert_eq!(items[0].enchantments, [("minecraft:sharpness".to_string(), 5)]);
+/// assert_eq!((items[1].slot_name().as_str(), items[1].count), ("inventory 1", 64));
+/// ```
+pub fn parse_inventory(response: &str) -> Option<Vec<InventoryItem>> {
+    let (_, data) = response.split_once(" has the following entity data: ")?;
+    let Tag::List(stacks) = Tag::parse(data).ok()? else {
+        return None;
+    };
+    stacks.iter().map(inventory_item).collect()
+}
+
+fn inventory_item(stack: &Tag) -> Option<InventoryItem> {
+    let number = |tag: Option<&Tag>| tag.and_then(Tag::as_f64).map(|value| value as i64);
+    let count = number(stack.get("count").or(stack.get("Count"))).unwrap_or(1);
+
+    let mut enchantments = Vec::new();
+    let mut add = |id: String, level: Option<i64>| {
+        enchantments.push((id, level.unwrap_or(1).max(0) as u32));
+    };
+    let tag = stack.get("tag");
+    for key in ["Enchantments", "StoredEnchantments"] {
+        if let Some(Tag::List(list)) = tag.and_then(|tag| tag.get(key)) {
+            for enchantment in list {
+                if let Some(id) = enchantment.get("id").and_then(Tag::as_str) {
+                    add(id.to_string(), number(enchantment.get("lvl")));
+                }
+            }
+        }
+    }
+    let components = stack.get("components");
+    for key in ["minecraft:enchantments", "minecraft:stored_enchantments"] {
+        let Some(levels) = components.and_then(|components| components.get(key)) else {
+            continue;
+        };
+        // Levels are nested under "levels" before 1.21.5
+        if let Tag::Compound(levels) = levels.get("levels").unwrap_or(levels) {
+            for (id, level) in levels {
+                add(id.clone(), number(Some(level)));
+            }
+        }
+    }
+
+    Some(InventoryItem {
+        slot: number(stack.get("Slot"))? as i32,
+        id: stack.get("id")?.as_str()?.to_string(),
+        count: u32::try_from(count).ok()?,
+        enchantments,
+    })
+}
+
+/// Lines of a LuckPerms response, without the `[LP]` prefix and formatting
+///
+/// LuckPerms sends one message per line, each starting with its prefix;
+/// splitting on the prefix as well keeps messages apart on servers that
+/// join them without line breaks. Indentation is kept, blank lines dropped.
+pub fn luckperms_lines(response: &str) -> Vec<String> {
+    strip_formatting(response)
+        .split('\n')
+        .flat_map(|line| line.split("[LP]"))
+        .map(|line| line.trim_end().strip_prefix(' ').unwrap_or(line.trim_end()))
+        .filter(|line| !line.trim().is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// A LuckPerms user or group, as reported by `lp user|group <name> info`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PermissionHolder {
+    pub name: String,
+    /// Only reported for users
+    pub uuid: Option<String>,
+    /// Whether a user is online; not reported for groups
+    pub online: Option<bool>,
+    /// A user's primary group
+    pub primary_group: Option<String>,
+    /// Only reported for groups
+    pub weight: Option<i32>,
+    /// Groups inherited from without an expiry
+    pub parents: Vec<String>,
+    /// Groups inherited from until they expire
+    pub temporary_parents: Vec<String>,
+}
+
+/// Parse the response of LuckPerms' `lp user <user> info` or
+/// `lp group <group> info`
+///
+/// ```
+/// use rcon_cli::parsers::parse_luckperms_info;
+///
+/// let response = "[LP] > User Info: Steve\n[LP] - UUID: 069a79f4-44e9-4726-a5be-fca90e38aaf5\n\
+///     [LP]     (type: official)\n[LP] - Status: Online\n[LP] - Parent Groups:\n\
+///     [LP]     > default\n[LP]     > builder\n[LP] - Contextual Data: (mode: server)\n\
+///     [LP]     Primary Group: builder";
+/// let user = parse_luckperms_info(response).unwrap();
+/// assert_eq!(user.parents, ["default", "builder"]);
+/// assert_eq!((user.online, user.primary_group.as_deref()), (Some(true), Some("builder")));
+/// ```
+pub fn parse_luckperms_info(response: &str) -> Option<PermissionHolder> {
+    #[derive(PartialEq)]
+    enum Section {
+        Parents,
+        TemporaryParents,
+        Other,
+    }
+
+    let lines = luckperms_lines(response);
+    let mut holder = lines.iter().find_map(|line| {
+        let line = line.trim().strip_prefix("> ")?;
+        let name = line
+            .strip_prefix("User Info: ")
+            .or_else(|| line.strip_prefix("Group Info: "))?;
+        Some(PermissionHolder {
+            name: name.trim().to_string(),
+            uuid: None,
+            online: None,
+            primary_group: None,
+            weight: None,
+            parents: Vec::new(),
+            temporary_parents: Vec::new(),
+        })
+    })?;
+
+    let mut section = Section::Other;
+    for line in &lines {
+        let line = line.trim();
+        if let Some(field) = line.strip_prefix("- ") {
+            section = match field {
+                "Parent Groups:" => Section::Parents,
+                "Temporary Parent Groups:" => Section::TemporaryParents,
+                _ => Section::Other,
+            };
+            if let Some(uuid) = field.strip_prefix("UUID: ") {
+                holder.uuid = Some(uuid.trim().to_string());
+            } else if let Some(status) = field.strip_prefix("Status: ") {
+                holder.online = Some(status.trim().eq_ignore_ascii_case("online"));
+            } else if let Some(weight) = field.strip_prefix("Weight: ") {
+                holder.weight = weight.trim().parse().ok();
+            }
+        } else if let Some(group) = line.strip_prefix("> ") {
+            // Groups are listed one per line, followed by their expiry
+            let group = group
+                .split_whitespace()
+                .next()
+                .unwrap_or_default()
+                .to_string();
+            match section {
+                Section::Parents => holder.parents.push(group),
+                Section::TemporaryParents => holder.temporary_parents.push(group),
+                Section::Other => {}
+            }
+        } else if let Some(group) = line.strip_prefix("Primary Group: ") {
+            holder.primary_group = Some(group.trim().to_string());
+        }
+    }
+    Some(holder)
+}
+
+/// The value a permission check resolved to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tristate {
+    True,
+    False,
+    /// Not set, so the server's default applies
+    Undefined,
+}
+
+impl Tristate {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Tristate::True => "true",
+            Tristate::False => "false",
+            Tristate::Undefined => "undefined",
+        }
+    }
+}
+
+/// Result of `lp user|group <name> permission check <permission>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PermissionCheck {
+    pub permission: String,
+    /// The value with inheritance applied
+    pub result: Tristate,
+}
+
+/// Parse the response of LuckPerms' `permission check`
+///
+/// LuckPerms 5.4 and later report the effective result with inheritance;
+/// earlier versions only whether the node is set on the holder itself.
+///
+/// ```
+/// use rcon_cli::parsers::{parse_permission_check, Tristate};
+///
+/// let response = "[LP] Permission information for essentials.fly:\n\
+///     [LP] - Steve does not have essentials.fly set.\n\
+///     [LP] - Steve inherits essentials.fly set to true from builder in context global.\n\
+///     [LP] Permission check for essentials.fly:\n[LP]     Result: true\n\
+///     [LP]     Processor: common.DirectProcessor";
+/// let check = parse_permission_check(response).unwrap();
+/// assert_eq!((check.permission.as_str(), check.result), ("essentials.fly", Tristate::True));
+/// ```
+pub fn parse_permission_check(response: &str) -> Option<PermissionCheck> {
+    static CHECK: OnceLock<Regex> = OnceLock::new();
+    static RESULT: OnceLock<Regex> = OnceLock::new();
+    static SET: OnceLock<Regex> = OnceLock::new();
+    static UNSET: OnceLock<Regex> = OnceLock::new();
+
+    let text = luckperms_lines(response).join("\n");
+    let tristate = |value: &str| match value {
+        "true" => Tristate::True,
+        "false" => Tristate::False,
+        _ => Tristate::Undefined,
+    };
+
+    if let Some(check) = regex(&CHECK, r"Permission check for (\S+?):").captures(&text) {
+        let result = regex(&RESULT, r"Result: (true|false|undefined)").captures(&text)?;
+        return Some(PermissionCheck {
+            permission: check[1].to_string(),
+            result: tristate(&result[1]),
+        });
+    }
+    if let Some(set) = regex(&SET, r"\S+ has (\S+) set to (true|false)").captures(&text) {
+        return Some(PermissionCheck {
+            permission: set[1].to_string(),
+            result: tristate(&set[2]),
+        });
+    }
+    regex(&UNSET, r"\S+ does not have (\S+?)\.? set")
+        .captures(&text)
+        .map(|unset| PermissionCheck {
+            permission: unset[1].to_string(),
+            result: Tristate::Undefined,
+        })
+}
+
+/// Parse an amount of money as Essentials formats it, e.g. `$1,234.50`
+///
+/// Currency symbols before or after the number are ignored. Grouping and
+/// decimal separators may be either `,` or `.`: a lone separator followed by
+/// exactly three digits groups thousands, any other last separator is the
+/// decimal point.
+///
+/// ```
+/// use rcon_cli::parsers::parse_money;
+///
+/// assert_eq!(parse_money("$1,234.50"), Some(1234.5));
+/// assert_eq!(parse_money("1.234,50€"), Some(1234.5));
+/// assert_eq!(parse_money("$-12"), Some(-12.0));
+/// assert_eq!(parse_money("$0,5"), Some(0.5));
+/// ```
+pub fn parse_money(text: &str) -> Option<f64> {
+    static NUMBER: OnceLock<Regex> = OnceLock::new();
+
+    let number = regex(&NUMBER, r"-?\d[\d.,]*").find(text)?;
+    let negative = text[..number.end()].contains('-');
+    let digits = number.as_str().trim_start_matches('-');
+    let decimal = digits.rfind([',', '.']).filter(|&index| {
+        let separator = digits.as_bytes()[index];
+        let mixed = digits
+            .bytes()
+            .any(|b| (b == b',' || b == b'.') && b != separator);
+        let lone = digits.bytes().filter(|&b| b == separator).count() == 1;
+        // "1,234" groups thousands, "0,5" and "1.234,50" have decimals
+        mixed || (lone && digits.len() - index - 1 != 3)
+    });
+    let normalized: String = digits
+        .char_indices()
+        .filter_map(|(index, c)| match c {
+            '0'..='9' => Some(c),
+            _ if Some(index) == decimal => Some('.'),
+            _ => None,
+        })
+        .collect();
+    let value: f64 = normalized.parse().ok()?;
+    Some(if negative { -value } else { value })
+}
+
+/// A player's balance, as reported by Essentials' `balance <player>`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Balance {
+    pub player: String,
+    pub balance: f64,
+}
+
+/// Parse the response of Essentials' `balance <player>`
+///
+/// ```
+/// use rcon_cli::parsers::parse_balance;
+///
+/// let balance = parse_balance("§aBalance of §fSteve§a:§c $1,234.50").unwrap();
+/// assert_eq!((balance.player.as_str(), balance.balance), ("Steve", 1234.5));
+/// ```
+pub fn parse_balance(response: &str) -> Option<Balance> {
+    static BALANCE: OnceLock<Regex> = OnceLock::new();
+
+    let text = strip_formatting(response);
+    let captures = regex(&BALANCE, r"Balance of (.+?):\s*(\S.*)").captures(text.trim())?;
+    Some(Balance {
+        player: captures[1].to_string(),
+        balance: parse_money(&captures[2])?,
+    })
+}
+
+/// Result of Essentials' `eco give` or `eco take`
+#[derive(Debug, Clone, PartialEq)]
+pub struct BalanceChange {
+    pub player: String,
+    /// Money added, negative when taken
+    pub amount: f64,
+    /// The balance afterwards
+    pub balance: f64,
+}
+
+/// Parse the response of Essentials' `eco give|take <player> <amount>`
+///
+/// ```
+/// use rcon_cli::parsers::parse_balance_change;
+///
+/// let response = "§a$25.00 taken from §fSteve§a account. New balance: $1,209.50";
+/// let change = parse_balance_change(response).unwrap();
+/// assert_eq!((change.amount, change.balance), (-25.0, 1209.5));
+/// ```
+pub fn parse_balance_change(response: &str) -> Option<BalanceChange> {
+    static CHANGE: OnceLock<Regex> = OnceLock::new();
+
+    let text = strip_formatting(response);
+    let captures = regex(
+        &CHANGE,
+        r"^(.+?) (added to|taken from) (.+?) account\. New balance: (.+)$",
+    )
+    .captures(text.trim())?;
+    let amount = parse_money(&captures[1])?;
+    Some(BalanceChange {
+        player: captures[3].to_string(),
+        amount: if &captures[2] == "added to" {
+            amount
+        } else {
+            -amount
+        },
+        balance: parse_money(&captures[4])?,
+    })
+}
+
+/// Result of `whitelist on` or `whitelist off`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WhitelistToggle {
+    /// Whether the whitelist is on now
+    pub enabled: bool,
+    /// Whether the command changed it; servers before 1.13 always claim so
+    pub changed: bool,
+}
+
+/// Parse the response of `whitelist on` or `whitelist off`
+///
+/// ```
+/// use rcon_cli::parsers::parse_whitelist_toggle;
+///
+/// let toggle = parse_whitelist_toggle("Whitelist is already turned on").unwrap();
+/// assert!(toggle.enabled && !toggle.changed);
+/// ```
+pub fn parse_whitelist_toggle(response: &str) -> Option<WhitelistToggle> {
+    static TOGGLE: OnceLock<Regex> = OnceLock::new();
+    static LEGACY: OnceLock<Regex> = OnceLock::new();
+
+    let text = strip_formatting(response);
+    if let Some(captures) =
+        regex(&TOGGLE, r"Whitelist is (now|already) turned (on|off)").captures(&text)
+    {
+        return Some(WhitelistToggle {
+            enabled: &captures[2] == "on",
+            changed: &captures[1] == "now",
+        });
+    }
+    regex(&LEGACY, r"Turned (on|off) the whitelist")
+        .captures(&text)
+        .map(|captures| WhitelistToggle {
+            enabled: &captures[1] == "on",
+            changed: true,
+        })
+}
@@ -0,0 +1,192 @@
+//! Trust-on-first-use server pins
+//!
+//! Plain RCON has no certificates to check, so a profile with `pin` set
+//! records two things the first time it connects: the addresses its host
+//! name resolves to, checked before the password is sent, and a
+//! fingerprint of the response to its `pin_command` (`seed` by default),
+//! checked right after logging in. When either changes the CLI warns, or
+//! with `pin = "strict"` refuses to go on, until `--accept-pin-change`
+//! records the new values.
+//!
+//! The seed tells worlds apart but isn't secret. A stronger pin reads back
+//! a random nonce stored on the server once:
+//!
+//! ```text
+//! data modify storage rcon-cli:pin nonce set value "k3v9Qm2xT8"
+//! ```
+//!
+//! together with `pin_command = "data get storage rcon-cli:pin nonce"`.
+//!
+//! Pins are kept per profile in `<data dir>/rcon-cli/known_servers.toml`.
+
+use crate::error::Result;
+use crate::state_file::StateFile;
+use base64::engine::general_purpose::STANDARD_NO_PAD;
+use base64::Engine;
+use ring::digest::{digest, SHA256};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+
+/// Command whose response identifies a server when the profile sets none
+pub const DEFAULT_PIN_COMMAND: &str = "seed";
+
+/// Default pin file, `<data dir>/rcon-cli/known_servers.toml`
+pub fn default_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("rcon-cli").join("known_servers.toml"))
+}
+
+/// What happens when a pin no longer matches
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PinMode {
+    /// Print a warning and connect anyway
+    Warn,
+    /// Refuse to connect
+    Strict,
+}
+
+/// How a profile's connections are pinned
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PinPolicy {
+    pub mode: PinMode,
+    /// Command whose response is fingerprinted
+    pub command: String,
+}
+
+/// What was recorded for a server
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Pin {
+    #[serde(default)]
+    pub addresses: Vec<SocketAddr>,
+    /// [`fingerprint`] of the response to the pin command
+    pub identity: Option<String>,
+}
+
+/// Outcome of comparing what a server shows with its pin
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PinCheck {
+    /// Nothing was pinned yet; what was seen is pinned now
+    New,
+    Unchanged,
+    /// The pin is kept; [`PinStore::update`] replaces it
+    Changed,
+}
+
+/// Fingerprint of a response, `SHA256:` and the unpadded base64 digest
+///
+/// Surrounding whitespace is ignored.
+///
+/// ```
+/// use rcon_cli::pins::fingerprint;
+///
+/// assert_eq!(fingerprint("Seed: [-4172144997902289642]\n"), fingerprint("Seed: [-4172144997902289642]"));
+/// assert!(fingerprint("Seed: [1]").starts_with("SHA256:"));
+/// ```
+pub fn fingerprint(response: &str) -> String {
+    let hash = digest(&SHA256, response.trim().as_bytes());
+    format!("SHA256:{}", STANDARD_NO_PAD.encode(hash.as_ref()))
+}
+
+/// A pin file
+///
+/// A [`StateFile`], so concurrent invocations don't lose each other's pins.
+///
+/// ```
+/// use rcon_cli::pins::{PinCheck, PinStore};
+///
+/// # fn main() -> rcon_cli::Result<()> {
+/// let path = std::env::temp_dir().join(format!("rcon-pins-{}.toml", std::process::id()));
+/// let store = PinStore::new(&path);
+/// let seen = ["203.0.113.5:25575".parse().unwrap()];
+/// assert_eq!(store.check_addresses("survival", &seen)?, PinCheck::New);
+/// assert_eq!(store.check_addresses("survival", &seen)?, PinCheck::Unchanged);
+///
+/// let moved = ["198.51.100.7:25575".parse().unwrap()];
+/// assert_eq!(store.check_addresses("survival", &moved)?, PinCheck::Changed);
+/// store.update("survival", |pin| pin.addresses = moved.to_vec())?;
+/// assert_eq!(store.check_addresses("survival", &moved)?, PinCheck::Unchanged);
+/// # std::fs::remove_file(&path).ok();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PinStore {
+    file: StateFile,
+}
+
+impl PinStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            file: StateFile::new("Pin file", path),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        self.file.path()
+    }
+
+    /// Every pin; a missing file has none
+    fn read(&self) -> Result<BTreeMap<String, Pin>> {
+        match self.file.read()? {
+            Some(source) => toml::from_str(&source).map_err(|e| self.file.error(e)),
+            None => Ok(BTreeMap::new()),
+        }
+    }
+
+    fn write(&self, pins: &BTreeMap<String, Pin>) -> Result<()> {
+        let contents = toml::to_string(pins).map_err(|e| self.file.error(e))?;
+        self.file.replace(&contents)
+    }
+
+    /// The pin of `server`, if any
+    pub fn get(&self, server: &str) -> Result<Option<Pin>> {
+        let _lock = self.file.lock()?;
+        Ok(self.read()?.remove(server))
+    }
+
+    /// Change the pin of `server`, creating it if needed
+    pub fn update(&self, server: &str, change: impl FnOnce(&mut Pin)) -> Result<()> {
+        let _lock = self.file.lock()?;
+        let mut pins = self.read()?;
+        change(pins.entry(server.to_string()).or_default());
+        self.write(&pins)
+    }
+
+    /// Compare the addresses a server resolved to with its pin
+    ///
+    /// Any pinned address counts as a match, so DNS records added next to
+    /// the pinned ones (or round-robin answers) don't raise alarms.
+    pub fn check_addresses(&self, server: &str, seen: &[SocketAddr]) -> Result<PinCheck> {
+        let _lock = self.file.lock()?;
+        let mut pins = self.read()?;
+        let pin = pins.entry(server.to_string()).or_default();
+        if pin.addresses.is_empty() {
+            pin.addresses = seen.to_vec();
+            self.write(&pins)?;
+            Ok(PinCheck::New)
+        } else if seen.iter().any(|address| pin.addresses.contains(address)) {
+            Ok(PinCheck::Unchanged)
+        } else {
+            Ok(PinCheck::Changed)
+        }
+    }
+
+    /// Compare the fingerprint of a server's pin command response with
+    /// its pin
+    pub fn check_identity(&self, server: &str, seen: &str) -> Result<PinCheck> {
+        let _lock = self.file.lock()?;
+        let mut pins = self.read()?;
+        let pin = pins.entry(server.to_string()).or_default();
+        match &pin.identity {
+            None => {
+                pin.identity = Some(seen.to_string());
+                self.write(&pins)?;
+                Ok(PinCheck::New)
+            }
+            Some(identity) if identity == seen => Ok(PinCheck::Unchanged),
+            Some(_) => Ok(PinCheck::Changed),
+        }
+    }
+}
@@ -0,0 +1,440 @@
+//! Playbooks: steps of commands with dependencies between them
+//!
+//! A playbook is a TOML file of `[[steps]]`, each sending its commands in
+//! order to one server. A step starts once every step it `depends_on` is
+//! done, so independent steps, e.g. on different servers of a fleet, run
+//! concurrently, up to a limit. A step's [`Severity`] decides what its
+//! failure means, like that of a script line: steps after a failed step
+//! are skipped, while those after a step that only warned or was ignored
+//! run as usual.
+//!
+//! ```toml
+//! [[steps]]
+//! name = "announce"
+//! profile = "survival"
+//! commands = ["say Restarting in 5 minutes"]
+//! severity = "warn_only"
+//!
+//! [[steps]]
+//! name = "save"
+//! profile = "survival"
+//! commands = ["save-all flush"]
+//! depends_on = ["announce"]
+//! severity = "critical"
+//! ```
+
+use crate::error::{RconError, Result};
+use crate::script::{ScriptLine, Severity};
+use futures_util::stream::{FuturesUnordered, StreamExt};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::Path;
+use toml::Spanned;
+
+/// A step of a playbook
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Step {
+    /// Name other steps depend on it by, unique within the playbook
+    pub name: String,
+    /// Profile of the server to run on; the selected server without one
+    #[serde(default)]
+    pub profile: Option<String>,
+    /// Commands sent in order over one connection
+    pub commands: Vec<String>,
+    /// Steps that must be done before this one starts
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// What a failure of the step means for the rest of the playbook
+    #[serde(default)]
+    pub severity: Severity,
+}
+
+/// How a step of a run ended
+#[derive(Debug)]
+pub enum StepStatus<T, E = RconError> {
+    Succeeded(T),
+    /// Failed with [`Severity::WarnOnly`]
+    Warned(E),
+    /// Failed with [`Severity::IgnoreErrors`]
+    Ignored(E),
+    Failed(E),
+    /// Not run, since a step it depends on failed or the run stopped
+    Skipped,
+}
+
+impl<T, E> StepStatus<T, E> {
+    /// Whether steps depending on this one can't run
+    pub fn is_blocking(&self) -> bool {
+        matches!(self, StepStatus::Failed(_) | StepStatus::Skipped)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PlaybookFile {
+    #[serde(default)]
+    steps: Vec<StepEntry>,
+}
+
+/// A step as written, with where its commands are
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StepEntry {
+    name: String,
+    #[serde(default)]
+    profile: Option<String>,
+    commands: Vec<Spanned<String>>,
+    #[serde(default)]
+    depends_on: Vec<String>,
+    #[serde(default)]
+    severity: Severity,
+}
+
+/// Steps with their dependencies checked
+#[derive(Debug, Clone)]
+pub struct Playbook {
+    steps: Vec<Step>,
+    /// 1-based line of each command of each step in the source, if parsed
+    lines: Vec<Vec<usize>>,
+    /// Indices of the steps each step depends on
+    dependencies: Vec<Vec<usize>>,
+    /// Indices of all steps, each after the steps it depends on
+    order: Vec<usize>,
+}
+
+impl Playbook {
+    /// Parse a playbook, rejecting duplicate names, unknown dependencies,
+    /// and cycles
+    ///
+    /// ```
+    /// use rcon_cli::playbook::Playbook;
+    ///
+    /// let playbook = Playbook::parse(r#"
+    ///     [[steps]]
+    ///     name = "save"
+    ///     commands = ["save-all flush"]
+    ///     depends_on = ["announce"]
+    ///
+    ///     [[steps]]
+    ///     name = "announce"
+    ///     commands = ["say Saving"]
+    /// "#).unwrap();
+    /// assert_eq!(playbook.steps().len(), 2);
+    ///
+    /// let cycle = r#"
+    ///     [[steps]]
+    ///     name = "a"
+    ///     commands = ["list"]
+    ///     depends_on = ["a"]
+    /// "#;
+    /// assert!(Playbook::parse(cycle).is_err());
+    /// ```
+    pub fn parse(source: &str) -> Result<Self> {
+        let file: PlaybookFile = toml::from_str(source)
+            .map_err(|e| RconError::Script(format!("invalid playbook: {}", e.message())))?;
+        let line_of = |offset: usize| source[..offset].matches('\n').count() + 1;
+        let (steps, lines) = file
+            .steps
+            .into_iter()
+            .map(|entry| {
+                let lines = entry
+                    .commands
+                    .iter()
+                    .map(|command| line_of(command.span().start))
+                    .collect();
+                let step = Step {
+                    name: entry.name,
+                    profile: entry.profile,
+                    commands: entry
+                        .commands
+                        .into_iter()
+                        .map(Spanned::into_inner)
+                        .collect(),
+                    depends_on: entry.depends_on,
+                    severity: entry.severity,
+                };
+                (step, lines)
+            })
+            .unzip();
+        Ok(Self {
+            lines,
+            ..Self::new(steps)?
+        })
+    }
+
+    /// Read and parse a playbook file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path).map_err(|e| {
+            RconError::Script(format!(
+                "Failed to read playbook '{}': {}",
+                path.display(),
+                e
+            ))
+        })?;
+        Self::parse(&source).map_err(|e| match e {
+            RconError::Script(message) => {
+                RconError::Script(format!("{}: {}", path.display(), message))
+            }
+            e => e,
+        })
+    }
+
+    pub fn new(steps: Vec<Step>) -> Result<Self> {
+        let invalid = |message: String| RconError::Script(message);
+        let mut indices = HashMap::new();
+        for (index, step) in steps.iter().enumerate() {
+            if step.commands.is_empty() {
+                return Err(invalid(format!("step '{}' has no commands", step.name)));
+            }
+            if indices.insert(step.name.as_str(), index).is_some() {
+                return Err(invalid(format!("step '{}' is defined twice", step.name)));
+            }
+        }
+        let dependencies = steps
+            .iter()
+            .map(|step| {
+                step.depends_on
+                    .iter()
+                    .map(|name| {
+                        indices.get(name.as_str()).copied().ok_or_else(|| {
+                            invalid(format!(
+                                "step '{}' depends on unknown step '{}'",
+                                step.name, name
+                            ))
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        // Kahn's algorithm, keeping the file's order among ready steps
+        let mut waiting: Vec<usize> = dependencies.iter().map(Vec::len).collect();
+        let mut order: Vec<usize> = (0..steps.len()).filter(|&i| waiting[i] == 0).collect();
+        let mut next = 0;
+        while next < order.len() {
+            let done = order[next];
+            next += 1;
+            for (index, deps) in dependencies.iter().enumerate() {
+                for _ in deps.iter().filter(|&&dep| dep == done) {
+                    waiting[index] -= 1;
+                    if waiting[index] == 0 {
+                        order.push(index);
+                    }
+                }
+            }
+        }
+        if let Some(index) = (0..steps.len()).find(|&i| waiting[i] > 0) {
+            return Err(invalid(format!(
+                "step '{}' is part of a dependency cycle",
+                steps[index].name
+            )));
+        }
+
+        Ok(Self {
+            lines: steps
+                .iter()
+                .map(|step| vec![0; step.commands.len()])
+                .collect(),
+            steps,
+            dependencies,
+            order,
+        })
+    }
+
+    /// Steps in the order of the file
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+
+    /// Every step's commands as script lines, e.g. to
+    /// [lint](crate::lint::Linter::lint) them
+    ///
+    /// Lines are numbered as in the parsed source; 0 for playbooks made
+    /// with [`new`](Self::new).
+    ///
+    /// ```
+    /// use rcon_cli::playbook::Playbook;
+    ///
+    /// let playbook = Playbook::parse(r#"
+    /// [[steps]]
+    /// name = "save"
+    /// commands = [
+    ///     "say Saving",
+    ///     "save-all flush",
+    /// ]
+    /// "#).unwrap();
+    /// let lines = playbook.script_lines();
+    /// assert_eq!((lines[1].line_number, lines[1].command.as_str()), (6, "save-all flush"));
+    /// ```
+    pub fn script_lines(&self) -> Vec<ScriptLine> {
+        self.steps
+            .iter()
+            .zip(&self.lines)
+            .flat_map(|(step, lines)| {
+                step.commands
+                    .iter()
+                    .zip(lines)
+                    .map(|(command, &line_number)| ScriptLine {
+                        line_number,
+                        command: command.clone(),
+                        severity: step.severity,
+                    })
+            })
+            .collect()
+    }
+
+    /// Steps grouped by how many steps must run before them
+    ///
+    /// The steps of a stage don't depend on each other; each depends on
+    /// at least one step of the stage before.
+    ///
+    /// ```
+    /// use rcon_cli::playbook::Playbook;
+    ///
+    /// let playbook = Playbook::parse(r#"
+    ///     [[steps]]
+    ///     name = "announce"
+    ///     commands = ["say Restarting"]
+    ///     [[steps]]
+    ///     name = "save-survival"
+    ///     profile = "survival"
+    ///     commands = ["save-all flush"]
+    ///     depends_on = ["announce"]
+    ///     [[steps]]
+    ///     name = "save-creative"
+    ///     profile = "creative"
+    ///     commands = ["save-all flush"]
+    ///     depends_on = ["announce"]
+    /// "#).unwrap();
+    ///
+    /// let stages: Vec<Vec<&str>> = playbook
+    ///     .stages()
+    ///     .iter()
+    ///     .map(|stage| stage.iter().map(|step| step.name.as_str()).collect())
+    ///     .collect();
+    /// assert_eq!(stages, [vec!["announce"], vec!["save-survival", "save-creative"]]);
+    /// ```
+    pub fn stages(&self) -> Vec<Vec<&Step>> {
+        let mut depths = vec![0; self.steps.len()];
+        for &index in &self.order {
+            depths[index] = self.dependencies[index]
+                .iter()
+                .map(|&dep| depths[dep] + 1)
+                .max()
+                .unwrap_or(0);
+        }
+        let count = depths.iter().max().map_or(0, |depth| depth + 1);
+        let mut stages = vec![Vec::new(); count];
+        for (step, depth) in self.steps.iter().zip(depths) {
+            stages[depth].push(step);
+        }
+        stages
+    }
+
+    /// Run every step with `run_step`, at most `concurrency` at a time
+    ///
+    /// A step starts as soon as the steps it depends on are done. Once a
+    /// step fails, nothing new starts unless `continue_on_error` is set,
+    /// in which case only the steps depending on it are skipped; a failed
+    /// [`Severity::Critical`] step stops the run either way. Steps already
+    /// running are always waited for. Returns how each step ended, in the
+    /// order of [`steps`](Self::steps).
+    ///
+    /// ```
+    /// use rcon_cli::playbook::{Playbook, StepStatus};
+    /// use rcon_cli::RconError;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let playbook = Playbook::parse(r#"
+    ///     [[steps]]
+    ///     name = "broadcast"
+    ///     commands = ["say Back soon"]
+    ///     severity = "warn_only"
+    ///     [[steps]]
+    ///     name = "backup"
+    ///     commands = ["save-all flush"]
+    ///     [[steps]]
+    ///     name = "stop"
+    ///     commands = ["stop"]
+    ///     depends_on = ["broadcast", "backup"]
+    /// "#).unwrap();
+    ///
+    /// let statuses = playbook
+    ///     .run(2, false, |step| async move {
+    ///         match step.name.as_str() {
+    ///             "broadcast" => Err(RconError::CommandFailed("no players".into())),
+    ///             _ => Ok(step.commands.len()),
+    ///         }
+    ///     })
+    ///     .await;
+    /// assert!(matches!(statuses[0], StepStatus::Warned(_)));
+    /// assert!(matches!(statuses[2], StepStatus::Succeeded(1)));
+    /// # }
+    /// ```
+    pub async fn run<'a, T, E, F, Fut>(
+        &'a self,
+        concurrency: usize,
+        continue_on_error: bool,
+        mut run_step: F,
+    ) -> Vec<StepStatus<T, E>>
+    where
+        F: FnMut(&'a Step) -> Fut,
+        Fut: Future<Output = std::result::Result<T, E>> + 'a,
+    {
+        let concurrency = concurrency.max(1);
+        let mut statuses: Vec<Option<StepStatus<T, E>>> = self.steps.iter().map(|_| None).collect();
+        let mut started = vec![false; self.steps.len()];
+        let mut stopping = false;
+        let mut running = FuturesUnordered::new();
+
+        loop {
+            // Dependencies come first in `order`, so skips cascade in one pass
+            for &index in self.order.iter().filter(|_| !stopping) {
+                if started[index] {
+                    continue;
+                }
+                let deps = &self.dependencies[index];
+                let status = |dep: &usize| statuses[*dep].as_ref();
+                if deps
+                    .iter()
+                    .any(|dep| status(dep).is_some_and(|s| s.is_blocking()))
+                {
+                    started[index] = true;
+                    statuses[index] = Some(StepStatus::Skipped);
+                } else if running.len() < concurrency
+                    && deps.iter().all(|dep| status(dep).is_some())
+                {
+                    started[index] = true;
+                    let step = run_step(&self.steps[index]);
+                    running.push(async move { (index, step.await) });
+                }
+            }
+
+            let Some((index, result)) = running.next().await else {
+                break;
+            };
+            let severity = self.steps[index].severity;
+            let status = match result {
+                Ok(output) => StepStatus::Succeeded(output),
+                Err(e) => match severity {
+                    Severity::IgnoreErrors => StepStatus::Ignored(e),
+                    Severity::WarnOnly => StepStatus::Warned(e),
+                    Severity::Normal | Severity::Critical => {
+                        stopping |= severity == Severity::Critical || !continue_on_error;
+                        StepStatus::Failed(e)
+                    }
+                },
+            };
+            statuses[index] = Some(status);
+        }
+
+        statuses
+            .into_iter()
+            .map(|status| status.unwrap_or(StepStatus::Skipped))
+            .collect()
+    }
+}
@@ -0,0 +1,404 @@
+//! Client-side command policies
+//!
+//! A [`CommandPolicy`] limits the commands a client sends to an allowlist
+//! and rejects everything else before it reaches the server. `--read-only`
+//! and `read_only = true` in a profile apply [`CommandPolicy::read_only`],
+//! so moderators or dashboards can be given observation access through
+//! shared credentials.
+//!
+//! Front ends serving several clients over one connection, like the `web`
+//! feature's router, give each client a [`Role`] from an [`AccessPolicy`]
+//! instead, by API token or client IP.
+//!
+//! [`ExclusiveCommands`] keep commands like `stop` from interleaving with
+//! others sent over connections sharing a config, such as a pool's.
+
+use crate::error::{RconError, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+use std::sync::Arc;
+use tokio::sync::{OwnedRwLockReadGuard, OwnedRwLockWriteGuard, RwLock};
+
+/// Queries allowed by [`CommandPolicy::read_only`]
+pub const READ_ONLY_COMMANDS: &[&str] = &[
+    "list",
+    "version",
+    "seed",
+    "data get",
+    "worldborder get",
+    "time query",
+    // TPS on Paper and Spigot, Forge, NeoForge, and vanilla 1.20.3+
+    "tps",
+    "forge tps",
+    "neoforge tps",
+    "tick query",
+];
+
+/// An allowlist of commands
+///
+/// Entries match the leading words of a command, case-insensitively and
+/// ignoring a leading `/`, so `list` also allows `list uuids` but not
+/// `listen`. A `*` word matches any word, so `*` alone allows everything.
+///
+/// ```
+/// use rcon_cli::policy::CommandPolicy;
+///
+/// let policy = CommandPolicy::read_only();
+/// assert!(policy.permits("list uuids"));
+/// assert!(policy.permits("/data get entity @p Pos"));
+/// assert!(!policy.permits("data merge entity @p {Health:20f}"));
+/// assert!(policy.check("stop").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandPolicy {
+    /// Shown when a command is rejected, e.g. `read-only mode`
+    name: String,
+    /// Lowercase leading words of each allowed command
+    allowed: Vec<Vec<String>>,
+}
+
+/// Lowercase words of a command, without a leading `/`
+fn words(command: &str) -> Vec<String> {
+    command
+        .trim()
+        .trim_start_matches('/')
+        .split_whitespace()
+        .map(str::to_lowercase)
+        .collect()
+}
+
+impl CommandPolicy {
+    /// Allow only commands starting with one of `commands`
+    pub fn allow<I, S>(name: impl Into<String>, commands: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Self {
+            name: name.into(),
+            allowed: commands
+                .into_iter()
+                .map(|command| words(command.as_ref()))
+                .filter(|words| !words.is_empty())
+                .collect(),
+        }
+    }
+
+    /// Allow the queries in [`READ_ONLY_COMMANDS`]
+    pub fn read_only() -> Self {
+        Self::allow("read-only mode", READ_ONLY_COMMANDS)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether `command` may be sent
+    pub fn permits(&self, command: &str) -> bool {
+        let words = words(command);
+        self.allowed.iter().any(|allowed| {
+            words.len() >= allowed.len()
+                && words.iter().zip(allowed).all(|(a, b)| b == "*" || a == b)
+        })
+    }
+
+    /// Reject `command` with [`RconError::CommandDenied`] unless permitted
+    pub fn check(&self, command: &str) -> Result<()> {
+        if self.permits(command) {
+            return Ok(());
+        }
+        Err(RconError::CommandDenied(format!(
+            "'{}' is not allowed in {}",
+            command.trim(),
+            self.name
+        )))
+    }
+}
+
+/// A `[roles.<name>]` entry of an [`AccessConfig`]
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RoleConfig {
+    /// Allowed commands, see [`CommandPolicy`]
+    #[serde(default)]
+    pub commands: Vec<String>,
+    /// Also allow [`READ_ONLY_COMMANDS`]
+    #[serde(default)]
+    pub read_only: bool,
+    /// API tokens of the clients with this role
+    #[serde(default)]
+    pub tokens: Vec<String>,
+    /// Client addresses with this role, as IPs or CIDR networks
+    #[serde(default)]
+    pub networks: Vec<String>,
+}
+
+/// Roles and the clients they are given to
+///
+/// ```toml
+/// [roles.admin]
+/// commands = ["*"]
+/// tokens = ["0d6f..."]
+///
+/// [roles.monitoring]
+/// read_only = true
+/// networks = ["10.0.0.0/8", "192.168.1.20"]
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AccessConfig {
+    #[serde(default)]
+    pub roles: BTreeMap<String, RoleConfig>,
+}
+
+/// A named set of allowed commands
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Role {
+    name: String,
+    policy: CommandPolicy,
+}
+
+impl Role {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn policy(&self) -> &CommandPolicy {
+        &self.policy
+    }
+
+    /// Reject `command` with [`RconError::CommandDenied`] unless the role
+    /// allows it
+    pub fn check(&self, command: &str) -> Result<()> {
+        self.policy.check(command)
+    }
+}
+
+/// An IP address or CIDR network
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Network {
+    address: IpAddr,
+    prefix: u8,
+}
+
+impl Network {
+    fn parse(network: &str) -> Result<Self> {
+        let invalid = || RconError::InvalidConfig(format!("Invalid network '{}'", network));
+        let (address, prefix) = match network.split_once('/') {
+            Some((address, prefix)) => (address, Some(prefix)),
+            None => (network, None),
+        };
+        let address = address
+            .trim()
+            .parse::<IpAddr>()
+            .map_err(|_| invalid())?
+            .to_canonical();
+        let bits = if address.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(prefix) => prefix.trim().parse().map_err(|_| invalid())?,
+            None => bits,
+        };
+        if prefix > bits {
+            return Err(invalid());
+        }
+        Ok(Self { address, prefix })
+    }
+
+    fn contains(&self, ip: IpAddr) -> bool {
+        // The leading `prefix` of `width` bits
+        let leading = |bits: u128, width: u8| match self.prefix {
+            0 => 0,
+            prefix => bits >> (width - prefix),
+        };
+        match (self.address, ip.to_canonical()) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                leading(u32::from(network).into(), 32) == leading(u32::from(ip).into(), 32)
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                leading(network.into(), 128) == leading(ip.into(), 128)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Compare tokens in a time independent of where they differ
+fn tokens_match(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
+
+/// Gives clients of a shared connection their [`Role`]
+///
+/// A client presenting a known API token gets that token's role; others
+/// get the role of the most specific network containing their address, and
+/// no role at all if none does.
+///
+/// ```
+/// use rcon_cli::policy::{AccessConfig, AccessPolicy};
+///
+/// let config: AccessConfig = toml::from_str(r#"
+///     [roles.admin]
+///     commands = ["*"]
+///     tokens = ["admin-token"]
+///
+///     [roles.monitoring]
+///     read_only = true
+///     networks = ["10.0.0.0/8"]
+/// "#).unwrap();
+/// let access = AccessPolicy::new(&config).unwrap();
+///
+/// let admin = access.role(Some("admin-token"), None).unwrap();
+/// assert!(admin.check("stop").is_ok());
+///
+/// let monitoring = access.role(None, Some("10.1.2.3".parse().unwrap())).unwrap();
+/// assert!(monitoring.check("list").is_ok());
+/// assert!(monitoring.check("stop").is_err());
+///
+/// assert!(access.role(Some("guess"), Some("192.0.2.1".parse().unwrap())).is_none());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct AccessPolicy {
+    roles: Vec<Role>,
+    /// Token and index of its role
+    tokens: Vec<(String, usize)>,
+    /// Network and index of its role
+    networks: Vec<(Network, usize)>,
+}
+
+impl AccessPolicy {
+    /// Gather the roles of a config, checking its tokens and networks
+    pub fn new(config: &AccessConfig) -> Result<Self> {
+        let mut access = Self::default();
+        for (name, role) in &config.roles {
+            let index = access.roles.len();
+            let read_only = READ_ONLY_COMMANDS.iter().filter(|_| role.read_only);
+            let commands = role
+                .commands
+                .iter()
+                .map(String::as_str)
+                .chain(read_only.copied());
+            access.roles.push(Role {
+                name: name.clone(),
+                policy: CommandPolicy::allow(format!("the '{}' role", name), commands),
+            });
+            for token in &role.tokens {
+                if token.is_empty() {
+                    return Err(RconError::InvalidConfig(format!(
+                        "Role '{}' has an empty token",
+                        name
+                    )));
+                }
+                if access.tokens.iter().any(|(other, _)| other == token) {
+                    return Err(RconError::InvalidConfig(format!(
+                        "Role '{}' shares a token with another role",
+                        name
+                    )));
+                }
+                access.tokens.push((token.clone(), index));
+            }
+            for network in &role.networks {
+                access.networks.push((Network::parse(network)?, index));
+            }
+        }
+        Ok(access)
+    }
+
+    pub fn roles(&self) -> &[Role] {
+        &self.roles
+    }
+
+    /// The role of a client, by its API token or else its address
+    pub fn role(&self, token: Option<&str>, ip: Option<IpAddr>) -> Option<&Role> {
+        // Every token is compared, so timing reveals nothing about them
+        let by_token = token.and_then(|token| {
+            self.tokens.iter().fold(None, |found, (known, index)| {
+                found.or(tokens_match(known, token).then_some(*index))
+            })
+        });
+        let by_network = || {
+            let ip = ip?;
+            self.networks
+                .iter()
+                .filter(|(network, _)| network.contains(ip))
+                .max_by_key(|(network, _)| network.prefix)
+                .map(|(_, index)| *index)
+        };
+        by_token.or_else(by_network).map(|index| &self.roles[index])
+    }
+}
+
+/// Commands that run alone across every connection sharing them
+///
+/// Entries match the leading words of a command like those of a
+/// [`CommandPolicy`]. Clones share their turns, so the clones in the
+/// configs of a pool's connections order commands across all of them:
+/// other commands run concurrently, while an exclusive one waits for those
+/// in flight, and commands arriving after it wait for it in turn.
+///
+/// ```
+/// use rcon_cli::policy::ExclusiveCommands;
+/// use std::time::Duration;
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let exclusive = ExclusiveCommands::new(["stop", "reload"]);
+/// assert!(exclusive.is_exclusive("reload confirm"));
+///
+/// let list = exclusive.turn("list").await;
+/// let _tps = exclusive.turn("tps").await;
+/// let stop = tokio::time::timeout(Duration::from_millis(10), exclusive.turn("stop"));
+/// assert!(stop.await.is_err());
+/// # drop(list);
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExclusiveCommands {
+    commands: CommandPolicy,
+    turns: Arc<RwLock<()>>,
+}
+
+/// Held while a command runs, see [`ExclusiveCommands::turn`]
+#[derive(Debug)]
+pub struct CommandTurn {
+    _shared: Option<OwnedRwLockReadGuard<()>>,
+    _exclusive: Option<OwnedRwLockWriteGuard<()>>,
+}
+
+impl ExclusiveCommands {
+    pub fn new<I, S>(commands: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Self {
+            commands: CommandPolicy::allow("exclusive commands", commands),
+            turns: Arc::new(RwLock::new(())),
+        }
+    }
+
+    /// Whether `command` runs alone
+    pub fn is_exclusive(&self, command: &str) -> bool {
+        self.commands.permits(command)
+    }
+
+    /// Wait until `command` may be sent, in the order commands arrive
+    pub async fn turn(&self, command: &str) -> CommandTurn {
+        let turns = Arc::clone(&self.turns);
+        if self.is_exclusive(command) {
+            CommandTurn {
+                _shared: None,
+                _exclusive: Some(turns.write_owned().await),
+            }
+        } else {
+            CommandTurn {
+                _shared: Some(turns.read_owned().await),
+                _exclusive: None,
+            }
+        }
+    }
+}
@@ -0,0 +1,104 @@
+//! Connection managers for the `deadpool` and `bb8` pools
+//!
+//! Web services that already pool database connections with one of these
+//! crates can manage RCON connections the same way. Enable the `deadpool`
+//! or `bb8` feature and hand an [`RconManager`] to the pool:
+//!
+//! ```no_run
+//! use rcon_cli::pool::RconManager;
+//! use rcon_cli::RconConfig;
+//!
+//! # #[cfg(feature = "deadpool")]
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let config = RconConfig::new("127.0.0.1:25575".parse()?, "secret");
+//! let pool = deadpool::managed::Pool::<RconManager>::builder(RconManager::new(config))
+//!     .max_size(4)
+//!     .build()?;
+//! let response = pool.get().await?.execute_command("list").await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Recycled connections are checked without a round trip; a connection
+//! found closed is replaced by a fresh one.
+//!
+//! Commands like `stop` or `reload` shouldn't interleave with others sent
+//! over the pool's other connections. Marked with
+//! [`RconManager::with_exclusive_commands`], they wait for the commands in
+//! flight and hold back later ones until they are answered.
+
+use crate::client::{RconClient, RconConfig};
+use crate::error::RconError;
+use crate::policy::ExclusiveCommands;
+
+/// Creates and checks pooled connections to one server
+#[derive(Debug, Clone)]
+pub struct RconManager {
+    config: RconConfig,
+}
+
+impl RconManager {
+    pub fn new(config: RconConfig) -> Self {
+        Self { config }
+    }
+
+    /// Get the configuration new connections are made with
+    pub fn config(&self) -> &RconConfig {
+        &self.config
+    }
+
+    /// Run `commands` alone across all of the pool's connections, see
+    /// [`ExclusiveCommands`]
+    pub fn with_exclusive_commands<I, S>(mut self, commands: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.config = self
+            .config
+            .with_exclusive_commands(ExclusiveCommands::new(commands));
+        self
+    }
+}
+
+#[cfg(feature = "deadpool")]
+impl deadpool::managed::Manager for RconManager {
+    type Type = RconClient;
+    type Error = RconError;
+
+    async fn create(&self) -> Result<RconClient, RconError> {
+        RconClient::connect(self.config.clone()).await
+    }
+
+    async fn recycle(
+        &self,
+        client: &mut RconClient,
+        _metrics: &deadpool::managed::Metrics,
+    ) -> deadpool::managed::RecycleResult<RconError> {
+        if client.is_closed() {
+            return Err(deadpool::managed::RecycleError::message(
+                "Connection was closed",
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "bb8")]
+impl bb8::ManageConnection for RconManager {
+    type Connection = RconClient;
+    type Error = RconError;
+
+    async fn connect(&self) -> Result<RconClient, RconError> {
+        RconClient::connect(self.config.clone()).await
+    }
+
+    /// Only called with `test_on_check_out`, which costs a round trip
+    async fn is_valid(&self, client: &mut RconClient) -> Result<(), RconError> {
+        client.ping().await
+    }
+
+    fn has_broken(&self, client: &mut RconClient) -> bool {
+        client.is_closed()
+    }
+}
@@ -0,0 +1,108 @@
+//! Connection progress reporting
+//!
+//! A client configured with a [`ConnectProgress`] reports each phase of
+//! establishing a session, on the first connection and on every reconnect,
+//! so callers can show where a slow server is stuck. Addresses are resolved
+//! before a configuration exists, so callers using [`RconConfig::resolve`]
+//! report [`ConnectPhase::Resolving`] themselves.
+//!
+//! [`RconConfig::resolve`]: crate::RconConfig::resolve
+
+use std::fmt;
+use std::sync::Arc;
+use tokio::sync::watch;
+
+/// Where a connection attempt is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConnectPhase {
+    /// Looking up the server's addresses
+    Resolving,
+    /// Opening the TCP connection
+    Connecting,
+    /// Logging in, including tries of fallback passwords
+    Authenticating,
+    /// Logged in and the `on_connect`/`on_reconnect` hook has run
+    Ready,
+    /// The attempt failed; another one may follow
+    Failed,
+}
+
+impl ConnectPhase {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ConnectPhase::Resolving => "resolving",
+            ConnectPhase::Connecting => "connecting",
+            ConnectPhase::Authenticating => "authenticating",
+            ConnectPhase::Ready => "ready",
+            ConnectPhase::Failed => "failed",
+        }
+    }
+
+    /// Whether an attempt is still under way
+    pub fn is_pending(self) -> bool {
+        matches!(
+            self,
+            ConnectPhase::Resolving | ConnectPhase::Connecting | ConnectPhase::Authenticating
+        )
+    }
+}
+
+impl fmt::Display for ConnectPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Callback receiving each phase as it starts
+pub type PhaseHook = Arc<dyn Fn(ConnectPhase) + Send + Sync>;
+
+/// Where a client reports its connection phases
+///
+/// The callback runs on the connecting task, so it should return quickly;
+/// [`ConnectProgress::watch`] hands phases to a watch channel instead.
+///
+/// ```
+/// use rcon_cli::progress::{ConnectPhase, ConnectProgress};
+/// use rcon_cli::RconConfig;
+///
+/// let (progress, phases) = ConnectProgress::watch();
+/// let config = RconConfig::new("127.0.0.1:25575".parse().unwrap(), "secret")
+///     .with_progress(progress.clone());
+///
+/// progress.report(ConnectPhase::Connecting);
+/// assert_eq!(*phases.borrow(), ConnectPhase::Connecting);
+/// ```
+#[derive(Clone)]
+pub struct ConnectProgress {
+    on_phase: PhaseHook,
+}
+
+impl ConnectProgress {
+    pub fn new<F>(on_phase: F) -> Self
+    where
+        F: Fn(ConnectPhase) + Send + Sync + 'static,
+    {
+        Self {
+            on_phase: Arc::new(on_phase),
+        }
+    }
+
+    /// Report phases to a watch channel, starting at `Resolving`
+    pub fn watch() -> (Self, watch::Receiver<ConnectPhase>) {
+        let (sender, receiver) = watch::channel(ConnectPhase::Resolving);
+        let progress = Self::new(move |phase| {
+            sender.send_replace(phase);
+        });
+        (progress, receiver)
+    }
+
+    pub fn report(&self, phase: ConnectPhase) {
+        (self.on_phase)(phase);
+    }
+}
+
+impl fmt::Debug for ConnectProgress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConnectProgress").finish_non_exhaustive()
+    }
+}
@@ -1,3 +1,4 @@
+use crate::dialect::{Dialect, MinecraftJava};
 use crate::error::{RconError, Result};
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use std::io::Cursor;
@@ -15,8 +16,31 @@ pub const MAX_REQUEST_PAYLOAD_SIZE: usize = 1446;
 /// Maximum payload size for server-to-client packets
 pub const MAX_RESPONSE_PAYLOAD_SIZE: usize = 4096;
 
+/// Smallest valid body: request ID, type, and the two terminating null bytes
+pub const MIN_PACKET_LENGTH: usize = 4 + 4 + 2;
+
+/// Largest packet body accepted from the wire, regardless of dialect
+pub const MAX_PACKET_LENGTH: usize = 16 * 1024 * 1024;
+
+/// Validate a length prefix read from the wire
+///
+/// Returns the body length in bytes, or `InvalidPacket` for negative,
+/// too-short, or unreasonably large values.
+pub fn validate_packet_length(declared: i32) -> Result<usize> {
+    usize::try_from(declared)
+        .ok()
+        .filter(|length| (MIN_PACKET_LENGTH..=MAX_PACKET_LENGTH).contains(length))
+        .ok_or_else(|| {
+            RconError::InvalidPacket(format!(
+                "Invalid packet length: {} (expected {}..={})",
+                declared, MIN_PACKET_LENGTH, MAX_PACKET_LENGTH
+            ))
+        })
+}
+
 /// Represents an RCON packet
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct RconPacket {
     pub request_id: i32,
     pub packet_type: i32,
@@ -43,16 +67,23 @@ impl RconPacket {
         Self::new(request_id, packet_type::EXECCOMMAND, command)
     }
 
-    /// Serialize the packet to bytes
+    /// Serialize the packet to bytes using the Minecraft dialect
     pub fn to_bytes(&self) -> Result<Vec<u8>> {
-        let payload_bytes = self.payload.as_bytes();
+        self.encode(&MinecraftJava)
+    }
+
+    /// Serialize the packet to bytes using the given dialect's limits and encoding
+    pub fn encode(&self, dialect: &dyn Dialect) -> Result<Vec<u8>> {
+        let encoded = dialect.encode(&self.payload);
+        let payload_bytes = encoded.as_slice();
 
         // Check payload size limit
-        if payload_bytes.len() > MAX_REQUEST_PAYLOAD_SIZE {
+        let max_payload = dialect.max_request_payload();
+        if payload_bytes.len() > max_payload {
             return Err(RconError::InvalidPacket(format!(
                 "Payload too large: {} bytes (max: {})",
                 payload_bytes.len(),
-                MAX_REQUEST_PAYLOAD_SIZE
+                max_payload
             )));
         }
 
@@ -83,23 +114,32 @@ impl RconPacket {
         Ok(buffer)
     }
 
-    /// Deserialize a packet from bytes
+    /// Deserialize a packet from bytes using the Minecraft dialect
     pub fn from_bytes(data: &[u8]) -> Result<Self> {
-        if data.len() < 12 {
-            return Err(RconError::InvalidPacket(
-                "Packet too short (minimum 12 bytes required)".to_string(),
-            ));
+        Self::decode(data, &MinecraftJava)
+    }
+
+    /// Deserialize a packet from bytes using the given dialect's encoding
+    pub fn decode(data: &[u8], dialect: &dyn Dialect) -> Result<Self> {
+        if data.len() < 4 + MIN_PACKET_LENGTH {
+            return Err(RconError::InvalidPacket(format!(
+                "Packet too short (minimum {} bytes required)",
+                4 + MIN_PACKET_LENGTH
+            )));
         }
 
         let mut cursor = Cursor::new(data);
 
-        // Read packet length
-        let packet_length = cursor.read_i32::<LittleEndian>().map_err(|e| {
+        // Read and bound the packet length before doing any arithmetic with it
+        let declared_length = cursor.read_i32::<LittleEndian>().map_err(|e| {
             RconError::InvalidPacket(format!("Failed to read packet length: {}", e))
         })?;
+        let packet_length = validate_packet_length(declared_length)?;
 
         // Verify packet length matches data
-        let expected_total_length = packet_length as usize + 4; // +4 for the length field itself
+        let expected_total_length = packet_length
+            .checked_add(4) // +4 for the length field itself
+            .ok_or_else(|| RconError::InvalidPacket("Packet length overflow".to_string()))?;
         if data.len() != expected_total_length {
             return Err(RconError::InvalidPacket(format!(
                 "Packet length mismatch: expected {}, got {}",
@@ -117,17 +157,21 @@ impl RconPacket {
             .read_i32::<LittleEndian>()
             .map_err(|e| RconError::InvalidPacket(format!("Failed to read packet type: {}", e)))?;
 
-        // Read payload (everything except the last 2 null bytes)
-        let payload_length = packet_length as usize - 8 - 2; // subtract request_id, type, and padding
-        let mut payload_bytes = vec![0u8; payload_length];
-
-        if payload_length > 0 {
-            std::io::Read::read_exact(&mut cursor, &mut payload_bytes)
-                .map_err(|e| RconError::InvalidPacket(format!("Failed to read payload: {}", e)))?;
-        }
+        // Payload is everything between the header and the 2 trailing null bytes
+        let payload_end = expected_total_length
+            .checked_sub(2)
+            .ok_or_else(|| RconError::InvalidPacket("Packet length underflow".to_string()))?;
+        let payload_bytes = data.get(12..payload_end).ok_or_else(|| {
+            RconError::InvalidPacket(format!(
+                "Payload range 12..{} out of bounds for {} bytes",
+                payload_end,
+                data.len()
+            ))
+        })?;
 
         // Convert payload to string, handling potential non-UTF8 bytes gracefully
-        let payload = String::from_utf8_lossy(&payload_bytes)
+        let payload = dialect
+            .decode(payload_bytes)
             .trim_end_matches('\0')
             .to_string();
 
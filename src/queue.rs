@@ -0,0 +1,126 @@
+//! Durable command queue
+//!
+//! `queue push` appends commands to a JSON Lines file without connecting,
+//! and `queue drain` later sends a server's commands in order. Each command
+//! is removed only after the server answered it, so a command whose
+//! response was lost is sent again on the next drain: delivery is at least
+//! once, never at most once. Commands that must not run twice should be
+//! idempotent, e.g. `tag Steve add rewarded` rather than a plain `give`.
+//!
+//! The file is a [`StateFile`], so pushes from cron jobs or scripts can run
+//! while a drain is in progress.
+
+use crate::error::Result;
+use crate::executor::RconExecutor;
+use crate::state_file::StateFile;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Default queue file, `<data dir>/rcon-cli/queue.jsonl`
+pub fn default_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("rcon-cli").join("queue.jsonl"))
+}
+
+/// A command waiting to be sent
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueuedCommand {
+    pub queued_at: DateTime<Utc>,
+    /// Profile name or address the command is for
+    pub server: String,
+    pub command: String,
+}
+
+/// A queue file
+#[derive(Debug, Clone)]
+pub struct CommandQueue {
+    file: StateFile,
+}
+
+impl CommandQueue {
+    /// Create the file and its directory if needed
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        Ok(Self {
+            file: StateFile::create("Queue", path)?,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        self.file.path()
+    }
+
+    /// Append a command, returning once it is on disk
+    pub fn push(&self, server: impl Into<String>, command: impl Into<String>) -> Result<()> {
+        let entry = QueuedCommand {
+            queued_at: Utc::now(),
+            server: server.into(),
+            command: command.into(),
+        };
+        let _lock = self.file.lock()?;
+        self.file.append_line(&entry)
+    }
+
+    /// Every queued command, oldest first
+    pub fn entries(&self) -> Result<Vec<QueuedCommand>> {
+        let _lock = self.file.lock()?;
+        self.file.read_lines()
+    }
+
+    /// Remove the first entry equal to `entry`, replacing the file atomically
+    pub fn remove(&self, entry: &QueuedCommand) -> Result<()> {
+        let _lock = self.file.lock()?;
+        let mut entries: Vec<QueuedCommand> = self.file.read_lines()?;
+        if let Some(index) = entries.iter().position(|queued| queued == entry) {
+            entries.remove(index);
+        }
+        self.file.write_lines(&entries)
+    }
+
+    /// Send `server`'s commands in order, removing each once answered
+    ///
+    /// `on_sent` sees each command with its response. The first error stops
+    /// the drain and leaves that command and the ones after it queued;
+    /// commands for other servers are left alone. Returns how many
+    /// commands were sent.
+    ///
+    /// ```
+    /// use rcon_cli::mock::MockRconClient;
+    /// use rcon_cli::queue::CommandQueue;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> rcon_cli::Result<()> {
+    /// let path = std::env::temp_dir().join(format!("rcon-queue-{}.jsonl", std::process::id()));
+    /// let queue = CommandQueue::open(&path)?;
+    /// queue.push("survival", "give Steve diamond 1")?;
+    /// queue.push("creative", "time set day")?;
+    ///
+    /// let mut client = MockRconClient::new().respond("give Steve diamond 1", "Gave 1 [Diamond] to Steve");
+    /// let sent = queue.drain("survival", &mut client, |_, _| {}).await?;
+    /// assert_eq!(sent, 1);
+    /// assert_eq!(queue.entries()?[0].server, "creative");
+    /// # std::fs::remove_file(&path).ok();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn drain<F>(
+        &self,
+        server: &str,
+        client: &mut dyn RconExecutor,
+        mut on_sent: F,
+    ) -> Result<usize>
+    where
+        F: FnMut(&QueuedCommand, &str),
+    {
+        let pending: Vec<QueuedCommand> = self
+            .entries()?
+            .into_iter()
+            .filter(|entry| entry.server == server)
+            .collect();
+        for entry in &pending {
+            let response = client.execute_command(&entry.command).await?;
+            self.remove(entry)?;
+            on_sent(entry, &response);
+        }
+        Ok(pending.len())
+    }
+}
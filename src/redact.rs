@@ -0,0 +1,236 @@
+//! Masking of passwords and sensitive command arguments
+//!
+//! Log output, interactive history, and the events sent to subscribers
+//! never show the password a client connected with, and commands matching
+//! a sensitive pattern keep only their leading words. Patterns come from
+//! the `[redact]` section of the config file and are applied along with
+//! the built-in ones:
+//!
+//! ```toml
+//! [redact]
+//! defaults = true
+//! commands = ["opme", "vault login _"]
+//! ```
+//!
+//! A pattern is a command's leading words, case-insensitive, where `_`
+//! stands for any one argument left visible. Every argument after the
+//! pattern is replaced by a single [`MASK`], so its length isn't shown
+//! either.
+
+use serde::Deserialize;
+use std::io::{self, Write};
+use std::sync::{OnceLock, RwLock};
+
+/// What masked text is replaced with
+pub const MASK: &str = "***";
+
+/// The `[redact]` section of the config file
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RedactConfig {
+    /// Keep the built-in patterns next to the user's own
+    #[serde(default = "default_true")]
+    pub defaults: bool,
+    #[serde(default)]
+    pub commands: Vec<String>,
+}
+
+impl Default for RedactConfig {
+    fn default() -> Self {
+        Self {
+            defaults: true,
+            commands: Vec::new(),
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Built-in patterns for login plugins and password-protected `op`
+pub fn default_commands() -> Vec<String> {
+    [
+        // AuthMe and similar login plugins, run for a player or by them
+        "authme register _",
+        "authme password _",
+        "authme changepassword _",
+        "login",
+        "l",
+        "register",
+        "reg",
+        "changepassword",
+        // Plugins that ask for a password after the player's name
+        "op _",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect()
+}
+
+/// Masks known secrets and the arguments of sensitive commands
+///
+/// ```
+/// use rcon_cli::redact::Redactor;
+///
+/// let mut redactor = Redactor::new(["authme register _"]);
+/// redactor.add_secret("hunter2");
+/// assert_eq!(redactor.command("authme register Steve s3cret!"), "authme register Steve ***");
+/// assert_eq!(redactor.command("/AuthMe register Steve s3cret!"), "/AuthMe register Steve ***");
+/// assert_eq!(redactor.command("say the password is hunter2"), "say the password is ***");
+/// assert_eq!(redactor.command("list"), "list");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Redactor {
+    commands: Vec<Vec<String>>,
+    /// Longest first, so a secret containing another is masked whole
+    secrets: Vec<String>,
+}
+
+impl Redactor {
+    pub fn new<I, S>(commands: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Self {
+            commands: commands
+                .into_iter()
+                .map(|pattern| {
+                    pattern
+                        .as_ref()
+                        .split_whitespace()
+                        .map(str::to_lowercase)
+                        .collect::<Vec<_>>()
+                })
+                .filter(|words| !words.is_empty())
+                .collect(),
+            secrets: Vec::new(),
+        }
+    }
+
+    /// The user's patterns, then the built-in ones unless disabled
+    pub fn from_config(config: &RedactConfig) -> Self {
+        let mut commands = config.commands.clone();
+        if config.defaults {
+            commands.extend(default_commands());
+        }
+        Self::new(commands)
+    }
+
+    /// Mask `secret` wherever it appears from now on
+    pub fn add_secret(&mut self, secret: &str) {
+        if secret.is_empty() || self.secrets.iter().any(|known| known == secret) {
+            return;
+        }
+        self.secrets.push(secret.to_string());
+        self.secrets
+            .sort_by_key(|known| std::cmp::Reverse(known.len()));
+    }
+
+    /// `text` with every known secret masked
+    pub fn text(&self, text: &str) -> String {
+        self.secrets
+            .iter()
+            .fold(text.to_string(), |text, secret| text.replace(secret, MASK))
+    }
+
+    /// Words of `command` a pattern leaves visible, if one matches and
+    /// there is anything after them
+    fn sensitive_prefix(&self, command: &str) -> Option<usize> {
+        let words: Vec<&str> = command.trim_start_matches('/').split_whitespace().collect();
+        self.commands
+            .iter()
+            .filter(|pattern| words.len() > pattern.len())
+            .find(|pattern| {
+                pattern
+                    .iter()
+                    .zip(&words)
+                    .all(|(expected, word)| expected == "_" || word.eq_ignore_ascii_case(expected))
+            })
+            .map(Vec::len)
+    }
+
+    /// Whether a pattern masks some of `command`'s arguments
+    pub fn is_sensitive(&self, command: &str) -> bool {
+        self.sensitive_prefix(command.trim()).is_some()
+    }
+
+    /// `command` with sensitive arguments and known secrets masked
+    pub fn command(&self, command: &str) -> String {
+        let command = command.trim();
+        match self.sensitive_prefix(command) {
+            Some(visible) => {
+                let mut words: Vec<&str> = command.split_whitespace().take(visible).collect();
+                words.push(MASK);
+                self.text(&words.join(" "))
+            }
+            None => self.text(command),
+        }
+    }
+}
+
+fn global() -> &'static RwLock<Redactor> {
+    static REDACTOR: OnceLock<RwLock<Redactor>> = OnceLock::new();
+    REDACTOR.get_or_init(|| RwLock::new(Redactor::from_config(&RedactConfig::default())))
+}
+
+/// Use `redactor`'s patterns process-wide, keeping the secrets added so far
+pub fn install(redactor: Redactor) {
+    let mut current = global().write().unwrap_or_else(|e| e.into_inner());
+    let secrets = std::mem::take(&mut current.secrets);
+    *current = redactor;
+    for secret in &secrets {
+        current.add_secret(secret);
+    }
+}
+
+/// Mask `secret` process-wide; clients add the passwords they log in with
+pub fn add_secret(secret: &str) {
+    global()
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .add_secret(secret);
+}
+
+/// [`Redactor::text`] with the process-wide redactor
+pub fn text(text: &str) -> String {
+    global()
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .text(text)
+}
+
+/// [`Redactor::command`] with the process-wide redactor
+pub fn command(command: &str) -> String {
+    global()
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .command(command)
+}
+
+/// [`Redactor::is_sensitive`] with the process-wide redactor
+pub fn is_sensitive(command: &str) -> bool {
+    global()
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .is_sensitive(command)
+}
+
+/// Writes text with the process-wide secrets masked
+///
+/// Log lines are formatted whole before they are written, so a secret is
+/// never split across two writes.
+pub struct RedactingWriter<W>(pub W);
+
+impl<W: Write> Write for RedactingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0
+            .write_all(text(&String::from_utf8_lossy(buf)).as_bytes())?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
@@ -0,0 +1,556 @@
+//! Command schema for validating and completing commands
+//!
+//! The schema is a brigadier command tree in the format of the `commands.json`
+//! report written by the vanilla data generator
+//! (`java -DbundlerMainClass=net.minecraft.data.Main -jar server.jar --reports`).
+//! The tree of the Minecraft version in [`BUILTIN_VERSION`] is built in;
+//! servers on other versions or with plugins can export their own.
+
+use crate::arguments::{validate_entity, validate_time, Coordinate};
+use crate::items::edit_distance;
+use serde_json::Value;
+use std::fmt;
+
+/// Minecraft version of the built-in command tree
+pub const BUILTIN_VERSION: &str = "1.21.4";
+
+const BUILTIN: &str = include_str!("../data/commands-1.21.4.json");
+
+/// Target selector variables offered when completing entity arguments
+const SELECTORS: [&str; 6] = ["@a", "@e", "@n", "@p", "@r", "@s"];
+
+const GAMEMODES: [&str; 4] = ["adventure", "creative", "spectator", "survival"];
+
+const ROOT: usize = 0;
+
+/// Why a command schema couldn't be loaded
+#[derive(Debug, thiserror::Error)]
+pub enum SchemaError {
+    #[error("Invalid command schema: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Invalid command schema: {0}")]
+    Invalid(String),
+}
+
+/// How many tokens an argument takes and which values it accepts
+///
+/// Parsers without a check here accept any single token.
+#[derive(Debug, Clone, PartialEq)]
+enum Parser {
+    Integer { min: f64, max: f64 },
+    Decimal { min: f64, max: f64 },
+    Bool,
+    Entity,
+    Coordinates(usize),
+    Time,
+    Gamemode,
+    Greedy,
+    Token,
+}
+
+impl Parser {
+    fn from_node(parser: &str, properties: Option<&Value>) -> Self {
+        let bound = |key: &str, default: f64| {
+            properties
+                .and_then(|properties| properties.get(key))
+                .and_then(Value::as_f64)
+                .unwrap_or(default)
+        };
+        match parser {
+            "brigadier:integer" | "brigadier:long" => Parser::Integer {
+                min: bound("min", f64::MIN),
+                max: bound("max", f64::MAX),
+            },
+            "brigadier:float" | "brigadier:double" => Parser::Decimal {
+                min: bound("min", f64::MIN),
+                max: bound("max", f64::MAX),
+            },
+            "brigadier:bool" => Parser::Bool,
+            "brigadier:string" => match properties.and_then(|properties| properties.get("type")) {
+                Some(Value::String(kind)) if kind == "greedy" => Parser::Greedy,
+                _ => Parser::Token,
+            },
+            "minecraft:message" => Parser::Greedy,
+            "minecraft:entity" | "minecraft:game_profile" => Parser::Entity,
+            "minecraft:vec3" | "minecraft:block_pos" => Parser::Coordinates(3),
+            "minecraft:vec2" | "minecraft:column_pos" | "minecraft:rotation" => {
+                Parser::Coordinates(2)
+            }
+            "minecraft:time" => Parser::Time,
+            "minecraft:gamemode" => Parser::Gamemode,
+            _ => Parser::Token,
+        }
+    }
+
+    /// Number of tokens taken from the start of `tokens`, if they are acceptable
+    fn consume(&self, tokens: &[&str]) -> Option<usize> {
+        let token = *tokens.first()?;
+        let accepted = match self {
+            Parser::Integer { min, max } => token
+                .parse::<i64>()
+                .is_ok_and(|value| (*min..=*max).contains(&(value as f64))),
+            Parser::Decimal { min, max } => token
+                .parse::<f64>()
+                .is_ok_and(|value| (*min..=*max).contains(&value)),
+            Parser::Bool => token == "true" || token == "false",
+            Parser::Entity => validate_entity(token).is_ok(),
+            Parser::Coordinates(count) => {
+                return (tokens.len() >= *count
+                    && tokens[..*count]
+                        .iter()
+                        .all(|token| token.parse::<Coordinate>().is_ok()))
+                .then_some(*count);
+            }
+            Parser::Time => validate_time(token).is_ok(),
+            Parser::Gamemode => GAMEMODES.contains(&token),
+            Parser::Greedy => return Some(tokens.len()),
+            Parser::Token => true,
+        };
+        accepted.then_some(1)
+    }
+
+    /// Values worth offering when completing this argument
+    fn suggestions(&self) -> &'static [&'static str] {
+        match self {
+            Parser::Bool => &["false", "true"],
+            Parser::Entity => &SELECTORS,
+            Parser::Gamemode => &GAMEMODES,
+            _ => &[],
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Kind {
+    Root,
+    Literal,
+    Argument(Parser),
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    name: String,
+    kind: Kind,
+    children: Vec<usize>,
+    executable: bool,
+    redirect: Option<usize>,
+}
+
+impl Node {
+    /// How the node is shown in messages: literals as-is, arguments as `<name>`
+    fn label(&self) -> String {
+        match self.kind {
+            Kind::Argument(_) => format!("<{}>", self.name),
+            _ => self.name.clone(),
+        }
+    }
+
+    fn consume(&self, tokens: &[&str]) -> Option<usize> {
+        match &self.kind {
+            Kind::Root => None,
+            Kind::Literal => (tokens.first() == Some(&self.name.as_str())).then_some(1),
+            Kind::Argument(parser) => parser.consume(tokens),
+        }
+    }
+}
+
+/// What is wrong with a command, as far as the schema can tell
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    /// The command name isn't in the schema, with the closest known one
+    UnknownCommand {
+        name: String,
+        suggestion: Option<String>,
+    },
+    /// The command ends where more arguments are required
+    Incomplete { expected: Vec<String> },
+    /// The argument at `position` (the command name being 0) doesn't fit
+    Unexpected {
+        position: usize,
+        token: String,
+        expected: Vec<String>,
+    },
+    /// The command is complete before the argument at `position`
+    TooManyArguments { position: usize, extra: String },
+}
+
+/// List expected arguments, eliding long lists such as every command name
+fn expected_list(expected: &[String]) -> String {
+    const SHOWN: usize = 8;
+    if expected.len() > SHOWN {
+        format!("{}, ...", expected[..SHOWN].join(", "))
+    } else {
+        expected.join(", ")
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::UnknownCommand {
+                name,
+                suggestion: Some(suggestion),
+            } => write!(
+                f,
+                "Unknown command '{}' (did you mean '{}'?)",
+                name, suggestion
+            ),
+            Problem::UnknownCommand { name, .. } => write!(f, "Unknown command '{}'", name),
+            Problem::Incomplete { expected } => write!(
+                f,
+                "Incomplete command, expected {}",
+                expected_list(expected)
+            ),
+            Problem::Unexpected {
+                position,
+                token,
+                expected,
+            } => write!(
+                f,
+                "Unexpected '{}' at argument {}, expected {}",
+                token,
+                position,
+                expected_list(expected)
+            ),
+            Problem::TooManyArguments { position, extra } => write!(
+                f,
+                "Too many arguments, '{}' from argument {} on is left over",
+                extra, position
+            ),
+        }
+    }
+}
+
+/// A brigadier command tree
+///
+/// ```
+/// use rcon_cli::schema::{CommandSchema, Problem};
+///
+/// let schema = CommandSchema::builtin();
+/// assert!(schema.validate("time set day").is_ok());
+/// assert!(schema.validate("tp @s ~ ~10 ~").is_ok());
+/// assert!(schema.validate("execute as @a at @s run say hi").is_ok());
+/// assert!(matches!(
+///     schema.validate("tmie set day"),
+///     Err(Problem::UnknownCommand { suggestion: Some(name), .. }) if name == "time"
+/// ));
+/// assert!(matches!(schema.validate("give @p"), Err(Problem::Incomplete { .. })));
+/// assert!(matches!(
+///     schema.validate("weather clear 10 20"),
+///     Err(Problem::TooManyArguments { position: 3, .. })
+/// ));
+/// ```
+#[derive(Debug, Clone)]
+pub struct CommandSchema {
+    nodes: Vec<Node>,
+}
+
+impl CommandSchema {
+    /// The built-in tree of vanilla Minecraft [`BUILTIN_VERSION`]
+    pub fn builtin() -> Self {
+        Self::from_json(BUILTIN).expect("the built-in command schema is valid")
+    }
+
+    /// Load a tree exported by the data generator (`reports/commands.json`)
+    pub fn from_json(json: &str) -> Result<Self, SchemaError> {
+        let root: Value = serde_json::from_str(json)?;
+        if root.get("type").and_then(Value::as_str) != Some("root") {
+            return Err(SchemaError::Invalid(
+                "the top level is not a root node".into(),
+            ));
+        }
+
+        let mut schema = Self { nodes: Vec::new() };
+        let mut redirects = Vec::new();
+        schema.add_node(String::new(), &root, &mut redirects)?;
+        for (node, path) in redirects {
+            let target = schema.find(&path).ok_or_else(|| {
+                SchemaError::Invalid(format!("redirect to unknown node '{}'", path.join(" ")))
+            })?;
+            schema.nodes[node].redirect = Some(target);
+        }
+
+        // Redirects to the root (`execute run`) are exported without a path,
+        // leaving a node that neither executes nor continues
+        for node in schema.nodes.iter_mut().skip(1) {
+            if !node.executable && node.children.is_empty() && node.redirect.is_none() {
+                node.redirect = Some(ROOT);
+            }
+        }
+        Ok(schema)
+    }
+
+    fn add_node(
+        &mut self,
+        name: String,
+        value: &Value,
+        redirects: &mut Vec<(usize, Vec<String>)>,
+    ) -> Result<usize, SchemaError> {
+        let kind = match value.get("type").and_then(Value::as_str) {
+            Some("root") => Kind::Root,
+            Some("literal") => Kind::Literal,
+            Some("argument") => {
+                let parser = value.get("parser").and_then(Value::as_str).ok_or_else(|| {
+                    SchemaError::Invalid(format!("argument '{}' has no parser", name))
+                })?;
+                Kind::Argument(Parser::from_node(parser, value.get("properties")))
+            }
+            _ => {
+                return Err(SchemaError::Invalid(format!(
+                    "node '{}' has no valid type",
+                    name
+                )))
+            }
+        };
+
+        let index = self.nodes.len();
+        self.nodes.push(Node {
+            name,
+            kind,
+            children: Vec::new(),
+            executable: value.get("executable").and_then(Value::as_bool) == Some(true),
+            redirect: None,
+        });
+
+        if let Some(path) = value.get("redirect").and_then(Value::as_array) {
+            let path = path
+                .iter()
+                .filter_map(|segment| segment.as_str().map(str::to_string))
+                .collect();
+            redirects.push((index, path));
+        }
+        if let Some(children) = value.get("children").and_then(Value::as_object) {
+            for (name, child) in children {
+                let child = self.add_node(name.clone(), child, redirects)?;
+                self.nodes[index].children.push(child);
+            }
+        }
+        Ok(index)
+    }
+
+    /// The node at a path of child names from the root
+    fn find(&self, path: &[String]) -> Option<usize> {
+        path.iter().try_fold(ROOT, |node, name| {
+            self.nodes[node]
+                .children
+                .iter()
+                .copied()
+                .find(|&child| self.nodes[child].name == *name)
+        })
+    }
+
+    /// Nodes that may follow `node`, through its redirect if it has one
+    fn next(&self, node: usize) -> &[usize] {
+        &self.nodes[self.nodes[node].redirect.unwrap_or(node)].children
+    }
+
+    fn expected(&self, node: usize) -> Vec<String> {
+        self.next(node)
+            .iter()
+            .map(|&child| self.nodes[child].label())
+            .collect()
+    }
+
+    /// Names of the top-level commands
+    pub fn commands(&self) -> impl Iterator<Item = &str> {
+        self.nodes[ROOT]
+            .children
+            .iter()
+            .map(|&child| self.nodes[child].name.as_str())
+    }
+
+    /// Check a command's name and arguments against the tree
+    ///
+    /// Arguments are checked as far as their parser allows without the
+    /// server's registries: numbers, coordinates, selectors and durations are
+    /// checked, names of items, blocks and the like are not.
+    pub fn validate(&self, command: &str) -> Result<(), Problem> {
+        let command = command.trim();
+        let command = command.strip_prefix('/').unwrap_or(command);
+        let tokens: Vec<&str> = split_command(command)
+            .into_iter()
+            .map(|(_, token)| token)
+            .collect();
+
+        let Some(name) = tokens.first() else {
+            return Err(Problem::Incomplete {
+                expected: self.expected(ROOT),
+            });
+        };
+        if !self.commands().any(|command| command == *name) {
+            return Err(Problem::UnknownCommand {
+                name: name.to_string(),
+                suggestion: self.closest_command(name),
+            });
+        }
+
+        // Follow every reading of the tokens; report the one that got furthest
+        let mut problem: Option<(usize, Problem)> = None;
+        let mut record = |index: usize, found: Problem| {
+            if problem
+                .as_ref()
+                .is_none_or(|(furthest, _)| index > *furthest)
+            {
+                problem = Some((index, found));
+            }
+        };
+        let mut pending = vec![(ROOT, 0)];
+        while let Some((node, index)) = pending.pop() {
+            if index == tokens.len() {
+                if self.nodes[node].executable {
+                    return Ok(());
+                }
+                record(
+                    index,
+                    Problem::Incomplete {
+                        expected: self.expected(node),
+                    },
+                );
+                continue;
+            }
+
+            let next = self.next(node);
+            if next.is_empty() {
+                record(
+                    index,
+                    Problem::TooManyArguments {
+                        position: index,
+                        extra: tokens[index..].join(" "),
+                    },
+                );
+                continue;
+            }
+            let before = pending.len();
+            for &child in next.iter().rev() {
+                if let Some(width) = self.nodes[child].consume(&tokens[index..]) {
+                    pending.push((child, index + width));
+                }
+            }
+            if pending.len() == before {
+                record(
+                    index,
+                    Problem::Unexpected {
+                        position: index,
+                        token: tokens[index].to_string(),
+                        expected: self.expected(node),
+                    },
+                );
+            }
+        }
+        Err(problem
+            .map(|(_, problem)| problem)
+            .unwrap_or(Problem::Incomplete {
+                expected: Vec::new(),
+            }))
+    }
+
+    fn closest_command(&self, name: &str) -> Option<String> {
+        self.commands()
+            .map(|command| (edit_distance(name, command), command))
+            .filter(|(distance, command)| *distance <= 2.min(command.len() / 3 + 1))
+            .min()
+            .map(|(_, command)| command.to_string())
+    }
+
+    /// Completions for the last word of a partially typed command
+    ///
+    /// Returns the byte offset where the word starts and the literals, and
+    /// for some arguments the common values, that may be typed there.
+    ///
+    /// ```
+    /// use rcon_cli::schema::CommandSchema;
+    ///
+    /// let schema = CommandSchema::builtin();
+    /// assert_eq!(schema.complete("weather cl"), (8, vec!["clear".to_string()]));
+    /// assert_eq!(schema.complete("time query "), (11, vec![
+    ///     "day".to_string(), "daytime".to_string(), "gametime".to_string(),
+    /// ]));
+    /// ```
+    pub fn complete(&self, line: &str) -> (usize, Vec<String>) {
+        let offset = line.len() - line.trim_start().len();
+        let offset = offset + usize::from(line[offset..].starts_with('/'));
+        let mut words = split_command(&line[offset..]);
+        let (start, partial) = match words.last() {
+            Some(&(start, word)) if start + word.len() == line.len() - offset => {
+                words.pop();
+                (offset + start, word)
+            }
+            _ => (line.len(), ""),
+        };
+        let words: Vec<&str> = words.into_iter().map(|(_, word)| word).collect();
+
+        let mut candidates = Vec::new();
+        let mut pending = vec![(ROOT, 0)];
+        while let Some((node, index)) = pending.pop() {
+            for &child in self.next(node) {
+                let child_node = &self.nodes[child];
+                if index == words.len() {
+                    match &child_node.kind {
+                        Kind::Literal => candidates.push(child_node.name.clone()),
+                        Kind::Argument(parser) => candidates
+                            .extend(parser.suggestions().iter().map(|value| value.to_string())),
+                        Kind::Root => {}
+                    }
+                } else if let Some(width) = child_node.consume(&words[index..]) {
+                    pending.push((child, index + width));
+                }
+            }
+        }
+
+        candidates.retain(|candidate| candidate.starts_with(partial));
+        candidates.sort();
+        candidates.dedup();
+        (start, candidates)
+    }
+}
+
+/// Split a command into words with their byte offsets
+///
+/// Quoted strings and bracketed selector arguments, NBT and JSON stay in one
+/// word even when they contain spaces.
+///
+/// ```
+/// use rcon_cli::schema::split_command;
+///
+/// let words: Vec<&str> = split_command(r#"tellraw @a[tag=x, limit=1] {"text": "hi there"}"#)
+///     .into_iter()
+///     .map(|(_, word)| word)
+///     .collect();
+/// assert_eq!(words, ["tellraw", "@a[tag=x, limit=1]", r#"{"text": "hi there"}"#]);
+/// ```
+pub fn split_command(command: &str) -> Vec<(usize, &str)> {
+    let mut words = Vec::new();
+    let mut start = None;
+    let mut depth = 0usize;
+    let mut quote = None;
+    let mut escaped = false;
+    for (index, c) in command.char_indices() {
+        if let Some(open) = quote {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == open {
+                quote = None;
+            }
+            continue;
+        }
+        match c {
+            c if c.is_whitespace() && depth == 0 => {
+                if let Some(begin) = start.take() {
+                    words.push((begin, &command[begin..index]));
+                }
+                continue;
+            }
+            '"' | '\'' => quote = Some(c),
+            '[' | '{' | '(' => depth += 1,
+            ']' | '}' | ')' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        start.get_or_insert(index);
+    }
+    if let Some(begin) = start {
+        words.push((begin, &command[begin..]));
+    }
+    words
+}
@@ -0,0 +1,119 @@
+use crate::error::{RconError, Result};
+use serde::Deserialize;
+use std::fmt;
+use std::path::Path;
+
+/// What a failed command means for the rest of a script
+///
+/// A line declares it with a tag in front of the command, e.g.
+/// `[warn_only] say Back in 5 minutes`; untagged lines are [`Normal`].
+///
+/// [`Normal`]: Severity::Normal
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// The failure is mentioned, but doesn't count
+    IgnoreErrors,
+    /// The failure is reported as a warning, but doesn't fail the script
+    WarnOnly,
+    /// The failure fails the script, which stops unless told to continue
+    #[default]
+    Normal,
+    /// The failure stops the script, even if told to continue
+    Critical,
+}
+
+impl Severity {
+    /// Tags a line may start with
+    pub const TAGS: &'static [&'static str] = &["ignore_errors", "warn_only", "critical"];
+
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        match tag {
+            "ignore_errors" => Some(Severity::IgnoreErrors),
+            "warn_only" => Some(Severity::WarnOnly),
+            "critical" => Some(Severity::Critical),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::IgnoreErrors => "ignore_errors",
+            Severity::WarnOnly => "warn_only",
+            Severity::Normal => "normal",
+            Severity::Critical => "critical",
+        })
+    }
+}
+
+/// A single command read from an RCON script
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptLine {
+    /// 1-based line number in the source file
+    pub line_number: usize,
+    /// Command to send to the server
+    pub command: String,
+    /// What a failure of the command means for the rest of the script
+    pub severity: Severity,
+}
+
+/// Parse an RCON script: one command per line
+///
+/// Blank lines and lines starting with `#` are ignored; surrounding
+/// whitespace is trimmed. A `[tag]` in front of a command sets its
+/// [`Severity`]; an unknown tag is an error.
+///
+/// ```
+/// use rcon_cli::script::{parse_script, Severity};
+///
+/// let script = parse_script("[warn_only] say Maintenance starts now\n[critical] save-all flush\nstop\n").unwrap();
+/// assert_eq!(script[0].command, "say Maintenance starts now");
+/// assert_eq!(script[0].severity, Severity::WarnOnly);
+/// assert_eq!(script[1].severity, Severity::Critical);
+/// assert_eq!(script[2].severity, Severity::Normal);
+/// assert!(parse_script("[optional] say Hi").is_err());
+/// ```
+pub fn parse_script(source: &str) -> Result<Vec<ScriptLine>> {
+    let mut lines = Vec::new();
+    for (index, line) in source.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (severity, command) = match line.strip_prefix('[').and_then(|rest| rest.split_once(']'))
+        {
+            Some((tag, command)) => {
+                let severity = Severity::from_tag(tag.trim()).ok_or_else(|| {
+                    RconError::Script(format!(
+                        "line {}: unknown tag '[{}]' (expected one of: {})",
+                        index + 1,
+                        tag,
+                        Severity::TAGS.join(", ")
+                    ))
+                })?;
+                (severity, command.trim())
+            }
+            None => (Severity::Normal, line),
+        };
+        lines.push(ScriptLine {
+            line_number: index + 1,
+            command: command.to_string(),
+            severity,
+        });
+    }
+    Ok(lines)
+}
+
+/// Read and parse an RCON script file
+pub fn load_script(path: impl AsRef<Path>) -> Result<Vec<ScriptLine>> {
+    let path = path.as_ref();
+    let source = std::fs::read_to_string(path).map_err(|e| {
+        RconError::Script(format!("Failed to read script '{}': {}", path.display(), e))
+    })?;
+    parse_script(&source).map_err(|e| match e {
+        RconError::Script(message) => RconError::Script(format!("{}: {}", path.display(), message)),
+        e => e,
+    })
+}
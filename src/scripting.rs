@@ -0,0 +1,273 @@
+//! Rhai scripts driving a connection
+//!
+//! `rcon-cli eval` runs a [Rhai](https://rhai.rs) script against one server,
+//! for automation that polls, decides, and acts without a separate program:
+//!
+//! ```rhai
+//! while true {
+//!     let players = parse_players(rcon.execute("list"));
+//!     if players.online == 0 {
+//!         rcon.execute("save-all");
+//!         rcon.execute("stop");
+//!         break;
+//!     }
+//!     sleep(60);
+//! }
+//! ```
+//!
+//! Besides Rhai's own functions, scripts get:
+//!
+//! - `rcon.execute(command)`, returning the response and throwing on errors
+//! - `parse_players`, `parse_tps`, `parse_time`, `parse_seed`,
+//!   `parse_difficulty`, `parse_version`, and `parse_whitelist`, returning
+//!   `()` for responses they don't recognize
+//! - `sleep(seconds)`, `elapsed()` in seconds since the script started, and
+//!   `timestamp()` as RFC 3339
+//! - `parse_json(text)` and `to_json(value)`
+//! - `ARGS`, the arguments after the script's path
+//! - `exit(code)`, ending the script with an exit code
+//!
+//! Scripts run on a blocking thread; each command waits for its response
+//! on the runtime the client was created on.
+
+use crate::client::RconClient;
+use crate::error::{RconError, Result};
+use crate::parsers::{
+    parse_bukkit_tps, parse_difficulty, parse_forge_tps, parse_player_list, parse_seed,
+    parse_server_version, parse_tick_query, parse_time, parse_whitelist, TpsSample,
+};
+use crate::shutdown::Shutdown;
+use rhai::{Array, Dynamic, Engine, EvalAltResult, Position, Scope};
+use serde_json::{json, Value};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::runtime::Handle;
+use tokio::sync::Mutex;
+
+/// The connection scripts see as `rcon`
+#[derive(Clone)]
+struct Rcon {
+    client: Arc<Mutex<RconClient>>,
+    runtime: Handle,
+}
+
+impl Rcon {
+    fn execute(&mut self, command: &str) -> std::result::Result<String, Box<EvalAltResult>> {
+        self.runtime
+            .block_on(async { self.client.lock().await.execute_command(command).await })
+            .map_err(|e| e.to_string().into())
+    }
+}
+
+/// How a script ended
+#[derive(Debug, Clone)]
+pub enum Outcome {
+    /// The value of the script's last statement
+    Finished(Dynamic),
+    /// The script called `exit` with an exit code
+    Exited(i32),
+    /// A shutdown was requested while the script ran
+    Interrupted,
+}
+
+/// A Rhai engine with the functions scripts get
+///
+/// ```
+/// use rcon_cli::scripting::{Outcome, ScriptEngine};
+///
+/// let engine = ScriptEngine::offline();
+/// let script = r#"
+///     let players = parse_players("There are 2 of a max of 20 players online: Alex, Steve");
+///     to_json(#{ online: players.online, first: players.players[0], args: ARGS })
+/// "#;
+/// match engine.run(script, vec!["--quick".to_string()]).unwrap() {
+///     Outcome::Finished(value) => assert_eq!(
+///         value.to_string(),
+///         r#"{"args":["--quick"],"first":"Alex","online":2}"#
+///     ),
+///     outcome => panic!("unexpected {:?}", outcome),
+/// }
+/// ```
+pub struct ScriptEngine {
+    rcon: Option<Rcon>,
+    shutdown: Option<Shutdown>,
+}
+
+impl ScriptEngine {
+    /// An engine whose `rcon` sends commands with `client`
+    ///
+    /// Must be called inside a Tokio runtime, which commands then run on.
+    pub fn new(client: RconClient) -> Self {
+        Self {
+            rcon: Some(Rcon {
+                client: Arc::new(Mutex::new(client)),
+                runtime: Handle::current(),
+            }),
+            shutdown: None,
+        }
+    }
+
+    /// An engine without a connection, e.g. for trying out scripts
+    ///
+    /// `rcon` is not defined, so scripts using it fail.
+    pub fn offline() -> Self {
+        Self {
+            rcon: None,
+            shutdown: None,
+        }
+    }
+
+    /// Stop the script between operations and in `sleep` once a shutdown
+    /// is requested
+    pub fn with_shutdown(mut self, shutdown: Shutdown) -> Self {
+        self.shutdown = Some(shutdown);
+        self
+    }
+
+    /// Run a script to completion; blocks, so call it off the runtime
+    ///
+    /// Errors name the line and position they occurred at.
+    pub fn run(&self, script: &str, args: Vec<String>) -> Result<Outcome> {
+        let exit_code = Arc::new(OnceLock::new());
+        let mut engine = self.engine();
+        // Rhai's own `exit` returns a value; this one can't be caught
+        let code = exit_code.clone();
+        engine.register_fn(
+            "exit",
+            move |exit: i64| -> std::result::Result<(), Box<EvalAltResult>> {
+                let _ = code.set(i32::try_from(exit).unwrap_or(1));
+                Err(EvalAltResult::ErrorTerminated(Dynamic::UNIT, Position::NONE).into())
+            },
+        );
+        let mut scope = Scope::new();
+        let args: Array = args.into_iter().map(Dynamic::from).collect();
+        scope.push_constant("ARGS", args);
+        if let Some(rcon) = &self.rcon {
+            scope.push_constant("rcon", rcon.clone());
+        }
+
+        match engine.eval_with_scope::<Dynamic>(&mut scope, script) {
+            Ok(value) => Ok(Outcome::Finished(value)),
+            Err(e) => match *e {
+                EvalAltResult::ErrorTerminated(..) => Ok(match exit_code.get() {
+                    Some(&code) => Outcome::Exited(code),
+                    None => Outcome::Interrupted,
+                }),
+                e => Err(RconError::Script(e.to_string())),
+            },
+        }
+    }
+
+    /// A fresh engine, so `elapsed` starts at zero for every run
+    fn engine(&self) -> Engine {
+        let mut engine = Engine::new();
+        engine
+            .register_type_with_name::<Rcon>("Rcon")
+            .register_fn("execute", Rcon::execute);
+        register_parsers(&mut engine);
+        register_json(&mut engine);
+
+        let started = Instant::now();
+        engine
+            .register_fn("elapsed", move || started.elapsed().as_secs_f64())
+            .register_fn("timestamp", || chrono::Utc::now().to_rfc3339());
+        let shutdown = self.shutdown.clone();
+        let runtime = self.rcon.as_ref().map(|rcon| rcon.runtime.clone());
+        let sleep = move |seconds: f64| -> std::result::Result<(), Box<EvalAltResult>> {
+            let duration = Duration::try_from_secs_f64(seconds)
+                .map_err(|_| format!("invalid sleep duration: {}", seconds))?;
+            let completed = match (&shutdown, &runtime) {
+                (Some(shutdown), Some(runtime)) => {
+                    runtime.block_on(shutdown.clone().sleep(duration))
+                }
+                _ => {
+                    std::thread::sleep(duration);
+                    true
+                }
+            };
+            if completed {
+                Ok(())
+            } else {
+                Err(EvalAltResult::ErrorTerminated(Dynamic::UNIT, Position::NONE).into())
+            }
+        };
+        engine
+            .register_fn("sleep", sleep.clone())
+            .register_fn("sleep", move |seconds: i64| sleep(seconds as f64));
+
+        if let Some(shutdown) = self.shutdown.clone() {
+            engine.on_progress(move |_| shutdown.is_requested().then_some(Dynamic::UNIT));
+        }
+        engine
+    }
+}
+
+/// `()` for responses a parser doesn't recognize
+fn parsed(value: Option<Value>) -> Dynamic {
+    value
+        .and_then(|value| rhai::serde::to_dynamic(value).ok())
+        .unwrap_or(Dynamic::UNIT)
+}
+
+fn tps_samples(samples: Vec<TpsSample>) -> Value {
+    samples
+        .into_iter()
+        .map(|sample| json!({ "label": sample.label, "tps": sample.tps, "mspt": sample.mspt }))
+        .collect()
+}
+
+fn register_parsers(engine: &mut Engine) {
+    engine
+        .register_fn("parse_players", |response: &str| {
+            parsed(parse_player_list(response).map(|list| {
+                let names: Vec<String> =
+                    list.players.into_iter().map(|player| player.name).collect();
+                json!({ "online": list.online, "max": list.max, "players": names })
+            }))
+        })
+        .register_fn("parse_tps", |response: &str| {
+            parsed(
+                parse_bukkit_tps(response)
+                    .or_else(|| parse_forge_tps(response))
+                    .or_else(|| parse_tick_query(response).map(|sample| vec![sample]))
+                    .map(tps_samples),
+            )
+        })
+        .register_fn("parse_time", |response: &str| {
+            parsed(parse_time(response).map(Value::from))
+        })
+        .register_fn("parse_seed", |response: &str| {
+            parsed(parse_seed(response).map(Value::from))
+        })
+        .register_fn("parse_difficulty", |response: &str| {
+            parsed(parse_difficulty(response).map(Value::from))
+        })
+        .register_fn("parse_version", |response: &str| {
+            parsed(
+                parse_server_version(response)
+                    .map(|version| json!({ "flavor": version.flavor, "version": version.version })),
+            )
+        })
+        .register_fn("parse_whitelist", |response: &str| {
+            parsed(parse_whitelist(response).map(Value::from))
+        });
+}
+
+fn register_json(engine: &mut Engine) {
+    engine
+        .register_fn(
+            "parse_json",
+            |text: &str| -> std::result::Result<Dynamic, Box<EvalAltResult>> {
+                let value: Value =
+                    serde_json::from_str(text).map_err(|e| format!("invalid JSON: {}", e))?;
+                rhai::serde::to_dynamic(value)
+            },
+        )
+        .register_fn(
+            "to_json",
+            |value: Dynamic| -> std::result::Result<String, Box<EvalAltResult>> {
+                let value: Value = rhai::serde::from_dynamic(&value)?;
+                Ok(value.to_string())
+            },
+        );
+}
@@ -0,0 +1,154 @@
+//! Player session tracking from periodic player lists
+//!
+//! RCON has no event stream, so joins and leaves are inferred by comparing
+//! consecutive `list` responses. Sessions shorter than the polling interval
+//! can be missed, and join times are only as precise as the interval.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::Duration;
+
+/// What happened to a player between two polls
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionEventKind {
+    /// Already online when tracking started; the real join time is unknown
+    Online,
+    Join,
+    Leave,
+}
+
+impl SessionEventKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SessionEventKind::Online => "online",
+            SessionEventKind::Join => "join",
+            SessionEventKind::Leave => "leave",
+        }
+    }
+}
+
+/// A join or leave detected by [`SessionTracker`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionEvent {
+    pub kind: SessionEventKind,
+    pub player: String,
+    pub timestamp: DateTime<Utc>,
+    /// Length of the session that ended, for leave events
+    pub duration: Option<Duration>,
+}
+
+/// Session totals for one player
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerSessions {
+    pub player: String,
+    /// Number of sessions, including one still in progress
+    pub sessions: usize,
+    pub total: Duration,
+    pub longest: Duration,
+    pub online: bool,
+}
+
+/// Infers sessions from successive player lists
+#[derive(Debug, Clone, Default)]
+pub struct SessionTracker {
+    /// Players currently online, with the time they were first seen
+    online: BTreeMap<String, DateTime<Utc>>,
+    /// Finished sessions per player
+    finished: BTreeMap<String, Vec<Duration>>,
+    observed: bool,
+}
+
+impl SessionTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the players online at `at`, returning what changed
+    ///
+    /// The first observation reports everyone as [`SessionEventKind::Online`].
+    pub fn observe<I, S>(&mut self, players: I, at: DateTime<Utc>) -> Vec<SessionEvent>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let current: BTreeSet<String> = players.into_iter().map(Into::into).collect();
+        let mut events = Vec::new();
+
+        let left: Vec<String> = self
+            .online
+            .keys()
+            .filter(|player| !current.contains(*player))
+            .cloned()
+            .collect();
+        for player in left {
+            let joined = self.online.remove(&player).unwrap_or(at);
+            let duration = elapsed(joined, at);
+            self.finished
+                .entry(player.clone())
+                .or_default()
+                .push(duration);
+            events.push(SessionEvent {
+                kind: SessionEventKind::Leave,
+                player,
+                timestamp: at,
+                duration: Some(duration),
+            });
+        }
+
+        let kind = if self.observed {
+            SessionEventKind::Join
+        } else {
+            SessionEventKind::Online
+        };
+        for player in current {
+            if self.online.contains_key(&player) {
+                continue;
+            }
+            self.online.insert(player.clone(), at);
+            events.push(SessionEvent {
+                kind,
+                player,
+                timestamp: at,
+                duration: None,
+            });
+        }
+
+        self.observed = true;
+        events
+    }
+
+    /// Players currently online
+    pub fn online(&self) -> impl Iterator<Item = &str> {
+        self.online.keys().map(String::as_str)
+    }
+
+    /// Per-player totals, counting sessions in progress up to `now`
+    pub fn summary(&self, now: DateTime<Utc>) -> Vec<PlayerSessions> {
+        let players: BTreeSet<&String> = self.online.keys().chain(self.finished.keys()).collect();
+
+        players
+            .into_iter()
+            .map(|player| {
+                let mut durations = self.finished.get(player).cloned().unwrap_or_default();
+                let online = self.online.get(player);
+                if let Some(joined) = online {
+                    durations.push(elapsed(*joined, now));
+                }
+
+                PlayerSessions {
+                    player: player.clone(),
+                    sessions: durations.len(),
+                    total: durations.iter().sum(),
+                    longest: durations.iter().max().copied().unwrap_or_default(),
+                    online: online.is_some(),
+                }
+            })
+            .collect()
+    }
+}
+
+fn elapsed(from: DateTime<Utc>, to: DateTime<Utc>) -> Duration {
+    (to - from).to_std().unwrap_or_default()
+}
@@ -0,0 +1,111 @@
+//! Graceful shutdown on SIGINT and SIGTERM
+//!
+//! Long-running modes stop between commands rather than in the middle of
+//! one: they check [`Shutdown::is_requested`] after each command and wait
+//! with [`Shutdown::sleep`], so the in-flight command completes, its output
+//! is written, and connections are closed before exiting. A second signal
+//! exits immediately.
+
+use std::io;
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing::info;
+
+/// Exit status after a second signal, as a shell reports one killed by SIGINT
+const FORCED_EXIT_CODE: i32 = 130;
+
+/// Handle on the process-wide shutdown request
+///
+/// Clones observe the same request.
+#[derive(Debug, Clone)]
+pub struct Shutdown {
+    requested: watch::Receiver<bool>,
+}
+
+impl Shutdown {
+    /// Start catching SIGINT and SIGTERM (Ctrl+C elsewhere)
+    ///
+    /// From then on the signals no longer terminate the process, so call
+    /// this only in modes that check for shutdown. Must be called inside a
+    /// Tokio runtime.
+    pub fn listen() -> io::Result<Self> {
+        let (sender, requested) = watch::channel(false);
+        let mut signals = Signals::new()?;
+        tokio::spawn(async move {
+            signals.recv().await;
+            info!("Shutdown requested, finishing the current command");
+            let _ = sender.send(true);
+
+            signals.recv().await;
+            std::process::exit(FORCED_EXIT_CODE);
+        });
+        Ok(Self { requested })
+    }
+
+    /// Whether a shutdown has been requested
+    pub fn is_requested(&self) -> bool {
+        *self.requested.borrow()
+    }
+
+    /// Wait until a shutdown is requested
+    pub async fn requested(&mut self) {
+        if self
+            .requested
+            .wait_for(|requested| *requested)
+            .await
+            .is_err()
+        {
+            std::future::pending::<()>().await;
+        }
+    }
+
+    /// Sleep for `duration`, returning false if a shutdown was requested
+    /// before or during it
+    pub async fn sleep(&mut self, duration: Duration) -> bool {
+        if self.is_requested() {
+            return false;
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(duration) => true,
+            _ = self.requested() => false,
+        }
+    }
+}
+
+#[cfg(unix)]
+struct Signals {
+    interrupt: tokio::signal::unix::Signal,
+    terminate: tokio::signal::unix::Signal,
+}
+
+#[cfg(unix)]
+impl Signals {
+    fn new() -> io::Result<Self> {
+        use tokio::signal::unix::{signal, SignalKind};
+        Ok(Self {
+            interrupt: signal(SignalKind::interrupt())?,
+            terminate: signal(SignalKind::terminate())?,
+        })
+    }
+
+    async fn recv(&mut self) {
+        tokio::select! {
+            _ = self.interrupt.recv() => {}
+            _ = self.terminate.recv() => {}
+        }
+    }
+}
+
+#[cfg(not(unix))]
+struct Signals;
+
+#[cfg(not(unix))]
+impl Signals {
+    fn new() -> io::Result<Self> {
+        Ok(Self)
+    }
+
+    async fn recv(&mut self) {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
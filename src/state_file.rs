@@ -0,0 +1,144 @@
+//! Local files that several invocations change at once
+//!
+//! The command [queue](crate::queue), the delivery [ledger](crate::delivery),
+//! the server [pins](crate::pins), and the [maintenance](crate::maintenance)
+//! audit log are kept in files that cron jobs, shop webhooks, and
+//! interactive runs may all touch at the same time. Every change holds an exclusive lock on `<file>.lock`; appended lines are on
+//! disk before an append returns, and rewritten files are replaced
+//! atomically. Failures are [`RconError::State`] errors naming the file.
+
+use crate::error::{RconError, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// A file shared between invocations
+#[derive(Debug, Clone)]
+pub struct StateFile {
+    /// What the file is, e.g. `Queue`, for error messages
+    kind: &'static str,
+    path: PathBuf,
+}
+
+impl StateFile {
+    /// A file at `path`, which need not exist yet
+    pub fn new(kind: &'static str, path: impl Into<PathBuf>) -> Self {
+        Self {
+            kind,
+            path: path.into(),
+        }
+    }
+
+    /// A file at `path`, created empty with its directory if needed
+    pub fn create(kind: &'static str, path: impl Into<PathBuf>) -> Result<Self> {
+        let file = Self::new(kind, path);
+        file.create_dir()?;
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&file.path)
+            .map_err(|e| file.error(e))?;
+        Ok(file)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// A failure to use the file, naming it
+    pub fn error(&self, e: impl fmt::Display) -> RconError {
+        RconError::State(format!("{} '{}': {}", self.kind, self.path.display(), e))
+    }
+
+    fn create_dir(&self) -> Result<()> {
+        match self
+            .path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            Some(parent) => std::fs::create_dir_all(parent).map_err(|e| self.error(e)),
+            None => Ok(()),
+        }
+    }
+
+    /// Hold the file's lock until the returned file is dropped
+    ///
+    /// Reads and changes that must see each other's results, e.g. a read
+    /// followed by a rewrite, go under one lock.
+    pub fn lock(&self) -> Result<File> {
+        self.create_dir()?;
+        let mut lock_path = self.path.clone().into_os_string();
+        lock_path.push(".lock");
+        let lock = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(lock_path)
+            .map_err(|e| self.error(e))?;
+        lock.lock().map_err(|e| self.error(e))?;
+        Ok(lock)
+    }
+
+    /// The file's contents; `None` if it doesn't exist
+    pub fn read(&self) -> Result<Option<String>> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(source) => Ok(Some(source)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(self.error(e)),
+        }
+    }
+
+    /// Replace the file's contents atomically, through `<file>.tmp`
+    pub fn replace(&self, contents: &str) -> Result<()> {
+        let mut temporary = self.path.clone().into_os_string();
+        temporary.push(".tmp");
+        File::create(&temporary)
+            .and_then(|mut file| {
+                file.write_all(contents.as_bytes())?;
+                file.sync_all()
+            })
+            .and_then(|()| std::fs::rename(&temporary, &self.path))
+            .map_err(|e| self.error(e))
+    }
+
+    /// Every entry of a JSON Lines file, skipping blank lines; a missing
+    /// file has none
+    pub fn read_lines<T: DeserializeOwned>(&self) -> Result<Vec<T>> {
+        let source = self.read()?.unwrap_or_default();
+        source
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                serde_json::from_str(line)
+                    .map_err(|e| self.error(format!("line {}: {}", index + 1, e)))
+            })
+            .collect()
+    }
+
+    /// Append an entry to a JSON Lines file, returning once it is on disk
+    pub fn append_line<T: Serialize>(&self, entry: &T) -> Result<()> {
+        let line = serde_json::to_string(entry).map_err(|e| self.error(e))?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| self.error(e))?;
+        file.write_all(format!("{}\n", line).as_bytes())
+            .and_then(|()| file.sync_data())
+            .map_err(|e| self.error(e))
+    }
+
+    /// Replace a JSON Lines file with `entries`
+    pub fn write_lines<T: Serialize>(&self, entries: &[T]) -> Result<()> {
+        let mut contents = String::new();
+        for entry in entries {
+            contents.push_str(&serde_json::to_string(entry).map_err(|e| self.error(e))?);
+            contents.push('\n');
+        }
+        self.replace(&contents)
+    }
+}
@@ -0,0 +1,73 @@
+//! systemd socket activation and readiness notification
+//!
+//! Implements the small parts of `sd_listen_fds(3)` and `sd_notify(3)` the
+//! long-running modes need, without linking libsystemd: listening sockets
+//! passed in from a `.socket` unit, and `READY=1`/`STOPPING=1` messages for
+//! `Type=notify` services. Outside systemd both are no-ops.
+
+use std::io;
+use std::os::unix::io::{FromRawFd, RawFd};
+use std::os::unix::net::{UnixDatagram, UnixListener};
+
+/// First file descriptor passed by systemd
+const LISTEN_FDS_START: RawFd = 3;
+
+/// Number of sockets systemd passed to this process
+fn listen_fds() -> usize {
+    let for_us = std::env::var("LISTEN_PID")
+        .ok()
+        .and_then(|pid| pid.parse::<u32>().ok())
+        .is_some_and(|pid| pid == std::process::id());
+    if !for_us {
+        return 0;
+    }
+    std::env::var("LISTEN_FDS")
+        .ok()
+        .and_then(|count| count.parse().ok())
+        .unwrap_or(0)
+}
+
+/// Take the Unix stream socket passed in by socket activation, if any
+///
+/// Fails if systemd passed more than one socket or one that is not a Unix
+/// socket. Must be called at most once per process.
+pub fn activated_unix_listener() -> io::Result<Option<UnixListener>> {
+    match listen_fds() {
+        0 => Ok(None),
+        1 => {
+            // SAFETY: systemd hands over this descriptor for the process to
+            // own, and it is taken only once
+            let listener = unsafe { UnixListener::from_raw_fd(LISTEN_FDS_START) };
+            // Reading the address fails for anything but a Unix socket
+            listener.local_addr()?;
+            listener.set_nonblocking(true)?;
+            Ok(Some(listener))
+        }
+        count => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Expected one socket from systemd, got {}", count),
+        )),
+    }
+}
+
+/// Send a state change such as `READY=1` to the service manager
+///
+/// Returns whether a manager was listening (`NOTIFY_SOCKET` is set).
+pub fn notify(state: &str) -> io::Result<bool> {
+    let Some(path) = std::env::var_os("NOTIFY_SOCKET") else {
+        return Ok(false);
+    };
+    let socket = UnixDatagram::unbound()?;
+
+    // A leading `@` names a socket in the abstract namespace
+    #[cfg(target_os = "linux")]
+    if let Some(name) = path.as_encoded_bytes().strip_prefix(b"@") {
+        use std::os::linux::net::SocketAddrExt;
+        let address = std::os::unix::net::SocketAddr::from_abstract_name(name)?;
+        socket.send_to_addr(state.as_bytes(), &address)?;
+        return Ok(true);
+    }
+
+    socket.send_to(state.as_bytes(), path)?;
+    Ok(true)
+}
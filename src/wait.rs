@@ -0,0 +1,181 @@
+//! Waiting for a server to accept RCON logins
+//!
+//! A server that is still starting refuses connections. One that crashes
+//! during startup and is restarted by its supervisor behaves differently:
+//! it accepts a connection now and then, drops it before the login
+//! completes, and is gone again on the next attempt. Each such drop
+//! followed by a refused attempt counts as a crash, and enough crashes
+//! within a window end the wait early with [`RconError::CrashLoop`]
+//! instead of waiting out the timeout.
+
+use crate::client::{RconClient, RconConfig};
+use crate::error::{RconError, Result};
+use crate::progress::{ConnectPhase, ConnectProgress};
+use std::collections::VecDeque;
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::{sleep, Instant};
+use tracing::debug;
+
+/// Options controlling how long to wait for a server to accept RCON logins
+#[derive(Debug, Clone)]
+pub struct WaitOptions {
+    /// Give up once this much time has passed
+    pub timeout: Duration,
+    /// Delay between connection attempts
+    pub interval: Duration,
+    /// Give up after this many crashes within `crash_window`; 0 never does
+    pub crash_limit: u32,
+    pub crash_window: Duration,
+}
+
+impl WaitOptions {
+    /// Options without crash loop detection
+    pub fn new(timeout: Duration, interval: Duration) -> Self {
+        Self {
+            timeout,
+            interval,
+            crash_limit: 0,
+            crash_window: timeout,
+        }
+    }
+
+    /// Give up after `limit` crashes within `window`
+    pub fn with_crash_limit(mut self, limit: u32, window: Duration) -> Self {
+        self.crash_limit = limit;
+        self.crash_window = window;
+        self
+    }
+}
+
+/// A failed readiness attempt, reported while waiting
+#[derive(Debug)]
+pub struct WaitAttempt<'a> {
+    /// 1-based attempt number
+    pub attempt: u32,
+    /// Time since waiting started
+    pub elapsed: Duration,
+    /// Why the attempt failed
+    pub error: &'a RconError,
+    /// Whether the server accepted the connection before it failed
+    pub accepted: bool,
+    /// Whether this attempt revealed a crash: the previous one was
+    /// accepted, this one wasn't
+    pub crashed: bool,
+    /// Crashes within the crash window, including this one
+    pub crashes: u32,
+}
+
+/// Whether an error means the server accepted the connection, then reset it
+///
+/// On a fast network the reset may arrive before the connection is
+/// reported open, failing the connect itself.
+fn is_reset(error: &RconError) -> bool {
+    match error {
+        RconError::Disconnected => true,
+        RconError::Network(e) => matches!(
+            e.kind(),
+            io::ErrorKind::ConnectionReset
+                | io::ErrorKind::ConnectionAborted
+                | io::ErrorKind::UnexpectedEof
+                | io::ErrorKind::BrokenPipe
+        ),
+        _ => false,
+    }
+}
+
+/// Repeatedly connect and authenticate until the server is ready
+///
+/// `on_attempt` is called after every failed attempt. Authentication
+/// failures and pin mismatches are returned immediately since retrying
+/// cannot fix them; running out of time yields [`RconError::Timeout`], and
+/// reaching the crash limit [`RconError::CrashLoop`].
+pub async fn wait_for_server<F>(
+    config: &RconConfig,
+    options: &WaitOptions,
+    mut on_attempt: F,
+) -> Result<RconClient>
+where
+    F: FnMut(WaitAttempt<'_>),
+{
+    let started = Instant::now();
+    let deadline = started + options.timeout;
+    let mut attempt = 0;
+
+    // Reaching the login means the server accepted the connection
+    let accepted = Arc::new(AtomicBool::new(false));
+    let mut config = config.clone();
+    let inner = config.progress.take();
+    config.progress = Some(ConnectProgress::new({
+        let accepted = accepted.clone();
+        move |phase| {
+            if phase == ConnectPhase::Authenticating {
+                accepted.store(true, Ordering::Relaxed);
+            }
+            if let Some(inner) = &inner {
+                inner.report(phase);
+            }
+        }
+    }));
+    let mut previous_accepted = false;
+    let mut crashes: VecDeque<Instant> = VecDeque::new();
+
+    loop {
+        attempt += 1;
+        debug!("Readiness attempt {} for {}", attempt, config.address);
+        accepted.store(false, Ordering::Relaxed);
+
+        let error =
+            match tokio::time::timeout_at(deadline, RconClient::connect(config.clone())).await {
+                Ok(Ok(client)) => return Ok(client),
+                // Waiting can't fix a rejected password or a changed server
+                Ok(Err(e @ (RconError::AuthenticationFailed | RconError::PinMismatch(_)))) => {
+                    return Err(e)
+                }
+                Ok(Err(e)) => e,
+                Err(_) => RconError::Timeout,
+            };
+
+        let now = Instant::now();
+        let was_accepted = accepted.load(Ordering::Relaxed) || is_reset(&error);
+        let crashed = previous_accepted && !was_accepted;
+        previous_accepted = was_accepted;
+        if crashed {
+            crashes.push_back(now);
+        }
+        while crashes
+            .front()
+            .is_some_and(|at| now.duration_since(*at) > options.crash_window)
+        {
+            crashes.pop_front();
+        }
+
+        on_attempt(WaitAttempt {
+            attempt,
+            elapsed: started.elapsed(),
+            error: &error,
+            accepted: was_accepted,
+            crashed,
+            crashes: crashes.len() as u32,
+        });
+
+        if options.crash_limit > 0 && crashes.len() as u32 >= options.crash_limit {
+            let first = crashes.front().expect("crash limit is positive");
+            return Err(RconError::CrashLoop(format!(
+                "{} crashes within {}s, after {} attempts in {}s",
+                crashes.len(),
+                now.duration_since(*first).as_secs(),
+                attempt,
+                started.elapsed().as_secs()
+            )));
+        }
+
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(RconError::Timeout);
+        }
+        sleep(options.interval.min(remaining)).await;
+    }
+}
@@ -0,0 +1,218 @@
+//! WebAssembly plugins (requires the `wasm` feature)
+//!
+//! A plugin is a WebAssembly component implementing `wit/plugin.wit`. It
+//! can parse responses, offer completions in interactive mode, and define
+//! a subcommand of `rcon-cli plugin`. Plugins are listed in the config file:
+//!
+//! ```toml
+//! [[plugins]]
+//! path = "/usr/local/share/rcon-cli/luckperms.wasm"
+//! fuel = 100000000
+//! ```
+//!
+//! Components are compiled once when loaded, and every call runs in a
+//! fresh instance with limited fuel and memory. The host provides nothing
+//! to import: imports a toolchain adds anyway, such as WASI's for a Rust
+//! plugin built for `wasm32-wasip2`, trap when called. A plugin behaves the
+//! same on every platform and can't touch anything but the values passed
+//! to it.
+
+use crate::config::PluginConfig;
+use crate::error::{RconError, Result};
+use serde_json::Value;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use wasmtime::component::{Component, Linker};
+use wasmtime::{Config, Engine, Store, StoreLimits, StoreLimitsBuilder};
+
+wasmtime::component::bindgen!({
+    path: "wit/plugin.wit",
+    world: "rcon-plugin",
+});
+
+use exports::etheria::rcon_cli::plugin::Subcommand;
+
+/// Fuel a call gets when its plugin's entry sets none
+pub const DEFAULT_FUEL: u64 = 1_000_000_000;
+
+/// Memory an instance may grow to
+pub const MEMORY_LIMIT: usize = 64 << 20;
+
+/// A compiled plugin
+pub struct WasmPlugin {
+    name: String,
+    path: PathBuf,
+    engine: Engine,
+    pre: RconPluginPre<StoreLimits>,
+    fuel: u64,
+    subcommand: Option<Subcommand>,
+}
+
+impl WasmPlugin {
+    /// Compile a component and ask it for its subcommand
+    pub fn load(engine: &Engine, config: &PluginConfig) -> Result<Self> {
+        let path = &config.path;
+        let error = |e: wasmtime::Error| plugin_error(path, e);
+        let component = Component::from_file(engine, path).map_err(error)?;
+        let mut linker = Linker::new(engine);
+        linker
+            .define_unknown_imports_as_traps(&component)
+            .map_err(error)?;
+        let pre = linker.instantiate_pre(&component).map_err(error)?;
+        let mut plugin = Self {
+            name: path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            path: path.clone(),
+            engine: engine.clone(),
+            pre: RconPluginPre::new(pre).map_err(error)?,
+            fuel: config.fuel.unwrap_or(DEFAULT_FUEL),
+            subcommand: None,
+        };
+        plugin.subcommand = plugin.call(|plugin, store| plugin.call_define_subcommand(store))?;
+        Ok(plugin)
+    }
+
+    /// The file name without its extension
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The subcommand's name and description, if the plugin defines one
+    pub fn subcommand(&self) -> Option<(&str, &str)> {
+        self.subcommand
+            .as_ref()
+            .map(|subcommand| (subcommand.name.as_str(), subcommand.about.as_str()))
+    }
+
+    /// The JSON for a response, unless the plugin doesn't know the command
+    pub fn parse_response(&self, command: &str, response: &str) -> Result<Option<Value>> {
+        let parsed =
+            self.call(|plugin, store| plugin.call_parse_response(store, command, response))?;
+        match parsed {
+            None => Ok(None),
+            Some(Err(message)) => Err(self.error(message)),
+            Some(Ok(json)) => serde_json::from_str(&json)
+                .map(Some)
+                .map_err(|e| self.error(format!("output is not JSON: {}", e))),
+        }
+    }
+
+    /// Completions for the last word of `line`
+    pub fn provide_completions(&self, line: &str) -> Result<Vec<String>> {
+        self.call(|plugin, store| plugin.call_provide_completions(store, line))
+    }
+
+    /// The commands the subcommand sends for `args`
+    pub fn run_subcommand(&self, args: &[String]) -> Result<Vec<String>> {
+        self.call(|plugin, store| plugin.call_run_subcommand(store, args))?
+            .map_err(|message| self.error(message))
+    }
+
+    /// Call an export in a fresh instance
+    fn call<R>(
+        &self,
+        f: impl FnOnce(
+            &exports::etheria::rcon_cli::plugin::Guest,
+            &mut Store<StoreLimits>,
+        ) -> wasmtime::Result<R>,
+    ) -> Result<R> {
+        let limits = StoreLimitsBuilder::new().memory_size(MEMORY_LIMIT).build();
+        let mut store = Store::new(&self.engine, limits);
+        store.limiter(|limits| limits);
+        store
+            .set_fuel(self.fuel)
+            .map_err(|e| plugin_error(&self.path, e))?;
+        let instance = self
+            .pre
+            .instantiate(&mut store)
+            .map_err(|e| plugin_error(&self.path, e))?;
+        f(instance.etheria_rcon_cli_plugin(), &mut store).map_err(|e| plugin_error(&self.path, e))
+    }
+
+    fn error(&self, message: String) -> RconError {
+        RconError::Plugin(format!("{}: {}", self.path.display(), message))
+    }
+}
+
+impl fmt::Debug for WasmPlugin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WasmPlugin")
+            .field("path", &self.path)
+            .field("fuel", &self.fuel)
+            .field("subcommand", &self.subcommand().map(|(name, _)| name))
+            .finish_non_exhaustive()
+    }
+}
+
+fn plugin_error(path: &Path, e: wasmtime::Error) -> RconError {
+    RconError::Plugin(format!("{}: {:#}", path.display(), e))
+}
+
+/// The configured plugins, in config file order
+///
+/// ```
+/// use rcon_cli::wasm::PluginHost;
+///
+/// let host = PluginHost::load(&[]).unwrap();
+/// assert!(host.is_empty());
+/// assert!(host.parse_response("lp user Steve info", "").is_none());
+/// ```
+#[derive(Debug)]
+pub struct PluginHost {
+    plugins: Vec<WasmPlugin>,
+}
+
+impl PluginHost {
+    /// Compile every configured plugin
+    pub fn load(configs: &[PluginConfig]) -> Result<Self> {
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config).map_err(|e| RconError::Plugin(format!("{:#}", e)))?;
+        let plugins = configs
+            .iter()
+            .map(|config| WasmPlugin::load(&engine, config))
+            .collect::<Result<_>>()?;
+        Ok(Self { plugins })
+    }
+
+    pub fn plugins(&self) -> &[WasmPlugin] {
+        &self.plugins
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.plugins.is_empty()
+    }
+
+    /// The first plugin's JSON for a response it knows
+    pub fn parse_response(&self, command: &str, response: &str) -> Option<Result<Value>> {
+        self.plugins
+            .iter()
+            .find_map(|plugin| plugin.parse_response(command, response).transpose())
+    }
+
+    /// Every plugin's completions for the last word of `line`
+    ///
+    /// A failing plugin offers none rather than breaking completion.
+    pub fn completions(&self, line: &str) -> Vec<String> {
+        self.plugins
+            .iter()
+            .filter_map(|plugin| plugin.provide_completions(line).ok())
+            .flatten()
+            .collect()
+    }
+
+    /// The plugin defining the subcommand `name`
+    pub fn subcommand(&self, name: &str) -> Option<&WasmPlugin> {
+        self.plugins.iter().find(|plugin| {
+            plugin
+                .subcommand()
+                .is_some_and(|(defined, _)| defined == name)
+        })
+    }
+}
@@ -0,0 +1,522 @@
+//! Ready-made HTTP endpoints for web panels
+//!
+//! With the `web` feature, [`router`] exposes a server's console over
+//! JSON, and [`SharedRcon`] is the state handlers share the connection
+//! through:
+//!
+//! ```no_run
+//! use rcon_cli::web::{router, SharedRcon};
+//! use rcon_cli::RconConfig;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let rcon = SharedRcon::new(RconConfig::new("127.0.0.1:25575".parse()?, "secret"));
+//! let app = axum::Router::new().nest("/rcon", router(rcon));
+//! let listener = tokio::net::TcpListener::bind("127.0.0.1:8080").await?;
+//! axum::serve(listener, app).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! | Endpoint | Response |
+//! |---|---|
+//! | `POST /command` with `{"command": "..."}` and optionally `"priority"` | `{"response", "duration_ms", "fragments"}` |
+//! | `GET /players` | `{"online", "max", "players": [{"name", "uuid"}]}` |
+//! | `GET /ping` | `{"latency_ms"}` |
+//! | `GET /status` | [`QueueStats`] of the shared connection |
+//!
+//! Failures are `{"error", "code"}` objects, as in the CLI's JSON output.
+//! The plain router does no authentication of its own: anyone who can reach
+//! it can run any command, so mount it behind the application's auth layer.
+//!
+//! [`router_with_access`] instead gives every request a
+//! [`Role`](crate::policy::Role) from an [`AccessPolicy`], by the
+//! `Authorization: Bearer` token or else the client's IP, so one bridge can
+//! serve admins and read-only monitoring alike. Requests without a role get
+//! `401`, and commands their role doesn't allow `403` with the
+//! `command_denied` code. Client IPs are only known when the app is served
+//! with `into_make_service_with_connect_info::<SocketAddr>()`; behind a
+//! reverse proxy they are the proxy's.
+//!
+//! Requests share one connection, [multiplexed](crate::mux) fairly between
+//! clients, told apart by the same token or IP; `/ping` goes ahead of other
+//! waiting commands, and `/command` may set a `high`, `normal`, or `low`
+//! priority. With
+//! [`SharedRcon::with_rate_limit`], a client exceeding its budget gets `429`
+//! with the `rate_limited` code. With [`SharedRcon::with_max_queue`],
+//! commands arriving while the queue is full get `503` with the
+//! `queue_full` code right away.
+//!
+//! A caller's timeout, from an `X-Request-Timeout` header (`5s`, `1500ms`)
+//! or gRPC's `grpc-timeout` (`5S`, `1500m`) set by gRPC-Web and transcoding
+//! proxies, becomes its command's [`Deadline`]: once it passes, the command
+//! fails with `504` and the `timeout` code, even if it's still queued.
+//! A caller disconnecting cancels its command as well, since the server
+//! drops the request's handler.
+
+use crate::cli::parse_duration;
+use crate::client::{CommandResponse, RconConfig};
+use crate::error::{RconError, Result};
+use crate::lazy::LazyRconClient;
+use crate::mux::{Multiplexer, Priority, QueueStats, RateLimit};
+use crate::parsers::parse_player_list;
+use crate::policy::{AccessPolicy, Role};
+use axum::extract::{ConnectInfo, FromRequestParts, State};
+use axum::http::request::Parts;
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Extension, Json, Router};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::hash_map::DefaultHasher;
+use std::convert::Infallible;
+use std::hash::{Hash, Hasher};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// One connection shared by all handlers
+///
+/// The connection is opened on first use and re-dialed after the server
+/// drops it; commands from concurrent requests are sent one at a time,
+/// taking turns between clients.
+/// Cloning is cheap, so the handle can be kept in router state or added
+/// as an [`Extension`], from which it is also extracted directly:
+///
+/// ```no_run
+/// use rcon_cli::web::SharedRcon;
+///
+/// async fn weather(rcon: SharedRcon) -> String {
+///     match rcon.execute_command("weather query").await {
+///         Ok(response) => response,
+///         Err(e) => e.to_string(),
+///     }
+/// }
+/// ```
+#[derive(Clone)]
+pub struct SharedRcon {
+    mux: Multiplexer,
+}
+
+/// Client of commands sent without naming one
+const APPLICATION: &str = "application";
+
+impl SharedRcon {
+    /// Create the handle without connecting yet
+    pub fn new(config: RconConfig) -> Self {
+        Self {
+            mux: Multiplexer::new(LazyRconClient::new(config)),
+        }
+    }
+
+    /// Limit the commands of each client
+    pub fn with_rate_limit(self, rate_limit: RateLimit) -> Self {
+        Self {
+            mux: self.mux.with_rate_limit(Some(rate_limit)),
+        }
+    }
+
+    /// Reject commands arriving while `max_queue` others wait
+    pub fn with_max_queue(self, max_queue: usize) -> Self {
+        Self {
+            mux: self.mux.with_max_queue(Some(max_queue)),
+        }
+    }
+
+    /// The queue's current depth and totals so far
+    pub fn stats(&self) -> QueueStats {
+        self.mux.stats()
+    }
+
+    /// Execute a command, waiting for other requests' commands to finish
+    pub async fn execute_command(&self, command: &str) -> Result<String> {
+        self.mux.execute_command(APPLICATION, command).await
+    }
+
+    /// Execute a command and report how the response was received
+    pub async fn execute_command_detailed(&self, command: &str) -> Result<CommandResponse> {
+        self.mux
+            .execute_command_detailed(APPLICATION, command)
+            .await
+    }
+
+    /// Execute a command ahead of or after other waiting commands by
+    /// `priority`
+    pub async fn execute_command_with_priority(
+        &self,
+        command: &str,
+        priority: Priority,
+    ) -> Result<CommandResponse> {
+        self.mux
+            .execute_with_priority(APPLICATION, command, priority)
+            .await
+    }
+
+    /// Execute a command, failing with [`RconError::Timeout`] once the
+    /// caller's deadline passes
+    ///
+    /// ```no_run
+    /// use rcon_cli::web::{Deadline, SharedRcon};
+    ///
+    /// async fn seed(rcon: SharedRcon, deadline: Deadline) -> String {
+    ///     match rcon.execute_command_with_deadline("seed", deadline).await {
+    ///         Ok(response) => response.body,
+    ///         Err(e) => e.to_string(),
+    ///     }
+    /// }
+    /// ```
+    pub async fn execute_command_with_deadline(
+        &self,
+        command: &str,
+        deadline: Deadline,
+    ) -> Result<CommandResponse> {
+        self.mux
+            .execute_with_deadline(APPLICATION, command, Priority::of(command), deadline.0)
+            .await
+    }
+
+    /// Execute a command on behalf of `client`, taking turns with other
+    /// clients and counting against its rate limit
+    pub async fn execute_command_as(&self, client: &str, command: &str) -> Result<String> {
+        self.mux.execute_command(client, command).await
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for SharedRcon {
+    type Rejection = <Extension<SharedRcon> as FromRequestParts<S>>::Rejection;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &S,
+    ) -> std::result::Result<Self, Self::Rejection> {
+        let Extension(rcon) = Extension::<SharedRcon>::from_request_parts(parts, state).await?;
+        Ok(rcon)
+    }
+}
+
+/// Router with the command endpoints, its state already applied
+pub fn router(rcon: SharedRcon) -> Router {
+    routes(ApiState { rcon, access: None })
+}
+
+/// Router with the command endpoints, allowing each client only the
+/// commands of its role
+///
+/// ```no_run
+/// use rcon_cli::policy::{AccessConfig, AccessPolicy};
+/// use rcon_cli::web::{router_with_access, SharedRcon};
+/// use rcon_cli::RconConfig;
+/// use std::net::SocketAddr;
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let config: AccessConfig = toml::from_str(&std::fs::read_to_string("roles.toml")?)?;
+/// let rcon = SharedRcon::new(RconConfig::new("127.0.0.1:25575".parse()?, "secret"));
+/// let app = router_with_access(rcon, AccessPolicy::new(&config)?);
+/// let listener = tokio::net::TcpListener::bind("0.0.0.0:8080").await?;
+/// axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn router_with_access(rcon: SharedRcon, access: AccessPolicy) -> Router {
+    routes(ApiState {
+        rcon,
+        access: Some(Arc::new(access)),
+    })
+}
+
+fn routes(state: ApiState) -> Router {
+    Router::new()
+        .route("/command", post(command))
+        .route("/players", get(players))
+        .route("/ping", get(ping))
+        .route("/status", get(status))
+        .with_state(state)
+}
+
+/// State of the endpoints
+#[derive(Clone)]
+struct ApiState {
+    rcon: SharedRcon,
+    /// Roles of clients, or `None` to allow everyone everything
+    access: Option<Arc<AccessPolicy>>,
+}
+
+impl ApiState {
+    /// Reject `command` unless the client's role allows it
+    fn authorize(&self, client: &Client, command: &str) -> std::result::Result<(), ApiError> {
+        match self.role(client)? {
+            Some(role) => Ok(role.check(command)?),
+            None => Ok(()),
+        }
+    }
+
+    /// The client's role, or `None` if everyone may do everything
+    fn role(&self, client: &Client) -> std::result::Result<Option<&Role>, ApiError> {
+        let Some(access) = &self.access else {
+            return Ok(None);
+        };
+        let role = access
+            .role(client.token.as_deref(), client.ip)
+            .ok_or_else(|| ApiError {
+                status: StatusCode::UNAUTHORIZED,
+                code: "unauthorized",
+                message: "No role for this token or address".to_string(),
+            })?;
+        Ok(Some(role))
+    }
+}
+
+/// Who sent a request
+struct Client {
+    /// `Authorization: Bearer` token
+    token: Option<String>,
+    /// Only known with connect info
+    ip: Option<IpAddr>,
+}
+
+impl Client {
+    /// Name to take turns and count rate limits under, without the token
+    fn key(&self) -> String {
+        match (&self.token, self.ip) {
+            (Some(token), _) => {
+                let mut hasher = DefaultHasher::new();
+                token.hash(&mut hasher);
+                format!("token {:016x}", hasher.finish())
+            }
+            (None, Some(ip)) => format!("ip {}", ip),
+            (None, None) => "anonymous".to_string(),
+        }
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for Client {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        _state: &S,
+    ) -> std::result::Result<Self, Self::Rejection> {
+        let token = parts
+            .headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(|token| token.trim().to_string());
+        let ip = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip());
+        Ok(Self { token, ip })
+    }
+}
+
+/// Header with the caller's timeout, as a duration like `5s` or `1500ms`
+pub const TIMEOUT_HEADER: &str = "x-request-timeout";
+
+/// gRPC's header with the time left until the caller's deadline
+pub const GRPC_TIMEOUT_HEADER: &str = "grpc-timeout";
+
+/// Parse a `grpc-timeout` value: at most 8 digits and a unit, `H`, `M`,
+/// `S`, `m` (milliseconds), `u`, or `n`
+///
+/// ```
+/// use rcon_cli::web::parse_grpc_timeout;
+/// use std::time::Duration;
+///
+/// assert_eq!(parse_grpc_timeout("1500m"), Some(Duration::from_millis(1500)));
+/// assert_eq!(parse_grpc_timeout("2M"), Some(Duration::from_secs(120)));
+/// assert_eq!(parse_grpc_timeout("5s"), None);
+/// assert_eq!(parse_grpc_timeout("123456789S"), None);
+/// ```
+pub fn parse_grpc_timeout(value: &str) -> Option<Duration> {
+    let (amount, unit) = value.split_at(value.len().checked_sub(1)?);
+    if amount.is_empty() || amount.len() > 8 || !amount.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let amount: u64 = amount.parse().ok()?;
+    match unit {
+        "H" => Some(Duration::from_secs(amount * 3600)),
+        "M" => Some(Duration::from_secs(amount * 60)),
+        "S" => Some(Duration::from_secs(amount)),
+        "m" => Some(Duration::from_millis(amount)),
+        "u" => Some(Duration::from_micros(amount)),
+        "n" => Some(Duration::from_nanos(amount)),
+        _ => None,
+    }
+}
+
+/// When the caller stops waiting for a response, from its timeout headers
+///
+/// With both headers the earlier deadline applies; without either there is
+/// none. A malformed header is rejected with `400`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Deadline(pub Option<Instant>);
+
+impl<S: Send + Sync> FromRequestParts<S> for Deadline {
+    type Rejection = Response;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        _state: &S,
+    ) -> std::result::Result<Self, Self::Rejection> {
+        let received = Instant::now();
+        let timeouts = [
+            header_timeout(parts, TIMEOUT_HEADER, |value| parse_duration(value).ok()),
+            header_timeout(parts, GRPC_TIMEOUT_HEADER, parse_grpc_timeout),
+        ];
+        let timeouts = timeouts
+            .into_iter()
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(IntoResponse::into_response)?;
+        let deadline = timeouts
+            .into_iter()
+            .flatten()
+            .min()
+            .map(|timeout| received + timeout);
+        Ok(Self(deadline))
+    }
+}
+
+/// The timeout in header `name`, if it's set
+fn header_timeout(
+    parts: &Parts,
+    name: &str,
+    parse: fn(&str) -> Option<Duration>,
+) -> std::result::Result<Option<Duration>, ApiError> {
+    let Some(value) = parts.headers.get(name) else {
+        return Ok(None);
+    };
+    let value = value.to_str().unwrap_or_default().trim();
+    match parse(value) {
+        Some(timeout) => Ok(Some(timeout)),
+        None => Err(ApiError {
+            status: StatusCode::BAD_REQUEST,
+            code: "invalid_arguments",
+            message: format!("Invalid {} header '{}'", name, value),
+        }),
+    }
+}
+
+/// An error response in the CLI's JSON error shape
+struct ApiError {
+    status: StatusCode,
+    code: &'static str,
+    message: String,
+}
+
+impl From<RconError> for ApiError {
+    fn from(error: RconError) -> Self {
+        let status = match error {
+            RconError::Timeout => StatusCode::GATEWAY_TIMEOUT,
+            RconError::CommandDenied(_) => StatusCode::FORBIDDEN,
+            RconError::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
+            RconError::QueueFull(_) => StatusCode::SERVICE_UNAVAILABLE,
+            _ => StatusCode::BAD_GATEWAY,
+        };
+        Self {
+            status,
+            code: error.code(),
+            message: error.to_string(),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = json!({ "error": self.message, "code": self.code });
+        (self.status, Json(body)).into_response()
+    }
+}
+
+#[derive(Deserialize)]
+struct CommandRequest {
+    command: String,
+    #[serde(default)]
+    priority: Option<Priority>,
+}
+
+async fn command(
+    State(state): State<ApiState>,
+    client: Client,
+    Deadline(deadline): Deadline,
+    Json(request): Json<CommandRequest>,
+) -> std::result::Result<Json<Value>, ApiError> {
+    if request.command.trim().is_empty() {
+        return Err(ApiError {
+            status: StatusCode::BAD_REQUEST,
+            code: "invalid_arguments",
+            message: "Command cannot be empty".to_string(),
+        });
+    }
+    state.authorize(&client, &request.command)?;
+    let priority = request
+        .priority
+        .unwrap_or_else(|| Priority::of(&request.command));
+    let response = state
+        .rcon
+        .mux
+        .execute_with_deadline(&client.key(), &request.command, priority, deadline)
+        .await?;
+    Ok(Json(json!({
+        "response": response.body,
+        "duration_ms": response.duration.as_secs_f64() * 1000.0,
+        "fragments": response.fragments,
+    })))
+}
+
+async fn players(
+    State(state): State<ApiState>,
+    client: Client,
+    Deadline(deadline): Deadline,
+) -> std::result::Result<Json<Value>, ApiError> {
+    state.authorize(&client, "list uuids")?;
+    let mux = &state.rcon.mux;
+    let key = client.key();
+    let list = |command| mux.execute_with_deadline(&key, command, Priority::Normal, deadline);
+    // Servers before 1.13 only know the plain list
+    let mut response = list("list uuids").await?.body;
+    if parse_player_list(&response).is_none() {
+        response = list("list").await?.body;
+    }
+    let list = parse_player_list(&response).ok_or_else(|| ApiError {
+        status: StatusCode::BAD_GATEWAY,
+        code: "unexpected_response",
+        message: format!("Unrecognized player list: {}", response),
+    })?;
+    let players: Vec<Value> = list
+        .players
+        .iter()
+        .map(|player| json!({ "name": player.name, "uuid": player.uuid }))
+        .collect();
+    Ok(Json(json!({
+        "online": list.online,
+        "max": list.max,
+        "players": players,
+    })))
+}
+
+async fn ping(
+    State(state): State<ApiState>,
+    client: Client,
+    Deadline(deadline): Deadline,
+) -> std::result::Result<Json<Value>, ApiError> {
+    state.authorize(&client, "list")?;
+    let started = Instant::now();
+    state
+        .rcon
+        .mux
+        .execute_with_deadline(&client.key(), "list", Priority::High, deadline)
+        .await?;
+    Ok(Json(json!({
+        "latency_ms": started.elapsed().as_secs_f64() * 1000.0,
+    })))
+}
+
+async fn status(
+    State(state): State<ApiState>,
+    client: Client,
+) -> std::result::Result<Json<QueueStats>, ApiError> {
+    // Any role may see the queue
+    state.role(&client)?;
+    Ok(Json(state.rcon.stats()))
+}
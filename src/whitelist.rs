@@ -0,0 +1,126 @@
+//! Whitelist synchronization against an externally managed roster
+//!
+//! A roster is either plain text with one player name per line (`#` starts
+//! a comment) or JSON: an array of names, or of objects with a `name` field
+//! as in the server's own `whitelist.json`.
+
+use crate::error::{RconError, Result};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::io;
+use std::path::Path;
+
+/// Commands needed to turn the current whitelist into the desired one
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WhitelistDiff {
+    /// Players missing from the whitelist
+    pub add: Vec<String>,
+    /// Whitelisted players not on the roster
+    pub remove: Vec<String>,
+}
+
+impl WhitelistDiff {
+    /// Compare whitelists; player names are matched case-insensitively
+    ///
+    /// ```
+    /// use rcon_cli::whitelist::WhitelistDiff;
+    ///
+    /// let current = ["Alice".to_string(), "bob".to_string()];
+    /// let desired = ["Bob".to_string(), "Carol".to_string()];
+    /// let diff = WhitelistDiff::between(&current, &desired);
+    /// assert_eq!(diff.add, ["Carol"]);
+    /// assert_eq!(diff.remove, ["Alice"]);
+    /// ```
+    pub fn between(current: &[String], desired: &[String]) -> Self {
+        let lowercase = |names: &[String]| -> BTreeSet<String> {
+            names.iter().map(|name| name.to_lowercase()).collect()
+        };
+        let (current_set, desired_set) = (lowercase(current), lowercase(desired));
+
+        let mut seen = BTreeSet::new();
+        let add = desired
+            .iter()
+            .filter(|name| !current_set.contains(&name.to_lowercase()))
+            .filter(|name| seen.insert(name.to_lowercase()))
+            .cloned()
+            .collect();
+        let remove = current
+            .iter()
+            .filter(|name| !desired_set.contains(&name.to_lowercase()))
+            .cloned()
+            .collect();
+
+        Self { add, remove }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.add.is_empty() && self.remove.is_empty()
+    }
+
+    /// Server commands applying the diff, removals first
+    pub fn commands(&self) -> Vec<String> {
+        self.remove
+            .iter()
+            .map(|name| format!("whitelist remove {}", name))
+            .chain(
+                self.add
+                    .iter()
+                    .map(|name| format!("whitelist add {}", name)),
+            )
+            .collect()
+    }
+}
+
+/// Parse a roster in text or JSON form
+pub fn parse_roster(source: &str) -> Result<Vec<String>> {
+    let trimmed = source.trim_start();
+    if !trimmed.starts_with('[') {
+        return Ok(source
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_string)
+            .collect());
+    }
+
+    let entries: Vec<Value> = serde_json::from_str(trimmed)
+        .map_err(|e| RconError::InvalidConfig(format!("Invalid roster JSON: {}", e)))?;
+    entries
+        .iter()
+        .map(|entry| match entry {
+            Value::String(name) => Ok(name.clone()),
+            Value::Object(fields) => fields
+                .get("name")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .ok_or_else(|| {
+                    RconError::InvalidConfig("Roster entry without a \"name\"".to_string())
+                }),
+            other => Err(RconError::InvalidConfig(format!(
+                "Unexpected roster entry: {}",
+                other
+            ))),
+        })
+        .collect()
+}
+
+/// Read and parse a roster file
+pub fn load_roster(path: impl AsRef<Path>) -> Result<Vec<String>> {
+    let path = path.as_ref();
+    let source = std::fs::read_to_string(path).map_err(|e| {
+        RconError::InvalidConfig(format!("Failed to read roster '{}': {}", path.display(), e))
+    })?;
+    parse_roster(&source)
+}
+
+/// Download and parse a roster
+pub async fn fetch_roster(url: &str) -> Result<Vec<String>> {
+    let fetch = async { reqwest::get(url).await?.error_for_status()?.text().await };
+    let source = fetch.await.map_err(|e| {
+        RconError::Network(io::Error::other(format!(
+            "Failed to fetch roster from {}: {}",
+            url, e
+        )))
+    })?;
+    parse_roster(&source)
+}
@@ -0,0 +1,43 @@
+//! Recovery from malformed packets against the mock server
+
+use rcon_cli::mock_server::MockServer;
+use rcon_cli::{LazyRconClient, RconClient, RconError};
+
+fn server() -> MockServer {
+    MockServer::new("secret")
+        .respond("list", "There are 0 of a max of 20 players online: ")
+        .garble("seed")
+}
+
+#[tokio::test]
+async fn bad_length_drops_the_connection() {
+    let server = server().start().await.unwrap();
+    let mut client = RconClient::connect(server.config()).await.unwrap();
+
+    let garbled = client.execute_command("seed").await;
+    assert!(matches!(garbled, Err(RconError::InvalidPacket(_))));
+    assert!(client.is_closed());
+
+    // The rest of the garbled packet must not be read as the next response
+    let next = client.execute_command("list").await;
+    assert!(matches!(next, Err(RconError::Disconnected)));
+    assert_eq!(server.received(), ["seed"]);
+
+    client.reconnect().await.unwrap();
+    let list = client.execute_command("list").await.unwrap();
+    assert!(list.starts_with("There are 0"));
+}
+
+#[tokio::test]
+async fn lazy_client_redials_after_a_bad_length() {
+    let server = server().start().await.unwrap();
+    let mut client = LazyRconClient::new(server.config());
+
+    let garbled = client.execute_command("seed").await;
+    assert!(matches!(garbled, Err(RconError::InvalidPacket(_))));
+    assert!(!client.is_connected());
+
+    let list = client.execute_command("list").await.unwrap();
+    assert!(list.starts_with("There are 0"));
+    assert_eq!(server.received(), ["seed", "list"]);
+}
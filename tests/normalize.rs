@@ -0,0 +1,47 @@
+//! Checks `normalize` against the responses in `fixtures/normalize.toml`
+
+use rcon_cli::normalize::normalize;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct Corpus {
+    case: Vec<Case>,
+}
+
+#[derive(Deserialize)]
+struct Case {
+    command: String,
+    server: String,
+    response: String,
+    canonical: String,
+}
+
+fn corpus() -> Corpus {
+    toml::from_str(include_str!("../fixtures/normalize.toml")).unwrap()
+}
+
+#[test]
+fn responses_normalize_to_canonical_phrasing() {
+    for case in corpus().case {
+        assert_eq!(
+            normalize(&case.response),
+            case.canonical,
+            "`{}` on {}",
+            case.command,
+            case.server
+        );
+    }
+}
+
+#[test]
+fn canonical_phrasing_is_unchanged() {
+    for case in corpus().case {
+        assert_eq!(
+            normalize(&case.canonical),
+            case.canonical,
+            "`{}` on {}",
+            case.command,
+            case.server
+        );
+    }
+}
@@ -0,0 +1,139 @@
+//! Golden tests of the parsers against the responses in
+//! `fixtures/responses.toml`
+//!
+//! Each response's parsed result is compared with
+//! `fixtures/golden/<name>.txt`. Run with `UPDATE_GOLDEN=1` to write the
+//! golden files after adding responses or changing a parser on purpose.
+
+use rcon_cli::fixtures::{self, Fixture};
+use rcon_cli::parsers::*;
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+
+/// Parsers covered by the corpus, each with at least one response
+const PARSERS: &[&str] = &[
+    "player_list",
+    "banlist",
+    "whitelist",
+    "save_response",
+    "datapack_list",
+    "plugin_list",
+    "plugin_version",
+    "mod_list",
+    "seed",
+    "time",
+    "forceload_query",
+    "forceload_list",
+    "forceload_change",
+    "xp_query",
+    "xp_change",
+    "effect_change",
+    "server_version",
+    "bukkit_tps",
+    "forge_tps",
+    "tick_query",
+    "inventory",
+    "difficulty",
+    "world_border",
+    "test_result",
+    "luckperms_info",
+    "permission_check",
+    "balance",
+    "balance_change",
+    "whitelist_toggle",
+];
+
+/// The parsed result of a response, as stored in its golden file
+fn parse(fixture: &Fixture) -> String {
+    let response = fixture.response.as_str();
+    let parsed = match fixture.parser.as_str() {
+        "player_list" => format!("{:#?}", parse_player_list(response)),
+        "banlist" => format!("{:#?}", parse_banlist(response)),
+        "whitelist" => format!("{:#?}", parse_whitelist(response)),
+        "save_response" => format!("{:#?}", parse_save_response(response)),
+        "datapack_list" => format!("{:#?}", parse_datapack_list(response)),
+        "plugin_list" => format!("{:#?}", parse_plugin_list(response)),
+        "plugin_version" => format!("{:#?}", parse_plugin_version(response)),
+        "mod_list" => format!("{:#?}", parse_mod_list(response)),
+        "seed" => format!("{:#?}", parse_seed(response)),
+        "time" => format!("{:#?}", parse_time(response)),
+        "forceload_query" => format!("{:#?}", parse_forceload_query(response)),
+        "forceload_list" => format!("{:#?}", parse_forceload_list(response)),
+        "forceload_change" => format!("{:#?}", parse_forceload_change(response)),
+        "xp_query" => format!("{:#?}", parse_xp_query(response)),
+        "xp_change" => format!("{:#?}", parse_xp_change(response)),
+        "effect_change" => format!("{:#?}", parse_effect_change(response)),
+        "server_version" => format!("{:#?}", parse_server_version(response)),
+        "bukkit_tps" => format!("{:#?}", parse_bukkit_tps(response)),
+        "forge_tps" => format!("{:#?}", parse_forge_tps(response)),
+        "tick_query" => format!("{:#?}", parse_tick_query(response)),
+        "inventory" => format!("{:#?}", parse_inventory(response)),
+        "difficulty" => format!("{:#?}", parse_difficulty(response)),
+        "world_border" => format!("{:#?}", parse_world_border(response)),
+        "test_result" => format!("{:#?}", parse_test_result(response)),
+        "luckperms_info" => format!("{:#?}", parse_luckperms_info(response)),
+        "permission_check" => format!("{:#?}", parse_permission_check(response)),
+        "balance" => format!("{:#?}", parse_balance(response)),
+        "balance_change" => format!("{:#?}", parse_balance_change(response)),
+        "whitelist_toggle" => format!("{:#?}", parse_whitelist_toggle(response)),
+        parser => panic!("{}: unknown parser '{}'", fixture.name, parser),
+    };
+    parsed + "\n"
+}
+
+fn golden_path(fixture: &Fixture) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("fixtures/golden")
+        .join(format!("{}.txt", fixture.name))
+}
+
+#[test]
+fn parsed_responses_match_golden_files() {
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+    let mut mismatches = Vec::new();
+
+    for fixture in fixtures::all() {
+        let parsed = parse(fixture);
+        let path = golden_path(fixture);
+        if update {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, &parsed).unwrap();
+            continue;
+        }
+        match fs::read_to_string(&path) {
+            Ok(golden) if golden == parsed => {}
+            Ok(golden) => mismatches.push(format!(
+                "{} ({}, `{}`):\n--- golden\n{}--- parsed\n{}",
+                fixture.name, fixture.server, fixture.command, golden, parsed
+            )),
+            Err(_) => mismatches.push(format!("{}: no golden file", fixture.name)),
+        }
+    }
+
+    assert!(
+        mismatches.is_empty(),
+        "{} response(s) differ, rerun with UPDATE_GOLDEN=1 if intended:\n\n{}",
+        mismatches.len(),
+        mismatches.join("\n")
+    );
+}
+
+#[test]
+fn every_parser_has_responses() {
+    for parser in PARSERS {
+        assert!(
+            fixtures::for_parser(parser).next().is_some(),
+            "no responses for {}",
+            parser
+        );
+    }
+}
+
+#[test]
+fn response_names_are_unique() {
+    let mut names = HashSet::new();
+    for fixture in fixtures::all() {
+        assert!(names.insert(&fixture.name), "duplicate {}", fixture.name);
+    }
+}
@@ -0,0 +1,98 @@
+//! Property tests of packet decoding on untrusted bytes
+
+use proptest::prelude::*;
+use rcon_cli::protocol::{validate_packet_length, MAX_PACKET_LENGTH, MIN_PACKET_LENGTH};
+use rcon_cli::{RconError, RconPacket};
+
+/// A packet whose length prefix says `declared` and whose body is `body`
+fn framed(declared: i32, body: &[u8]) -> Vec<u8> {
+    let mut data = declared.to_le_bytes().to_vec();
+    data.extend_from_slice(body);
+    data
+}
+
+fn is_invalid_packet<T>(result: rcon_cli::Result<T>) -> bool {
+    matches!(result, Err(RconError::InvalidPacket(_)))
+}
+
+proptest! {
+    #[test]
+    fn decode_never_panics(data in proptest::collection::vec(any::<u8>(), 0..512)) {
+        let _ = RconPacket::from_bytes(&data);
+    }
+
+    #[test]
+    fn decode_never_panics_with_plausible_lengths(
+        declared in -16i32..1024,
+        body in proptest::collection::vec(any::<u8>(), 0..1024),
+    ) {
+        let _ = RconPacket::from_bytes(&framed(declared, &body));
+    }
+
+    #[test]
+    fn encoded_packets_decode_unchanged(
+        request_id in any::<i32>(),
+        packet_type in any::<i32>(),
+        payload in "[^\0]{0,256}",
+    ) {
+        let packet = RconPacket::new(request_id, packet_type, payload.clone());
+        let decoded = RconPacket::from_bytes(&packet.to_bytes().unwrap()).unwrap();
+        prop_assert_eq!(decoded.request_id, request_id);
+        prop_assert_eq!(decoded.packet_type, packet_type);
+        prop_assert_eq!(decoded.payload, payload);
+    }
+
+    #[test]
+    fn lengths_below_the_minimum_are_invalid(declared in 0..MIN_PACKET_LENGTH as i32) {
+        prop_assert!(is_invalid_packet(validate_packet_length(declared)));
+        let body = vec![0; MIN_PACKET_LENGTH];
+        prop_assert!(is_invalid_packet(RconPacket::from_bytes(&framed(declared, &body))));
+    }
+
+    #[test]
+    fn negative_lengths_are_invalid(declared in i32::MIN..0) {
+        prop_assert!(is_invalid_packet(validate_packet_length(declared)));
+        let body = vec![0; MIN_PACKET_LENGTH];
+        prop_assert!(is_invalid_packet(RconPacket::from_bytes(&framed(declared, &body))));
+    }
+
+    #[test]
+    fn lengths_above_the_maximum_are_invalid(declared in MAX_PACKET_LENGTH as i32 + 1..=i32::MAX) {
+        prop_assert!(is_invalid_packet(validate_packet_length(declared)));
+        let body = vec![0; MIN_PACKET_LENGTH];
+        prop_assert!(is_invalid_packet(RconPacket::from_bytes(&framed(declared, &body))));
+    }
+
+    #[test]
+    fn lengths_within_bounds_are_valid(length in MIN_PACKET_LENGTH..=MAX_PACKET_LENGTH) {
+        prop_assert_eq!(validate_packet_length(length as i32).unwrap(), length);
+    }
+
+    #[test]
+    fn truncated_packets_are_invalid(
+        payload in "[a-z ]{0,64}",
+        cut in 1usize..64,
+    ) {
+        let bytes = RconPacket::new(1, 0, payload).to_bytes().unwrap();
+        let truncated = &bytes[..bytes.len() - cut.min(bytes.len())];
+        prop_assert!(is_invalid_packet(RconPacket::from_bytes(truncated)));
+    }
+
+    #[test]
+    fn mismatched_lengths_are_invalid(
+        payload in "[a-z ]{0,64}",
+        extra in 1usize..64,
+        shorter in any::<bool>(),
+    ) {
+        let mut bytes = RconPacket::new(1, 0, payload).to_bytes().unwrap();
+        let body = bytes.len() - 4;
+        let declared = if shorter {
+            body.saturating_sub(extra).max(MIN_PACKET_LENGTH)
+        } else {
+            body + extra
+        };
+        prop_assume!(declared != body);
+        bytes[..4].copy_from_slice(&(declared as i32).to_le_bytes());
+        prop_assert!(is_invalid_packet(RconPacket::from_bytes(&bytes)));
+    }
+}